@@ -18,7 +18,9 @@ import (
 	"fmt"
 
 	"github.com/apecloud/myduckserver/adapter"
+	"github.com/apecloud/myduckserver/binlogreplication"
 	"github.com/apecloud/myduckserver/catalog"
+	"github.com/apecloud/myduckserver/delta"
 	"github.com/apecloud/myduckserver/transpiler"
 	"github.com/dolthub/go-mysql-server/sql"
 	"github.com/dolthub/go-mysql-server/sql/expression"
@@ -60,6 +62,10 @@ func (b *DuckBuilder) Build(ctx *sql.Context, root sql.Node, r sql.Row) (sql.Row
 		}
 	}
 
+	if err := checkPublishedTables(ctx, root); err != nil {
+		return nil, err
+	}
+
 	n := root
 
 	if log := ctx.GetLogger(); log.Logger.IsLevelEnabled(logrus.TraceLevel) {
@@ -268,6 +274,37 @@ func (b *DuckBuilder) executeDML(ctx *sql.Context, n sql.Node, conn *stdsql.Conn
 	})), nil
 }
 
+// checkPublishedTables rejects the INSERT, REPLACE, UPDATE, and DELETE statements of the clients on the tables published
+// to the logical replication subscribers of the Postgres protocol, whose changes would be missed by the subscribers,
+// since only those flushed by the replication are captured (see delta.ChangeSink). The tables joined by an UPDATE or
+// a DELETE are checked as well. The statements replicated by the binlog applier are not rejected.
+func checkPublishedTables(ctx *sql.Context, n sql.Node) error {
+	if ctx.Client().User == binlogreplication.BinlogApplierUser {
+		return nil
+	}
+	var target sql.Node
+	switch n := n.(type) {
+	case *plan.InsertInto:
+		target = n.Destination
+	case *plan.Update:
+		target = n.Child
+	case *plan.DeleteFrom:
+		target = n.Child
+	default:
+		return nil
+	}
+	var err error
+	transform.Inspect(target, func(n sql.Node) bool {
+		if tn, ok := n.(sql.TableNode); ok && delta.Captured(tn.Database().Name(), tn.Name()) {
+			err = fmt.Errorf("cannot modify table %s.%s because it is published; the published tables may only be written by the replication",
+				tn.Database().Name(), tn.Name())
+			return false
+		}
+		return true
+	})
+	return err
+}
+
 // containsVariable inspects if the plan contains a system or user variable.
 func containsVariable(n sql.Node) bool {
 	found := false
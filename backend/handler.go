@@ -17,6 +17,10 @@ package backend
 import (
 	"context"
 	"fmt"
+	"maps"
+	"sync"
+	"time"
+
 	"github.com/apecloud/myduckserver/catalog"
 
 	"github.com/dolthub/go-mysql-server/server"
@@ -27,9 +31,12 @@ import (
 type MyHandler struct {
 	*server.Handler
 	provider *catalog.DatabaseProvider
+	// txnLogs are the logs of the DuckDB transactions of the connections, by connection ID, for savepoints.
+	txnLogs sync.Map
 }
 
 func (h *MyHandler) ConnectionClosed(c *mysql.Conn) {
+	h.txnLogs.Delete(c.ConnectionID)
 	h.provider.Pool().CloseConn(c.ConnectionID)
 	h.Handler.ConnectionClosed(c)
 }
@@ -62,7 +69,14 @@ func (h *MyHandler) ComMultiQuery(
 	var modifiers []ResultModifier
 	query, modifiers = applyRequestModifiers(query, defaultRequestModifiers)
 
-	return h.Handler.ComMultiQuery(ctx, c, query, wrapResultCallback(callback, modifiers...))
+	start := time.Now()
+	callback, rows := countResultRows(callback)
+	remainder, err := h.Handler.ComMultiQuery(ctx, c, query, wrapResultCallback(callback, modifiers...))
+	h.logQuery(c, query, nil, false, err)
+	if err == nil {
+		RecordStatement(c.User, h.provider.DefaultCatalogName(), query[:len(query)-len(remainder)], time.Since(start), *rows)
+	}
+	return remainder, err
 }
 
 // Naive query rewriting. This is just a temporary solution
@@ -76,7 +90,48 @@ func (h *MyHandler) ComQuery(
 	var modifiers []ResultModifier
 	query, modifiers = applyRequestModifiers(query, defaultRequestModifiers)
 
-	return h.Handler.ComQuery(ctx, c, query, wrapResultCallback(callback, modifiers...))
+	// Savepoints in DuckDB transactions are emulated; outside them, they are left to the framework.
+	if kind, name, ok := parseSavepointStatement(query); ok && h.provider.Pool().TryGetTxn(c.ConnectionID) != nil {
+		return h.execSavepointStatement(ctx, c, kind, name, callback)
+	}
+
+	start := time.Now()
+	callback, rows := countResultRows(callback)
+	err := h.Handler.ComQuery(ctx, c, query, wrapResultCallback(callback, modifiers...))
+	h.logQuery(c, query, nil, true, err)
+	if err == nil {
+		RecordStatement(c.User, h.provider.DefaultCatalogName(), query, time.Since(start), *rows)
+	}
+	return err
+}
+
+// ComStmtExecute executes a prepared statement through the binary protocol, which is logged with its bound
+// parameters for savepoints, like the queries.
+func (h *MyHandler) ComStmtExecute(
+	ctx context.Context,
+	c *mysql.Conn,
+	prepare *mysql.PrepareData,
+	callback func(*sqltypes.Result) error,
+) error {
+	if kind, name, ok := parseSavepointStatement(prepare.PrepareStmt); ok && h.provider.Pool().TryGetTxn(c.ConnectionID) != nil {
+		return h.execSavepointStatement(ctx, c, kind, name, func(res *sqltypes.Result, _ bool) error { return callback(res) })
+	}
+
+	err := h.Handler.ComStmtExecute(ctx, c, prepare, callback)
+	// The map of the bound parameters is reused by the following executions of the statement.
+	h.logQuery(c, prepare.PrepareStmt, maps.Clone(prepare.BindVars), true, err)
+	return err
+}
+
+// countResultRows wraps the callback to count the rows returned or affected by the statement, for its statistics.
+func countResultRows(callback mysql.ResultSpoolFn) (mysql.ResultSpoolFn, *int64) {
+	rows := new(int64)
+	return func(res *sqltypes.Result, more bool) error {
+		if res != nil {
+			*rows += int64(len(res.Rows)) + int64(res.RowsAffected)
+		}
+		return callback(res, more)
+	}, rows
 }
 
 func WrapHandler(provider *catalog.DatabaseProvider) server.HandlerWrapper {
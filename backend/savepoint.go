@@ -0,0 +1,191 @@
+// Copyright 2024-2025 ApeCloud, Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+package backend
+
+import (
+	"context"
+	stdsql "database/sql"
+	"errors"
+	"fmt"
+	"regexp"
+	"strings"
+
+	"github.com/dolthub/go-mysql-server/sql"
+	"github.com/dolthub/vitess/go/mysql"
+	"github.com/dolthub/vitess/go/sqltypes"
+	querypb "github.com/dolthub/vitess/go/vt/proto/query"
+)
+
+// DuckDB does not support savepoints, and a failed statement aborts the whole transaction.
+// Savepoints are therefore emulated by replaying: the queries run in a DuckDB transaction are logged,
+// and ROLLBACK TO SAVEPOINT rolls back the transaction, then replays the queries run before the savepoint.
+// The queries sent in a multi-statement request can't be replayed, so ROLLBACK TO SAVEPOINT fails after them.
+// The prepared statements executed through the binary protocol are logged with their bound parameters, and replayed
+// by executing them again.
+
+var savepointRegex = regexp.MustCompile("(?i)^\\s*(SAVEPOINT|RELEASE\\s+SAVEPOINT|ROLLBACK(?:\\s+WORK)?\\s+TO(?:\\s+SAVEPOINT)?)\\s+(`[^`]+`|\\w+)\\s*;?\\s*$")
+
+type savepointKind int
+
+const (
+	savepointCreate savepointKind = iota
+	savepointRelease
+	savepointRollback
+)
+
+// parseSavepointStatement parses SAVEPOINT, RELEASE SAVEPOINT, and ROLLBACK TO SAVEPOINT statements.
+func parseSavepointStatement(query string) (kind savepointKind, name string, ok bool) {
+	m := savepointRegex.FindStringSubmatch(query)
+	if m == nil {
+		return 0, "", false
+	}
+	switch keyword := strings.ToUpper(m[1]); {
+	case strings.HasPrefix(keyword, "SAVEPOINT"):
+		kind = savepointCreate
+	case strings.HasPrefix(keyword, "RELEASE"):
+		kind = savepointRelease
+	default:
+		kind = savepointRollback
+	}
+	return kind, strings.Trim(m[2], "`"), true
+}
+
+// transactionLog is the log of the queries run in the current DuckDB transaction of a connection.
+type transactionLog struct {
+	tx           *stdsql.Tx
+	queries      []loggedQuery
+	savepoints   []savepoint
+	unreplayable bool
+}
+
+// loggedQuery is a query run in a DuckDB transaction, along with its bound parameters if it is a prepared statement
+// executed through the binary protocol.
+type loggedQuery struct {
+	query    string
+	bindVars map[string]*querypb.BindVariable
+}
+
+type savepoint struct {
+	name    string
+	queries int
+}
+
+// findSavepoint returns the index of the savepoint with the name, which is case-insensitive.
+func (l *transactionLog) findSavepoint(name string) int {
+	for i := len(l.savepoints) - 1; i >= 0; i-- {
+		if strings.EqualFold(l.savepoints[i].name, name) {
+			return i
+		}
+	}
+	return -1
+}
+
+// txnLog returns the log of the DuckDB transaction of the connection, which is started anew if the
+// transaction is not the logged one.
+func (h *MyHandler) txnLog(c *mysql.Conn, tx *stdsql.Tx) *transactionLog {
+	if entry, ok := h.txnLogs.Load(c.ConnectionID); ok {
+		log := entry.(*transactionLog)
+		// The transaction is not restarted yet if it is rolled back to a savepoint before any query.
+		if log.tx == tx || log.tx == nil {
+			log.tx = tx
+			return log
+		}
+	}
+	log := &transactionLog{tx: tx}
+	h.txnLogs.Store(c.ConnectionID, log)
+	return log
+}
+
+// logQuery records the query run on the connection if it is in a DuckDB transaction. The bound parameters are nil
+// unless the query is a prepared statement executed through the binary protocol.
+func (h *MyHandler) logQuery(c *mysql.Conn, query string, bindVars map[string]*querypb.BindVariable, replayable bool, err error) {
+	tx := h.provider.Pool().TryGetTxn(c.ConnectionID)
+	if tx == nil {
+		h.txnLogs.Delete(c.ConnectionID)
+		return
+	}
+	log := h.txnLog(c, tx)
+	if !replayable {
+		log.unreplayable = true
+	} else if err == nil {
+		log.queries = append(log.queries, loggedQuery{query: query, bindVars: bindVars})
+	}
+}
+
+// execSavepointStatement runs a savepoint statement in the current DuckDB transaction.
+func (h *MyHandler) execSavepointStatement(
+	ctx context.Context,
+	c *mysql.Conn,
+	kind savepointKind,
+	name string,
+	callback mysql.ResultSpoolFn,
+) error {
+	log := h.txnLog(c, h.provider.Pool().TryGetTxn(c.ConnectionID))
+	i := log.findSavepoint(name)
+	switch kind {
+	case savepointCreate:
+		// A savepoint with the same name is replaced.
+		if i >= 0 {
+			log.savepoints = append(log.savepoints[:i], log.savepoints[i+1:]...)
+		}
+		log.savepoints = append(log.savepoints, savepoint{name: name, queries: len(log.queries)})
+	case savepointRelease:
+		if i < 0 {
+			return sql.ErrSavepointDoesNotExist.New(name)
+		}
+		log.savepoints = log.savepoints[:i]
+	case savepointRollback:
+		if i < 0 {
+			return sql.ErrSavepointDoesNotExist.New(name)
+		}
+		if err := h.rollbackToSavepoint(ctx, c, log, log.savepoints[i]); err != nil {
+			return err
+		}
+		log.savepoints = log.savepoints[:i+1]
+	}
+	return callback(&sqltypes.Result{}, false)
+}
+
+// rollbackToSavepoint rolls back the DuckDB transaction and replays the queries run before the savepoint.
+func (h *MyHandler) rollbackToSavepoint(ctx context.Context, c *mysql.Conn, log *transactionLog, sp savepoint) error {
+	if log.unreplayable {
+		return fmt.Errorf("ROLLBACK TO SAVEPOINT is not supported after a multi-statement query in the same transaction")
+	}
+	discard := func(*sqltypes.Result, bool) error { return nil }
+	queries := log.queries[:sp.queries]
+	log.queries = queries
+
+	if err := h.Handler.ComQuery(ctx, c, "ROLLBACK", discard); err != nil {
+		return err
+	}
+	defer func() {
+		log.tx = h.provider.Pool().TryGetTxn(c.ConnectionID)
+	}()
+	for _, query := range queries {
+		var err error
+		if query.bindVars == nil {
+			err = h.Handler.ComQuery(ctx, c, query.query, discard)
+		} else {
+			prepare := &mysql.PrepareData{PrepareStmt: query.query, BindVars: query.bindVars}
+			err = h.Handler.ComStmtExecute(ctx, c, prepare, func(*sqltypes.Result) error { return nil })
+		}
+		if err != nil {
+			// The transaction is left aborted, and has to be rolled back by the client.
+			log.unreplayable = true
+			return errors.Join(fmt.Errorf("failed to replay the %d queries before savepoint %s", len(queries), sp.name), err)
+		}
+	}
+	return nil
+}
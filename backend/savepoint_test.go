@@ -0,0 +1,65 @@
+// Copyright 2024-2025 ApeCloud, Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+package backend
+
+import (
+	"context"
+	"testing"
+
+	"github.com/apecloud/myduckserver/testutil"
+	"github.com/stretchr/testify/require"
+)
+
+func TestRollbackToSavepointReplaysPreparedStatements(t *testing.T) {
+	testDir := testutil.CreateTestDir(t)
+	testEnv := testutil.NewTestEnv()
+	err := testutil.StartDuckSqlServer(t, testDir, nil, testEnv)
+	require.NoError(t, err)
+	defer testutil.StopDuckSqlServer(t, testEnv.DuckProcess)
+
+	ctx := context.Background()
+	conn, err := testEnv.MyDuckServer.Conn(ctx)
+	require.NoError(t, err)
+	defer conn.Close()
+
+	exec := func(query string, args ...any) {
+		_, err := conn.ExecContext(ctx, query, args...)
+		require.NoError(t, err, query)
+	}
+	exec("CREATE DATABASE IF NOT EXISTS test_savepoint")
+	exec("CREATE TABLE test_savepoint.t (id INT PRIMARY KEY, name TEXT)")
+
+	exec("BEGIN")
+	exec("INSERT INTO test_savepoint.t VALUES (1, 'text')")
+	// The go-sql-driver executes the statements with arguments as prepared statements through the binary protocol.
+	exec("INSERT INTO test_savepoint.t VALUES (?, ?)", 2, "prepared")
+	exec("SAVEPOINT sp")
+	exec("INSERT INTO test_savepoint.t VALUES (?, ?)", 3, "rolled back")
+	exec("ROLLBACK TO SAVEPOINT sp")
+	exec("COMMIT")
+
+	rows, err := conn.QueryContext(ctx, "SELECT id, name FROM test_savepoint.t ORDER BY id")
+	require.NoError(t, err)
+	defer rows.Close()
+	var got [][2]any
+	for rows.Next() {
+		var id int
+		var name string
+		require.NoError(t, rows.Scan(&id, &name))
+		got = append(got, [2]any{id, name})
+	}
+	require.NoError(t, rows.Err())
+	require.Equal(t, [][2]any{{1, "text"}, {2, "prepared"}}, got)
+}
@@ -0,0 +1,263 @@
+// Copyright 2024-2025 ApeCloud, Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+package backend
+
+import (
+	"hash/fnv"
+	"math"
+	"sort"
+	"strconv"
+	"strings"
+	"sync"
+	"time"
+)
+
+// The statistics of the statements executed through the MySQL and the Postgres protocols, as pg_stat_statements
+// keeps them in Postgres: the statements are grouped by their users, their databases, and their normalized texts,
+// in which the constants are replaced by the parameters $1, $2, and so on. Only the statements that succeed are
+// counted. The statistics are kept in memory, for up to MaxStatementStats statements, beyond which those called the
+// least are evicted.
+
+// MaxStatementStats is the maximum number of statements whose statistics are kept, as pg_stat_statements.max.
+const MaxStatementStats = 5000
+
+// StatementStat is the statistics of a normalized statement.
+type StatementStat struct {
+	User     string
+	Database string
+	QueryID  int64
+	Query    string
+	Calls    int64
+	Rows     int64
+	// TotalTime, MinTime, MaxTime, MeanTime, and StddevTime are the execution times in milliseconds.
+	TotalTime  float64
+	MinTime    float64
+	MaxTime    float64
+	MeanTime   float64
+	StddevTime float64
+
+	// m2 is the sum of the squared differences from the mean, by Welford's algorithm.
+	m2 float64
+}
+
+type statementKey struct {
+	user     string
+	database string
+	query    string
+}
+
+type statementStats struct {
+	mu         sync.Mutex
+	statements map[statementKey]*StatementStat
+	reset      time.Time
+}
+
+var stats = &statementStats{statements: make(map[statementKey]*StatementStat), reset: time.Now()}
+
+// RecordStatement adds an execution of the statement, which returned or affected |rows| rows, to its statistics.
+func RecordStatement(user, database, query string, elapsed time.Duration, rows int64) {
+	query = NormalizeQuery(query)
+	if query == "" {
+		return
+	}
+	ms := float64(elapsed) / float64(time.Millisecond)
+	key := statementKey{user: user, database: database, query: query}
+
+	stats.mu.Lock()
+	defer stats.mu.Unlock()
+	stat, ok := stats.statements[key]
+	if !ok {
+		if len(stats.statements) >= MaxStatementStats {
+			stats.evict()
+		}
+		stat = &StatementStat{User: user, Database: database, QueryID: queryID(query), Query: query, MinTime: ms, MaxTime: ms}
+		stats.statements[key] = stat
+	}
+	stat.Calls++
+	stat.Rows += rows
+	stat.TotalTime += ms
+	stat.MinTime = min(stat.MinTime, ms)
+	stat.MaxTime = max(stat.MaxTime, ms)
+	delta := ms - stat.MeanTime
+	stat.MeanTime += delta / float64(stat.Calls)
+	stat.m2 += delta * (ms - stat.MeanTime)
+	stat.StddevTime = math.Sqrt(stat.m2 / float64(stat.Calls))
+}
+
+// evict removes the tenth of the statements that are called the least, as Postgres does.
+func (s *statementStats) evict() {
+	all := make([]statementKey, 0, len(s.statements))
+	for key := range s.statements {
+		all = append(all, key)
+	}
+	sort.Slice(all, func(i, j int) bool {
+		return s.statements[all[i]].Calls < s.statements[all[j]].Calls
+	})
+	for _, key := range all[:max(1, len(all)/10)] {
+		delete(s.statements, key)
+	}
+}
+
+// StatementStats returns the statistics of the statements, ordered by their users, databases, and texts.
+func StatementStats() []StatementStat {
+	stats.mu.Lock()
+	defer stats.mu.Unlock()
+	list := make([]StatementStat, 0, len(stats.statements))
+	for _, stat := range stats.statements {
+		list = append(list, *stat)
+	}
+	sort.Slice(list, func(i, j int) bool {
+		a, b := list[i], list[j]
+		if a.User != b.User {
+			return a.User < b.User
+		}
+		if a.Database != b.Database {
+			return a.Database < b.Database
+		}
+		return a.Query < b.Query
+	})
+	return list
+}
+
+// ResetStatementStats discards the statistics of all the statements, and returns the time of the reset.
+func ResetStatementStats() time.Time {
+	stats.mu.Lock()
+	defer stats.mu.Unlock()
+	stats.statements = make(map[statementKey]*StatementStat)
+	stats.reset = time.Now()
+	return stats.reset
+}
+
+// queryID returns the identifier of the normalized statement, which is a hash of its text.
+func queryID(query string) int64 {
+	h := fnv.New64a()
+	h.Write([]byte(query))
+	return int64(h.Sum64())
+}
+
+// NormalizeQuery returns the text of the statement with its string and numeric constants replaced by the parameters
+// $1, $2, and so on, numbered after those already in the statement, and with its comments removed, its whitespace
+// collapsed, and its trailing semicolon trimmed.
+func NormalizeQuery(query string) string {
+	isWordByte := func(c byte) bool {
+		return c == '_' || c == '$' || c >= 0x80 || (c >= 'a' && c <= 'z') || (c >= 'A' && c <= 'Z') || (c >= '0' && c <= '9')
+	}
+	isDigit := func(c byte) bool { return c >= '0' && c <= '9' }
+
+	// The constants are numbered after the largest parameter of the statement.
+	param := 0
+	for i := 0; i < len(query); i++ {
+		if query[i] == '$' && (i == 0 || !isWordByte(query[i-1])) {
+			j := i + 1
+			for j < len(query) && isDigit(query[j]) {
+				j++
+			}
+			if n, err := strconv.Atoi(query[i+1 : j]); err == nil {
+				param = max(param, n)
+			}
+		}
+	}
+
+	var b strings.Builder
+	b.Grow(len(query))
+	space := false
+	write := func(s string) {
+		if space && b.Len() > 0 {
+			b.WriteByte(' ')
+		}
+		space = false
+		b.WriteString(s)
+	}
+	for i := 0; i < len(query); {
+		c := query[i]
+		switch {
+		case c == ' ' || c == '\t' || c == '\n' || c == '\r' || c == '\f':
+			space = true
+			i++
+		case c == '-' && i+1 < len(query) && query[i+1] == '-':
+			for i < len(query) && query[i] != '\n' {
+				i++
+			}
+			space = true
+		case c == '/' && i+1 < len(query) && query[i+1] == '*':
+			end := strings.Index(query[i+2:], "*/")
+			if end < 0 {
+				i = len(query)
+			} else {
+				i += end + 4
+			}
+			space = true
+		case c == '\'' || ((c == 'E' || c == 'e' || c == 'X' || c == 'x' || c == 'B' || c == 'b') &&
+			i+1 < len(query) && query[i+1] == '\'' && (i == 0 || !isWordByte(query[i-1]))):
+			// A string constant, in which a quote is doubled or escaped by a backslash.
+			j := i + 1
+			if c != '\'' {
+				j++
+			}
+			for j < len(query) {
+				if query[j] == '\\' {
+					j += 2
+					continue
+				}
+				if query[j] == '\'' {
+					if j+1 < len(query) && query[j+1] == '\'' {
+						j += 2
+						continue
+					}
+					break
+				}
+				j++
+			}
+			param++
+			write("$" + strconv.Itoa(param))
+			i = j + 1
+		case c == '"' || c == '`':
+			// A quoted identifier is kept as it is.
+			j := i + 1
+			for j < len(query) && query[j] != c {
+				j++
+			}
+			write(query[i:min(j+1, len(query))])
+			i = j + 1
+		case isDigit(c) && (i == 0 || !isWordByte(query[i-1])):
+			j := i + 1
+			for j < len(query) && (isWordByte(query[j]) || query[j] == '.' ||
+				((query[j] == '+' || query[j] == '-') && (query[j-1] == 'e' || query[j-1] == 'E'))) {
+				j++
+			}
+			param++
+			write("$" + strconv.Itoa(param))
+			i = j
+		case c == '$' && i+1 < len(query) && isDigit(query[i+1]):
+			j := i + 1
+			for j < len(query) && isDigit(query[j]) {
+				j++
+			}
+			write(query[i:j])
+			i = j
+		case isWordByte(c):
+			j := i + 1
+			for j < len(query) && isWordByte(query[j]) {
+				j++
+			}
+			write(query[i:j])
+			i = j
+		default:
+			write(string(c))
+			i++
+		}
+	}
+	return strings.TrimSpace(strings.TrimRight(b.String(), "; "))
+}
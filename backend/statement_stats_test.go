@@ -0,0 +1,70 @@
+// Copyright 2024-2025 ApeCloud, Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+package backend
+
+import (
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/require"
+)
+
+func TestNormalizeQuery(t *testing.T) {
+	tests := []struct {
+		query string
+		want  string
+	}{
+		{query: "SELECT * FROM t WHERE id = 42;", want: "SELECT * FROM t WHERE id = $1"},
+		{query: "select  a,\n\tb from t1 where name = 'it''s' and x > -1.5e+3", want: "select a, b from t1 where name = $1 and x > -$2"},
+		{query: "INSERT INTO t VALUES ($1, 'a', E'b\\'c', 3)", want: "INSERT INTO t VALUES ($1, $2, $3, $4)"},
+		{query: `SELECT "col 1", ` + "`col2`" + ` FROM s.t2 -- comment
+			/* block */ LIMIT 10`, want: `SELECT "col 1", ` + "`col2`" + ` FROM s.t2 LIMIT $1`},
+		{query: "UPDATE t SET v = ? WHERE k = x'ff'", want: "UPDATE t SET v = ? WHERE k = $1"},
+		{query: "  ;  ", want: ""},
+	}
+	for _, tt := range tests {
+		require.Equal(t, tt.want, NormalizeQuery(tt.query), tt.query)
+	}
+}
+
+func TestRecordStatement(t *testing.T) {
+	ResetStatementStats()
+	defer ResetStatementStats()
+
+	RecordStatement("alice", "mysql", "SELECT * FROM t WHERE id = 1", 2*time.Millisecond, 1)
+	RecordStatement("alice", "mysql", "SELECT * FROM t WHERE id = 2", 4*time.Millisecond, 0)
+	RecordStatement("bob", "mysql", "SELECT * FROM t WHERE id = 3", time.Millisecond, 1)
+	RecordStatement("bob", "mysql", "  ", time.Millisecond, 0)
+
+	stats := StatementStats()
+	require.Len(t, stats, 2)
+	alice := stats[0]
+	require.Equal(t, "alice", alice.User)
+	require.Equal(t, "SELECT * FROM t WHERE id = $1", alice.Query)
+	require.Equal(t, int64(2), alice.Calls)
+	require.Equal(t, int64(1), alice.Rows)
+	require.InDelta(t, 6.0, alice.TotalTime, 1e-9)
+	require.InDelta(t, 2.0, alice.MinTime, 1e-9)
+	require.InDelta(t, 4.0, alice.MaxTime, 1e-9)
+	require.InDelta(t, 3.0, alice.MeanTime, 1e-9)
+	require.InDelta(t, 1.0, alice.StddevTime, 1e-9)
+	require.Equal(t, alice.QueryID, stats[1].QueryID)
+	require.Equal(t, "bob", stats[1].User)
+
+	for i := 0; i < MaxStatementStats; i++ {
+		RecordStatement("carol", "mysql", "SELECT "+string(rune('a'+i%26))+"_"+time.Duration(i).String(), 0, 0)
+	}
+	require.LessOrEqual(t, len(StatementStats()), MaxStatementStats)
+}
@@ -33,10 +33,10 @@ import (
 
 var MyBinlogReplicaController = newMyBinlogReplicaController()
 
-// binlogApplierUser is the locked, super user account that is used to execute replicated SQL statements.
+// BinlogApplierUser is the locked, super user account that is used to execute replicated SQL statements.
 // We cannot always assume the root account will exist, so we automatically create this account that is
 // specific to binlog replication and lock it so that it cannot be used to login.
-const binlogApplierUser = "my-binlog-applier"
+const BinlogApplierUser = "my-binlog-applier"
 
 // ErrServerNotConfiguredAsReplica is returned when replication is started without enough configuration provided.
 var ErrServerNotConfiguredAsReplica = fmt.Errorf(
@@ -142,7 +142,7 @@ func (d *myBinlogReplicaController) StartReplica(ctx *sql.Context) error {
 
 	// Set execution context's user to the binlog replication user
 	d.ctx.SetClient(sql.Client{
-		User:    binlogApplierUser,
+		User:    BinlogApplierUser,
 		Address: "localhost",
 	})
 
@@ -167,11 +167,11 @@ func (d *myBinlogReplicaController) configureReplicationUser(ctx *sql.Context) e
 	ed := mySQLDb.Editor()
 	defer ed.Close()
 
-	replicationUser := mySQLDb.GetUser(ed, binlogApplierUser, "localhost", false)
+	replicationUser := mySQLDb.GetUser(ed, BinlogApplierUser, "localhost", false)
 	if replicationUser == nil {
 		// If the replication user doesn't exist yet, create it and lock it
-		mySQLDb.AddSuperUser(ed, binlogApplierUser, "localhost", "")
-		replicationUser := mySQLDb.GetUser(ed, binlogApplierUser, "localhost", false)
+		mySQLDb.AddSuperUser(ed, BinlogApplierUser, "localhost", "")
+		replicationUser := mySQLDb.GetUser(ed, BinlogApplierUser, "localhost", false)
 		if replicationUser == nil {
 			return fmt.Errorf("unable to load replication user")
 		}
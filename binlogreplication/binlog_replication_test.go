@@ -235,7 +235,7 @@ func TestBinlogSystemUserIsLocked(t *testing.T) {
 	defer teardown(t)
 	startSqlServers(t)
 
-	dsn := fmt.Sprintf("%s@tcp(127.0.0.1:%v)/", binlogApplierUser, duckPort)
+	dsn := fmt.Sprintf("%s@tcp(127.0.0.1:%v)/", BinlogApplierUser, duckPort)
 	db, err := sqlx.Open("mysql", dsn)
 	require.NoError(t, err)
 
@@ -1,6 +1,9 @@
 package catalog
 
-import "strings"
+import (
+	"strconv"
+	"strings"
+)
 
 type MacroDefinition struct {
 	Params []string
@@ -12,6 +15,11 @@ var (
 	MacroNameMyListContains string = "my_list_contains"
 
 	MacroNameMySplitListStr string = "my_split_list_str"
+
+	MacroNameJSONTypeof            string = "pg_json_typeof"
+	MacroNameJSONObjectKeys        string = "pg_json_object_keys"
+	MacroNameJSONArrayElements     string = "pg_json_array_elements"
+	MacroNameJSONArrayElementsText string = "pg_json_array_elements_text"
 )
 
 type InternalMacro struct {
@@ -30,6 +38,72 @@ func (v *InternalMacro) QualifiedName() string {
 	return v.Schema + "." + v.Name
 }
 
+// pgAttributeNames lists the names of the columns of each relation in __sys__.pg_attribute in order.
+const pgAttributeNames = "(SELECT attrelid, list(attname ORDER BY attnum) AS names FROM __sys__.pg_attribute GROUP BY attrelid)"
+
+// pgIndexDef returns the definition of an index, or the name of its column at |columnNo| if it is not 0.
+func pgIndexDef(columnNo string) string {
+	return `(SELECT CASE
+        WHEN ` + columnNo + ` = 0
+        THEN 'CREATE ' || CASE WHEN i.indisunique THEN 'UNIQUE ' ELSE '' END || 'INDEX ' || ci.relname ||
+            ' ON ' || n.nspname || '.' || ct.relname || ' USING btree (' ||
+            list_aggregate(list_transform(i.indkey, k -> a.names[k]), 'string_agg', ', ') || ')'
+        ELSE a.names[i.indkey[` + columnNo + `]]
+    END
+    FROM __sys__.pg_index i
+    JOIN __sys__.pg_class ci ON ci.oid = i.indexrelid
+    JOIN __sys__.pg_class ct ON ct.oid = i.indrelid
+    JOIN __sys__.pg_namespace n ON n.oid = ct.relnamespace
+    JOIN ` + pgAttributeNames + ` a ON a.attrelid = i.indrelid
+    WHERE i.indexrelid = index_oid)`
+}
+
+// pgConstraintDef returns the definition of a constraint, as it would be written in CREATE TABLE.
+const pgConstraintDef = `(SELECT CASE c.contype
+        WHEN 'p' THEN 'PRIMARY KEY (' || list_aggregate(list_transform(c.conkey, k -> a.names[k]), 'string_agg', ', ') || ')'
+        WHEN 'u' THEN 'UNIQUE (' || list_aggregate(list_transform(c.conkey, k -> a.names[k]), 'string_agg', ', ') || ')'
+        WHEN 'f' THEN 'FOREIGN KEY (' || list_aggregate(list_transform(c.conkey, k -> a.names[k]), 'string_agg', ', ') ||
+            ') REFERENCES ' || r.relname || '(' || list_aggregate(list_transform(c.confkey, k -> ra.names[k]), 'string_agg', ', ') || ')'
+        ELSE 'CHECK ' || CASE WHEN c.conbin LIKE '(%)' THEN c.conbin ELSE '(' || c.conbin || ')' END
+    END
+    FROM __sys__.pg_constraint c
+    LEFT JOIN ` + pgAttributeNames + ` a ON a.attrelid = c.conrelid
+    LEFT JOIN __sys__.pg_class r ON r.oid = c.confrelid
+    LEFT JOIN ` + pgAttributeNames + ` ra ON ra.attrelid = c.confrelid
+    WHERE c.oid = constraint_oid)`
+
+// pgViewDef returns the query of a view, which is extracted from the SQL of the view in DuckDB.
+const pgViewDef = `(SELECT regexp_extract(v.sql, '(?is)^\s*CREATE\s+(?:OR\s+REPLACE\s+)?(?:TEMP(?:ORARY)?\s+)?VIEW\s+.*?\s+AS\s+(.*?)\s*;?\s*$', 1)
+    FROM duckdb_views() v
+    WHERE v.database_name = current_database() AND ` + pgOIDOffset + ` + v.view_oid = view_oid)`
+
+// pgLSNValue returns the byte position of an LSN in the textual form X/Y, where X and Y are the hexadecimal high
+// and low 32 bits of the position.
+func pgLSNValue(lsn string) string {
+	return `((('0x' || split_part(` + lsn + `::VARCHAR, '/', 1))::UBIGINT << 32) + ('0x' || split_part(` + lsn + `::VARCHAR, '/', 2))::UBIGINT)`
+}
+
+// pgFormatType returns the SQL name of a type with its modifier, e.g., numeric(10,2).
+func pgFormatType() string {
+	var b strings.Builder
+	b.WriteString(`CASE
+    WHEN type_oid IS NULL THEN NULL
+    WHEN type_oid = 1700 AND typemod >= 4 THEN 'numeric(' || ((typemod - 4) // 65536)::VARCHAR || ',' || ((typemod - 4) % 65536)::VARCHAR || ')'`)
+	seen := make(map[int]bool)
+	for _, t := range pgTypes {
+		if seen[t.oid] {
+			continue
+		}
+		seen[t.oid] = true
+		b.WriteString("\n    WHEN type_oid = " + strconv.Itoa(t.oid) + " THEN '" + t.sqlName + "'")
+		b.WriteString("\n    WHEN type_oid = " + strconv.Itoa(t.arrayOID) + " THEN '" + t.sqlName + "[]'")
+	}
+	b.WriteString(`
+    ELSE COALESCE((SELECT typname FROM __sys__.pg_type WHERE oid = type_oid), '???')
+END`)
+	return b.String()
+}
+
 var InternalMacros = []InternalMacro{
 	{
 		Schema:       "information_schema",
@@ -52,13 +126,52 @@ var InternalMacros = []InternalMacro{
 		Definitions: []MacroDefinition{
 			{
 				Params: []string{"index_oid"},
-				// Do nothing currently
-				DDL: `''`,
+				DDL:    pgIndexDef("0"),
 			},
 			{
 				Params: []string{"index_oid", "column_no", "pretty_bool"},
-				// Do nothing currently
-				DDL: `''`,
+				DDL:    pgIndexDef("column_no"),
+			},
+		},
+	},
+	{
+		Schema:       "pg_catalog",
+		Name:         "pg_get_constraintdef",
+		IsTableMacro: false,
+		Definitions: []MacroDefinition{
+			{
+				Params: []string{"constraint_oid"},
+				DDL:    pgConstraintDef,
+			},
+			{
+				Params: []string{"constraint_oid", "pretty_bool"},
+				DDL:    pgConstraintDef,
+			},
+		},
+	},
+	{
+		Schema:       "pg_catalog",
+		Name:         "format_type",
+		IsTableMacro: false,
+		Definitions: []MacroDefinition{
+			{
+				Params: []string{"type_oid", "typemod"},
+				DDL:    pgFormatType(),
+			},
+		},
+	},
+	{
+		Schema:       "pg_catalog",
+		Name:         "pg_get_viewdef",
+		IsTableMacro: false,
+		Definitions: []MacroDefinition{
+			{
+				Params: []string{"view_oid"},
+				DDL:    pgViewDef,
+			},
+			{
+				Params: []string{"view_oid", "pretty_bool"},
+				DDL:    pgViewDef,
 			},
 		},
 	},
@@ -79,6 +192,51 @@ var InternalMacros = []InternalMacro{
 			},
 		},
 	},
+	// The array functions missing in DuckDB, which only support the first dimension of the arrays.
+	{
+		Schema:       "pg_catalog",
+		Name:         "array_lower",
+		IsTableMacro: false,
+		Definitions: []MacroDefinition{
+			{
+				Params: []string{"arr", "dim"},
+				DDL:    `CASE WHEN dim = 1 AND len(arr) > 0 THEN 1 END`,
+			},
+		},
+	},
+	{
+		Schema:       "pg_catalog",
+		Name:         "array_upper",
+		IsTableMacro: false,
+		Definitions: []MacroDefinition{
+			{
+				Params: []string{"arr", "dim"},
+				DDL:    `CASE WHEN dim = 1 AND len(arr) > 0 THEN len(arr) END`,
+			},
+		},
+	},
+	{
+		Schema:       "pg_catalog",
+		Name:         "cardinality",
+		IsTableMacro: false,
+		Definitions: []MacroDefinition{
+			{
+				Params: []string{"arr"},
+				DDL:    `len(arr)`,
+			},
+		},
+	},
+	{
+		Schema:       "pg_catalog",
+		Name:         "pg_wal_lsn_diff",
+		IsTableMacro: false,
+		Definitions: []MacroDefinition{
+			{
+				Params: []string{"lsn1", "lsn2"},
+				DDL:    `(` + pgLSNValue("lsn1") + `::HUGEINT - ` + pgLSNValue("lsn2") + `)::BIGINT`,
+			},
+		},
+	},
 	{
 		Schema:       SchemaNameSYS,
 		Name:         MacroNameMyListContains,
@@ -106,4 +264,55 @@ var InternalMacros = []InternalMacro{
 			},
 		},
 	},
+	{
+		Schema:       SchemaNameSYS,
+		Name:         MacroNameJSONTypeof,
+		IsTableMacro: false,
+		Definitions: []MacroDefinition{
+			{
+				Params: []string{"j"},
+				DDL: `CASE json_type(j)
+    WHEN 'OBJECT' THEN 'object'
+    WHEN 'ARRAY' THEN 'array'
+    WHEN 'VARCHAR' THEN 'string'
+    WHEN 'BOOLEAN' THEN 'boolean'
+    WHEN 'NULL' THEN 'null'
+    ELSE 'number'
+    END`,
+			},
+		},
+	},
+	{
+		Schema:       SchemaNameSYS,
+		Name:         MacroNameJSONObjectKeys,
+		IsTableMacro: false,
+		Definitions: []MacroDefinition{
+			{
+				Params: []string{"j"},
+				DDL:    `unnest(json_keys(j))`,
+			},
+		},
+	},
+	{
+		Schema:       SchemaNameSYS,
+		Name:         MacroNameJSONArrayElements,
+		IsTableMacro: false,
+		Definitions: []MacroDefinition{
+			{
+				Params: []string{"j"},
+				DDL:    `unnest(j::JSON[])`,
+			},
+		},
+	},
+	{
+		Schema:       SchemaNameSYS,
+		Name:         MacroNameJSONArrayElementsText,
+		IsTableMacro: false,
+		Definitions: []MacroDefinition{
+			{
+				Params: []string{"j"},
+				DDL:    `unnest(json_extract_string(j, '$[*]'))`,
+			},
+		},
+	},
 }
@@ -159,16 +159,44 @@ var InternalTables = struct {
 	BinlogPosition     InternalTable
 	PgSubscription     InternalTable
 	GlobalStatus       InternalTable
-	// TODO(sean): This is a temporary work around for clients that query the 'pg_catalog.pg_stat_replication'.
-	//             Once we add 'pg_catalog' and support views for PG, replace this by a view.
-	//             https://www.postgresql.org/docs/current/monitoring-stats.html#MONITORING-PG-STAT-REPLICATION-VIEW
-	PGStatReplication InternalTable
-	PGRange           InternalTable
-	PGType            InternalTable
-	PGProc            InternalTable
-	PGClass           InternalTable
-	PGNamespace       InternalTable
-	PGMatViews        InternalTable
+	PGRange            InternalTable
+	PGType             InternalTable
+	PGProc             InternalTable
+	PGClass            InternalTable
+	PGNamespace        InternalTable
+	PGMatViews         InternalTable
+	// PreparedTransaction persists the transactions prepared by PREPARE TRANSACTION, which are shown in the
+	// pg_prepared_xacts view.
+	PreparedTransaction InternalTable
+	// PGAuthID stores the roles of the Postgres protocol, which are shown in the pg_roles and pg_user views.
+	PGAuthID InternalTable
+	// RolePassword stores the passwords of the roles, apart from PGAuthID, since only the superusers may read them.
+	RolePassword InternalTable
+	// PGAuthMembers stores the memberships of the roles.
+	PGAuthMembers InternalTable
+	// DatabasePrivilege stores the roles granted CONNECT on each database, where 0 stands for PUBLIC.
+	// A database without a row may be connected to by all the roles.
+	DatabasePrivilege InternalTable
+	// PGPublication stores the publications created by CREATE PUBLICATION.
+	PGPublication InternalTable
+	// PublicationRel stores the tables of the publications that are not FOR ALL TABLES, which are shown with those
+	// of the others in the pg_publication_tables view.
+	PublicationRel InternalTable
+	// ReplicationSlot stores the logical replication slots, with the LSNs up to which their subscribers have
+	// confirmed the changes, and the connections streaming them. They are shown in the pg_replication_slots view.
+	ReplicationSlot InternalTable
+	// ReplicationChange stores the captured changes of the published tables by their LSNs, until all the replication
+	// slots have confirmed them.
+	ReplicationChange InternalTable
+	// ForeignServer stores the foreign servers created by CREATE SERVER, which are attached to DuckDB, and are shown
+	// in the pg_foreign_server view, apart from their credentials.
+	ForeignServer InternalTable
+	// UserMapping stores the user and the password of each foreign server, apart from ForeignServer, since only the
+	// superusers may read them. They are those of all the roles, since there are no user mappings of their own.
+	UserMapping InternalTable
+	// ForeignTable stores the foreign tables, which are views of the tables of the foreign servers, and are shown in
+	// the pg_foreign_table view.
+	ForeignTable InternalTable
 }{
 	PersistentVariable: InternalTable{
 		Schema:       "__sys__",
@@ -201,84 +229,6 @@ var InternalTables = struct {
 			{"Innodb_redo_log_enabled", "OFF"}, // Queried by MySQL Shell
 		},
 	},
-	//	postgres=# \d+ pg_catalog.pg_stat_replication
-	//                                 View "pg_catalog.pg_stat_replication"
-	//      Column      |           Type           | Collation | Nullable | Default | Storage  | Description
-	//------------------+--------------------------+-----------+----------+---------+----------+-------------
-	// pid              | integer                  |           |          |         | plain    |
-	// usesysid         | oid                      |           |          |         | plain    |
-	// usename          | name                     |           |          |         | plain    |
-	// application_name | text                     |           |          |         | extended |
-	// client_addr      | inet                     |           |          |         | main     |
-	// client_hostname  | text                     |           |          |         | extended |
-	// client_port      | integer                  |           |          |         | plain    |
-	// backend_start    | timestamp with time zone |           |          |         | plain    |
-	// backend_xmin     | xid                      |           |          |         | plain    |
-	// state            | text                     |           |          |         | extended |
-	// sent_lsn         | pg_lsn                   |           |          |         | plain    |
-	// write_lsn        | pg_lsn                   |           |          |         | plain    |
-	// flush_lsn        | pg_lsn                   |           |          |         | plain    |
-	// replay_lsn       | pg_lsn                   |           |          |         | plain    |
-	// write_lag        | interval                 |           |          |         | plain    |
-	// flush_lag        | interval                 |           |          |         | plain    |
-	// replay_lag       | interval                 |           |          |         | plain    |
-	// sync_priority    | integer                  |           |          |         | plain    |
-	// sync_state       | text                     |           |          |         | extended |
-	// reply_time       | timestamp with time zone |           |          |         | plain    |
-	//View definition:
-	// SELECT s.pid,
-	//    s.usesysid,
-	//    u.rolname AS usename,
-	//    s.application_name,
-	//    s.client_addr,
-	//    s.client_hostname,
-	//    s.client_port,
-	//    s.backend_start,
-	//    s.backend_xmin,
-	//    w.state,
-	//    w.sent_lsn,
-	//    w.write_lsn,
-	//    w.flush_lsn,
-	//    w.replay_lsn,
-	//    w.write_lag,
-	//    w.flush_lag,
-	//    w.replay_lag,
-	//    w.sync_priority,
-	//    w.sync_state,
-	//    w.reply_time
-	//   FROM pg_stat_get_activity(NULL::integer) s(datid, pid, usesysid, application_name, state, query, wait_event_type, wait_event, xact_start, query_start, backend_start, state_change, client_addr, client_hostname, client_port, backend_xid, backend_xmin, backend_type, ssl, sslversion, sslcipher, sslbits, ssl_client_dn, ssl_client_serial, ssl_issuer_dn, gss_auth, gss_princ, gss_enc, gss_delegation, leader_pid, query_id)
-	//     JOIN pg_stat_get_wal_senders() w(pid, state, sent_lsn, write_lsn, flush_lsn, replay_lsn, write_lag, flush_lag, replay_lag, sync_priority, sync_state, reply_time) ON s.pid = w.pid
-	//     LEFT JOIN pg_authid u ON s.usesysid = u.oid;
-	PGStatReplication: InternalTable{
-		// Since the "pg_catalog" is the system catalog on DuckDB, we use "__sys__" as the schema name.
-		Schema: "__sys__",
-		Name:   "pg_stat_replication",
-		KeyColumns: []string{
-			"pid",
-		},
-		ValueColumns: []string{
-			"usesysid",
-			"usename",
-			"application_name",
-			"client_addr",
-			"client_hostname",
-			"client_port",
-			"backend_start",
-			"backend_xmin",
-			"state",
-			"sent_lsn",
-			"write_lsn",
-			"flush_lsn",
-			"replay_lsn",
-			"write_lag",
-			"flush_lag",
-			"replay_lag",
-			"sync_priority",
-			"sync_state",
-			"reply_time",
-		},
-		DDL: "pid INTEGER PRIMARY KEY, usesysid TEXT, usename TEXT, application_name TEXT, client_addr TEXT, client_hostname TEXT, client_port INTEGER, backend_start TIMESTAMP, backend_xmin INTEGER, state TEXT, sent_lsn TEXT, write_lsn TEXT, flush_lsn TEXT, replay_lsn TEXT, write_lag INTERVAL, flush_lag INTERVAL, replay_lag INTERVAL, sync_priority INTEGER, sync_state TEXT, reply_time TIMESTAMP",
-	},
 	PGRange: InternalTable{
 		Schema:       "__sys__",
 		Name:         "pg_range",
@@ -323,9 +273,10 @@ var InternalTables = struct {
 	// typdefaultbin     | VARCHAR       | C         |          |         | extended  |             |              |
 	// typdefault        | TEXT          | C         |          |         | extended  |             |              |
 	// typacl            | TEXT[]        |           |          |         | extended  |             |              |
+	// The built-in types of Postgres, which the pg_type view combines with the enum types in DuckDB.
 	PGType: InternalTable{
 		Schema: "__sys__",
-		Name:   "pg_type",
+		Name:   "pg_type_builtin",
 		KeyColumns: []string{
 			"oid",
 		},
@@ -511,9 +462,10 @@ var InternalTables = struct {
 	// relacl              | aclitem[]    |           |          |         | extended |             |              |
 	// reloptions          | text[]       | C         |          |         | extended |             |              |
 	// relpartbound        | pg_node_tree | C         |          |         | extended |             |              |
+	// The built-in relations of Postgres, which the pg_class view combines with the relations in DuckDB.
 	PGClass: InternalTable{
 		Schema: "__sys__",
-		Name:   "pg_class",
+		Name:   "pg_class_builtin",
 		KeyColumns: []string{
 			"oid",
 		},
@@ -592,9 +544,10 @@ var InternalTables = struct {
 	// nspname  | name      |           | not null |         | plain    |             |              |
 	// nspowner | oid       |           | not null |         | plain    |             |              |
 	// nspacl   | aclitem[] |           |          |         | extended |             |              |
+	// The built-in schemas of Postgres, which the pg_namespace view combines with the schemas in DuckDB.
 	PGNamespace: InternalTable{
 		Schema: "__sys__",
-		Name:   "pg_namespace",
+		Name:   "pg_namespace_builtin",
 		KeyColumns: []string{
 			"oid",
 		},
@@ -655,6 +608,139 @@ var InternalTables = struct {
 			"ispopulated BOOLEAN, " +
 			"definition TEXT",
 	},
+	PreparedTransaction: InternalTable{
+		Schema:       "__sys__",
+		Name:         "prepared_transaction",
+		KeyColumns:   []string{"gid"},
+		ValueColumns: []string{"xid", "prepared", "owner", "database", "schema", "statements"},
+		DDL: "gid TEXT PRIMARY KEY, " +
+			"xid BIGINT, " +
+			"prepared TIMESTAMPTZ, " +
+			"owner TEXT, " +
+			"database TEXT, " +
+			"schema TEXT, " +
+			"statements JSON",
+	},
+	PGAuthID: InternalTable{
+		Schema:     "__sys__",
+		Name:       "pg_authid",
+		KeyColumns: []string{"oid"},
+		ValueColumns: []string{
+			"rolname", "rolsuper", "rolinherit", "rolcreaterole", "rolcreatedb", "rolcanlogin", "rolreplication",
+			"rolbypassrls", "rolconnlimit", "rolvaliduntil",
+		},
+		DDL: "oid BIGINT PRIMARY KEY, " +
+			"rolname TEXT NOT NULL, " +
+			"rolsuper BOOLEAN, " +
+			"rolinherit BOOLEAN, " +
+			"rolcreaterole BOOLEAN, " +
+			"rolcreatedb BOOLEAN, " +
+			"rolcanlogin BOOLEAN, " +
+			"rolreplication BOOLEAN, " +
+			"rolbypassrls BOOLEAN, " +
+			"rolconnlimit INTEGER, " +
+			"rolvaliduntil TIMESTAMPTZ",
+		// The bootstrap superuser, whose password is given on the command line.
+		InitialData: [][]any{
+			{10, "postgres", true, true, true, true, true, true, true, -1, nil},
+		},
+	},
+	RolePassword: InternalTable{
+		Schema:       "__sys__",
+		Name:         "role_password",
+		KeyColumns:   []string{"oid"},
+		ValueColumns: []string{"rolpassword"},
+		DDL:          "oid BIGINT PRIMARY KEY, rolpassword TEXT NOT NULL",
+	},
+	PGAuthMembers: InternalTable{
+		Schema:       "__sys__",
+		Name:         "pg_auth_members",
+		KeyColumns:   []string{"roleid", "member"},
+		ValueColumns: []string{"grantor", "admin_option"},
+		DDL: "roleid BIGINT, " +
+			"member BIGINT, " +
+			"grantor BIGINT, " +
+			"admin_option BOOLEAN, " +
+			"PRIMARY KEY (roleid, member)",
+	},
+	DatabasePrivilege: InternalTable{
+		Schema:       "__sys__",
+		Name:         "database_privilege",
+		KeyColumns:   []string{"database"},
+		ValueColumns: []string{"connect"},
+		DDL:          "database TEXT PRIMARY KEY, connect BIGINT[]",
+	},
+	PGPublication: InternalTable{
+		Schema:       "__sys__",
+		Name:         "pg_publication",
+		KeyColumns:   []string{"pubname"},
+		ValueColumns: []string{"oid", "pubowner", "puballtables", "pubinsert", "pubupdate", "pubdelete", "pubtruncate"},
+		DDL: "pubname TEXT PRIMARY KEY, " +
+			"oid BIGINT NOT NULL, " +
+			"pubowner BIGINT, " +
+			"puballtables BOOLEAN, " +
+			"pubinsert BOOLEAN, " +
+			"pubupdate BOOLEAN, " +
+			"pubdelete BOOLEAN, " +
+			"pubtruncate BOOLEAN",
+	},
+	PublicationRel: InternalTable{
+		Schema:     "__sys__",
+		Name:       "publication_rel",
+		KeyColumns: []string{"pubname", "schemaname", "tablename"},
+		DDL: "pubname TEXT, " +
+			"schemaname TEXT, " +
+			"tablename TEXT, " +
+			"PRIMARY KEY (pubname, schemaname, tablename)",
+	},
+	ReplicationSlot: InternalTable{
+		Schema:       "__sys__",
+		Name:         "replication_slot",
+		KeyColumns:   []string{"slot_name"},
+		ValueColumns: []string{"plugin", "database", "confirmed_flush_lsn", "active_pid"},
+		DDL: "slot_name TEXT PRIMARY KEY, " +
+			"plugin TEXT, " +
+			"database TEXT, " +
+			"confirmed_flush_lsn BIGINT, " +
+			"active_pid INTEGER",
+	},
+	ReplicationChange: InternalTable{
+		Schema:       "__sys__",
+		Name:         "replication_change",
+		KeyColumns:   []string{"lsn"},
+		ValueColumns: []string{"committed", "changes"},
+		DDL:          "lsn BIGINT PRIMARY KEY, committed TIMESTAMPTZ, changes JSON",
+	},
+	ForeignServer: InternalTable{
+		Schema:       "__sys__",
+		Name:         "foreign_server",
+		KeyColumns:   []string{"srvname"},
+		ValueColumns: []string{"oid", "srvowner", "srvfdw", "srvoptions"},
+		DDL: "srvname TEXT PRIMARY KEY, " +
+			"oid BIGINT NOT NULL, " +
+			"srvowner BIGINT, " +
+			"srvfdw TEXT, " +
+			"srvoptions TEXT[]",
+	},
+	UserMapping: InternalTable{
+		Schema:       "__sys__",
+		Name:         "user_mapping",
+		KeyColumns:   []string{"srvname"},
+		ValueColumns: []string{"umoptions"},
+		DDL:          "srvname TEXT PRIMARY KEY, umoptions TEXT[]",
+	},
+	ForeignTable: InternalTable{
+		Schema:       "__sys__",
+		Name:         "foreign_table",
+		KeyColumns:   []string{"schemaname", "tablename"},
+		ValueColumns: []string{"srvname", "ftowner", "ftoptions"},
+		DDL: "schemaname TEXT, " +
+			"tablename TEXT, " +
+			"srvname TEXT, " +
+			"ftowner BIGINT, " +
+			"ftoptions TEXT[], " +
+			"PRIMARY KEY (schemaname, tablename)",
+	},
 }
 
 var internalTables = []InternalTable{
@@ -662,13 +748,24 @@ var internalTables = []InternalTable{
 	InternalTables.BinlogPosition,
 	InternalTables.PgSubscription,
 	InternalTables.GlobalStatus,
-	InternalTables.PGStatReplication,
 	InternalTables.PGRange,
 	InternalTables.PGType,
 	InternalTables.PGProc,
 	InternalTables.PGClass,
 	InternalTables.PGNamespace,
 	InternalTables.PGMatViews,
+	InternalTables.PreparedTransaction,
+	InternalTables.PGAuthID,
+	InternalTables.RolePassword,
+	InternalTables.PGAuthMembers,
+	InternalTables.DatabasePrivilege,
+	InternalTables.PGPublication,
+	InternalTables.PublicationRel,
+	InternalTables.ReplicationSlot,
+	InternalTables.ReplicationChange,
+	InternalTables.ForeignServer,
+	InternalTables.UserMapping,
+	InternalTables.ForeignTable,
 }
 
 func GetInternalTables() []InternalTable {
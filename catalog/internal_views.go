@@ -1,5 +1,10 @@
 package catalog
 
+import (
+	"strconv"
+	"strings"
+)
+
 type InternalView struct {
 	Schema string
 	Name   string
@@ -10,6 +15,125 @@ func (v *InternalView) QualifiedName() string {
 	return v.Schema + "." + v.Name
 }
 
+// The objects in DuckDB are given their OIDs in DuckDB offset by FirstNormalObjectId of Postgres,
+// so that they do not collide with the OIDs of the built-in objects in pg_catalog.
+const pgOIDOffset = "16384"
+
+// pgUserObjects selects the rows of a DuckDB metadata function for the objects in the current catalog,
+// except those in the internal schemas.
+func pgUserObjects(function string) string {
+	return "(SELECT * FROM " + function + " WHERE database_name = current_database()" +
+		" AND schema_name NOT IN ('__sys__', 'mysql', 'pg_catalog', 'information_schema'))"
+}
+
+// pgConstraintOID and pgConstraintIndexOID return the OIDs of a constraint in duckdb_constraints(), and of the
+// index that implements it if it is a primary key or unique constraint, since DuckDB does not assign OIDs to them.
+func pgConstraintOID(alias string) string {
+	return "((" + pgOIDOffset + " + " + alias + ".table_oid) * 1000 + " + alias + ".constraint_index * 2)"
+}
+
+func pgConstraintIndexOID(alias string) string {
+	return "(" + pgConstraintOID(alias) + " + 1)"
+}
+
+// pgColumnNames lists the names of the columns of each table in order.
+const pgColumnNames = "(SELECT table_oid, list(column_name ORDER BY column_index) AS names FROM duckdb_columns() GROUP BY table_oid)"
+
+// pgIndexes lists the indexes of the tables in DuckDB with the positions of their columns, including the indexes
+// that implement the primary key and unique constraints. The columns of an index created by CREATE INDEX are
+// parsed from its SQL, since DuckDB does not expose them.
+var pgIndexes = `SELECT
+        ` + pgConstraintIndexOID("c") + ` AS oid,
+        c.constraint_name AS relname,
+        c.schema_oid,
+        c.table_oid,
+        c.constraint_type = 'PRIMARY KEY' AS is_primary,
+        TRUE AS is_unique,
+        list_transform(c.constraint_column_indexes, x -> x + 1)::BIGINT[] AS columns
+    FROM ` + pgUserObjects("duckdb_constraints()") + ` c
+    WHERE c.constraint_type IN ('PRIMARY KEY', 'UNIQUE')
+    UNION ALL
+    SELECT
+        ` + pgOIDOffset + ` + i.index_oid,
+        i.index_name,
+        i.schema_oid,
+        i.table_oid,
+        i.is_primary,
+        i.is_unique,
+        list_transform(
+            string_split(regexp_extract(i.sql, '\(([^()]*)\)\s*;?\s*$', 1), ','),
+            n -> COALESCE(list_position(tc.names, trim(trim(n), '"')), 0)
+        )::BIGINT[]
+    FROM ` + pgUserObjects("duckdb_indexes()") + ` i
+    LEFT JOIN ` + pgColumnNames + ` tc ON tc.table_oid = i.table_oid`
+
+// pgEnumTypes selects the enum types created in the current catalog by CREATE TYPE ... AS ENUM.
+var pgEnumTypes = "(SELECT * FROM " + pgUserObjects("duckdb_types()") + " t WHERE t.logical_type = 'ENUM' AND NOT t.internal)"
+
+// pgTypes maps the DuckDB types to the OIDs of the Postgres types and of their array types, and to their names,
+// consistently with the types of the columns in the query results.
+var pgTypes = []struct {
+	duckType string
+	oid      int
+	arrayOID int
+	sqlName  string // The name of the type in information_schema
+}{
+	{"BOOLEAN", 16, 1000, "boolean"},
+	{"TINYINT", 21, 1005, "smallint"},
+	{"SMALLINT", 21, 1005, "smallint"},
+	{"INTEGER", 23, 1007, "integer"},
+	{"BIGINT", 20, 1016, "bigint"},
+	{"UTINYINT", 21, 1005, "smallint"},
+	{"USMALLINT", 23, 1007, "integer"},
+	{"UINTEGER", 20, 1016, "bigint"},
+	{"UBIGINT", 1700, 1231, "numeric"},
+	{"HUGEINT", 1700, 1231, "numeric"},
+	{"UHUGEINT", 1700, 1231, "numeric"},
+	{"VARINT", 1700, 1231, "numeric"},
+	{"DECIMAL", 1700, 1231, "numeric"},
+	{"FLOAT", 700, 1021, "real"},
+	{"DOUBLE", 701, 1022, "double precision"},
+	{"VARCHAR", 25, 1009, "text"},
+	{"BLOB", 17, 1001, "bytea"},
+	{"DATE", 1082, 1182, "date"},
+	{"TIME", 1083, 1183, "time without time zone"},
+	{"TIME WITH TIME ZONE", 1266, 1270, "time with time zone"},
+	{"TIMESTAMP", 1114, 1115, "timestamp without time zone"},
+	{"TIMESTAMP_S", 1114, 1115, "timestamp without time zone"},
+	{"TIMESTAMP_MS", 1114, 1115, "timestamp without time zone"},
+	{"TIMESTAMP_NS", 1114, 1115, "timestamp without time zone"},
+	{"TIMESTAMP WITH TIME ZONE", 1184, 1185, "timestamp with time zone"},
+	{"INTERVAL", 1186, 1187, "interval"},
+	{"UUID", 2950, 2951, "uuid"},
+	{"BIT", 1560, 1561, "bit"},
+}
+
+// pgTypeOID returns the SQL expression of the OID of the Postgres type of a column of the given DuckDB type.
+// Any other type is shown as text.
+func pgTypeOID(dataType string) string {
+	var scalars, arrays strings.Builder
+	for _, t := range pgTypes {
+		scalars.WriteString(" WHEN '" + t.duckType + "' THEN " + strconv.Itoa(t.oid))
+		arrays.WriteString(" WHEN '" + t.duckType + "' THEN " + strconv.Itoa(t.arrayOID))
+	}
+	baseType := "regexp_replace(" + dataType + `, '(\(.*\))?(\[\])?$', '')`
+	return "CASE WHEN " + dataType + " LIKE '%[]'" +
+		" THEN CASE " + baseType + arrays.String() + " ELSE 1009 END" +
+		" ELSE CASE " + baseType + scalars.String() + " ELSE 25 END END"
+}
+
+// pgTypeName returns the SQL expression of the name of the Postgres type of a column of the given DuckDB type,
+// as shown in information_schema.
+func pgTypeName(dataType string) string {
+	var b strings.Builder
+	for _, t := range pgTypes {
+		b.WriteString(" WHEN '" + t.duckType + "' THEN '" + t.sqlName + "'")
+	}
+	baseType := "regexp_replace(" + dataType + `, '(\(.*\))?$', '')`
+	return "CASE WHEN " + dataType + " LIKE '%[]' THEN 'ARRAY'" +
+		" ELSE CASE " + baseType + b.String() + " ELSE 'text' END END"
+}
+
 var InternalViews = []InternalView{
 	{
 		Schema: "__sys__",
@@ -45,44 +169,541 @@ FROM
     information_schema.tables t
 WHERE
     t.table_type = 'BASE TABLE'; -- Include only base tables (not views)`,
+	},
+	{
+		Schema: "__sys__",
+		Name:   "pg_namespace",
+		DDL: `SELECT oid, nspname, nspowner, nspacl
+FROM __sys__.pg_namespace_builtin
+WHERE nspname IN ('pg_catalog', 'pg_toast', 'information_schema')
+UNION ALL
+SELECT
+    ` + pgOIDOffset + ` + s.oid AS oid,
+    s.schema_name AS nspname,
+    10 AS nspowner,                                   -- The OID of the superuser
+    NULL AS nspacl
+FROM ` + pgUserObjects("duckdb_schemas()") + ` s;`,
 	},
 	{
 		Schema: "__sys__",
 		Name:   "pg_index",
 		DDL: `SELECT
-    ROW_NUMBER() OVER () AS indexrelid,                -- Simulated unique ID for the index
-    t.table_oid AS indrelid,                          -- OID of the table
-    COUNT(k.column_name) AS indnatts,                 -- Number of columns included in the index
-    COUNT(k.column_name) AS indnkeyatts,              -- Number of key columns in the index (same as indnatts here)
-    CASE
-        WHEN c.constraint_type = 'UNIQUE' THEN TRUE
-        ELSE FALSE
-    END AS indisunique,                               -- Indicates if the index is unique
-    CASE
-        WHEN c.constraint_type = 'PRIMARY KEY' THEN TRUE
-        ELSE FALSE
-    END AS indisprimary,                              -- Indicates if the index is a primary key
-    ARRAY_AGG(k.ordinal_position ORDER BY k.ordinal_position) AS indkey,  -- Array of column positions
-    ARRAY[]::BIGINT[] AS indcollation,                -- DuckDB does not support collation, set to default
-    ARRAY[]::BIGINT[] AS indclass,                    -- DuckDB does not support index class, set to default
-    ARRAY[]::INTEGER[] AS indoption,                  -- DuckDB does not support index options, set to default
+    i.oid AS indexrelid,
+    ` + pgOIDOffset + ` + i.table_oid AS indrelid,
+    len(i.columns)::SMALLINT AS indnatts,
+    len(i.columns)::SMALLINT AS indnkeyatts,          -- DuckDB does not support included columns
+    i.is_unique AS indisunique,
+    FALSE AS indnullsnotdistinct,
+    i.is_primary AS indisprimary,
+    FALSE AS indisexclusion,
+    TRUE AS indimmediate,
+    FALSE AS indisclustered,
+    TRUE AS indisvalid,
+    FALSE AS indcheckxmin,
+    TRUE AS indisready,
+    TRUE AS indislive,
+    FALSE AS indisreplident,
+    i.columns AS indkey,                              -- Column positions, or 0 for an expression
+    list_transform(i.columns, x -> 0) AS indcollation, -- DuckDB does not support collation, set to default
+    list_transform(i.columns, x -> 0) AS indclass,    -- DuckDB does not support index class, set to default
+    list_transform(i.columns, x -> 0) AS indoption,   -- DuckDB does not support index options, set to default
     NULL AS indexprs,                                 -- DuckDB does not support expression indexes, set to NULL
     NULL AS indpred                                   -- DuckDB does not support partial indexes, set to NULL
-FROM
-    information_schema.key_column_usage k
-JOIN
-    information_schema.table_constraints c
-    ON k.constraint_name = c.constraint_name
-    AND k.table_name = c.table_name
-JOIN
-    duckdb_tables() t
-    ON k.table_name = t.table_name
-    AND k.table_schema = t.schema_name
-WHERE
-    c.constraint_type IN ('PRIMARY KEY', 'UNIQUE')    -- Only select primary key and unique constraints
-GROUP BY
-    t.table_oid, c.constraint_type, c.constraint_name
-ORDER BY
-    t.table_oid;`,
+FROM (` + pgIndexes + `) i;`,
+	},
+	{
+		Schema: "__sys__",
+		Name:   "pg_class",
+		DDL: `WITH rels AS (
+    SELECT
+        ` + pgOIDOffset + ` + t.table_oid AS oid, t.table_name AS relname, t.schema_oid, 'r' AS relkind,
+        2 AS relam, t.column_count AS relnatts, t.check_constraint_count AS relchecks,
+        t.estimated_size AS reltuples, t.temporary
+    FROM ` + pgUserObjects("duckdb_tables()") + ` t
+    UNION ALL
+    SELECT ` + pgOIDOffset + ` + v.view_oid, v.view_name, v.schema_oid, 'v', 0, v.column_count, 0, 0, v.temporary
+    FROM ` + pgUserObjects("duckdb_views()") + ` v
+    UNION ALL
+    SELECT ` + pgOIDOffset + ` + s.sequence_oid, s.sequence_name, s.schema_oid, 'S', 0, 3, 0, 1, s.temporary
+    FROM ` + pgUserObjects("duckdb_sequences()") + ` s
+    UNION ALL
+    SELECT i.oid, i.relname, i.schema_oid, 'i', 403, len(i.columns), 0, 0, FALSE -- 403 is the btree access method
+    FROM (` + pgIndexes + `) i
+)
+SELECT
+    oid, relname, relnamespace, reltype, reloftype, relowner, relam, relfilenode, reltablespace, relpages,
+    reltuples, relallvisible, reltoastrelid, relhasindex, relisshared, relpersistence, relkind, relnatts,
+    relchecks, relhasrules, relhastriggers, relhassubclass, relrowsecurity, relforcerowsecurity, relispopulated,
+    relreplident, relispartition, relrewrite, relfrozenxid, relminmxid, relacl, reloptions, relpartbound
+FROM __sys__.pg_class_builtin
+UNION ALL
+SELECT
+    r.oid,
+    r.relname,
+    ` + pgOIDOffset + ` + r.schema_oid AS relnamespace,
+    0 AS reltype,
+    0 AS reloftype,
+    10 AS relowner,                                   -- The OID of the superuser
+    r.relam,
+    0 AS relfilenode,
+    0 AS reltablespace,
+    0 AS relpages,
+    r.reltuples::FLOAT AS reltuples,
+    0 AS relallvisible,
+    0 AS reltoastrelid,
+    r.oid IN (SELECT indrelid FROM __sys__.pg_index) AS relhasindex,
+    FALSE AS relisshared,
+    CASE WHEN r.temporary THEN 't' ELSE 'p' END AS relpersistence,
+    r.relkind,
+    r.relnatts,
+    r.relchecks,
+    FALSE AS relhasrules,
+    FALSE AS relhastriggers,
+    FALSE AS relhassubclass,
+    FALSE AS relrowsecurity,
+    FALSE AS relforcerowsecurity,
+    TRUE AS relispopulated,
+    'd' AS relreplident,
+    FALSE AS relispartition,
+    0 AS relrewrite,
+    0 AS relfrozenxid,
+    0 AS relminmxid,
+    NULL AS relacl,
+    NULL AS reloptions,
+    NULL AS relpartbound
+FROM rels r;`,
+	},
+	{
+		Schema: "__sys__",
+		Name:   "pg_type",
+		DDL: `SELECT
+    oid, typname, typnamespace, typowner, typlen, typbyval, typtype, typcategory, typispreferred, typisdefined,
+    typdelim, typrelid, typsubscript, typelem, typarray, typinput, typoutput, typreceive, typsend, typmodin,
+    typmodout, typanalyze, typalign, typstorage, typnotnull, typbasetype, typtypmod, typndims, typcollation,
+    typdefaultbin, typdefault, typacl
+FROM __sys__.pg_type_builtin
+UNION ALL
+SELECT
+    ` + pgOIDOffset + ` + t.type_oid AS oid,
+    t.type_name AS typname,
+    ` + pgOIDOffset + ` + t.schema_oid AS typnamespace,
+    10 AS typowner,
+    4 AS typlen,
+    TRUE AS typbyval,
+    'e' AS typtype,
+    'E' AS typcategory,
+    FALSE AS typispreferred,
+    TRUE AS typisdefined,
+    ',' AS typdelim,
+    0 AS typrelid,
+    0 AS typsubscript,
+    0 AS typelem,
+    0 AS typarray,                                    -- DuckDB does not create the array types of enums
+    3504 AS typinput,                                 -- enum_in
+    3505 AS typoutput,                                -- enum_out
+    3532 AS typreceive,                               -- enum_recv
+    3533 AS typsend,                                  -- enum_send
+    0 AS typmodin,
+    0 AS typmodout,
+    0 AS typanalyze,
+    'i' AS typalign,
+    'p' AS typstorage,
+    FALSE AS typnotnull,
+    0 AS typbasetype,
+    -1 AS typtypmod,
+    0 AS typndims,
+    0 AS typcollation,
+    NULL AS typdefaultbin,
+    NULL AS typdefault,
+    NULL AS typacl
+FROM ` + pgEnumTypes + ` t;`,
+	},
+	{
+		Schema: "__sys__",
+		Name:   "pg_enum",
+		DDL: `SELECT
+    (` + pgOIDOffset + ` + e.type_oid) * 65536 + e.sortorder AS oid, -- DuckDB does not assign OIDs to the labels
+    ` + pgOIDOffset + ` + e.type_oid AS enumtypid,
+    e.sortorder::REAL AS enumsortorder,
+    e.label AS enumlabel
+FROM (
+    SELECT type_oid, unnest(labels) AS label, unnest(range(1, len(labels) + 1)) AS sortorder
+    FROM ` + pgEnumTypes + ` t
+) e;`,
+	},
+	{
+		Schema: "__sys__",
+		Name:   "pg_attribute",
+		DDL: `SELECT
+    ` + pgOIDOffset + ` + c.table_oid AS attrelid,
+    c.column_name AS attname,
+    c.atttypid,
+    COALESCE(t.typlen, -1) AS attlen,
+    c.column_index AS attnum,
+    -1 AS attcacheoff,
+    CASE
+        WHEN c.data_type LIKE 'DECIMAL(%)' THEN c.numeric_precision * 65536 + c.numeric_scale + 4
+        ELSE -1
+    END AS atttypmod,                                 -- The precision and the scale of a numeric column
+    CASE WHEN c.data_type LIKE '%[]' THEN 1 ELSE 0 END AS attndims,
+    COALESCE(t.typbyval, FALSE) AS attbyval,
+    COALESCE(t.typalign, 'i') AS attalign,
+    COALESCE(t.typstorage, 'x') AS attstorage,
+    '' AS attcompression,
+    NOT c.is_nullable AS attnotnull,
+    c.column_default IS NOT NULL AS atthasdef,
+    FALSE AS atthasmissing,
+    '' AS attidentity,
+    '' AS attgenerated,
+    FALSE AS attisdropped,
+    TRUE AS attislocal,
+    0 AS attinhcount,
+    -1 AS attstattarget,
+    CASE WHEN c.atttypid = 25 THEN 100 ELSE 0 END AS attcollation, -- The default collation for text
+    NULL AS attacl,
+    NULL AS attoptions,
+    NULL AS attfdwoptions,
+    NULL AS attmissingval
+FROM (
+    SELECT col.*, COALESCE(` + pgOIDOffset + ` + e.type_oid, ` + pgTypeOID("col.data_type") + `) AS atttypid
+    FROM ` + pgUserObjects("duckdb_columns()") + ` col
+    LEFT JOIN ` + pgEnumTypes + ` e ON e.schema_name = col.schema_name AND e.type_name = col.data_type
+) c
+LEFT JOIN __sys__.pg_type t ON t.oid = c.atttypid;`,
+	},
+	{
+		Schema: "__sys__",
+		Name:   "pg_attrdef",
+		DDL: `SELECT
+    (` + pgOIDOffset + ` + c.table_oid) * 1000 + c.column_index AS oid,
+    ` + pgOIDOffset + ` + c.table_oid AS adrelid,
+    c.column_index AS adnum,
+    c.column_default AS adbin                         -- The SQL of the default value rather than a node tree
+FROM ` + pgUserObjects("duckdb_columns()") + ` c
+WHERE c.column_default IS NOT NULL;`,
+	},
+	{
+		Schema: "__sys__",
+		Name:   "pg_constraint",
+		DDL: `SELECT
+    ` + pgConstraintOID("c") + ` AS oid,
+    c.constraint_name AS conname,
+    ` + pgOIDOffset + ` + c.schema_oid AS connamespace,
+    CASE c.constraint_type
+        WHEN 'PRIMARY KEY' THEN 'p'
+        WHEN 'UNIQUE' THEN 'u'
+        WHEN 'FOREIGN KEY' THEN 'f'
+        ELSE 'c'
+    END AS contype,
+    FALSE AS condeferrable,
+    FALSE AS condeferred,
+    TRUE AS convalidated,
+    ` + pgOIDOffset + ` + c.table_oid AS conrelid,
+    0 AS contypid,
+    CASE
+        WHEN c.constraint_type IN ('PRIMARY KEY', 'UNIQUE') THEN ` + pgConstraintIndexOID("c") + `
+        ELSE 0
+    END AS conindid,                                  -- The index that implements the constraint
+    0 AS conparentid,
+    COALESCE(` + pgOIDOffset + ` + r.table_oid, 0) AS confrelid,
+    CASE WHEN c.constraint_type = 'FOREIGN KEY' THEN 'a' ELSE ' ' END AS confupdtype,
+    CASE WHEN c.constraint_type = 'FOREIGN KEY' THEN 'a' ELSE ' ' END AS confdeltype,
+    CASE WHEN c.constraint_type = 'FOREIGN KEY' THEN 's' ELSE ' ' END AS confmatchtype,
+    TRUE AS conislocal,
+    0 AS coninhcount,
+    FALSE AS connoinherit,
+    list_transform(c.constraint_column_indexes, x -> x + 1)::SMALLINT[] AS conkey,
+    CASE
+        WHEN c.constraint_type = 'FOREIGN KEY'
+        THEN list_transform(c.referenced_column_names, n -> list_position(rc.names, n))::SMALLINT[]
+    END AS confkey,
+    NULL AS conpfeqop,
+    NULL AS conppeqop,
+    NULL AS conffeqop,
+    NULL AS confdelsetcols,
+    NULL AS conexclop,
+    c.expression AS conbin
+FROM ` + pgUserObjects("duckdb_constraints()") + ` c
+LEFT JOIN ` + pgUserObjects("duckdb_tables()") + ` r
+    ON r.schema_name = c.schema_name AND r.table_name = c.referenced_table
+LEFT JOIN ` + pgColumnNames + ` rc
+    ON rc.table_oid = r.table_oid
+WHERE c.constraint_type IN ('PRIMARY KEY', 'UNIQUE', 'FOREIGN KEY', 'CHECK');`,
+	},
+	{
+		Schema: "__sys__",
+		Name:   "pg_depend",
+		DDL: `-- A table, view, or sequence depends on its schema
+SELECT 1259 AS classid, oid AS objid, 0 AS objsubid, 2615 AS refclassid, relnamespace AS refobjid, 0 AS refobjsubid, 'n' AS deptype
+FROM __sys__.pg_class
+WHERE oid >= ` + pgOIDOffset + ` AND relkind IN ('r', 'v', 'S')
+UNION ALL
+-- The index of a primary key or unique constraint is a part of the constraint
+SELECT 1259, i.indexrelid, 0, 2606, c.oid, 0, 'i'
+FROM __sys__.pg_index i
+JOIN __sys__.pg_constraint c ON c.conindid = i.indexrelid
+UNION ALL
+-- Any other index depends on the columns of its table
+SELECT 1259, i.indexrelid, 0, 1259, i.indrelid, unnest(i.indkey), 'a'
+FROM __sys__.pg_index i
+WHERE i.indexrelid NOT IN (SELECT conindid FROM __sys__.pg_constraint)
+UNION ALL
+-- A constraint depends on the columns of its table
+SELECT 2606, c.oid, 0, 1259, c.conrelid, unnest(c.conkey), 'a'
+FROM __sys__.pg_constraint c
+UNION ALL
+-- A foreign key depends on the referenced columns
+SELECT 2606, c.oid, 0, 1259, c.confrelid, unnest(c.confkey), 'n'
+FROM __sys__.pg_constraint c
+WHERE c.contype = 'f'
+UNION ALL
+-- A view depends on the relations it references. DuckDB does not record them, so they are the relations
+-- whose names appear in the SQL of the view.
+SELECT 1259, ` + pgOIDOffset + ` + v.view_oid, 0, 1259, r.oid, 0, 'n'
+FROM ` + pgUserObjects("duckdb_views()") + ` v
+JOIN __sys__.pg_class r
+    ON r.oid >= ` + pgOIDOffset + ` AND r.relkind IN ('r', 'v') AND r.oid <> ` + pgOIDOffset + ` + v.view_oid
+    AND list_contains(string_split_regex(lower(v.sql), '[^\w$]+'), lower(r.relname));`,
+	},
+	{
+		Schema: "__sys__",
+		Name:   "pg_description",
+		DDL: `SELECT objoid, classoid, objsubid, description
+FROM (
+    SELECT
+        objoid, classoid, objsubid,
+        CASE
+            WHEN comment LIKE '` + ManagedCommentPrefix + `%'
+            THEN json_extract_string(decode(from_base64(substr(comment, length('` + ManagedCommentPrefix + `') + 1))), '$.text')
+            ELSE comment
+        END AS description                            -- The text of a comment managed by MyDuck Server
+    FROM (
+        SELECT ` + pgOIDOffset + ` + table_oid AS objoid, 1259 AS classoid, 0 AS objsubid, comment
+        FROM ` + pgUserObjects("duckdb_tables()") + `
+        UNION ALL
+        SELECT ` + pgOIDOffset + ` + view_oid, 1259, 0, comment FROM ` + pgUserObjects("duckdb_views()") + `
+        UNION ALL
+        SELECT ` + pgOIDOffset + ` + table_oid, 1259, column_index, comment FROM ` + pgUserObjects("duckdb_columns()") + `
+        UNION ALL
+        SELECT ` + pgOIDOffset + ` + index_oid, 1259, 0, comment FROM ` + pgUserObjects("duckdb_indexes()") + `
+        UNION ALL
+        SELECT ` + pgOIDOffset + ` + sequence_oid, 1259, 0, comment FROM ` + pgUserObjects("duckdb_sequences()") + `
+        UNION ALL
+        SELECT ` + pgOIDOffset + ` + oid, 2615, 0, comment FROM ` + pgUserObjects("duckdb_schemas()") + `
+    )
+)
+WHERE description IS NOT NULL AND description <> '';`,
+	},
+	{
+		Schema: "__sys__",
+		Name:   "pg_prepared_xacts",
+		DDL:    `SELECT xid AS "transaction", gid, prepared, owner, database FROM __sys__.prepared_transaction;`,
+	},
+	{
+		Schema: "__sys__",
+		Name:   "pg_roles",
+		DDL: `SELECT rolname, rolsuper, rolinherit, rolcreaterole, rolcreatedb, rolcanlogin, rolreplication, rolconnlimit,
+    '********' AS rolpassword, rolvaliduntil, rolbypassrls, NULL::TEXT[] AS rolconfig, oid
+FROM __sys__.pg_authid;`,
+	},
+	// The reads of pg_authid are those of this view, which does not show the passwords.
+	{
+		Schema: "__sys__",
+		Name:   "pg_authid_masked",
+		DDL: `SELECT oid, rolname, rolsuper, rolinherit, rolcreaterole, rolcreatedb, rolcanlogin, rolreplication, rolbypassrls,
+    rolconnlimit, NULL::TEXT AS rolpassword, rolvaliduntil
+FROM __sys__.pg_authid;`,
+	},
+	{
+		Schema: "__sys__",
+		Name:   "pg_user",
+		DDL: `SELECT rolname AS usename, oid AS usesysid, rolcreatedb AS usecreatedb, rolsuper AS usesuper,
+    rolreplication AS userepl, rolbypassrls AS usebypassrls, '********' AS passwd, rolvaliduntil AS valuntil,
+    NULL::TEXT[] AS useconfig
+FROM __sys__.pg_authid
+WHERE rolcanlogin;`,
+	},
+	// The databases are the catalogs attached from the data directory, excluding those of the foreign servers.
+	{
+		Schema: "__sys__",
+		Name:   "pg_database",
+		DDL: `SELECT ` + pgOIDOffset + ` + database_oid AS oid, database_name AS datname, 10::BIGINT AS datdba, 6 AS encoding,
+    'c' AS datlocprovider, false AS datistemplate, true AS datallowconn, -1 AS datconnlimit, '0'::TEXT AS datfrozenxid,
+    '1'::TEXT AS datminmxid, 1663::BIGINT AS dattablespace, 'en_US.UTF-8' AS datcollate, 'en_US.UTF-8' AS datctype,
+    NULL::TEXT AS daticulocale, NULL::TEXT AS daticurules, NULL::TEXT AS datcollversion, NULL::TEXT[] AS datacl
+FROM duckdb_databases()
+WHERE NOT internal AND type = 'duckdb' AND (path IS NULL OR ends_with(path, '.db'));`,
+	},
+	{
+		Schema: "__sys__",
+		Name:   "pg_publication_tables",
+		DDL: `SELECT r.pubname, r.schemaname, r.tablename
+FROM __sys__.publication_rel r
+UNION ALL
+SELECT p.pubname, t.schema_name, t.table_name
+FROM __sys__.pg_publication p, ` + pgUserObjects("duckdb_tables()") + ` t
+WHERE p.puballtables AND NOT t.temporary;`,
+	},
+	{
+		Schema: "__sys__",
+		Name:   "pg_replication_slots",
+		DDL: `SELECT slot_name, plugin, 'logical' AS slot_type, NULL::BIGINT AS datoid, database, false AS temporary,
+    active_pid IS NOT NULL AS active, active_pid, NULL::BIGINT AS xmin, NULL::BIGINT AS catalog_xmin,
+    printf('%X/%X', confirmed_flush_lsn >> 32, confirmed_flush_lsn & 4294967295) AS restart_lsn,
+    printf('%X/%X', confirmed_flush_lsn >> 32, confirmed_flush_lsn & 4294967295) AS confirmed_flush_lsn,
+    'reserved' AS wal_status, NULL::BIGINT AS safe_wal_size, false AS two_phase, false AS conflicting
+FROM __sys__.replication_slot;`,
+	},
+	// The foreign-data wrappers implemented by the scanners of DuckDB (see pgserver/foreign_data.go).
+	{
+		Schema: "__sys__",
+		Name:   "pg_foreign_data_wrapper",
+		DDL: `SELECT oid, fdwname, 10::BIGINT AS fdwowner, 0::BIGINT AS fdwhandler, 0::BIGINT AS fdwvalidator,
+    NULL::TEXT[] AS fdwacl, NULL::TEXT[] AS fdwoptions
+FROM (VALUES (13001, 'postgres_fdw'), (13002, 'mysql_fdw'), (13003, 'sqlite_fdw'), (13004, 'duckdb_fdw')) w(oid, fdwname);`,
+	},
+	{
+		Schema: "__sys__",
+		Name:   "pg_foreign_server",
+		DDL: `SELECT s.oid, s.srvname, s.srvowner, w.oid AS srvfdw, NULL::TEXT AS srvtype, NULL::TEXT AS srvversion,
+    NULL::TEXT[] AS srvacl, s.srvoptions
+FROM __sys__.foreign_server s
+LEFT JOIN __sys__.pg_foreign_data_wrapper w ON w.fdwname = s.srvfdw;`,
+	},
+	{
+		Schema: "__sys__",
+		Name:   "pg_foreign_table",
+		DDL: `SELECT ` + pgOIDOffset + ` + v.view_oid AS ftrelid, s.oid AS ftserver, f.ftoptions
+FROM __sys__.foreign_table f
+JOIN __sys__.foreign_server s ON s.srvname = f.srvname
+JOIN ` + pgUserObjects("duckdb_views()") + ` v ON v.schema_name = f.schemaname AND v.view_name = f.tablename;`,
+	},
+	// The views of information_schema with the semantics and the type names of Postgres,
+	// which replace those of DuckDB in the queries over the Postgres protocol.
+	{
+		Schema: "__sys__",
+		Name:   "information_schema_tables",
+		DDL: `SELECT
+    t.database_name AS table_catalog,
+    t.schema_name AS table_schema,
+    t.table_name,
+    CASE WHEN t.temporary THEN 'LOCAL TEMPORARY' ELSE 'BASE TABLE' END AS table_type,
+    NULL AS self_referencing_column_name,
+    NULL AS reference_generation,
+    NULL AS user_defined_type_catalog,
+    NULL AS user_defined_type_schema,
+    NULL AS user_defined_type_name,
+    'YES' AS is_insertable_into,
+    'NO' AS is_typed,
+    CASE WHEN t.temporary THEN 'PRESERVE' END AS commit_action
+FROM ` + pgUserObjects("duckdb_tables()") + ` t
+UNION ALL
+SELECT
+    v.database_name, v.schema_name, v.view_name, 'VIEW', NULL, NULL, NULL, NULL, NULL, 'NO', 'NO', NULL
+FROM ` + pgUserObjects("duckdb_views()") + ` v;`,
+	},
+	{
+		Schema: "__sys__",
+		Name:   "information_schema_columns",
+		DDL: `SELECT
+    c.database_name AS table_catalog,
+    c.schema_name AS table_schema,
+    c.table_name,
+    c.column_name,
+    c.column_index AS ordinal_position,
+    c.column_default,
+    CASE WHEN c.is_nullable THEN 'YES' ELSE 'NO' END AS is_nullable,
+    c.pg_data_type AS data_type,
+    NULL AS character_maximum_length,
+    CASE WHEN c.atttypid = 25 THEN 1073741824 END AS character_octet_length,
+    CASE c.atttypid
+        WHEN 21 THEN 16
+        WHEN 23 THEN 32
+        WHEN 20 THEN 64
+        WHEN 700 THEN 24
+        WHEN 701 THEN 53
+        WHEN 1700 THEN CASE WHEN c.data_type LIKE 'DECIMAL(%)' THEN c.numeric_precision END
+    END AS numeric_precision,
+    CASE WHEN c.atttypid IN (20, 21, 23, 700, 701) THEN 2 WHEN c.atttypid = 1700 THEN 10 END AS numeric_precision_radix,
+    CASE
+        WHEN c.atttypid IN (20, 21, 23) THEN 0
+        WHEN c.atttypid = 1700 AND c.data_type LIKE 'DECIMAL(%)' THEN c.numeric_scale
+    END AS numeric_scale,
+    CASE WHEN c.atttypid = 1082 THEN 0 WHEN c.atttypid IN (1083, 1114, 1184, 1186, 1266) THEN 6 END AS datetime_precision,
+    NULL AS interval_type,
+    NULL AS interval_precision,
+    NULL AS character_set_catalog,
+    NULL AS character_set_schema,
+    NULL AS character_set_name,
+    NULL AS collation_catalog,
+    NULL AS collation_schema,
+    NULL AS collation_name,
+    NULL AS domain_catalog,
+    NULL AS domain_schema,
+    NULL AS domain_name,
+    c.database_name AS udt_catalog,
+    'pg_catalog' AS udt_schema,
+    COALESCE(t.typname, 'text') AS udt_name,
+    NULL AS scope_catalog,
+    NULL AS scope_schema,
+    NULL AS scope_name,
+    NULL AS maximum_cardinality,
+    c.column_index::VARCHAR AS dtd_identifier,
+    'NO' AS is_self_referencing,
+    'NO' AS is_identity,
+    NULL AS identity_generation,
+    NULL AS identity_start,
+    NULL AS identity_increment,
+    NULL AS identity_maximum,
+    NULL AS identity_minimum,
+    'NO' AS identity_cycle,
+    'NEVER' AS is_generated,
+    NULL AS generation_expression,
+    CASE WHEN c.table_oid IN (SELECT view_oid FROM duckdb_views()) THEN 'NO' ELSE 'YES' END AS is_updatable
+FROM (
+    SELECT *, ` + pgTypeOID("data_type") + ` AS atttypid, ` + pgTypeName("data_type") + ` AS pg_data_type
+    FROM ` + pgUserObjects("duckdb_columns()") + `
+) c
+LEFT JOIN __sys__.pg_type t ON t.oid = c.atttypid;`,
+	},
+	{
+		Schema: "__sys__",
+		Name:   "information_schema_table_constraints",
+		DDL: `SELECT
+    c.database_name AS constraint_catalog,
+    c.schema_name AS constraint_schema,
+    c.constraint_name,
+    c.database_name AS table_catalog,
+    c.schema_name AS table_schema,
+    c.table_name,
+    CASE c.constraint_type WHEN 'NOT NULL' THEN 'CHECK' ELSE c.constraint_type END AS constraint_type,
+    'NO' AS is_deferrable,
+    'NO' AS initially_deferred,
+    'YES' AS enforced,
+    CASE WHEN c.constraint_type IN ('PRIMARY KEY', 'UNIQUE') THEN 'YES' END AS nulls_distinct
+FROM ` + pgUserObjects("duckdb_constraints()") + ` c
+WHERE c.constraint_type IN ('PRIMARY KEY', 'UNIQUE', 'FOREIGN KEY', 'CHECK', 'NOT NULL');`,
+	},
+	{
+		Schema: "__sys__",
+		Name:   "information_schema_key_column_usage",
+		DDL: `SELECT
+    constraint_catalog, constraint_schema, constraint_name, table_catalog, table_schema, table_name,
+    column_name, ordinal_position,
+    CASE WHEN constraint_type = 'FOREIGN KEY' THEN ordinal_position END AS position_in_unique_constraint
+FROM (
+    SELECT
+        c.database_name AS constraint_catalog,
+        c.schema_name AS constraint_schema,
+        c.constraint_name,
+        c.database_name AS table_catalog,
+        c.schema_name AS table_schema,
+        c.table_name,
+        c.constraint_type,
+        unnest(c.constraint_column_names) AS column_name,
+        generate_subscripts(c.constraint_column_names, 1) AS ordinal_position
+    FROM ` + pgUserObjects("duckdb_constraints()") + ` c
+    WHERE c.constraint_type IN ('PRIMARY KEY', 'UNIQUE', 'FOREIGN KEY')
+);`,
 	},
 }
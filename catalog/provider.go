@@ -91,7 +91,7 @@ func NewDBProvider(defaultTimeZone, dataDir, defaultDB string) (prov *DatabasePr
 		}
 	}
 
-	err = prov.initCatalog()
+	err = prov.initCatalog(prov.storage)
 	if err != nil {
 		return nil, err
 	}
@@ -105,10 +105,17 @@ func NewDBProvider(defaultTimeZone, dataDir, defaultDB string) (prov *DatabasePr
 	return prov, nil
 }
 
-func (prov *DatabaseProvider) initCatalog() error {
+// catalogExecutor runs the statements that initialize a catalog, on the storage or on a single connection to it.
+type catalogExecutor interface {
+	ExecContext(ctx context.Context, query string, args ...any) (stdsql.Result, error)
+	QueryRowContext(ctx context.Context, query string, args ...any) *stdsql.Row
+}
 
+// initCatalog creates the internal schemas, tables, views, and macros, and the public schema in the current catalog
+// of |q|.
+func (prov *DatabaseProvider) initCatalog(q catalogExecutor) error {
 	for _, t := range internalSchemas {
-		if _, err := prov.storage.ExecContext(
+		if _, err := q.ExecContext(
 			context.Background(),
 			"CREATE SCHEMA IF NOT EXISTS "+t.Schema,
 		); err != nil {
@@ -117,20 +124,20 @@ func (prov *DatabaseProvider) initCatalog() error {
 	}
 
 	for _, t := range internalTables {
-		if _, err := prov.storage.ExecContext(
+		if _, err := q.ExecContext(
 			context.Background(),
 			"CREATE SCHEMA IF NOT EXISTS "+t.Schema,
 		); err != nil {
 			return fmt.Errorf("failed to create internal schema %q: %w", t.Schema, err)
 		}
-		if _, err := prov.storage.ExecContext(
+		if _, err := q.ExecContext(
 			context.Background(),
 			"CREATE TABLE IF NOT EXISTS "+t.QualifiedName()+"("+t.DDL+")",
 		); err != nil {
 			return fmt.Errorf("failed to create internal table %q: %w", t.Name, err)
 		}
 		for _, row := range t.InitialData {
-			if _, err := prov.storage.ExecContext(
+			if _, err := q.ExecContext(
 				context.Background(),
 				t.UpsertStmt(),
 				row...,
@@ -143,7 +150,7 @@ func (prov *DatabaseProvider) initCatalog() error {
 		if initialFileContent != "" {
 			var count int
 			// Count rows in the internal table
-			if err := prov.storage.QueryRow(t.CountAllStmt()).Scan(&count); err != nil {
+			if err := q.QueryRowContext(context.Background(), t.CountAllStmt()).Scan(&count); err != nil {
 				return fmt.Errorf("failed to count rows in internal table %q: %w", t.Name, err)
 			}
 
@@ -167,7 +174,7 @@ func (prov *DatabaseProvider) initCatalog() error {
 				}
 
 				// Execute the COPY command to insert data into the table
-				if _, err := prov.storage.ExecContext(
+				if _, err := q.ExecContext(
 					context.Background(),
 					fmt.Sprintf("COPY %s FROM '%s' (DELIMITER ',', HEADER)", t.QualifiedName(), tmpFile.Name()),
 				); err != nil {
@@ -178,22 +185,39 @@ func (prov *DatabaseProvider) initCatalog() error {
 	}
 
 	for _, v := range InternalViews {
-		if _, err := prov.storage.ExecContext(
+		if _, err := q.ExecContext(
 			context.Background(),
 			"CREATE SCHEMA IF NOT EXISTS "+v.Schema,
 		); err != nil {
 			return fmt.Errorf("failed to create internal schema %q: %w", v.Schema, err)
 		}
-		if _, err := prov.storage.ExecContext(
+		// Some internal views replace the internal tables of the same names in earlier versions.
+		var isTable bool
+		if err := q.QueryRowContext(
+			context.Background(),
+			"SELECT count(*) > 0 FROM duckdb_tables() WHERE database_name = current_database() AND schema_name = ? AND table_name = ?",
+			v.Schema, v.Name,
+		).Scan(&isTable); err != nil {
+			return fmt.Errorf("failed to look up internal view %q: %w", v.Name, err)
+		}
+		if isTable {
+			if _, err := q.ExecContext(
+				context.Background(),
+				"DROP TABLE "+v.QualifiedName(),
+			); err != nil {
+				return fmt.Errorf("failed to drop the table of internal view %q: %w", v.Name, err)
+			}
+		}
+		if _, err := q.ExecContext(
 			context.Background(),
-			"CREATE VIEW IF NOT EXISTS "+v.QualifiedName()+" AS "+v.DDL,
+			"CREATE OR REPLACE VIEW "+v.QualifiedName()+" AS "+v.DDL,
 		); err != nil {
 			return fmt.Errorf("failed to create internal view %q: %w", v.Name, err)
 		}
 	}
 
 	for _, m := range InternalMacros {
-		if _, err := prov.storage.ExecContext(
+		if _, err := q.ExecContext(
 			context.Background(),
 			"CREATE SCHEMA IF NOT EXISTS "+m.Schema,
 		); err != nil {
@@ -210,7 +234,7 @@ func (prov *DatabaseProvider) initCatalog() error {
 			}
 			definitions = append(definitions, fmt.Sprintf("\n(%s) AS %s%s", macroParams, asType, d.DDL))
 		}
-		if _, err := prov.storage.ExecContext(
+		if _, err := q.ExecContext(
 			context.Background(),
 			"CREATE OR REPLACE MACRO "+m.QualifiedName()+strings.Join(definitions, ",")+";",
 		); err != nil {
@@ -231,7 +255,7 @@ func (prov *DatabaseProvider) initCatalog() error {
 
 	// Postgres tables are created in the `public` schema by default.
 	// Create the `public` schema if it doesn't exist.
-	_, err := prov.pool.ExecContext(context.Background(), "CREATE SCHEMA IF NOT EXISTS public")
+	_, err := q.ExecContext(context.Background(), "CREATE SCHEMA IF NOT EXISTS public")
 	if err != nil {
 		logrus.WithError(err).Fatalln("Failed to create the `public` schema")
 	}
@@ -242,6 +266,7 @@ func (prov *DatabaseProvider) IsReady() bool {
 	return prov.ready
 }
 
+// HasCatalog returns whether the database exists, i.e., whether its file is in the data directory.
 func (prov *DatabaseProvider) HasCatalog(name string) bool {
 	name = strings.TrimSpace(name)
 	// in memory database does not need to be created
@@ -250,9 +275,8 @@ func (prov *DatabaseProvider) HasCatalog(name string) bool {
 	}
 
 	dsn := filepath.Join(prov.dataDir, name+".db")
-	// if already exists, return error
 	_, err := os.Stat(dsn)
-	return os.IsExist(err)
+	return err == nil
 }
 
 // attachCatalogs attaches all the databases in the data directory
@@ -293,63 +317,59 @@ func (prov *DatabaseProvider) AttachCatalog(file interface {
 	return nil
 }
 
+// CreateCatalog creates the database as a file in the data directory and attaches it.
 func (prov *DatabaseProvider) CreateCatalog(name string, ifNotExists bool) error {
 	name = strings.TrimSpace(name)
 	// in memory database does not need to be created
 	if name == "" || name == "memory" {
 		return nil
 	}
+	if prov.HasCatalog(name) {
+		if ifNotExists {
+			return nil
+		}
+		return fmt.Errorf("database \"%s\" already exists", name)
+	}
 	dsn := filepath.Join(prov.dataDir, name+".db")
 
-	_, err := os.Stat(dsn)
-	shouldInit := os.IsNotExist(err)
-
-	// attach
-	attachSQL := "ATTACH"
-	if ifNotExists {
-		attachSQL += " IF NOT EXISTS"
-	}
-	attachSQL += " '" + dsn + "' AS " + name
-	_, err = prov.storage.ExecContext(context.Background(), attachSQL)
+	// The new catalog is initialized on a single connection, which is switched to it and back,
+	// since the other connections of the storage stay in the default catalog.
+	ctx := context.Background()
+	conn, err := prov.storage.Conn(ctx)
 	if err != nil {
 		return err
 	}
+	defer conn.Close()
 
-	if shouldInit {
-		res, err := prov.storage.QueryContext(context.Background(), "SELECT current_catalog")
-		if err != nil {
-			return fmt.Errorf("failed to init catalog: %w", err)
-		}
-		lastCatalog := ""
-		for res.Next() {
-			if err := res.Scan(&lastCatalog); err != nil {
-				return fmt.Errorf("failed to init catalog: %w", err)
-			}
-		}
-
-		if _, err := prov.storage.ExecContext(context.Background(), "USE "+name); err != nil {
-			return fmt.Errorf("failed to switch to the new catalog: %w", err)
-		}
+	if _, err := conn.ExecContext(ctx, "ATTACH '"+dsn+"' AS "+name); err != nil {
+		return err
+	}
 
-		defer func() {
-			if _, err := prov.storage.ExecContext(context.Background(), "USE "+lastCatalog); err != nil {
-				logrus.WithError(err).Errorln("Failed to switch back to the old catalog")
-			}
-		}()
-		err = prov.initCatalog()
-		if err != nil {
-			return err
-		}
+	var lastCatalog string
+	if err := conn.QueryRowContext(ctx, "SELECT current_catalog").Scan(&lastCatalog); err != nil {
+		return fmt.Errorf("failed to init catalog: %w", err)
 	}
-	return nil
+	if _, err := conn.ExecContext(ctx, "USE "+name); err != nil {
+		return fmt.Errorf("failed to switch to the new catalog: %w", err)
+	}
+	defer func() {
+		if _, err := conn.ExecContext(ctx, "USE "+lastCatalog); err != nil {
+			logrus.WithError(err).Errorln("Failed to switch back to the old catalog")
+		}
+	}()
+	return prov.initCatalog(conn)
 }
 
+// DropCatalog detaches the database and deletes its file.
 func (prov *DatabaseProvider) DropCatalog(name string, ifExists bool) error {
 	name = strings.TrimSpace(name)
 	// in memory database does not need to be created
 	if name == "" || name == "memory" {
 		return fmt.Errorf("cannot drop the in-memory catalog")
 	}
+	if name == prov.defaultCatalogName {
+		return fmt.Errorf("cannot drop the default database \"%s\"", name)
+	}
 	dsn := filepath.Join(prov.dataDir, name+".db")
 	// if file does not exist, return error
 	_, err := os.Stat(dsn)
@@ -357,7 +377,7 @@ func (prov *DatabaseProvider) DropCatalog(name string, ifExists bool) error {
 		if ifExists {
 			return nil
 		}
-		return fmt.Errorf("database file %s does not exist", dsn)
+		return fmt.Errorf("database \"%s\" does not exist", name)
 	}
 	// detach
 	if _, err := prov.storage.ExecContext(context.Background(), "DETACH "+name); err != nil {
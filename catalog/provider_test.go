@@ -84,15 +84,15 @@ func TestCreateCatalog(t *testing.T) {
 		{
 			name: "drop database",
 			executions: []Execution{
-				//{
-				//	SQL:      "USE testdb1;",
-				//	Expected: "SET",
-				//},
-				//// Can not drop the database when the current database is the one to be dropped
-				//{
-				//	SQL:     "DROP DATABASE testdb1;",
-				//	WantErr: true,
-				//},
+				{
+					SQL:      "USE testdb1;",
+					Expected: "SET",
+				},
+				// Can not drop the database when the current database is the one to be dropped
+				{
+					SQL:     "DROP DATABASE testdb1;",
+					WantErr: true,
+				},
 				{
 					SQL:      "USE testdb2;",
 					Expected: "SET",
@@ -0,0 +1,1106 @@
+// What the MySQL and the PostgreSQL harnesses share with the tests that embed
+// them: a view of the connection of a test that works with either protocol,
+// and the custom assertions run on it. Also what does not depend on the
+// protocol, which both harnesses use: the comparison of expected cells, the
+// parsing of test files and directive values, the reports, the fuzzer, and
+// the helpers of the load, unicode and bulk runs.
+
+use std::collections::HashMap;
+use std::io::{self, IsTerminal};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::process::exit;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+extern crate regex;
+extern crate serde;
+extern crate serde_json;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+// A connection to the server under test, given to custom assertions. Cells
+// are rendered like the expected rows of a test file, with NULL as `\N`.
+pub trait SqlClient {
+    // "mysql" or "pg".
+    fn protocol(&self) -> &'static str;
+
+    // Runs statements that return no rows, such as DDL or SET.
+    fn execute(&mut self, statements: &str) -> Result<(), String>;
+
+    // Runs a query and returns its rows.
+    fn query(&mut self, query: &str) -> Result<Vec<Vec<String>>, String>;
+}
+
+// What a custom assertion sees of a test that met its own expectations.
+pub struct TestCase<'a> {
+    pub name: Option<&'a str>,
+    // The query as it ran, with the variables substituted.
+    pub query: &'a str,
+    pub tags: &'a [String],
+    // The rows of the first result set, rendered like those of `SqlClient`.
+    pub rows: &'a [Vec<String>],
+}
+
+// A check added to every test, for what the directives of the test files do
+// not cover. It runs on the connection of the test after the test passed,
+// and an error fails the test with its message.
+pub trait Assertion: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn check(&self, client: &mut dyn SqlClient, test: &TestCase) -> Result<(), String>;
+}
+
+// How NULL is written in expected results, so that it can be told apart
+// from an empty string.
+pub const NULL: &str = "\\N";
+
+// The kind of the column of a cell, which decides how the cell is compared.
+// Each harness maps the column types of its protocol to these.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CellKind {
+    Integer,
+    // Floating point and decimal numbers.
+    Float,
+    Temporal,
+    Other,
+}
+
+// Compares an expected cell with the rendered value. Besides patterns (see
+// `pattern_matches`), the kind of the column is taken into account: numbers
+// are compared by value (floating point and decimal ones within `epsilon`,
+// relative to their magnitude), and temporal values are compared in a
+// canonical form.
+pub fn cell_matches(expected: &str, actual: &str, kind: CellKind, epsilon: f64) -> bool {
+    if expected == actual {
+        return true;
+    }
+    if expected == NULL || actual == NULL {
+        return false;
+    }
+    if let Some(matched) = pattern_matches(expected, actual) {
+        return matched;
+    }
+    match kind {
+        CellKind::Integer => match (expected.parse::<i128>(), actual.parse::<i128>()) {
+            (Ok(e), Ok(a)) => e == a,
+            _ => numbers_match(expected, actual, 0.0),
+        },
+        CellKind::Float => numbers_match(expected, actual, epsilon),
+        CellKind::Temporal => canonical_temporal(expected) == canonical_temporal(actual),
+        CellKind::Other => false,
+    }
+}
+
+// A cell written as `/regex/` must match the whole value, and a cell
+// containing `*` is a wildcard pattern where `*` stands for any run of
+// characters. Returns None if the cell is not a pattern.
+pub fn pattern_matches(expected: &str, actual: &str) -> Option<bool> {
+    if let Some(pattern) = regex_pattern(expected) {
+        let re = Regex::new(&format!("^(?:{})$", pattern)).ok()?;
+        return Some(re.is_match(actual));
+    }
+    if !expected.contains('*') {
+        return None;
+    }
+    let parts: Vec<&str> = expected.split('*').collect();
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if !actual.starts_with(first) {
+        return Some(false);
+    }
+    let mut rest = &actual[first.len()..];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(k) => rest = &rest[k + part.len()..],
+            None => return Some(false),
+        }
+    }
+    Some(rest.len() >= last.len() && rest.ends_with(last))
+}
+
+// The regex of a cell written as `/regex/`.
+pub fn regex_pattern(cell: &str) -> Option<&str> {
+    if cell.len() >= 2 && cell.starts_with('/') && cell.ends_with('/') {
+        Some(&cell[1..cell.len() - 1])
+    } else {
+        None
+    }
+}
+
+pub fn numbers_match(expected: &str, actual: &str, epsilon: f64) -> bool {
+    match (expected.trim().parse::<f64>(), actual.trim().parse::<f64>()) {
+        (Ok(e), Ok(a)) => e == a || (e - a).abs() <= epsilon * e.abs().max(a.abs()).max(1.0),
+        _ => false,
+    }
+}
+
+// Accepts an ISO 8601 `T` separator and drops trailing zeros from the
+// fractional seconds, so `2024-01-01T10:00:00.500000` and
+// `2024-01-01 10:00:00.5` compare equal.
+pub fn canonical_temporal(value: &str) -> String {
+    let value = value.trim().replacen('T', " ", 1);
+    let dot = match value.find('.') {
+        Some(dot) => dot,
+        None => return value,
+    };
+    let rest = &value[dot + 1..];
+    let digits = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+    let fraction = rest[..digits].trim_end_matches('0');
+    let suffix = &rest[digits..];
+    if fraction.is_empty() {
+        format!("{}{}", &value[..dot], suffix)
+    } else {
+        format!("{}.{}{}", &value[..dot], fraction, suffix)
+    }
+}
+
+// Parses a duration such as `30s`, `500ms` or `2m`.
+pub fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Some(millis) = value.strip_suffix("ms") {
+        return millis.parse().ok().map(Duration::from_millis);
+    }
+    if let Some(seconds) = value.strip_suffix('s') {
+        return seconds.parse().ok().map(Duration::from_secs);
+    }
+    let minutes: u64 = value.strip_suffix('m')?.parse().ok()?;
+    Some(Duration::from_secs(minutes * 60))
+}
+
+// The options of a harness that the helpers below read.
+pub trait HarnessOptions {
+    fn format(&self) -> Format;
+
+    // The command run by `-- restart`, set by `--restart-command`.
+    fn restart_command(&self) -> Option<&str>;
+
+    fn connect_retries(&self) -> u32;
+
+    fn connect_backoff(&self) -> Duration;
+
+    // Progress goes to stdout, unless stdout carries a JUnit or TAP report.
+    fn log(&self, message: &str) {
+        if self.format() == Format::Text {
+            println!("{}", message);
+        } else {
+            eprintln!("{}", message);
+        }
+    }
+}
+
+// What the reports see of a test file: its name and the results of its last
+// run.
+pub trait SuiteResults {
+    fn filename(&self) -> &str;
+
+    fn results(&self) -> &[TestResult];
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Format {
+    Text,
+    Junit,
+    Tap,
+}
+
+// The outcome of a test (or of the setup/teardown statements), collected for
+// the JUnit, TAP and JSON reports.
+pub struct TestResult {
+    pub name: String,
+    // The query of a test, None for the setup and teardown.
+    pub query: Option<String>,
+    pub duration: Duration,
+    pub outcome: Outcome,
+}
+
+pub enum Outcome {
+    Passed,
+    Failed(String),
+    // Not run because an earlier test failed, or filtered out by tag.
+    Skipped,
+}
+
+// The row count and checksum of a result, for `-- rows:` and `-- checksum:`.
+#[derive(Default)]
+pub struct Digest {
+    pub rows: u64,
+    pub checksum: u64,
+}
+
+impl Digest {
+    // Each row is hashed on its own (FNV-1a over the rendered cells) and the
+    // hashes are summed, so the checksum does not depend on the row order.
+    pub fn add(&mut self, cells: &[String]) {
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for cell in cells {
+            for b in cell.bytes().chain(std::iter::once(0)) {
+                hash ^= u64::from(b);
+                hash = hash.wrapping_mul(0x100000001b3);
+            }
+        }
+        self.rows += 1;
+        self.checksum = self.checksum.wrapping_add(hash);
+    }
+
+    pub fn checksum(&self) -> String {
+        format!("{:016x}", self.checksum)
+    }
+}
+
+// A row of a parameter matrix, or `[[test.matrix]]` in a `.toml` file.
+#[derive(Clone, Deserialize, Serialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ParamSet {
+    pub params: Vec<String>,
+    #[serde(rename = "results", default, skip_serializing_if = "Vec::is_empty")]
+    pub expected_results: Vec<Vec<String>>,
+    #[serde(rename = "error", default, skip_serializing_if = "Option::is_none")]
+    pub expected_error: Option<String>,
+}
+
+pub fn is_false(b: &bool) -> bool {
+    !*b
+}
+
+// How long a `-- wait_until` test waits before running its query again.
+pub const WAIT_INTERVAL: Duration = Duration::from_millis(200);
+
+// How long a query cancelled by `-- cancel_after` may take to return when
+// `--timeout` is disabled.
+pub const CANCEL_GRACE: Duration = Duration::from_secs(30);
+
+pub fn column_matches(expected: &str, actual: &str) -> bool {
+    let expected: Vec<&str> = expected.split_whitespace().collect();
+    let mut actual: Vec<&str> = actual.split_whitespace().collect();
+    if expected.len() <= 1 {
+        actual.truncate(1);
+    } else if !expected.iter().any(|word| word.starts_with("charset=")) {
+        actual.retain(|word| !word.starts_with("charset="));
+    }
+    expected == actual
+}
+
+// Compares two JSON documents by value, so that the order of object keys
+// and the whitespace do not matter. False if either side is not JSON.
+pub fn json_matches(expected: &str, actual: &str) -> bool {
+    match (serde_json::from_str::<serde_json::Value>(expected), serde_json::from_str::<serde_json::Value>(actual)) {
+        (Ok(expected), Ok(actual)) => expected == actual,
+        _ => false,
+    }
+}
+
+// Decodes an expected cell written as `0x` followed by pairs of hex digits.
+pub fn hex_literal(cell: &str) -> Option<Vec<u8>> {
+    let digits = cell.strip_prefix("0x").or_else(|| cell.strip_prefix("0X"))?;
+    if digits.len() % 2 != 0 || !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    (0..digits.len()).step_by(2).map(|i| u8::from_str_radix(&digits[i..i + 2], 16).ok()).collect()
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    let mut s = "0x".to_string();
+    for b in bytes {
+        s += &format!("{:02X}", b);
+    }
+    s
+}
+
+// The volatile parts of a plan line, removed by `normalize_plan`: costs and
+// timings, row and width estimates, and the timing lines of EXPLAIN ANALYZE.
+pub const PLAN_VOLATILE: &[&str] = &[
+    r"\((cost|actual)[^)]*\)",
+    r"\b(cost|rows|width|loops)=[0-9.]+",
+    r"(?i)~[0-9,.]+ rows?",
+    r"\bEC: *[0-9]+",
+    r"\b(Planning|Execution) Time: .*",
+];
+
+// The lines of a plan without the volatile details, so that only its shape
+// is compared: pointers become `0x?`, box-drawing characters and the `rows`
+// and `filtered` columns go, whitespace collapses, and empty lines are
+// dropped. Cells spanning several lines are split into them.
+pub fn normalize_plan(columns: &[String], rows: &[Vec<String>]) -> Vec<String> {
+    let volatile: Vec<Regex> = PLAN_VOLATILE.iter().map(|re| Regex::new(re).unwrap()).collect();
+    let pointer = Regex::new("0x[0-9a-fA-F]+").unwrap();
+    let kept: Vec<bool> = columns.iter().map(|c| !matches!(c.split_whitespace().next(), Some("rows" | "filtered"))).collect();
+    let mut lines = Vec::new();
+    for row in rows {
+        let cells: Vec<&str> = row.iter().enumerate().filter(|(j, _)| kept.get(*j) != Some(&false)).map(|(_, c)| c.as_str()).collect();
+        for line in cells.join(" ").lines() {
+            let mut line: String = line.chars().filter(|c| !('\u{2500}'..='\u{257f}').contains(c)).collect();
+            for re in &volatile {
+                line = re.replace_all(&line, "").into_owned();
+            }
+            let line = pointer.replace_all(&line, "0x?").split_whitespace().collect::<Vec<_>>().join(" ");
+            if !line.is_empty() {
+                lines.push(line);
+            }
+        }
+    }
+    lines
+}
+
+// Unchanged lines shown around each change of a diff.
+pub const DIFF_CONTEXT: usize = 3;
+
+// Renders a unified diff from the `expected` rows to the `actual` ones, where
+// `same(i, k)` tells whether expected row `i` matches actual row `k`, so
+// that cells matching by value or pattern are not reported.
+pub fn diff_rows(expected: &[String], actual: &[String], same: impl Fn(usize, usize) -> bool) -> String {
+    let (n, m) = (expected.len(), actual.len());
+    // The length of the longest common subsequence of the suffixes.
+    let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for k in (0..m).rev() {
+            lcs[i][k] = if same(i, k) { lcs[i + 1][k + 1] + 1 } else { lcs[i + 1][k].max(lcs[i][k + 1]) };
+        }
+    }
+    // Each line of the diff is a tag and the next expected and actual rows.
+    let mut lines = Vec::new();
+    let (mut i, mut k) = (0, 0);
+    while i < n || k < m {
+        if i < n && k < m && same(i, k) {
+            lines.push((' ', i, k));
+            i += 1;
+            k += 1;
+        } else if i < n && (k == m || lcs[i + 1][k] >= lcs[i][k + 1]) {
+            lines.push(('-', i, k));
+            i += 1;
+        } else {
+            lines.push(('+', i, k));
+            k += 1;
+        }
+    }
+    let changed = |l: usize| lines[l].0 != ' ';
+    let mut out = "--- expected\n+++ actual".to_string();
+    let mut start = 0;
+    while let Some(first) = (start..lines.len()).find(|&l| changed(l)) {
+        let mut last = first;
+        while let Some(next) = (last + 1..lines.len()).find(|&l| changed(l)) {
+            if next - last > 2 * DIFF_CONTEXT {
+                break;
+            }
+            last = next;
+        }
+        let hunk = &lines[first.saturating_sub(DIFF_CONTEXT).max(start)..(last + DIFF_CONTEXT + 1).min(lines.len())];
+        let old = hunk.iter().filter(|line| line.0 != '+').count();
+        let new = hunk.iter().filter(|line| line.0 != '-').count();
+        out += &format!("\n@@ -{},{} +{},{} @@", hunk[0].1 + 1, old, hunk[0].2 + 1, new);
+        for &(tag, i, k) in hunk {
+            let row = if tag == '+' { &actual[k] } else { &expected[i] };
+            out += &format!("\n{}{}", tag, row);
+        }
+        start = (last + DIFF_CONTEXT + 1).min(lines.len());
+    }
+    out
+}
+
+// Prints a failure on stderr. When stderr is a terminal and NO_COLOR is not
+// set, the removed, added and hunk lines of a diff are colored.
+pub fn print_failure(failure: &str) {
+    if !io::stderr().is_terminal() || std::env::var_os("NO_COLOR").is_some() {
+        eprintln!("{}", failure);
+        return;
+    }
+    let mut diff = false;
+    for line in failure.lines() {
+        diff = diff || line == "--- expected";
+        let color = match line.chars().next() {
+            Some('-') if diff => "31",
+            Some('+') if diff => "32",
+            Some('@') if diff => "36",
+            _ => {
+                eprintln!("{}", line);
+                continue;
+            }
+        };
+        eprintln!("\x1b[{}m{}\x1b[0m", color, line);
+    }
+}
+
+// A database name unique to the test file and to this run, so that
+// concurrent runs against the same server do not share tables.
+pub fn isolated_database(filename: &str) -> String {
+    let stem = Path::new(filename).file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    let stem: String = stem.chars().map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' }).collect();
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos();
+    let mut name = format!("test_{:x}_{:x}_{}", std::process::id(), nanos, stem);
+    // The longest identifier PostgreSQL accepts, and one less than MySQL.
+    name.truncate(63);
+    name
+}
+
+// Forwards a single connection to the server, so that the harness can cut it
+// like a crashed client or a network failure would, without the goodbye
+// message of the protocol. Used by `-- disconnect`.
+pub struct Proxy {
+    pub port: u16,
+    pub streams: Arc<Mutex<Vec<TcpStream>>>,
+}
+
+impl Proxy {
+    pub fn start(host: &str, port: u16) -> io::Result<Proxy> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let upstream = TcpStream::connect((host, port))?;
+        let streams = Arc::new(Mutex::new(vec![upstream.try_clone()?]));
+        let proxy = Proxy { port: listener.local_addr()?.port(), streams: streams.clone() };
+        thread::spawn(move || {
+            let client = match listener.accept() {
+                Ok((client, _)) => client,
+                Err(_) => return,
+            };
+            let clones = (client.try_clone(), client.try_clone(), upstream.try_clone());
+            if let (Ok(kept), Ok(reader), Ok(writer)) = clones {
+                streams.lock().unwrap().push(kept);
+                thread::spawn(move || forward(reader, writer));
+                forward(upstream, client);
+            }
+        });
+        Ok(proxy)
+    }
+
+    // Closes both sides: the server sees the connection drop and the client
+    // gets an error on its next query.
+    pub fn cut(&self) {
+        for stream in self.streams.lock().unwrap().iter() {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+    }
+}
+
+// Copies `from` into `to` until either side closes.
+pub fn forward(mut from: TcpStream, mut to: TcpStream) {
+    let _ = io::copy(&mut from, &mut to);
+    let _ = to.shutdown(Shutdown::Write);
+}
+
+// Runs `--restart-command` for `-- restart`. It must return once the server
+// went down or restarted, after which the harness waits for it.
+pub fn restart_server(options: &impl HarnessOptions) -> Result<(), String> {
+    let command = options.restart_command().ok_or("-- restart needs --restart-command <command>")?;
+    options.log(&format!("Restarting the server: {}", command));
+    let status = std::process::Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .status()
+        .map_err(|e| format!("Cannot run the restart command: {}", e))?;
+    if !status.success() {
+        return Err(format!("The restart command failed: {}", status));
+    }
+    Ok(())
+}
+
+// Calls `connect` until it succeeds or `--connect-retries` retries failed,
+// waiting `--connect-backoff` before the first retry and twice as long as
+// the previous wait before each of the following ones. Lets the harness
+// start before the server accepts connections.
+pub fn connect_with_retries<T, E>(options: &impl HarnessOptions, mut connect: impl FnMut() -> Result<T, E>) -> Result<T, E> {
+    let mut backoff = options.connect_backoff();
+    let mut retries = 0;
+    loop {
+        match connect() {
+            Err(_) if retries < options.connect_retries() => {
+                options.log(&format!("Cannot connect to the server, retrying in {:?}", backoff));
+                thread::sleep(backoff);
+                backoff *= 2;
+                retries += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+// Runs `query` while a watchdog thread calls `cancel` once `after` has
+// passed, and returns whether the query was cancelled. A query that still
+// does not return `grace` after being cancelled means the server is hung, so
+// the harness gives up instead of waiting forever.
+pub fn with_cancel<T>(after: Duration, grace: Duration, cancel: impl FnOnce() + Send, query: impl FnOnce() -> T) -> (T, bool) {
+    let (done, finished) = mpsc::channel::<()>();
+    thread::scope(|scope| {
+        let watchdog = scope.spawn(move || {
+            if finished.recv_timeout(after) != Err(RecvTimeoutError::Timeout) {
+                return false;
+            }
+            cancel();
+            if finished.recv_timeout(grace) == Err(RecvTimeoutError::Timeout) {
+                eprintln!("Query still running {:?} after being cancelled, giving up", grace);
+                exit(1);
+            }
+            true
+        });
+        let result = query();
+        drop(done);
+        (result, watchdog.join().unwrap())
+    })
+}
+
+pub fn junit_report<S: SuiteResults>(suites: &[S]) -> String {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites>\n");
+    for suite in suites {
+        let results = suite.results();
+        let count = |f: fn(&Outcome) -> bool| results.iter().filter(|r| f(&r.outcome)).count();
+        let failures = count(|o| matches!(o, Outcome::Failed(_)));
+        let skipped = count(|o| matches!(o, Outcome::Skipped));
+        let total: Duration = results.iter().map(|r| r.duration).sum();
+        xml += &format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" skipped=\"{}\" time=\"{:.3}\">\n",
+            xml_escape(suite.filename()),
+            results.len(),
+            failures,
+            skipped,
+            total.as_secs_f64()
+        );
+        for result in results {
+            xml += &format!(
+                "    <testcase name=\"{}\" classname=\"{}\" time=\"{:.3}\"",
+                xml_escape(&result.name),
+                xml_escape(suite.filename()),
+                result.duration.as_secs_f64()
+            );
+            match &result.outcome {
+                Outcome::Passed => xml += "/>\n",
+                Outcome::Failed(message) => {
+                    let summary = message.lines().next().unwrap_or_default();
+                    xml += &format!(
+                        ">\n      <failure message=\"{}\">{}</failure>\n    </testcase>\n",
+                        xml_escape(summary),
+                        xml_escape(message)
+                    );
+                }
+                Outcome::Skipped => xml += ">\n      <skipped/>\n    </testcase>\n",
+            }
+        }
+        xml += "  </testsuite>\n";
+    }
+    xml += "</testsuites>\n";
+    xml
+}
+
+pub fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+// The summary printed by `--keep-going`: the outcome and the duration of
+// every test, followed by the totals.
+pub fn summary_report<S: SuiteResults>(suites: &[S]) -> String {
+    let (mut passed, mut failed, mut skipped) = (0, 0, 0);
+    let mut summary = "\nSummary:\n".to_string();
+    for suite in suites {
+        for result in suite.results() {
+            let outcome = match result.outcome {
+                Outcome::Passed => {
+                    passed += 1;
+                    "PASS"
+                }
+                Outcome::Failed(_) => {
+                    failed += 1;
+                    "FAIL"
+                }
+                Outcome::Skipped => {
+                    skipped += 1;
+                    "SKIP"
+                }
+            };
+            let time = result.duration.as_secs_f64() * 1000.0;
+            let name = result.name.replace('\n', " ");
+            summary += &format!("{} {:>10.3}ms  {}: {}\n", outcome, time, suite.filename(), name);
+        }
+    }
+    summary += &format!("{} passed, {} failed, {} skipped\n", passed, failed, skipped);
+    summary
+}
+
+// The results written by `--output`: the outcome, duration and failure of
+// every test, by file. The diff of a failure, if any, is also given apart.
+pub fn json_report<S: SuiteResults>(suites: &[S]) -> String {
+    let files: Vec<serde_json::Value> = suites
+        .iter()
+        .map(|suite| {
+            let tests: Vec<serde_json::Value> = suite
+                .results()
+                .iter()
+                .map(|result| {
+                    let (status, message) = match &result.outcome {
+                        Outcome::Passed => ("passed", None),
+                        Outcome::Failed(message) => ("failed", Some(message.as_str())),
+                        Outcome::Skipped => ("skipped", None),
+                    };
+                    let diff = message.and_then(|message| message.find("--- expected").map(|i| &message[i..]));
+                    serde_json::json!({
+                        "name": result.name,
+                        "query": result.query,
+                        "status": status,
+                        "duration_ms": result.duration.as_secs_f64() * 1000.0,
+                        "message": message,
+                        "diff": diff,
+                    })
+                })
+                .collect();
+            serde_json::json!({ "file": suite.filename(), "tests": tests })
+        })
+        .collect();
+    let count = |status: fn(&Outcome) -> bool| suites.iter().flat_map(|suite| suite.results()).filter(|r| status(&r.outcome)).count();
+    let report = serde_json::json!({
+        "passed": count(|o| matches!(o, Outcome::Passed)),
+        "failed": count(|o| matches!(o, Outcome::Failed(_))),
+        "skipped": count(|o| matches!(o, Outcome::Skipped)),
+        "files": files,
+    });
+    format!("{:#}\n", report)
+}
+
+pub fn tap_report<S: SuiteResults>(suites: &[S]) -> String {
+    let total: usize = suites.iter().map(|suite| suite.results().len()).sum();
+    let mut tap = format!("TAP version 13\n1..{}\n", total);
+    let mut i = 0;
+    for suite in suites {
+        tap += &format!("# {}\n", suite.filename());
+        for result in suite.results() {
+            i += 1;
+            // `#` starts a directive in TAP, and a test line cannot span lines.
+            let name = result.name.replace('\n', " ").replace('#', "\\#");
+            let time = result.duration.as_secs_f64() * 1000.0;
+            match &result.outcome {
+                Outcome::Passed => tap += &format!("ok {} - {} # time={:.3}ms\n", i, name, time),
+                Outcome::Failed(message) => {
+                    tap += &format!("not ok {} - {} # time={:.3}ms\n  ---\n  message: |\n", i, name, time);
+                    for line in message.lines() {
+                        tap += &format!("    {}\n", line);
+                    }
+                    tap += "  ...\n";
+                }
+                Outcome::Skipped => tap += &format!("ok {} - {} # SKIP\n", i, name),
+            }
+        }
+    }
+    tap
+}
+
+// Expands directories into the test files they contain, in name order.
+pub fn discover_test_files(path: &str) -> io::Result<Vec<String>> {
+    if !Path::new(path).is_dir() {
+        return Ok(vec![path.to_string()]);
+    }
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(path)? {
+        let path = entry?.path();
+        let name = path.to_string_lossy().into_owned();
+        if path.is_dir() {
+            files.extend(discover_test_files(&name)?);
+        } else if name.ends_with(".data") || name.ends_with(".toml") {
+            files.push(name);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+// Reads the statements of an SQL file, each ending with a `;` at the end of
+// a line. Comment lines are skipped.
+pub fn read_fixture(path: &Path) -> io::Result<Vec<String>> {
+    let content = std::fs::read_to_string(path).map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))?;
+    let mut statements = Vec::new();
+    let mut statement = String::new();
+    let mut delimiter = ";".to_string();
+    for line in content.lines() {
+        if line.trim().is_empty() || line.trim_start().starts_with("--") {
+            continue;
+        }
+        if let Some(new) = parse_delimiter(line).filter(|_| statement.is_empty()) {
+            delimiter = new;
+            continue;
+        }
+        if !statement.is_empty() {
+            statement.push('\n');
+        }
+        statement.push_str(line);
+        if let Some(complete) = statement.trim_end().strip_suffix(delimiter.as_str()) {
+            statements.push(complete.trim().to_string());
+            statement.clear();
+        }
+    }
+    if !statement.trim().is_empty() {
+        statements.push(statement.trim().to_string());
+    }
+    Ok(statements)
+}
+
+// `DELIMITER <delimiter>` on a line of its own, as in the mysql client.
+pub fn parse_delimiter(line: &str) -> Option<String> {
+    let (keyword, delimiter) = line.trim().split_once(char::is_whitespace)?;
+    let delimiter = delimiter.trim();
+    (keyword.eq_ignore_ascii_case("DELIMITER") && !delimiter.is_empty() && !delimiter.contains(char::is_whitespace))
+        .then(|| delimiter.to_string())
+}
+
+// Reads a statement of a `.sql` file from its first line up to the line
+// ending with the delimiter, which is dropped. Blank lines are part of it.
+pub fn read_statement(first: String, lines: &mut impl Iterator<Item = io::Result<String>>, delimiter: &str) -> io::Result<String> {
+    let mut statement = first;
+    loop {
+        if let Some(complete) = statement.trim_end().strip_suffix(delimiter) {
+            return Ok(complete.trim_end().to_string());
+        }
+        match lines.next() {
+            Some(line) => {
+                statement.push('\n');
+                statement.push_str(&line?);
+            }
+            None => {
+                let message = format!("A statement without its delimiter {}: {}", delimiter, statement);
+                return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+            }
+        }
+    }
+}
+
+// Parses `$name = [row][column]`.
+pub fn parse_capture(capture: &str) -> Option<(&str, usize, usize)> {
+    let (name, cell) = capture.split_once('=')?;
+    let name = name.trim().strip_prefix('$')?;
+    let (i, j) = cell.trim().strip_prefix('[')?.strip_suffix(']')?.split_once("][")?;
+    Some((name, i.trim().parse().ok()?, j.trim().parse().ok()?))
+}
+
+// Replaces each `$name` or `${name}` by the value of the variable. `${NAME}`
+// also falls back to the environment. Unknown names are left alone, like
+// `$1` placeholders and `$$` quotes.
+pub fn substitute(text: &str, variables: &HashMap<String, String>) -> String {
+    if !text.contains('$') {
+        return text.to_string();
+    }
+    let re = Regex::new(r"\$\{([A-Za-z_][A-Za-z0-9_]*)\}|\$([A-Za-z_][A-Za-z0-9_]*)").unwrap();
+    re.replace_all(text, |caps: &regex::Captures| {
+        let value = match (caps.get(1), caps.get(2)) {
+            (Some(name), _) => variables.get(name.as_str()).cloned().or_else(|| std::env::var(name.as_str()).ok()),
+            (_, Some(name)) => variables.get(name.as_str()).cloned(),
+            _ => None,
+        };
+        value.unwrap_or_else(|| caps[0].to_string())
+    })
+    .into_owned()
+}
+
+// The nearest-rank percentile `p` of sorted, non-empty `samples`.
+pub fn percentile(samples: &[Duration], p: usize) -> Duration {
+    samples[(samples.len() * p).div_ceil(100).saturating_sub(1)]
+}
+
+pub fn parse_count(value: &str) -> io::Result<u64> {
+    value.trim().parse().map_err(|_| io::Error::new(io::ErrorKind::InvalidData, format!("Invalid number: {}", value.trim())))
+}
+
+// Parses the `i/n` of `--shard`.
+pub fn parse_shard(shard: &str) -> Option<(usize, usize)> {
+    let (i, n) = shard.split_once('/')?;
+    let (i, n) = (i.trim().parse().ok()?, n.trim().parse().ok()?);
+    if i == 0 || i > n {
+        return None;
+    }
+    Some((i, n))
+}
+
+pub fn parse_list(list: &str) -> Vec<String> {
+    list.split(',').map(str::trim).filter(|item| !item.is_empty()).map(String::from).collect()
+}
+
+// Parses a transaction step: `begin` with an optional isolation level,
+// `commit` or `rollback`. Returns the keyword and the level, in upper case.
+pub fn parse_transaction(step: &str) -> Option<(String, Option<String>)> {
+    let words: Vec<String> = step.split_whitespace().map(|w| w.to_ascii_uppercase()).collect();
+    let (kind, level) = words.split_first()?;
+    match (kind.as_str(), level.join(" ")) {
+        ("BEGIN" | "COMMIT" | "ROLLBACK", level) if level.is_empty() => Some((kind.clone(), None)),
+        ("BEGIN", level) if ISOLATION_LEVELS.contains(&level.as_str()) => Some((kind.clone(), Some(level))),
+        _ => None,
+    }
+}
+
+pub const ISOLATION_LEVELS: [&str; 4] = ["READ UNCOMMITTED", "READ COMMITTED", "REPEATABLE READ", "SERIALIZABLE"];
+
+pub fn format_step_line(step: &str, connection: Option<&str>) -> String {
+    let (kind, level) = step.split_once(' ').unwrap_or((step, ""));
+    format!("-- {}: {} {}", kind, connection.unwrap_or("default"), level).trim_end().to_string()
+}
+
+// A xorshift64* generator, so that a fuzzing run is reproduced by its seed.
+pub struct Rng(pub u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // The state must never be 0.
+        Rng(seed.max(1))
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545f4914f6cdd1d)
+    }
+
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    pub fn chance(&mut self, percent: u64) -> bool {
+        self.next_u64() % 100 < percent
+    }
+
+    pub fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[self.below(items.len())]
+    }
+}
+
+// A table to run the generated statements on, with its name and the names
+// of its columns, already quoted.
+pub struct FuzzTable {
+    pub name: String,
+    pub columns: Vec<String>,
+}
+
+pub const FUZZ_LITERALS: &[&str] = &[
+    "0",
+    "1",
+    "-1",
+    "2147483647",
+    "-2147483648",
+    "9223372036854775807",
+    "-9223372036854775808",
+    "0.5",
+    "-0.0",
+    "1e308",
+    "''",
+    "'a'",
+    "'abc'",
+    "'%'",
+    "'é'",
+    "NULL",
+    "TRUE",
+    "FALSE",
+];
+
+pub const FUZZ_OPERATORS: &[&str] = &["+", "-", "*", "/", "%", "=", "<>", "<", "<=", ">", ">=", "AND", "OR"];
+
+pub const FUZZ_FUNCTIONS: &[(&str, usize)] = &[
+    ("ABS", 1),
+    ("UPPER", 1),
+    ("LOWER", 1),
+    ("LENGTH", 1),
+    ("ROUND", 1),
+    ("COALESCE", 2),
+    ("NULLIF", 2),
+    ("CONCAT", 2),
+    ("GREATEST", 2),
+    ("SUBSTRING", 2),
+];
+
+pub const FUZZ_AGGREGATES: &[&str] = &["COUNT", "SUM", "MIN", "MAX", "AVG"];
+
+// Generates random SELECT statements from a small grammar over `tables`.
+// They are valid syntax, but may well fail, e.g. on mismatched types. Only
+// queries are generated, so fuzzing never changes the data.
+pub struct Fuzzer {
+    pub rng: Rng,
+    pub tables: Vec<FuzzTable>,
+    // The types of the CAST expressions, as the protocol writes them.
+    pub casts: &'static [&'static str],
+}
+
+impl Fuzzer {
+    pub fn statement(&mut self) -> String {
+        if self.tables.is_empty() || self.rng.chance(20) {
+            let exprs: Vec<String> = (0..1 + self.rng.below(3)).map(|_| self.expr(&[], 0)).collect();
+            return format!("SELECT {}", exprs.join(", "));
+        }
+        let table = self.rng.below(self.tables.len());
+        let columns = self.tables[table].columns.clone();
+        let mut sql = if self.rng.chance(30) {
+            let aggregate = self.rng.pick(FUZZ_AGGREGATES);
+            let arg = if aggregate == "COUNT" && self.rng.chance(50) { "*".to_string() } else { self.expr(&columns, 1) };
+            format!("SELECT {}({})", aggregate, arg)
+        } else {
+            let distinct = if self.rng.chance(10) { "DISTINCT " } else { "" };
+            let exprs: Vec<String> = (0..1 + self.rng.below(3)).map(|_| self.expr(&columns, 0)).collect();
+            format!("SELECT {}{}", distinct, exprs.join(", "))
+        };
+        sql += &format!(" FROM {}", self.tables[table].name);
+        if self.rng.chance(60) {
+            sql += &format!(" WHERE {}", self.predicate(&columns, 0));
+        }
+        if self.rng.chance(20) {
+            sql += &format!(" GROUP BY {}", columns[self.rng.below(columns.len())]);
+        }
+        if self.rng.chance(30) {
+            sql += if self.rng.chance(50) { " ORDER BY 1 DESC" } else { " ORDER BY 1" };
+        }
+        if self.rng.chance(50) {
+            sql += &format!(" LIMIT {}", self.rng.below(100));
+        }
+        sql
+    }
+
+    pub fn expr(&mut self, columns: &[String], depth: usize) -> String {
+        if depth >= 3 || self.rng.chance(40) {
+            if !columns.is_empty() && self.rng.chance(50) {
+                return columns[self.rng.below(columns.len())].clone();
+            }
+            return self.rng.pick(FUZZ_LITERALS).to_string();
+        }
+        match self.rng.below(4) {
+            0 => {
+                let operator = self.rng.pick(FUZZ_OPERATORS);
+                format!("({} {} {})", self.expr(columns, depth + 1), operator, self.expr(columns, depth + 1))
+            }
+            1 => {
+                let (name, arity) = FUZZ_FUNCTIONS[self.rng.below(FUZZ_FUNCTIONS.len())];
+                let args: Vec<String> = (0..arity).map(|_| self.expr(columns, depth + 1)).collect();
+                format!("{}({})", name, args.join(", "))
+            }
+            2 => {
+                let expr = self.expr(columns, depth + 1);
+                format!("CAST({} AS {})", expr, self.rng.pick(self.casts))
+            }
+            _ => {
+                let predicate = self.predicate(columns, depth + 1);
+                format!("CASE WHEN {} THEN {} ELSE {} END", predicate, self.expr(columns, depth + 1), self.expr(columns, depth + 1))
+            }
+        }
+    }
+
+    pub fn predicate(&mut self, columns: &[String], depth: usize) -> String {
+        let expr = self.expr(columns, depth + 1);
+        match self.rng.below(5) {
+            0 => format!("{} IS NULL", expr),
+            1 => format!("{} IS NOT NULL", expr),
+            2 => format!("{} BETWEEN {} AND {}", expr, self.rng.pick(FUZZ_LITERALS), self.rng.pick(FUZZ_LITERALS)),
+            3 => format!("{} LIKE {}", expr, self.rng.pick(&["'a%'", "'%'", "'_'", "''"])),
+            _ => {
+                let operator = self.rng.pick(&["=", "<>", "<", ">", "AND", "OR"]);
+                format!("({} {} {})", expr, operator, self.expr(columns, depth + 1))
+            }
+        }
+    }
+}
+
+// The options of `fuzz`, following the address of the server.
+pub struct FuzzOptions<O> {
+    pub iterations: usize,
+    pub seed: u64,
+    // Where reproducers are written.
+    pub out: PathBuf,
+    pub options: O,
+}
+
+// The options of `load`, following the address of the server.
+pub struct LoadOptions<O> {
+    pub threads: usize,
+    pub duration: Duration,
+    // The share of point selects, in percent, the rest being updates.
+    pub read_percent: u64,
+    pub table_size: u32,
+    pub options: O,
+}
+
+// What the workers of `load` did.
+#[derive(Default)]
+pub struct LoadStats {
+    pub reads: u64,
+    pub writes: u64,
+    pub errors: u64,
+    pub latencies: Vec<Duration>,
+}
+
+impl LoadStats {
+    pub fn merge(&mut self, other: LoadStats) {
+        self.reads += other.reads;
+        self.writes += other.writes;
+        self.errors += other.errors;
+        self.latencies.extend(other.latencies);
+    }
+
+    pub fn report(&mut self, elapsed: Duration) -> String {
+        if self.latencies.is_empty() {
+            return format!("No query succeeded, {} errors", self.errors);
+        }
+        self.latencies.sort();
+        let queries = self.reads + self.writes;
+        format!(
+            "{} queries in {:.1}s: {:.1} queries/s ({} reads, {} writes), {} errors, latency p50 {:?}, p95 {:?}",
+            queries,
+            elapsed.as_secs_f64(),
+            queries as f64 / elapsed.as_secs_f64(),
+            self.reads,
+            self.writes,
+            self.errors,
+            percentile(&self.latencies, 50),
+            percentile(&self.latencies, 95)
+        )
+    }
+}
+
+// The rows of the load table, inserted in batches of 1000.
+pub fn load_rows(table_size: u32) -> Vec<String> {
+    let rows: Vec<String> = (1..=table_size).map(|id| format!("({}, {}, 'c-{}', 'pad-{}')", id, id % 1000, id, id)).collect();
+    rows.chunks(1000).map(|chunk| chunk.join(", ")).collect()
+}
+
+// The strings of `unicode`, by what they exercise: multi-byte sequences of
+// each length, code points next to the surrogate range and at the ends of
+// the planes, and pairs that Unicode normalization would merge.
+pub const UNICODE_STRINGS: &[(&str, &str)] = &[
+    ("two-byte", "h\u{e9}llo w\u{f6}rld"),
+    ("three-byte", "\u{6f22}\u{5b57}\u{304b}\u{306a}\u{30ab}\u{30ca}"),
+    ("four-byte emoji", "\u{1f600}\u{1f389}"),
+    ("emoji ZWJ sequence", "\u{1f469}\u{200d}\u{1f469}\u{200d}\u{1f467}"),
+    ("flags", "\u{1f1e8}\u{1f1f3}\u{1f1fa}\u{1f1f8}"),
+    ("skin tone modifier", "\u{1f44d}\u{1f3fd}"),
+    ("precomposed", "\u{e9}"),
+    ("combining", "e\u{301}"),
+    ("Hangul syllable", "\u{d55c}"),
+    ("Hangul jamo", "\u{1112}\u{1161}\u{11ab}"),
+    ("Angstrom sign", "\u{212b}"),
+    ("A with ring", "\u{c5}"),
+    ("ligature", "\u{fb01}"),
+    ("last before surrogates", "\u{d7ff}"),
+    ("first after surrogates", "\u{e000}"),
+    ("replacement character", "\u{fffd}"),
+    ("last of the BMP", "\u{ffff}"),
+    ("first supplementary", "\u{10000}"),
+    ("last code point", "\u{10ffff}"),
+    ("right-to-left", "\u{5e9}\u{5dc}\u{5d5}\u{5dd} \u{645}\u{631}\u{62d}\u{628}\u{627}"),
+    ("zero-width", "a\u{200b}b\u{200d}c\u{feff}"),
+    ("mixed", "a\u{e9}\u{6f22}\u{1f600}"),
+];
+
+// The UTF-8 bytes of `s`, as the server shows them in hex.
+pub fn unicode_hex(s: &str) -> String {
+    s.bytes().map(|b| format!("{:02X}", b)).collect()
+}
+
+// The characters of the `bulk` strings, with quotes, a backslash and
+// multi-byte ones to exercise the escaping and the encoding.
+pub const BULK_CHARS: &[char] = &['a', 'z', 'A', 'Z', '0', '9', '_', '\'', '\\', '%', '\u{e9}', '\u{6f22}', '\u{1f600}'];
+
+// Writes a number of ten-thousandths as a decimal(18, 4).
+pub fn format_units(units: i128) -> String {
+    let sign = if units < 0 { "-" } else { "" };
+    format!("{}{}.{:04}", sign, units.abs() / 10000, units.abs() % 10000)
+}
+
+// The options of `bulk`, following the address of the server.
+pub struct BulkOptions<O> {
+    pub rows: u64,
+    // The rows of each INSERT statement.
+    pub batch: usize,
+    pub seed: u64,
+    pub options: O,
+}
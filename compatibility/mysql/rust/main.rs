@@ -0,0 +1,410 @@
+extern crate clap;
+extern crate mysql;
+extern crate mysql_test;
+
+use std::path::PathBuf;
+use std::process::exit;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use clap::{Args, Parser};
+use mysql::Compression;
+use mysql_test::{BulkOptions, Format, FuzzOptions, LoadOptions, LongDataOptions, Options, ReplicateOptions, SslMode, UnicodeOptions};
+
+// The subcommands, recognized by the first argument. Anything else runs test
+// files, keeping `mysql_test <ip> <port> <user> <password> <testFile>...`.
+const SUBCOMMANDS: &[&str] = &["convert", "fuzz", "load", "bulk", "replicate", "unicode", "long-data"];
+
+/// Runs test files against a MySQL-compatible server.
+#[derive(Parser)]
+#[command(name = "mysql_test", after_help = "Other commands: mysql_test <convert|fuzz|load|bulk|replicate|unicode|long-data> --help")]
+struct Run {
+    #[command(flatten)]
+    server: Server,
+    /// Test files, or directories searched for them
+    #[arg(required = true)]
+    files: Vec<String>,
+    /// Run every query through the binary protocol
+    #[arg(long)]
+    prepared: bool,
+    /// Allowed relative difference between FLOAT/DOUBLE/DECIMAL values
+    #[arg(long, default_value_t = 1e-6)]
+    epsilon: f64,
+    /// Replace the expectations of failing tests with the actual results
+    #[arg(long)]
+    record: bool,
+    /// Run every test after a failure, and print a summary
+    #[arg(long)]
+    keep_going: bool,
+    /// Also write the results there as JSON
+    #[arg(long, value_name = "FILE")]
+    output: Option<String>,
+    /// Only run the i-th of every n test files, 1-based
+    #[arg(long, value_name = "I/N", value_parser = shard)]
+    shard: Option<(usize, usize)>,
+    /// How the results are reported: text, junit or tap
+    #[arg(long, default_value = "text", value_parser = format)]
+    format: Format,
+    /// Number of test files run concurrently
+    #[arg(long, default_value_t = 1)]
+    jobs: usize,
+    /// Run each test file in a database of its own
+    #[arg(long)]
+    isolate: bool,
+    /// Run every passing query returning rows N more times
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    bench: usize,
+    /// Run every passing query returning rows again with a cursor, fetching N rows at a time
+    #[arg(long, value_name = "ROWS", value_parser = clap::value_parser!(u64).range(1..))]
+    cursor_fetch: Option<u64>,
+    #[command(flatten)]
+    timeout: Timeout,
+    /// Fail the tests not started after this many seconds
+    #[arg(long, value_name = "SECONDS")]
+    deadline: Option<u64>,
+    /// How many times connecting to the server is retried
+    #[arg(long, default_value_t = 0)]
+    connect_retries: u32,
+    /// The wait before the first retry, in seconds or as a duration such as 500ms
+    #[arg(long, default_value = "1", value_parser = duration)]
+    connect_backoff: Duration,
+    /// Connect through this Unix domain socket
+    #[arg(long, value_name = "PATH")]
+    socket: Option<String>,
+    /// Run through sh -c by -- restart to restart the server
+    #[arg(long, value_name = "COMMAND")]
+    restart_command: Option<String>,
+    /// How long the server may take to come back after a restart
+    #[arg(long, default_value = "60", value_parser = duration)]
+    restart_timeout: Duration,
+    /// Compress the packets with zlib: zlib, fast, best or a level from 0 to 9
+    #[arg(long, value_name = "LEVEL", value_parser = compression)]
+    compress: Option<Compression>,
+    /// Run SET NAMES with this character set on every connection
+    #[arg(long, value_name = "NAME")]
+    charset: Option<String>,
+    /// DISABLED, REQUIRED, VERIFY_CA or VERIFY_IDENTITY
+    #[arg(long, default_value = "DISABLED", value_parser = ssl_mode)]
+    ssl_mode: SslMode,
+    /// The CA verifying the server certificate
+    #[arg(long, value_name = "FILE")]
+    ssl_ca: Option<PathBuf>,
+    /// The client certificate, given with --ssl-key
+    #[arg(long, value_name = "FILE", requires = "ssl_key")]
+    ssl_cert: Option<PathBuf>,
+    /// The key of the client certificate
+    #[arg(long, value_name = "FILE", requires = "ssl_cert")]
+    ssl_key: Option<PathBuf>,
+    /// Authenticate once with this plugin before running the tests
+    #[arg(long, value_name = "NAME")]
+    auth_plugin: Option<String>,
+    /// The plugin the server must accept, --auth-plugin by default
+    #[arg(long = "expect-auth-plugin", value_name = "NAME")]
+    expected_auth_plugin: Option<String>,
+    /// A variable used like a captured one
+    #[arg(long = "var", value_name = "NAME=VALUE", value_parser = variable)]
+    variables: Vec<(String, String)>,
+    /// A MySQL server, as a mysql:// URL, whose responses replace the expectations
+    #[arg(long, value_name = "URL")]
+    reference_dsn: Option<String>,
+    /// The PostgreSQL port of the same server, for the pg: and both: tests
+    #[arg(long, value_name = "DSN")]
+    pg: Option<String>,
+    /// Only run tests carrying one of these tags
+    #[arg(long = "include-tag", value_name = "TAG,...")]
+    include_tags: Vec<String>,
+    /// Never run tests carrying one of these tags
+    #[arg(long = "exclude-tag", value_name = "TAG,...")]
+    exclude_tags: Vec<String>,
+}
+
+#[derive(Parser)]
+#[command(name = "mysql_test")]
+enum Command {
+    /// Prints a legacy test file in the structured format
+    Convert { file: String },
+    /// Runs random statements, writing a reproducer for each crash
+    Fuzz {
+        #[command(flatten)]
+        server: Server,
+        #[arg(long, default_value_t = 1000)]
+        iterations: usize,
+        /// Seed of the generator, the current time by default
+        #[arg(long)]
+        seed: Option<u64>,
+        #[command(flatten)]
+        timeout: Timeout,
+        /// Where reproducers are written
+        #[arg(long, value_name = "DIR", default_value = ".")]
+        out: PathBuf,
+    },
+    /// Runs point selects and updates from concurrent clients
+    Load {
+        #[command(flatten)]
+        server: Server,
+        #[arg(long, default_value_t = 4)]
+        threads: usize,
+        /// In seconds or as a duration such as 2m
+        #[arg(long, default_value = "10", value_parser = duration)]
+        duration: Duration,
+        /// The share of point selects, the rest being updates
+        #[arg(long, default_value_t = 80, value_parser = clap::value_parser!(u64).range(0..=100))]
+        read_percent: u64,
+        #[arg(long, value_name = "ROWS", default_value_t = 10000, value_parser = clap::value_parser!(u32).range(1..))]
+        table_size: u32,
+    },
+    /// Inserts random rows in batches and checks aggregates over them
+    Bulk {
+        #[command(flatten)]
+        server: Server,
+        #[arg(long, default_value_t = 100000)]
+        rows: u64,
+        /// The rows of each INSERT statement
+        #[arg(long, default_value_t = 1000, value_parser = clap::value_parser!(u64).range(1..))]
+        batch: u64,
+        /// Seed of the generator, the current time by default
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+    /// Checks that tables replicated from a source match it
+    Replicate {
+        #[command(flatten)]
+        server: Server,
+        /// The upstream MySQL server, as a mysql:// URL
+        #[arg(long, value_name = "URL")]
+        source: String,
+        /// The tables compared between the source and the server
+        #[arg(long, value_name = "TABLE,...", required = true)]
+        tables: Vec<String>,
+        /// An SQL file of statements run on the source
+        #[arg(long, value_name = "FILE")]
+        workload: Option<PathBuf>,
+        /// Compare the tables by checksum rather than row by row
+        #[arg(long)]
+        checksum: bool,
+        /// How long the server may take to catch up, in seconds or as a duration
+        #[arg(long, default_value = "60", value_parser = duration)]
+        catch_up: Duration,
+        #[command(flatten)]
+        timeout: Timeout,
+    },
+    /// Checks that multi-byte strings round-trip byte for byte
+    Unicode {
+        #[command(flatten)]
+        server: Server,
+        /// The database of the table the strings go through
+        #[arg(long, default_value = "test")]
+        database: String,
+        /// Also check the strings through the PostgreSQL port
+        #[arg(long, value_name = "DSN")]
+        pg: Option<String>,
+        /// Where the generated test file is written
+        #[arg(long, value_name = "FILE")]
+        out: Option<PathBuf>,
+        #[command(flatten)]
+        timeout: Timeout,
+    },
+    /// Streams large parameters in chunks and checks that they round-trip
+    LongData {
+        #[command(flatten)]
+        server: Server,
+        /// The sizes of the values, in bytes with an optional K or M suffix
+        #[arg(long, value_name = "SIZE,...", value_delimiter = ',', default_value = "1M,4M,16M", value_parser = size)]
+        sizes: Vec<usize>,
+        /// The bytes of each COM_STMT_SEND_LONG_DATA packet
+        #[arg(long, default_value = "1M", value_parser = chunk)]
+        chunk: usize,
+        /// Seed of the generator, the current time by default
+        #[arg(long)]
+        seed: Option<u64>,
+        #[command(flatten)]
+        timeout: Timeout,
+    },
+}
+
+#[derive(Args)]
+struct Server {
+    ip: String,
+    port: u16,
+    user: String,
+    password: String,
+}
+
+#[derive(Args)]
+struct Timeout {
+    /// Cancel queries running longer than this many seconds, 0 for never
+    #[arg(long = "timeout", value_name = "SECONDS", default_value_t = 30)]
+    seconds: u64,
+}
+
+impl Timeout {
+    fn options(&self) -> Options {
+        let timeout = if self.seconds == 0 { None } else { Some(Duration::from_secs(self.seconds)) };
+        Options { timeout, ..Options::default() }
+    }
+}
+
+fn duration(value: &str) -> Result<Duration, String> {
+    value
+        .parse()
+        .ok()
+        .map(Duration::from_secs)
+        .or_else(|| mysql_test::parse_duration(value))
+        .ok_or_else(|| "expected a number of seconds or a duration such as 500ms".to_string())
+}
+
+fn shard(value: &str) -> Result<(usize, usize), String> {
+    mysql_test::parse_shard(value).ok_or_else(|| "expected i/n, with 1 <= i <= n".to_string())
+}
+
+fn format(value: &str) -> Result<Format, String> {
+    match value {
+        "text" => Ok(Format::Text),
+        "junit" => Ok(Format::Junit),
+        "tap" => Ok(Format::Tap),
+        _ => Err("expected one of text, junit or tap".to_string()),
+    }
+}
+
+fn compression(value: &str) -> Result<Compression, String> {
+    match value.to_ascii_lowercase().as_str() {
+        "zlib" | "default" => Ok(Compression::default()),
+        "fast" => Ok(Compression::fast()),
+        "best" => Ok(Compression::best()),
+        "zstd" => Err("zstd is not supported: the mysql crate only implements zlib".to_string()),
+        level => match level.parse() {
+            Ok(level @ 0..=9) => Ok(Compression::new(level)),
+            _ => Err("expected zlib, fast, best or a level from 0 to 9".to_string()),
+        },
+    }
+}
+
+fn ssl_mode(value: &str) -> Result<SslMode, String> {
+    match value.to_ascii_uppercase().as_str() {
+        "DISABLED" => Ok(SslMode::Disabled),
+        "REQUIRED" => Ok(SslMode::Required),
+        "VERIFY_CA" => Ok(SslMode::VerifyCa),
+        "VERIFY_IDENTITY" => Ok(SslMode::VerifyIdentity),
+        _ => Err("expected one of DISABLED, REQUIRED, VERIFY_CA or VERIFY_IDENTITY".to_string()),
+    }
+}
+
+// The largest chunk fitting in a packet along with the command, the
+// statement id and the parameter index.
+const MAX_CHUNK: usize = (1 << 24) - 1 - 7;
+
+fn size(value: &str) -> Result<usize, String> {
+    let (digits, unit) = match value.char_indices().last() {
+        Some((i, 'K' | 'k')) => (&value[..i], 1 << 10),
+        Some((i, 'M' | 'm')) => (&value[..i], 1 << 20),
+        _ => (value, 1),
+    };
+    digits.parse::<usize>().map(|n| n * unit).map_err(|_| "expected a number of bytes, with an optional K or M suffix".to_string())
+}
+
+fn chunk(value: &str) -> Result<usize, String> {
+    match size(value)? {
+        chunk @ 1..=MAX_CHUNK => Ok(chunk),
+        _ => Err(format!("expected from 1 to {} bytes", MAX_CHUNK)),
+    }
+}
+
+fn variable(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((name, value)) => Ok((name.to_string(), value.to_string())),
+        None => Err("expected name=value".to_string()),
+    }
+}
+
+fn seed(seed: Option<u64>) -> u64 {
+    seed.unwrap_or_else(|| SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos() as u64)
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() < 2 || !SUBCOMMANDS.contains(&args[1].as_str()) {
+        let run = Run::parse();
+        let options = Options {
+            prepared: run.prepared,
+            epsilon: run.epsilon,
+            record: run.record,
+            keep_going: run.keep_going,
+            output: run.output,
+            shard: run.shard,
+            format: run.format,
+            jobs: run.jobs,
+            bench: run.bench,
+            cursor_fetch: run.cursor_fetch.unwrap_or(0) as usize,
+            isolate: run.isolate,
+            include_tags: run.include_tags.iter().flat_map(|tags| mysql_test::parse_list(tags)).collect(),
+            exclude_tags: run.exclude_tags.iter().flat_map(|tags| mysql_test::parse_list(tags)).collect(),
+            variables: run.variables.into_iter().collect(),
+            reference_dsn: run.reference_dsn,
+            pg: run.pg,
+            deadline: run.deadline.map(|seconds| Instant::now() + Duration::from_secs(seconds)),
+            connect_retries: run.connect_retries,
+            connect_backoff: run.connect_backoff,
+            socket: run.socket,
+            restart_command: run.restart_command,
+            restart_timeout: run.restart_timeout,
+            compress: run.compress,
+            charset: run.charset,
+            ssl_mode: run.ssl_mode,
+            ssl_ca: run.ssl_ca,
+            ssl_cert: run.ssl_cert,
+            ssl_key: run.ssl_key,
+            auth_plugin: run.auth_plugin,
+            expected_auth_plugin: run.expected_auth_plugin,
+            ..run.timeout.options()
+        };
+        let server = &run.server;
+        if !mysql_test::run(&server.ip, server.port, &server.user, &server.password, &run.files, options) {
+            exit(1);
+        }
+        return;
+    }
+
+    let passed = match Command::parse() {
+        Command::Convert { file } => match mysql_test::convert(&file) {
+            Ok(()) => true,
+            Err(err) => {
+                eprintln!("Failed to convert test file: {}", err);
+                false
+            }
+        },
+        Command::Fuzz { server, iterations, seed: s, timeout, out } => {
+            let fuzz = FuzzOptions { iterations, seed: seed(s), out, options: timeout.options() };
+            mysql_test::fuzz(&server.ip, server.port, &server.user, &server.password, fuzz)
+        }
+        Command::Load { server, threads, duration, read_percent, table_size } => {
+            let load = LoadOptions { threads, duration, read_percent, table_size, options: Options::default() };
+            mysql_test::load(&server.ip, server.port, &server.user, &server.password, load)
+        }
+        Command::Bulk { server, rows, batch, seed: s } => {
+            let bulk = BulkOptions { rows, batch: batch as usize, seed: seed(s), options: Options::default() };
+            mysql_test::bulk(&server.ip, server.port, &server.user, &server.password, bulk)
+        }
+        Command::Replicate { server, source, tables, workload, checksum, catch_up, timeout } => {
+            let replicate = ReplicateOptions {
+                source: Some(source),
+                workload,
+                tables: tables.iter().flat_map(|tables| mysql_test::parse_list(tables)).collect(),
+                catch_up,
+                checksum,
+                options: timeout.options(),
+            };
+            mysql_test::replicate(&server.ip, server.port, &server.user, &server.password, replicate)
+        }
+        Command::Unicode { server, database, pg, out, timeout } => {
+            let options = Options { keep_going: true, pg, ..timeout.options() };
+            let unicode = UnicodeOptions { database, out, options };
+            mysql_test::unicode(&server.ip, server.port, &server.user, &server.password, unicode)
+        }
+        Command::LongData { server, sizes, chunk, seed: s, timeout } => {
+            let long = LongDataOptions { sizes, chunk, seed: seed(s), options: timeout.options() };
+            mysql_test::long_data(&server.ip, server.port, &server.user, &server.password, long)
+        }
+    };
+    if !passed {
+        exit(1);
+    }
+}
@@ -88,4 +88,9 @@ check_status() {
 @test "mysql-rust" {
     start_process cargo build --release --manifest-path $BATS_TEST_DIRNAME/rust/Cargo.toml
     start_process $BATS_TEST_DIRNAME/rust/target/release/mysql_test 127.0.0.1 3306 root "" $BATS_TEST_DIRNAME/test.data
-}
\ No newline at end of file
+}
+
+@test "mysql-rust-prepared" {
+    start_process cargo build --release --manifest-path $BATS_TEST_DIRNAME/rust/Cargo.toml
+    start_process $BATS_TEST_DIRNAME/rust/target/release/mysql_test 127.0.0.1 3306 root "" $BATS_TEST_DIRNAME/test.data --prepared
+}
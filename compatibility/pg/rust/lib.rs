@@ -0,0 +1,2842 @@
+use std::borrow::Cow;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+extern crate chrono;
+extern crate compat_harness;
+extern crate postgres;
+extern crate regex;
+extern crate serde;
+extern crate serde_json;
+extern crate toml;
+extern crate uuid;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use postgres::{Client, Config, NoTls, Error, Row};
+use postgres::config::Host;
+use postgres::fallible_iterator::FallibleIterator;
+use postgres::types::{FromSql, ToSql, Type};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+pub use compat_harness::{Assertion, Format, SqlClient, TestCase, discover_test_files, parse_duration, parse_list, parse_shard};
+use compat_harness::{CellKind, NULL, cell_matches, regex_pattern};
+use compat_harness::{BULK_CHARS, CANCEL_GRACE, Digest, FuzzTable, Fuzzer, HarnessOptions, LoadStats, Outcome, ParamSet, Proxy, Rng, SuiteResults, TestResult, UNICODE_STRINGS, WAIT_INTERVAL, column_matches, connect_with_retries, diff_rows, format_step_line, format_units, hex_literal, is_false, isolated_database, json_matches, json_report, junit_report, load_rows, normalize_plan, parse_capture, parse_count, parse_delimiter, parse_transaction, percentile, print_failure, read_fixture, read_statement, restart_server, substitute, summary_report, tap_report, to_hex, unicode_hex, with_cancel};
+
+// Accepts any column type but only decodes NULL, which lets `cell_to_string`
+// detect NULL before trying the typed conversions.
+struct Null;
+
+impl<'a> FromSql<'a> for Null {
+    fn from_sql(_: &Type, _: &'a [u8]) -> Result<Self, Box<dyn std::error::Error + Sync + Send>> {
+        Err("not null".into())
+    }
+
+    fn from_sql_null(_: &Type) -> Result<Self, Box<dyn std::error::Error + Sync + Send>> {
+        Ok(Null)
+    }
+
+    fn accepts(_: &Type) -> bool {
+        true
+    }
+}
+
+// Decodes the binary NUMERIC representation into its exact decimal text:
+// base-10000 digits, the weight of the first digit, a sign and the scale.
+struct Numeric(String);
+
+impl<'a> FromSql<'a> for Numeric {
+    fn from_sql(_: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn std::error::Error + Sync + Send>> {
+        let word = |i: usize| -> Result<u16, Box<dyn std::error::Error + Sync + Send>> {
+            match raw.get(i * 2..i * 2 + 2) {
+                Some(b) => Ok(u16::from_be_bytes([b[0], b[1]])),
+                None => Err("invalid numeric".into()),
+            }
+        };
+        let ndigits = word(0)? as usize;
+        let weight = word(1)? as i16 as isize;
+        let sign = word(2)?;
+        let dscale = word(3)? as usize;
+        match sign {
+            0xC000 => return Ok(Numeric("NaN".to_string())),
+            0xD000 => return Ok(Numeric("Infinity".to_string())),
+            0xF000 => return Ok(Numeric("-Infinity".to_string())),
+            _ => {}
+        }
+        let mut digits = Vec::with_capacity(ndigits);
+        for i in 0..ndigits {
+            digits.push(word(4 + i)?);
+        }
+        let digit = |i: isize| if i >= 0 && (i as usize) < ndigits { digits[i as usize] } else { 0 };
+
+        let mut s = String::new();
+        if sign == 0x4000 {
+            s.push('-');
+        }
+        if weight < 0 {
+            s.push('0');
+        } else {
+            for i in 0..=weight {
+                if i == 0 {
+                    s += &digit(i).to_string();
+                } else {
+                    s += &format!("{:04}", digit(i));
+                }
+            }
+        }
+        if dscale > 0 {
+            let mut fraction = String::new();
+            let mut i = weight + 1;
+            while fraction.len() < dscale {
+                fraction += &format!("{:04}", digit(i));
+                i += 1;
+            }
+            fraction.truncate(dscale);
+            s.push('.');
+            s += &fraction;
+        }
+        Ok(Numeric(s))
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::NUMERIC
+    }
+}
+
+// Decodes JSON and JSONB values into the text sent by the server. Binary
+// JSONB is that text after a version byte.
+struct Json(String);
+
+impl<'a> FromSql<'a> for Json {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn std::error::Error + Sync + Send>> {
+        let text = match (ty, raw.split_first()) {
+            (&Type::JSONB, Some((1, text))) => text,
+            (&Type::JSONB, _) => return Err("unsupported jsonb version".into()),
+            _ => raw,
+        };
+        Ok(Json(std::str::from_utf8(text)?.to_string()))
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::JSON || *ty == Type::JSONB
+    }
+}
+
+// Renders a cell the way the other clients print it, so the same test.data
+// can be shared by every harness.
+fn cell_to_string(row: &Row, j: usize) -> String {
+    if row.try_get::<usize, Null>(j).is_ok() {
+        return NULL.to_string();
+    }
+    row.try_get::<usize, String>(j)
+        .or_else(|_| row.try_get::<usize, i16>(j).map(|v| v.to_string()))
+        .or_else(|_| row.try_get::<usize, i32>(j).map(|v| v.to_string()))
+        .or_else(|_| row.try_get::<usize, i64>(j).map(|v| v.to_string()))
+        .or_else(|_| row.try_get::<usize, f32>(j).map(|v| v.to_string()))
+        .or_else(|_| row.try_get::<usize, f64>(j).map(|v| v.to_string()))
+        .or_else(|_| row.try_get::<usize, bool>(j).map(|v| if v { "t" } else { "f" }.to_string()))
+        .or_else(|_| row.try_get::<usize, Numeric>(j).map(|v| v.0))
+        .or_else(|_| row.try_get::<usize, Json>(j).map(|v| v.0))
+        .or_else(|_| row.try_get::<usize, NaiveDate>(j).map(|v| v.to_string()))
+        .or_else(|_| row.try_get::<usize, NaiveTime>(j).map(|v| v.to_string()))
+        .or_else(|_| row.try_get::<usize, NaiveDateTime>(j).map(|v| v.to_string()))
+        .or_else(|_| row.try_get::<usize, DateTime<Utc>>(j).map(|v| v.format("%Y-%m-%d %H:%M:%S%.f+00").to_string()))
+        .or_else(|_| row.try_get::<usize, Uuid>(j).map(|v| v.to_string()))
+        .or_else(|_| row.try_get::<usize, &[u8]>(j).map(to_hex))
+        .unwrap_or_default()
+        .trim()
+        .to_string()
+}
+
+fn row_matches(row: &Row, expected: &[String], epsilon: f64, json: &[bool]) -> bool {
+    expected
+        .iter()
+        .enumerate()
+        .all(|(j, e)| value_matches(e, row, j, epsilon, json[j]))
+}
+
+// Compares an expected cell with cell `j` of `row`. A cell written as `0x...`
+// also matches a value with these raw bytes, for binary columns, and a `json`
+// cell also matches an equal JSON document.
+fn value_matches(expected: &str, row: &Row, j: usize, epsilon: f64, json: bool) -> bool {
+    if json && json_matches(expected, &cell_to_string(row, j)) {
+        return true;
+    }
+    cell_matches(expected, &cell_to_string(row, j), cell_kind(row.columns()[j].type_()), epsilon) || hex_matches(expected, row, j)
+}
+
+fn hex_matches(expected: &str, row: &Row, j: usize) -> bool {
+    let expected = match hex_literal(expected) {
+        Some(expected) => expected,
+        None => return false,
+    };
+    let actual = match row.try_get::<usize, &[u8]>(j) {
+        Ok(bytes) => bytes.to_vec(),
+        Err(_) => cell_to_string(row, j).into_bytes(),
+    };
+    *actual == *expected
+}
+
+// The values of `-- params:`, as the types of the placeholders: `\N` is
+// NULL, and a string may be quoted with single quotes, e.g. `''`.
+fn bind_params(types: &[Type], cells: &[String]) -> Result<Vec<Box<dyn ToSql + Sync>>, String> {
+    if types.len() != cells.len() {
+        return Err(format!("The query takes {} parameters, got {}", types.len(), cells.len()));
+    }
+    types.iter().zip(cells).map(|(ty, cell)| bind_param(ty, cell)).collect()
+}
+
+fn bind_param(ty: &Type, cell: &str) -> Result<Box<dyn ToSql + Sync>, String> {
+    let invalid = || format!("Invalid {} parameter: {}", ty.name(), cell);
+    Ok(match *ty {
+        Type::BOOL => Box::new(parse_param(cell, |c| match c {
+            "t" | "true" => Some(true),
+            "f" | "false" => Some(false),
+            _ => None,
+        })
+        .ok_or_else(invalid)?),
+        Type::INT2 => Box::new(parse_param::<i16>(cell, |c| c.parse().ok()).ok_or_else(invalid)?),
+        Type::INT4 => Box::new(parse_param::<i32>(cell, |c| c.parse().ok()).ok_or_else(invalid)?),
+        Type::INT8 => Box::new(parse_param::<i64>(cell, |c| c.parse().ok()).ok_or_else(invalid)?),
+        Type::OID => Box::new(parse_param::<u32>(cell, |c| c.parse().ok()).ok_or_else(invalid)?),
+        Type::FLOAT4 => Box::new(parse_param::<f32>(cell, |c| c.parse().ok()).ok_or_else(invalid)?),
+        Type::FLOAT8 => Box::new(parse_param::<f64>(cell, |c| c.parse().ok()).ok_or_else(invalid)?),
+        Type::BYTEA => Box::new(parse_param(cell, hex_literal).ok_or_else(invalid)?),
+        Type::DATE => Box::new(parse_param::<NaiveDate>(cell, |c| c.parse().ok()).ok_or_else(invalid)?),
+        Type::TIMESTAMP => Box::new(
+            parse_param(cell, |c| NaiveDateTime::parse_from_str(c, "%Y-%m-%d %H:%M:%S%.f").ok()).ok_or_else(invalid)?,
+        ),
+        Type::TIMESTAMPTZ => Box::new(parse_param::<DateTime<Utc>>(cell, |c| c.parse().ok()).ok_or_else(invalid)?),
+        Type::UUID => Box::new(parse_param::<Uuid>(cell, |c| c.parse().ok()).ok_or_else(invalid)?),
+        Type::TEXT | Type::VARCHAR | Type::BPCHAR | Type::NAME | Type::UNKNOWN => {
+            Box::new(parse_param(cell, |c| Some(c.strip_prefix('\'').and_then(|c| c.strip_suffix('\'')).unwrap_or(c).to_string())))
+        }
+        _ => return Err(format!("Unsupported parameter type {}: {}", ty.name(), cell)),
+    })
+}
+
+// `\N` as NULL, or the value parsed from the cell, if it parses.
+fn parse_param<T>(cell: &str, parse: impl Fn(&str) -> Option<T>) -> Option<Option<T>> {
+    if cell == "\\N" {
+        return Some(None);
+    }
+    parse(cell).map(Some)
+}
+
+fn render_row(row: &Row) -> Vec<String> {
+    (0..row.len()).map(|j| cell_to_string(row, j)).collect()
+}
+
+fn row_to_string(row: &Row) -> String {
+    render_row(row).join(",")
+}
+
+// How the cells of a column of the type are compared.
+fn cell_kind(ty: &Type) -> CellKind {
+    match *ty {
+        Type::INT2 | Type::INT4 | Type::INT8 | Type::OID => CellKind::Integer,
+        Type::FLOAT4 | Type::FLOAT8 | Type::NUMERIC => CellKind::Float,
+        Type::DATE | Type::TIME | Type::TIMETZ | Type::TIMESTAMP | Type::TIMESTAMPTZ => CellKind::Temporal,
+        _ => CellKind::Other,
+    }
+}
+
+// `Error`'s Display only says "db error" for server-side errors.
+fn error_message(err: &Error) -> String {
+    match err.as_db_error() {
+        Some(e) => format!("{}: {}", e.code().code(), e.message()),
+        None => err.to_string(),
+    }
+}
+
+// Harness-wide settings, taken from the command line.
+pub struct Options {
+    // Allowed relative difference between float and numeric values.
+    pub epsilon: f64,
+    // Run every test, replace the expectations of failing ones with the
+    // actual results, and rewrite the test file.
+    pub record: bool,
+    // Run every test and every file after a failure, and print a summary of
+    // the outcomes at the end.
+    pub keep_going: bool,
+    // Set by `--output <file>`: the results are also written there as JSON.
+    pub output: Option<String>,
+    // Set by `--shard i/n`: only the i-th of every n test files runs, 1-based.
+    pub shard: Option<(usize, usize)>,
+    // How the results are reported on stdout.
+    pub format: Format,
+    // Number of test files run concurrently, each on its own connection.
+    pub jobs: usize,
+    // With `--bench N`, every passing query returning rows runs N more
+    // times to measure its latency.
+    pub bench: usize,
+    // Run each test file in a database of its own, created before the file
+    // and dropped after it.
+    pub isolate: bool,
+    // Only run tests carrying one of these tags (every test if empty), and
+    // never those carrying one of `exclude_tags`.
+    pub include_tags: Vec<String>,
+    pub exclude_tags: Vec<String>,
+    // Set by `--var name=value`, and used like captured variables.
+    pub variables: HashMap<String, String>,
+    // A PostgreSQL server, as a connection string or a `postgresql://` URL,
+    // whose responses replace the expectations of the tests.
+    pub reference_dsn: Option<String>,
+    // A query running longer than this is cancelled and fails its test.
+    pub timeout: Option<Duration>,
+    // Tests not started by then fail, and the rest of the run is skipped.
+    pub deadline: Option<Instant>,
+    // How many times connecting to the server is retried at startup, and
+    // the wait before the first retry, doubled after each one.
+    pub connect_retries: u32,
+    pub connect_backoff: Duration,
+    // Connect through this Unix domain socket instead of TCP: the socket
+    // file, or the directory holding it as for libpq.
+    pub socket: Option<String>,
+    // Run by `-- restart` through `sh -c` to restart the server, e.g.
+    // `docker restart myduck` or a `curl` to an admin endpoint, and how long
+    // the server may then take to accept connections again.
+    pub restart_command: Option<String>,
+    pub restart_timeout: Duration,
+    // Checks run after every passing test, on the connection of the test,
+    // added with `Tests::add_assertion` by a crate embedding the harness.
+    pub assertions: Vec<Arc<dyn Assertion>>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            epsilon: 1e-6,
+            record: false,
+            keep_going: false,
+            output: None,
+            shard: None,
+            format: Format::Text,
+            jobs: 1,
+            isolate: false,
+            bench: 0,
+            include_tags: Vec::new(),
+            exclude_tags: Vec::new(),
+            variables: HashMap::new(),
+            reference_dsn: None,
+            timeout: Some(Duration::from_secs(30)),
+            deadline: None,
+            connect_retries: 0,
+            connect_backoff: Duration::from_secs(1),
+            socket: None,
+            restart_command: None,
+            restart_timeout: Duration::from_secs(60),
+            assertions: Vec::new(),
+        }
+    }
+}
+
+impl Options {
+    fn selects(&self, tags: &[&String]) -> bool {
+        (self.include_tags.is_empty() || self.include_tags.iter().any(|tag| tags.contains(&tag)))
+            && !self.exclude_tags.iter().any(|tag| tags.contains(&tag))
+    }
+}
+
+impl HarnessOptions for Options {
+    fn format(&self) -> Format {
+        self.format
+    }
+
+    fn restart_command(&self) -> Option<&str> {
+        self.restart_command.as_deref()
+    }
+
+    fn connect_retries(&self) -> u32 {
+        self.connect_retries
+    }
+
+    fn connect_backoff(&self) -> Duration {
+        self.connect_backoff
+    }
+}
+
+// The options of `fuzz`, `load` and `bulk`, following the address of the
+// server.
+pub type FuzzOptions = compat_harness::FuzzOptions<Options>;
+pub type LoadOptions = compat_harness::LoadOptions<Options>;
+pub type BulkOptions = compat_harness::BulkOptions<Options>;
+
+// What a query returned, as checked by the expectations of a test.
+struct Response {
+    result: Result<Vec<Row>, Error>,
+    columns: Vec<String>,
+    // Only counted when the test expects an affected row count.
+    affected_rows: u64,
+    // Set instead of the rows when the test streams the result.
+    digest: Option<Digest>,
+    // The rows written by a `COPY ... TO STDOUT`, split into cells.
+    copied: Option<Vec<Vec<String>>>,
+}
+
+// A single query and its expectations. Tests are either read from the
+// legacy line-based format or deserialized from a `.toml` file, where each
+// `[[test]]` table uses the field names below.
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+struct Test {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    query: String,
+    #[serde(rename = "results", default, skip_serializing_if = "Vec::is_empty")]
+    expected_results: Vec<Vec<String>>,
+    // Set by an `-- error: <SQLSTATE or message substring>` line: the query
+    // is expected to fail, and the error must match.
+    #[serde(rename = "error", default, skip_serializing_if = "Option::is_none")]
+    expected_error: Option<String>,
+    // Set by `-- affected: <n>`: the statement is run with `execute`, which
+    // returns the row count of its command tag instead of rows.
+    #[serde(rename = "affected", default, skip_serializing_if = "Option::is_none")]
+    expected_affected_rows: Option<u64>,
+    // Set by `-- columns: <name> [<type>], ...`: the columns of the result,
+    // with the name of their type, e.g. `int4` or `text`. Only the name is
+    // checked when the type is omitted.
+    #[serde(rename = "columns", default, skip_serializing_if = "Option::is_none")]
+    expected_columns: Option<Vec<String>>,
+    // Set by `-- sorted` or `-- unordered`: the rows may come back in any
+    // order and are matched as a multiset.
+    #[serde(default, skip_serializing_if = "is_false")]
+    unordered: bool,
+    // Set by `-- explain`: the query runs under EXPLAIN, unless it already
+    // is one, and each expected row is a line of the plan, compared once
+    // `normalize_plan` has stripped the costs, estimates and pointers.
+    #[serde(default, skip_serializing_if = "is_false")]
+    explain: bool,
+    // Set by `-- json: <column>, ...`: the cells of these columns are
+    // compared as JSON documents, ignoring the order of object keys and the
+    // whitespace.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    json: Vec<String>,
+    // Overrides `Options::epsilon` for this test.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    epsilon: Option<f64>,
+    // Set by `-- let $name = [row][column]`: stores a cell of the result in
+    // a variable, which the following queries, expected rows and teardown
+    // statements of the file refer to as `$name`.
+    #[serde(rename = "let", default, skip_serializing_if = "Vec::is_empty")]
+    captures: Vec<String>,
+    // Set by `-- tags: a, b`, for `--include-tag` and `--exclude-tag`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    // Set by `-- connection: <name>`: the session the test runs on. Named
+    // sessions are opened on their first use and closed after the file, and
+    // tests without one (or naming `default`) share the main connection.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    connection: Option<String>,
+    // Set by `-- begin: <connection> [<isolation level>]`, `-- commit:
+    // <connection>` and `-- rollback: <connection>` lines: instead of a query,
+    // the test starts or ends a transaction on its connection, e.g.
+    // "begin repeatable read".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    transaction: Option<String>,
+    // Set by `-- rows: <n>` and `-- checksum: <hex>`: the result is streamed
+    // and only its row count and checksum (see `Digest`) are checked, for
+    // results too large to list.
+    #[serde(rename = "rows", default, skip_serializing_if = "Option::is_none")]
+    expected_row_count: Option<u64>,
+    #[serde(rename = "checksum", default, skip_serializing_if = "Option::is_none")]
+    expected_checksum: Option<String>,
+    // Set by `-- max_latency: <n>ms` (or `<n>s`): with `--bench`, the 95th
+    // percentile of the latency of the query must not exceed this.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_latency: Option<String>,
+    // Set by `-- wait_until(<n>s)` (or `<n>ms`, `<n>m`): a failing query is
+    // run again until it passes or the time is up, for results that only
+    // become visible eventually, e.g. on a replica.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    wait_until: Option<String>,
+    // Set by `-- cancel_after(<n>ms)` (or `<n>s`): the query is cancelled
+    // with a cancel request once this long has passed, and fails if it
+    // finished before. Its connection must still work after.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cancel_after: Option<String>,
+    // Set by `-- disconnect`: after the query, the named session of the test
+    // is cut abruptly, without the goodbye message of the protocol, as if
+    // the client had crashed, and the next test naming it opens a new one.
+    // Such a session goes through a `Proxy`, which can cut it.
+    #[serde(default, skip_serializing_if = "is_false")]
+    disconnect: bool,
+    // Set by `-- restart`: before the query, the server is restarted with
+    // `--restart-command` and the connection of the file opened again, so the
+    // query can check that committed data and DDL survived. Named sessions
+    // are closed and reopened by the next tests naming them.
+    #[serde(default, skip_serializing_if = "is_false")]
+    restart: bool,
+    // The data fed to a `COPY ... FROM STDIN` query: the file set by
+    // `-- copy_in: <path>`, relative to the test file, followed by the lines
+    // of the `-- copy_data: <line>` directives.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    copy_in: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    copy_data: Vec<String>,
+    // `copy_in` resolved against the directory of the test file.
+    #[serde(skip)]
+    copy_in_path: Option<PathBuf>,
+    // Set by `-- sets: <parameter> = <value>`: after the statement, the run-
+    // time parameter reads as the value on the connection of the test, and
+    // as before on another connection, which must not see the change.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    sets: Vec<String>,
+    // Set by `-- time_zone: <zone>`: the test runs with the TimeZone
+    // parameter set to the zone, e.g. `Asia/Shanghai` or `UTC`, on its
+    // connection and on the reference, and the previous one is restored
+    // after it. A timestamptz written in one zone and read in another shows
+    // the conversion in the expected rows.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    time_zone: Option<String>,
+    // Set by `-- params: <value>, ...` lines, each followed by its own
+    // expected rows or `-- error:`: the query is prepared and runs once per
+    // parameter set, with the values bound to `$1`, `$2`... as the types the
+    // server inferred for them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    matrix: Vec<ParamSet>,
+    // The parameter set of the matrix being run.
+    #[serde(skip)]
+    params: Option<Vec<String>>,
+}
+
+#[derive(Deserialize, Serialize, Default)]
+#[serde(deny_unknown_fields)]
+struct TestFile {
+    // SQL files, relative to the test file, whose statements run before the
+    // setup. Set by `-- include: <path>` lines.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    include: Vec<String>,
+    // Tags shared by every test of the file, set by a `-- tags:` line of its
+    // own.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    // Statements run before the first test and after the last one, e.g. to
+    // create and drop fixtures. Their results are not checked.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    setup: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    teardown: Vec<String>,
+    #[serde(rename = "test", default)]
+    tests: Vec<Test>,
+}
+
+impl Test {
+    fn new(query: String, expected_results: Vec<Vec<String>>) -> Self {
+        Test {
+            name: None,
+            query,
+            expected_results,
+            expected_error: None,
+            unordered: false,
+            explain: false,
+            json: Vec::new(),
+            expected_columns: None,
+            expected_affected_rows: None,
+            epsilon: None,
+            captures: Vec::new(),
+            tags: Vec::new(),
+            connection: None,
+            transaction: None,
+            wait_until: None,
+            cancel_after: None,
+            max_latency: None,
+            expected_row_count: None,
+            expected_checksum: None,
+            disconnect: false,
+            restart: false,
+            copy_in: None,
+            copy_data: Vec::new(),
+            copy_in_path: None,
+            sets: Vec::new(),
+            time_zone: None,
+            matrix: Vec::new(),
+            params: None,
+        }
+    }
+
+    fn display_name(&self) -> String {
+        match (&self.name, &self.transaction) {
+            (Some(name), _) => name.clone(),
+            (None, Some(step)) => format!("{} on {}", step, self.connection.as_deref().unwrap_or("default")),
+            (None, None) => self.query.clone(),
+        }
+    }
+
+    // Runs a transaction step on the connection of the test, and on the
+    // reference.
+    fn run_transaction(&self, step: &str, client: &mut Client, reference: Option<&mut Client>, options: &Options) -> Option<String> {
+        let statements = transaction_statements(step);
+        let mut result = run_statements(client, &statements, false, options);
+        if let (Ok(()), Some(reference)) = (&result, reference) {
+            result = run_statements(reference, &statements, false, options).map_err(|f| format!("Reference: {}", f));
+        }
+        result.err()
+    }
+
+    // Runs the query and returns why the test failed, if it did. With a
+    // reference server, the query also runs there and its response replaces
+    // the expectations of the test. The other connection of `-- sets`
+    // connects with `conn_str`.
+    fn run(
+        &mut self,
+        client: &mut Client,
+        conn_str: &str,
+        mut reference: Option<&mut Client>,
+        variables: &mut HashMap<String, String>,
+        options: &Options,
+    ) -> Option<String> {
+        let time_zone = match self.time_zone.clone() {
+            Some(time_zone) => time_zone,
+            None => return self.run_query(client, conn_str, reference, variables, options),
+        };
+        let switched = switch_time_zone(client, &time_zone).and_then(|before| match reference.as_deref_mut() {
+            Some(reference) => {
+                let reference_before = switch_time_zone(reference, &time_zone).map_err(|f| format!("Reference: {}", f))?;
+                Ok((before, Some(reference_before)))
+            }
+            None => Ok((before, None)),
+        });
+        let (before, reference_before) = match switched {
+            Ok(before) => before,
+            Err(failure) => {
+                print_failure(&failure);
+                return Some(failure);
+            }
+        };
+        let failure = self.run_query(client, conn_str, reference.as_deref_mut(), variables, options);
+        let restored = switch_time_zone(client, &before).and_then(|_| match (reference, reference_before) {
+            (Some(reference), Some(before)) => switch_time_zone(reference, &before).map_err(|f| format!("Reference: {}", f)),
+            _ => Ok(String::new()),
+        });
+        failure.or_else(|| {
+            let failure = restored.err()?;
+            print_failure(&failure);
+            Some(failure)
+        })
+    }
+
+    fn run_query(
+        &mut self,
+        client: &mut Client,
+        conn_str: &str,
+        reference: Option<&mut Client>,
+        variables: &mut HashMap<String, String>,
+        options: &Options,
+    ) -> Option<String> {
+        if let Some(step) = &self.transaction {
+            return self.run_transaction(step, client, reference, options);
+        }
+        if !self.matrix.is_empty() {
+            return self.run_matrix(client, conn_str, reference, variables, options);
+        }
+        let mut query = substitute(&self.query, variables);
+        if self.explain && !query.trim_start().to_uppercase().starts_with("EXPLAIN") {
+            query = format!("EXPLAIN {}", query);
+        }
+        match &self.name {
+            Some(name) => options.log(&format!("Running test {}: {}", name, query)),
+            None => options.log(&format!("Running test: {}", query)),
+        }
+        let mut probe = match self.probe_sets(conn_str) {
+            Ok(probe) => probe,
+            Err(failure) => {
+                print_failure(&failure);
+                return Some(failure);
+            }
+        };
+        let start = Instant::now();
+        let mut response = match self.execute(client, &query, options) {
+            Ok(response) => response,
+            Err(failure) => {
+                print_failure(&failure);
+                return Some(failure);
+            }
+        };
+        let mut expected = Cow::Borrowed(&*self);
+        if self.expected_results.iter().flatten().any(|cell| cell.contains('$')) {
+            for cell in expected.to_mut().expected_results.iter_mut().flatten() {
+                *cell = substitute(cell, variables);
+            }
+        }
+        let from_reference = reference.is_some();
+        if let Some(reference) = reference {
+            match self.execute(reference, &query, options) {
+                Ok(response) => expected.to_mut().record(&response),
+                Err(failure) => {
+                    let failure = format!("Reference: {}", failure);
+                    print_failure(&failure);
+                    return Some(failure);
+                }
+            }
+        }
+        let epsilon = self.epsilon.unwrap_or(options.epsilon);
+        let mut verified = expected.verify(&response, epsilon);
+        if let Some(wait) = self.wait_until.as_deref().and_then(parse_duration) {
+            while verified.is_err() && start.elapsed() < wait {
+                thread::sleep(WAIT_INTERVAL);
+                response = match self.execute(client, &query, options) {
+                    Ok(response) => response,
+                    Err(failure) => {
+                        print_failure(&failure);
+                        return Some(failure);
+                    }
+                };
+                verified = expected.verify(&response, epsilon);
+            }
+        }
+        if let Err(failure) = self.capture(&response, variables) {
+            print_failure(&failure);
+            return Some(failure);
+        }
+        match verified {
+            Ok(summary) => {
+                options.log(&summary);
+                // Only statements returning rows are benchmarked, as running
+                // DDL or DML again would change the following results.
+                if options.bench > 0 && !response.columns.is_empty() {
+                    if let Err(failure) = self.bench(client, &query, options) {
+                        print_failure(&failure);
+                        return Some(failure);
+                    }
+                }
+                if let Some(probe) = probe.as_mut() {
+                    if let Err(failure) = self.check_sets(client, probe) {
+                        print_failure(&failure);
+                        return Some(failure);
+                    }
+                }
+                if !options.assertions.is_empty() {
+                    let rows = match (&response.copied, &response.result) {
+                        (Some(copied), _) => copied.clone(),
+                        (None, Ok(rows)) => rows.iter().map(render_row).collect(),
+                        (None, Err(_)) => Vec::new(),
+                    };
+                    let test = TestCase { name: self.name.as_deref(), query: &query, tags: &self.tags, rows: &rows };
+                    for assertion in &options.assertions {
+                        if let Err(failure) = assertion.check(&mut PgClient(client), &test) {
+                            let failure = format!("Assertion {}: {}", assertion.name(), failure);
+                            print_failure(&failure);
+                            return Some(failure);
+                        }
+                    }
+                }
+                None
+            }
+            Err(failure) => {
+                print_failure(&failure);
+                if options.record {
+                    if from_reference {
+                        *self = expected.into_owned();
+                    } else {
+                        self.record(&response);
+                    }
+                    options.log("Recorded the actual result");
+                }
+                Some(failure)
+            }
+        }
+    }
+
+    // Opens another connection for `-- sets` and reads the parameters
+    // there, before the statement runs.
+    fn probe_sets(&self, conn_str: &str) -> Result<Option<(Client, Vec<String>)>, String> {
+        if self.sets.is_empty() {
+            return Ok(None);
+        }
+        let mut probe = Client::connect(conn_str, NoTls).map_err(|e| format!("-- sets: {}", error_message(&e)))?;
+        let mut before = Vec::new();
+        for (parameter, _) in self.sets.iter().filter_map(|set| parse_set(set)) {
+            before.push(read_parameter(&mut probe, parameter)?);
+        }
+        Ok(Some((probe, before)))
+    }
+
+    // Checks that the statement set the parameters of `-- sets` on its
+    // connection, and left them alone on the probe connection.
+    fn check_sets(&self, client: &mut Client, probe: &mut (Client, Vec<String>)) -> Result<(), String> {
+        let sets = self.sets.iter().filter_map(|set| parse_set(set));
+        for ((parameter, value), before) in sets.zip(&probe.1) {
+            let actual = read_parameter(client, parameter)?;
+            if !cell_matches(value, &actual, CellKind::Other, 0.0) {
+                return Err(format!("Parameter {} on this connection: expected {}, got {}", parameter, value, actual));
+            }
+            let other = read_parameter(&mut probe.0, parameter)?;
+            if other != *before {
+                return Err(format!("Parameter {} changed on another connection: from {} to {}", parameter, before, other));
+            }
+        }
+        Ok(())
+    }
+
+    // Runs the query once per parameter set of the matrix, each as a test of
+    // its own named after the parameters. Every set runs, and the failures
+    // are reported together.
+    fn run_matrix(
+        &mut self,
+        client: &mut Client,
+        conn_str: &str,
+        mut reference: Option<&mut Client>,
+        variables: &mut HashMap<String, String>,
+        options: &Options,
+    ) -> Option<String> {
+        let mut failures = Vec::new();
+        for i in 0..self.matrix.len() {
+            let set = &self.matrix[i];
+            let label = format!("params {}", set.params.join(", "));
+            let mut test = Test {
+                name: Some(match &self.name {
+                    Some(name) => format!("{} ({})", name, label),
+                    None => label.clone(),
+                }),
+                expected_results: set.expected_results.clone(),
+                expected_error: set.expected_error.clone(),
+                matrix: Vec::new(),
+                params: Some(set.params.clone()),
+                ..self.clone()
+            };
+            if let Some(failure) = test.run_query(client, conn_str, reference.as_deref_mut(), variables, options) {
+                failures.push(format!("{}: {}", label, failure));
+            }
+            if options.record {
+                self.matrix[i].expected_results = test.expected_results;
+                self.matrix[i].expected_error = test.expected_error;
+            }
+        }
+        if failures.is_empty() {
+            None
+        } else {
+            Some(failures.join("\n"))
+        }
+    }
+
+    // Runs the query and collects what the expectations of the test can
+    // check. Fails only if the query timed out.
+    fn execute(&self, client: &mut Client, query: &str, options: &Options) -> Result<Response, String> {
+        let token = client.cancel_token();
+        let mut affected_rows = 0;
+        let mut columns = Vec::new();
+        let mut digest = if self.streams() { Some(Digest::default()) } else { None };
+        let copy_data = if is_copy(query, "FROM", "STDIN") { Some(self.copy_data()?) } else { None };
+        let copy_out = is_copy(query, "TO", "STDOUT");
+        let mut copied = None;
+        let mut failure = None;
+        let cancel = move || drop(token.cancel_query(NoTls));
+        let run = || {
+            let statement = client.prepare(query)?;
+            columns = statement.columns().iter().map(|c| format!("{} {}", c.name(), c.type_().name())).collect();
+            let params = match self.params.as_ref().map(|cells| bind_params(statement.params(), cells)) {
+                Some(Ok(params)) => params,
+                Some(Err(message)) => {
+                    failure = Some(message);
+                    return Ok(Vec::new());
+                }
+                None => Vec::new(),
+            };
+            let params: Vec<&(dyn ToSql + Sync)> = params.iter().map(|param| param.as_ref()).collect();
+            if let Some(data) = &copy_data {
+                let mut writer = client.copy_in(&statement)?;
+                // A failed write leaves the error of the server to `finish`.
+                let _ = writer.write_all(data);
+                affected_rows = writer.finish()?;
+                Ok(Vec::new())
+            } else if copy_out {
+                let mut data = String::new();
+                match client.copy_out(&statement)?.read_to_string(&mut data) {
+                    Ok(_) => copied = Some(parse_copy_rows(query, &data)),
+                    Err(err) => match copy_error(err) {
+                        Ok(err) => return Err(err),
+                        Err(message) => failure = Some(format!("COPY failed: {}", message)),
+                    },
+                }
+                Ok(Vec::new())
+            } else if self.expected_affected_rows.is_some() {
+                client.execute(&statement, &params).map(|n| {
+                    affected_rows = n;
+                    Vec::new()
+                })
+            } else if let Some(digest) = digest.as_mut() {
+                let mut rows = client.query_raw(&statement, params)?;
+                while let Some(row) = rows.next()? {
+                    digest.add(&render_row(&row));
+                }
+                Ok(Vec::new())
+            } else {
+                client.query(&statement, &params)
+            }
+        };
+        let cancel_after = self.cancel_after.as_deref().and_then(parse_duration);
+        let (result, timed_out) = match cancel_after {
+            Some(after) => with_cancel(after, options.timeout.unwrap_or(CANCEL_GRACE), cancel, run),
+            None => with_timeout(options.timeout, cancel, run),
+        };
+        match (cancel_after, timed_out) {
+            (None, true) => return Err(format!("Query timed out after {:?}", options.timeout.unwrap())),
+            (Some(after), false) => return Err(format!("Query finished before it was cancelled after {:?}", after)),
+            (Some(_), true) => {
+                if let Err(err) = client.batch_execute("SELECT 1") {
+                    return Err(format!("The connection does not work after the cancel: {}", error_message(&err)));
+                }
+            }
+            (None, false) => {}
+        }
+        if let Some(failure) = failure {
+            return Err(failure);
+        }
+        Ok(Response { result, columns, affected_rows, digest, copied })
+    }
+
+    // The data of `-- copy_in` and `-- copy_data`, in this order.
+    fn copy_data(&self) -> Result<Vec<u8>, String> {
+        let mut data = match &self.copy_in_path {
+            Some(path) => std::fs::read(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?,
+            None => Vec::new(),
+        };
+        for line in &self.copy_data {
+            data.extend_from_slice(line.as_bytes());
+            data.push(b'\n');
+        }
+        Ok(data)
+    }
+
+    // Whether the result is only checked through its `Digest`.
+    fn streams(&self) -> bool {
+        self.expected_row_count.is_some() || self.expected_checksum.is_some()
+    }
+
+    // Runs the query `--bench` more times, reports the median and 95th
+    // percentile of its latency, and checks the latter against
+    // `-- max_latency`.
+    fn bench(&self, client: &mut Client, query: &str, options: &Options) -> Result<(), String> {
+        let mut latencies = Vec::with_capacity(options.bench);
+        for _ in 0..options.bench {
+            let start = Instant::now();
+            self.execute(client, query, options)?;
+            latencies.push(start.elapsed());
+        }
+        latencies.sort();
+        let p95 = percentile(&latencies, 95);
+        options.log(&format!("Latency over {} runs: p50 {:?}, p95 {:?}", latencies.len(), percentile(&latencies, 50), p95));
+        match self.max_latency.as_deref().and_then(parse_duration) {
+            Some(max) if p95 > max => Err(format!("Latency p95 {:?} exceeds the maximum of {:?}", p95, max)),
+            _ => Ok(()),
+        }
+    }
+
+    // Stores the cells named by the `-- let` directives into `variables`.
+    fn capture(&self, response: &Response, variables: &mut HashMap<String, String>) -> Result<(), String> {
+        for capture in &self.captures {
+            let (name, i, j) = parse_capture(capture).ok_or_else(|| format!("Invalid capture: {}", capture))?;
+            let rows = match &response.result {
+                Ok(rows) => rows,
+                Err(_) => return Err(format!("Cannot capture ${}: the query failed", name)),
+            };
+            match rows.get(i) {
+                Some(row) if j < row.len() => {
+                    variables.insert(name.to_string(), cell_to_string(row, j));
+                }
+                _ => return Err(format!("Cannot capture ${}: the result has no cell [{}][{}]", name, i, j)),
+            }
+        }
+        Ok(())
+    }
+
+    // Returns a summary of the response if it meets every expectation, and
+    // the reason why not otherwise.
+    fn verify(&self, response: &Response, epsilon: f64) -> Result<String, String> {
+        let summary = match (&response.digest, &response.copied, &response.result) {
+            (Some(digest), _, Ok(_)) => self.check_digest(digest)?,
+            (_, Some(copied), Ok(_)) => self.check_copied(copied)?,
+            (_, _, Ok(rows)) if self.explain => self.check_plan(rows, &response.columns)?,
+            _ => self.check(&response.result, epsilon)?,
+        };
+        if response.result.is_ok() {
+            self.check_columns(&response.columns)?;
+            self.check_status(response.affected_rows)?;
+        }
+        Ok(summary)
+    }
+
+    // Checks the rows of a `COPY ... TO STDOUT`, which are only text.
+    fn check_copied(&self, rows: &[Vec<String>]) -> Result<String, String> {
+        if let Some(expected) = &self.expected_error {
+            return Err(format!("Expected error '{}', but the query succeeded", expected));
+        }
+        let matches = |row: &Vec<String>, expected: &Vec<String>| {
+            row.len() == expected.len() && row.iter().zip(expected).all(|(a, e)| cell_matches(e, a, CellKind::Other, 0.0))
+        };
+        if self.unordered {
+            let mut unmatched: Vec<&Vec<String>> = self.expected_results.iter().collect();
+            for row in rows {
+                match unmatched.iter().position(|expected| matches(row, expected)) {
+                    Some(k) => {
+                        unmatched.remove(k);
+                    }
+                    None => return Err(format!("Unexpected row:\n'{}'", row.join(","))),
+                }
+            }
+        } else {
+            for (row, expected) in rows.iter().zip(&self.expected_results) {
+                if !matches(row, expected) {
+                    return Err(format!("Expected:\n'{}'\nResult:\n'{}'", expected.join(","), row.join(",")));
+                }
+            }
+        }
+        if rows.len() != self.expected_results.len() {
+            return Err(format!("Expected {} rows, got {}", self.expected_results.len(), rows.len()));
+        }
+        Ok(format!("Returns {} rows", rows.len()))
+    }
+
+    // Checks the plan of an `-- explain` test, line by line.
+    fn check_plan(&self, rows: &[Row], columns: &[String]) -> Result<String, String> {
+        if let Some(expected) = &self.expected_error {
+            return Err(format!("Expected error '{}', but the query succeeded", expected));
+        }
+        let plan = normalize_plan(columns, &rows.iter().map(render_row).collect::<Vec<_>>());
+        let expected: Vec<String> = self.expected_results.iter().map(|row| row.join(",")).collect();
+        let same = |i: usize, k: usize| cell_matches(&expected[i], &plan[k], CellKind::Other, 0.0);
+        if plan.len() == expected.len() && (0..plan.len()).all(|i| same(i, i)) {
+            return Ok(format!("Returns a plan of {} lines", plan.len()));
+        }
+        Err(format!("The plan differs from the expectation\n{}", diff_rows(&expected, &plan, same)))
+    }
+
+    // Checks a streamed result against `-- rows:` and `-- checksum:`.
+    fn check_digest(&self, digest: &Digest) -> Result<String, String> {
+        if let Some(expected) = &self.expected_error {
+            return Err(format!("Expected error '{}', but the query succeeded", expected));
+        }
+        if let Some(expected) = self.expected_row_count.filter(|&rows| rows != digest.rows) {
+            return Err(format!("Expected {} rows, got {}", expected, digest.rows));
+        }
+        match &self.expected_checksum {
+            Some(expected) if !expected.eq_ignore_ascii_case(&digest.checksum()) => {
+                Err(format!("Expected checksum {}, got {}", expected, digest.checksum()))
+            }
+            _ => Ok(format!("Returns {} rows with checksum {}", digest.rows, digest.checksum())),
+        }
+    }
+
+    // Returns a summary of the result if it meets the expectations, and the
+    // reason why not otherwise.
+    fn check(&self, result: &Result<Vec<Row>, Error>, epsilon: f64) -> Result<String, String> {
+        let rows = match result {
+            Ok(rows) => rows,
+            Err(err) => return self.check_error(err),
+        };
+        if let Some(expected) = &self.expected_error {
+            return Err(format!("Expected error '{}', but the query succeeded", expected));
+        }
+        if rows.is_empty() {
+            if self.expected_results.is_empty() {
+                return Ok("Returns 0 rows".to_string());
+            }
+            return Err(format!("Expected {} rows, got 0", self.expected_results.len()));
+        }
+        if self.expected_results.is_empty() {
+            return Err(format!("Expected {} rows, got {}", self.expected_results.len(), rows.len()));
+        }
+        if rows[0].columns().len() != self.expected_results[0].len() {
+            return Err(format!(
+                "Expected {} columns, got {}",
+                self.expected_results[0].len(),
+                rows[0].columns().len()
+            ));
+        }
+        let json = self.json_columns(&rows[0])?;
+        if self.unordered {
+            return self.compare_unordered(rows, epsilon, &json);
+        }
+        let same = |i: usize, k: usize| {
+            let expected = &self.expected_results[i];
+            expected.len() == rows[k].len() && row_matches(&rows[k], expected, epsilon, &json)
+        };
+        if rows.len() == self.expected_results.len() && (0..rows.len()).all(|i| same(i, i)) {
+            return Ok(format!("Returns {} rows", rows.len()));
+        }
+        let expected: Vec<String> = self.expected_results.iter().map(|row| row.join(",")).collect();
+        let actual: Vec<String> = rows.iter().map(row_to_string).collect();
+        let diff = diff_rows(&expected, &actual, same);
+        if rows.len() != expected.len() {
+            return Err(format!("Expected {} rows, got {}\n{}", expected.len(), rows.len(), diff));
+        }
+        Err(format!("The rows differ from the expectation\n{}", diff))
+    }
+
+    // Tells for each column of `row` whether `-- json` names it.
+    fn json_columns(&self, row: &Row) -> Result<Vec<bool>, String> {
+        let json: Vec<bool> = (0..row.columns().len()).map(|j| self.json.iter().any(|c| *c == row.columns()[j].name())).collect();
+        match self.json.iter().find(|c| !(0..json.len()).any(|j| **c == row.columns()[j].name())) {
+            Some(column) => Err(format!("-- json: no column named {}", column)),
+            None => Ok(json),
+        }
+    }
+
+    // Pairs every returned row with a distinct expected row, ignoring order.
+    fn compare_unordered(&self, rows: &[Row], epsilon: f64, json: &[bool]) -> Result<String, String> {
+        let mut unmatched: Vec<&Vec<String>> = self.expected_results.iter().collect();
+        for row in rows {
+            match unmatched.iter().position(|expected| row_matches(row, expected, epsilon, json)) {
+                Some(k) => {
+                    unmatched.remove(k);
+                }
+                None => {
+                    let mut message = format!("Unexpected row:\n'{}'\nUnmatched expected rows:", row_to_string(row));
+                    for expected in &unmatched {
+                        message += &format!("\n'{}'", expected.join(","));
+                    }
+                    return Err(message);
+                }
+            }
+        }
+        if !unmatched.is_empty() {
+            return Err(format!("Expected {} rows, got {}", self.expected_results.len(), rows.len()));
+        }
+        Ok(format!("Returns {} rows", rows.len()))
+    }
+
+    // Replaces the expectations with what the server returned. Errors are
+    // recorded by their SQLSTATE.
+    fn record(&mut self, response: &Response) {
+        if self.expected_columns.is_some() && response.result.is_ok() {
+            self.expected_columns = Some(response.columns.clone());
+        }
+        if let Some(digest) = &response.digest {
+            self.expected_row_count = self.expected_row_count.map(|_| digest.rows);
+            self.expected_checksum = self.expected_checksum.as_ref().map(|_| digest.checksum());
+        }
+        self.record_status(response.affected_rows);
+        match &response.result {
+            Ok(_) if response.copied.is_some() => {
+                self.expected_error = None;
+                self.expected_results = response.copied.clone().unwrap_or_default();
+            }
+            Ok(rows) if self.explain => {
+                self.expected_error = None;
+                let plan = normalize_plan(&response.columns, &rows.iter().map(render_row).collect::<Vec<_>>());
+                self.expected_results = plan.into_iter().map(|line| vec![line]).collect();
+            }
+            Ok(rows) => {
+                self.expected_error = None;
+                self.expected_results = rows.iter().map(render_row).collect();
+            }
+            Err(err) => {
+                self.expected_results.clear();
+                self.expected_error = Some(match err.as_db_error() {
+                    Some(e) => e.code().code().to_string(),
+                    None => err.to_string(),
+                });
+            }
+        }
+    }
+
+    // Checks the row count of the command tag against `-- affected:`.
+    fn check_status(&self, affected_rows: u64) -> Result<(), String> {
+        match self.expected_affected_rows {
+            Some(expected) if expected != affected_rows => {
+                Err(format!("Expected {} affected rows, got {}", expected, affected_rows))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    // `columns` holds the full description of each column, which is cut
+    // down to the details given by the expectation.
+    fn check_columns(&self, columns: &[String]) -> Result<(), String> {
+        let expected = match &self.expected_columns {
+            Some(expected) => expected,
+            None => return Ok(()),
+        };
+        if expected.len() == columns.len() && expected.iter().zip(columns).all(|(e, a)| column_matches(e, a)) {
+            Ok(())
+        } else {
+            Err(format!("Expected columns [{}], got [{}]", expected.join(", "), columns.join(", ")))
+        }
+    }
+
+    // Only the expectations the test already has are recorded.
+    fn record_status(&mut self, affected_rows: u64) {
+        if self.expected_affected_rows.is_some() {
+            self.expected_affected_rows = Some(affected_rows);
+        }
+    }
+
+    // The expectation must equal the SQLSTATE or appear in the error message.
+    fn check_error(&self, err: &Error) -> Result<String, String> {
+        let expected = match &self.expected_error {
+            Some(expected) => expected,
+            None => return Err(error_message(err)),
+        };
+        let matched = match err.as_db_error() {
+            Some(e) => e.code().code() == expected || e.message().contains(expected.as_str()),
+            None => err.to_string().contains(expected.as_str()),
+        };
+        if !matched {
+            return Err(format!("Expected error '{}', got:\n{}", expected, error_message(err)));
+        }
+        Ok(format!("Returns expected error: {}", error_message(err)))
+    }
+}
+
+// A test file and the results of its last run.
+struct Suite {
+    filename: String,
+    file: TestFile,
+    // The statements of the included files. They are kept apart from the
+    // setup so that recording does not write them into the test file.
+    fixtures: Vec<String>,
+    results: Vec<TestResult>,
+}
+
+impl SuiteResults for Suite {
+    fn filename(&self) -> &str {
+        &self.filename
+    }
+
+    fn results(&self) -> &[TestResult] {
+        &self.results
+    }
+}
+
+impl Suite {
+    // Runs the setup, the tests and the teardown of the file on `client`,
+    // and on the reference server if there is one. Named sessions connect
+    // with `conn_str` and `reference_dsn`.
+    fn run(
+        &mut self,
+        client: &mut Client,
+        conn_str: &str,
+        mut reference: Option<&mut Client>,
+        reference_dsn: Option<&str>,
+        options: &Options,
+    ) -> bool {
+        let selected: Vec<bool> = self
+            .file
+            .tests
+            .iter()
+            .map(|test| {
+                let tags: Vec<&String> = self.file.tags.iter().chain(&test.tags).collect();
+                options.selects(&tags)
+            })
+            .collect();
+        if !selected.contains(&true) {
+            self.skip();
+            return true;
+        }
+        options.log(&format!("Running test file: {}", self.filename));
+        self.results.clear();
+        let mut variables = options.variables.clone();
+        let mut sessions = HashMap::new();
+        let mut reference_sessions = HashMap::new();
+        // Sessions cut by a test are opened through a proxy.
+        let disconnected: Vec<String> = self.file.tests.iter().filter(|t| t.disconnect).filter_map(|t| t.connection.clone()).collect();
+        let statements: Vec<String> = self.fixtures.iter().chain(&self.file.setup).map(|s| substitute(s, &variables)).collect();
+        let start = Instant::now();
+        let setup = run_statements(client, &statements, false, options).and_then(|()| match reference.as_deref_mut() {
+            Some(reference) => run_statements(reference, &statements, false, options).map_err(|f| format!("Reference: {}", f)),
+            None => Ok(()),
+        });
+        let mut passed = match setup {
+            Ok(()) => true,
+            Err(failure) => {
+                self.results.push(TestResult {
+                    name: "setup".to_string(),
+                    query: None,
+                    duration: start.elapsed(),
+                    outcome: Outcome::Failed(failure),
+                });
+                false
+            }
+        };
+        let mut running = passed;
+        for (test, selected) in self.file.tests.iter_mut().zip(selected) {
+            let name = test.display_name();
+            if !running || !selected {
+                self.results.push(TestResult { name, query: Some(test.query.clone()), duration: Duration::default(), outcome: Outcome::Skipped });
+                continue;
+            }
+            if options.deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                let failure = "Deadline exceeded".to_string();
+                print_failure(&failure);
+                self.results.push(TestResult { name, query: Some(test.query.clone()), duration: Duration::default(), outcome: Outcome::Failed(failure) });
+                passed = false;
+                running = false;
+                continue;
+            }
+            let start = Instant::now();
+            let connection = test.connection.clone();
+            let restarted = if test.restart {
+                sessions.clear();
+                reference_sessions.clear();
+                restart_server(options).and_then(|()| reconnect(conn_str, options.restart_timeout).map(|restarted| *client = restarted))
+            } else {
+                Ok(())
+            };
+            let failure = match (restarted, connection.as_deref()) {
+                (Err(failure), _) => {
+                    print_failure(&failure);
+                    Some(failure)
+                }
+                (Ok(()), None | Some("default")) => test.run(client, conn_str, reference.as_deref_mut(), &mut variables, options),
+                (Ok(()), Some(name)) => {
+                    let proxied = disconnected.iter().any(|d| d == name);
+                    let opened = session(&mut sessions, name, conn_str, proxied).and_then(|client| {
+                        let reference = match (&reference, reference_dsn) {
+                            (Some(_), Some(dsn)) => Some(
+                                session(&mut reference_sessions, name, dsn, proxied).map_err(|f| format!("Reference: {}", f))?,
+                            ),
+                            _ => None,
+                        };
+                        Ok((client, reference))
+                    });
+                    let failure = match opened {
+                        Ok((client, reference)) => test.run(client, conn_str, reference, &mut variables, options),
+                        Err(failure) => {
+                            print_failure(&failure);
+                            Some(failure)
+                        }
+                    };
+                    if test.disconnect {
+                        disconnect(sessions.remove(name));
+                        disconnect(reference_sessions.remove(name));
+                    }
+                    failure
+                }
+            };
+            let duration = start.elapsed();
+            let outcome = match failure {
+                // While recording, failing tests are expected and fixed up.
+                // With `--keep-going`, the file goes on after a failure.
+                Some(failure) => {
+                    passed = options.record;
+                    running = options.record || options.keep_going;
+                    Outcome::Failed(failure)
+                }
+                None => Outcome::Passed,
+            };
+            self.results.push(TestResult { name, query: Some(test.query.clone()), duration, outcome });
+        }
+        // Teardown also runs after a failure, so the server is left clean.
+        let start = Instant::now();
+        let statements: Vec<String> = self.file.teardown.iter().map(|s| substitute(s, &variables)).collect();
+        let mut teardown = run_statements(client, &statements, true, options);
+        if let Some(reference) = reference {
+            if let Err(failure) = run_statements(reference, &statements, true, options) {
+                let failure = format!("Reference: {}", failure);
+                teardown = Err(match teardown {
+                    Ok(()) => failure,
+                    Err(failures) => format!("{}\n{}", failures, failure),
+                });
+            }
+        }
+        if let Err(failure) = teardown {
+            self.results.push(TestResult {
+                name: "teardown".to_string(),
+                query: None,
+                duration: start.elapsed(),
+                outcome: Outcome::Failed(failure),
+            });
+            passed = false;
+        }
+        passed
+    }
+
+    // Runs the file like `run`. With `--isolate`, it runs in a database of
+    // its own, created before and dropped after on both servers.
+    fn run_isolated(&mut self, client: &mut Client, conn_str: &str, mut reference: Option<&mut Client>, options: &Options) -> bool {
+        let reference_dsn = options.reference_dsn.as_deref();
+        if !options.isolate {
+            return self.run(client, conn_str, reference, reference_dsn, options);
+        }
+        let database = isolated_database(&self.filename);
+        options.log(&format!("Creating database {}", database));
+        let isolated = create_database(client, conn_str, &database).and_then(|isolated| match (reference.as_deref_mut(), reference_dsn) {
+            (Some(reference), Some(dsn)) => match create_database(reference, dsn, &database) {
+                Ok(reference) => Ok((isolated, Some(reference))),
+                Err(failure) => Err(format!("Reference: {}", failure)),
+            },
+            _ => Ok((isolated, None)),
+        });
+        // The isolated connections are closed before dropping the database.
+        let mut passed = match isolated {
+            Ok(((mut client, conn_str), reference)) => {
+                let (mut reference, reference_dsn) = reference.unzip();
+                self.run(&mut client, &conn_str, reference.as_mut(), reference_dsn.as_deref(), options)
+            }
+            Err(failure) => {
+                self.fail("create database", failure);
+                false
+            }
+        };
+        let mut dropped = drop_database(client, &database);
+        if let Some(reference) = reference {
+            if let Err(failure) = drop_database(reference, &database) {
+                dropped = Err(format!("Reference: {}", failure));
+            }
+        }
+        if let Err(failure) = dropped {
+            print_failure(&failure);
+            self.results.push(TestResult { name: "drop database".to_string(), query: None, duration: Duration::default(), outcome: Outcome::Failed(failure) });
+            passed = false;
+        }
+        passed
+    }
+
+    // Marks every test as skipped, for files that were not run at all.
+    fn skip(&mut self) {
+        self.results = self
+            .file
+            .tests
+            .iter()
+            .map(|test| TestResult { name: test.display_name(), query: Some(test.query.clone()), duration: Duration::default(), outcome: Outcome::Skipped })
+            .collect();
+    }
+
+    fn fail(&mut self, name: &str, failure: String) {
+        print_failure(&failure);
+        self.results = vec![TestResult { name: name.to_string(), query: None, duration: Duration::default(), outcome: Outcome::Failed(failure) }];
+    }
+
+    // Writes the (recorded) tests back, keeping the format of the file.
+    fn save(&self) -> io::Result<()> {
+        let content = if self.filename.ends_with(".toml") {
+            toml::to_string(&self.file).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?
+        } else {
+            format_legacy_tests(&self.file, self.filename.ends_with(".sql"))
+        };
+        std::fs::write(&self.filename, content)
+    }
+}
+
+pub struct Tests {
+    conn_str: String,
+    client: Client,
+    suites: Vec<Suite>,
+    options: Options,
+}
+
+// The connection of a test, as seen by the assertions. Rows are rendered as
+// in the expectations.
+struct PgClient<'a>(&'a mut Client);
+
+impl SqlClient for PgClient<'_> {
+    fn protocol(&self) -> &'static str {
+        "pg"
+    }
+
+    fn execute(&mut self, statements: &str) -> Result<(), String> {
+        self.0.batch_execute(statements).map_err(|e| error_message(&e))
+    }
+
+    fn query(&mut self, query: &str) -> Result<Vec<Vec<String>>, String> {
+        let rows = self.0.query(query, &[]).map_err(|e| error_message(&e))?;
+        Ok(rows.iter().map(render_row).collect())
+    }
+}
+
+impl Tests {
+    pub fn new(ip: &str, port: u16, user: &str, password: &str, options: Options) -> Result<Self, Error> {
+        let (host, port) = match &options.socket {
+            Some(socket) => socket_host(socket, port),
+            None => (ip.to_string(), port),
+        };
+        let conn_str = format!("host={} port={} user={} password={} dbname=postgres", host, port, user, password);
+        let client = connect_with_retries(&options, || Client::connect(&conn_str, NoTls))?;
+        Ok(Tests { conn_str, client, suites: Vec::new(), options })
+    }
+
+    // Runs the test files in order, stopping at the first failure unless
+    // `--keep-going` is given. With `--jobs N`, N workers take the next file
+    // from a shared queue, so files must not depend on each other. The first
+    // worker reuses the initial connection, the others open their own.
+    pub fn run_tests(&mut self) -> bool {
+        let jobs = self.options.jobs.clamp(1, self.suites.len().max(1));
+        let failed = AtomicBool::new(false);
+        let queue = Mutex::new(self.suites.iter_mut());
+        let mut first = Some(&mut self.client);
+        let (failed, queue, conn_str, options) = (&failed, &queue, &self.conn_str, &self.options);
+        thread::scope(|scope| {
+            for _ in 0..jobs {
+                let mut borrowed = first.take();
+                scope.spawn(move || {
+                    let mut owned = None;
+                    let mut reference = None;
+                    loop {
+                        let suite = match queue.lock().unwrap().next() {
+                            Some(suite) => suite,
+                            None => break,
+                        };
+                        if failed.load(Ordering::SeqCst) && !options.keep_going {
+                            suite.skip();
+                            continue;
+                        }
+                        if borrowed.is_none() && owned.is_none() {
+                            match Client::connect(conn_str, NoTls) {
+                                Ok(client) => owned = Some(client),
+                                Err(err) => {
+                                    suite.fail("connect", error_message(&err));
+                                    failed.store(true, Ordering::SeqCst);
+                                    continue;
+                                }
+                            }
+                        }
+                        if let (Some(dsn), None) = (&options.reference_dsn, &reference) {
+                            match Client::connect(dsn, NoTls) {
+                                Ok(client) => reference = Some(client),
+                                Err(err) => {
+                                    suite.fail("connect", format!("Reference: {}", error_message(&err)));
+                                    failed.store(true, Ordering::SeqCst);
+                                    continue;
+                                }
+                            }
+                        }
+                        let client = borrowed.as_deref_mut().or(owned.as_mut()).unwrap();
+                        if !suite.run_isolated(client, conn_str, reference.as_mut(), options) {
+                            failed.store(true, Ordering::SeqCst);
+                        }
+                    }
+                });
+            }
+        });
+        !failed.load(Ordering::SeqCst)
+    }
+
+    // Prints the JUnit or TAP report of the last run, if one was requested.
+    pub fn print_report(&self) {
+        match self.options.format {
+            Format::Text if self.options.keep_going => print!("{}", summary_report(&self.suites)),
+            Format::Text => {}
+            Format::Junit => print!("{}", junit_report(&self.suites)),
+            Format::Tap => print!("{}", tap_report(&self.suites)),
+        }
+    }
+
+    // Writes the results of the last run to `--output`, if it was given.
+    pub fn write_output(&self) -> io::Result<()> {
+        match &self.options.output {
+            Some(path) => std::fs::write(path, json_report(&self.suites)),
+            None => Ok(()),
+        }
+    }
+
+    pub fn save_tests_to_files(&self) -> io::Result<()> {
+        for suite in &self.suites {
+            suite.save()?;
+            self.options.log(&format!("Rewrote {}", suite.filename));
+        }
+        Ok(())
+    }
+
+    // Runs the assertion after every passing test of the following runs.
+    pub fn add_assertion(&mut self, assertion: impl Assertion + 'static) {
+        self.options.assertions.push(Arc::new(assertion));
+    }
+
+    pub fn read_tests_from_file(&mut self, filename: &str) -> io::Result<()> {
+        let mut file = load_tests(filename)?;
+        let dir = Path::new(filename).parent().unwrap_or(Path::new(""));
+        for test in &mut file.tests {
+            test.copy_in_path = test.copy_in.as_ref().map(|path| dir.join(path));
+        }
+        let mut fixtures = Vec::new();
+        for include in &file.include {
+            fixtures.extend(read_fixture(&dir.join(include))?);
+        }
+        self.suites.push(Suite { filename: filename.to_string(), file, fixtures, results: Vec::new() });
+        Ok(())
+    }
+}
+
+// The host and port of a connection string for `--socket`. libpq takes the
+// directory of the socket, and finds the socket file from the port, so a
+// path to the file itself is split into both.
+fn socket_host(socket: &str, port: u16) -> (String, u16) {
+    let path = Path::new(socket);
+    let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+    match (path.parent(), name.strip_prefix(".s.PGSQL.").and_then(|p| p.parse().ok())) {
+        (Some(dir), Some(port)) => (dir.to_string_lossy().into_owned(), port),
+        _ => (socket.to_string(), port),
+    }
+}
+
+// Whether `query` is a COPY in the given direction, e.g. `FROM STDIN`.
+fn is_copy(query: &str, from: &str, to: &str) -> bool {
+    let words: Vec<String> = query.split_whitespace().map(|w| w.trim_end_matches(';').to_ascii_uppercase()).collect();
+    words.first().is_some_and(|w| w == "COPY") && words.windows(2).any(|w| w[0] == from && w[1] == to)
+}
+
+// Splits the output of a `COPY ... TO STDOUT` into rows of cells. The CSV
+// format is detected from the query; NULL, an unquoted empty field in CSV,
+// is rendered as `\N` like the text format does.
+fn parse_copy_rows(query: &str, data: &str) -> Vec<Vec<String>> {
+    let upper = query.to_ascii_uppercase();
+    let csv = upper.split(|c: char| !c.is_ascii_alphanumeric()).any(|w| w == "CSV");
+    let delimiter = match upper.find("DELIMITER") {
+        Some(i) => query[i + "DELIMITER".len()..].trim_start().strip_prefix('\'').and_then(|rest| rest.chars().next()),
+        None => None,
+    };
+    let delimiter = delimiter.unwrap_or(if csv { ',' } else { '\t' });
+    data.lines().map(|line| if csv { split_csv(line, delimiter) } else { split_copy_text(line, delimiter) }).collect()
+}
+
+// Splits a line of the text format, decoding its backslash escapes.
+fn split_copy_text(line: &str, delimiter: char) -> Vec<String> {
+    line.split(delimiter)
+        .map(|field| {
+            if field == NULL {
+                return NULL.to_string();
+            }
+            let mut cell = String::new();
+            let mut chars = field.chars();
+            while let Some(c) = chars.next() {
+                cell.push(match (c, c == '\\') {
+                    (_, true) => match chars.next() {
+                        Some('t') => '\t',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some(other) => other,
+                        None => '\\',
+                    },
+                    (c, false) => c,
+                });
+            }
+            cell
+        })
+        .collect()
+}
+
+// Splits a line of the CSV format, where quotes protect the delimiter and
+// are escaped by doubling them. Multi-line quoted values are not supported.
+fn split_csv(line: &str, delimiter: char) -> Vec<String> {
+    let mut cells = Vec::new();
+    let (mut cell, mut quoted, mut in_quotes) = (String::new(), false, false);
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                chars.next();
+                cell.push('"');
+            }
+            '"' => {
+                in_quotes = !in_quotes;
+                quoted = true;
+            }
+            c if c == delimiter && !in_quotes => {
+                cells.push(if cell.is_empty() && !quoted { NULL.to_string() } else { std::mem::take(&mut cell) });
+                quoted = false;
+            }
+            c => cell.push(c),
+        }
+    }
+    cells.push(if cell.is_empty() && !quoted { NULL.to_string() } else { cell });
+    cells
+}
+
+// The errors of COPY come through `io`, wrapping those of the server.
+fn copy_error(err: io::Error) -> Result<Error, String> {
+    let message = err.to_string();
+    match err.into_inner().map(|inner| inner.downcast::<Error>()) {
+        Some(Ok(err)) => Ok(*err),
+        _ => Err(message),
+    }
+}
+
+// Creates `database` from `client`, and connects to it with `conn_str`
+// pointed at it. Returns the connection and its connection string.
+fn create_database(client: &mut Client, conn_str: &str, database: &str) -> Result<(Client, String), String> {
+    client.batch_execute(&format!("CREATE DATABASE \"{}\"", database)).map_err(|e| error_message(&e))?;
+    let conn_str = with_dbname(conn_str, database);
+    let isolated = Client::connect(&conn_str, NoTls).map_err(|e| error_message(&e))?;
+    Ok((isolated, conn_str))
+}
+
+fn drop_database(client: &mut Client, database: &str) -> Result<(), String> {
+    client.batch_execute(&format!("DROP DATABASE IF EXISTS \"{}\"", database)).map_err(|e| error_message(&e))
+}
+
+// Points a connection string or a `postgresql://` URL at another database.
+fn with_dbname(conn_str: &str, database: &str) -> String {
+    let (scheme, rest) = match conn_str.split_once("://") {
+        Some((scheme, rest)) if scheme.starts_with("postgres") => (scheme, rest),
+        // A later `dbname` overrides an earlier one.
+        _ => return format!("{} dbname={}", conn_str, database),
+    };
+    let (location, params) = match rest.split_once('?') {
+        Some((location, params)) => (location, Some(params)),
+        None => (rest, None),
+    };
+    let authority = location.split('/').next().unwrap_or_default();
+    match params {
+        Some(params) => format!("{}://{}/{}?{}", scheme, authority, database, params),
+        None => format!("{}://{}/{}", scheme, authority, database),
+    }
+}
+
+// Returns the named session of a test file, connecting it with `conn_str`
+// on its first use.
+// A `proxied` session goes through a `Proxy`, so that `-- disconnect` can cut
+// it.
+fn session<'a>(
+    sessions: &'a mut HashMap<String, (Client, Option<Proxy>)>,
+    name: &str,
+    conn_str: &str,
+    proxied: bool,
+) -> Result<&'a mut Client, String> {
+    match sessions.entry(name.to_string()) {
+        Entry::Occupied(entry) => Ok(&mut entry.into_mut().0),
+        Entry::Vacant(entry) => {
+            let opened = if proxied {
+                connect_proxied(conn_str).map(|(client, proxy)| (client, Some(proxy)))
+            } else {
+                Client::connect(conn_str, NoTls).map(|client| (client, None)).map_err(|e| error_message(&e))
+            };
+            match opened {
+                Ok(session) => Ok(&mut entry.insert(session).0),
+                Err(err) => Err(format!("Cannot open connection {}: {}", name, err)),
+            }
+        }
+    }
+}
+
+// Connects with `conn_str` through a new `Proxy`.
+fn connect_proxied(conn_str: &str) -> Result<(Client, Proxy), String> {
+    let config: Config = conn_str.parse().map_err(|e| error_message(&e))?;
+    let host = match config.get_hosts().first() {
+        Some(Host::Tcp(host)) => host.clone(),
+        _ => return Err("-- disconnect needs a TCP connection".to_string()),
+    };
+    let port = config.get_ports().first().copied().unwrap_or(5432);
+    let proxy = Proxy::start(&host, port).map_err(|e| e.to_string())?;
+    let mut proxied = Config::new();
+    proxied.host("127.0.0.1").port(proxy.port);
+    if let Some(user) = config.get_user() {
+        proxied.user(user);
+    }
+    if let Some(password) = config.get_password() {
+        proxied.password(password);
+    }
+    if let Some(dbname) = config.get_dbname() {
+        proxied.dbname(dbname);
+    }
+    if let Some(options) = config.get_options() {
+        proxied.options(options);
+    }
+    if let Some(name) = config.get_application_name() {
+        proxied.application_name(name);
+    }
+    let client = proxied.connect(NoTls).map_err(|e| error_message(&e))?;
+    Ok((client, proxy))
+}
+
+// Connects again once the server accepts connections after `-- restart`,
+// waiting up to `timeout`.
+fn reconnect(conn_str: &str, timeout: Duration) -> Result<Client, String> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        match Client::connect(conn_str, NoTls) {
+            Ok(client) => return Ok(client),
+            Err(err) if Instant::now() >= deadline => {
+                return Err(format!("The server did not come back within {:?}: {}", timeout, error_message(&err)));
+            }
+            Err(_) => thread::sleep(WAIT_INTERVAL),
+        }
+    }
+}
+
+// Cuts a session removed by `-- disconnect`. The client is dropped after its
+// connection is gone, so it cannot say goodbye to the server.
+fn disconnect(session: Option<(Client, Option<Proxy>)>) {
+    if let Some((client, Some(proxy))) = session {
+        proxy.cut();
+        drop(client);
+    }
+}
+
+// The statements of a transaction step.
+fn transaction_statements(step: &str) -> Vec<String> {
+    match parse_transaction(step) {
+        Some((_, Some(level))) => vec![format!("BEGIN ISOLATION LEVEL {}", level)],
+        Some((kind, None)) => vec![kind],
+        None => Vec::new(),
+    }
+}
+
+// Runs setup or teardown statements. With `keep_going`, the remaining
+// statements still run after one fails. Returns the error messages.
+fn run_statements(client: &mut Client, statements: &[String], keep_going: bool, options: &Options) -> Result<(), String> {
+    let mut failures = Vec::new();
+    for statement in statements {
+        options.log(&format!("Running statement: {}", statement));
+        let token = client.cancel_token();
+        let (result, timed_out) = with_timeout(options.timeout, move || drop(token.cancel_query(NoTls)), || client.batch_execute(statement));
+        let failure = match result {
+            _ if timed_out => Some(format!("Statement timed out after {:?}", options.timeout.unwrap())),
+            Err(err) => Some(error_message(&err)),
+            Ok(()) => None,
+        };
+        if let Some(failure) = failure {
+            print_failure(&failure);
+            failures.push(failure);
+            if !keep_going {
+                break;
+            }
+        }
+    }
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(failures.join("\n"))
+    }
+}
+
+// Runs `query` while a watchdog thread calls `cancel` once `timeout` expires.
+// See `with_cancel`.
+fn with_timeout<T>(timeout: Option<Duration>, cancel: impl FnOnce() + Send, query: impl FnOnce() -> T) -> (T, bool) {
+    match timeout {
+        Some(timeout) => with_cancel(timeout, timeout, cancel, query),
+        None => (query(), false),
+    }
+}
+
+// Files ending in `.toml` use the structured format, anything else the
+// legacy one.
+fn load_tests(filename: &str) -> io::Result<TestFile> {
+    let file: TestFile = if filename.ends_with(".toml") {
+        let content = std::fs::read_to_string(filename)?;
+        toml::from_str(&content).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?
+    } else {
+        parse_legacy_tests(BufReader::new(File::open(filename)?), filename.ends_with(".sql"))?
+    };
+    for test in &file.tests {
+        match &test.transaction {
+            Some(step) if parse_transaction(step).is_none() => {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Invalid transaction step: {}", step)));
+            }
+            Some(step) if !test.query.is_empty() || !test.expected_results.is_empty() || test.expected_error.is_some() => {
+                let message = format!("A transaction step has no query nor expected result: {}", step);
+                return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+            }
+            None if test.query.is_empty() => {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "A test without a query"));
+            }
+            _ => {}
+        }
+        if let Some(set) = test.sets.iter().find(|s| parse_set(s).is_none()) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Invalid sets: {}", set)));
+        }
+        if test.time_zone.as_ref().is_some_and(|tz| tz.is_empty()) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Empty time_zone: {}", test.query)));
+        }
+        if let Some(capture) = test.captures.iter().find(|c| parse_capture(c).is_none()) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Invalid capture: {}", capture)));
+        }
+        if let Some(wait) = test.wait_until.as_ref().filter(|w| parse_duration(w).is_none()) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Invalid wait_until: {}", wait)));
+        }
+        if let Some(after) = test.cancel_after.as_ref().filter(|a| parse_duration(a).is_none()) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Invalid cancel_after: {}", after)));
+        }
+        if let Some(max) = test.max_latency.as_ref().filter(|m| parse_duration(m).is_none()) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Invalid max_latency: {}", max)));
+        }
+        if (test.copy_in.is_some() || !test.copy_data.is_empty()) && !is_copy(&test.query, "FROM", "STDIN") {
+            let message = format!("-- copy_in and -- copy_data need a COPY ... FROM STDIN query: {}", test.query);
+            return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+        }
+        if !test.matrix.is_empty() && (!test.expected_results.is_empty() || test.expected_error.is_some()) {
+            let message = format!("A test with parameter sets expects its rows and errors in each set: {}", test.query);
+            return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+        }
+        if test.matrix.iter().any(|set| set.params.len() != test.matrix[0].params.len()) {
+            let message = format!("The parameter sets differ in length: {}", test.query);
+            return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+        }
+        if test.disconnect && matches!(test.connection.as_deref(), None | Some("default")) {
+            let message = format!("-- disconnect needs a named -- connection: {}", test.query);
+            return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+        }
+        for cell in test.expected_results.iter().flatten() {
+            if let Some(pattern) = regex_pattern(cell) {
+                if let Err(err) = Regex::new(pattern) {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, err));
+                }
+            }
+        }
+    }
+    Ok(file)
+}
+
+// The legacy format: a query line, optional directives and expected rows,
+// terminated by a blank line. A block starting with `-- setup:` or
+// `-- teardown:` instead lists one statement per line, and an
+// `-- include: <path>` line names a fixture file run before the setup.
+// With `delimited` (a `.sql` file), the statements of the tests and of the
+// setup and teardown span lines up to the delimiter, `;` until a `DELIMITER`
+// line changes it, so stored routines and triggers can be written as usual.
+fn parse_legacy_tests<R: BufRead>(reader: R, delimited: bool) -> io::Result<TestFile> {
+    let mut file = TestFile::default();
+    let mut delimiter = delimited.then(|| ";".to_string());
+    let mut lines = reader.lines();
+    while let Some(Ok(line)) = lines.next() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let (Some(delimiter), Some(new)) = (delimiter.as_mut(), parse_delimiter(&line)) {
+            *delimiter = new;
+            continue;
+        }
+        if let Some(tags) = line.trim().strip_prefix("-- tags:") {
+            file.tags = parse_list(tags);
+            continue;
+        }
+        if let Some(path) = line.trim().strip_prefix("-- include:") {
+            file.include.push(path.trim().to_string());
+            continue;
+        }
+        let section = match line.trim() {
+            "-- setup:" => Some(&mut file.setup),
+            "-- teardown:" => Some(&mut file.teardown),
+            _ => None,
+        };
+        if let Some(statements) = section {
+            while let Some(Ok(line)) = lines.next() {
+                if line.trim().is_empty() {
+                    break;
+                }
+                match delimiter.as_mut() {
+                    Some(delimiter) => match parse_delimiter(&line) {
+                        Some(new) => *delimiter = new,
+                        None => statements.push(read_statement(line, &mut lines, delimiter)?),
+                    },
+                    None => statements.push(line),
+                }
+            }
+            continue;
+        }
+        let query = match &delimiter {
+            Some(delimiter) if parse_step_line(&line).is_none() => read_statement(line, &mut lines, delimiter)?,
+            _ => line,
+        };
+        let mut test = parse_step_line(&query).unwrap_or_else(|| Test::new(query, Vec::new()));
+        while let Some(Ok(line)) = lines.next() {
+            if line.trim().is_empty() {
+                break;
+            }
+            if let Some(error) = line.strip_prefix("-- error:") {
+                match test.matrix.last_mut() {
+                    Some(set) => set.expected_error = Some(error.trim().to_string()),
+                    None => test.expected_error = Some(error.trim().to_string()),
+                }
+                continue;
+            }
+            if let Some(params) = line.strip_prefix("-- params:") {
+                test.matrix.push(ParamSet { params: parse_list(params), ..ParamSet::default() });
+                continue;
+            }
+            if let Some(capture) = line.strip_prefix("-- let ") {
+                test.captures.push(capture.trim().to_string());
+                continue;
+            }
+            if let Some(set) = line.strip_prefix("-- sets:") {
+                test.sets.push(set.trim().to_string());
+                continue;
+            }
+            if let Some(time_zone) = line.strip_prefix("-- time_zone:") {
+                test.time_zone = Some(time_zone.trim().to_string());
+                continue;
+            }
+            if let Some(wait) = line.trim().strip_prefix("-- wait_until(").and_then(|rest| rest.strip_suffix(')')) {
+                test.wait_until = Some(wait.trim().to_string());
+                continue;
+            }
+            if let Some(after) = line.trim().strip_prefix("-- cancel_after(").and_then(|rest| rest.strip_suffix(')')) {
+                test.cancel_after = Some(after.trim().to_string());
+                continue;
+            }
+            if let Some(count) = line.strip_prefix("-- rows:") {
+                test.expected_row_count = Some(parse_count(count)?);
+                continue;
+            }
+            if let Some(checksum) = line.strip_prefix("-- checksum:") {
+                test.expected_checksum = Some(checksum.trim().to_string());
+                continue;
+            }
+            if let Some(path) = line.strip_prefix("-- copy_in:") {
+                test.copy_in = Some(path.trim().to_string());
+                continue;
+            }
+            if let Some(data) = line.strip_prefix("-- copy_data: ") {
+                test.copy_data.push(data.to_string());
+                continue;
+            }
+            if line.trim() == "-- disconnect" {
+                test.disconnect = true;
+                continue;
+            }
+            if line.trim() == "-- restart" {
+                test.restart = true;
+                continue;
+            }
+            if let Some(max) = line.strip_prefix("-- max_latency:") {
+                test.max_latency = Some(max.trim().to_string());
+                continue;
+            }
+            if let Some(name) = line.strip_prefix("-- connection:") {
+                test.connection = Some(name.trim().to_string());
+                continue;
+            }
+            if let Some(columns) = line.strip_prefix("-- columns:") {
+                test.expected_columns = Some(parse_list(columns));
+                continue;
+            }
+            if let Some(count) = line.strip_prefix("-- affected:") {
+                test.expected_affected_rows = Some(parse_count(count)?);
+                continue;
+            }
+            if let Some(tags) = line.trim().strip_prefix("-- tags:") {
+                test.tags = parse_list(tags);
+                continue;
+            }
+            if line.trim() == "-- sorted" || line.trim() == "-- unordered" {
+                test.unordered = true;
+                continue;
+            }
+            if line.trim() == "-- explain" {
+                test.explain = true;
+                continue;
+            }
+            if let Some(columns) = line.strip_prefix("-- json:") {
+                test.json = parse_list(columns);
+                continue;
+            }
+            let row = line.split(',').map(String::from).collect();
+            match test.matrix.last_mut() {
+                Some(set) => set.expected_results.push(row),
+                None => test.expected_results.push(row),
+            }
+        }
+        file.tests.push(test);
+    }
+    Ok(file)
+}
+
+// Parses `<parameter> = <value>` of `-- sets`.
+fn parse_set(set: &str) -> Option<(&str, &str)> {
+    let (parameter, value) = set.split_once('=')?;
+    let parameter = parameter.trim();
+    if parameter.is_empty() || !parameter.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.') {
+        return None;
+    }
+    Some((parameter, value.trim()))
+}
+
+// Reads the value of a run-time parameter for the session.
+fn read_parameter(client: &mut Client, parameter: &str) -> Result<String, String> {
+    match client.query_one("SELECT current_setting($1)", &[&parameter]) {
+        Ok(row) => Ok(row.get(0)),
+        Err(e) => Err(format!("Reading parameter {}: {}", parameter, error_message(&e))),
+    }
+}
+
+// Sets the TimeZone parameter for `-- time_zone`, and returns the previous
+// one.
+fn switch_time_zone(client: &mut Client, time_zone: &str) -> Result<String, String> {
+    let before = read_parameter(client, "TimeZone")?;
+    client
+        .execute("SELECT set_config('TimeZone', $1, false)", &[&time_zone])
+        .map_err(|e| format!("-- time_zone: {}", error_message(&e)))?;
+    Ok(before)
+}
+
+// Reads a `-- begin: <connection> [<isolation level>]`, `-- commit:
+// <connection>` or `-- rollback: <connection>` line into a step.
+fn parse_step_line(line: &str) -> Option<Test> {
+    let (kind, rest) = ["begin", "commit", "rollback"]
+        .iter()
+        .find_map(|kind| line.trim().strip_prefix(&format!("-- {}:", kind)).map(|rest| (*kind, rest)))?;
+    let mut words = rest.split_whitespace();
+    let mut test = Test::new(String::new(), Vec::new());
+    test.connection = words.next().map(str::to_string);
+    test.transaction = Some(std::iter::once(kind).chain(words).collect::<Vec<_>>().join(" "));
+    Some(test)
+}
+
+// With `delimited`, every statement is followed by the delimiter, which
+// becomes `//` for the statements containing a `;`.
+fn format_legacy_tests(file: &TestFile, delimited: bool) -> String {
+    let mut current = ";";
+    let mut delimit = |statement: &str| {
+        if !delimited {
+            return statement.to_string();
+        }
+        let delimiter = match statement {
+            s if !s.contains(';') => ";",
+            s if !s.contains("//") => "//",
+            _ => "$$",
+        };
+        let change = if delimiter == current { String::new() } else { format!("DELIMITER {}\n", delimiter) };
+        current = delimiter;
+        format!("{}{}{}", change, statement, delimiter)
+    };
+    let mut blocks = Vec::new();
+    for path in &file.include {
+        blocks.push(format!("-- include: {}", path));
+    }
+    if !file.tags.is_empty() {
+        blocks.push(format!("-- tags: {}", file.tags.join(", ")));
+    }
+    if !file.setup.is_empty() {
+        let setup: Vec<String> = file.setup.iter().map(|s| delimit(s)).collect();
+        blocks.push(format!("-- setup:\n{}", setup.join("\n")));
+    }
+    if !file.teardown.is_empty() {
+        let teardown: Vec<String> = file.teardown.iter().map(|s| delimit(s)).collect();
+        blocks.push(format!("-- teardown:\n{}", teardown.join("\n")));
+    }
+    for test in &file.tests {
+        let mut lines = vec![match &test.transaction {
+            Some(step) => format_step_line(step, test.connection.as_deref()),
+            None => delimit(&test.query),
+        }];
+        if let Some(name) = test.connection.as_ref().filter(|_| test.transaction.is_none()) {
+            lines.push(format!("-- connection: {}", name));
+        }
+        if test.disconnect {
+            lines.push("-- disconnect".to_string());
+        }
+        if test.restart {
+            lines.push("-- restart".to_string());
+        }
+        if let Some(path) = &test.copy_in {
+            lines.push(format!("-- copy_in: {}", path));
+        }
+        for data in &test.copy_data {
+            lines.push(format!("-- copy_data: {}", data));
+        }
+        if let Some(wait) = &test.wait_until {
+            lines.push(format!("-- wait_until({})", wait));
+        }
+        if let Some(after) = &test.cancel_after {
+            lines.push(format!("-- cancel_after({})", after));
+        }
+        if let Some(max) = &test.max_latency {
+            lines.push(format!("-- max_latency: {}", max));
+        }
+        if let Some(count) = test.expected_row_count {
+            lines.push(format!("-- rows: {}", count));
+        }
+        if let Some(checksum) = &test.expected_checksum {
+            lines.push(format!("-- checksum: {}", checksum));
+        }
+        if let Some(error) = &test.expected_error {
+            lines.push(format!("-- error: {}", error));
+        }
+        if test.unordered {
+            lines.push("-- unordered".to_string());
+        }
+        if test.explain {
+            lines.push("-- explain".to_string());
+        }
+        if !test.json.is_empty() {
+            lines.push(format!("-- json: {}", test.json.join(", ")));
+        }
+        for capture in &test.captures {
+            lines.push(format!("-- let {}", capture));
+        }
+        for set in &test.sets {
+            lines.push(format!("-- sets: {}", set));
+        }
+        if let Some(time_zone) = &test.time_zone {
+            lines.push(format!("-- time_zone: {}", time_zone));
+        }
+        if let Some(columns) = &test.expected_columns {
+            lines.push(format!("-- columns: {}", columns.join(", ")).trim_end().to_string());
+        }
+        if let Some(count) = test.expected_affected_rows {
+            lines.push(format!("-- affected: {}", count));
+        }
+        if !test.tags.is_empty() {
+            lines.push(format!("-- tags: {}", test.tags.join(", ")));
+        }
+        for row in &test.expected_results {
+            lines.push(row.join(","));
+        }
+        for set in &test.matrix {
+            lines.push(format!("-- params: {}", set.params.join(", ")));
+            if let Some(error) = &set.expected_error {
+                lines.push(format!("-- error: {}", error));
+            }
+            lines.extend(set.expected_results.iter().map(|row| row.join(",")));
+        }
+        blocks.push(lines.join("\n"));
+    }
+    blocks.join("\n\n") + "\n"
+}
+
+// Runs the test files and directories in `paths` as the command line does,
+// returning whether every test passed.
+pub fn run(ip: &str, port: u16, user: &str, password: &str, paths: &[String], options: Options) -> bool {
+    let mut test_files = Vec::new();
+    for path in paths {
+        match discover_test_files(path) {
+            Ok(files) => test_files.extend(files),
+            Err(err) => {
+                eprintln!("Failed to list test files in {}: {}", path, err);
+                return false;
+            }
+        }
+    }
+    if test_files.is_empty() {
+        eprintln!("No test files found");
+        return false;
+    }
+    // Every runner discovers the same files in the same order, so the shards
+    // do not overlap and cover every file.
+    if let Some((i, n)) = options.shard {
+        test_files = test_files.into_iter().enumerate().filter(|(k, _)| k % n == i - 1).map(|(_, file)| file).collect();
+        if test_files.is_empty() {
+            options.log(&format!("No test files in shard {}/{}", i, n));
+            return true;
+        }
+    }
+
+    let mut tests = match Tests::new(ip, port, user, password, options) {
+        Ok(tests) => tests,
+        Err(err) => {
+            eprintln!("Failed to connect to database: {}", error_message(&err));
+            return false;
+        }
+    };
+    for test_file in &test_files {
+        if let Err(err) = tests.read_tests_from_file(test_file) {
+            eprintln!("Failed to read test file {}: {}", test_file, err);
+            return false;
+        }
+    }
+
+    let passed = tests.run_tests();
+    tests.print_report();
+    if let Err(err) = tests.write_output() {
+        eprintln!("Failed to write the results: {}", err);
+        return false;
+    }
+    if !passed {
+        return false;
+    }
+    if tests.options.record {
+        if let Err(err) = tests.save_tests_to_files() {
+            eprintln!("Failed to write test file: {}", err);
+            return false;
+        }
+    }
+    true
+}
+
+// Prints a legacy test file in the structured format.
+pub fn convert(filename: &str) -> io::Result<()> {
+    let file = parse_legacy_tests(BufReader::new(File::open(filename)?), filename.ends_with(".sql"))?;
+    let content = toml::to_string(&file).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    print!("{}", content);
+    Ok(())
+}
+
+const FUZZ_CASTS: &[&str] = &["integer", "bigint", "text", "numeric(10, 2)", "double precision", "boolean", "date", "bytea"];
+
+// Writes a test file running `statement` again, named after the failure.
+fn save_reproducer(dir: &Path, seed: u64, i: usize, statement: &str, failure: &str) -> io::Result<PathBuf> {
+    let mut test = Test::new(statement.to_string(), Vec::new());
+    test.name = Some(format!("{} (seed {}, statement {})", failure, seed, i));
+    let file = TestFile { tests: vec![test], ..TestFile::default() };
+    let content = toml::to_string(&file).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    let path = dir.join(format!("fuzz-{}-{}.toml", seed, i));
+    std::fs::write(&path, content)?;
+    Ok(path)
+}
+
+// `fuzz` sends generated statements and checks that the server answers each
+// one, with rows or an error, without hanging or dropping the connection.
+// The statements that break it are saved as reproducers.
+pub fn fuzz(ip: &str, port: u16, user: &str, password: &str, fuzz: FuzzOptions) -> bool {
+    let options = &fuzz.options;
+    let conn_str = format!("host={} port={} user={} password={} dbname=postgres", ip, port, user, password);
+    let mut client = match Client::connect(&conn_str, NoTls) {
+        Ok(client) => client,
+        Err(err) => {
+            eprintln!("Failed to connect to database: {}", error_message(&err));
+            return false;
+        }
+    };
+    let tables = match fuzz_tables(&mut client) {
+        Ok(tables) => tables,
+        Err(err) => {
+            eprintln!("Failed to read the schema: {}", error_message(&err));
+            return false;
+        }
+    };
+    options.log(&format!("Fuzzing {} tables with seed {}", tables.len(), fuzz.seed));
+    let mut fuzzer = Fuzzer { rng: Rng::new(fuzz.seed), tables, casts: FUZZ_CASTS };
+    let mut failures = 0;
+    for i in 0..fuzz.iterations {
+        let statement = fuzzer.statement();
+        let failure = match fuzz_statement(&mut client, &statement, options) {
+            Some(failure) => failure,
+            None => continue,
+        };
+        failures += 1;
+        eprintln!("{}\n{}", failure, statement);
+        match save_reproducer(&fuzz.out, fuzz.seed, i, &statement, &failure) {
+            Ok(path) => options.log(&format!("Saved {}", path.display())),
+            Err(err) => eprintln!("Failed to save the reproducer: {}", err),
+        }
+        client = match Client::connect(&conn_str, NoTls) {
+            Ok(client) => client,
+            Err(err) => {
+                eprintln!("Cannot reconnect: {}", error_message(&err));
+                return false;
+            }
+        };
+    }
+    options.log(&format!("Ran {} statements, {} failed", fuzz.iterations, failures));
+    failures == 0
+}
+
+// The tables of the current schema, with their columns.
+fn fuzz_tables(client: &mut Client) -> Result<Vec<FuzzTable>, Error> {
+    let rows = client.query(
+        "SELECT table_name::text, column_name::text FROM information_schema.columns \
+         WHERE table_schema = current_schema() ORDER BY table_name, ordinal_position",
+        &[],
+    )?;
+    let quote = |name: String| format!("\"{}\"", name.replace('"', "\"\""));
+    let mut tables: Vec<FuzzTable> = Vec::new();
+    for row in rows {
+        let name = quote(row.get(0));
+        if tables.last().map(|table| &table.name) != Some(&name) {
+            tables.push(FuzzTable { name, columns: Vec::new() });
+        }
+        tables.last_mut().unwrap().columns.push(quote(row.get(1)));
+    }
+    Ok(tables)
+}
+
+// Runs a generated statement, and returns what went wrong if the server hung
+// or dropped the connection. Errors reported by the server are fine.
+fn fuzz_statement(client: &mut Client, statement: &str, options: &Options) -> Option<String> {
+    let token = client.cancel_token();
+    let (result, timed_out) = with_timeout(options.timeout, move || drop(token.cancel_query(NoTls)), || client.batch_execute(statement));
+    if timed_out {
+        return Some(format!("Query timed out after {:?}", options.timeout.unwrap()));
+    }
+    if let Err(err) = result {
+        if err.as_db_error().is_none() {
+            return Some(format!("Connection lost: {}", err));
+        }
+    }
+    // The server may have answered, and crashed right after.
+    match client.batch_execute("SELECT 1") {
+        Ok(()) => None,
+        Err(err) => Some(format!("Connection lost after the statement: {}", error_message(&err))),
+    }
+}
+
+// `load` fills a table of `--table-size` rows, then `--threads` workers run
+// point selects and updates on random rows for `--duration`, on connections
+// of their own. Reports the throughput and latency, and drops the table.
+pub fn load(ip: &str, port: u16, user: &str, password: &str, load: LoadOptions) -> bool {
+    let options = &load.options;
+    let conn_str = format!("host={} port={} user={} password={} dbname=postgres", ip, port, user, password);
+    let prepared = Client::connect(&conn_str, NoTls).and_then(|mut client| {
+        client.batch_execute(
+            "DROP TABLE IF EXISTS load_sbtest; \
+             CREATE TABLE load_sbtest (id int PRIMARY KEY, k int NOT NULL, c char(120) NOT NULL, pad char(60) NOT NULL)",
+        )?;
+        for rows in load_rows(load.table_size) {
+            client.batch_execute(&format!("INSERT INTO load_sbtest VALUES {}", rows))?;
+        }
+        Ok(client)
+    });
+    let mut client = match prepared {
+        Ok(client) => client,
+        Err(err) => {
+            eprintln!("Failed to prepare the load table: {}", error_message(&err));
+            return false;
+        }
+    };
+    options.log(&format!("Running {} threads for {:?} on {} rows", load.threads, load.duration, load.table_size));
+    let start = Instant::now();
+    let deadline = start + load.duration;
+    let mut stats = LoadStats::default();
+    let (conn_str, load) = (&conn_str, &load);
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..load.threads).map(|i| scope.spawn(move || load_worker(conn_str, load, i as u64, deadline))).collect();
+        for worker in workers {
+            stats.merge(worker.join().unwrap());
+        }
+    });
+    options.log(&stats.report(start.elapsed()));
+    if let Err(err) = client.batch_execute("DROP TABLE load_sbtest") {
+        eprintln!("Failed to drop the load table: {}", error_message(&err));
+    }
+    stats.errors == 0
+}
+
+fn load_worker(conn_str: &str, load: &LoadOptions, seed: u64, deadline: Instant) -> LoadStats {
+    let mut stats = LoadStats::default();
+    let connected = Client::connect(conn_str, NoTls).and_then(|mut client| {
+        let select = client.prepare("SELECT c FROM load_sbtest WHERE id = $1")?;
+        let update = client.prepare("UPDATE load_sbtest SET k = k + 1 WHERE id = $1")?;
+        Ok((client, select, update))
+    });
+    let (mut client, select, update) = match connected {
+        Ok(connected) => connected,
+        Err(err) => {
+            eprintln!("{}", error_message(&err));
+            stats.errors += 1;
+            return stats;
+        }
+    };
+    let mut rng = Rng::new(seed + 1);
+    while Instant::now() < deadline {
+        let id = 1 + rng.below(load.table_size as usize) as i32;
+        let read = rng.chance(load.read_percent);
+        let start = Instant::now();
+        let result = if read { client.query(&select, &[&id]).map(drop) } else { client.execute(&update, &[&id]).map(drop) };
+        match result {
+            Ok(()) if read => stats.reads += 1,
+            Ok(()) => stats.writes += 1,
+            Err(_) => {
+                stats.errors += 1;
+                continue;
+            }
+        }
+        stats.latencies.push(start.elapsed());
+    }
+    stats
+}
+
+// The options of `replicate`, following the address of the server.
+pub struct ReplicateOptions {
+    // The upstream PostgreSQL server MyDuck replicates from, as a connection
+    // string or a `postgresql://` URL.
+    pub source: Option<String>,
+    // An SQL file of statements run on the source, if any.
+    pub workload: Option<PathBuf>,
+    // The tables compared between the source and the server.
+    pub tables: Vec<String>,
+    // How long the server may take to catch up with the source.
+    pub catch_up: Duration,
+    // Compare the tables by checksum rather than row by row.
+    pub checksum: bool,
+    pub options: Options,
+}
+
+// `replicate` runs the `--workload` on the source, then waits until each of
+// `--tables` holds the same rows on the server as on the source, and shows
+// the diff of the tables that did not catch up in time. With `--checksum`,
+// the tables are compared by their row count and checksum instead, hashed
+// by the harness as for `-- checksum:` while the rows stream in, as
+// PostgreSQL has no CHECKSUM TABLE.
+pub fn replicate(ip: &str, port: u16, user: &str, password: &str, replicate: ReplicateOptions) -> bool {
+    let options = &replicate.options;
+    let conn_str = format!("host={} port={} user={} password={} dbname=postgres", ip, port, user, password);
+    let source = replicate.source.as_deref().unwrap();
+    let connected = Client::connect(&conn_str, NoTls)
+        .map_err(|e| error_message(&e))
+        .and_then(|client| Client::connect(source, NoTls).map(|source| (client, source)).map_err(|e| format!("Source: {}", error_message(&e))));
+    let (mut client, mut source) = match connected {
+        Ok(connected) => connected,
+        Err(failure) => {
+            eprintln!("Failed to connect to database: {}", failure);
+            return false;
+        }
+    };
+    if let Some(path) = &replicate.workload {
+        let statements = match read_fixture(path) {
+            Ok(statements) => statements,
+            Err(err) => {
+                eprintln!("Failed to read the workload: {}", err);
+                return false;
+            }
+        };
+        if let Err(failure) = run_statements(&mut source, &statements, false, options) {
+            eprintln!("Source: {}", failure);
+            return false;
+        }
+        options.log(&format!("Ran {} statements on the source", statements.len()));
+    }
+    let start = Instant::now();
+    let mut drifted = Vec::new();
+    for table in &replicate.tables {
+        let deadline = start + replicate.catch_up;
+        let synced = if replicate.checksum {
+            checksum_catch_up(&mut source, &mut client, table, deadline).map(|checksum| format!("checksum {}", checksum))
+        } else {
+            catch_up(&mut source, &mut client, table, deadline, options).map(|rows| format!("{} rows", rows))
+        };
+        match synced {
+            Ok(synced) => options.log(&format!("Table {}: {} in sync after {:?}", table, synced, start.elapsed())),
+            Err(failure) => {
+                print_failure(&format!("Table {} is not in sync after {:?}: {}", table, replicate.catch_up, failure));
+                drifted.push(table.as_str());
+            }
+        }
+    }
+    if !drifted.is_empty() {
+        eprintln!("{} of {} tables differ: {}", drifted.len(), replicate.tables.len(), drifted.join(", "));
+    }
+    drifted.is_empty()
+}
+
+// Compares `table` on both servers until it holds the same rows or the
+// deadline passes, and returns the number of rows or the last difference.
+fn catch_up(source: &mut Client, client: &mut Client, table: &str, deadline: Instant, options: &Options) -> Result<usize, String> {
+    let select = format!("SELECT * FROM {}", table);
+    loop {
+        let compared = source
+            .query(select.as_str(), &[])
+            .map_err(|e| format!("Source: {}", error_message(&e)))
+            .and_then(|expected| Ok((expected, client.query(select.as_str(), &[]).map_err(|e| error_message(&e))?)))
+            .and_then(|(expected, mut rows)| {
+                let mut expected: Vec<Vec<String>> = expected.iter().map(render_row).collect();
+                expected.sort();
+                rows.sort_by_cached_key(row_to_string);
+                let json = vec![false; rows.first().map_or(0, |row| row.len())];
+                let same = |i: usize, k: usize| expected[i].len() == rows[k].len() && row_matches(&rows[k], &expected[i], options.epsilon, &json);
+                if rows.len() == expected.len() && (0..rows.len()).all(|i| same(i, i)) {
+                    return Ok(rows.len());
+                }
+                let expected: Vec<String> = expected.iter().map(|row| row.join(",")).collect();
+                let actual: Vec<String> = rows.iter().map(row_to_string).collect();
+                Err(format!("{} rows on the source, {} on the server\n{}", expected.len(), actual.len(), diff_rows(&expected, &actual, same)))
+            });
+        match compared {
+            Err(_) if Instant::now() < deadline => thread::sleep(WAIT_INTERVAL),
+            compared => return compared,
+        }
+    }
+}
+
+// The options of `unicode`, following the address of the server.
+pub struct UnicodeOptions {
+    // The schema of the table the strings go through.
+    pub schema: String,
+    // Where the generated test file is written, if anywhere.
+    pub out: Option<PathBuf>,
+    pub options: Options,
+}
+
+// The tests of `unicode`: each string is inserted as a literal, then read
+// back with its UTF-8 bytes and its length in characters, looked up by
+// equality, which must not match the rows of other strings, and echoed
+// without a table.
+fn unicode_tests(schema: &str) -> TestFile {
+    let table = format!("{}.unicode_round_trip", schema);
+    let mut file = TestFile {
+        setup: vec![
+            format!("CREATE SCHEMA IF NOT EXISTS {}", schema),
+            format!("DROP TABLE IF EXISTS {}", table),
+            format!("CREATE TABLE {} (id int PRIMARY KEY, s text)", table),
+        ],
+        teardown: vec![format!("DROP TABLE {}", table)],
+        ..TestFile::default()
+    };
+    let strings = UNICODE_STRINGS.iter().enumerate().map(|(i, (label, s))| (i + 1, *label, *s));
+    let mut inserts = Vec::new();
+    let mut checks = Vec::new();
+    for (id, label, s) in strings {
+        let mut insert = Test::new(format!("INSERT INTO {} VALUES ({}, '{}')", table, id, s), Vec::new());
+        insert.name = Some(format!("{}: insert", label));
+        inserts.push(insert);
+        let results = vec![vec![s.to_string(), unicode_hex(s), s.chars().count().to_string()]];
+        let mut round_trip = Test::new(
+            format!("SELECT s, upper(encode(convert_to(s, 'UTF8'), 'hex')), char_length(s) FROM {} WHERE id = {}", table, id),
+            results,
+        );
+        round_trip.name = Some(format!("{}: round trip", label));
+        checks.push(round_trip);
+        let mut lookup = Test::new(format!("SELECT id FROM {} WHERE s = '{}'", table, s), vec![vec![id.to_string()]]);
+        lookup.name = Some(format!("{}: lookup", label));
+        checks.push(lookup);
+        let mut literal = Test::new(
+            format!("SELECT '{}'::text, upper(encode(convert_to('{}', 'UTF8'), 'hex'))", s, s),
+            vec![vec![s.to_string(), unicode_hex(s)]],
+        );
+        literal.name = Some(format!("{}: literal", label));
+        checks.push(literal);
+    }
+    file.tests = inserts.into_iter().chain(checks).collect();
+    file
+}
+
+// `unicode` generates the tests of `unicode_tests` and runs them, going on
+// after a failure so that every string that does not survive is reported.
+pub fn unicode(ip: &str, port: u16, user: &str, password: &str, unicode: UnicodeOptions) -> bool {
+    let file = unicode_tests(&unicode.schema);
+    if let Some(path) = &unicode.out {
+        let written = toml::to_string(&file).map_err(|e| e.to_string()).and_then(|content| std::fs::write(path, content).map_err(|e| e.to_string()));
+        match written {
+            Ok(()) => unicode.options.log(&format!("Wrote {}", path.display())),
+            Err(err) => {
+                eprintln!("Failed to write the tests: {}", err);
+                return false;
+            }
+        }
+    }
+    let mut tests = match Tests::new(ip, port, user, password, unicode.options) {
+        Ok(tests) => tests,
+        Err(err) => {
+            eprintln!("Failed to connect to database: {}", error_message(&err));
+            return false;
+        }
+    };
+    tests.suites.push(Suite { filename: "unicode".to_string(), file, fixtures: Vec::new(), results: Vec::new() });
+    let passed = tests.run_tests();
+    tests.print_report();
+    passed
+}
+
+// The columns of the `bulk` table after its id: the name, the SQL type, the
+// type the cells are compared by, and whether a value is quoted in SQL.
+const BULK_COLUMNS: &[(&str, &str, Type, bool)] = &[
+    ("i", "int", Type::INT4, false),
+    ("b", "bigint", Type::INT8, false),
+    ("d", "double precision", Type::FLOAT8, false),
+    ("n", "numeric(18, 4)", Type::NUMERIC, false),
+    ("s", "varchar(255)", Type::VARCHAR, true),
+    ("dt", "date", Type::DATE, true),
+    ("ts", "timestamp", Type::TIMESTAMP, true),
+    ("flag", "boolean", Type::BOOL, true),
+];
+
+// One value of each column of the `bulk` table, as the harness renders it,
+// with a NULL one time in twenty.
+fn bulk_row(rng: &mut Rng, id: u64) -> Vec<String> {
+    let mut row = vec![id.to_string()];
+    for (name, ..) in BULK_COLUMNS {
+        if rng.chance(5) {
+            row.push(NULL.to_string());
+            continue;
+        }
+        let date = format!("{:04}-{:02}-{:02}", 1970 + rng.below(130), 1 + rng.below(12), 1 + rng.below(28));
+        let value = match *name {
+            "i" => match rng.below(20) {
+                0 => i32::MIN.to_string(),
+                1 => i32::MAX.to_string(),
+                _ => (rng.next_u64() as i32).to_string(),
+            },
+            "b" => match rng.below(20) {
+                0 => i64::MIN.to_string(),
+                1 => i64::MAX.to_string(),
+                _ => (rng.next_u64() as i64).to_string(),
+            },
+            "d" => ((rng.next_u64() as i64 >> 20) as f64 / 1000.0).to_string(),
+            "n" => format_units(rng.next_u64() as i64 as i128 % 1_000_000_000_000_000_000),
+            "s" => (0..rng.below(64)).map(|_| BULK_CHARS[rng.below(BULK_CHARS.len())]).collect(),
+            "dt" => date,
+            "ts" => format!("{} {:02}:{:02}:{:02}.{:06}", date, rng.below(24), rng.below(60), rng.below(60), rng.below(1_000_000)),
+            _ => if rng.chance(50) { "t" } else { "f" }.to_string(),
+        };
+        row.push(value);
+    }
+    row
+}
+
+// The aggregates checked after the insert, as (expression, expected value,
+// type): the count of each column, the sum, min and max of the numbers, the
+// min and max of the dates, the bytes of the strings and the true flags.
+fn bulk_aggregates(rows: &[Vec<String>]) -> Vec<(String, String, Type)> {
+    let mut aggregates = vec![("count(*)".to_string(), rows.len().to_string(), Type::INT8)];
+    for (j, (name, _, ty, _)) in BULK_COLUMNS.iter().enumerate() {
+        let values: Vec<&str> = rows.iter().map(|row| row[j + 1].as_str()).filter(|v| *v != NULL).collect();
+        aggregates.push((format!("count({})", name), values.len().to_string(), Type::INT8));
+        let or_null = |value: Option<String>| value.unwrap_or_else(|| NULL.to_string());
+        let min_max = |aggregates: &mut Vec<(String, String, Type)>, values: &[&str], key: fn(&str) -> f64| {
+            let min = values.iter().min_by(|a, b| key(a).total_cmp(&key(b)));
+            let max = values.iter().max_by(|a, b| key(a).total_cmp(&key(b)));
+            aggregates.push((format!("min({})", name), or_null(min.map(|v| v.to_string())), ty.clone()));
+            aggregates.push((format!("max({})", name), or_null(max.map(|v| v.to_string())), ty.clone()));
+        };
+        match *name {
+            "i" | "b" => {
+                let sum = (!values.is_empty()).then(|| values.iter().map(|v| v.parse::<i128>().unwrap()).sum::<i128>().to_string());
+                aggregates.push((format!("sum({})", name), or_null(sum), Type::INT8));
+                let mut sorted: Vec<i128> = values.iter().map(|v| v.parse().unwrap()).collect();
+                sorted.sort();
+                aggregates.push((format!("min({})", name), or_null(sorted.first().map(|v| v.to_string())), Type::INT8));
+                aggregates.push((format!("max({})", name), or_null(sorted.last().map(|v| v.to_string())), Type::INT8));
+            }
+            "d" => {
+                let sum = (!values.is_empty()).then(|| values.iter().map(|v| v.parse::<f64>().unwrap()).sum::<f64>().to_string());
+                aggregates.push((format!("sum({})", name), or_null(sum), Type::FLOAT8));
+                min_max(&mut aggregates, &values, |v| v.parse().unwrap());
+            }
+            "n" => {
+                let units = |v: &str| v.replace('.', "").parse::<i128>().unwrap();
+                let sum = (!values.is_empty()).then(|| format_units(values.iter().map(|v| units(v)).sum()));
+                aggregates.push((format!("sum({})", name), or_null(sum), Type::NUMERIC));
+                min_max(&mut aggregates, &values, |v| v.parse().unwrap());
+            }
+            "s" => {
+                let bytes = (!values.is_empty()).then(|| values.iter().map(|v| v.len()).sum::<usize>().to_string());
+                aggregates.push((format!("sum(octet_length({}))", name), or_null(bytes), Type::INT8));
+            }
+            "dt" | "ts" => {
+                let mut sorted = values.clone();
+                sorted.sort();
+                aggregates.push((format!("min({})", name), or_null(sorted.first().map(|v| v.to_string())), ty.clone()));
+                aggregates.push((format!("max({})", name), or_null(sorted.last().map(|v| v.to_string())), ty.clone()));
+            }
+            _ => {
+                let flags = (!values.is_empty()).then(|| values.iter().filter(|v| **v == "t").count().to_string());
+                aggregates.push((format!("sum(CASE WHEN {} THEN 1 ELSE 0 END)", name), or_null(flags), Type::INT8));
+            }
+        }
+    }
+    aggregates
+}
+
+// `bulk` inserts `--rows` random rows of `BULK_COLUMNS` in batches, then
+// checks the aggregates of each column and reads every row back. Reports
+// the rate of the inserts, and drops the table.
+pub fn bulk(ip: &str, port: u16, user: &str, password: &str, bulk: BulkOptions) -> bool {
+    let options = &bulk.options;
+    let conn_str = format!("host={} port={} user={} password={} dbname=postgres", ip, port, user, password);
+    let mut client = match Client::connect(&conn_str, NoTls) {
+        Ok(client) => client,
+        Err(err) => {
+            eprintln!("Failed to connect to database: {}", error_message(&err));
+            return false;
+        }
+    };
+    options.log(&format!("Generating {} rows with seed {}", bulk.rows, bulk.seed));
+    let mut rng = Rng::new(bulk.seed);
+    let rows: Vec<Vec<String>> = (1..=bulk.rows).map(|id| bulk_row(&mut rng, id)).collect();
+    let columns: Vec<String> = BULK_COLUMNS.iter().map(|(name, ty, ..)| format!("{} {}", name, ty)).collect();
+    let start = Instant::now();
+    let inserted = client
+        .batch_execute(&format!("DROP TABLE IF EXISTS bulk_data; CREATE TABLE bulk_data (id bigint PRIMARY KEY, {})", columns.join(", ")))
+        .and_then(|()| {
+            for batch in rows.chunks(bulk.batch) {
+                let values: Vec<String> = batch.iter().map(|row| format!("({})", bulk_literals(row).join(", "))).collect();
+                client.batch_execute(&format!("INSERT INTO bulk_data VALUES {}", values.join(", ")))?;
+            }
+            Ok(())
+        });
+    if let Err(err) = inserted {
+        eprintln!("Failed to insert the rows: {}", error_message(&err));
+        return false;
+    }
+    let elapsed = start.elapsed();
+    options.log(&format!(
+        "Inserted {} rows in batches of {} in {:.1}s: {:.0} rows/s",
+        rows.len(),
+        bulk.batch,
+        elapsed.as_secs_f64(),
+        rows.len() as f64 / elapsed.as_secs_f64()
+    ));
+    let verified = bulk_verify(&mut client, &rows, options);
+    if let Err(err) = client.batch_execute("DROP TABLE bulk_data") {
+        eprintln!("Failed to drop the bulk table: {}", error_message(&err));
+    }
+    match verified {
+        Ok(summary) => {
+            options.log(&summary);
+            true
+        }
+        Err(failure) => {
+            print_failure(&failure);
+            false
+        }
+    }
+}
+
+// The SQL literals of a row of the `bulk` table.
+fn bulk_literals(row: &[String]) -> Vec<String> {
+    let quoted = std::iter::once(false).chain(BULK_COLUMNS.iter().map(|(.., quoted)| *quoted));
+    row.iter()
+        .zip(quoted)
+        .map(|(value, quoted)| match value.as_str() {
+            NULL => "NULL".to_string(),
+            value if quoted => format!("'{}'", value.replace('\'', "''")),
+            value => value.to_string(),
+        })
+        .collect()
+}
+
+// Checks the aggregates of the `bulk` table, then each of its rows, and
+// reports the first differences.
+fn bulk_verify(client: &mut Client, rows: &[Vec<String>], options: &Options) -> Result<String, String> {
+    let aggregates = bulk_aggregates(rows);
+    let expressions: Vec<&str> = aggregates.iter().map(|(expression, ..)| expression.as_str()).collect();
+    let row = client
+        .query_one(format!("SELECT {} FROM bulk_data", expressions.join(", ")).as_str(), &[])
+        .map_err(|e| format!("Aggregates: {}", error_message(&e)))?;
+    let epsilon = options.epsilon.max(1e-9);
+    let mut differences = Vec::new();
+    for (j, (expression, expected, ty)) in aggregates.iter().enumerate() {
+        let actual = cell_to_string(&row, j);
+        if !cell_matches(expected, &actual, cell_kind(ty), epsilon) {
+            differences.push(format!("{}: expected {}, got {}", expression, expected, actual));
+        }
+    }
+    let actual = client
+        .query("SELECT * FROM bulk_data ORDER BY id", &[])
+        .map_err(|e| format!("Reading the rows back: {}", error_message(&e)))?;
+    if actual.len() != rows.len() {
+        differences.push(format!("Expected {} rows, read {}", rows.len(), actual.len()));
+    }
+    let names: Vec<&str> = std::iter::once("id").chain(BULK_COLUMNS.iter().map(|c| c.0)).collect();
+    let types: Vec<&Type> = std::iter::once(&Type::INT8).chain(BULK_COLUMNS.iter().map(|c| &c.2)).collect();
+    for (expected, row) in rows.iter().zip(&actual) {
+        for (j, value) in expected.iter().enumerate() {
+            let cell = cell_to_string(row, j);
+            if !cell_matches(value, &cell, cell_kind(types[j]), epsilon) {
+                differences.push(format!("Row {}, column {}: expected {}, got {}", expected[0], names[j], value, cell));
+            }
+        }
+    }
+    if differences.is_empty() {
+        return Ok(format!("{} rows and {} aggregates match", rows.len(), aggregates.len()));
+    }
+    let count = differences.len();
+    differences.truncate(10);
+    Err(format!("{} differences, the first ones:\n{}", count, differences.join("\n")))
+}
+
+// Compares the checksum of `table` on both servers until they agree or the
+// deadline passes, and returns the checksum or the last difference.
+fn checksum_catch_up(source: &mut Client, client: &mut Client, table: &str, deadline: Instant) -> Result<String, String> {
+    loop {
+        let compared = table_checksum(source, table)
+            .map_err(|e| format!("Source: {}", error_message(&e)))
+            .and_then(|expected| Ok((expected, table_checksum(client, table).map_err(|e| error_message(&e))?)))
+            .and_then(|(expected, actual)| {
+                if expected != actual {
+                    return Err(format!("{} on the source, {} on the server", expected, actual));
+                }
+                Ok(actual)
+            });
+        match compared {
+            Err(_) if Instant::now() < deadline => thread::sleep(WAIT_INTERVAL),
+            compared => return compared,
+        }
+    }
+}
+
+fn table_checksum(client: &mut Client, table: &str) -> Result<String, Error> {
+    let mut digest = Digest::default();
+    let mut rows = client.query_raw(format!("SELECT * FROM {}", table).as_str(), std::iter::empty::<&dyn ToSql>())?;
+    while let Some(row) = rows.next()? {
+        digest.add(&render_row(&row));
+    }
+    Ok(format!("{} ({} rows)", digest.checksum(), digest.rows))
+}
@@ -0,0 +1,298 @@
+extern crate clap;
+extern crate pg_test;
+
+use std::path::PathBuf;
+use std::process::exit;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use clap::{Args, Parser};
+use pg_test::{BulkOptions, Format, FuzzOptions, LoadOptions, Options, ReplicateOptions, UnicodeOptions};
+
+// The subcommands, recognized by the first argument. Anything else runs test
+// files, keeping `pg_test <ip> <port> <user> <password> <testFile>...`.
+const SUBCOMMANDS: &[&str] = &["convert", "fuzz", "load", "bulk", "replicate", "unicode"];
+
+/// Runs test files against a PostgreSQL-compatible server.
+#[derive(Parser)]
+#[command(name = "pg_test", after_help = "Other commands: pg_test <convert|fuzz|load|bulk|replicate|unicode> --help")]
+struct Run {
+    #[command(flatten)]
+    server: Server,
+    /// Test files, or directories searched for them
+    #[arg(required = true)]
+    files: Vec<String>,
+    /// Allowed relative difference between float and numeric values
+    #[arg(long, default_value_t = 1e-6)]
+    epsilon: f64,
+    /// Replace the expectations of failing tests with the actual results
+    #[arg(long)]
+    record: bool,
+    /// Run every test after a failure, and print a summary
+    #[arg(long)]
+    keep_going: bool,
+    /// Also write the results there as JSON
+    #[arg(long, value_name = "FILE")]
+    output: Option<String>,
+    /// Only run the i-th of every n test files, 1-based
+    #[arg(long, value_name = "I/N", value_parser = shard)]
+    shard: Option<(usize, usize)>,
+    /// How the results are reported: text, junit or tap
+    #[arg(long, default_value = "text", value_parser = format)]
+    format: Format,
+    /// Number of test files run concurrently
+    #[arg(long, default_value_t = 1)]
+    jobs: usize,
+    /// Run each test file in a database of its own
+    #[arg(long)]
+    isolate: bool,
+    /// Run every passing query returning rows N more times
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    bench: usize,
+    #[command(flatten)]
+    timeout: Timeout,
+    /// Fail the tests not started after this many seconds
+    #[arg(long, value_name = "SECONDS")]
+    deadline: Option<u64>,
+    /// How many times connecting to the server is retried
+    #[arg(long, default_value_t = 0)]
+    connect_retries: u32,
+    /// The wait before the first retry, in seconds or as a duration such as 500ms
+    #[arg(long, default_value = "1", value_parser = duration)]
+    connect_backoff: Duration,
+    /// Connect through this Unix domain socket, or the directory holding it
+    #[arg(long, value_name = "PATH")]
+    socket: Option<String>,
+    /// Run through sh -c by -- restart to restart the server
+    #[arg(long, value_name = "COMMAND")]
+    restart_command: Option<String>,
+    /// How long the server may take to come back after a restart
+    #[arg(long, default_value = "60", value_parser = duration)]
+    restart_timeout: Duration,
+    /// A variable used like a captured one
+    #[arg(long = "var", value_name = "NAME=VALUE", value_parser = variable)]
+    variables: Vec<(String, String)>,
+    /// A PostgreSQL server whose responses replace the expectations
+    #[arg(long, value_name = "DSN")]
+    reference_dsn: Option<String>,
+    /// Only run tests carrying one of these tags
+    #[arg(long = "include-tag", value_name = "TAG,...")]
+    include_tags: Vec<String>,
+    /// Never run tests carrying one of these tags
+    #[arg(long = "exclude-tag", value_name = "TAG,...")]
+    exclude_tags: Vec<String>,
+}
+
+#[derive(Parser)]
+#[command(name = "pg_test")]
+enum Command {
+    /// Prints a legacy test file in the structured format
+    Convert { file: String },
+    /// Runs random statements, writing a reproducer for each crash
+    Fuzz {
+        #[command(flatten)]
+        server: Server,
+        #[arg(long, default_value_t = 1000)]
+        iterations: usize,
+        /// Seed of the generator, the current time by default
+        #[arg(long)]
+        seed: Option<u64>,
+        #[command(flatten)]
+        timeout: Timeout,
+        /// Where reproducers are written
+        #[arg(long, value_name = "DIR", default_value = ".")]
+        out: PathBuf,
+    },
+    /// Runs point selects and updates from concurrent clients
+    Load {
+        #[command(flatten)]
+        server: Server,
+        #[arg(long, default_value_t = 4)]
+        threads: usize,
+        /// In seconds or as a duration such as 2m
+        #[arg(long, default_value = "10", value_parser = duration)]
+        duration: Duration,
+        /// The share of point selects, the rest being updates
+        #[arg(long, default_value_t = 80, value_parser = clap::value_parser!(u64).range(0..=100))]
+        read_percent: u64,
+        #[arg(long, value_name = "ROWS", default_value_t = 10000, value_parser = clap::value_parser!(u32).range(1..))]
+        table_size: u32,
+    },
+    /// Inserts random rows in batches and checks aggregates over them
+    Bulk {
+        #[command(flatten)]
+        server: Server,
+        #[arg(long, default_value_t = 100000)]
+        rows: u64,
+        /// The rows of each INSERT statement
+        #[arg(long, default_value_t = 1000, value_parser = clap::value_parser!(u64).range(1..))]
+        batch: u64,
+        /// Seed of the generator, the current time by default
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+    /// Checks that tables replicated from a source match it
+    Replicate {
+        #[command(flatten)]
+        server: Server,
+        /// The upstream PostgreSQL server
+        #[arg(long, value_name = "DSN")]
+        source: String,
+        /// The tables compared between the source and the server
+        #[arg(long, value_name = "TABLE,...", required = true)]
+        tables: Vec<String>,
+        /// An SQL file of statements run on the source
+        #[arg(long, value_name = "FILE")]
+        workload: Option<PathBuf>,
+        /// Compare the tables by checksum rather than row by row
+        #[arg(long)]
+        checksum: bool,
+        /// How long the server may take to catch up, in seconds or as a duration
+        #[arg(long, default_value = "60", value_parser = duration)]
+        catch_up: Duration,
+        #[command(flatten)]
+        timeout: Timeout,
+    },
+    /// Checks that multi-byte strings round-trip byte for byte
+    Unicode {
+        #[command(flatten)]
+        server: Server,
+        /// The schema of the table the strings go through
+        #[arg(long, default_value = "test")]
+        schema: String,
+        /// Where the generated test file is written
+        #[arg(long, value_name = "FILE")]
+        out: Option<PathBuf>,
+        #[command(flatten)]
+        timeout: Timeout,
+    },
+}
+
+#[derive(Args)]
+struct Server {
+    ip: String,
+    port: u16,
+    user: String,
+    password: String,
+}
+
+#[derive(Args)]
+struct Timeout {
+    /// Cancel queries running longer than this many seconds, 0 for never
+    #[arg(long = "timeout", value_name = "SECONDS", default_value_t = 30)]
+    seconds: u64,
+}
+
+impl Timeout {
+    fn options(&self) -> Options {
+        let timeout = if self.seconds == 0 { None } else { Some(Duration::from_secs(self.seconds)) };
+        Options { timeout, ..Options::default() }
+    }
+}
+
+fn duration(value: &str) -> Result<Duration, String> {
+    value
+        .parse()
+        .ok()
+        .map(Duration::from_secs)
+        .or_else(|| pg_test::parse_duration(value))
+        .ok_or_else(|| "expected a number of seconds or a duration such as 500ms".to_string())
+}
+
+fn shard(value: &str) -> Result<(usize, usize), String> {
+    pg_test::parse_shard(value).ok_or_else(|| "expected i/n, with 1 <= i <= n".to_string())
+}
+
+fn format(value: &str) -> Result<Format, String> {
+    match value {
+        "text" => Ok(Format::Text),
+        "junit" => Ok(Format::Junit),
+        "tap" => Ok(Format::Tap),
+        _ => Err("expected one of text, junit or tap".to_string()),
+    }
+}
+
+fn variable(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((name, value)) => Ok((name.to_string(), value.to_string())),
+        None => Err("expected name=value".to_string()),
+    }
+}
+
+fn seed(seed: Option<u64>) -> u64 {
+    seed.unwrap_or_else(|| SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos() as u64)
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() < 2 || !SUBCOMMANDS.contains(&args[1].as_str()) {
+        let run = Run::parse();
+        let options = Options {
+            epsilon: run.epsilon,
+            record: run.record,
+            keep_going: run.keep_going,
+            output: run.output,
+            shard: run.shard,
+            format: run.format,
+            jobs: run.jobs,
+            bench: run.bench,
+            isolate: run.isolate,
+            include_tags: run.include_tags.iter().flat_map(|tags| pg_test::parse_list(tags)).collect(),
+            exclude_tags: run.exclude_tags.iter().flat_map(|tags| pg_test::parse_list(tags)).collect(),
+            variables: run.variables.into_iter().collect(),
+            reference_dsn: run.reference_dsn,
+            deadline: run.deadline.map(|seconds| Instant::now() + Duration::from_secs(seconds)),
+            connect_retries: run.connect_retries,
+            connect_backoff: run.connect_backoff,
+            socket: run.socket,
+            restart_command: run.restart_command,
+            restart_timeout: run.restart_timeout,
+            ..run.timeout.options()
+        };
+        let server = &run.server;
+        if !pg_test::run(&server.ip, server.port, &server.user, &server.password, &run.files, options) {
+            exit(1);
+        }
+        return;
+    }
+
+    let passed = match Command::parse() {
+        Command::Convert { file } => match pg_test::convert(&file) {
+            Ok(()) => true,
+            Err(err) => {
+                eprintln!("Failed to convert test file: {}", err);
+                false
+            }
+        },
+        Command::Fuzz { server, iterations, seed: s, timeout, out } => {
+            let fuzz = FuzzOptions { iterations, seed: seed(s), out, options: timeout.options() };
+            pg_test::fuzz(&server.ip, server.port, &server.user, &server.password, fuzz)
+        }
+        Command::Load { server, threads, duration, read_percent, table_size } => {
+            let load = LoadOptions { threads, duration, read_percent, table_size, options: Options::default() };
+            pg_test::load(&server.ip, server.port, &server.user, &server.password, load)
+        }
+        Command::Bulk { server, rows, batch, seed: s } => {
+            let bulk = BulkOptions { rows, batch: batch as usize, seed: seed(s), options: Options::default() };
+            pg_test::bulk(&server.ip, server.port, &server.user, &server.password, bulk)
+        }
+        Command::Replicate { server, source, tables, workload, checksum, catch_up, timeout } => {
+            let replicate = ReplicateOptions {
+                source: Some(source),
+                workload,
+                tables: tables.iter().flat_map(|tables| pg_test::parse_list(tables)).collect(),
+                catch_up,
+                checksum,
+                options: timeout.options(),
+            };
+            pg_test::replicate(&server.ip, server.port, &server.user, &server.password, replicate)
+        }
+        Command::Unicode { server, schema, out, timeout } => {
+            let options = Options { keep_going: true, ..timeout.options() };
+            let unicode = UnicodeOptions { schema, out, options };
+            pg_test::unicode(&server.ip, server.port, &server.user, &server.password, unicode)
+        }
+    };
+    if !passed {
+        exit(1);
+    }
+}
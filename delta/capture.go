@@ -0,0 +1,134 @@
+package delta
+
+import (
+	stdsql "database/sql"
+	"encoding/hex"
+	"sync"
+
+	"github.com/apache/arrow-go/v18/arrow"
+	"github.com/apache/arrow-go/v18/arrow/array"
+	"github.com/apecloud/myduckserver/binlog"
+	"github.com/dolthub/go-mysql-server/sql"
+)
+
+// ChangeSink receives the row changes flushed to the tables, e.g., to publish them to logical replication
+// subscribers. The changes are passed in the transaction of the flush, so that they are committed with the tables.
+type ChangeSink interface {
+	// Captures returns whether the changes to the table are passed to Capture.
+	Captures(dbName, tableName string) bool
+	// Capture receives the changes to the captured tables in a flush.
+	Capture(ctx *sql.Context, tx *stdsql.Tx, changes []TableChanges) error
+}
+
+// TableChanges are the changes to a table in a flush, in the order they are applied.
+type TableChanges struct {
+	DBName    string
+	TableName string
+	Schema    sql.Schema
+	Rows      []RowChange
+}
+
+// RowChange is a change to a row, with the values of the columns in their text forms, where nil stands for NULL.
+// Old is the row before a delete or an update, and New is the row after an insert or an update.
+type RowChange struct {
+	Action binlog.RowEventType
+	Old    []*string
+	New    []*string
+}
+
+var changeSink struct {
+	sync.RWMutex
+	sink ChangeSink
+}
+
+// SetChangeSink sets the sink of the changes flushed by all the controllers, or removes it if |sink| is nil.
+func SetChangeSink(sink ChangeSink) {
+	changeSink.Lock()
+	defer changeSink.Unlock()
+	changeSink.sink = sink
+}
+
+// Captured returns whether the changes flushed to the table are passed to the change sink, if any.
+func Captured(dbName, tableName string) bool {
+	sink := getChangeSink()
+	return sink != nil && sink.Captures(dbName, tableName)
+}
+
+func getChangeSink() ChangeSink {
+	changeSink.RLock()
+	defer changeSink.RUnlock()
+	return changeSink.sink
+}
+
+// captureChanges converts the delta record of the table to its row changes. The rows of a rows event are its
+// deleted rows followed by its inserted ones, so an event of as many deletes as inserts is an update of the rows,
+// paired in order. The events of a statement are told apart by the deletes that follow its inserts, so that each
+// event of an update of many rows is paired by itself.
+func captureChanges(table tableIdentifier, appender *DeltaAppender, record arrow.Record) TableChanges {
+	var (
+		offset  = appender.NumAugmentedFields()
+		actions = record.Column(0).(*array.Int8)
+		seqs    = record.Column(4).(*array.Uint64)
+		stmts   = record.Column(5).(*array.Uint64)
+		n       = int(record.NumRows())
+		rows    = make([]RowChange, 0, n)
+	)
+	for start := 0; start < n; {
+		sameStatement := func(i int) bool {
+			return i < n && seqs.Value(i) == seqs.Value(start) && stmts.Value(i) == stmts.Value(start)
+		}
+		deletes := start
+		for sameStatement(deletes) && actions.Value(deletes) == int8(binlog.DeleteRowEvent) {
+			deletes++
+		}
+		end := deletes
+		for sameStatement(end) && actions.Value(end) != int8(binlog.DeleteRowEvent) {
+			end++
+		}
+		if deletes-start == end-deletes {
+			for i := start; i < deletes; i++ {
+				rows = append(rows, RowChange{
+					Action: binlog.UpdateRowEvent,
+					Old:    rowValues(record, offset, i),
+					New:    rowValues(record, offset, i+deletes-start),
+				})
+			}
+		} else {
+			for i := start; i < end; i++ {
+				if actions.Value(i) == int8(binlog.DeleteRowEvent) {
+					rows = append(rows, RowChange{Action: binlog.DeleteRowEvent, Old: rowValues(record, offset, i)})
+				} else {
+					rows = append(rows, RowChange{Action: binlog.InsertRowEvent, New: rowValues(record, offset, i)})
+				}
+			}
+		}
+		start = end
+	}
+	return TableChanges{
+		DBName:    table.dbName,
+		TableName: table.tableName,
+		Schema:    appender.BaseSchema(),
+		Rows:      rows,
+	}
+}
+
+// rowValues returns the text forms of the values in the row of the record, skipping the augmented fields.
+// The binary values are in the hex format of bytea.
+func rowValues(record arrow.Record, offset int, row int) []*string {
+	values := make([]*string, int(record.NumCols())-offset)
+	for i := range values {
+		column := record.Column(offset + i)
+		if column.IsNull(row) {
+			continue
+		}
+		var value string
+		switch c := column.(type) {
+		case *array.Binary:
+			value = `\x` + hex.EncodeToString(c.Value(row))
+		default:
+			value = c.ValueStr(row)
+		}
+		values[i] = &value
+	}
+	return values
+}
@@ -32,6 +32,8 @@ type DeltaController struct {
 	mutex  sync.Mutex
 	tables map[tableIdentifier]*DeltaAppender
 	seed   maphash.Seed
+	// captured are the changes of the current flush to be passed to the change sink.
+	captured []TableChanges
 }
 
 func NewController() *DeltaController {
@@ -99,6 +101,7 @@ func (c *DeltaController) Flush(ctx *sql.Context, conn *stdsql.Conn, tx *stdsql.
 	//  https://github.com/duckdb/duckdb/issues/14133
 
 	var stats FlushStats
+	c.captured = nil
 
 	for table, appender := range c.tables {
 		deltaRowCount := appender.RowCount()
@@ -120,6 +123,12 @@ func (c *DeltaController) Flush(ctx *sql.Context, conn *stdsql.Conn, tx *stdsql.
 		}
 	}
 
+	if sink := getChangeSink(); sink != nil && len(c.captured) > 0 {
+		if err := sink.Capture(ctx, tx, c.captured); err != nil {
+			return stats, err
+		}
+	}
+
 	if stats.DeltaSize > 0 {
 		if log := ctx.GetLogger(); log.Logger.IsLevelEnabled(logrus.DebugLevel) {
 			log.WithFields(logrus.Fields{
@@ -202,6 +211,11 @@ func (c *DeltaController) prepareArrowView(
 
 	// fmt.Println("record:", record)
 
+	// The changes are captured from the whole record, before it is projected.
+	if sink := getChangeSink(); sink != nil && sink.Captures(table.dbName, table.tableName) {
+		c.captured = append(c.captured, captureChanges(table, appender, record))
+	}
+
 	var ar *duckdb.Arrow
 	err = conn.Raw(func(driverConn any) error {
 		var err error
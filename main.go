@@ -16,6 +16,8 @@ package main
 
 import (
 	"context"
+	"crypto/tls"
+	"crypto/x509"
 	"flag"
 	"fmt"
 	"log"
@@ -56,7 +58,19 @@ var (
 
 	replicaOptions replica.ReplicaOptions
 
-	postgresPort = 5432
+	postgresPort       = 5432
+	postgresUsers      = ""
+	postgresTLSCert    = ""
+	postgresTLSKey     = ""
+	postgresTLSCA      = ""
+	postgresRequireSSL = false
+	postgresSocketDir  = ""
+	postgresSocketPerm = "0770"
+	postgresPeerAuth   = false
+	postgresHBAFile    = ""
+
+	postgresInsertBatchSize = 0
+	postgresResultFlushSize = 0
 
 	// Shared between the MySQL and Postgres servers.
 	superuserPassword = ""
@@ -91,6 +105,17 @@ func init() {
 	flag.StringVar(&replicaOptions.ReportPassword, "report-password", replicaOptions.ReportPassword, "The account password of the replica to be reported to the source during replica registration.")
 
 	flag.IntVar(&postgresPort, "pg-port", postgresPort, "The port to bind to for PostgreSQL wire protocol.")
+	flag.StringVar(&postgresUsers, "pg-users-file", postgresUsers, "A file of name:password lines, where the password may be a SCRAM-SHA-256 verifier, defining the users of the PostgreSQL wire protocol.")
+	flag.StringVar(&postgresTLSCert, "pg-tls-cert", postgresTLSCert, "The certificate file for SSL connections over the PostgreSQL wire protocol.")
+	flag.StringVar(&postgresTLSKey, "pg-tls-key", postgresTLSKey, "The private key file for SSL connections over the PostgreSQL wire protocol.")
+	flag.StringVar(&postgresTLSCA, "pg-tls-ca", postgresTLSCA, "The file of the CA certificates to verify the client certificates over the PostgreSQL wire protocol against.")
+	flag.BoolVar(&postgresRequireSSL, "pg-require-ssl", postgresRequireSSL, "Reject TCP connections without SSL over the PostgreSQL wire protocol.")
+	flag.StringVar(&postgresSocketDir, "pg-socket-dir", postgresSocketDir, "The directory of the Unix domain socket .s.PGSQL.<pg-port> for the PostgreSQL wire protocol. By default, there is no socket.")
+	flag.StringVar(&postgresSocketPerm, "pg-socket-permissions", postgresSocketPerm, "The access permissions of the Unix domain socket of the PostgreSQL wire protocol in octal, as unix_socket_permissions of PostgreSQL.")
+	flag.BoolVar(&postgresPeerAuth, "pg-peer-auth", postgresPeerAuth, "Authenticate the connections over the Unix domain socket of the PostgreSQL wire protocol by the operating system users of the clients, who log in as the users of the same names without passwords.")
+	flag.StringVar(&postgresHBAFile, "pg-hba-file", postgresHBAFile, "A file of host-based authentication rules in the format of pg_hba.conf, which decide the authentication methods (trust, password, scram-sha-256, md5, peer, or reject) of the connections over the PostgreSQL wire protocol by their types, databases, users, and client addresses.")
+	flag.IntVar(&postgresInsertBatchSize, "pg-insert-batch-size", postgresInsertBatchSize, "Group up to this many INSERT statements outside transaction blocks into one transaction over the PostgreSQL wire protocol, which speeds up restoring plain SQL dumps. 0 disables the grouping.")
+	flag.IntVar(&postgresResultFlushSize, "pg-result-flush-size", postgresResultFlushSize, "Flush the rows of a query result to the client over the PostgreSQL wire protocol whenever this many bytes are buffered. 0 uses the default of 64 KiB.")
 	flag.StringVar(&defaultTimeZone, "default-time-zone", defaultTimeZone, "The default time zone to use.")
 
 	flag.StringVar(&restoreFile, "restore-file", restoreFile, "The file to restore from.")
@@ -162,6 +187,58 @@ func main() {
 	}
 
 	if postgresPort > 0 {
+		options := []pgserver.ListenerOpt{
+			pgserver.WithEngine(myServer.Engine),
+			pgserver.WithSessionManager(myServer.SessionManager()),
+			pgserver.WithConnID(&myServer.Listener.(*mysql.Listener).ConnectionID), // Shared connection ID counter
+		}
+		if postgresTLSCert != "" || postgresTLSKey != "" {
+			cert, err := tls.LoadX509KeyPair(postgresTLSCert, postgresTLSKey)
+			if err != nil {
+				logrus.WithError(err).Fatalln("Failed to load the certificate for the Postgres-protocol server")
+			}
+			options = append(options, pgserver.WithCertificate(cert))
+		}
+		if postgresTLSCA != "" {
+			pem, err := os.ReadFile(postgresTLSCA)
+			if err != nil {
+				logrus.WithError(err).Fatalln("Failed to read the CA certificates for the Postgres-protocol server")
+			}
+			pool := x509.NewCertPool()
+			if !pool.AppendCertsFromPEM(pem) {
+				logrus.Fatalln("No CA certificate is found in", postgresTLSCA)
+			}
+			options = append(options, pgserver.WithClientCAs(pool))
+		}
+		if postgresRequireSSL {
+			if postgresTLSCert == "" {
+				logrus.Fatalln("SSL cannot be required without a certificate; set --pg-tls-cert and --pg-tls-key")
+			}
+			options = append(options, pgserver.WithRequireSSL())
+		}
+		if postgresSocketDir != "" {
+			perm, err := strconv.ParseUint(postgresSocketPerm, 8, 32)
+			if err != nil || perm > 0777 {
+				logrus.Fatalln("Invalid permissions of the Unix domain socket:", postgresSocketPerm)
+			}
+			options = append(options, pgserver.WithUnixSocketDir(postgresSocketDir), pgserver.WithUnixSocketPermissions(os.FileMode(perm)))
+		}
+		if postgresPeerAuth {
+			options = append(options, pgserver.WithPeerAuthentication())
+		}
+		if postgresHBAFile != "" {
+			rules, err := pgserver.LoadHBAFile(postgresHBAFile)
+			if err != nil {
+				logrus.WithError(err).Fatalln("Failed to load the host-based authentication rules")
+			}
+			options = append(options, pgserver.WithHBARules(rules))
+		}
+		if postgresInsertBatchSize > 0 {
+			options = append(options, pgserver.WithInsertBatchSize(postgresInsertBatchSize))
+		}
+		if postgresResultFlushSize > 0 {
+			options = append(options, pgserver.WithResultFlushSize(postgresResultFlushSize))
+		}
 		pgServer, err := pgserver.NewServer(
 			provider,
 			address, postgresPort,
@@ -170,13 +247,16 @@ func main() {
 				session := backend.NewSession(memory.NewSession(sql.NewBaseSession(), provider), provider)
 				return sql.NewContext(context.Background(), sql.WithSession(session))
 			},
-			pgserver.WithEngine(myServer.Engine),
-			pgserver.WithSessionManager(myServer.SessionManager()),
-			pgserver.WithConnID(&myServer.Listener.(*mysql.Listener).ConnectionID), // Shared connection ID counter
+			options...,
 		)
 		if err != nil {
 			logrus.WithError(err).Fatalln("Failed to create Postgres-protocol server")
 		}
+		if postgresUsers != "" {
+			if err := pgServer.LoadUsers(postgresUsers); err != nil {
+				logrus.WithError(err).Fatalln("Failed to load the users of the Postgres-protocol server")
+			}
+		}
 
 		// Check if there is a replication subscription and start replication if there is.
 		err = logrepl.UpdateSubscriptions(pgServer.NewInternalCtx())
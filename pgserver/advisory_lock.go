@@ -0,0 +1,375 @@
+package pgserver
+
+import (
+	"context"
+	"errors"
+	"fmt"
+	"math"
+	"regexp"
+	"strconv"
+	"strings"
+	"sync"
+	"time"
+
+	"github.com/jackc/pgx/v5/pgtype"
+)
+
+// This file implements the advisory lock functions of Postgres, whose locks are kept in a lock table of the server:
+//
+//	SELECT pg_advisory_lock(key);               -- Waits for an exclusive session-level lock.
+//	SELECT pg_advisory_lock_shared(key);        -- Waits for a shared session-level lock.
+//	SELECT pg_try_advisory_lock(key);           -- Takes the lock if it is available, and returns whether it did.
+//	SELECT pg_advisory_unlock(key);             -- Releases a session-level lock, and returns whether it was held.
+//	SELECT pg_advisory_unlock_all();            -- Releases all the session-level locks of the connection.
+//	SELECT pg_advisory_xact_lock(key);          -- Waits for a transaction-level lock.
+//	SELECT pg_try_advisory_xact_lock_shared(k); -- ...and so on.
+//
+// A key is either a bigint or a pair of integers, which identify different locks, and may be a parameter of the
+// extended query protocol. Only a SELECT statement calling one of the functions by itself is supported.
+//
+// A session-level lock is held until it is released or the connection is closed, and may be locked several times
+// by its holder, which then has to unlock it as many times. A transaction-level lock is released at the end of the
+// transaction, or of the statement outside a transaction block. A wait for a lock is interrupted by a cancel request
+// or statement_timeout, but deadlocks are not detected.
+
+// AdvisoryLockConfig represents a call to an advisory lock function.
+type AdvisoryLockConfig struct {
+	Function string
+	Try      bool
+	Xact     bool
+	Shared   bool
+	Unlock   bool
+	All      bool
+	// Args are the keys of the lock, each an integer literal or a parameter such as $1.
+	Args  []string
+	Alias string
+}
+
+// advisoryLockRegex matches a SELECT statement calling an advisory lock function, and captures the function, its
+// arguments, and the alias.
+var advisoryLockRegex = regexp.MustCompile(`(?is)^\s*select\s+(?:pg_catalog\.)?(pg_(?:try_)?advisory_(?:xact_)?(?:unlock|lock)(?:_shared|_all)?)\s*\(([^)]*)\)(?:\s+(?:as\s+)?("[^"]+"|\w+))?\s*;?\s*$`)
+
+// advisoryLockArgRegex matches an argument of an advisory lock function, with an optional cast.
+var advisoryLockArgRegex = regexp.MustCompile(`(?i)^\s*(-?\d+|\$\d+)\s*(?:::\s*(?:bigint|int8|integer|int4|int))?\s*$`)
+
+// parseAdvisoryLockSQL parses the given SQL statement and returns an AdvisoryLockConfig, or nil if it does not call
+// an advisory lock function.
+func parseAdvisoryLockSQL(query string) (*AdvisoryLockConfig, error) {
+	matches := advisoryLockRegex.FindStringSubmatch(RemoveComments(query))
+	if matches == nil {
+		return nil, nil
+	}
+	function := strings.ToLower(matches[1])
+	config := &AdvisoryLockConfig{
+		Function: function,
+		Try:      strings.HasPrefix(function, "pg_try_"),
+		Xact:     strings.Contains(function, "_xact_"),
+		Shared:   strings.HasSuffix(function, "_shared"),
+		Unlock:   strings.Contains(function, "_unlock"),
+		All:      strings.HasSuffix(function, "_all"),
+		Alias:    function,
+	}
+	if matches[3] != "" {
+		config.Alias = foldIdentifier(matches[3])
+	}
+	if args := strings.TrimSpace(matches[2]); args != "" {
+		for _, arg := range strings.Split(args, ",") {
+			m := advisoryLockArgRegex.FindStringSubmatch(arg)
+			if m == nil {
+				return nil, fmt.Errorf("unsupported argument of %s(): %s", function, strings.TrimSpace(arg))
+			}
+			config.Args = append(config.Args, m[1])
+		}
+	}
+
+	// The unlock functions are session-level only, and pg_advisory_unlock_all() is the only one taking no key.
+	valid := len(config.Args) == 1 || len(config.Args) == 2
+	if (config.Unlock && (config.Xact || config.Try)) || (config.All && (!config.Unlock || config.Shared)) {
+		valid = false
+	} else if config.All {
+		valid = len(config.Args) == 0
+	}
+	if !valid {
+		return nil, fmt.Errorf("function %s() with %d arguments does not exist", function, len(config.Args))
+	}
+	return config, nil
+}
+
+// ParamTypes returns the OIDs of the parameters of the call, which are bigint for a single key and integer for a
+// pair of keys.
+func (config *AdvisoryLockConfig) ParamTypes() []uint32 {
+	var types []uint32
+	for _, arg := range config.Args {
+		if !strings.HasPrefix(arg, "$") {
+			continue
+		}
+		n, _ := strconv.Atoi(arg[1:])
+		for len(types) < n {
+			types = append(types, 0)
+		}
+		if len(config.Args) == 1 {
+			types[n-1] = pgtype.Int8OID
+		} else {
+			types[n-1] = pgtype.Int4OID
+		}
+	}
+	return types
+}
+
+// key returns the lock of the call, given the values of its parameters.
+func (config *AdvisoryLockConfig) key(vars []any) (advisoryLockKey, error) {
+	values := make([]int64, len(config.Args))
+	for i, arg := range config.Args {
+		if strings.HasPrefix(arg, "$") {
+			n, _ := strconv.Atoi(arg[1:])
+			if n < 1 || n > len(vars) || vars[n-1] == nil {
+				return advisoryLockKey{}, fmt.Errorf("no value for parameter %s of %s()", arg, config.Function)
+			}
+			arg = fmt.Sprint(vars[n-1])
+		}
+		v, err := strconv.ParseInt(arg, 10, 64)
+		if err != nil || (len(config.Args) == 2 && (v < math.MinInt32 || v > math.MaxInt32)) {
+			return advisoryLockKey{}, fmt.Errorf("invalid key of %s(): %s", config.Function, arg)
+		}
+		values[i] = v
+	}
+	// The keys are laid out as in pg_locks.
+	if len(values) == 1 {
+		return advisoryLockKey{classID: uint32(uint64(values[0]) >> 32), objID: uint32(values[0]), objSubID: 1}, nil
+	}
+	return advisoryLockKey{classID: uint32(values[0]), objID: uint32(values[1]), objSubID: 2}, nil
+}
+
+// advisoryLockKey identifies an advisory lock.
+type advisoryLockKey struct {
+	classID  uint32
+	objID    uint32
+	objSubID uint16
+}
+
+// advisoryLockHolds counts the holds of a connection on an advisory lock.
+type advisoryLockHolds struct {
+	exclusive     int
+	shared        int
+	xactExclusive int
+	xactShared    int
+}
+
+func (holds *advisoryLockHolds) empty() bool {
+	return holds.exclusive+holds.shared+holds.xactExclusive+holds.xactShared == 0
+}
+
+// advisoryLockTable keeps track of the advisory locks of all the connections of the server.
+type advisoryLockTable struct {
+	mu    sync.Mutex
+	locks map[advisoryLockKey]map[*ConnectionHandler]*advisoryLockHolds
+	// released is closed and replaced whenever a lock is released, which wakes up the waiting connections.
+	released chan struct{}
+}
+
+// tryLock takes the lock for the connection if no other connection holds it in a conflicting mode.
+// The caller must hold the mutex.
+func (t *advisoryLockTable) tryLock(key advisoryLockKey, h *ConnectionHandler, shared, xact bool) bool {
+	for holder, holds := range t.locks[key] {
+		if holder != h && (!shared || holds.exclusive+holds.xactExclusive > 0) {
+			return false
+		}
+	}
+	if t.locks == nil {
+		t.locks = make(map[advisoryLockKey]map[*ConnectionHandler]*advisoryLockHolds)
+	}
+	if t.locks[key] == nil {
+		t.locks[key] = make(map[*ConnectionHandler]*advisoryLockHolds)
+	}
+	holds := t.locks[key][h]
+	if holds == nil {
+		holds = &advisoryLockHolds{}
+		t.locks[key][h] = holds
+	}
+	switch {
+	case shared && xact:
+		holds.xactShared++
+	case shared:
+		holds.shared++
+	case xact:
+		holds.xactExclusive++
+	default:
+		holds.exclusive++
+	}
+	return true
+}
+
+// lock takes the lock for the connection, waiting until it is available or the context is done. If |wait| is not
+// set, it returns false right away if the lock is not available.
+func (t *advisoryLockTable) lock(ctx context.Context, key advisoryLockKey, h *ConnectionHandler, shared, xact, wait bool) (bool, error) {
+	for {
+		t.mu.Lock()
+		if t.tryLock(key, h, shared, xact) {
+			t.mu.Unlock()
+			return true, nil
+		}
+		if t.released == nil {
+			t.released = make(chan struct{})
+		}
+		released := t.released
+		t.mu.Unlock()
+		if !wait {
+			return false, nil
+		}
+		select {
+		case <-released:
+		case <-ctx.Done():
+			return false, ctx.Err()
+		}
+	}
+}
+
+// unlock releases a session-level hold of the connection on the lock, and returns whether there was one.
+func (t *advisoryLockTable) unlock(key advisoryLockKey, h *ConnectionHandler, shared bool) bool {
+	t.mu.Lock()
+	defer t.mu.Unlock()
+	holds := t.locks[key][h]
+	if holds == nil {
+		return false
+	}
+	if shared {
+		if holds.shared == 0 {
+			return false
+		}
+		holds.shared--
+	} else {
+		if holds.exclusive == 0 {
+			return false
+		}
+		holds.exclusive--
+	}
+	t.cleanUp(key, h, holds)
+	return true
+}
+
+// unlockAll releases the transaction-level holds of the connection if |xact| is set, or the session-level ones
+// otherwise.
+func (t *advisoryLockTable) unlockAll(h *ConnectionHandler, xact bool) {
+	t.mu.Lock()
+	defer t.mu.Unlock()
+	for key, holders := range t.locks {
+		holds := holders[h]
+		if holds == nil {
+			continue
+		}
+		if xact {
+			holds.xactExclusive, holds.xactShared = 0, 0
+		} else {
+			holds.exclusive, holds.shared = 0, 0
+		}
+		t.cleanUp(key, h, holds)
+	}
+}
+
+// cleanUp forgets the holds of the connection once they are all released, and wakes up the waiting connections.
+// The caller must hold the mutex.
+func (t *advisoryLockTable) cleanUp(key advisoryLockKey, h *ConnectionHandler, holds *advisoryLockHolds) {
+	if !holds.empty() {
+		return
+	}
+	delete(t.locks[key], h)
+	if len(t.locks[key]) == 0 {
+		delete(t.locks, key)
+	}
+	if t.released != nil {
+		close(t.released)
+		t.released = nil
+	}
+}
+
+// voidResult is the result of the functions returning void, which is an empty string in the text format.
+const voidResult = "''"
+
+// executeAdvisoryLock calls an advisory lock function with the values of its parameters, and sends its result.
+func (h *ConnectionHandler) executeAdvisoryLock(statement ConvertedStatement, vars []any) error {
+	config := statement.AdvisoryLockConfig
+	if h.server == nil {
+		return fmt.Errorf("%s() is not supported by this connection", config.Function)
+	}
+	locks := &h.server.advisoryLocks
+
+	var result string
+	switch {
+	case config.All:
+		locks.unlockAll(h, false)
+		result = voidResult
+	case config.Unlock:
+		key, err := config.key(vars)
+		if err != nil {
+			return err
+		}
+		unlocked := locks.unlock(key, h, config.Shared)
+		if !unlocked {
+			mode := "ExclusiveLock"
+			if config.Shared {
+				mode = "ShareLock"
+			}
+			_ = h.sendNotice(ErrorResponseSeverity_Warning, "01000" /* warning */, fmt.Sprintf("you don't own a lock of type %s", mode))
+		}
+		result = strconv.FormatBool(unlocked)
+	default:
+		key, err := config.key(vars)
+		if err != nil {
+			return err
+		}
+		locked, err := h.waitForAdvisoryLock(statement.String, key, config)
+		if err != nil {
+			return err
+		}
+		if config.Try {
+			result = strconv.FormatBool(locked)
+		} else {
+			result = voidResult
+		}
+	}
+
+	return h.run(ConvertedStatement{
+		String: fmt.Sprintf(`SELECT %s AS "%s";`, result, strings.ReplaceAll(config.Alias, `"`, `""`)),
+		Tag:    "SELECT",
+	})
+}
+
+// waitForAdvisoryLock takes the advisory lock for the connection. The wait is registered as a running query, so
+// that it can be canceled, and is limited by statement_timeout and lock_timeout.
+func (h *ConnectionHandler) waitForAdvisoryLock(query string, key advisoryLockKey, config *AdvisoryLockConfig) (bool, error) {
+	sqlCtx, err := h.duckHandler.NewContext(context.Background(), h.mysqlConn, query)
+	if err != nil {
+		return false, err
+	}
+	ctx, err := sqlCtx.ProcessList.BeginQuery(sqlCtx, query)
+	if err != nil {
+		return false, err
+	}
+	defer sqlCtx.ProcessList.EndQuery(ctx)
+
+	lockCtx := context.Context(ctx)
+	if timeout := h.sessionTimeout("lock_timeout"); timeout > 0 {
+		var cancel context.CancelFunc
+		lockCtx, cancel = context.WithTimeout(ctx, time.Duration(timeout)*time.Millisecond)
+		defer cancel()
+	}
+
+	stopTimeout := h.startStatementTimeout()
+	locked, err := h.server.advisoryLocks.lock(lockCtx, key, h, config.Shared, config.Xact, !config.Try)
+	if errors.Is(err, context.DeadlineExceeded) {
+		err = errLockTimeout
+	}
+	return locked, stopTimeout(err)
+}
+
+// releaseAdvisoryLocks releases the transaction-level advisory locks of the connection at the end of a transaction,
+// or all of them if |session| is set.
+func (h *ConnectionHandler) releaseAdvisoryLocks(session bool) {
+	if h.server == nil {
+		return
+	}
+	h.server.advisoryLocks.unlockAll(h, true)
+	if session {
+		h.server.advisoryLocks.unlockAll(h, false)
+	}
+}
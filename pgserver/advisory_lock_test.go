@@ -0,0 +1,110 @@
+package pgserver
+
+import (
+	"context"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+)
+
+func TestParseAdvisoryLockSQL(t *testing.T) {
+	tests := []struct {
+		query string
+		want  *AdvisoryLockConfig
+	}{
+		{
+			query: "SELECT pg_advisory_lock(42)",
+			want:  &AdvisoryLockConfig{Function: "pg_advisory_lock", Args: []string{"42"}, Alias: "pg_advisory_lock"},
+		},
+		{
+			query: "select pg_catalog.pg_try_advisory_xact_lock_shared(1, -2) AS \"Locked\";",
+			want: &AdvisoryLockConfig{
+				Function: "pg_try_advisory_xact_lock_shared",
+				Try:      true,
+				Xact:     true,
+				Shared:   true,
+				Args:     []string{"1", "-2"},
+				Alias:    "Locked",
+			},
+		},
+		{
+			query: "SELECT pg_advisory_unlock($1::bigint) unlocked",
+			want:  &AdvisoryLockConfig{Function: "pg_advisory_unlock", Unlock: true, Args: []string{"$1"}, Alias: "unlocked"},
+		},
+		{
+			query: "SELECT pg_advisory_unlock_all()",
+			want:  &AdvisoryLockConfig{Function: "pg_advisory_unlock_all", Unlock: true, All: true, Alias: "pg_advisory_unlock_all"},
+		},
+		{
+			query: "SELECT pg_advisory_lock(1), pg_advisory_lock(2)",
+		},
+		{
+			query: "SELECT pg_is_in_recovery()",
+		},
+	}
+
+	for _, tt := range tests {
+		got, err := parseAdvisoryLockSQL(tt.query)
+		require.NoError(t, err, tt.query)
+		require.Equal(t, tt.want, got, tt.query)
+	}
+
+	for _, query := range []string{
+		"SELECT pg_advisory_lock()",
+		"SELECT pg_advisory_lock(1, 2, 3)",
+		"SELECT pg_advisory_xact_unlock(1)",
+		"SELECT pg_advisory_unlock_all(1)",
+		"SELECT pg_advisory_lock('a')",
+	} {
+		_, err := parseAdvisoryLockSQL(query)
+		require.Error(t, err, query)
+	}
+}
+
+func TestAdvisoryLockKey(t *testing.T) {
+	config := &AdvisoryLockConfig{Function: "pg_advisory_lock", Args: []string{"$1"}}
+	key, err := config.key([]any{"4294967298"})
+	require.NoError(t, err)
+	require.Equal(t, advisoryLockKey{classID: 1, objID: 2, objSubID: 1}, key)
+	require.Equal(t, []uint32{20}, config.ParamTypes())
+
+	config = &AdvisoryLockConfig{Function: "pg_advisory_lock", Args: []string{"1", "$1"}}
+	key, err = config.key([]any{"2"})
+	require.NoError(t, err)
+	require.Equal(t, advisoryLockKey{classID: 1, objID: 2, objSubID: 2}, key)
+	require.Equal(t, []uint32{23}, config.ParamTypes())
+
+	_, err = config.key([]any{"4294967296"})
+	require.Error(t, err)
+}
+
+func TestAdvisoryLockTable(t *testing.T) {
+	var locks advisoryLockTable
+	a, b := &ConnectionHandler{}, &ConnectionHandler{}
+	key := advisoryLockKey{objID: 1, objSubID: 1}
+
+	tryLock := func(h *ConnectionHandler, shared, xact bool) bool {
+		locked, err := locks.lock(context.Background(), key, h, shared, xact, false)
+		require.NoError(t, err)
+		return locked
+	}
+
+	// Session-level locks are reentrant, and conflict with the other connections only.
+	require.True(t, tryLock(a, false, false))
+	require.True(t, tryLock(a, false, false))
+	require.True(t, tryLock(a, true, true))
+	require.False(t, tryLock(b, true, false))
+	require.True(t, locks.unlock(key, a, false))
+	require.False(t, tryLock(b, false, false))
+	require.True(t, locks.unlock(key, a, false))
+	require.False(t, locks.unlock(key, a, false))
+
+	// Shared locks are compatible with each other, but not with exclusive ones.
+	require.True(t, tryLock(b, true, false))
+	require.False(t, tryLock(a, false, true))
+	locks.unlockAll(b, false)
+	require.True(t, tryLock(a, false, true))
+
+	locks.unlockAll(a, true)
+	require.Empty(t, locks.locks)
+}
@@ -0,0 +1,210 @@
+package pgserver
+
+import (
+	"fmt"
+	"regexp"
+	"strings"
+
+	"github.com/jackc/pgx/v5/pgproto3"
+	"github.com/jackc/pgx/v5/pgtype"
+)
+
+// The arrays of Postgres are the LIST types of DuckDB. The two are written differently as text: Postgres writes
+// {1,2,NULL} and {{"a b",c},{d,e}}, while DuckDB writes [1, 2, NULL] and [["a b", c], [d, e]]. DuckDB casts only its
+// own form of text to a LIST, so the array literals in the queries and the array values of the bind parameters
+// are rewritten into that form. The results are encoded by the ArrayCodec of pgtype, see SqlRowIter.
+
+var (
+	// pgArrayCastRegex matches the casts of Postgres array literals to array types, e.g., '{1,2}'::int[].
+	pgArrayCastRegex = regexp.MustCompile(`(?i)'(\{(?:[^']|'')*\})'(\s*::\s*` + arrayTypePattern + `)`)
+	// pgArrayCastFuncRegex matches the CAST of Postgres array literals to array types, e.g., CAST('{1,2}' AS int[]).
+	pgArrayCastFuncRegex = regexp.MustCompile(`(?i)(\bCAST\s*\(\s*)'(\{(?:[^']|'')*\})'(\s+AS\s+` + arrayTypePattern + `\s*\))`)
+)
+
+// arrayTypePattern matches the name of an array type, which may have a type modifier and several dimensions.
+const arrayTypePattern = `[a-z_][\w.]*(?:\s+(?:precision|varying))?(?:\s*\([\d\s,]*\))?\s*(?:\[\d*\]\s*)+`
+
+// ConvertArrayLiterals rewrites the Postgres array literals cast to array types in the query into DuckDB lists.
+// The literals that are not valid arrays are left as they are, so that DuckDB reports the error.
+func ConvertArrayLiterals(sql string) string {
+	if !strings.Contains(sql, "'{") {
+		return sql
+	}
+	convert := func(literal string) (string, bool) {
+		list, err := pgArrayToDuckDBList(strings.ReplaceAll(literal, "''", "'"))
+		if err != nil {
+			return "", false
+		}
+		return "'" + strings.ReplaceAll(list, "'", "''") + "'", true
+	}
+	sql = pgArrayCastRegex.ReplaceAllStringFunc(sql, func(m string) string {
+		sub := pgArrayCastRegex.FindStringSubmatch(m)
+		if list, ok := convert(sub[1]); ok {
+			return list + sub[2]
+		}
+		return m
+	})
+	return pgArrayCastFuncRegex.ReplaceAllStringFunc(sql, func(m string) string {
+		sub := pgArrayCastFuncRegex.FindStringSubmatch(m)
+		if list, ok := convert(sub[2]); ok {
+			return sub[1] + list + sub[3]
+		}
+		return m
+	})
+}
+
+// pgArrayToDuckDBList rewrites the text of a Postgres array into that of a DuckDB list.
+func pgArrayToDuckDBList(text string) (string, error) {
+	array, err := parsePgArray(text)
+	if err != nil {
+		return "", err
+	}
+	var b strings.Builder
+	writeDuckDBList(&b, array)
+	return b.String(), nil
+}
+
+// parsePgArray parses the text of a Postgres array into nested slices, whose elements are strings, or nil for NULL.
+// https://www.postgresql.org/docs/current/arrays.html#ARRAYS-IO
+func parsePgArray(text string) ([]any, error) {
+	s := strings.TrimSpace(text)
+	// The lower bounds may be given before the elements, e.g., [0:1]={1,2}, which are irrelevant to DuckDB.
+	if strings.HasPrefix(s, "[") {
+		_, elements, ok := strings.Cut(s, "=")
+		if !ok {
+			return nil, fmt.Errorf("malformed array literal: %q", text)
+		}
+		s = strings.TrimSpace(elements)
+	}
+	array, rest, err := parsePgArrayElements(s)
+	if err != nil {
+		return nil, fmt.Errorf("malformed array literal: %q: %w", text, err)
+	}
+	if strings.TrimSpace(rest) != "" {
+		return nil, fmt.Errorf("malformed array literal: %q: junk after closing right brace", text)
+	}
+	return array, nil
+}
+
+// parsePgArrayElements parses the elements between a pair of braces at the start of |s|, and returns the rest of it.
+func parsePgArrayElements(s string) (array []any, rest string, err error) {
+	if !strings.HasPrefix(s, "{") {
+		return nil, "", fmt.Errorf(`array value must start with "{"`)
+	}
+	s = strings.TrimLeft(s[1:], " \t\r\n")
+	if strings.HasPrefix(s, "}") {
+		return []any{}, s[1:], nil
+	}
+	for {
+		switch {
+		case strings.HasPrefix(s, "{"):
+			var sub []any
+			if sub, s, err = parsePgArrayElements(s); err != nil {
+				return nil, "", err
+			}
+			array = append(array, sub)
+		case strings.HasPrefix(s, `"`):
+			var b strings.Builder
+			i := 1
+			for ; i < len(s) && s[i] != '"'; i++ {
+				if s[i] == '\\' && i+1 < len(s) {
+					i++
+				}
+				b.WriteByte(s[i])
+			}
+			if i == len(s) {
+				return nil, "", fmt.Errorf("unexpected end of input")
+			}
+			array = append(array, b.String())
+			s = s[i+1:]
+		default:
+			var b strings.Builder
+			i, escaped := 0, false
+			for ; i < len(s) && s[i] != ',' && s[i] != '}'; i++ {
+				if s[i] == '\\' && i+1 < len(s) {
+					i++
+					escaped = true
+				}
+				b.WriteByte(s[i])
+			}
+			element := strings.TrimSpace(b.String())
+			switch {
+			case element == "" && !escaped:
+				return nil, "", fmt.Errorf("unexpected delimiter")
+			case strings.EqualFold(element, "NULL") && !escaped:
+				array = append(array, nil)
+			default:
+				array = append(array, element)
+			}
+			s = s[i:]
+		}
+
+		s = strings.TrimLeft(s, " \t\r\n")
+		switch {
+		case strings.HasPrefix(s, ","):
+			s = strings.TrimLeft(s[1:], " \t\r\n")
+		case strings.HasPrefix(s, "}"):
+			return array, s[1:], nil
+		default:
+			return nil, "", fmt.Errorf("unexpected end of input")
+		}
+	}
+}
+
+// writeDuckDBList writes nested slices parsed by parsePgArray as the text of a DuckDB list.
+func writeDuckDBList(b *strings.Builder, array []any) {
+	b.WriteByte('[')
+	for i, element := range array {
+		if i > 0 {
+			b.WriteString(", ")
+		}
+		switch v := element.(type) {
+		case nil:
+			b.WriteString("NULL")
+		case []any:
+			writeDuckDBList(b, v)
+		case string:
+			if v != "" && !strings.EqualFold(v, "NULL") && strings.TrimSpace(v) == v && !strings.ContainsAny(v, `[]{}(),:'"\`) {
+				b.WriteString(v)
+				continue
+			}
+			b.WriteByte('"')
+			for _, c := range []byte(v) {
+				if c == '"' || c == '\\' {
+					b.WriteByte('\\')
+				}
+				b.WriteByte(c)
+			}
+			b.WriteByte('"')
+		}
+	}
+	b.WriteByte(']')
+}
+
+// isArrayType returns whether the type of the OID is an array type.
+func (h *ConnectionHandler) isArrayType(oid uint32) bool {
+	t, ok := h.pgTypeMap.TypeForOID(oid)
+	if !ok {
+		return false
+	}
+	_, ok = t.Codec.(*pgtype.ArrayCodec)
+	return ok
+}
+
+// convertArrayBindParameter converts the value of an array bind parameter in text or binary format into the text of
+// a DuckDB list. The binary format is decoded and encoded as text by pgtype first.
+func (h *ConnectionHandler) convertArrayBindParameter(oid uint32, format int16, value []byte) (string, error) {
+	text := string(value)
+	if format == pgproto3.BinaryFormat {
+		var array pgtype.Array[any]
+		if err := h.pgTypeMap.Scan(oid, format, value, &array); err != nil {
+			return "", err
+		}
+		encoded, err := h.pgTypeMap.Encode(oid, pgproto3.TextFormat, array, nil)
+		if err != nil {
+			return "", err
+		}
+		text = string(encoded)
+	}
+	return pgArrayToDuckDBList(text)
+}
@@ -0,0 +1,131 @@
+package pgserver
+
+import (
+	"context"
+	"strconv"
+	"testing"
+
+	"github.com/apecloud/myduckserver/testutil"
+	"github.com/jackc/pgx/v5"
+	"github.com/jackc/pgx/v5/pgproto3"
+	"github.com/jackc/pgx/v5/pgtype"
+	"github.com/stretchr/testify/require"
+)
+
+func TestPgArrayToDuckDBList(t *testing.T) {
+	tests := []struct {
+		text    string
+		want    string
+		wantErr bool
+	}{
+		{text: "{}", want: "[]"},
+		{text: "{1,2,NULL}", want: "[1, 2, NULL]"},
+		{text: `{"a b",c,"NULL","",d\,e,"f\"g"}`, want: `[a b, c, "NULL", "", "d,e", "f\"g"]`},
+		{text: "{{1,2},{3,4}}", want: "[[1, 2], [3, 4]]"},
+		{text: "[0:1]={1,2}", want: "[1, 2]"},
+		{text: " { 1 , 2 } ", want: "[1, 2]"},
+		{text: "{1,2", wantErr: true},
+		{text: "{1,,2}", wantErr: true},
+		{text: "{1}x", wantErr: true},
+		{text: "1,2", wantErr: true},
+	}
+
+	for _, tt := range tests {
+		got, err := pgArrayToDuckDBList(tt.text)
+		if tt.wantErr {
+			if err == nil {
+				t.Errorf("pgArrayToDuckDBList(%q) = %q; want an error", tt.text, got)
+			}
+			continue
+		}
+		if err != nil || got != tt.want {
+			t.Errorf("pgArrayToDuckDBList(%q) = %q, %v; want %q", tt.text, got, err, tt.want)
+		}
+	}
+}
+
+func TestConvertArrayLiterals(t *testing.T) {
+	tests := []struct {
+		query string
+		want  string
+	}{
+		{
+			query: "SELECT '{1,2,3}'::int[]",
+			want:  "SELECT '[1, 2, 3]'::int[]",
+		},
+		{
+			query: "SELECT '{{1.5,2},{3,4}}'::numeric(10, 2)[][], '{it''s}'::text[]",
+			want:  "SELECT '[[1.5, 2], [3, 4]]'::numeric(10, 2)[][], '[\"it''s\"]'::text[]",
+		},
+		{
+			query: "INSERT INTO t VALUES (CAST('{a,b}' AS VARCHAR[]))",
+			want:  "INSERT INTO t VALUES (CAST('[a, b]' AS VARCHAR[]))",
+		},
+		{
+			query: "SELECT '{1,2}', '{\"a\": 1}'::json",
+			want:  "SELECT '{1,2}', '{\"a\": 1}'::json",
+		},
+	}
+
+	for _, tt := range tests {
+		if got := ConvertArrayLiterals(tt.query); got != tt.want {
+			t.Errorf("ConvertArrayLiterals(%q) = %q; want %q", tt.query, got, tt.want)
+		}
+	}
+}
+
+func TestArrays(t *testing.T) {
+	// Setup MyDuck Server
+	testDir := testutil.CreateTestDir(t)
+	testEnv := testutil.NewTestEnv()
+	err := testutil.StartDuckSqlServer(t, testDir, nil, testEnv)
+	require.NoError(t, err)
+	defer testutil.StopDuckSqlServer(t, testEnv.DuckProcess)
+	dsn := "postgresql://postgres@localhost:" + strconv.Itoa(testEnv.DuckPgPort) + "/postgres"
+
+	ctx := context.Background()
+	conn, err := pgx.Connect(ctx, dsn)
+	require.NoError(t, err)
+	defer conn.Close(ctx)
+
+	// The results in binary format, with the OIDs and the type modifiers of the array types
+	rows, err := conn.Query(ctx, "SELECT [1, 2, NULL]::INTEGER[] AS i, ['a', 'b c']::VARCHAR[] AS s, [[1, 2], [3, 4]]::BIGINT[][] AS m, [1.5]::DECIMAL(10, 2)[] AS d")
+	require.NoError(t, err)
+	fields := rows.FieldDescriptions()
+	require.Equal(t, uint32(pgtype.Int4ArrayOID), fields[0].DataTypeOID)
+	require.Equal(t, uint32(pgtype.TextArrayOID), fields[1].DataTypeOID)
+	require.Equal(t, uint32(pgtype.Int8ArrayOID), fields[2].DataTypeOID)
+	require.Equal(t, uint32(pgtype.NumericArrayOID), fields[3].DataTypeOID)
+	require.Equal(t, int32(10<<16|2)+4, fields[3].TypeModifier)
+	require.True(t, rows.Next())
+	var i []*int32
+	var s []string
+	var m [][]int64
+	var d []pgtype.Numeric
+	require.NoError(t, rows.Scan(&i, &s, &m, &d))
+	require.Len(t, i, 3)
+	require.Equal(t, int32(2), *i[1])
+	require.Nil(t, i[2])
+	require.Equal(t, []string{"a", "b c"}, s)
+	require.Equal(t, [][]int64{{1, 2}, {3, 4}}, m)
+	require.Len(t, d, 1)
+	rows.Close()
+	require.NoError(t, rows.Err())
+
+	// The results in text format, and the Postgres array literals in the query
+	var text string
+	err = conn.QueryRow(ctx, "SELECT list_append('{1,2,3}'::INTEGER[], 4)", pgx.QueryExecModeSimpleProtocol).Scan(&text)
+	require.NoError(t, err)
+	require.Equal(t, "{1,2,3,4}", text)
+
+	// The bind parameters in text and binary format
+	for _, format := range []int16{pgproto3.TextFormat, pgproto3.BinaryFormat} {
+		param, err := conn.TypeMap().Encode(pgtype.TextArrayOID, format, []string{"x", "y, z", `"`}, nil)
+		require.NoError(t, err)
+		result := conn.PgConn().ExecParams(ctx, "SELECT len($1::VARCHAR[]), $1::VARCHAR[]",
+			[][]byte{param}, []uint32{pgtype.TextArrayOID}, []int16{format}, []int16{pgproto3.TextFormat}).Read()
+		require.NoError(t, result.Err)
+		require.Equal(t, "3", string(result.Rows[0][0]))
+		require.Equal(t, `{x,"y, z","\""}`, string(result.Rows[0][1]))
+	}
+}
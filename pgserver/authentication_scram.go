@@ -16,9 +16,19 @@ package pgserver
 
 import (
 	"bytes"
+	"crypto/hmac"
+	"crypto/sha256"
+	"crypto/sha512"
+	"crypto/tls"
+	"crypto/x509"
 	"encoding/base64"
+	"errors"
 	"fmt"
+	"hash"
 	"net"
+	"os"
+	"slices"
+	"strconv"
 	"strings"
 
 	"github.com/dolthub/doltgresql/server/auth"
@@ -26,6 +36,7 @@ import (
 
 	"github.com/dolthub/go-mysql-server/sql"
 	"github.com/jackc/pgx/v5/pgproto3"
+	"github.com/sirupsen/logrus"
 )
 
 // SCRAM authentication is defined in RFC-5802:
@@ -37,6 +48,13 @@ const (
 	SASLMechanism_SCRAM_SHA_256_PLUS = "SCRAM-SHA-256-PLUS"
 )
 
+// SASLChannelBinding_TLSServerEndPoint is the only channel binding type supported by SCRAM-SHA-256-PLUS,
+// as it is for Postgres.
+const SASLChannelBinding_TLSServerEndPoint = "tls-server-end-point"
+
+// scramSha256VerifierPrefix starts a SCRAM-SHA-256 verifier, as stored in pg_authid.rolpassword.
+const scramSha256VerifierPrefix = "SCRAM-SHA-256$"
+
 // EnableAuthentication handles whether authentication is enabled. If enabled, it verifies that the given user exists,
 // and checks that the encrypted password is derivable from the stored encrypted password.
 var EnableAuthentication = true
@@ -48,17 +66,132 @@ func InitSuperuser(password string) {
 	var err error
 	postgres := auth.CreateDefaultRole("postgres")
 	postgres.CanLogin = true
-	postgres.Password, err = auth.NewScramSha256Password(password)
-	if err != nil {
-		panic(err)
-	}
-	auth.SetRole(postgres)
-
 	// Postgres does not allow empty passwords,
 	// so we disable authentication if the superuser password is empty.
+	// If users are loaded later, the superuser cannot log in unless it is one of them.
 	if password == "" {
 		EnableAuthentication = false
+	} else {
+		postgres.Password, err = auth.NewScramSha256Password(password)
+		if err != nil {
+			panic(err)
+		}
+	}
+	auth.SetRole(postgres)
+}
+
+// LoadUsers creates a login role for each line of the file at |path|, which reads `name:password`. The password is
+// either a SCRAM-SHA-256 verifier, as stored in pg_authid.rolpassword, or a plain password, which is hashed when
+// loaded. Empty lines and lines starting with `#` are skipped. Loading users enables authentication. The loaded roles
+// are returned.
+func LoadUsers(path string) ([]auth.Role, error) {
+	data, err := os.ReadFile(path)
+	if err != nil {
+		return nil, err
+	}
+	var roles []auth.Role
+	for i, line := range strings.Split(string(data), "\n") {
+		line = strings.TrimSpace(line)
+		if len(line) == 0 || strings.HasPrefix(line, "#") {
+			continue
+		}
+		name, password, ok := strings.Cut(line, ":")
+		if !ok || len(name) == 0 || len(password) == 0 {
+			return nil, fmt.Errorf("%s:%d: expected name:password", path, i+1)
+		}
+
+		var role auth.Role
+		if auth.RoleExists(name) {
+			role = auth.GetRole(name)
+		} else {
+			role = auth.CreateDefaultRole(name)
+		}
+		role.CanLogin = true
+		switch {
+		case strings.HasPrefix(password, scramSha256VerifierPrefix):
+			role.Password, err = ParseScramSha256Verifier(password)
+		case strings.HasPrefix(password, "md5") && len(password) == 35:
+			err = fmt.Errorf("MD5 passwords are not supported, only SCRAM-SHA-256 ones")
+		default:
+			role.Password, err = auth.NewScramSha256Password(password)
+		}
+		if err != nil {
+			return nil, fmt.Errorf("%s:%d: %w", path, i+1, err)
+		}
+		auth.SetRole(role)
+		roles = append(roles, role)
+	}
+	EnableAuthentication = true
+	return roles, nil
+}
+
+// ParseScramSha256Verifier parses a SCRAM-SHA-256 verifier in the format of pg_authid.rolpassword:
+// `SCRAM-SHA-256$<iterations>:<salt>$<StoredKey>:<ServerKey>`, with the salt and the keys encoded in base64.
+func ParseScramSha256Verifier(verifier string) (*auth.ScramSha256Password, error) {
+	invalid := fmt.Errorf("invalid SCRAM-SHA-256 verifier")
+	params, keys, ok := strings.Cut(strings.TrimPrefix(verifier, scramSha256VerifierPrefix), "$")
+	if !ok || !strings.HasPrefix(verifier, scramSha256VerifierPrefix) {
+		return nil, invalid
+	}
+	iterations, salt, ok := strings.Cut(params, ":")
+	if !ok {
+		return nil, invalid
+	}
+	storedKey, serverKey, ok := strings.Cut(keys, ":")
+	if !ok {
+		return nil, invalid
+	}
+	n, err := strconv.ParseUint(iterations, 10, 32)
+	if err != nil || n == 0 {
+		return nil, invalid
+	}
+	decoded := make([][]byte, 3)
+	for i, s := range []string{salt, storedKey, serverKey} {
+		if decoded[i], err = base64.StdEncoding.DecodeString(s); err != nil || len(decoded[i]) == 0 {
+			return nil, invalid
+		}
+	}
+	if len(decoded[1]) != sha256.Size || len(decoded[2]) != sha256.Size {
+		return nil, invalid
 	}
+	return &auth.ScramSha256Password{
+		Iterations: uint32(n),
+		Salt:       rfc5802.OctetString(decoded[0]),
+		StoredKey:  rfc5802.OctetString(decoded[1]),
+		ServerKey:  rfc5802.OctetString(decoded[2]),
+	}, nil
+}
+
+// FormatScramSha256Verifier formats the password as a SCRAM-SHA-256 verifier, as stored in pg_authid.rolpassword.
+func FormatScramSha256Verifier(password *auth.ScramSha256Password) string {
+	return fmt.Sprintf("%s%d:%s$%s:%s", scramSha256VerifierPrefix, password.Iterations,
+		password.Salt.ToBase64(), password.StoredKey.ToBase64(), password.ServerKey.ToBase64())
+}
+
+// tlsServerEndPoint returns the channel binding data of type tls-server-end-point for the certificate, as defined
+// in RFC 5929: the hash of the certificate, with the hash function of its signature, or SHA-256 for MD5 and SHA-1.
+func tlsServerEndPoint(cert tls.Certificate) ([]byte, error) {
+	if len(cert.Certificate) == 0 {
+		return nil, fmt.Errorf("no certificate")
+	}
+	leaf := cert.Leaf
+	if leaf == nil {
+		var err error
+		if leaf, err = x509.ParseCertificate(cert.Certificate[0]); err != nil {
+			return nil, err
+		}
+	}
+	var h hash.Hash
+	switch leaf.SignatureAlgorithm {
+	case x509.SHA384WithRSA, x509.SHA384WithRSAPSS, x509.ECDSAWithSHA384:
+		h = sha512.New384()
+	case x509.SHA512WithRSA, x509.SHA512WithRSAPSS, x509.ECDSAWithSHA512:
+		h = sha512.New()
+	default:
+		h = sha256.New()
+	}
+	h.Write(leaf.Raw)
+	return h.Sum(nil), nil
 }
 
 // SASLBindingFlag are the flags for gs2-cbind-flag, used in SASL authentication.
@@ -79,6 +212,8 @@ type SASLInitial struct {
 	Username string // Prepared using SASLprep, currently ignored in favor of the startup message's username
 	Nonce    string
 	RawData  []byte // The bytes that were received in the message
+
+	ChannelBinding []byte // The cbind-data of the connection, only set when Flag is SASLBindingFlag_Used
 }
 
 // SASLContinue is the structured form of the output for *pgproto3.SASLInitialResponse.
@@ -119,15 +254,48 @@ func (h *ConnectionHandler) handleAuthentication(startupMessage *pgproto3.Startu
 		User: username,
 		Host: host,
 	}
+	// The host-based authentication rules, if any, decide the method, or reject the connection.
+	method := HBAMethodScram
+	if peerAuth && h.Conn().RemoteAddr().Network() == "unix" {
+		method = HBAMethodPeer
+	}
+	if len(hbaRules) > 0 {
+		conn := h.hbaConnection(startupMessage, username)
+		rule := matchHBARule(hbaRules, conn)
+		if rule == nil || rule.Method == HBAMethodReject {
+			message := hbaError(conn, rule != nil)
+			_ = h.send(&pgproto3.ErrorResponse{
+				Severity: "FATAL",
+				Code:     "28000", // invalid_authorization_specification
+				Message:  message,
+			})
+			return errors.New(message)
+		}
+		method = rule.Method
+	}
 	// Currently, regression tests disable authentication, since we can't just replay the messages due to nonces.
-	if !EnableAuthentication {
+	if !EnableAuthentication || method == HBAMethodTrust {
 		return h.send(&pgproto3.AuthenticationOk{})
 	}
-	// We only support one mechanism for now.
+	switch method {
+	case HBAMethodPeer:
+		return h.handlePeerAuthentication(username)
+	case HBAMethodPassword:
+		return h.handlePasswordAuthentication(username)
+	}
+	// Channel binding is offered on TLS connections, where the client can check it.
+	mechanisms := []string{SASLMechanism_SCRAM_SHA_256}
+	var channelBinding []byte
+	if _, ok := h.Conn().(*tls.Conn); ok {
+		var err error
+		if channelBinding, err = tlsServerEndPoint(certificate); err != nil {
+			logrus.WithError(err).Warnln("Channel binding is not available")
+		} else {
+			mechanisms = []string{SASLMechanism_SCRAM_SHA_256_PLUS, SASLMechanism_SCRAM_SHA_256}
+		}
+	}
 	if err := h.send(&pgproto3.AuthenticationSASL{
-		AuthMechanisms: []string{
-			SASLMechanism_SCRAM_SHA_256,
-		},
+		AuthMechanisms: mechanisms,
 	}); err != nil {
 		return err
 	}
@@ -146,7 +314,7 @@ func (h *ConnectionHandler) handleAuthentication(startupMessage *pgproto3.Startu
 		}
 		switch response := initialResponse.(type) {
 		case *pgproto3.SASLInitialResponse:
-			saslInitial, err = readSASLInitial(response)
+			saslInitial, err = readSASLInitial(response, channelBinding)
 			if err != nil {
 				_ = h.send(&pgproto3.ErrorResponse{
 					Severity: "FATAL",
@@ -156,8 +324,10 @@ func (h *ConnectionHandler) handleAuthentication(startupMessage *pgproto3.Startu
 				return err
 			}
 			var salt string
+			iterations := uint32(4096)
 			if role.Password != nil {
 				salt = role.Password.Salt.ToBase64()
+				iterations = role.Password.Iterations
 			} else {
 				// We do this to get a stable salt. An unstable salt could be used to determine whether a username exists.
 				salt = rfc5802.H(rfc5802.OctetString(username))[:16].ToBase64()
@@ -165,7 +335,7 @@ func (h *ConnectionHandler) handleAuthentication(startupMessage *pgproto3.Startu
 			saslContinue = SASLContinue{
 				Nonce:      saslInitial.Nonce + auth.GenerateRandomOctetString(16).ToBase64(),
 				Salt:       salt,
-				Iterations: 4096,
+				Iterations: iterations,
 			}
 			if err = h.send(saslContinue.Encode()); err != nil {
 				return err
@@ -204,9 +374,116 @@ func (h *ConnectionHandler) handleAuthentication(startupMessage *pgproto3.Startu
 	}
 }
 
-// readSASLInitial reads the initial SASL response from the client.
-func readSASLInitial(r *pgproto3.SASLInitialResponse) (SASLInitial, error) {
-	if r.AuthMechanism != SASLMechanism_SCRAM_SHA_256 {
+// handlePeerAuthentication authenticates the connection over a Unix domain socket if the operating system user of the
+// client is the user to log in as, which must be a role that can log in, with or without a password.
+func (h *ConnectionHandler) handlePeerAuthentication(username string) error {
+	// A role without a password is only stored in pg_authid, not among the roles used for password authentication.
+	role, err := h.currentRole()
+	if err != nil {
+		h.logger.WithError(err).Warnln("Failed to look up the role of the connection")
+	}
+	canLogin := auth.GetRole(username).CanLogin || (role != nil && role.canLogin)
+	if h.peerUser == "" || h.peerUser != username || !canLogin {
+		err := fmt.Errorf(`peer authentication failed for user "%s"`, username)
+		_ = h.send(&pgproto3.ErrorResponse{
+			Severity: "FATAL",
+			Code:     "28000",
+			Message:  err.Error(),
+		})
+		return err
+	}
+	return h.send(&pgproto3.AuthenticationOk{})
+}
+
+// handlePasswordAuthentication authenticates the connection by the password sent in clear text, which is verified
+// against the SCRAM-SHA-256 verifier of the role.
+func (h *ConnectionHandler) handlePasswordAuthentication(username string) error {
+	if err := h.send(&pgproto3.AuthenticationCleartextPassword{}); err != nil {
+		return err
+	}
+	if err := h.backend.SetAuthType(pgproto3.AuthTypeCleartextPassword); err != nil {
+		return err
+	}
+	msg, err := h.backend.Receive()
+	if err != nil {
+		return err
+	}
+	response, ok := msg.(*pgproto3.PasswordMessage)
+	if !ok {
+		return fmt.Errorf("unknown message type encountered during password authentication: %T", msg)
+	}
+	role := auth.GetRole(username)
+	if !role.CanLogin || role.Password == nil || !verifyScramSha256Password(role.Password, response.Password) {
+		err := fmt.Errorf(`password authentication failed for user "%s"`, username)
+		_ = h.send(&pgproto3.ErrorResponse{
+			Severity: "FATAL",
+			Code:     "28P01",
+			Message:  err.Error(),
+		})
+		return err
+	}
+	return h.send(&pgproto3.AuthenticationOk{})
+}
+
+// verifyScramSha256Password returns whether the clear text password derives the stored key of the verifier, as
+// defined in RFC 5802: StoredKey := H(HMAC(Hi(password, salt, i), "Client Key")).
+func verifyScramSha256Password(verifier *auth.ScramSha256Password, password string) bool {
+	// Hi is PBKDF2 with HMAC-SHA-256, of a single block since the output is as long as the hash.
+	mac := hmac.New(sha256.New, []byte(password))
+	mac.Write(verifier.Salt)
+	mac.Write([]byte{0, 0, 0, 1})
+	u := mac.Sum(nil)
+	salted := slices.Clone(u)
+	for i := uint32(1); i < verifier.Iterations; i++ {
+		mac.Reset()
+		mac.Write(u)
+		u = mac.Sum(u[:0])
+		for j := range salted {
+			salted[j] ^= u[j]
+		}
+	}
+	mac = hmac.New(sha256.New, salted)
+	mac.Write([]byte("Client Key"))
+	storedKey := sha256.Sum256(mac.Sum(nil))
+	return hmac.Equal(storedKey[:], verifier.StoredKey)
+}
+
+// hbaConnection describes the connection for matching it against the host-based authentication rules.
+func (h *ConnectionHandler) hbaConnection(startupMessage *pgproto3.StartupMessage, username string) hbaConnection {
+	conn := hbaConnection{
+		local:    h.Conn().RemoteAddr().Network() == "unix",
+		database: startupMessage.Parameters["database"],
+		user:     username,
+	}
+	if conn.database == "" {
+		conn.database = username
+	}
+	if _, ok := h.Conn().(*tls.Conn); ok {
+		conn.ssl = true
+	}
+	if addr, ok := h.Conn().RemoteAddr().(*net.TCPAddr); ok {
+		conn.ip = addr.IP
+	} else if !conn.local {
+		host, _, _ := net.SplitHostPort(h.Conn().RemoteAddr().String())
+		conn.ip = net.ParseIP(host)
+	}
+	switch strings.ToLower(startupMessage.Parameters["replication"]) {
+	case "true", "on", "yes", "1":
+		conn.replication = true
+	}
+	return conn
+}
+
+// readSASLInitial reads the initial SASL response from the client. |channelBinding| is the cbind-data of the
+// connection, or nil if it does not support channel binding.
+func readSASLInitial(r *pgproto3.SASLInitialResponse, channelBinding []byte) (SASLInitial, error) {
+	switch r.AuthMechanism {
+	case SASLMechanism_SCRAM_SHA_256:
+	case SASLMechanism_SCRAM_SHA_256_PLUS:
+		if channelBinding == nil {
+			return SASLInitial{}, fmt.Errorf("SASL mechanism not supported without SSL: %s", r.AuthMechanism)
+		}
+	default:
 		return SASLInitial{}, fmt.Errorf("SASL mechanism not supported: %s", r.AuthMechanism)
 	}
 	saslInitial := SASLInitial{}
@@ -265,6 +542,24 @@ func readSASLInitial(r *pgproto3.SASLInitialResponse) (SASLInitial, error) {
 	if len(saslInitial.Nonce) == 0 {
 		return SASLInitial{}, fmt.Errorf("invalid SASLInitialResponse: missing nonce")
 	}
+
+	// Channel binding is used with SCRAM-SHA-256-PLUS, and only then.
+	switch {
+	case r.AuthMechanism == SASLMechanism_SCRAM_SHA_256_PLUS:
+		if saslInitial.Flag != SASLBindingFlag_Used {
+			return SASLInitial{}, fmt.Errorf("invalid SASLInitialResponse: channel binding is required by %s", r.AuthMechanism)
+		}
+		if saslInitial.BindName != SASLChannelBinding_TLSServerEndPoint {
+			return SASLInitial{}, fmt.Errorf("unsupported SCRAM channel-binding type: %s", saslInitial.BindName)
+		}
+		saslInitial.ChannelBinding = channelBinding
+	case saslInitial.Flag == SASLBindingFlag_Used:
+		return SASLInitial{}, fmt.Errorf("invalid SASLInitialResponse: channel binding requires %s", SASLMechanism_SCRAM_SHA_256_PLUS)
+	case saslInitial.Flag == SASLBindingFlag_AssumedNoServerSupport && channelBinding != nil:
+		// The client supports channel binding and thinks that the server does not, although it was offered.
+		return SASLInitial{}, fmt.Errorf("SCRAM channel binding negotiation error")
+	}
+
 	// Copy the message bytes, since the backend may re-use the slice for future responses
 	saslInitial.RawData = make([]byte, len(r.Data))
 	copy(saslInitial.RawData, r.Data)
@@ -334,7 +629,11 @@ func verifySASLClientProof(user auth.Role, saslInitial SASLInitial, saslContinue
 
 // Base64Header returns the base64-encoded GS2 header and channel binding data.
 func (si SASLInitial) Base64Header() string {
-	return base64.StdEncoding.EncodeToString(si.base64HeaderBytes())
+	cbindInput := si.base64HeaderBytes()
+	if si.Flag == SASLBindingFlag_Used {
+		cbindInput = append(cbindInput, si.ChannelBinding...)
+	}
+	return base64.StdEncoding.EncodeToString(cbindInput)
 }
 
 // MessageBare returns the message without the GS2 header.
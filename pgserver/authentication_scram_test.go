@@ -0,0 +1,123 @@
+package pgserver
+
+import (
+	"bytes"
+	"crypto/hmac"
+	"crypto/sha256"
+	"encoding/base64"
+	"encoding/binary"
+	"testing"
+
+	"github.com/dolthub/doltgresql/server/auth"
+	"github.com/jackc/pgx/v5/pgproto3"
+)
+
+// saltedPassword is Hi(password, salt, iterations) of RFC 5802, i.e., PBKDF2 with HMAC-SHA-256.
+func saltedPassword(password string, salt []byte, iterations uint32) []byte {
+	mac := hmac.New(sha256.New, []byte(password))
+	mac.Write(salt)
+	mac.Write(binary.BigEndian.AppendUint32(nil, 1))
+	u := mac.Sum(nil)
+	result := bytes.Clone(u)
+	for i := uint32(1); i < iterations; i++ {
+		mac.Reset()
+		mac.Write(u)
+		u = mac.Sum(nil)
+		for j := range result {
+			result[j] ^= u[j]
+		}
+	}
+	return result
+}
+
+func TestParseScramSha256Verifier(t *testing.T) {
+	// Written by Postgres for the password "secret".
+	verifier := "SCRAM-SHA-256$4096:fKuV8Hs72ynsjTn6+qsFuw==$8parcoIgoe+2RABDi+Vd03jN0Va4DsLYzCECdPoDPc4=:WIr0fifK8acsQjUpHuaE1bHOQAQ71hFGKPA71TrjjXI="
+	password, err := ParseScramSha256Verifier(verifier)
+	if err != nil {
+		t.Fatal(err)
+	}
+	if password.Iterations != 4096 {
+		t.Errorf("iterations = %d, expected 4096", password.Iterations)
+	}
+
+	salted := saltedPassword("secret", password.Salt, password.Iterations)
+	mac := hmac.New(sha256.New, salted)
+	mac.Write([]byte("Client Key"))
+	storedKey := sha256.Sum256(mac.Sum(nil))
+	if !bytes.Equal(storedKey[:], password.StoredKey) {
+		t.Errorf("StoredKey = %x, expected %x", []byte(password.StoredKey), storedKey)
+	}
+	mac = hmac.New(sha256.New, salted)
+	mac.Write([]byte("Server Key"))
+	if serverKey := mac.Sum(nil); !bytes.Equal(serverKey, password.ServerKey) {
+		t.Errorf("ServerKey = %x, expected %x", []byte(password.ServerKey), serverKey)
+	}
+
+	for _, invalid := range []string{
+		"",
+		"secret",
+		"md5e5e9fa1ba31ecd1ae84f75caaa474f3a",
+		"SCRAM-SHA-256$4096:fKuV8Hs72ynsjTn6+qsFuw==",
+		"SCRAM-SHA-256$0:fKuV8Hs72ynsjTn6+qsFuw==$8parcoIgoe+2RABDi+Vd03jN0Va4DsLYzCECdPoDPc4=:WIr0fifK8acsQjUpHuaE1bHOQAQ71hFGKPA71TrjjXI=",
+		"SCRAM-SHA-256$4096:fKuV8Hs72ynsjTn6+qsFuw==$8parcoIgoe+2RABDi+Vd03jN0Va4DsLYzCECdPoDPc4=",
+		"SCRAM-SHA-256$4096:!!!$8parcoIgoe+2RABDi+Vd03jN0Va4DsLYzCECdPoDPc4=:WIr0fifK8acsQjUpHuaE1bHOQAQ71hFGKPA71TrjjXI=",
+		"SCRAM-SHA-256$4096:fKuV8Hs72ynsjTn6+qsFuw==$fKuV8Hs72ynsjTn6+qsFuw==:WIr0fifK8acsQjUpHuaE1bHOQAQ71hFGKPA71TrjjXI=",
+	} {
+		if _, err := ParseScramSha256Verifier(invalid); err == nil {
+			t.Errorf("expected an error for %q", invalid)
+		}
+	}
+}
+
+func TestReadSASLInitialChannelBinding(t *testing.T) {
+	binding := []byte{1, 2, 3}
+	tests := []struct {
+		name      string
+		mechanism string
+		data      string
+		binding   []byte
+		wantErr   bool
+	}{
+		{"no binding", SASLMechanism_SCRAM_SHA_256, "n,,n=,r=nonce", nil, false},
+		{"no binding over SSL", SASLMechanism_SCRAM_SHA_256, "n,,n=,r=nonce", binding, false},
+		{"binding", SASLMechanism_SCRAM_SHA_256_PLUS, "p=tls-server-end-point,,n=,r=nonce", binding, false},
+		{"binding without SSL", SASLMechanism_SCRAM_SHA_256_PLUS, "p=tls-server-end-point,,n=,r=nonce", nil, true},
+		{"binding without PLUS", SASLMechanism_SCRAM_SHA_256, "p=tls-server-end-point,,n=,r=nonce", binding, true},
+		{"PLUS without binding", SASLMechanism_SCRAM_SHA_256_PLUS, "n,,n=,r=nonce", binding, true},
+		{"unsupported binding type", SASLMechanism_SCRAM_SHA_256_PLUS, "p=tls-unique,,n=,r=nonce", binding, true},
+		{"downgrade", SASLMechanism_SCRAM_SHA_256, "y,,n=,r=nonce", binding, true},
+		{"no server support", SASLMechanism_SCRAM_SHA_256, "y,,n=,r=nonce", nil, false},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			initial, err := readSASLInitial(&pgproto3.SASLInitialResponse{AuthMechanism: tt.mechanism, Data: []byte(tt.data)}, tt.binding)
+			if (err != nil) != tt.wantErr {
+				t.Fatalf("readSASLInitial() error = %v, wantErr %v", err, tt.wantErr)
+			}
+			if err != nil {
+				return
+			}
+			// The c= attribute of the client-final-message is the GS2 header followed by the cbind-data.
+			expected := []byte(tt.data[:len(tt.data)-len("n=,r=nonce")])
+			if initial.Flag == SASLBindingFlag_Used {
+				expected = append(expected, binding...)
+			}
+			if header, want := initial.Base64Header(), base64.StdEncoding.EncodeToString(expected); header != want {
+				t.Errorf("Base64Header() = %s, expected %s", header, want)
+			}
+		})
+	}
+}
+
+func TestVerifyScramSha256Password(t *testing.T) {
+	verifier, err := auth.NewScramSha256Password("secret")
+	if err != nil {
+		t.Fatal(err)
+	}
+	for password, want := range map[string]bool{"secret": true, "Secret": false, "": false} {
+		if got := verifyScramSha256Password(verifier, password); got != want {
+			t.Errorf("verifyScramSha256Password(%q) = %v, expected %v", password, got, want)
+		}
+	}
+}
@@ -0,0 +1,243 @@
+package pgserver
+
+import (
+	"bytes"
+	"encoding/binary"
+	"fmt"
+	"strings"
+
+	"github.com/apache/arrow-go/v18/arrow/array"
+	"github.com/apache/arrow-go/v18/arrow/ipc"
+	"github.com/apache/arrow-go/v18/arrow/memory"
+	"github.com/apecloud/myduckserver/adapter"
+	"github.com/apecloud/myduckserver/catalog"
+	"github.com/apecloud/myduckserver/myarrow"
+	"github.com/apecloud/myduckserver/pgserver/logrepl"
+	"github.com/apecloud/myduckserver/pgtypes"
+	"github.com/cockroachdb/cockroachdb-parser/pkg/sql/sem/tree"
+	"github.com/dolthub/go-mysql-server/sql"
+	"github.com/jackc/pglogrepl"
+	"github.com/jackc/pgx/v5/pgtype"
+)
+
+// binaryCopySignature starts the header of the binary COPY format.
+// https://www.postgresql.org/docs/current/sql-copy.html#id-1.9.3.55.9.4
+var binaryCopySignature = []byte("PGCOPY\n\xff\r\n\x00")
+
+// binaryCopyBatchSize is the number of rows decoded into an Arrow record before it is sent to DuckDB.
+const binaryCopyBatchSize = 1 << 14
+
+// BinaryDataLoader loads data sent in the binary COPY format. The tuples are decoded into Arrow records,
+// which are streamed to an ArrowDataLoader through its pipe in the Arrow IPC format.
+type BinaryDataLoader struct {
+	*ArrowDataLoader
+	columns []*pglogrepl.RelationMessageColumn
+	builder *array.RecordBuilder
+	writer  *ipc.Writer
+	rows    int    // the rows decoded since the last flush
+	partial []byte // the bytes of an incomplete header or tuple, saved for the next chunk
+	header  bool   // whether the header has been read
+	trailer bool   // whether the trailer has been read
+}
+
+var _ DataLoader = (*BinaryDataLoader)(nil)
+
+func NewBinaryDataLoader(ctx *sql.Context, handler *DuckHandler, schema string, table sql.InsertableTable, columns tree.NameList) (DataLoader, error) {
+	// Look up the types of the target columns, which the binary format does not carry.
+	var b strings.Builder
+	b.WriteString("SELECT ")
+	if len(columns) > 0 {
+		b.WriteString(columns.String())
+	} else {
+		b.WriteString("*")
+	}
+	b.WriteString(" FROM ")
+	if schema != "" {
+		b.WriteString(catalog.QuoteIdentifierANSI(schema))
+		b.WriteString(".")
+	}
+	b.WriteString(catalog.QuoteIdentifierANSI(table.Name()))
+	b.WriteString(" LIMIT 0")
+
+	rows, err := adapter.Query(ctx, b.String())
+	if err != nil {
+		return nil, err
+	}
+	defer rows.Close()
+	sqlSchema, err := pgtypes.InferSchema(rows)
+	if err != nil {
+		return nil, err
+	}
+	arrowSchema, err := myarrow.ToArrowSchema(sqlSchema)
+	if err != nil {
+		return nil, err
+	}
+
+	relationColumns := make([]*pglogrepl.RelationMessageColumn, len(sqlSchema))
+	for i, col := range sqlSchema {
+		pgType := col.Type.(pgtypes.PostgresType)
+		typmod := int32(-1)
+		if pgType.Precision > 0 {
+			// The type modifier of numeric(p, s), as decoded by pgtypes.DecodePrecisionScale.
+			typmod = (pgType.Precision<<16 | pgType.Scale) + 4
+		}
+		relationColumns[i] = &pglogrepl.RelationMessageColumn{
+			Name:         col.Name,
+			DataType:     pgType.PG.OID,
+			TypeModifier: typmod,
+		}
+	}
+
+	arrowLoader, err := NewArrowDataLoader(ctx, handler, schema, table, columns, "")
+	if err != nil {
+		return nil, err
+	}
+
+	loader := &BinaryDataLoader{
+		ArrowDataLoader: arrowLoader.(*ArrowDataLoader),
+		columns:         relationColumns,
+		builder:         array.NewRecordBuilder(memory.DefaultAllocator, arrowSchema),
+	}
+	loader.writer = ipc.NewWriter(pipeWriter{loader}, ipc.WithSchema(arrowSchema))
+	return loader, nil
+}
+
+// pipeWriter writes the Arrow IPC stream to the pipe of the ArrowDataLoader.
+type pipeWriter struct {
+	loader *BinaryDataLoader
+}
+
+func (w pipeWriter) Write(p []byte) (int, error) {
+	if err := w.loader.ArrowDataLoader.LoadChunk(w.loader.ctx, p); err != nil {
+		return 0, err
+	}
+	return len(p), nil
+}
+
+func (loader *BinaryDataLoader) LoadChunk(ctx *sql.Context, data []byte) error {
+	if errp := loader.err.Load(); errp != nil {
+		return fmt.Errorf("COPY operation has been aborted: %w", *errp)
+	}
+	if len(loader.partial) > 0 {
+		data = append(loader.partial, data...)
+	}
+
+	n, err := loader.decode(data)
+	if err != nil {
+		loader.Abort(ctx)
+		return err
+	}
+	// Copy the rest, as the client may reuse the buffer of the message.
+	loader.partial = append(loader.partial[:0:0], data[n:]...)
+
+	if loader.rows >= binaryCopyBatchSize {
+		return loader.flush(ctx)
+	}
+	return nil
+}
+
+// decode decodes the header and the complete tuples at the start of |data|, and returns the number of bytes used.
+func (loader *BinaryDataLoader) decode(data []byte) (int, error) {
+	offset := 0
+	if !loader.header {
+		// The signature, the flags, and the length of the header extension area.
+		if len(data) < len(binaryCopySignature)+8 {
+			return 0, nil
+		}
+		if !bytes.Equal(data[:len(binaryCopySignature)], binaryCopySignature) {
+			return 0, fmt.Errorf("COPY file signature not recognized")
+		}
+		offset = len(binaryCopySignature)
+		flags := binary.BigEndian.Uint32(data[offset:])
+		if flags&(1<<16) != 0 {
+			return 0, fmt.Errorf("OIDs are not supported in binary COPY")
+		}
+		extension := int(binary.BigEndian.Uint32(data[offset+4:]))
+		offset += 8
+		if len(data) < offset+extension {
+			return 0, nil
+		}
+		offset += extension
+		loader.header = true
+	}
+
+	for !loader.trailer {
+		if len(data) < offset+2 {
+			return offset, nil
+		}
+		count := int16(binary.BigEndian.Uint16(data[offset:]))
+		if count == -1 {
+			loader.trailer = true
+			offset += 2
+			break
+		}
+		if int(count) != len(loader.columns) {
+			return 0, fmt.Errorf("row field count is %d, expected %d", count, len(loader.columns))
+		}
+
+		// Find the fields of the tuple before decoding any, so that an incomplete tuple is left for the next chunk.
+		fields := make([][]byte, count)
+		end := offset + 2
+		for i := range fields {
+			if len(data) < end+4 {
+				return offset, nil
+			}
+			length := int32(binary.BigEndian.Uint32(data[end:]))
+			end += 4
+			if length < 0 {
+				continue // NULL
+			}
+			if len(data) < end+int(length) {
+				return offset, nil
+			}
+			fields[i] = data[end : end+int(length)]
+			end += int(length)
+		}
+
+		for i, field := range fields {
+			if _, err := logrepl.DecodeToArrow(pgtypes.DefaultTypeMap, loader.columns[i], field, pgtype.BinaryFormatCode, loader.builder.Field(i)); err != nil {
+				return 0, fmt.Errorf("column %s: %w", loader.columns[i].Name, err)
+			}
+		}
+		loader.rows++
+		offset = end
+	}
+
+	if offset < len(data) {
+		return 0, fmt.Errorf("unexpected data after the binary COPY trailer")
+	}
+	return offset, nil
+}
+
+// flush sends the decoded rows to DuckDB as an Arrow record.
+func (loader *BinaryDataLoader) flush(ctx *sql.Context) error {
+	record := loader.builder.NewRecord()
+	defer record.Release()
+	loader.rows = 0
+	if err := loader.writer.Write(record); err != nil {
+		loader.Abort(ctx)
+		return err
+	}
+	return nil
+}
+
+func (loader *BinaryDataLoader) Finish(ctx *sql.Context) (*LoadDataResults, error) {
+	if errp := loader.err.Load(); errp != nil {
+		return nil, *errp
+	}
+	if !loader.trailer {
+		loader.Abort(ctx)
+		return nil, fmt.Errorf("unexpected EOF in binary COPY data")
+	}
+	if loader.rows > 0 {
+		if err := loader.flush(ctx); err != nil {
+			return nil, err
+		}
+	}
+	// Close the Arrow IPC stream, so that the reader sees its end.
+	if err := loader.writer.Close(); err != nil {
+		loader.Abort(ctx)
+		return nil, err
+	}
+	return loader.ArrowDataLoader.Finish(ctx)
+}
@@ -0,0 +1,85 @@
+package pgserver
+
+import (
+	"os"
+	"testing"
+
+	"github.com/apache/arrow-go/v18/arrow"
+	"github.com/apache/arrow-go/v18/arrow/array"
+	"github.com/apache/arrow-go/v18/arrow/memory"
+	"github.com/jackc/pglogrepl"
+	"github.com/jackc/pgx/v5/pgtype"
+)
+
+func TestBinaryCopyDecode(t *testing.T) {
+	// Written by PostgreSQL with COPY ... TO STDOUT (FORMAT binary), the columns are
+	// (i int, s text, f float8, n numeric(10, 2), ts timestamp, b bytea) and the last row is all NULLs but i.
+	data, err := os.ReadFile("../pgtest/testdata/basic.bin")
+	if err != nil {
+		t.Fatal(err)
+	}
+	columns := []*pglogrepl.RelationMessageColumn{
+		{Name: "i", DataType: pgtype.Int4OID, TypeModifier: -1},
+		{Name: "s", DataType: pgtype.TextOID, TypeModifier: -1},
+		{Name: "f", DataType: pgtype.Float8OID, TypeModifier: -1},
+		{Name: "n", DataType: pgtype.NumericOID, TypeModifier: (10<<16 | 2) + 4},
+		{Name: "ts", DataType: pgtype.TimestampOID, TypeModifier: -1},
+		{Name: "b", DataType: pgtype.ByteaOID, TypeModifier: -1},
+	}
+	schema := arrow.NewSchema([]arrow.Field{
+		{Name: "i", Type: arrow.PrimitiveTypes.Int32, Nullable: true},
+		{Name: "s", Type: arrow.BinaryTypes.String, Nullable: true},
+		{Name: "f", Type: arrow.PrimitiveTypes.Float64, Nullable: true},
+		{Name: "n", Type: &arrow.Decimal128Type{Precision: 10, Scale: 2}, Nullable: true},
+		{Name: "ts", Type: &arrow.TimestampType{Unit: arrow.Microsecond}, Nullable: true},
+		{Name: "b", Type: arrow.BinaryTypes.Binary, Nullable: true},
+	}, nil)
+
+	// Split the data at every offset, as CopyData messages may end anywhere.
+	for split := 0; split <= len(data); split++ {
+		loader := &BinaryDataLoader{
+			columns: columns,
+			builder: array.NewRecordBuilder(memory.DefaultAllocator, schema),
+		}
+		n, err := loader.decode(data[:split])
+		if err != nil {
+			t.Fatalf("split at %d: %v", split, err)
+		}
+		rest := append(append([]byte{}, data[n:split]...), data[split:]...)
+		if _, err := loader.decode(rest); err != nil {
+			t.Fatalf("split at %d: %v", split, err)
+		}
+		if !loader.trailer {
+			t.Fatalf("split at %d: the trailer was not read", split)
+		}
+
+		record := loader.builder.NewRecord()
+		if record.NumRows() != 3 {
+			t.Fatalf("split at %d: decoded %d rows, expected 3", split, record.NumRows())
+		}
+		if s := record.Column(1).(*array.String).Value(1); s != "zwei ü" {
+			t.Errorf("split at %d: s = %q, expected %q", split, s, "zwei ü")
+		}
+		if n := record.Column(3).(*array.Decimal128).Value(0).LowBits(); n != 1025 {
+			t.Errorf("split at %d: n = %d, expected 1025", split, n)
+		}
+		if b := record.Column(5).(*array.Binary).Value(0); string(b) != "\xde\xad\xbe\xef" {
+			t.Errorf("split at %d: b = %x, expected deadbeef", split, b)
+		}
+		for j := 1; j < len(columns); j++ {
+			if !record.Column(j).IsNull(2) {
+				t.Errorf("split at %d: column %s of the last row is not NULL", split, columns[j].Name)
+			}
+		}
+		record.Release()
+	}
+
+	// Trailing data after the trailer is rejected.
+	loader := &BinaryDataLoader{
+		columns: columns,
+		builder: array.NewRecordBuilder(memory.DefaultAllocator, schema),
+	}
+	if _, err := loader.decode(append(append([]byte{}, data...), 0)); err == nil {
+		t.Error("expected an error for data after the trailer")
+	}
+}
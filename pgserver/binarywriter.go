@@ -0,0 +1,175 @@
+package pgserver
+
+import (
+	"bufio"
+	"encoding/binary"
+	"io"
+	"os"
+	"strings"
+	"sync/atomic"
+
+	"github.com/apecloud/myduckserver/adapter"
+	"github.com/apecloud/myduckserver/backend"
+	"github.com/apecloud/myduckserver/catalog"
+	"github.com/apecloud/myduckserver/pgtypes"
+	"github.com/cockroachdb/cockroachdb-parser/pkg/sql/sem/tree"
+	"github.com/dolthub/go-mysql-server/sql"
+	"github.com/jackc/pgx/v5/pgtype"
+)
+
+// BinaryWriter writes the result of a query in the binary COPY format of Postgres,
+// encoding each value as it would be sent in a DataRow with the binary format code.
+type BinaryWriter struct {
+	ctx      *sql.Context
+	duckSQL  string
+	pipePath string
+}
+
+func NewBinaryWriter(
+	ctx *sql.Context,
+	handler *DuckHandler,
+	schema string, table sql.Table, columns tree.NameList,
+	query string,
+) (*BinaryWriter, error) {
+	// Create the FIFO pipe
+	db := handler.e.Analyzer.ExecBuilder.(*backend.DuckBuilder)
+	pipePath, err := db.CreatePipe(ctx, "pg-to-binary")
+	if err != nil {
+		return nil, err
+	}
+
+	var builder strings.Builder
+	builder.Grow(128)
+
+	if table != nil {
+		builder.WriteString("FROM ")
+		if schema != "" {
+			builder.WriteString(catalog.QuoteIdentifierANSI(schema))
+			builder.WriteString(".")
+		}
+		builder.WriteString(catalog.QuoteIdentifierANSI(table.Name()))
+		if columns != nil {
+			builder.WriteString(" SELECT ")
+			builder.WriteString(columns.String())
+		}
+	} else {
+		builder.WriteString(query)
+	}
+
+	return &BinaryWriter{
+		ctx:      ctx,
+		duckSQL:  builder.String(),
+		pipePath: pipePath,
+	}, nil
+}
+
+func (dw *BinaryWriter) Start(globalErr *atomic.Pointer[error]) (string, chan CopyToResult, error) {
+	// Execute the statement in a separate goroutine.
+	ch := make(chan CopyToResult, 1)
+	go func() {
+		defer close(ch)
+
+		// Open the pipe for writing.
+		// This operation will block until the reader opens the pipe for reading.
+		// It is opened before the query runs, so that a failing query only closes it.
+		pipe, err := os.OpenFile(dw.pipePath, os.O_WRONLY, os.ModeNamedPipe)
+		if err != nil {
+			globalErr.Store(&err)
+			ch <- CopyToResult{Err: err}
+			return
+		}
+		defer pipe.Close()
+
+		// If there is a global error, return immediately.
+		if e := globalErr.Load(); e != nil {
+			ch <- CopyToResult{Err: *e}
+			return
+		}
+
+		rowCount, err := dw.write(pipe)
+		if err != nil {
+			globalErr.Store(&err)
+			ch <- CopyToResult{Err: err}
+			return
+		}
+		ch <- CopyToResult{RowCount: rowCount}
+	}()
+
+	return dw.pipePath, ch, nil
+}
+
+// write runs the query and writes its rows to |w| in the binary COPY format.
+// https://www.postgresql.org/docs/current/sql-copy.html#id-1.9.3.55.9.4
+func (dw *BinaryWriter) write(w io.Writer) (int64, error) {
+	dw.ctx.GetLogger().Tracef("Executing statement for binary COPY: %s", dw.duckSQL)
+	rows, err := adapter.Query(dw.ctx, dw.duckSQL)
+	if err != nil {
+		return 0, err
+	}
+	defer rows.Close()
+
+	schema, err := pgtypes.InferSchema(rows)
+	if err != nil {
+		return 0, err
+	}
+	iter, err := NewSqlRowIter(rows, schema)
+	if err != nil {
+		return 0, err
+	}
+
+	out := bufio.NewWriterSize(w, 1<<20)
+
+	// The header: the signature, no flags, and an empty header extension area.
+	out.Write(binaryCopySignature)
+	out.Write([]byte{0, 0, 0, 0, 0, 0, 0, 0})
+
+	var (
+		rowCount int64
+		buf      []byte
+	)
+	for {
+		row, err := iter.Next(dw.ctx)
+		if err == io.EOF {
+			break
+		} else if err != nil {
+			return 0, err
+		}
+
+		buf = binary.BigEndian.AppendUint16(buf[:0], uint16(len(row)))
+		for i, v := range row {
+			if v == nil {
+				buf = binary.BigEndian.AppendUint32(buf, 0xFFFFFFFF) // -1 for NULL
+				continue
+			}
+			// Reserve the length, then encode the value after it.
+			start := len(buf)
+			buf = append(buf, 0, 0, 0, 0)
+			oid := schema[i].Type.(pgtypes.PostgresType).PG.OID
+			encoded, err := pgtypes.DefaultTypeMap.Encode(oid, pgtype.BinaryFormatCode, v, buf)
+			if err != nil {
+				return 0, err
+			}
+			if encoded == nil { // the value encodes to NULL
+				buf = binary.BigEndian.AppendUint32(buf[:start], 0xFFFFFFFF)
+				continue
+			}
+			buf = encoded
+			binary.BigEndian.PutUint32(buf[start:], uint32(len(buf)-start-4))
+		}
+		if _, err := out.Write(buf); err != nil {
+			return 0, err
+		}
+		rowCount++
+	}
+
+	// The trailer: a field count of -1.
+	out.Write([]byte{0xFF, 0xFF})
+	if err := out.Flush(); err != nil {
+		return 0, err
+	}
+	return rowCount, nil
+}
+
+func (dw *BinaryWriter) Close() {
+	os.Remove(dw.pipePath)
+}
@@ -0,0 +1,102 @@
+package pgserver
+
+import (
+	"context"
+	"crypto/rand"
+	"crypto/subtle"
+	"encoding/binary"
+	"errors"
+	"fmt"
+	"strings"
+
+	"github.com/jackc/pgx/v5/pgproto3"
+	"github.com/sirupsen/logrus"
+)
+
+// The backend key data of a connection is its connection ID and a random secret key.
+// A client cancels the running query of the connection by sending both in a CancelRequest over a new connection.
+// https://www.postgresql.org/docs/current/protocol-flow.html#PROTOCOL-FLOW-CANCELING-REQUESTS
+
+// registerBackend generates the secret key of the connection and registers it for cancellation.
+func (s *Server) registerBackend(h *ConnectionHandler) error {
+	var b [4]byte
+	if _, err := rand.Read(b[:]); err != nil {
+		return err
+	}
+	h.secretKey = binary.BigEndian.Uint32(b[:])
+	s.backends.Store(h.mysqlConn.ConnectionID, h)
+	return nil
+}
+
+// unregisterBackend removes the connection from the registered ones, if it was registered.
+func (s *Server) unregisterBackend(h *ConnectionHandler) {
+	s.backends.CompareAndDelete(h.mysqlConn.ConnectionID, h)
+}
+
+// lookupBackend returns the connection with the given ID, or nil if there is no such connection.
+func (s *Server) lookupBackend(pid uint32) *ConnectionHandler {
+	if v, ok := s.backends.Load(pid); ok {
+		return v.(*ConnectionHandler)
+	}
+	return nil
+}
+
+// handleCancelRequest cancels the running query of the connection identified by the request.
+// Postgres sends no response to a CancelRequest, so a mismatched key is only logged.
+func (s *Server) handleCancelRequest(r *pgproto3.CancelRequest) {
+	target := s.lookupBackend(r.ProcessID)
+	if target == nil || subtle.ConstantTimeEq(int32(target.secretKey), int32(r.SecretKey)) != 1 {
+		logrus.WithField("connectionID", r.ProcessID).Warnln("Ignoring a CancelRequest with an unknown backend key")
+		return
+	}
+	target.logger.Infoln("Canceling the running query at the request of the client")
+	target.cancelQuery()
+}
+
+// cancelQuery cancels the running query of the connection, if there is one.
+func (h *ConnectionHandler) cancelQuery() {
+	h.duckHandler.e.ProcessList.Kill(h.mysqlConn.ConnectionID)
+}
+
+// terminate cancels the running query of the connection and closes it,
+// which stops its message loop as if the client had hung up.
+func (h *ConnectionHandler) terminate() {
+	h.cancelQuery()
+	if err := h.Conn().Close(); err != nil {
+		h.logger.WithError(err).Warnln("Failed to close the connection")
+	}
+}
+
+// signalBackend implements pg_cancel_backend() and pg_terminate_backend(): it cancels the running query of the
+// connection with the given ID, and also terminates the connection if |terminate| is set. As in Postgres, a user may
+// signal its own connections only, unless it is the superuser. It returns false if there is no such connection.
+func (h *ConnectionHandler) signalBackend(pid uint32, terminate bool) (bool, error) {
+	var target *ConnectionHandler
+	if h.server != nil {
+		target = h.server.lookupBackend(pid)
+	}
+	if target == nil {
+		_ = h.sendNotice(ErrorResponseSeverity_Warning, "01000" /* warning */, fmt.Sprintf("PID %d is not a MyDuck Server backend process", pid))
+		return false, nil
+	}
+	if h.mysqlConn.User != target.mysqlConn.User && !h.isSuperuser() {
+		action := "cancel query"
+		if terminate {
+			action = "terminate process"
+		}
+		return false, fmt.Errorf("permission denied to %s: only superusers and the role of the process may signal it", action)
+	}
+	if terminate {
+		target.logger.Infof("Terminating the connection at the request of connection %d", h.mysqlConn.ConnectionID)
+		target.terminate()
+	} else {
+		target.logger.Infof("Canceling the running query at the request of connection %d", h.mysqlConn.ConnectionID)
+		target.cancelQuery()
+	}
+	return true, nil
+}
+
+// isQueryCanceled returns whether the error results from a query canceled by a CancelRequest or pg_cancel_backend().
+func isQueryCanceled(err error) bool {
+	return errors.Is(err, context.Canceled) || strings.Contains(err.Error(), "INTERRUPT Error")
+}
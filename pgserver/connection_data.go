@@ -62,6 +62,17 @@ type ConvertedStatement struct {
 	SubscriptionConfig *SubscriptionConfig
 	BackupConfig       *BackupConfig
 	RestoreConfig      *RestoreConfig
+	CursorConfig       *CursorConfig
+	NotifyConfig       *NotifyConfig
+	TempTableConfig    *TempTableConfig
+	TwoPhaseConfig     *TwoPhaseConfig
+	AdvisoryLockConfig *AdvisoryLockConfig
+	RoleConfig         *RoleConfig
+	PublicationConfig  *PublicationConfig
+	ReplicationCommand *ReplicationCommand
+	PrepareConfig      *PrepareConfig
+	CopyFileConfig     *CopyFileConfig
+	ForeignDataConfig  *ForeignDataConfig
 }
 
 func (cs ConvertedStatement) WithQueryString(queryString string) ConvertedStatement {
@@ -75,6 +86,17 @@ func (cs ConvertedStatement) WithQueryString(queryString string) ConvertedStatem
 		SubscriptionConfig: cs.SubscriptionConfig,
 		BackupConfig:       cs.BackupConfig,
 		RestoreConfig:      cs.RestoreConfig,
+		CursorConfig:       cs.CursorConfig,
+		NotifyConfig:       cs.NotifyConfig,
+		TempTableConfig:    cs.TempTableConfig,
+		TwoPhaseConfig:     cs.TwoPhaseConfig,
+		AdvisoryLockConfig: cs.AdvisoryLockConfig,
+		RoleConfig:         cs.RoleConfig,
+		PublicationConfig:  cs.PublicationConfig,
+		ReplicationCommand: cs.ReplicationCommand,
+		PrepareConfig:      cs.PrepareConfig,
+		CopyFileConfig:     cs.CopyFileConfig,
+		ForeignDataConfig:  cs.ForeignDataConfig,
 	}
 }
 
@@ -109,7 +131,9 @@ type PortalData struct {
 	ResultFormatCodes []int16
 	Stmt              *duckdb.Stmt
 	Vars              []any
-	Closed            *atomic.Bool
+	Closed            *atomic.Bool // Shared with the prepared statement, which owns |Stmt|
+	// Cursor is the open result of the portal once an Execute message with a row limit has suspended it.
+	Cursor *portalCursor
 }
 
 type PreparedStatementData struct {
@@ -19,6 +19,7 @@ import (
 	"bytes"
 	"context"
 	"crypto/tls"
+	"encoding/binary"
 	"encoding/json"
 	"errors"
 	"fmt"
@@ -28,15 +29,18 @@ import (
 	"runtime/debug"
 	"slices"
 	"strings"
+	"sync"
 	"sync/atomic"
 
 	"github.com/apecloud/myduckserver/adapter"
+	"github.com/apecloud/myduckserver/pgtypes"
 	"github.com/cockroachdb/cockroachdb-parser/pkg/sql/parser"
 	"github.com/cockroachdb/cockroachdb-parser/pkg/sql/sem/tree"
 	gms "github.com/dolthub/go-mysql-server"
 	"github.com/dolthub/go-mysql-server/server"
 	"github.com/dolthub/go-mysql-server/sql"
 	"github.com/dolthub/vitess/go/mysql"
+	"github.com/jackc/pgx/v5/pgconn"
 	"github.com/jackc/pgx/v5/pgproto3"
 	"github.com/jackc/pgx/v5/pgtype"
 	"github.com/sirupsen/logrus"
@@ -55,6 +59,47 @@ type ConnectionHandler struct {
 	// copyFromStdinState is set when this connection is in the COPY FROM STDIN mode, meaning it is waiting on
 	// COPY DATA messages from the client to import data into tables.
 	copyFromStdinState *copyFromStdinState
+	// cursors are the open cursors declared by DECLARE CURSOR statements, by name.
+	cursors map[string]*sqlCursor
+	// secretKey is sent to the client in the BackendKeyData, and identifies it in a CancelRequest.
+	secretKey uint32
+	// inTransaction is set between the BEGIN and the COMMIT or ROLLBACK of a transaction block.
+	inTransaction bool
+	// listening are the channels this connection listens on, by LISTEN statements.
+	listening map[string]struct{}
+	// outgoingNotifications are the notifications sent in the current transaction, which are delivered at COMMIT.
+	outgoingNotifications []*pgproto3.NotificationResponse
+	// customSettings are the custom parameters of the application, whose names contain a dot, e.g., myapp.tenant_id.
+	customSettings map[string]string
+	// pgSettingsCreated is set once the pg_settings table of the session is created in DuckDB.
+	pgSettingsCreated bool
+	// activity is the state and the current query of the connection shown in pg_stat_activity.
+	activity backendActivity
+	// statement is the statement being executed, which is counted in pg_stat_statements when it completes.
+	statement statementTiming
+	// insertBatch is the implicit transaction grouping the INSERT statements run outside transaction blocks.
+	insertBatch insertBatch
+	// tempTables are the temporary tables created with ON COMMIT DROP or ON COMMIT DELETE ROWS.
+	tempTables []*tempTable
+	// transactionLog are the statements run in the current transaction block, which are replayed to emulate
+	// ROLLBACK TO SAVEPOINT.
+	transactionLog transactionLog
+	// walsender is set for the replication connections, which accept the replication commands.
+	walsender bool
+	// replication is the status of the stream of a replication connection shown in pg_stat_replication.
+	replication walSenderStatus
+	// terminated is set once an error has ended the protocol of the connection, so that it is closed.
+	terminated bool
+	// peerUser is the operating system user of the client over a Unix domain socket, for the peer authentication.
+	peerUser string
+
+	// sendMu serializes the messages sent to the client, since the notifications are delivered by the goroutines
+	// of the notifying connections. It also guards the fields below.
+	sendMu sync.Mutex
+	// idle is set once ReadyForQuery is sent outside a transaction, until the next message is received.
+	idle bool
+	// incomingNotifications are the notifications received while the connection is not idle.
+	incomingNotifications []*pgproto3.NotificationResponse
 
 	server *Server
 	logger *logrus.Entry
@@ -100,6 +145,9 @@ func NewConnectionHandler(conn net.Conn, handler mysql.Handler, engine *gms.Engi
 		mysqlConn:          mysqlConn,
 		preparedStatements: preparedStatements,
 		portals:            portals,
+		cursors:            make(map[string]*sqlCursor),
+		listening:          make(map[string]struct{}),
+		customSettings:     make(map[string]string),
 		duckHandler:        duckHandler,
 		backend:            pgproto3.NewBackend(conn, conn),
 		pgTypeMap:          pgtype.NewMap(),
@@ -127,6 +175,13 @@ func (h *ConnectionHandler) closeBackendConn() {
 // Expected to run in a goroutine per connection.
 func (h *ConnectionHandler) HandleConnection() {
 	var returnErr error
+	if h.server != nil {
+		defer h.server.unregisterBackend(h)
+	}
+	defer h.closeCursors()
+	defer h.deletePortals()
+	defer h.unlisten("")
+	defer h.releaseAdvisoryLocks(true)
 	if HandlePanics {
 		defer func() {
 			if r := recover(); r != nil {
@@ -181,6 +236,12 @@ func (h *ConnectionHandler) HandleConnection() {
 			break
 		}
 	}
+
+	h.insertBatch.mu.Lock()
+	defer h.insertBatch.mu.Unlock()
+	if err := h.commitInsertBatch(); err != nil {
+		h.logger.Warnf("Failed to commit the batched INSERT statements at the end of the connection: %v", err)
+	}
 }
 
 // Conn returns the underlying net.Conn for this connection.
@@ -207,6 +268,9 @@ func (h *ConnectionHandler) handleStartup() (bool, error) {
 
 	switch sm := startupMessage.(type) {
 	case *pgproto3.StartupMessage:
+		if err = h.checkSSL(sm); err != nil {
+			return false, err
+		}
 		if err = h.handleAuthentication(sm); err != nil {
 			return false, err
 		}
@@ -216,10 +280,25 @@ func (h *ConnectionHandler) handleStartup() (bool, error) {
 		if err = h.chooseInitialDatabase(sm); err != nil {
 			return false, err
 		}
+		if err = h.checkRoleLogin(); err != nil {
+			return false, err
+		}
+		if err = h.checkReplicationConnection(sm); err != nil {
+			return false, err
+		}
+		if err = h.applyStartupSearchPath(sm); err != nil {
+			return false, err
+		}
+		if err = h.initActivity(sm); err != nil {
+			return false, err
+		}
 		return true, h.send(&pgproto3.ReadyForQuery{
 			TxStatus: byte(ReadyForQueryTransactionIndicator_Idle),
 		})
 	case *pgproto3.SSLRequest:
+		if _, ok := h.Conn().(*tls.Conn); ok {
+			return false, fmt.Errorf("terminating connection: SSL was requested twice")
+		}
 		hasCertificate := len(certificate.Certificate) > 0
 		var performSSL = []byte("N")
 		if hasCertificate {
@@ -233,11 +312,28 @@ func (h *ConnectionHandler) handleStartup() (bool, error) {
 		// This involves swapping out our underlying net connection for a new one.
 		// We can't start in SSL mode, as the client does not attempt the handshake until after our response.
 		if hasCertificate {
-			h.setConn(tls.Server(h.Conn(), &tls.Config{
+			config := &tls.Config{
 				Certificates: []tls.Certificate{certificate},
-			}))
+				MinVersion:   tls.VersionTLS12,
+			}
+			if clientCAs != nil {
+				config.ClientCAs = clientCAs
+				config.ClientAuth = tls.VerifyClientCertIfGiven
+			}
+			conn := tls.Server(h.Conn(), config)
+			// Perform the handshake now, so that a failure is reported as such instead of as a bad startup message.
+			if err = conn.Handshake(); err != nil {
+				return false, fmt.Errorf("error performing SSL handshake: %w", err)
+			}
+			h.setConn(conn)
 		}
 		return h.handleStartup()
+	case *pgproto3.CancelRequest:
+		// The connection is closed without any response after the request is handled.
+		if h.server != nil {
+			h.server.handleCancelRequest(sm)
+		}
+		return false, nil
 	case *pgproto3.GSSEncRequest:
 		// we don't support GSSAPI
 		_, err = h.Conn().Write([]byte("N"))
@@ -250,8 +346,31 @@ func (h *ConnectionHandler) handleStartup() (bool, error) {
 	}
 }
 
+// checkSSL rejects the startup of a TCP connection without SSL if the listener requires SSL.
+func (h *ConnectionHandler) checkSSL(startupMessage *pgproto3.StartupMessage) error {
+	if !requireSSL || h.Conn().RemoteAddr().Network() == "unix" {
+		return nil
+	}
+	if _, ok := h.Conn().(*tls.Conn); ok {
+		return nil
+	}
+	host, _, _ := net.SplitHostPort(h.Conn().RemoteAddr().String())
+	err := fmt.Errorf("connection for host %q, user %q, database %q is rejected: SSL is required",
+		host, startupMessage.Parameters["user"], startupMessage.Parameters["database"])
+	_ = h.send(&pgproto3.ErrorResponse{
+		Severity: "FATAL",
+		Code:     "28000", // invalid_authorization_specification
+		Message:  err.Error(),
+	})
+	return err
+}
+
 // sendClientStartupMessages sends introductory messages to the client and returns any error
 func (h *ConnectionHandler) sendClientStartupMessages() error {
+	isSuperuser := "off"
+	if h.isSuperuser() {
+		isSuperuser = "on"
+	}
 	sessParams := []struct {
 		Name  string
 		Value any
@@ -264,18 +383,18 @@ func (h *ConnectionHandler) sendClientStartupMessages() error {
 		// a connection to this server.
 		// Some of these may not exists in postgresConfigParameters(in doltgresql),
 		// which lists all the available parameters in PostgreSQL. In that case,
-		// we will use a mock value for that parameter. e.g. "on" for "is_superuser".
+		// we will use a mock value for that parameter. e.g. "on" for "integer_datetimes".
 		{"in_hot_standby", nil},
 		{"integer_datetimes", "on"},
 		{"TimeZone", nil},
 		{"IntervalStyle", nil},
-		{"is_superuser", "on"}, // This is not specified in postgresConfigParameters now.
+		{"is_superuser", isSuperuser}, // This is not specified in postgresConfigParameters now.
 		{"application_name", nil},
 		{"default_transaction_read_only", nil},
 		{"scram_iterations", nil},
 		{"DateStyle", nil},
 		{"standard_conforming_strings", nil},
-		{"session_authorization", "postgres"}, // This is not specified in postgresConfigParameters now.
+		{"session_authorization", h.mysqlConn.User}, // This is not specified in postgresConfigParameters now.
 		{"client_encoding", nil},
 		{"server_version", nil},
 		{"server_encoding", nil},
@@ -299,9 +418,14 @@ func (h *ConnectionHandler) sendClientStartupMessages() error {
 			return err
 		}
 	}
+	if h.server != nil {
+		if err := h.server.registerBackend(h); err != nil {
+			return err
+		}
+	}
 	return h.send(&pgproto3.BackendKeyData{
-		ProcessID: processID,
-		SecretKey: 0, // TODO: this should represent an ID that can uniquely identify this connection, so that CancelRequest will work
+		ProcessID: h.mysqlConn.ConnectionID,
+		SecretKey: h.secretKey,
 	})
 }
 
@@ -313,9 +437,21 @@ func (h *ConnectionHandler) chooseInitialDatabase(startupMessage *pgproto3.Start
 	if !dbSpecified {
 		db = h.mysqlConn.User
 	}
-	if db == "postgres" || db == "mysql" {
-		if provider := h.duckHandler.GetCatalogProvider(); provider != nil {
-			db = provider.DefaultCatalogName()
+	if provider := h.duckHandler.GetCatalogProvider(); provider != nil {
+		db = resolveDatabaseName(provider, db)
+		// Each database is a catalog, i.e., a DuckDB file in the data directory.
+		if !provider.HasCatalog(db) {
+			if !dbSpecified {
+				return nil
+			}
+			err := fmt.Errorf("database \"%s\" does not exist", db)
+			_ = h.send(&pgproto3.ErrorResponse{
+				Severity: string(ErrorResponseSeverity_Fatal),
+				Code:     "3D000", // invalid_catalog_name
+				Message:  err.Error(),
+				Routine:  "InitPostgres",
+			})
+			return err
 		}
 	}
 
@@ -342,6 +478,79 @@ func (h *ConnectionHandler) chooseInitialDatabase(startupMessage *pgproto3.Start
 	return nil
 }
 
+// applyStartupSearchPath sets the search_path given in the startup message, either as a parameter or in the
+// command-line options, e.g., PGOPTIONS='-c search_path=myschema,public'.
+func (h *ConnectionHandler) applyStartupSearchPath(startupMessage *pgproto3.StartupMessage) error {
+	searchPath, ok := startupMessage.Parameters["search_path"]
+	if value, found := parseStartupOptions(startupMessage.Parameters["options"])["search_path"]; found {
+		searchPath, ok = value, true
+	}
+	if !ok {
+		return nil
+	}
+	if _, err := h.setPgSessionVarValue("search_path", searchPath, false); err != nil {
+		_ = h.send(&pgproto3.ErrorResponse{
+			Severity: string(ErrorResponseSeverity_Fatal),
+			Code:     "22023", // invalid_parameter_value
+			Message:  fmt.Sprintf(`invalid value for parameter "search_path": "%s"`, searchPath),
+			Detail:   err.Error(),
+			Routine:  "InitPostgres",
+		})
+		return err
+	}
+	return nil
+}
+
+// parseStartupOptions parses the configuration parameters in the options of a startup message, which are given as
+// "-c name=value" or "--name=value" separated by spaces. A backslash escapes the next character, such as a space.
+func parseStartupOptions(options string) map[string]string {
+	var args []string
+	var arg strings.Builder
+	inArg, escaped := false, false
+	for _, c := range options {
+		switch {
+		case escaped:
+			arg.WriteRune(c)
+			escaped = false
+		case c == '\\':
+			inArg, escaped = true, true
+		case c == ' ' || c == '\t' || c == '\n':
+			if inArg {
+				args = append(args, arg.String())
+				arg.Reset()
+				inArg = false
+			}
+		default:
+			arg.WriteRune(c)
+			inArg = true
+		}
+	}
+	if inArg {
+		args = append(args, arg.String())
+	}
+
+	params := make(map[string]string)
+	for i := 0; i < len(args); i++ {
+		var param string
+		switch {
+		case args[i] == "-c" && i+1 < len(args):
+			i++
+			param = args[i]
+		case strings.HasPrefix(args[i], "-c"):
+			param = args[i][2:]
+		case strings.HasPrefix(args[i], "--"):
+			param = args[i][2:]
+		default:
+			continue
+		}
+		if name, value, ok := strings.Cut(param, "="); ok {
+			// As in Postgres, dashes in the names stand for underscores.
+			params[strings.ToLower(strings.ReplaceAll(name, "-", "_"))] = value
+		}
+	}
+	return params
+}
+
 // receiveMessage reads a single message off the connection and processes it, returning an error if no message could be
 // received from the connection. Otherwise, (a message is received successfully), the message is processed and any
 // error is handled appropriately. The return value indicates whether the connection should be closed.
@@ -377,6 +586,9 @@ func (h *ConnectionHandler) receiveMessage() (bool, error) {
 	if err != nil {
 		return false, fmt.Errorf("error receiving message: %w", err)
 	}
+	h.sendMu.Lock()
+	h.idle = false
+	h.sendMu.Unlock()
 
 	if m, ok := msg.(json.Marshaler); ok && logrus.IsLevelEnabled(logrus.DebugLevel) {
 		msgInfo, err := m.MarshalJSON()
@@ -390,6 +602,9 @@ func (h *ConnectionHandler) receiveMessage() (bool, error) {
 
 	var stop bool
 	stop, endOfMessages, err = h.handleMessage(msg)
+	if h.terminated {
+		return true, err
+	}
 	if err != nil {
 		if !endOfMessages && h.waitForSync {
 			if syncErr := h.discardToSync(); syncErr != nil {
@@ -410,11 +625,26 @@ func (h *ConnectionHandler) receiveMessage() (bool, error) {
 // and a READY FOR QUERY message should be sent back to the client, so it can send the next query.
 func (h *ConnectionHandler) handleMessage(msg pgproto3.Message) (stop, endOfMessages bool, err error) {
 	logrus.Tracef("Handling message: %T", msg)
+	h.insertBatch.mu.Lock()
+	defer h.insertBatch.mu.Unlock()
+	// The batched INSERT statements are committed before any other message, see handleQuery for the query messages.
+	switch msg.(type) {
+	case *pgproto3.Query, *pgproto3.Terminate:
+	default:
+		if err := h.commitInsertBatch(); err != nil {
+			return false, false, err
+		}
+	}
+
 	switch message := msg.(type) {
 	case *pgproto3.Terminate:
 		return true, false, nil
 	case *pgproto3.Sync:
 		h.waitForSync = false
+		// Sync ends the implicit transaction of the messages outside a transaction block, which destroys the portals.
+		if !h.inTransaction {
+			h.deletePortals()
+		}
 		return false, true, nil
 	case *pgproto3.Query:
 		endOfMessages, err = h.handleQuery(message)
@@ -459,7 +689,13 @@ func (h *ConnectionHandler) handleQuery(message *pgproto3.Query) (endOfMessages
 		return true, nil
 	}
 
-	handled, err := h.handledPSQLCommands(message.String)
+	if !maybeInsertQuery(message.String) {
+		if err := h.commitInsertBatch(); err != nil {
+			return true, err
+		}
+	}
+
+	handled, err := h.handledClientQueries(message.String)
 	if handled || err != nil {
 		return true, err
 	}
@@ -471,6 +707,7 @@ func (h *ConnectionHandler) handleQuery(message *pgproto3.Query) (endOfMessages
 		return true, err
 	}
 
+	h.startActivity(message.String)
 	statements, err := h.convertQuery(message.String)
 	if err != nil {
 		return true, err
@@ -480,8 +717,16 @@ func (h *ConnectionHandler) handleQuery(message *pgproto3.Query) (endOfMessages
 	h.deletePreparedStatement("")
 	h.deletePortal("")
 
+	if h.canBatchInserts(statements) {
+		return true, h.runBatchedInserts(statements)
+	}
+	if err := h.commitInsertBatch(); err != nil {
+		return true, err
+	}
+
 	for _, statement := range statements {
 		statement.IsExtendedQuery = false
+		h.beginStatement(statement.String)
 		// Certain statement types get handled directly by the handler instead of being passed to the engine
 		handled, endOfMessages, err = h.handleStatementOutsideEngine(statement)
 		if handled {
@@ -508,12 +753,49 @@ func (h *ConnectionHandler) handleQuery(message *pgproto3.Query) (endOfMessages
 // if no more messages are expected for this query and server should send the client a READY FOR QUERY message,
 // and any error that occurred while handling the query.
 func (h *ConnectionHandler) handleStatementOutsideEngine(statement ConvertedStatement) (handled bool, endOfMessages bool, err error) {
+	if statement.CursorConfig != nil {
+		return true, true, h.executeCursorStatement(statement)
+	}
+	if statement.NotifyConfig != nil {
+		return true, true, h.executeNotifyStatement(statement)
+	}
+	if statement.TwoPhaseConfig != nil {
+		return true, true, h.executeTwoPhaseStatement(statement)
+	}
+	if statement.AdvisoryLockConfig != nil {
+		return true, true, h.executeAdvisoryLock(statement, nil)
+	}
+	if statement.RoleConfig != nil {
+		return true, true, h.executeRoleStatement(statement)
+	}
+	if statement.PublicationConfig != nil {
+		return true, true, h.executePublicationStatement(statement)
+	}
+	if statement.ReplicationCommand != nil {
+		return true, true, h.executeReplicationCommand(statement)
+	}
+	if statement.PrepareConfig != nil {
+		return true, true, h.executePrepareStatement(statement)
+	}
+	if statement.CopyFileConfig != nil {
+		return true, true, h.executeCopyFile(statement)
+	}
+	if statement.ForeignDataConfig != nil {
+		return true, true, h.executeForeignDataStatement(statement)
+	}
+
 	switch stmt := statement.AST.(type) {
-	case *tree.Deallocate:
-		// TODO: handle ALL keyword
-		return true, true, h.deallocatePreparedStatement(stmt.Name.String(), h.preparedStatements, statement, h.Conn())
-	case *tree.Discard:
-		return true, true, h.discardAll(statement)
+	case *tree.BeginTransaction:
+		// As in Postgres, a redundant BEGIN, COMMIT, or ROLLBACK is only warned about.
+		if h.inTransaction {
+			return true, true, h.skipTransactionStatement(statement, "25001" /* active_sql_transaction */, "there is already a transaction in progress")
+		}
+	case *tree.CommitTransaction, *tree.RollbackTransaction:
+		if !h.inTransaction {
+			return true, true, h.skipTransactionStatement(statement, "25P01" /* no_active_sql_transaction */, "there is no transaction in progress")
+		}
+	case *tree.Savepoint, *tree.ReleaseSavepoint, *tree.RollbackToSavepoint:
+		return true, true, h.executeSavepointStatement(statement)
 	case *tree.CopyFrom:
 		// When copying data from STDIN, the data is sent to the server as CopyData messages
 		// We send endOfMessages=false since the server will be in COPY DATA mode and won't
@@ -563,7 +845,15 @@ func (h *ConnectionHandler) handleStatementOutsideEngine(statement ConvertedStat
 func (h *ConnectionHandler) handleParse(message *pgproto3.Parse) error {
 	h.waitForSync = true
 
-	// TODO: "Named prepared statements must be explicitly closed before they can be redefined by another Parse message, but this is not required for the unnamed statement"
+	// > Named prepared statements must be explicitly closed before they can be redefined by another Parse message,
+	// > but this is not required for the unnamed statement.
+	if message.Name == "" {
+		h.deletePreparedStatement("")
+	} else if _, ok := h.preparedStatements[message.Name]; ok {
+		return fmt.Errorf("prepared statement \"%s\" already exists", message.Name)
+	}
+
+	h.startActivity(message.Query)
 	statements, err := h.convertQuery(message.Query)
 	if err != nil {
 		return err
@@ -576,6 +866,7 @@ func (h *ConnectionHandler) handleParse(message *pgproto3.Parse) error {
 		// special case: empty query
 		h.preparedStatements[message.Name] = PreparedStatementData{
 			Statement: statement,
+			Closed:    new(atomic.Bool),
 		}
 		return h.send(&pgproto3.ParseComplete{})
 	}
@@ -584,7 +875,16 @@ func (h *ConnectionHandler) handleParse(message *pgproto3.Parse) error {
 	if err != nil {
 		return err
 	}
-	if handledOutsideEngine {
+	switch statement.AST.(type) {
+	case *tree.Savepoint, *tree.ReleaseSavepoint, *tree.RollbackToSavepoint:
+		handledOutsideEngine = true
+	}
+	if statement.ReplicationCommand != nil {
+		return fmt.Errorf("extended query protocol not supported in a replication connection")
+	}
+	if handledOutsideEngine || statement.CursorConfig != nil || statement.NotifyConfig != nil || statement.TwoPhaseConfig != nil ||
+		statement.RoleConfig != nil || statement.PublicationConfig != nil || statement.PrepareConfig != nil ||
+		statement.CopyFileConfig != nil || statement.ForeignDataConfig != nil {
 		h.preparedStatements[message.Name] = PreparedStatementData{
 			Statement:    statement,
 			ReturnFields: nil,
@@ -594,6 +894,25 @@ func (h *ConnectionHandler) handleParse(message *pgproto3.Parse) error {
 		}
 		return h.send(&pgproto3.ParseComplete{})
 	}
+	if config := statement.AdvisoryLockConfig; config != nil {
+		// The keys of an advisory lock may be parameters, which are bound without preparing the statement in DuckDB.
+		bindVarTypes := message.ParameterOIDs
+		params := config.ParamTypes()
+		if len(bindVarTypes) < len(params) {
+			bindVarTypes = append(bindVarTypes, params[len(bindVarTypes):]...)
+		}
+		for i := range params {
+			if bindVarTypes[i] == 0 {
+				bindVarTypes[i] = params[i]
+			}
+		}
+		h.preparedStatements[message.Name] = PreparedStatementData{
+			Statement:    statement,
+			BindVarTypes: bindVarTypes,
+			Closed:       new(atomic.Bool),
+		}
+		return h.send(&pgproto3.ParseComplete{})
+	}
 
 	stmt, params, fields, err := h.duckHandler.ComPrepareParsed(context.Background(), h.mysqlConn, statement.String, statement.AST)
 	if err != nil {
@@ -656,6 +975,8 @@ func (h *ConnectionHandler) handleDescribe(message *pgproto3.Describe) error {
 
 			bindvarTypes = preparedStatementData.BindVarTypes
 			tag = preparedStatementData.Statement.Tag
+		} else if preparedStatementData.Statement.AdvisoryLockConfig != nil {
+			bindvarTypes = preparedStatementData.BindVarTypes
 		}
 
 		if bindvarTypes == nil {
@@ -684,21 +1005,35 @@ func (h *ConnectionHandler) handleDescribe(message *pgproto3.Describe) error {
 func (h *ConnectionHandler) handleBind(message *pgproto3.Bind) error {
 	h.waitForSync = true
 
-	// TODO: a named portal object lasts till the end of the current transaction, unless explicitly destroyed
-	//  we need to destroy the named portal as a side effect of the transaction ending
 	logrus.Tracef("binding portal %q to prepared statement %s", message.DestinationPortal, message.PreparedStatement)
 	preparedData, ok := h.preparedStatements[message.PreparedStatement]
 	if !ok {
 		return fmt.Errorf("prepared statement %s does not exist", message.PreparedStatement)
 	}
 
+	// Binding the unnamed portal destroys the previous one, while a named portal must be closed first.
+	if message.DestinationPortal == "" {
+		h.deletePortal("")
+	} else if _, ok := h.portals[message.DestinationPortal]; ok {
+		return fmt.Errorf("portal \"%s\" already exists", message.DestinationPortal)
+	}
+
 	if preparedData.Stmt == nil {
+		var bindVars []any
+		if len(preparedData.BindVarTypes) > 0 {
+			var err error
+			bindVars, err = h.convertBindParameters(preparedData.BindVarTypes, message.ParameterFormatCodes, message.Parameters)
+			if err != nil {
+				return err
+			}
+		}
 		h.portals[message.DestinationPortal] = PortalData{
 			Statement:    preparedData.Statement,
 			IsEmptyQuery: strings.TrimSpace(preparedData.Statement.String) == "",
 			Fields:       nil,
 			Stmt:         nil,
-			Vars:         nil,
+			Vars:         bindVars,
+			Closed:       preparedData.Closed,
 		}
 		return h.send(&pgproto3.BindComplete{})
 	}
@@ -708,6 +1043,7 @@ func (h *ConnectionHandler) handleBind(message *pgproto3.Bind) error {
 		h.portals[message.DestinationPortal] = PortalData{
 			Statement:    preparedData.Statement,
 			IsEmptyQuery: true,
+			Closed:       preparedData.Closed,
 		}
 		return h.send(&pgproto3.BindComplete{})
 	}
@@ -722,6 +1058,21 @@ func (h *ConnectionHandler) handleBind(message *pgproto3.Bind) error {
 		return err
 	}
 
+	// The formats of the result columns are known once they are bound, so Describe reports them for the portal.
+	if codes := message.ResultFormatCodes; len(codes) > 0 {
+		if len(codes) != 1 && len(codes) != len(fields) {
+			return fmt.Errorf("bind message has %d result formats but query has %d columns", len(codes), len(fields))
+		}
+		fields = slices.Clone(fields)
+		for i := range fields {
+			if len(codes) == 1 {
+				fields[i].Format = codes[0]
+			} else {
+				fields[i].Format = codes[i]
+			}
+		}
+	}
+
 	h.portals[message.DestinationPortal] = PortalData{
 		Statement:         preparedData.Statement,
 		Fields:            fields,
@@ -737,7 +1088,6 @@ func (h *ConnectionHandler) handleBind(message *pgproto3.Bind) error {
 func (h *ConnectionHandler) handleExecute(message *pgproto3.Execute) error {
 	h.waitForSync = true
 
-	// TODO: implement the RowMax
 	portalData, ok := h.portals[message.Portal]
 	if !ok {
 		return fmt.Errorf("portal %s does not exist", message.Portal)
@@ -745,6 +1095,8 @@ func (h *ConnectionHandler) handleExecute(message *pgproto3.Execute) error {
 
 	logrus.Tracef("executing portal %s with contents %v", message.Portal, portalData)
 	query := portalData.Statement
+	h.startActivity(query.String)
+	h.beginStatement(query.String)
 
 	if portalData.IsEmptyQuery {
 		err := h.send(&pgproto3.NoData{})
@@ -754,6 +1106,16 @@ func (h *ConnectionHandler) handleExecute(message *pgproto3.Execute) error {
 		return h.send(&pgproto3.EmptyQueryResponse{})
 	}
 
+	// A portal executed with a row limit is suspended when the limit is reached, and resumed by the next Execute.
+	// The row limit is ignored for statements that return no rows.
+	if portalData.Cursor != nil || (message.MaxRows > 0 && portalData.Stmt != nil && returnsRow(query.Tag)) {
+		return h.executeCursor(message.Portal, portalData, message.MaxRows)
+	}
+
+	if query.AdvisoryLockConfig != nil {
+		return h.executeAdvisoryLock(query, portalData.Vars)
+	}
+
 	// Certain statement types get handled directly by the handler instead of being passed to the engine
 	if strings.ToUpper(query.Tag) != "SELECT" || portalData.Stmt == nil {
 		handled, _, err := h.handleStatementOutsideEngine(query)
@@ -765,15 +1127,49 @@ func (h *ConnectionHandler) handleExecute(message *pgproto3.Execute) error {
 	// |rowsAffected| gets altered by the callback below
 	rowsAffected := int32(0)
 
+	if _, ok := query.AST.(*tree.CommitTransaction); ok {
+		if err := h.holdCursors(); err != nil {
+			return err
+		}
+	}
+
 	callback := h.spoolRowsCallback(query, &rowsAffected, true)
-	err := h.duckHandler.ComExecuteBound(context.Background(), h.mysqlConn, portalData, callback)
+	stopTimeout := h.startStatementTimeout()
+	err := stopTimeout(h.duckHandler.ComExecuteBound(context.Background(), h.mysqlConn, portalData, callback))
 	if err != nil {
 		return err
 	}
+	h.trackTransaction(query, portalData.Vars)
 
 	return h.send(makeCommandComplete(query.Tag, rowsAffected))
 }
 
+// executeCursor fetches at most |maxRows| rows, or all the remaining ones if it is 0, from the result of the portal,
+// which is opened by the first Execute. It sends PortalSuspended if the limit is reached, or CommandComplete once
+// the result is exhausted.
+func (h *ConnectionHandler) executeCursor(name string, portalData PortalData, maxRows uint32) error {
+	if portalData.Cursor == nil {
+		cursor, err := h.duckHandler.OpenCursor(context.Background(), h.mysqlConn, portalData)
+		if err != nil {
+			return err
+		}
+		portalData.Cursor = cursor
+		h.portals[name] = portalData
+	}
+
+	rows, err := portalData.Cursor.fetch(h.duckHandler, maxRows, func(row [][]byte) error {
+		return h.send(&pgproto3.DataRow{Values: row})
+	})
+	if err != nil {
+		return err
+	}
+	if !portalData.Cursor.done {
+		return h.send(&pgproto3.PortalSuspended{})
+	}
+	// Postgres reports the rows fetched by the last Execute only.
+	return h.send(makeCommandComplete(portalData.Statement.Tag, rows))
+}
+
 func makeCommandComplete(tag string, rows int32) *pgproto3.CommandComplete {
 	switch tag {
 	case "INSERT", "DELETE", "UPDATE", "MERGE", "SELECT", "CREATE TABLE AS", "MOVE", "FETCH", "COPY":
@@ -849,7 +1245,10 @@ func (h *ConnectionHandler) handleCopyDataHelper(message *pgproto3.CopyData) (st
 				rawOptions,
 			)
 		case tree.CopyFormatBinary:
-			err = fmt.Errorf("BINARY format is not supported for COPY FROM")
+			dataLoader, err = NewBinaryDataLoader(
+				sqlCtx, h.duckHandler,
+				copyFrom.Table.Schema(), table, copyFrom.Columns,
+			)
 		default:
 			err = fmt.Errorf("unknown format specified for COPY FROM: %v", copyFrom.Options.CopyFormat)
 		}
@@ -905,6 +1304,7 @@ func (h *ConnectionHandler) handleCopyDone(_ *pgproto3.CopyDone) (stop bool, end
 	}
 
 	loadDataResults, err := dataLoader.Finish(sqlCtx)
+	h.forwardWarnings(sqlCtx)
 	if err != nil {
 		return false, false, err
 	}
@@ -939,57 +1339,106 @@ func (h *ConnectionHandler) handleCopyFail(_ *pgproto3.CopyFail) (stop bool, end
 	return false, true, nil
 }
 
-func (h *ConnectionHandler) deallocatePreparedStatement(name string, preparedStatements map[string]PreparedStatementData, query ConvertedStatement, conn net.Conn) error {
-	_, ok := preparedStatements[name]
-	if !ok {
-		return fmt.Errorf("prepared statement %s does not exist", name)
-	}
-	h.deletePreparedStatement(name)
-
-	return h.send(&pgproto3.CommandComplete{
-		CommandTag: []byte(query.Tag),
-	})
-}
-
+// deletePreparedStatement closes the prepared statement with the given name, if there is one. Since its portals
+// execute the underlying DuckDB statement, they are closed as well, while Postgres would keep them open.
 func (h *ConnectionHandler) deletePreparedStatement(name string) {
 	ps, ok := h.preparedStatements[name]
 	if ok {
 		delete(h.preparedStatements, name)
-		if ps.Closed.CompareAndSwap(false, true) {
+		for portal, p := range h.portals {
+			if p.Closed == ps.Closed {
+				h.deletePortal(portal)
+			}
+		}
+		if ps.Stmt != nil && ps.Closed.CompareAndSwap(false, true) {
 			ps.Stmt.Close()
 		}
 	}
 }
 
+// deletePortal closes the portal with the given name, if there is one, along with its open result.
+// The prepared statement of the portal is left open.
 func (h *ConnectionHandler) deletePortal(name string) {
 	p, ok := h.portals[name]
 	if ok {
 		delete(h.portals, name)
-		if p.Closed.CompareAndSwap(false, true) {
-			p.Stmt.Close()
+		if p.Cursor != nil {
+			if err := p.Cursor.close(); err != nil {
+				h.logger.WithError(err).Warnf("Failed to close the result of portal %q", name)
+			}
 		}
 	}
 }
 
+// deletePortals closes all the portals, as at the end of a transaction.
+func (h *ConnectionHandler) deletePortals() {
+	for name := range h.portals {
+		h.deletePortal(name)
+	}
+}
+
 // convertBindParameters handles the conversion from bind parameters to variable values.
 func (h *ConnectionHandler) convertBindParameters(types []uint32, formatCodes []int16, values [][]byte) ([]any, error) {
 	if len(types) != len(values) {
 		return nil, fmt.Errorf("number of values does not match number of parameters")
 	}
-	bindings := make([]pgtype.Text, len(values))
+	// There may be no format codes, which means text, a single one for all parameters, or one for each parameter.
+	if len(formatCodes) > 1 && len(formatCodes) != len(values) {
+		return nil, fmt.Errorf("bind message has %d parameter formats but %d parameters", len(formatCodes), len(values))
+	}
+	vars := make([]any, len(values))
 	for i := range values {
 		typ := types[i]
+		format := int16(pgproto3.TextFormat)
+		if len(formatCodes) == 1 {
+			format = formatCodes[0]
+		} else if len(formatCodes) > 1 {
+			format = formatCodes[i]
+		}
+		// An array is bound as the text of a DuckDB list, which DuckDB casts to the LIST type of the parameter.
+		if values[i] != nil && h.isArrayType(typ) {
+			list, err := h.convertArrayBindParameter(typ, format, values[i])
+			if err != nil {
+				return nil, err
+			}
+			vars[i] = list
+			continue
+		}
+		// A user-defined type is an enum, whose values are the labels in both the text and the binary format.
+		if _, ok := h.pgTypeMap.TypeForOID(typ); !ok && typ >= pgtypes.FirstNormalObjectID && values[i] != nil {
+			vars[i] = string(values[i])
+			continue
+		}
 		// We'll rely on a library to decode each format, which will deal with text and binary representations for us
-		if err := h.pgTypeMap.Scan(typ, formatCodes[i], values[i], &bindings[i]); err != nil {
-			return nil, err
+		var binding pgtype.Text
+		if err := h.pgTypeMap.Scan(typ, format, values[i], &binding); err != nil {
+			if format != pgproto3.BinaryFormat {
+				return nil, err
+			}
+			// Some types, e.g., timestamp and interval, are not scanned from the binary format into text directly,
+			// so they are decoded into their values first, and then encoded as text.
+			text, err := h.binaryToText(typ, values[i])
+			if err != nil {
+				return nil, err
+			}
+			binding = pgtype.Text{String: text, Valid: true}
 		}
+		vars[i] = binding.String
 	}
+	return vars, nil
+}
 
-	vars := make([]any, len(bindings))
-	for i, b := range bindings {
-		vars[i] = b.String
+// binaryToText converts a value in the binary format of the type of the OID into the text format.
+func (h *ConnectionHandler) binaryToText(oid uint32, value []byte) (string, error) {
+	var v any
+	if err := h.pgTypeMap.Scan(oid, pgproto3.BinaryFormat, value, &v); err != nil {
+		return "", err
 	}
-	return vars, nil
+	encoded, err := h.pgTypeMap.Encode(oid, pgproto3.TextFormat, v, nil)
+	if err != nil {
+		return "", err
+	}
+	return string(encoded), nil
 }
 
 // run runs the given statement and sends a CommandComplete message to the client
@@ -1029,26 +1478,75 @@ func (h *ConnectionHandler) run(statement ConvertedStatement) error {
 		})
 	}
 
+	if _, ok := statement.AST.(*tree.CommitTransaction); ok {
+		if err := h.holdCursors(); err != nil {
+			return err
+		}
+	}
+
 	callback := h.spoolRowsCallback(statement, &rowsAffected, false)
-	if err := h.duckHandler.ComQuery(
+	stopTimeout := h.startStatementTimeout()
+	if err := stopTimeout(h.duckHandler.ComQuery(
 		context.Background(),
 		h.mysqlConn,
 		statement.String,
 		statement.AST,
 		callback,
-	); err != nil {
+	)); err != nil {
+		// Unlike Postgres, which rejects it with 42704, setting an unknown parameter outside a transaction is only
+		// warned about; see ignoreUnrecognizedSetting.
+		if setVar, ok := statement.AST.(*tree.SetVar); ok && isUnrecognizedSetting(err) && !h.inTransaction {
+			return h.ignoreUnrecognizedSetting(strings.ToLower(setVar.Name))
+		}
 		return fmt.Errorf("fallback statement execution failed: %w", err)
 	}
 
+	h.trackTransaction(statement, nil)
+
 	return h.send(makeCommandComplete(statement.Tag, rowsAffected))
 }
 
+// trackTransaction keeps track of the transaction block of the connection. At the end of the transaction, the portals
+// and the cursors declared without WITH HOLD are closed, the notifications queued in the transaction are sent if it is committed,
+// the ON COMMIT actions are taken on the temporary tables, and the transaction-level advisory locks are released.
+// The statements run in the transaction block are logged along with their bound parameters |vars| for ROLLBACK TO
+// SAVEPOINT.
+func (h *ConnectionHandler) trackTransaction(statement ConvertedStatement, vars []any) {
+	if statement.TempTableConfig != nil {
+		h.trackTempTable(statement.TempTableConfig)
+	}
+	h.logTransactionStatement(statement, vars)
+	switch statement.AST.(type) {
+	case *tree.BeginTransaction:
+		h.inTransaction = true
+		h.setActivityTransaction(true)
+	case *tree.CommitTransaction, *tree.RollbackTransaction:
+		_, commit := statement.AST.(*tree.CommitTransaction)
+		h.inTransaction = false
+		h.setActivityTransaction(false)
+		h.deletePortals()
+		h.endCursorTransaction(commit)
+		h.sendQueuedNotifications(commit)
+		h.endTempTableTransaction(commit)
+		h.releaseAdvisoryLocks(false)
+	default:
+		// A statement outside a transaction block is committed on its own.
+		if !h.inTransaction {
+			h.endTempTableTransaction(true)
+			h.releaseAdvisoryLocks(false)
+		}
+	}
+}
+
 // spoolRowsCallback returns a callback function that will send RowDescription message,
-// then a DataRow message for each row in the result set.
+// then a DataRow message for each row in the result set. The rows are streamed as the callback is called,
+// and flushed to the client whenever resultFlushSize bytes are buffered; the rest are flushed by the
+// CommandComplete message that ends the result.
 func (h *ConnectionHandler) spoolRowsCallback(statement ConvertedStatement, rows *int32, isExecute bool) func(res *Result) error {
 	// IsIUD returns whether the query is either an INSERT, UPDATE, or DELETE query.
 	tag := statement.Tag
 	isIUD := tag == "INSERT" || tag == "UPDATE" || tag == "DELETE"
+	buffered := 0
 	return func(res *Result) error {
 		logrus.Tracef("spooling %d rows for tag %s (execute = %v)", res.RowsAffected, tag, isExecute)
 		if returnsRow(tag) {
@@ -1056,19 +1554,24 @@ func (h *ConnectionHandler) spoolRowsCallback(statement ConvertedStatement, rows
 			// We only send RowDescription once per statement execution.
 			if !isExecute && !statement.HasSentRowDesc {
 				logrus.Tracef("sending RowDescription %+v for tag %s", res.Fields, tag)
-				if err := h.send(&pgproto3.RowDescription{
+				h.sendBuffered(&pgproto3.RowDescription{
 					Fields: res.Fields,
-				}); err != nil {
-					return err
-				}
+				})
 				statement.HasSentRowDesc = true
 			}
 
 			logrus.Tracef("sending Rows %+v for tag %s", res.Rows, tag)
 			for _, row := range res.Rows {
-				if err := h.send(&pgproto3.DataRow{
+				h.sendBuffered(&pgproto3.DataRow{
 					Values: row.val,
-				}); err != nil {
+				})
+				for _, v := range row.val {
+					buffered += len(v) + 4
+				}
+			}
+			if buffered >= resultFlushSize {
+				buffered = 0
+				if err := h.flush(); err != nil {
 					return err
 				}
 			}
@@ -1105,84 +1608,6 @@ func (h *ConnectionHandler) sendDescribeResponse(fields []pgproto3.FieldDescript
 	}
 }
 
-// handledPSQLCommands handles the special PSQL commands, such as \l and \dt.
-func (h *ConnectionHandler) handledPSQLCommands(statement string) (bool, error) {
-	statement = strings.ToLower(statement)
-	// Command: \l
-	if statement == "select d.datname as \"name\",\n       pg_catalog.pg_get_userbyid(d.datdba) as \"owner\",\n       pg_catalog.pg_encoding_to_char(d.encoding) as \"encoding\",\n       d.datcollate as \"collate\",\n       d.datctype as \"ctype\",\n       d.daticulocale as \"icu locale\",\n       case d.datlocprovider when 'c' then 'libc' when 'i' then 'icu' end as \"locale provider\",\n       pg_catalog.array_to_string(d.datacl, e'\\n') as \"access privileges\"\nfrom pg_catalog.pg_database d\norder by 1;" {
-		query, err := h.convertQuery(`select d.datname as "Name", 'postgres' as "Owner", 'UTF8' as "Encoding", 'en_US.UTF-8' as "Collate", 'en_US.UTF-8' as "Ctype", 'en-US' as "ICU Locale", case d.datlocprovider when 'c' then 'libc' when 'i' then 'icu' end as "locale provider", '' as "access privileges" from pg_catalog.pg_database d order by 1;`)
-		if err != nil {
-			return false, err
-		}
-		return true, h.run(query[0])
-	}
-	// Command: \l on psql 16
-	if statement == "select\n  d.datname as \"name\",\n  pg_catalog.pg_get_userbyid(d.datdba) as \"owner\",\n  pg_catalog.pg_encoding_to_char(d.encoding) as \"encoding\",\n  case d.datlocprovider when 'c' then 'libc' when 'i' then 'icu' end as \"locale provider\",\n  d.datcollate as \"collate\",\n  d.datctype as \"ctype\",\n  d.daticulocale as \"icu locale\",\n  null as \"icu rules\",\n  pg_catalog.array_to_string(d.datacl, e'\\n') as \"access privileges\"\nfrom pg_catalog.pg_database d\norder by 1;" {
-		query, err := h.convertQuery(`select d.datname as "Name", 'postgres' as "Owner", 'UTF8' as "Encoding", 'en_US.UTF-8' as "Collate", 'en_US.UTF-8' as "Ctype", 'en-US' as "ICU Locale", case d.datlocprovider when 'c' then 'libc' when 'i' then 'icu' end as "locale provider", '' as "access privileges" from pg_catalog.pg_database d order by 1;`)
-		if err != nil {
-			return false, err
-		}
-		return true, h.run(query[0])
-	}
-	// Command: \dt
-	if statement == "select n.nspname as \"schema\",\n  c.relname as \"name\",\n  case c.relkind when 'r' then 'table' when 'v' then 'view' when 'm' then 'materialized view' when 'i' then 'index' when 's' then 'sequence' when 't' then 'toast table' when 'f' then 'foreign table' when 'p' then 'partitioned table' when 'i' then 'partitioned index' end as \"type\",\n  pg_catalog.pg_get_userbyid(c.relowner) as \"owner\"\nfrom pg_catalog.pg_class c\n     left join pg_catalog.pg_namespace n on n.oid = c.relnamespace\n     left join pg_catalog.pg_am am on am.oid = c.relam\nwhere c.relkind in ('r','p','')\n      and n.nspname <> 'pg_catalog'\n      and n.nspname !~ '^pg_toast'\n      and n.nspname <> 'information_schema'\n  and pg_catalog.pg_table_is_visible(c.oid)\norder by 1,2;" {
-		return true, h.run(ConvertedStatement{
-			String: `SELECT table_schema AS "Schema", TABLE_NAME AS "Name", 'table' AS "Type", 'postgres' AS "Owner" FROM INFORMATION_SCHEMA.TABLES WHERE TABLE_SCHEMA <> 'pg_catalog' AND TABLE_SCHEMA <> 'information_schema' AND TABLE_TYPE = 'BASE TABLE' ORDER BY 2;`,
-			Tag:    "SELECT",
-		})
-	}
-	// Command: \d
-	if statement == "select n.nspname as \"schema\",\n  c.relname as \"name\",\n  case c.relkind when 'r' then 'table' when 'v' then 'view' when 'm' then 'materialized view' when 'i' then 'index' when 's' then 'sequence' when 't' then 'toast table' when 'f' then 'foreign table' when 'p' then 'partitioned table' when 'i' then 'partitioned index' end as \"type\",\n  pg_catalog.pg_get_userbyid(c.relowner) as \"owner\"\nfrom pg_catalog.pg_class c\n     left join pg_catalog.pg_namespace n on n.oid = c.relnamespace\n     left join pg_catalog.pg_am am on am.oid = c.relam\nwhere c.relkind in ('r','p','v','m','s','f','')\n      and n.nspname <> 'pg_catalog'\n      and n.nspname !~ '^pg_toast'\n      and n.nspname <> 'information_schema'\n  and pg_catalog.pg_table_is_visible(c.oid)\norder by 1,2;" {
-		return true, h.run(ConvertedStatement{
-			String: `SELECT table_schema AS "Schema", TABLE_NAME AS "Name", IF(TABLE_TYPE = 'VIEW', 'view', 'table') AS "Type", 'postgres' AS "Owner" FROM INFORMATION_SCHEMA.TABLES WHERE TABLE_SCHEMA <> 'pg_catalog' AND TABLE_SCHEMA <> 'information_schema' AND TABLE_TYPE = 'BASE TABLE' OR TABLE_TYPE = 'VIEW' ORDER BY 2;`,
-			Tag:    "SELECT",
-		})
-	}
-	// Alternate \d for psql 14
-	if statement == "select n.nspname as \"schema\",\n  c.relname as \"name\",\n  case c.relkind when 'r' then 'table' when 'v' then 'view' when 'm' then 'materialized view' when 'i' then 'index' when 's' then 'sequence' when 's' then 'special' when 't' then 'toast table' when 'f' then 'foreign table' when 'p' then 'partitioned table' when 'i' then 'partitioned index' end as \"type\",\n  pg_catalog.pg_get_userbyid(c.relowner) as \"owner\"\nfrom pg_catalog.pg_class c\n     left join pg_catalog.pg_namespace n on n.oid = c.relnamespace\n     left join pg_catalog.pg_am am on am.oid = c.relam\nwhere c.relkind in ('r','p','v','m','s','f','')\n      and n.nspname <> 'pg_catalog'\n      and n.nspname !~ '^pg_toast'\n      and n.nspname <> 'information_schema'\n  and pg_catalog.pg_table_is_visible(c.oid)\norder by 1,2;" {
-		return true, h.run(ConvertedStatement{
-			String: `SELECT table_schema AS "Schema", TABLE_NAME AS "Name", IF(TABLE_TYPE = 'VIEW', 'view', 'table') AS "Type", 'postgres' AS "Owner" FROM INFORMATION_SCHEMA.TABLES WHERE TABLE_SCHEMA <> 'pg_catalog' AND TABLE_SCHEMA <> 'information_schema' AND TABLE_TYPE = 'BASE TABLE' OR TABLE_TYPE = 'VIEW' ORDER BY 2;`,
-			Tag:    "SELECT",
-		})
-	}
-	// Command: \d table_name
-	if strings.HasPrefix(statement, "select c.oid,\n  n.nspname,\n  c.relname\nfrom pg_catalog.pg_class c\n     left join pg_catalog.pg_namespace n on n.oid = c.relnamespace\nwhere c.relname operator(pg_catalog.~) '^(") && strings.HasSuffix(statement, ")$' collate pg_catalog.default\n  and pg_catalog.pg_table_is_visible(c.oid)\norder by 2, 3;") {
-		// There are >at least< 15 separate statements sent for this command, which is far too much to validate and
-		// implement, so we'll just return an error for now
-		return true, fmt.Errorf("PSQL command not yet supported")
-	}
-	// Command: \dn
-	if statement == "select n.nspname as \"name\",\n  pg_catalog.pg_get_userbyid(n.nspowner) as \"owner\"\nfrom pg_catalog.pg_namespace n\nwhere n.nspname !~ '^pg_' and n.nspname <> 'information_schema'\norder by 1;" {
-		return true, h.run(ConvertedStatement{
-			String: `SELECT 'public' AS "Name", 'pg_database_owner' AS "Owner";`,
-			Tag:    "SELECT",
-		})
-	}
-	// Command: \df
-	if statement == "select n.nspname as \"schema\",\n  p.proname as \"name\",\n  pg_catalog.pg_get_function_result(p.oid) as \"result data type\",\n  pg_catalog.pg_get_function_arguments(p.oid) as \"argument data types\",\n case p.prokind\n  when 'a' then 'agg'\n  when 'w' then 'window'\n  when 'p' then 'proc'\n  else 'func'\n end as \"type\"\nfrom pg_catalog.pg_proc p\n     left join pg_catalog.pg_namespace n on n.oid = p.pronamespace\nwhere pg_catalog.pg_function_is_visible(p.oid)\n      and n.nspname <> 'pg_catalog'\n      and n.nspname <> 'information_schema'\norder by 1, 2, 4;" {
-		return true, h.run(ConvertedStatement{
-			String: `SELECT '' AS "Schema", '' AS "Name", '' AS "Result data type", '' AS "Argument data types", '' AS "Type" LIMIT 0;`,
-			Tag:    "SELECT",
-		})
-	}
-	// Command: \dv
-	if statement == "select n.nspname as \"schema\",\n  c.relname as \"name\",\n  case c.relkind when 'r' then 'table' when 'v' then 'view' when 'm' then 'materialized view' when 'i' then 'index' when 's' then 'sequence' when 't' then 'toast table' when 'f' then 'foreign table' when 'p' then 'partitioned table' when 'i' then 'partitioned index' end as \"type\",\n  pg_catalog.pg_get_userbyid(c.relowner) as \"owner\"\nfrom pg_catalog.pg_class c\n     left join pg_catalog.pg_namespace n on n.oid = c.relnamespace\nwhere c.relkind in ('v','')\n      and n.nspname <> 'pg_catalog'\n      and n.nspname !~ '^pg_toast'\n      and n.nspname <> 'information_schema'\n  and pg_catalog.pg_table_is_visible(c.oid)\norder by 1,2;" {
-		return true, h.run(ConvertedStatement{
-			String: `SELECT table_schema AS "Schema", TABLE_NAME AS "Name", 'view' AS "Type", 'postgres' AS "Owner" FROM INFORMATION_SCHEMA.TABLES WHERE TABLE_SCHEMA <> 'pg_catalog' AND TABLE_SCHEMA <> 'information_schema' AND TABLE_TYPE = 'VIEW' ORDER BY 2;`,
-			Tag:    "SELECT",
-		})
-	}
-	// Command: \du
-	if statement == "select r.rolname, r.rolsuper, r.rolinherit,\n  r.rolcreaterole, r.rolcreatedb, r.rolcanlogin,\n  r.rolconnlimit, r.rolvaliduntil,\n  array(select b.rolname\n        from pg_catalog.pg_auth_members m\n        join pg_catalog.pg_roles b on (m.roleid = b.oid)\n        where m.member = r.oid) as memberof\n, r.rolreplication\n, r.rolbypassrls\nfrom pg_catalog.pg_roles r\nwhere r.rolname !~ '^pg_'\norder by 1;" {
-		// We don't support users yet, so we'll just return nothing for now
-		return true, h.run(ConvertedStatement{
-			String: `SELECT '' FROM dual LIMIT 0;`,
-			Tag:    "SELECT",
-		})
-	}
-	return false, nil
-}
-
 // handledWorkbenchCommands handles commands used by some workbenches, such as dolt-workbench.
 func (h *ConnectionHandler) handledWorkbenchCommands(statement string) (bool, error) {
 	lower := strings.ToLower(statement)
@@ -1209,22 +1634,56 @@ func (h *ConnectionHandler) endOfMessages(err error) {
 	if err != nil {
 		h.sendError(err)
 	}
-	if sendErr := h.send(&pgproto3.ReadyForQuery{
-		TxStatus: byte(ReadyForQueryTransactionIndicator_Idle),
-	}); sendErr != nil {
+	h.stopActivity()
+	h.statement.query = ""
+
+	h.sendMu.Lock()
+	defer h.sendMu.Unlock()
+	// The notifications received in a transaction are delivered at its end.
+	if !h.inTransaction {
+		for _, notification := range h.incomingNotifications {
+			h.backend.Send(notification)
+		}
+		h.incomingNotifications = nil
+	}
+	// Drivers such as JDBC rely on the transaction status to issue COMMIT and to keep fetching the suspended portals.
+	txStatus := ReadyForQueryTransactionIndicator_Idle
+	if h.inTransaction {
+		txStatus = ReadyForQueryTransactionIndicator_TransactionBlock
+	}
+	h.backend.Send(&pgproto3.ReadyForQuery{
+		TxStatus: byte(txStatus),
+	})
+	if sendErr := h.backend.Flush(); sendErr != nil {
 		// We panic here for the same reason as above.
 		panic(sendErr)
 	}
+	h.idle = !h.inTransaction
 }
 
 // sendError sends the given error to the client. This should generally never be called directly.
 func (h *ConnectionHandler) sendError(err error) {
 	fmt.Println(err.Error())
-	if sendErr := h.send(&pgproto3.ErrorResponse{
+	response := &pgproto3.ErrorResponse{
 		Severity: string(ErrorResponseSeverity_Error),
 		Code:     "XX000", // internal_error for now
 		Message:  err.Error(),
-	}); sendErr != nil {
+	}
+	var pgErr *pgconn.PgError
+	if errors.As(err, &pgErr) {
+		response.Code = pgErr.Code
+		response.Message = pgErr.Message
+	} else if errors.Is(err, errStatementTimeout) {
+		response.Code = "57014" // query_canceled
+		response.Message = errStatementTimeout.Error()
+	} else if errors.Is(err, errLockTimeout) {
+		response.Code = "55P03" // lock_not_available
+		response.Message = errLockTimeout.Error()
+	} else if isQueryCanceled(err) {
+		response.Code = "57014" // query_canceled
+		response.Message = "canceling statement due to user request"
+	}
+	if sendErr := h.send(response); sendErr != nil {
 		// If we're unable to send anything to the connection, then there's something wrong with the connection and
 		// we should terminate it. This will be caught in HandleConnection's defer block.
 		panic(sendErr)
@@ -1237,6 +1696,22 @@ func (h *ConnectionHandler) convertQuery(query string, modifiers ...QueryModifie
 		query = modifier(query)
 	}
 
+	// The replication commands are only accepted by the replication connections.
+	if h.walsender {
+		replicationCommand, err := parseReplicationCommand(query)
+		if replicationCommand != nil || err != nil {
+			if err != nil {
+				return nil, err
+			}
+			return []ConvertedStatement{{
+				String:             query,
+				Tag:                string(replicationCommand.Action),
+				PgParsable:         true,
+				ReplicationCommand: replicationCommand,
+			}}, nil
+		}
+	}
+
 	// Check if the query is a subscription query, and if so, parse it as a subscription query.
 	subscriptionConfig, err := parseSubscriptionSQL(query)
 	if subscriptionConfig != nil && err == nil {
@@ -1265,8 +1740,132 @@ func (h *ConnectionHandler) convertQuery(query string, modifiers ...QueryModifie
 		}}, nil
 	}
 
+	// LISTEN and NOTIFY are not supported by the Postgres parser.
+	notifyConfig, err := parseNotifySQL(query)
+	if notifyConfig != nil || err != nil {
+		if err != nil {
+			return nil, err
+		}
+		return []ConvertedStatement{{
+			String:       query,
+			Tag:          string(notifyConfig.Action),
+			PgParsable:   true,
+			NotifyConfig: notifyConfig,
+		}}, nil
+	}
+
+	// The advisory locks are kept by MyDuck Server, and their functions are only supported in a SELECT by themselves.
+	advisoryLockConfig, err := parseAdvisoryLockSQL(query)
+	if advisoryLockConfig != nil || err != nil {
+		if err != nil {
+			return nil, err
+		}
+		return []ConvertedStatement{{
+			String:             query,
+			Tag:                "SELECT",
+			PgParsable:         true,
+			AdvisoryLockConfig: advisoryLockConfig,
+		}}, nil
+	}
+
+	// Two-phase commit is handled by MyDuck Server, since DuckDB does not support it.
+	twoPhaseConfig, err := parseTwoPhaseSQL(query)
+	if twoPhaseConfig != nil || err != nil {
+		if err != nil {
+			return nil, err
+		}
+		return []ConvertedStatement{{
+			String:         query,
+			Tag:            string(twoPhaseConfig.Action),
+			PgParsable:     true,
+			TwoPhaseConfig: twoPhaseConfig,
+		}}, nil
+	}
+
+	// The roles are kept in the catalog by MyDuck Server, since DuckDB has none.
+	roleConfig, err := parseRoleSQL(query)
+	if roleConfig != nil || err != nil {
+		if err != nil {
+			return nil, err
+		}
+		return []ConvertedStatement{{
+			String:     query,
+			Tag:        roleConfig.Tag(),
+			PgParsable: true,
+			RoleConfig: roleConfig,
+		}}, nil
+	}
+
+	// The publications are kept in the catalog by MyDuck Server, which streams their changes to the subscribers.
+	publicationConfig, err := parsePublicationSQL(query)
+	if publicationConfig != nil || err != nil {
+		if err != nil {
+			return nil, err
+		}
+		return []ConvertedStatement{{
+			String:            query,
+			Tag:               string(publicationConfig.Action),
+			PgParsable:        true,
+			PublicationConfig: publicationConfig,
+		}}, nil
+	}
+
+	// The foreign servers are attached to DuckDB, and the foreign tables are views of their tables.
+	foreignDataConfig, err := parseForeignDataSQL(query)
+	if foreignDataConfig != nil || err != nil {
+		if err != nil {
+			return nil, err
+		}
+		return []ConvertedStatement{{
+			String:            query,
+			Tag:               string(foreignDataConfig.Action),
+			PgParsable:        true,
+			ForeignDataConfig: foreignDataConfig,
+		}}, nil
+	}
+
+	// COPY to and from files and object storage is delegated to DuckDB, whose options the Postgres parser rejects.
+	copyFileConfig, err := parseCopyFileSQL(query)
+	if copyFileConfig != nil || err != nil {
+		if err != nil {
+			return nil, err
+		}
+		return []ConvertedStatement{{
+			String:         query,
+			Tag:            "COPY",
+			PgParsable:     true,
+			CopyFileConfig: copyFileConfig,
+		}}, nil
+	}
+
+	query, onCommitActions := stripOnCommit(query)
 	stmts, err := parser.Parse(query)
 	if err != nil {
+		// The query of a DECLARE CURSOR statement may be in DuckDB syntax.
+		if cursorConfig, err := parseCursorSQL(query); cursorConfig != nil || err != nil {
+			if err != nil {
+				return nil, err
+			}
+			return []ConvertedStatement{{
+				String:       query,
+				Tag:          string(cursorConfig.Action),
+				PgParsable:   true,
+				CursorConfig: cursorConfig,
+			}}, nil
+		}
+		// So may the query of a PREPARE statement, and DISCARD PLANS is not supported by the Postgres parser.
+		if prepareConfig, err := parsePrepareSQL(query); prepareConfig != nil || err != nil {
+			if err != nil {
+				return nil, err
+			}
+			return []ConvertedStatement{{
+				String:        query,
+				Tag:           prepareConfig.Tag(),
+				PgParsable:    true,
+				PrepareConfig: prepareConfig,
+			}}, nil
+		}
+
 		// DuckDB syntax is not fully compatible with PostgreSQL, so we need to handle some queries differently.
 		stmts, _ = parser.Parse("SELECT 'SQL syntax is incompatible with PostgreSQL' AS error")
 		return []ConvertedStatement{{
@@ -1288,17 +1887,55 @@ func (h *ConnectionHandler) convertQuery(query string, modifiers ...QueryModifie
 		if fullMatchQuery != "" {
 			convertedStmts[i].String = fullMatchQuery
 		} else {
-			convertedStmts[i].String = stmt.SQL
+			convertedStmts[i].String = ConvertTempSchema(ConvertJSON(ConvertArrayLiterals(stmt.SQL)))
 		}
 		convertedStmts[i].AST = stmt.AST
 		convertedStmts[i].Tag = stmt.AST.StatementTag()
 		convertedStmts[i].PgParsable = true
+
+		// The ON COMMIT actions of the temporary tables are taken by MyDuck Server, since DuckDB does not support them.
+		if createTable, ok := stmt.AST.(*tree.CreateTable); ok && createTable.Persistence.IsTemporary() && len(onCommitActions) > 0 {
+			if onCommitActions[0] != OnCommitPreserveRows {
+				convertedStmts[i].TempTableConfig = &TempTableConfig{Name: createTable.Table.Table(), OnCommit: onCommitActions[0]}
+			}
+			onCommitActions = onCommitActions[1:]
+		}
+
+		// SQL-level cursors are handled by MyDuck Server, since DuckDB does not support them.
+		cursorConfig, err := parseCursorSQL(stmt.SQL)
+		if err != nil {
+			return nil, err
+		}
+		if cursorConfig != nil {
+			convertedStmts[i].Tag = string(cursorConfig.Action)
+			convertedStmts[i].CursorConfig = cursorConfig
+		}
+
+		// SQL-level prepared statements are handled by MyDuck Server, since they share their names with the
+		// protocol-level ones.
+		prepareConfig, err := parsePrepareSQL(stmt.SQL)
+		if err != nil {
+			return nil, err
+		}
+		if prepareConfig != nil {
+			convertedStmts[i].Tag = prepareConfig.Tag()
+			convertedStmts[i].PrepareConfig = prepareConfig
+		}
 	}
 	return convertedStmts, nil
 }
 
 // discardAll handles the DISCARD ALL command
 func (h *ConnectionHandler) discardAll(query ConvertedStatement) error {
+	// The prepared statements are closed before the DuckDB connection they are prepared on.
+	h.deleteAllPreparedStatements()
+	h.closeCursors()
+	h.unlisten("")
+	h.releaseAdvisoryLocks(true)
+	clear(h.customSettings)
+	// The pg_settings table and the temporary tables are dropped along with the DuckDB connection.
+	h.pgSettingsCreated = false
+	h.tempTables = nil
 	h.closeBackendConn()
 
 	return h.send(&pgproto3.CommandComplete{
@@ -1319,6 +1956,16 @@ func (h *ConnectionHandler) handleCopyFromStdinQuery(
 	}
 	sqlCtx.SetLogger(sqlCtx.GetLogger().WithField("query", query.String))
 
+	// The internal tables of the roles and the foreign servers may only be written by the superusers.
+	if !h.isSuperuser() {
+		if err := permissionDenied(superuserTableRegex, copyFrom.Table.String()); err != nil {
+			return err
+		}
+	}
+	if err := h.duckHandler.checkClientWrite(sqlCtx, copyFrom); err != nil {
+		return err
+	}
+
 	table, err := ValidateCopyFrom(copyFrom, sqlCtx)
 	if err != nil {
 		return err
@@ -1329,6 +1976,7 @@ func (h *ConnectionHandler) handleCopyFromStdinQuery(
 		targetTable:       table,
 		rawOptions:        rawOptions,
 	}
+	h.markTransactionUnreplayable("COPY FROM STDIN")
 
 	var format byte
 	switch copyFrom.Options.CopyFormat {
@@ -1338,8 +1986,19 @@ func (h *ConnectionHandler) handleCopyFromStdinQuery(
 		format = 1 // binary format
 	}
 
+	// The format of each column must match the overall format.
+	columns := len(copyFrom.Columns)
+	if columns == 0 {
+		columns = len(table.Schema())
+	}
+	columnFormats := make([]uint16, columns)
+	for i := range columnFormats {
+		columnFormats[i] = uint16(format)
+	}
+
 	return h.send(&pgproto3.CopyInResponse{
-		OverallFormat: format,
+		OverallFormat:     format,
+		ColumnFormatCodes: columnFormats,
 	})
 }
 
@@ -1360,10 +2019,32 @@ func (h *ConnectionHandler) discardToSync() error {
 
 // Send sends the given message over the connection.
 func (h *ConnectionHandler) send(message pgproto3.BackendMessage) error {
+	if cc, ok := message.(*pgproto3.CommandComplete); ok {
+		h.completeStatement(cc.CommandTag)
+	}
+	h.sendMu.Lock()
+	defer h.sendMu.Unlock()
 	h.backend.Send(message)
 	return h.backend.Flush()
 }
 
+// sendBuffered buffers a message to be sent to the client by the next flush, which is done by send or flush.
+func (h *ConnectionHandler) sendBuffered(message pgproto3.BackendMessage) {
+	if cc, ok := message.(*pgproto3.CommandComplete); ok {
+		h.completeStatement(cc.CommandTag)
+	}
+	h.sendMu.Lock()
+	defer h.sendMu.Unlock()
+	h.backend.Send(message)
+}
+
+// flush sends the buffered messages to the client.
+func (h *ConnectionHandler) flush() error {
+	h.sendMu.Lock()
+	defer h.sendMu.Unlock()
+	return h.backend.Flush()
+}
+
 // returnsRow returns whether the query returns set of rows such as SELECT and FETCH statements.
 func returnsRow(tag string) bool {
 	switch tag {
@@ -1374,12 +2055,58 @@ func returnsRow(tag string) bool {
 	}
 }
 
+// handleCopyToStdout handles the COPY TO STDOUT query, which is limited by the statement_timeout.
+// executeCopyFile runs a COPY statement that writes to or reads from a file in DuckDB, which reports the number of the
+// copied rows. As in Postgres, only superusers may read and write the files of the server.
+func (h *ConnectionHandler) executeCopyFile(statement ConvertedStatement) error {
+	config := statement.CopyFileConfig
+	if !h.isSuperuser() {
+		direction, role := "to", "pg_write_server_files"
+		if config.From {
+			direction, role = "from", "pg_read_server_files"
+		}
+		return fmt.Errorf("must be superuser or have privileges of the %s role to COPY %s a file", role, direction)
+	}
+	sqlCtx, err := h.duckHandler.NewContext(context.Background(), h.mysqlConn, statement.String)
+	if err != nil {
+		return err
+	}
+	// The COPY is registered as a running query, so that it can be canceled.
+	ctx, err := sqlCtx.ProcessList.BeginQuery(sqlCtx, statement.String)
+	if err != nil {
+		return err
+	}
+	defer sqlCtx.ProcessList.EndQuery(ctx)
+
+	stopTimeout := h.startStatementTimeout()
+	var count int64
+	err = stopTimeout(adapter.QueryRow(ctx, config.DuckSQL()).Scan(&count))
+	if err != nil {
+		return err
+	}
+	return h.send(&pgproto3.CommandComplete{
+		CommandTag: []byte(fmt.Sprintf("COPY %d", count)),
+	})
+}
+
 func (h *ConnectionHandler) handleCopyToStdout(query ConvertedStatement, copyTo *tree.CopyTo, subquery string, format tree.CopyFormat, rawOptions string) error {
-	ctx, err := h.duckHandler.NewContext(context.Background(), h.mysqlConn, query.String)
+	stopTimeout := h.startStatementTimeout()
+	return stopTimeout(h.copyToStdout(query, copyTo, subquery, format, rawOptions))
+}
+
+func (h *ConnectionHandler) copyToStdout(query ConvertedStatement, copyTo *tree.CopyTo, subquery string, format tree.CopyFormat, rawOptions string) error {
+	sqlCtx, err := h.duckHandler.NewContext(context.Background(), h.mysqlConn, query.String)
 	if err != nil {
 		return err
 	}
-	ctx.SetLogger(ctx.GetLogger().WithField("query", query.String))
+	sqlCtx.SetLogger(sqlCtx.GetLogger().WithField("query", query.String))
+
+	// The COPY is registered as a running query, so that it can be canceled.
+	ctx, err := sqlCtx.ProcessList.BeginQuery(sqlCtx, query.String)
+	if err != nil {
+		return err
+	}
+	defer sqlCtx.ProcessList.EndQuery(ctx)
 
 	// Create cancelable context
 	childCtx, cancel := context.WithCancel(ctx)
@@ -1415,6 +2142,15 @@ func (h *ConnectionHandler) handleCopyToStdout(query ConvertedStatement, copyTo
 		}
 	}
 
+	// The rows are read by DuckDB, so the tables they come from are checked as those of a SELECT.
+	source := stmt
+	if source == "" {
+		source = copyTo.Table.String()
+	}
+	if err := h.duckHandler.checkTableAccess(ctx, "SELECT * FROM "+source, nil); err != nil {
+		return err
+	}
+
 	var writer DataWriter
 
 	switch format {
@@ -1425,6 +2161,12 @@ func (h *ConnectionHandler) handleCopyToStdout(query ConvertedStatement, copyTo
 			stmt,
 			rawOptions,
 		)
+	case tree.CopyFormatBinary:
+		writer, err = NewBinaryWriter(
+			ctx, h.duckHandler,
+			schema, table, columns,
+			stmt,
+		)
 	default:
 		writer, err = NewDuckDataWriter(
 			ctx, h.duckHandler,
@@ -1473,12 +2215,15 @@ func (h *ConnectionHandler) handleCopyToStdout(query ConvertedStatement, copyTo
 			ctx.GetLogger().Debug("Finished copying data from the pipe to the client")
 		}()
 
-		sendCopyOutResponse := func(numberOfColumns int) error {
+		sendCopyOutResponse := func(format byte, numberOfColumns int) error {
 			ctx.GetLogger().Debug("sending CopyOutResponse to the client")
 			columnsFormatCodes := make([]uint16, numberOfColumns)
+			for i := range columnsFormatCodes {
+				columnsFormatCodes[i] = uint16(format)
+			}
 			copyOutResponse := &pgproto3.CopyOutResponse{
-				OverallFormat:     0,                  // 0 for text format
-				ColumnFormatCodes: columnsFormatCodes, // 0 for text format
+				OverallFormat:     format,             // 0 for text format, 1 for binary format
+				ColumnFormatCodes: columnsFormatCodes, // the same as the overall format
 			}
 			return h.send(copyOutResponse)
 		}
@@ -1505,7 +2250,7 @@ func (h *ConnectionHandler) handleCopyToStdout(query ConvertedStatement, copyTo
 				if !responsed {
 					responsed = true
 					count := bytes.Count(line, []byte{'\t'})
-					err := sendCopyOutResponse(count + 1)
+					err := sendCopyOutResponse(0, count+1)
 					if err != nil {
 						globalErr.Store(&err)
 						cancel()
@@ -1520,16 +2265,40 @@ func (h *ConnectionHandler) handleCopyToStdout(query ConvertedStatement, copyTo
 				}
 			}
 		default:
-			err := sendCopyOutResponse(1)
-			if err != nil {
-				globalErr.Store(&err)
-				cancel()
-				return
+			var source io.Reader = pipe
+			if format == tree.CopyFormatBinary {
+				// The number of columns is the field count of the first tuple, after the 19-byte header.
+				// If there is no tuple, it is the -1 of the trailer.
+				reader := bufio.NewReader(pipe)
+				head, err := reader.Peek(len(binaryCopySignature) + 8 + 2)
+				if err != nil {
+					if err == io.EOF {
+						return // the query failed before writing anything
+					}
+					globalErr.Store(&err)
+					cancel()
+					return
+				}
+				count := max(int(int16(binary.BigEndian.Uint16(head[len(head)-2:]))), 0)
+				err = sendCopyOutResponse(1, count)
+				if err != nil {
+					globalErr.Store(&err)
+					cancel()
+					return
+				}
+				source = reader
+			} else {
+				err := sendCopyOutResponse(0, 1)
+				if err != nil {
+					globalErr.Store(&err)
+					cancel()
+					return
+				}
 			}
 
 			buf := make([]byte, 1<<20) // 1MB buffer
 			for {
-				n, err := pipe.Read(buf)
+				n, err := source.Read(buf)
 				if err != nil {
 					if err == io.EOF {
 						break
@@ -25,6 +25,9 @@ var (
 	reCopyToFormat = regexp.MustCompile(`(?i)^COPY\s+(.*?)\s+TO\s+STDOUT(?:\s+(?:WITH\s*)?\(\s*(?:FORMAT\s+(\w+)\s*,?\s*)?(.*?)\s*\))?$`)
 	// Also for COPY ... FROM STDIN [WITH] (FORMAT PARQUET, OPT1 v1, OPT2, OPT3 v3, ...)
 	reCopyFromFormat = regexp.MustCompile(`(?i)^COPY\s+(.*?)\s+FROM\s+STDIN(?:\s+(?:WITH\s*)?\(\s*(?:FORMAT\s+(\w+)\s*,?\s*)?(.*?)\s*\))?$`)
+	// And for COPY ... TO|FROM 'path' [WITH] (FORMAT PARQUET, OPT1 v1, ...), where the path may be a URL of
+	// object storage, e.g., s3://bucket/key.parquet.
+	reCopyFile = regexp.MustCompile(`(?is)^COPY\s+(.*?)\s+(TO|FROM)\s+'((?:[^']|'')*)'(?:\s+(?:WITH\s*)?\(\s*(?:FORMAT\s+(\w+)\s*,?\s*)?(.*?)\s*\))?$`)
 )
 
 func ParseFormat(s string) (format tree.CopyFormat, ok bool) {
@@ -75,6 +78,69 @@ func ParseCopyFrom(stmt string) (target string, format tree.CopyFormat, options
 	return
 }
 
+// CopyFileConfig is a COPY statement that writes to or reads from a file on the server or in object storage, which
+// is delegated to the native COPY of DuckDB.
+type CopyFileConfig struct {
+	From    bool   // COPY FROM the file, or COPY TO the file
+	Source  string // the table with an optional column list, or the parenthesized query of COPY TO
+	Path    string // the path or the URL of the file
+	Format  string // PARQUET, CSV, or JSON, or empty to infer it from the extension of the path
+	Options string // the other options, which are passed to DuckDB as they are
+}
+
+// parseCopyFileSQL parses a COPY statement that writes to or reads from a file. It returns nil if the statement is not
+// such a COPY statement.
+func parseCopyFileSQL(query string) (*CopyFileConfig, error) {
+	stmt := strings.TrimSpace(sql.RemoveSpaceAndDelimiter(RemoveComments(query), ';'))
+	m := reCopyFile.FindStringSubmatch(stmt)
+	if m == nil {
+		return nil, nil
+	}
+	config := &CopyFileConfig{
+		From:    strings.EqualFold(m[2], "FROM"),
+		Source:  strings.TrimSpace(m[1]),
+		Path:    strings.ReplaceAll(m[3], "''", "'"),
+		Format:  strings.ToUpper(m[4]),
+		Options: strings.TrimSpace(m[5]),
+	}
+	if config.From && strings.HasPrefix(config.Source, "(") {
+		return nil, fmt.Errorf("COPY FROM a file does not support a query")
+	}
+	switch config.Format {
+	case "", "PARQUET", "CSV", "JSON":
+	default:
+		return nil, fmt.Errorf("COPY format \"%s\" is not supported for files, only PARQUET, CSV, and JSON are", strings.ToLower(m[4]))
+	}
+	return config, nil
+}
+
+// DuckSQL returns the COPY statement to run in DuckDB.
+func (c *CopyFileConfig) DuckSQL() string {
+	var b strings.Builder
+	b.WriteString("COPY ")
+	b.WriteString(c.Source)
+	if c.From {
+		b.WriteString(" FROM '")
+	} else {
+		b.WriteString(" TO '")
+	}
+	b.WriteString(strings.ReplaceAll(c.Path, "'", "''"))
+	b.WriteString("'")
+	var options []string
+	if c.Format != "" {
+		options = append(options, "FORMAT "+c.Format)
+	}
+	if c.Options != "" {
+		options = append(options, c.Options)
+	}
+	if len(options) > 0 {
+		b.WriteString(" (")
+		b.WriteString(strings.Join(options, ", "))
+		b.WriteString(")")
+	}
+	return b.String()
+}
+
 type OptionValueType uint8
 
 const (
@@ -2,6 +2,8 @@ package pgserver
 
 import (
 	"testing"
+
+	"github.com/stretchr/testify/require"
 )
 
 func TestParseCopyOptions(t *testing.T) {
@@ -347,3 +349,49 @@ func TestParseCopyOptions(t *testing.T) {
 		})
 	}
 }
+
+func TestParseCopyFileSQL(t *testing.T) {
+	tests := []struct {
+		query   string
+		want    *CopyFileConfig
+		duckSQL string
+	}{
+		{
+			query:   "COPY t TO 's3://bucket/t.parquet' (FORMAT PARQUET)",
+			want:    &CopyFileConfig{Source: "t", Path: "s3://bucket/t.parquet", Format: "PARQUET"},
+			duckSQL: "COPY t TO 's3://bucket/t.parquet' (FORMAT PARQUET)",
+		},
+		{
+			query:   "copy (SELECT a FROM t WHERE b = 'x') to '/tmp/it''s.csv' with (format csv, HEADER, DELIMITER '|');",
+			want:    &CopyFileConfig{Source: "(SELECT a FROM t WHERE b = 'x')", Path: "/tmp/it's.csv", Format: "CSV", Options: "HEADER, DELIMITER '|'"},
+			duckSQL: "COPY (SELECT a FROM t WHERE b = 'x') TO '/tmp/it''s.csv' (FORMAT CSV, HEADER, DELIMITER '|')",
+		},
+		{
+			query:   "COPY s.t (a, b) FROM 'gs://bucket/data/*.parquet'",
+			want:    &CopyFileConfig{From: true, Source: "s.t (a, b)", Path: "gs://bucket/data/*.parquet"},
+			duckSQL: "COPY s.t (a, b) FROM 'gs://bucket/data/*.parquet'",
+		},
+		{
+			query: "COPY t TO STDOUT (FORMAT PARQUET)",
+		},
+		{
+			query: "COPY t FROM STDIN",
+		},
+	}
+	for _, tt := range tests {
+		got, err := parseCopyFileSQL(tt.query)
+		require.NoError(t, err, tt.query)
+		require.Equal(t, tt.want, got, tt.query)
+		if got != nil {
+			require.Equal(t, tt.duckSQL, got.DuckSQL())
+		}
+	}
+
+	for _, query := range []string{
+		"COPY t TO '/tmp/t.bin' (FORMAT BINARY)",
+		"COPY (SELECT 1) FROM '/tmp/t.csv'",
+	} {
+		_, err := parseCopyFileSQL(query)
+		require.Error(t, err, query)
+	}
+}
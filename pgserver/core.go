@@ -14,9 +14,12 @@
 package pgserver
 
 import (
+	"fmt"
 	"strings"
 
+	"github.com/apecloud/myduckserver/adapter"
 	"github.com/apecloud/myduckserver/backend"
+	"github.com/apecloud/myduckserver/catalog"
 	"github.com/dolthub/go-mysql-server/sql"
 )
 
@@ -38,23 +41,36 @@ func GetSqlDatabaseFromContext(ctx *sql.Context, database string) (sql.Database,
 	return db, nil
 }
 
-// GetSqlTableFromContext returns the table from the context. Uses the context's current database if an empty database
-// name is provided. Returns nil if no table was found.
-// TODO(fan): Support search_path.
+// GetSqlTableFromContext returns the table from the context. If an empty database name is provided, the table is
+// looked up in the context's current database, then in the schemas of the search_path setting. Returns an
+// ErrTableNotFound error if no table was found.
 func GetSqlTableFromContext(ctx *sql.Context, databaseName string, tableName string) (sql.Table, error) {
-	db, err := GetSqlDatabaseFromContext(ctx, databaseName)
-	if err != nil || db == nil {
-		return nil, err
+	candidates := []string{databaseName}
+	if len(databaseName) == 0 {
+		path, err := SearchPath(ctx)
+		if err != nil {
+			return nil, err
+		}
+		candidates = append(candidates, path...)
 	}
 
-	tbl, ok, err := db.GetTableInsensitive(ctx, tableName)
-	if err != nil {
-		return nil, err
-	}
-	if !ok {
-		return nil, sql.ErrTableNotFound.New(tableName)
+	for _, candidate := range candidates {
+		db, err := GetSqlDatabaseFromContext(ctx, candidate)
+		if err != nil {
+			return nil, err
+		}
+		if db == nil {
+			continue
+		}
+		tbl, ok, err := db.GetTableInsensitive(ctx, tableName)
+		if err != nil {
+			return nil, err
+		}
+		if ok {
+			return tbl, nil
+		}
 	}
-	return tbl, nil
+	return nil, sql.ErrTableNotFound.New(tableName)
 }
 
 // SearchPath returns all the schemas in the search_path setting, with elements like "$user" expanded
@@ -65,9 +81,11 @@ func SearchPath(ctx *sql.Context) ([]string, error) {
 	}
 
 	pathElems := strings.Split(searchPathVar.(string), ",")
-	path := make([]string, len(pathElems))
-	for i, pathElem := range pathElems {
-		path[i] = normalizeSearchPathSchema(ctx, pathElem)
+	path := make([]string, 0, len(pathElems))
+	for _, pathElem := range pathElems {
+		if schemaName := normalizeSearchPathSchema(ctx, pathElem); schemaName != "" {
+			path = append(path, schemaName)
+		}
 	}
 
 	return path, nil
@@ -79,5 +97,71 @@ func normalizeSearchPathSchema(ctx *sql.Context, schemaName string) string {
 		client := ctx.Session.Client()
 		return client.User
 	}
+	// Unquoted schema names are case-insensitive, as the other identifiers.
+	if strings.HasPrefix(schemaName, `"`) && strings.HasSuffix(schemaName, `"`) && len(schemaName) > 1 {
+		return strings.ReplaceAll(schemaName[1:len(schemaName)-1], `""`, `"`)
+	}
+	return strings.ToLower(schemaName)
+}
+
+// quoteSearchPathSchema quotes the schema name for the search_path setting if it is not a lower-case identifier.
+func quoteSearchPathSchema(schemaName string) string {
+	for i, c := range schemaName {
+		if !(c >= 'a' && c <= 'z' || c == '_' || i > 0 && c >= '0' && c <= '9') {
+			return `"` + strings.ReplaceAll(schemaName, `"`, `""`) + `"`
+		}
+	}
 	return schemaName
 }
+
+// ApplySearchPath makes the underlying DuckDB connection resolve unqualified names in the schemas of the search_path
+// setting, in order. As in Postgres, the schemas that do not exist are skipped; if none of them exists, the "public"
+// schema is used. The first schema becomes the current database, where the new tables are created.
+func ApplySearchPath(ctx *sql.Context) error {
+	path, err := SearchPath(ctx)
+	if err != nil {
+		return err
+	}
+	conn, err := adapter.GetCatalogConn(ctx)
+	if err != nil {
+		return err
+	}
+	catalogName := adapter.GetCurrentCatalog(ctx)
+
+	rows, err := conn.QueryContext(ctx, "SELECT schema_name FROM information_schema.schemata WHERE catalog_name = ?", catalogName)
+	if err != nil {
+		return err
+	}
+	defer rows.Close()
+	existing := make(map[string]bool)
+	for rows.Next() {
+		var schemaName string
+		if err := rows.Scan(&schemaName); err != nil {
+			return err
+		}
+		existing[schemaName] = true
+	}
+	if err := rows.Err(); err != nil {
+		return err
+	}
+
+	var schemas, entries []string
+	for _, schemaName := range path {
+		if existing[schemaName] {
+			existing[schemaName] = false // Skip the duplicates
+			schemas = append(schemas, schemaName)
+			entries = append(entries, catalog.FullSchemaName(catalogName, schemaName))
+		}
+	}
+	if len(schemas) == 0 {
+		schemas = []string{"public"}
+		entries = []string{catalog.FullSchemaName(catalogName, "public")}
+	}
+
+	searchPath := strings.ReplaceAll(strings.Join(entries, ","), "'", "''")
+	if _, err := conn.ExecContext(ctx, fmt.Sprintf("SET search_path = '%s'", searchPath)); err != nil {
+		return err
+	}
+	ctx.SetCurrentDatabase(schemas[0])
+	return nil
+}
@@ -0,0 +1,142 @@
+package pgserver
+
+import (
+	"context"
+	"io"
+	"slices"
+
+	"github.com/apecloud/myduckserver/adapter"
+	"github.com/apecloud/myduckserver/pgtypes"
+	"github.com/dolthub/go-mysql-server/sql"
+	"github.com/dolthub/vitess/go/mysql"
+	"github.com/jackc/pgx/v5/pgproto3"
+)
+
+// portalCursor is the open result of a portal that is executed with a row limit.
+// https://www.postgresql.org/docs/current/protocol-flow.html#PROTOCOL-FLOW-EXT-QUERY
+// > If Execute terminates before completing the execution of a portal (due to reaching a nonzero result-row count),
+// > it will send a PortalSuspended message; the appearance of this message tells the frontend that another Execute
+// > should be issued against the same portal to complete the operation.
+type portalCursor struct {
+	ctx    *sql.Context
+	schema sql.Schema
+	iter   sql.RowIter
+	fields []pgproto3.FieldDescription
+	done   bool
+}
+
+// OpenCursor executes the bound portal and returns its result as a cursor, from which the rows are fetched on demand.
+func (h *DuckHandler) OpenCursor(ctx context.Context, c *mysql.Conn, portal PortalData) (*portalCursor, error) {
+	sqlCtx, err := h.sm.NewContextWithQuery(ctx, c, portal.Statement.String)
+	if err != nil {
+		return nil, err
+	}
+	schema, iter, _, err := h.executeBoundPlan(sqlCtx, portal.Statement.String, portal.Statement.AST, portal.Stmt, portal.Vars)
+	if err != nil {
+		return nil, sql.CastSQLError(err)
+	}
+	return &portalCursor{
+		ctx:    sqlCtx,
+		schema: schema,
+		iter:   iter,
+		fields: schemaToFieldDescriptions(sqlCtx, schema, portal.ResultFormatCodes, ExtendedQueryMode),
+	}, nil
+}
+
+// DeclareCursor runs the query of a DECLARE CURSOR statement and returns its result as a cursor, whose rows are
+// fetched by the FETCH statements in the given formats.
+func (h *DuckHandler) DeclareCursor(ctx context.Context, c *mysql.Conn, query string, resultFormatCodes []int16) (*portalCursor, error) {
+	sqlCtx, err := h.sm.NewContextWithQuery(ctx, c, query)
+	if err != nil {
+		return nil, err
+	}
+	if err := h.checkTableAccess(sqlCtx, query, nil); err != nil {
+		return nil, err
+	}
+	rows, err := adapter.QueryCatalog(sqlCtx, query)
+	if err != nil {
+		return nil, err
+	}
+	schema, err := pgtypes.InferSchema(rows)
+	if err != nil {
+		rows.Close()
+		return nil, err
+	}
+	iter, err := NewSqlRowIter(rows, schema)
+	if err != nil {
+		rows.Close()
+		return nil, err
+	}
+	return &portalCursor{
+		ctx:    sqlCtx,
+		schema: schema,
+		iter:   iter,
+		fields: schemaToFieldDescriptions(sqlCtx, schema, resultFormatCodes, ExtendedQueryMode),
+	}, nil
+}
+
+// fetch sends at most |maxRows| rows of the cursor, or all the remaining rows if |maxRows| is 0, and returns the
+// number of rows sent. If |send| is nil, the rows are skipped instead. The cursor is closed once it is exhausted,
+// or if an error occurs.
+func (c *portalCursor) fetch(h *DuckHandler, maxRows uint32, send func(row [][]byte) error) (int32, error) {
+	var n int32
+	for !c.done && (maxRows == 0 || uint32(n) < maxRows) {
+		row, err := c.iter.Next(c.ctx)
+		if err == io.EOF {
+			return n, c.close()
+		} else if err != nil {
+			_ = c.close()
+			return n, err
+		}
+		if send == nil {
+			n++
+			continue
+		}
+		values, err := h.rowToBytes(c.ctx, c.schema, c.fields, row)
+		if err != nil {
+			_ = c.close()
+			return n, err
+		}
+		if err := send(values); err != nil {
+			_ = c.close()
+			return n, err
+		}
+		n++
+	}
+	return n, nil
+}
+
+// materialize reads the remaining rows of the cursor into memory and closes its result in DuckDB, so that the rows
+// can still be fetched after the transaction ends.
+func (c *portalCursor) materialize() error {
+	if c.done {
+		return nil
+	}
+	var rows []sql.Row
+	for {
+		row, err := c.iter.Next(c.ctx)
+		if err == io.EOF {
+			break
+		} else if err != nil {
+			_ = c.close()
+			return err
+		}
+		// The iterator may reuse the buffer of the row.
+		rows = append(rows, slices.Clone(row))
+	}
+	if err := c.iter.Close(c.ctx); err != nil {
+		c.done = true
+		return err
+	}
+	c.iter = sql.RowsToRowIter(rows...)
+	return nil
+}
+
+// close closes the result of the cursor, after which no more rows are fetched.
+func (c *portalCursor) close() error {
+	if c.done {
+		return nil
+	}
+	c.done = true
+	return c.iter.Close(c.ctx)
+}
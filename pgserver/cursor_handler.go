@@ -0,0 +1,316 @@
+package pgserver
+
+import (
+	"context"
+	"fmt"
+	"math"
+	"regexp"
+	"strconv"
+	"strings"
+
+	"github.com/dolthub/go-mysql-server/sql"
+	"github.com/jackc/pgx/v5/pgproto3"
+)
+
+// This file handles the SQL statements for cursors. It supports:
+//
+// 1. Declaring a cursor:
+//    DECLARE c [BINARY] [NO SCROLL] CURSOR [WITH HOLD | WITHOUT HOLD] FOR SELECT ...;
+//    The query runs in DuckDB, whose result is consumed as the rows are fetched.
+//
+// 2. Fetching rows from a cursor, or skipping them:
+//    FETCH [NEXT | FORWARD | count | FORWARD count | ALL | FORWARD ALL | RELATIVE count | ABSOLUTE count] [FROM | IN] c;
+//    MOVE [direction] [FROM | IN] c;
+//    The cursors only scan forward, so the directions that move backward are rejected.
+//
+// 3. Closing a cursor or all of them:
+//    CLOSE c;
+//    CLOSE ALL;
+//    The cursors declared without WITH HOLD are also closed at the end of the transaction.
+//
+// As in Postgres, the remaining rows of a cursor declared WITH HOLD are read into memory when the transaction
+// that declares it is committed, so that it can still be fetched afterward. It is closed if the transaction is
+// rolled back instead.
+
+// CursorAction represents the type of cursor statement.
+type CursorAction string
+
+const (
+	CursorDeclare CursorAction = "DECLARE CURSOR"
+	CursorFetch   CursorAction = "FETCH"
+	CursorMove    CursorAction = "MOVE"
+	CursorClose   CursorAction = "CLOSE CURSOR"
+)
+
+// CursorConfig represents a parsed cursor statement.
+type CursorConfig struct {
+	Action CursorAction
+	Name   string // Empty for CLOSE ALL
+	// For DECLARE CURSOR
+	Query  string
+	Binary bool
+	Hold   bool
+	// For FETCH and MOVE: the rows to skip, then the rows to fetch, which is -1 for ALL.
+	// For ABSOLUTE, the rows to skip depend on the position of the cursor.
+	Skip     int64
+	Count    int64
+	Absolute int64
+}
+
+// sqlCursor is a cursor declared by DECLARE CURSOR.
+type sqlCursor struct {
+	*portalCursor
+	hold bool
+	// declaredInTransaction is set until the end of the transaction block that declares the cursor.
+	declaredInTransaction bool
+	position              int64 // The number of rows fetched or skipped so far
+}
+
+// declareCursorRegex matches a DECLARE CURSOR statement and captures the cursor name, the options, and the query.
+var declareCursorRegex = regexp.MustCompile(`(?is)^DECLARE\s+(\w+|"[^"]+")\s+(BINARY\s+)?(?:(?:ASENSITIVE|INSENSITIVE)\s+)?(NO\s+SCROLL\s+|SCROLL\s+)?CURSOR\s+(?:(WITH|WITHOUT)\s+HOLD\s+)?FOR\s+(.+)$`)
+
+// fetchCursorRegex matches a FETCH or MOVE statement and captures the direction and the cursor name.
+var fetchCursorRegex = regexp.MustCompile(`(?is)^(FETCH|MOVE)\s+(?:(.*?)\s+)?(\w+|"[^"]+")$`)
+
+// closeCursorRegex matches a CLOSE statement and captures the cursor name, or ALL.
+var closeCursorRegex = regexp.MustCompile(`(?i)^CLOSE\s+(\w+|"[^"]+")$`)
+
+var errCursorScanForward = fmt.Errorf("cursor can only scan forward")
+
+// parseCursorSQL parses the given SQL statement and returns a CursorConfig, or nil if it is not a cursor statement.
+func parseCursorSQL(query string) (*CursorConfig, error) {
+	query = strings.TrimSpace(sql.RemoveSpaceAndDelimiter(RemoveComments(query), ';'))
+	var config CursorConfig
+	switch {
+	case declareCursorRegex.MatchString(query):
+		matches := declareCursorRegex.FindStringSubmatch(query)
+		if strings.EqualFold(strings.TrimSpace(matches[3]), "SCROLL") {
+			return nil, fmt.Errorf("SCROLL cursors are not supported")
+		}
+		config.Action = CursorDeclare
+		config.Name = foldIdentifier(matches[1])
+		config.Binary = matches[2] != ""
+		config.Hold = strings.EqualFold(matches[4], "WITH")
+		config.Query = matches[5]
+
+	case fetchCursorRegex.MatchString(query):
+		matches := fetchCursorRegex.FindStringSubmatch(query)
+		config.Action = CursorAction(strings.ToUpper(matches[1]))
+		config.Name = foldIdentifier(matches[3])
+		direction := strings.Fields(strings.ToUpper(matches[2]))
+		if n := len(direction); n > 0 && (direction[n-1] == "FROM" || direction[n-1] == "IN") {
+			direction = direction[:n-1]
+		}
+		if err := config.parseDirection(direction); err != nil {
+			return nil, err
+		}
+
+	case closeCursorRegex.MatchString(query):
+		matches := closeCursorRegex.FindStringSubmatch(query)
+		config.Action = CursorClose
+		if !strings.EqualFold(matches[1], "ALL") {
+			config.Name = foldIdentifier(matches[1])
+		}
+
+	default:
+		return nil, nil
+	}
+	return &config, nil
+}
+
+// foldIdentifier folds an unquoted identifier to lower case as Postgres does, and strips the quotes of a quoted one.
+func foldIdentifier(name string) string {
+	if strings.HasPrefix(name, `"`) {
+		return strings.Trim(name, `"`)
+	}
+	return strings.ToLower(name)
+}
+
+// parseDirection parses the direction of a FETCH or MOVE statement, given as upper-case words.
+func (config *CursorConfig) parseDirection(direction []string) error {
+	count := func(s string) (int64, error) {
+		n, err := strconv.ParseInt(s, 10, 64)
+		if err != nil {
+			return 0, fmt.Errorf("invalid %s count: %s", config.Action, s)
+		}
+		if n <= 0 {
+			// A count of 0 re-fetches the current row, and a negative one moves backward.
+			return 0, errCursorScanForward
+		}
+		return n, nil
+	}
+
+	var err error
+	switch len(direction) {
+	case 0:
+		config.Count = 1
+	case 1:
+		switch direction[0] {
+		case "NEXT", "FORWARD":
+			config.Count = 1
+		case "ALL":
+			config.Count = -1
+		case "PRIOR", "FIRST", "LAST", "BACKWARD":
+			return errCursorScanForward
+		default:
+			config.Count, err = count(direction[0])
+		}
+	case 2:
+		switch direction[0] {
+		case "FORWARD":
+			if direction[1] == "ALL" {
+				config.Count = -1
+			} else {
+				config.Count, err = count(direction[1])
+			}
+		case "RELATIVE":
+			var n int64
+			n, err = count(direction[1])
+			config.Skip, config.Count = n-1, 1
+		case "ABSOLUTE":
+			config.Absolute, err = count(direction[1])
+			config.Count = 1
+		case "BACKWARD":
+			return errCursorScanForward
+		default:
+			return fmt.Errorf("invalid %s direction: %s", config.Action, strings.Join(direction, " "))
+		}
+	default:
+		return fmt.Errorf("invalid %s direction: %s", config.Action, strings.Join(direction, " "))
+	}
+	return err
+}
+
+// executeCursorStatement executes a DECLARE CURSOR, FETCH, MOVE, or CLOSE statement.
+func (h *ConnectionHandler) executeCursorStatement(statement ConvertedStatement) error {
+	config := statement.CursorConfig
+	switch config.Action {
+	case CursorDeclare:
+		return h.declareCursor(config)
+	case CursorFetch, CursorMove:
+		return h.fetchCursor(config)
+	case CursorClose:
+		if config.Name == "" {
+			h.closeCursors()
+			return h.send(makeCommandComplete("CLOSE CURSOR ALL", 0))
+		}
+		cursor, ok := h.cursors[config.Name]
+		if !ok {
+			return fmt.Errorf("cursor \"%s\" does not exist", config.Name)
+		}
+		delete(h.cursors, config.Name)
+		if err := cursor.close(); err != nil {
+			return err
+		}
+		return h.send(makeCommandComplete(string(CursorClose), 0))
+	default:
+		return fmt.Errorf("unsupported cursor statement: %s", statement.String)
+	}
+}
+
+func (h *ConnectionHandler) declareCursor(config *CursorConfig) error {
+	if _, ok := h.cursors[config.Name]; ok {
+		return fmt.Errorf("cursor \"%s\" already exists", config.Name)
+	}
+	tag, err := h.duckHandler.getStatementTag(h.mysqlConn, config.Query)
+	if err != nil {
+		return err
+	}
+	if tag != "SELECT" {
+		return fmt.Errorf("cursor \"%s\" must be declared for a SELECT query, not %s", config.Name, tag)
+	}
+	format := int16(pgproto3.TextFormat)
+	if config.Binary {
+		format = pgproto3.BinaryFormat
+	}
+	cursor, err := h.duckHandler.DeclareCursor(context.Background(), h.mysqlConn, config.Query, []int16{format})
+	if err != nil {
+		return err
+	}
+	if config.Hold && !h.inTransaction {
+		// The statement is committed on its own.
+		if err := cursor.materialize(); err != nil {
+			return err
+		}
+	}
+	h.cursors[config.Name] = &sqlCursor{portalCursor: cursor, hold: config.Hold, declaredInTransaction: h.inTransaction}
+	return h.send(makeCommandComplete(string(CursorDeclare), 0))
+}
+
+func (h *ConnectionHandler) fetchCursor(config *CursorConfig) error {
+	cursor, ok := h.cursors[config.Name]
+	if !ok {
+		return fmt.Errorf("cursor \"%s\" does not exist", config.Name)
+	}
+
+	skip, count := config.Skip, config.Count
+	if config.Absolute > 0 {
+		if config.Absolute <= cursor.position {
+			return errCursorScanForward
+		}
+		skip = config.Absolute - cursor.position - 1
+	}
+	if skip > 0 {
+		n, err := cursor.fetch(h.duckHandler, uint32(min(skip, math.MaxUint32)), nil)
+		cursor.position += int64(n)
+		if err != nil {
+			return err
+		}
+	}
+
+	var send func(row [][]byte) error
+	if config.Action == CursorFetch {
+		if err := h.send(&pgproto3.RowDescription{Fields: cursor.fields}); err != nil {
+			return err
+		}
+		send = func(row [][]byte) error {
+			return h.send(&pgproto3.DataRow{Values: row})
+		}
+	}
+	var maxRows uint32 // 0 for ALL
+	if count > 0 {
+		maxRows = uint32(min(count, math.MaxUint32))
+	}
+	n, err := cursor.fetch(h.duckHandler, maxRows, send)
+	cursor.position += int64(n)
+	if err != nil {
+		return err
+	}
+	return h.send(makeCommandComplete(string(config.Action), n))
+}
+
+// holdCursors materializes the cursors declared WITH HOLD in the transaction before it is committed.
+func (h *ConnectionHandler) holdCursors() error {
+	for name, cursor := range h.cursors {
+		if cursor.hold && cursor.declaredInTransaction {
+			if err := cursor.materialize(); err != nil {
+				return fmt.Errorf("failed to hold cursor \"%s\": %w", name, err)
+			}
+		}
+	}
+	return nil
+}
+
+// endCursorTransaction closes the cursors declared without WITH HOLD at the end of a transaction, and the ones
+// declared WITH HOLD in the transaction if it is rolled back.
+func (h *ConnectionHandler) endCursorTransaction(commit bool) {
+	for name, cursor := range h.cursors {
+		if !cursor.hold || (!commit && cursor.declaredInTransaction) {
+			delete(h.cursors, name)
+			if err := cursor.close(); err != nil {
+				h.logger.WithError(err).Warnf("Failed to close cursor %q", name)
+			}
+		}
+		cursor.declaredInTransaction = false
+	}
+}
+
+// closeCursors closes all the cursors.
+func (h *ConnectionHandler) closeCursors() {
+	for name, cursor := range h.cursors {
+		delete(h.cursors, name)
+		if err := cursor.close(); err != nil {
+			h.logger.WithError(err).Warnf("Failed to close cursor %q", name)
+		}
+	}
+}
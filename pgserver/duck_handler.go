@@ -68,8 +68,6 @@ type Row struct {
 	val [][]byte
 }
 
-const rowsBatch = 128
-
 type QueryMode bool
 
 const (
@@ -321,6 +319,12 @@ func (h *DuckHandler) doQuery(ctx context.Context, c *mysql.Conn, query string,
 		"protocol": "postgres",
 	}).Trace("doQuery")
 
+	// The warnings raised while running the query are sent as notices, before the query completes.
+	sqlCtx.ClearWarnings()
+	if h.connectionHandler != nil {
+		defer h.connectionHandler.forwardWarnings(sqlCtx)
+	}
+
 	start := time.Now()
 	var queryStrToLog string
 	if h.encodeLoggedQuery {
@@ -363,7 +367,7 @@ func (h *DuckHandler) doQuery(ctx context.Context, c *mysql.Conn, query string,
 
 	// create result before goroutines to avoid |ctx| racing
 	var r *Result
-	var processedAtLeastOneBatch bool
+	var streamed bool
 
 	// zero/single return schema use spooling shortcut
 	if types.IsOkResultSchema(schema) {
@@ -375,7 +379,7 @@ func (h *DuckHandler) doQuery(ctx context.Context, c *mysql.Conn, query string,
 		r, err = h.resultForMax1RowIter(sqlCtx, schema, rowIter, resultFields)
 	} else {
 		resultFields := schemaToFieldDescriptions(sqlCtx, schema, resultFormatCodes, mode)
-		r, processedAtLeastOneBatch, err = h.resultForDefaultIter(sqlCtx, schema, rowIter, callback, resultFields)
+		r, streamed, err = h.resultForDefaultIter(sqlCtx, schema, rowIter, callback, resultFields)
 	}
 	if err != nil {
 		return err
@@ -386,11 +390,11 @@ func (h *DuckHandler) doQuery(ctx context.Context, c *mysql.Conn, query string,
 
 	sqlCtx.GetLogger().Debugf("Query finished in %d ms", time.Since(start).Milliseconds())
 
-	sqlCtx.GetLogger().Tracef("AtLeastOneBatch=%v RowsInLastBatch=%d", processedAtLeastOneBatch, len(r.Rows))
+	sqlCtx.GetLogger().Tracef("Streamed=%v RowsInResult=%d", streamed, len(r.Rows))
 
-	// processedAtLeastOneBatch means we already called callback() at least
-	// once, so no need to call it if RowsAffected == 0.
-	if r != nil && (r.RowsAffected == 0 && processedAtLeastOneBatch) {
+	// |streamed| means the rows have been passed to callback() one by one,
+	// so there is nothing left to pass.
+	if streamed {
 		return nil
 	}
 
@@ -425,6 +429,12 @@ func (h *DuckHandler) executeQuery(ctx *sql.Context, query string, parsed tree.S
 	case *tree.BeginTransaction, *tree.CommitTransaction, *tree.RollbackTransaction,
 		*tree.CreateTable, *tree.DropTable, *tree.AlterTable, *tree.CreateIndex, *tree.DropIndex,
 		*tree.Insert, *tree.Update, *tree.Delete, *tree.Truncate, *tree.CopyFrom, *tree.CopyTo, *tree.SetVar:
+		if err = h.checkTableAccess(ctx, query, nil); err != nil {
+			break
+		}
+		if err = h.checkClientWrite(ctx, parsed); err != nil {
+			break
+		}
 		result, err = adapter.Exec(ctx, query)
 		if err != nil {
 			break
@@ -458,6 +468,10 @@ func (h *DuckHandler) executeQuery(ctx *sql.Context, query string, parsed tree.S
 		}
 		p := parsed.(*tree.DropDatabase)
 		dbName := parsed.(*tree.DropDatabase).Name.String()
+		if dbName == adapter.GetCurrentCatalog(ctx) {
+			err = fmt.Errorf("cannot drop the currently open database")
+			break
+		}
 		err = provider.DropCatalog(dbName, p.IfExists)
 		if err != nil {
 			break
@@ -465,6 +479,9 @@ func (h *DuckHandler) executeQuery(ctx *sql.Context, query string, parsed tree.S
 		schema = types.OkResultSchema
 		iter = sql.RowsToRowIter(sql.NewRow(types.OkResult{}))
 	default:
+		if err = h.checkTableAccess(ctx, query, nil); err != nil {
+			break
+		}
 		rows, err = adapter.QueryCatalog(ctx, query)
 		if err != nil {
 			break
@@ -489,7 +506,7 @@ func (h *DuckHandler) executeQuery(ctx *sql.Context, query string, parsed tree.S
 
 // executeBoundPlan is a QueryExecutor that calls QueryWithBindings on the given engine using the given query and parsed
 // statement, which may be nil.
-func (h *DuckHandler) executeBoundPlan(ctx *sql.Context, query string, _ tree.Statement, stmt *duckdb.Stmt, vars []any) (sql.Schema, sql.RowIter, *sql.QueryFlags, error) {
+func (h *DuckHandler) executeBoundPlan(ctx *sql.Context, query string, parsed tree.Statement, stmt *duckdb.Stmt, vars []any) (sql.Schema, sql.RowIter, *sql.QueryFlags, error) {
 	// return h.e.PrepQueryPlanForExecution(ctx, query, plan, nil)
 
 	// TODO(fan): Currently, the result of executing the bound query is occasionally incorrect.
@@ -551,6 +568,12 @@ func (h *DuckHandler) executeBoundPlan(ctx *sql.Context, query string, _ tree.St
 		result   stdsql.Result
 		err      error
 	)
+	if err = h.checkTableAccess(ctx, query, vars); err != nil {
+		return nil, nil, nil, err
+	}
+	if err = h.checkClientWrite(ctx, parsed); err != nil {
+		return nil, nil, nil, err
+	}
 
 	switch stmtType {
 	case duckdb.DUCKDB_STATEMENT_TYPE_SELECT,
@@ -616,23 +639,24 @@ func schemaToFieldDescriptions(ctx *sql.Context, s sql.Schema, resultFormatCodes
 		var size int16
 		var format int16
 		var err error
+		typmod := int32(-1)
 		if pgType, ok := c.Type.(pgtypes.PostgresType); ok {
 			oid = pgType.PG.OID
+			typmod = pgType.TypeModifier()
 			if mode == SimpleQueryMode {
 				// https://www.postgresql.org/docs/current/protocol-flow.html
 				// > In simple Query mode, the format of retrieved values is always text, except ...
 				format = pgproto3.TextFormat
-			} else {
-				if resultFormatCodes != nil && len(resultFormatCodes) > 0 {
-					// Specified overall or per-column format codes
-					if len(resultFormatCodes) == 1 {
-						format = resultFormatCodes[0]
-					} else {
-						format = resultFormatCodes[i]
-					}
+			} else if len(resultFormatCodes) > 0 {
+				// Specified overall or per-column format codes
+				if len(resultFormatCodes) == 1 {
+					format = resultFormatCodes[0]
 				} else {
-					format = pgType.PG.Codec.PreferredFormat()
+					format = resultFormatCodes[i]
 				}
+			} else {
+				// > If there are no result-column format codes, the default is text.
+				format = pgproto3.TextFormat
 			}
 			size = int16(pgType.Size)
 		} else {
@@ -655,7 +679,7 @@ func schemaToFieldDescriptions(ctx *sql.Context, s sql.Schema, resultFormatCodes
 			TableAttributeNumber: uint16(0),
 			DataTypeOID:          oid,
 			DataTypeSize:         size,
-			TypeModifier:         int32(-1), // TODO: used for domain type, which we don't support yet
+			TypeModifier:         typmod,
 			Format:               format,
 		}
 	}
@@ -723,9 +747,11 @@ func (h *DuckHandler) resultForMax1RowIter(ctx *sql.Context, schema sql.Schema,
 	return &Result{Fields: resultFields, Rows: []Row{{outputRow}}, RowsAffected: 1}, nil
 }
 
-// resultForDefaultIter reads batches of rows from the iterator
-// and writes results into the callback function.
-func (h *DuckHandler) resultForDefaultIter(ctx *sql.Context, schema sql.Schema, iter sql.RowIter, callback func(*Result) error, resultFields []pgproto3.FieldDescription) (r *Result, processedAtLeastOneBatch bool, returnErr error) {
+// resultForDefaultIter streams the rows from the iterator to the callback function as they are read, in batches of
+// about resultFlushSize bytes, the last of which holds the remaining rows. The batch is reused by the calls, so the
+// callback must not retain its rows. It returns whether any row is streamed, and otherwise the result to pass to the
+// callback, which is either empty or an OK result.
+func (h *DuckHandler) resultForDefaultIter(ctx *sql.Context, schema sql.Schema, iter sql.RowIter, callback func(*Result) error, resultFields []pgproto3.FieldDescription) (r *Result, streamed bool, returnErr error) {
 	defer trace.StartRegion(ctx, "DuckHandler.resultForDefaultIter").End()
 
 	eg, ctx := ctx.NewErrgroup()
@@ -778,33 +804,41 @@ func (h *DuckHandler) resultForDefaultIter(ctx *sql.Context, schema sql.Schema,
 	defer timer.Stop()
 
 	// reads rows from the channel, converts them to wire format,
-	// and calls |callback| to give them to vitess.
+	// and calls |callback| to send them to the client in batches.
 	eg.Go(func() error {
 		defer pan2err()
 		// defer cancelF()
 		defer wg.Done()
-		for {
-			if r == nil {
-				r = &Result{Fields: resultFields}
+		r = &Result{Fields: resultFields}
+		batch := &Result{Fields: resultFields}
+		batchSize := 0
+		var sent uint64
+		sendBatch := func() error {
+			if len(batch.Rows) == 0 {
+				return nil
 			}
-			if r.RowsAffected == rowsBatch {
-				if err := callback(r); err != nil {
-					return err
-				}
-				r = nil
-				processedAtLeastOneBatch = true
-				continue
+			sent += uint64(len(batch.Rows))
+			// The number of the rows streamed so far, which is the count of the command tag of INSERT ... RETURNING.
+			batch.RowsAffected = sent
+			if err := callback(batch); err != nil {
+				return err
 			}
-
+			clear(batch.Rows)
+			batch.Rows = batch.Rows[:0]
+			batchSize = 0
+			streamed = true
+			return nil
+		}
+		for {
 			select {
 			case <-ctx.Done():
 				return nil
 			case row, ok := <-rowChan:
 				if !ok {
-					return nil
+					return sendBatch()
 				}
 				if types.IsOkResult(row) {
-					if len(r.Rows) > 0 {
+					if streamed || len(batch.Rows) > 0 {
 						panic("Got OkResult mixed with RowResult")
 					}
 					result := row[0].(types.OkResult)
@@ -820,8 +854,16 @@ func (h *DuckHandler) resultForDefaultIter(ctx *sql.Context, schema sql.Schema,
 				}
 
 				ctx.GetLogger().Tracef("spooling result row %+v", outputRow)
-				r.Rows = append(r.Rows, Row{outputRow})
-				r.RowsAffected++
+				batch.Rows = append(batch.Rows, Row{outputRow})
+				for _, v := range outputRow {
+					batchSize += len(v) + 4
+				}
+				// A batch is passed to the callback once it holds as many bytes as it flushes to the client.
+				if batchSize >= resultFlushSize {
+					if err := sendBatch(); err != nil {
+						return err
+					}
+				}
 			case <-timer.C:
 				if h.readTimeout != 0 {
 					// Cancel and return so Vitess can call the CloseConnection callback
@@ -0,0 +1,1010 @@
+package pgserver
+
+import (
+	"context"
+	stdsql "database/sql"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"regexp"
+	"slices"
+	"sort"
+	"strings"
+
+	"github.com/apecloud/myduckserver/adapter"
+	"github.com/apecloud/myduckserver/catalog"
+	"github.com/apecloud/myduckserver/pgtypes"
+	"github.com/dolthub/go-mysql-server/sql"
+	"github.com/sirupsen/logrus"
+)
+
+// This file handles the statements of the foreign servers and the foreign tables, which map the external databases
+// to the scanners of DuckDB, so that their live tables may be joined with the local ones:
+//
+//	CREATE SERVER [IF NOT EXISTS] name FOREIGN DATA WRAPPER wrapper [OPTIONS (option 'value', ...)];
+//	DROP SERVER [IF EXISTS] name, ... [CASCADE | RESTRICT];
+//	CREATE FOREIGN TABLE [IF NOT EXISTS] table ([column type [OPTIONS (column_name 'remote')], ...])
+//	    SERVER name [OPTIONS (option 'value', ...)];
+//	DROP FOREIGN TABLE [IF EXISTS] table, ... [CASCADE | RESTRICT];
+//	IMPORT FOREIGN SCHEMA remote [LIMIT TO (table, ...) | EXCEPT (table, ...)] FROM SERVER name INTO schema;
+//
+// A server is attached to DuckDB under its name, with the TYPE of its wrapper (see foreignDataWrappers), and is
+// attached again when MyDuck Server starts. Since the attachment is shared by all the connections, there are no user
+// mappings, and the user and the password of the remote database are options of the server, which are stored apart
+// from the others in __sys__.user_mapping, which only the superusers may read (see checkTableAccess), so that they are
+// not shown by the pg_foreign_server view. The internal tables of the servers may only be written by the superusers. A
+// foreign table is a view of the remote table, whose columns are those of the remote table, or those listed by the
+// statement, whose types are not enforced. The tables of a server may also be queried as server.schema.table.
+//
+// The servers and the foreign tables are stored in the __sys__.foreign_server and __sys__.foreign_table tables of the
+// default catalog, and the foreign tables may only be created in the default database.
+
+// ForeignDataAction represents the type of foreign data statement.
+type ForeignDataAction string
+
+const (
+	ForeignServerCreate ForeignDataAction = "CREATE SERVER"
+	ForeignServerDrop   ForeignDataAction = "DROP SERVER"
+	ForeignTableCreate  ForeignDataAction = "CREATE FOREIGN TABLE"
+	ForeignTableDrop    ForeignDataAction = "DROP FOREIGN TABLE"
+	ForeignSchemaImport ForeignDataAction = "IMPORT FOREIGN SCHEMA"
+)
+
+// ForeignDataConfig represents a parsed foreign data statement.
+type ForeignDataConfig struct {
+	Action      ForeignDataAction
+	IfExists    bool
+	IfNotExists bool
+	Cascade     bool
+	// Servers are the servers of CREATE SERVER and DROP SERVER, of which only DROP SERVER may have several.
+	Servers []string
+	Wrapper string
+	// Server is the server of CREATE FOREIGN TABLE and IMPORT FOREIGN SCHEMA.
+	Server string
+	// Tables are the foreign tables of CREATE FOREIGN TABLE and DROP FOREIGN TABLE, of which only DROP FOREIGN TABLE
+	// may have several.
+	Tables []ForeignTableName
+	// Columns are the columns of CREATE FOREIGN TABLE, or nil for all the columns of the remote table.
+	Columns []ForeignColumn
+	// Options are the options of the server or of the foreign table.
+	Options map[string]string
+	// RemoteSchema, LimitTo, Except, and LocalSchema are the clauses of IMPORT FOREIGN SCHEMA.
+	RemoteSchema string
+	LimitTo      []string
+	Except       []string
+	LocalSchema  string
+}
+
+// ForeignTableName is the name of a foreign table, whose schema is empty if it is not qualified.
+type ForeignTableName struct {
+	Schema string
+	Name   string
+}
+
+// ForeignColumn is a column of a foreign table, whose RemoteName is empty if it has the same name as the column of
+// the remote table.
+type ForeignColumn struct {
+	Name       string
+	RemoteName string
+}
+
+// foreignDataStatementRegex matches the statements that may be foreign data statements, which are then tokenized.
+var foreignDataStatementRegex = regexp.MustCompile(`(?is)^((CREATE|DROP)\s+(SERVER|FOREIGN\s+TABLE)|IMPORT\s+FOREIGN\s+SCHEMA)\s`)
+
+// parseForeignDataSQL parses the given SQL statement and returns a ForeignDataConfig, or nil if it is not a foreign
+// data statement.
+func parseForeignDataSQL(query string) (*ForeignDataConfig, error) {
+	query = strings.TrimSpace(sql.RemoveSpaceAndDelimiter(RemoveComments(query), ';'))
+	if !foreignDataStatementRegex.MatchString(query) {
+		return nil, nil
+	}
+	tokens, err := tokenizeRoleSQL(query)
+	if err != nil {
+		return nil, err
+	}
+	p := &roleParser{tokens: tokens}
+	var config *ForeignDataConfig
+	switch {
+	case p.accept("create", "server"):
+		config, err = p.parseCreateServer()
+	case p.accept("drop", "server"):
+		config = &ForeignDataConfig{Action: ForeignServerDrop, IfExists: p.accept("if", "exists")}
+		if config.Servers, err = p.names(); err == nil {
+			config.Cascade = p.dropBehavior()
+		}
+	case p.accept("create", "foreign", "table"):
+		config, err = p.parseCreateForeignTable()
+	case p.accept("drop", "foreign", "table"):
+		config = &ForeignDataConfig{Action: ForeignTableDrop, IfExists: p.accept("if", "exists")}
+		for err == nil {
+			var table ForeignTableName
+			if table, err = p.foreignTableName(); err != nil {
+				break
+			}
+			config.Tables = append(config.Tables, table)
+			if !p.acceptPunctuation(",") {
+				config.Cascade = p.dropBehavior()
+				break
+			}
+		}
+	case p.accept("import", "foreign", "schema"):
+		config, err = p.parseImportForeignSchema()
+	}
+	if err != nil {
+		return nil, err
+	}
+	if !p.done() {
+		return nil, p.syntaxError()
+	}
+	return config, nil
+}
+
+func (p *roleParser) parseCreateServer() (*ForeignDataConfig, error) {
+	config := &ForeignDataConfig{Action: ForeignServerCreate, IfNotExists: p.accept("if", "not", "exists")}
+	name, err := p.name()
+	if err != nil {
+		return nil, err
+	}
+	config.Servers = []string{name}
+	// The type and the version of the server are informational.
+	for _, clause := range []string{"type", "version"} {
+		if p.accept(clause) {
+			if _, err := p.value(); err != nil {
+				return nil, err
+			}
+		}
+	}
+	if err := p.expect("foreign", "data", "wrapper"); err != nil {
+		return nil, err
+	}
+	if config.Wrapper, err = p.name(); err != nil {
+		return nil, err
+	}
+	config.Options, err = p.foreignOptions()
+	return config, err
+}
+
+func (p *roleParser) parseCreateForeignTable() (*ForeignDataConfig, error) {
+	config := &ForeignDataConfig{Action: ForeignTableCreate, IfNotExists: p.accept("if", "not", "exists")}
+	table, err := p.foreignTableName()
+	if err != nil {
+		return nil, err
+	}
+	config.Tables = []ForeignTableName{table}
+	if !p.acceptPunctuation("(") {
+		return nil, p.syntaxError()
+	}
+	for !p.acceptPunctuation(")") {
+		if len(config.Columns) > 0 && !p.acceptPunctuation(",") {
+			return nil, p.syntaxError()
+		}
+		column, err := p.foreignColumn()
+		if err != nil {
+			return nil, err
+		}
+		config.Columns = append(config.Columns, column)
+	}
+	if err := p.expect("server"); err != nil {
+		return nil, err
+	}
+	if config.Server, err = p.name(); err != nil {
+		return nil, err
+	}
+	config.Options, err = p.foreignOptions()
+	return config, err
+}
+
+// foreignColumn consumes a column definition of a foreign table. Its type and constraints are skipped, up to the
+// comma or the parenthesis that ends it, except for the column_name option.
+func (p *roleParser) foreignColumn() (ForeignColumn, error) {
+	var column ForeignColumn
+	name, err := p.name()
+	if err != nil {
+		return column, err
+	}
+	column.Name = name
+	for depth := 0; ; {
+		if p.done() {
+			return column, p.syntaxError()
+		}
+		token := p.tokens[p.pos]
+		switch {
+		case depth == 0 && token.kind == roleTokenPunctuation && (token.text == "," || token.text == ")"):
+			return column, nil
+		case depth == 0 && token.kind == roleTokenWord && token.text == "options":
+			options, err := p.foreignOptions()
+			if err != nil {
+				return column, err
+			}
+			for option, value := range options {
+				if option != "column_name" {
+					return column, fmt.Errorf("invalid option \"%s\"", option)
+				}
+				column.RemoteName = value
+			}
+			continue
+		case token.kind == roleTokenPunctuation && token.text == "(":
+			depth++
+		case token.kind == roleTokenPunctuation && token.text == ")":
+			depth--
+		}
+		p.pos++
+	}
+}
+
+func (p *roleParser) parseImportForeignSchema() (*ForeignDataConfig, error) {
+	config := &ForeignDataConfig{Action: ForeignSchemaImport}
+	var err error
+	if config.RemoteSchema, err = p.name(); err != nil {
+		return nil, err
+	}
+	switch {
+	case p.accept("limit", "to"):
+		config.LimitTo, err = p.parenthesizedNames()
+	case p.accept("except"):
+		config.Except, err = p.parenthesizedNames()
+	}
+	if err != nil {
+		return nil, err
+	}
+	if err := p.expect("from", "server"); err != nil {
+		return nil, err
+	}
+	if config.Server, err = p.name(); err != nil {
+		return nil, err
+	}
+	if err := p.expect("into"); err != nil {
+		return nil, err
+	}
+	config.LocalSchema, err = p.name()
+	return config, err
+}
+
+// dropBehavior consumes CASCADE or RESTRICT, and returns whether it is CASCADE.
+func (p *roleParser) dropBehavior() bool {
+	if p.accept("cascade") {
+		return true
+	}
+	_ = p.accept("restrict")
+	return false
+}
+
+// foreignTableName consumes the name of a foreign table, which may be qualified by its schema.
+func (p *roleParser) foreignTableName() (ForeignTableName, error) {
+	var table ForeignTableName
+	name, err := p.name()
+	if err != nil {
+		return table, err
+	}
+	if p.acceptPunctuation(".") {
+		table.Schema = name
+		if name, err = p.name(); err != nil {
+			return table, err
+		}
+	}
+	table.Name = name
+	return table, nil
+}
+
+// parenthesizedNames consumes a parenthesized, comma-separated list of names.
+func (p *roleParser) parenthesizedNames() ([]string, error) {
+	if !p.acceptPunctuation("(") {
+		return nil, p.syntaxError()
+	}
+	names, err := p.names()
+	if err != nil {
+		return nil, err
+	}
+	if !p.acceptPunctuation(")") {
+		return nil, p.syntaxError()
+	}
+	return names, nil
+}
+
+// foreignOptions consumes the options of a server, a foreign table, or a column, as OPTIONS (name 'value', ...),
+// if they are next.
+func (p *roleParser) foreignOptions() (map[string]string, error) {
+	if !p.accept("options") {
+		return nil, nil
+	}
+	if !p.acceptPunctuation("(") {
+		return nil, p.syntaxError()
+	}
+	options := make(map[string]string)
+	for {
+		name, err := p.name()
+		if err != nil {
+			return nil, err
+		}
+		if p.done() || p.tokens[p.pos].kind != roleTokenString {
+			return nil, p.syntaxError()
+		}
+		if _, ok := options[name]; ok {
+			return nil, fmt.Errorf("option \"%s\" provided more than once", name)
+		}
+		options[name] = p.tokens[p.pos].text
+		p.pos++
+		if p.acceptPunctuation(")") {
+			return options, nil
+		}
+		if !p.acceptPunctuation(",") {
+			return nil, p.syntaxError()
+		}
+	}
+}
+
+// foreignDataWrapper is a foreign-data wrapper, which is implemented by a scanner of DuckDB.
+type foreignDataWrapper struct {
+	// duckType is the TYPE of the ATTACH statement, or empty for a DuckDB database.
+	duckType string
+	// serverOptions are the options of the servers, besides updatable.
+	serverOptions []string
+	// schemaOption is the option of the foreign tables that names the remote schema, and defaultSchema is the
+	// schema if it is not given.
+	schemaOption  string
+	defaultSchema string
+}
+
+// foreignDataWrappers are the supported foreign-data wrappers by their names, whose options are those of the
+// Postgres extensions of the same names.
+var foreignDataWrappers = map[string]foreignDataWrapper{
+	"postgres_fdw": {
+		duckType:      "POSTGRES",
+		serverOptions: []string{"host", "port", "dbname", "user", "password"},
+		schemaOption:  "schema_name",
+		defaultSchema: "public",
+	},
+	"mysql_fdw": {
+		// The schemas of a MySQL database attached to DuckDB are the MySQL databases.
+		duckType:      "MYSQL",
+		serverOptions: []string{"host", "port", "database", "user", "password"},
+		schemaOption:  "dbname",
+	},
+	"sqlite_fdw": {
+		duckType:      "SQLITE",
+		serverOptions: []string{"database"},
+		defaultSchema: "main",
+	},
+	"duckdb_fdw": {
+		serverOptions: []string{"database"},
+		schemaOption:  "schema_name",
+		defaultSchema: "main",
+	},
+}
+
+// lookupForeignDataWrapper returns the foreign-data wrapper with the name.
+func lookupForeignDataWrapper(name string) (*foreignDataWrapper, error) {
+	w, ok := foreignDataWrappers[name]
+	if !ok {
+		return nil, fmt.Errorf("foreign-data wrapper \"%s\" does not exist", name)
+	}
+	return &w, nil
+}
+
+// checkServerOptions checks the options of a server.
+func (w *foreignDataWrapper) checkServerOptions(options map[string]string) error {
+	for option, value := range options {
+		switch {
+		case option == "updatable":
+			if _, err := parseForeignBool(option, value); err != nil {
+				return err
+			}
+		case !slices.Contains(w.serverOptions, option):
+			return fmt.Errorf("invalid option \"%s\"", option)
+		}
+	}
+	if w.duckType == "SQLITE" || w.duckType == "" {
+		if options["database"] == "" {
+			return fmt.Errorf("option \"database\" is required")
+		}
+	}
+	return nil
+}
+
+// checkTableOptions checks the options of a foreign table.
+func (w *foreignDataWrapper) checkTableOptions(options map[string]string) error {
+	for option := range options {
+		if option != "table_name" && (w.schemaOption == "" || option != w.schemaOption) {
+			return fmt.Errorf("invalid option \"%s\"", option)
+		}
+	}
+	return nil
+}
+
+// remoteSchema returns the remote schema of a foreign table.
+func (w *foreignDataWrapper) remoteSchema(server *foreignServerInfo, options map[string]string) (string, error) {
+	if schema := options[w.schemaOption]; w.schemaOption != "" && schema != "" {
+		return schema, nil
+	}
+	if w.duckType == "MYSQL" {
+		if database := server.options["database"]; database != "" {
+			return database, nil
+		}
+		return "", fmt.Errorf("option \"dbname\" is required for the foreign tables of server \"%s\"", server.name)
+	}
+	return w.defaultSchema, nil
+}
+
+// attachSQL returns the statement that attaches the server to DuckDB.
+func (w *foreignDataWrapper) attachSQL(server string, options map[string]string, ifNotExists bool) (string, error) {
+	var dsn string
+	if w.duckType == "POSTGRES" || w.duckType == "MYSQL" {
+		var pairs []string
+		for _, option := range w.serverOptions {
+			if value, ok := options[option]; ok {
+				pairs = append(pairs, option+"="+quoteConnectionValue(value))
+			}
+		}
+		dsn = strings.Join(pairs, " ")
+	} else {
+		dsn = options["database"]
+	}
+	var attachOptions []string
+	if w.duckType != "" {
+		attachOptions = append(attachOptions, "TYPE "+w.duckType)
+	}
+	if value, ok := options["updatable"]; ok {
+		updatable, err := parseForeignBool("updatable", value)
+		if err != nil {
+			return "", err
+		}
+		if !updatable {
+			attachOptions = append(attachOptions, "READ_ONLY")
+		}
+	}
+	var b strings.Builder
+	b.WriteString("ATTACH ")
+	if ifNotExists {
+		b.WriteString("IF NOT EXISTS ")
+	}
+	b.WriteString(quoteString(dsn))
+	b.WriteString(" AS ")
+	b.WriteString(catalog.QuoteIdentifierANSI(server))
+	if len(attachOptions) > 0 {
+		b.WriteString(" (" + strings.Join(attachOptions, ", ") + ")")
+	}
+	return b.String(), nil
+}
+
+// quoteConnectionValue quotes a value of a libpq-style connection string if it is empty or has spaces, quotes, or
+// backslashes.
+func quoteConnectionValue(value string) string {
+	if value != "" && !strings.ContainsAny(value, " '\\\t") {
+		return value
+	}
+	return "'" + strings.NewReplacer(`\`, `\\`, `'`, `\'`).Replace(value) + "'"
+}
+
+func parseForeignBool(option, value string) (bool, error) {
+	switch strings.ToLower(value) {
+	case "true", "on", "yes", "1":
+		return true, nil
+	case "false", "off", "no", "0":
+		return false, nil
+	}
+	return false, fmt.Errorf("%s requires a Boolean value", option)
+}
+
+// encodeForeignOptions returns the options as a JSON array of name=value, sorted by name, as the srvoptions and
+// ftoptions columns are in Postgres.
+func encodeForeignOptions(options map[string]string) string {
+	list := make([]string, 0, len(options))
+	for option, value := range options {
+		list = append(list, option+"="+value)
+	}
+	sort.Strings(list)
+	encoded, _ := json.Marshal(list)
+	return string(encoded)
+}
+
+func decodeForeignOptions(encoded string) (map[string]string, error) {
+	var list []string
+	if err := json.Unmarshal([]byte(encoded), &list); err != nil {
+		return nil, err
+	}
+	options := make(map[string]string, len(list))
+	for _, option := range list {
+		name, value, _ := strings.Cut(option, "=")
+		options[name] = value
+	}
+	return options, nil
+}
+
+// foreignCredentialOptions are the options of the servers that are stored in user_mapping rather than in
+// foreign_server.
+var foreignCredentialOptions = []string{"user", "password"}
+
+// splitForeignCredentials splits the options of a server into those stored in foreign_server and its credentials.
+func splitForeignCredentials(options map[string]string) (map[string]string, map[string]string) {
+	server, credentials := make(map[string]string), make(map[string]string)
+	for option, value := range options {
+		if slices.Contains(foreignCredentialOptions, option) {
+			credentials[option] = value
+		} else {
+			server[option] = value
+		}
+	}
+	return server, credentials
+}
+
+// foreignDataStore accesses the foreign server and foreign table tables in the default catalog.
+type foreignDataStore struct {
+	q       roleQuerier
+	storage *stdsql.DB
+	catalog string
+}
+
+func newForeignDataStore(provider *catalog.DatabaseProvider, q roleQuerier) *foreignDataStore {
+	return &foreignDataStore{q: q, storage: provider.Storage(), catalog: provider.DefaultCatalogName()}
+}
+
+// table returns the qualified name of the foreign server or foreign table table.
+func (s *foreignDataStore) table(t catalog.InternalTable) string {
+	return s.catalog + "." + t.QualifiedName()
+}
+
+// serversQuery returns the query of the columns of the servers, followed by their options as a JSON array, which
+// include their credentials in user_mapping.
+func (s *foreignDataStore) serversQuery(columns string) string {
+	return "SELECT " + columns + ", to_json(list_concat(s.srvoptions, coalesce(u.umoptions, []::TEXT[])))::TEXT FROM " +
+		s.table(catalog.InternalTables.ForeignServer) + " s LEFT JOIN " + s.table(catalog.InternalTables.UserMapping) +
+		" u ON u.srvname = s.srvname"
+}
+
+// foreignServerInfo is a server as stored in foreign_server and user_mapping.
+type foreignServerInfo struct {
+	oid     int64
+	name    string
+	owner   int64
+	wrapper string
+	options map[string]string
+}
+
+// lookupServer returns the server with the name, or nil if there is no such server.
+func (s *foreignDataStore) lookupServer(name string) (*foreignServerInfo, error) {
+	server := &foreignServerInfo{}
+	var options string
+	err := s.q.QueryRowContext(context.Background(),
+		s.serversQuery("s.oid, s.srvname, s.srvowner, s.srvfdw")+" WHERE s.srvname = ?",
+		name,
+	).Scan(&server.oid, &server.name, &server.owner, &server.wrapper, &options)
+	if errors.Is(err, stdsql.ErrNoRows) {
+		return nil, nil
+	} else if err != nil {
+		return nil, err
+	}
+	if server.options, err = decodeForeignOptions(options); err != nil {
+		return nil, err
+	}
+	return server, nil
+}
+
+// mustLookupServer returns the server with the name, or an error if there is no such server.
+func (s *foreignDataStore) mustLookupServer(name string) (*foreignServerInfo, error) {
+	server, err := s.lookupServer(name)
+	if err == nil && server == nil {
+		err = fmt.Errorf("server \"%s\" does not exist", name)
+	}
+	return server, err
+}
+
+// lookupTable returns the server and the owner of the foreign table, or an empty server if there is no such table.
+func (s *foreignDataStore) lookupTable(table ForeignTableName) (server string, owner int64, err error) {
+	err = s.q.QueryRowContext(context.Background(),
+		"SELECT srvname, ftowner FROM "+s.table(catalog.InternalTables.ForeignTable)+" WHERE schemaname = ? AND tablename = ?",
+		table.Schema, table.Name,
+	).Scan(&server, &owner)
+	if errors.Is(err, stdsql.ErrNoRows) {
+		return "", 0, nil
+	}
+	return server, owner, err
+}
+
+// serverTables returns the foreign tables of the server.
+func (s *foreignDataStore) serverTables(server string) ([]ForeignTableName, error) {
+	rows, err := s.q.QueryContext(context.Background(),
+		"SELECT schemaname, tablename FROM "+s.table(catalog.InternalTables.ForeignTable)+" WHERE srvname = ? ORDER BY 1, 2",
+		server,
+	)
+	if err != nil {
+		return nil, err
+	}
+	defer rows.Close()
+	var tables []ForeignTableName
+	for rows.Next() {
+		var table ForeignTableName
+		if err := rows.Scan(&table.Schema, &table.Name); err != nil {
+			return nil, err
+		}
+		tables = append(tables, table)
+	}
+	return tables, rows.Err()
+}
+
+// dropTable drops the view of the foreign table, and removes it from foreign_table.
+func (s *foreignDataStore) dropTable(table ForeignTableName) error {
+	if _, err := s.q.ExecContext(context.Background(),
+		"DROP VIEW IF EXISTS "+catalog.ConnectIdentifiersANSI(s.catalog, table.Schema, table.Name),
+	); err != nil {
+		return err
+	}
+	_, err := s.q.ExecContext(context.Background(),
+		"DELETE FROM "+s.table(catalog.InternalTables.ForeignTable)+" WHERE schemaname = ? AND tablename = ?",
+		table.Schema, table.Name,
+	)
+	return err
+}
+
+// detach detaches the server from DuckDB, outside the transaction of the statement.
+func (s *foreignDataStore) detach(server string) error {
+	_, err := s.storage.ExecContext(context.Background(), "DETACH DATABASE IF EXISTS "+catalog.QuoteIdentifierANSI(server))
+	return err
+}
+
+// executeForeignDataStatement executes a foreign data statement in a transaction of the storage.
+func (h *ConnectionHandler) executeForeignDataStatement(statement ConvertedStatement) error {
+	config := statement.ForeignDataConfig
+	provider, err := h.catalogProvider()
+	if err != nil {
+		return err
+	}
+	ctx, err := h.duckHandler.NewContext(context.Background(), h.mysqlConn, statement.String)
+	if err != nil {
+		return err
+	}
+	switch config.Action {
+	case ForeignTableCreate, ForeignTableDrop, ForeignSchemaImport:
+		if adapter.GetCurrentCatalog(ctx) != provider.DefaultCatalogName() {
+			return fmt.Errorf("foreign tables are only supported in the default database")
+		}
+	}
+	tx, err := provider.Storage().BeginTx(context.Background(), nil)
+	if err != nil {
+		return err
+	}
+	defer tx.Rollback()
+	store := newForeignDataStore(provider, tx)
+	current, err := newRoleStore(provider, tx).lookup(h.mysqlConn.User)
+	if err != nil {
+		return err
+	}
+
+	var attached, detached []string
+	switch config.Action {
+	case ForeignServerCreate:
+		attached, err = h.createForeignServer(store, current, config)
+	case ForeignServerDrop:
+		detached, err = h.dropForeignServers(store, current, config)
+	case ForeignTableCreate:
+		err = h.createForeignTable(ctx, store, current, config)
+	case ForeignTableDrop:
+		err = h.dropForeignTables(ctx, store, current, config)
+	case ForeignSchemaImport:
+		err = h.importForeignSchema(store, current, config)
+	default:
+		err = fmt.Errorf("unsupported foreign data statement: %s", statement.String)
+	}
+	if err == nil {
+		err = tx.Commit()
+	}
+	if err != nil {
+		for _, server := range attached {
+			if err := store.detach(server); err != nil {
+				h.logger.Warnf("failed to detach server %s: %v", server, err)
+			}
+		}
+		return err
+	}
+	for _, server := range detached {
+		if err := store.detach(server); err != nil {
+			return err
+		}
+	}
+	return h.send(makeCommandComplete(string(config.Action), 0))
+}
+
+// checkForeignServerUsage rejects the foreign tables of a server by a role other than its owner or a superuser.
+func checkForeignServerUsage(current *roleInfo, server *foreignServerInfo) error {
+	if !current.isSuperuser() && current.oidOrZero() != server.owner {
+		return fmt.Errorf("permission denied for foreign server %s", server.name)
+	}
+	return nil
+}
+
+func (h *ConnectionHandler) createForeignServer(store *foreignDataStore, current *roleInfo, config *ForeignDataConfig) ([]string, error) {
+	name := config.Servers[0]
+	wrapper, err := lookupForeignDataWrapper(config.Wrapper)
+	if err != nil {
+		return nil, err
+	}
+	if !current.isSuperuser() {
+		return nil, fmt.Errorf("permission denied for foreign-data wrapper %s", config.Wrapper)
+	}
+	if existing, err := store.lookupServer(name); err != nil {
+		return nil, err
+	} else if existing != nil {
+		if config.IfNotExists {
+			return nil, h.sendNotice(ErrorResponseSeverity_Notice, "00000" /* successful_completion */, fmt.Sprintf("server \"%s\" already exists, skipping", name))
+		}
+		return nil, fmt.Errorf("server \"%s\" already exists", name)
+	}
+	if err := wrapper.checkServerOptions(config.Options); err != nil {
+		return nil, err
+	}
+	attachSQL, err := wrapper.attachSQL(name, config.Options, false)
+	if err != nil {
+		return nil, err
+	}
+	if _, err := store.storage.ExecContext(context.Background(), attachSQL); err != nil {
+		return nil, fmt.Errorf("could not connect to server \"%s\": %w", name, err)
+	}
+
+	options, credentials := splitForeignCredentials(config.Options)
+	table := store.table(catalog.InternalTables.ForeignServer)
+	if _, err := store.q.ExecContext(context.Background(),
+		fmt.Sprintf("INSERT INTO %s VALUES (?, (SELECT greatest(coalesce(max(oid), 0) + 1, %d) FROM %s), ?, ?, ?::JSON::TEXT[])",
+			table, pgtypes.FirstNormalObjectID, table),
+		name, current.oidOrZero(), config.Wrapper, encodeForeignOptions(options),
+	); err != nil {
+		return []string{name}, err
+	}
+	if len(credentials) > 0 {
+		if _, err := store.q.ExecContext(context.Background(),
+			"INSERT INTO "+store.table(catalog.InternalTables.UserMapping)+" VALUES (?, ?::JSON::TEXT[])",
+			name, encodeForeignOptions(credentials),
+		); err != nil {
+			return []string{name}, err
+		}
+	}
+	return []string{name}, nil
+}
+
+func (h *ConnectionHandler) dropForeignServers(store *foreignDataStore, current *roleInfo, config *ForeignDataConfig) ([]string, error) {
+	var detached []string
+	for _, name := range config.Servers {
+		server, err := store.lookupServer(name)
+		if err != nil {
+			return nil, err
+		}
+		if server == nil {
+			if !config.IfExists {
+				return nil, fmt.Errorf("server \"%s\" does not exist", name)
+			}
+			if err := h.sendNotice(ErrorResponseSeverity_Notice, "00000" /* successful_completion */, fmt.Sprintf("server \"%s\" does not exist, skipping", name)); err != nil {
+				return nil, err
+			}
+			continue
+		}
+		if !current.isSuperuser() && current.oidOrZero() != server.owner {
+			return nil, fmt.Errorf("must be owner of foreign server %s", name)
+		}
+		tables, err := store.serverTables(name)
+		if err != nil {
+			return nil, err
+		}
+		if len(tables) > 0 && !config.Cascade {
+			return nil, fmt.Errorf("cannot drop server %s because other objects depend on it", name)
+		}
+		for _, table := range tables {
+			if err := store.dropTable(table); err != nil {
+				return nil, err
+			}
+		}
+		for _, t := range []catalog.InternalTable{catalog.InternalTables.UserMapping, catalog.InternalTables.ForeignServer} {
+			if _, err := store.q.ExecContext(context.Background(), "DELETE FROM "+store.table(t)+" WHERE srvname = ?", name); err != nil {
+				return nil, err
+			}
+		}
+		detached = append(detached, name)
+	}
+	return detached, nil
+}
+
+func (h *ConnectionHandler) createForeignTable(ctx *sql.Context, store *foreignDataStore, current *roleInfo, config *ForeignDataConfig) error {
+	table := config.Tables[0]
+	if table.Schema == "" {
+		table.Schema = adapter.GetCurrentSchema(ctx)
+	}
+	server, err := store.mustLookupServer(config.Server)
+	if err != nil {
+		return err
+	}
+	if err := checkForeignServerUsage(current, server); err != nil {
+		return err
+	}
+	if existing, _, err := store.lookupTable(table); err != nil {
+		return err
+	} else if existing != "" {
+		if config.IfNotExists {
+			return h.sendNotice(ErrorResponseSeverity_Notice, "00000" /* successful_completion */, fmt.Sprintf("relation \"%s\" already exists, skipping", table.Name))
+		}
+		return fmt.Errorf("relation \"%s\" already exists", table.Name)
+	}
+	wrapper, err := lookupForeignDataWrapper(server.wrapper)
+	if err != nil {
+		return err
+	}
+	if err := wrapper.checkTableOptions(config.Options); err != nil {
+		return err
+	}
+	return createForeignTableView(store, current, server, wrapper, table, config.Columns, config.Options)
+}
+
+// createForeignTableView creates the view of a foreign table over the remote table, and adds it to foreign_table.
+func createForeignTableView(store *foreignDataStore, current *roleInfo, server *foreignServerInfo, wrapper *foreignDataWrapper,
+	table ForeignTableName, columns []ForeignColumn, options map[string]string) error {
+	remoteSchema, err := wrapper.remoteSchema(server, options)
+	if err != nil {
+		return err
+	}
+	remoteTable := table.Name
+	if name := options["table_name"]; name != "" {
+		remoteTable = name
+	}
+
+	selectList := "*"
+	if len(columns) > 0 {
+		list := make([]string, len(columns))
+		for i, column := range columns {
+			list[i] = catalog.QuoteIdentifierANSI(column.Name)
+			if column.RemoteName != "" {
+				list[i] = catalog.QuoteIdentifierANSI(column.RemoteName) + " AS " + list[i]
+			}
+		}
+		selectList = strings.Join(list, ", ")
+	}
+	if _, err := store.q.ExecContext(context.Background(),
+		"CREATE VIEW "+catalog.ConnectIdentifiersANSI(store.catalog, table.Schema, table.Name)+
+			" AS SELECT "+selectList+" FROM "+catalog.ConnectIdentifiersANSI(server.name, remoteSchema, remoteTable),
+	); err != nil {
+		return err
+	}
+	_, err = store.q.ExecContext(context.Background(),
+		"INSERT INTO "+store.table(catalog.InternalTables.ForeignTable)+" VALUES (?, ?, ?, ?, ?::JSON::TEXT[])",
+		table.Schema, table.Name, server.name, current.oidOrZero(), encodeForeignOptions(options),
+	)
+	return err
+}
+
+func (h *ConnectionHandler) dropForeignTables(ctx *sql.Context, store *foreignDataStore, current *roleInfo, config *ForeignDataConfig) error {
+	for _, table := range config.Tables {
+		if table.Schema == "" {
+			table.Schema = adapter.GetCurrentSchema(ctx)
+		}
+		server, owner, err := store.lookupTable(table)
+		if err != nil {
+			return err
+		}
+		if server == "" {
+			if !config.IfExists {
+				return fmt.Errorf("foreign table \"%s\" does not exist", table.Name)
+			}
+			if err := h.sendNotice(ErrorResponseSeverity_Notice, "00000" /* successful_completion */, fmt.Sprintf("foreign table \"%s\" does not exist, skipping", table.Name)); err != nil {
+				return err
+			}
+			continue
+		}
+		if !current.isSuperuser() && current.oidOrZero() != owner {
+			return fmt.Errorf("must be owner of foreign table %s", table.Name)
+		}
+		if err := store.dropTable(table); err != nil {
+			return err
+		}
+	}
+	return nil
+}
+
+func (h *ConnectionHandler) importForeignSchema(store *foreignDataStore, current *roleInfo, config *ForeignDataConfig) error {
+	server, err := store.mustLookupServer(config.Server)
+	if err != nil {
+		return err
+	}
+	if err := checkForeignServerUsage(current, server); err != nil {
+		return err
+	}
+	wrapper, err := lookupForeignDataWrapper(server.wrapper)
+	if err != nil {
+		return err
+	}
+	var exists bool
+	if err := store.q.QueryRowContext(context.Background(),
+		"SELECT count(*) > 0 FROM information_schema.schemata WHERE catalog_name = ? AND schema_name = ?",
+		store.catalog, config.LocalSchema,
+	).Scan(&exists); err != nil {
+		return err
+	}
+	if !exists {
+		return fmt.Errorf("schema \"%s\" does not exist", config.LocalSchema)
+	}
+	if err := store.q.QueryRowContext(context.Background(),
+		"SELECT count(*) > 0 FROM information_schema.schemata WHERE catalog_name = ? AND schema_name = ?",
+		server.name, config.RemoteSchema,
+	).Scan(&exists); err != nil {
+		return err
+	}
+	if !exists {
+		return fmt.Errorf("schema \"%s\" is not present on foreign server \"%s\"", config.RemoteSchema, server.name)
+	}
+
+	rows, err := store.q.QueryContext(context.Background(),
+		"SELECT table_name FROM information_schema.tables WHERE table_catalog = ? AND table_schema = ? ORDER BY 1",
+		server.name, config.RemoteSchema,
+	)
+	if err != nil {
+		return err
+	}
+	var names []string
+	for rows.Next() {
+		var name string
+		if err := rows.Scan(&name); err != nil {
+			rows.Close()
+			return err
+		}
+		if (config.LimitTo == nil || slices.Contains(config.LimitTo, name)) && !slices.Contains(config.Except, name) {
+			names = append(names, name)
+		}
+	}
+	rows.Close()
+	if err := rows.Err(); err != nil {
+		return err
+	}
+
+	var options map[string]string
+	if wrapper.schemaOption != "" {
+		options = map[string]string{wrapper.schemaOption: config.RemoteSchema}
+	}
+	for _, name := range names {
+		table := ForeignTableName{Schema: config.LocalSchema, Name: name}
+		if err := createForeignTableView(store, current, server, wrapper, table, nil, options); err != nil {
+			return err
+		}
+	}
+	return nil
+}
+
+// attachForeignServers attaches the stored servers to DuckDB when the server starts. A server that cannot be
+// attached is skipped, and its foreign tables fail until it is created again.
+func (s *Server) attachForeignServers() error {
+	if s.Provider == nil {
+		return nil
+	}
+	store := newForeignDataStore(s.Provider, s.Provider.Storage())
+	rows, err := store.q.QueryContext(context.Background(),
+		store.serversQuery("s.srvname, s.srvfdw")+" ORDER BY s.oid",
+	)
+	if err != nil {
+		return err
+	}
+	var servers []foreignServerInfo
+	for rows.Next() {
+		var server foreignServerInfo
+		var options string
+		if err := rows.Scan(&server.name, &server.wrapper, &options); err != nil {
+			rows.Close()
+			return err
+		}
+		if server.options, err = decodeForeignOptions(options); err != nil {
+			rows.Close()
+			return err
+		}
+		servers = append(servers, server)
+	}
+	rows.Close()
+	if err := rows.Err(); err != nil {
+		return err
+	}
+	for _, server := range servers {
+		wrapper, err := lookupForeignDataWrapper(server.wrapper)
+		if err != nil {
+			return err
+		}
+		attachSQL, err := wrapper.attachSQL(server.name, server.options, true)
+		if err != nil {
+			return err
+		}
+		if _, err := store.storage.ExecContext(context.Background(), attachSQL); err != nil {
+			logrus.WithError(err).Warnf("Failed to attach the foreign server %s", server.name)
+		}
+	}
+	return nil
+}
@@ -0,0 +1,124 @@
+package pgserver
+
+import (
+	"testing"
+
+	"github.com/stretchr/testify/require"
+)
+
+func TestParseForeignDataSQL(t *testing.T) {
+	tests := []struct {
+		query string
+		want  *ForeignDataConfig
+	}{
+		{
+			query: "CREATE SERVER pg FOREIGN DATA WRAPPER postgres_fdw OPTIONS (host 'db.example.com', port '5432', dbname 'shop')",
+			want: &ForeignDataConfig{
+				Action:  ForeignServerCreate,
+				Servers: []string{"pg"},
+				Wrapper: "postgres_fdw",
+				Options: map[string]string{"host": "db.example.com", "port": "5432", "dbname": "shop"},
+			},
+		},
+		{
+			query: `create server if not exists "Lite" type 'file' version '3' foreign data wrapper sqlite_fdw;`,
+			want:  &ForeignDataConfig{Action: ForeignServerCreate, IfNotExists: true, Servers: []string{"Lite"}, Wrapper: "sqlite_fdw"},
+		},
+		{
+			query: "DROP SERVER IF EXISTS pg, lite CASCADE",
+			want:  &ForeignDataConfig{Action: ForeignServerDrop, IfExists: true, Servers: []string{"pg", "lite"}, Cascade: true},
+		},
+		{
+			query: `CREATE FOREIGN TABLE shop.orders (id integer NOT NULL, total numeric(10, 2) OPTIONS (column_name 'amount'), "Note" text)
+				SERVER pg OPTIONS (schema_name 'sales', table_name 'orders_2024')`,
+			want: &ForeignDataConfig{
+				Action:  ForeignTableCreate,
+				Tables:  []ForeignTableName{{Schema: "shop", Name: "orders"}},
+				Columns: []ForeignColumn{{Name: "id"}, {Name: "total", RemoteName: "amount"}, {Name: "Note"}},
+				Server:  "pg",
+				Options: map[string]string{"schema_name": "sales", "table_name": "orders_2024"},
+			},
+		},
+		{
+			query: "CREATE FOREIGN TABLE IF NOT EXISTS items () SERVER pg",
+			want:  &ForeignDataConfig{Action: ForeignTableCreate, IfNotExists: true, Tables: []ForeignTableName{{Name: "items"}}, Server: "pg"},
+		},
+		{
+			query: "DROP FOREIGN TABLE shop.orders, items RESTRICT",
+			want:  &ForeignDataConfig{Action: ForeignTableDrop, Tables: []ForeignTableName{{Schema: "shop", Name: "orders"}, {Name: "items"}}},
+		},
+		{
+			query: "IMPORT FOREIGN SCHEMA public LIMIT TO (orders, items) FROM SERVER pg INTO shop",
+			want: &ForeignDataConfig{
+				Action:       ForeignSchemaImport,
+				RemoteSchema: "public",
+				LimitTo:      []string{"orders", "items"},
+				Server:       "pg",
+				LocalSchema:  "shop",
+			},
+		},
+		{
+			query: "CREATE TABLE server (id int)",
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.query, func(t *testing.T) {
+			config, err := parseForeignDataSQL(tt.query)
+			require.NoError(t, err)
+			require.Equal(t, tt.want, config)
+		})
+	}
+
+	for _, query := range []string{
+		"CREATE SERVER pg FOREIGN DATA postgres_fdw",
+		"CREATE SERVER pg FOREIGN DATA WRAPPER postgres_fdw OPTIONS (host 'a', host 'b')",
+		"CREATE FOREIGN TABLE t (a int OPTIONS (remote 'b')) SERVER pg",
+		"CREATE FOREIGN TABLE t (a int SERVER pg",
+		"IMPORT FOREIGN SCHEMA public FROM SERVER pg",
+	} {
+		_, err := parseForeignDataSQL(query)
+		require.Error(t, err, query)
+	}
+}
+
+func TestForeignDataWrapperAttachSQL(t *testing.T) {
+	tests := []struct {
+		wrapper string
+		options map[string]string
+		want    string
+	}{
+		{
+			wrapper: "postgres_fdw",
+			options: map[string]string{"host": "127.0.0.1", "dbname": "shop", "user": "app", "password": "it's secret"},
+			want:    `ATTACH 'host=127.0.0.1 dbname=shop user=app password=''it\''s secret''' AS "remote" (TYPE POSTGRES)`,
+		},
+		{
+			wrapper: "mysql_fdw",
+			options: map[string]string{"host": "localhost", "port": "3306", "updatable": "false"},
+			want:    `ATTACH 'host=localhost port=3306' AS "remote" (TYPE MYSQL, READ_ONLY)`,
+		},
+		{
+			wrapper: "duckdb_fdw",
+			options: map[string]string{"database": "/data/other.duckdb"},
+			want:    `ATTACH '/data/other.duckdb' AS "remote"`,
+		},
+	}
+	for _, tt := range tests {
+		wrapper, err := lookupForeignDataWrapper(tt.wrapper)
+		require.NoError(t, err)
+		require.NoError(t, wrapper.checkServerOptions(tt.options))
+		got, err := wrapper.attachSQL("remote", tt.options, false)
+		require.NoError(t, err)
+		require.Equal(t, tt.want, got)
+	}
+
+	postgres, err := lookupForeignDataWrapper("postgres_fdw")
+	require.NoError(t, err)
+	require.Error(t, postgres.checkServerOptions(map[string]string{"database": "shop"}))
+	require.Error(t, postgres.checkServerOptions(map[string]string{"updatable": "maybe"}))
+	sqlite, err := lookupForeignDataWrapper("sqlite_fdw")
+	require.NoError(t, err)
+	require.Error(t, sqlite.checkServerOptions(nil))
+	_, err = lookupForeignDataWrapper("oracle_fdw")
+	require.Error(t, err)
+}
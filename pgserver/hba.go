@@ -0,0 +1,251 @@
+package pgserver
+
+import (
+	"bufio"
+	"fmt"
+	"io"
+	"net"
+	"os"
+	"slices"
+	"strings"
+)
+
+// HBAMethod is the authentication method of a host-based authentication rule.
+type HBAMethod string
+
+const (
+	HBAMethodTrust    HBAMethod = "trust"         // accept the connection without a password
+	HBAMethodReject   HBAMethod = "reject"        // reject the connection
+	HBAMethodPassword HBAMethod = "password"      // ask for the password in clear text
+	HBAMethodScram    HBAMethod = "scram-sha-256" // authenticate with SCRAM-SHA-256
+	HBAMethodMD5      HBAMethod = "md5"           // authenticate with SCRAM-SHA-256, as Postgres does for SCRAM passwords
+	HBAMethodPeer     HBAMethod = "peer"          // authenticate by the operating system user, for local connections
+)
+
+// HBARule is a rule of the host-based authentication, as a line of pg_hba.conf:
+//
+//	local      DATABASE  USER  METHOD
+//	host       DATABASE  USER  ADDRESS  METHOD
+//	hostssl    DATABASE  USER  ADDRESS  METHOD
+//	hostnossl  DATABASE  USER  ADDRESS  METHOD
+//
+// A connection is authenticated by the method of the first rule that matches its type, its database, its user, and
+// the address of its client, and is rejected if no rule matches it.
+type HBARule struct {
+	Type      string     // local (a Unix domain socket), host, hostssl, or hostnossl
+	Databases []string   // the database names, or the keywords all, sameuser, and replication
+	Users     []string   // the user names, or the keyword all
+	Address   *net.IPNet // the client addresses, or nil for all addresses and for the local rules
+	Method    HBAMethod
+	Line      int // the line number in the file
+}
+
+// hbaConnection is what the rules are matched against at connection time.
+type hbaConnection struct {
+	local       bool   // over a Unix domain socket
+	ssl         bool   // over SSL
+	ip          net.IP // the client address of a TCP connection
+	database    string
+	user        string
+	replication bool // a physical replication connection, which matches the keyword replication only
+}
+
+// LoadHBAFile parses the rules of the file at |path|, in the format of pg_hba.conf.
+func LoadHBAFile(path string) ([]HBARule, error) {
+	f, err := os.Open(path)
+	if err != nil {
+		return nil, err
+	}
+	defer f.Close()
+	rules, err := parseHBA(f)
+	if err != nil {
+		return nil, fmt.Errorf("%s: %w", path, err)
+	}
+	return rules, nil
+}
+
+// parseHBA parses the rules in the format of pg_hba.conf. Empty lines and comments starting with `#` are skipped.
+func parseHBA(r io.Reader) ([]HBARule, error) {
+	var rules []HBARule
+	scanner := bufio.NewScanner(r)
+	for n := 1; scanner.Scan(); n++ {
+		line := scanner.Text()
+		if i := strings.IndexByte(line, '#'); i >= 0 {
+			line = line[:i]
+		}
+		fields := strings.Fields(line)
+		if len(fields) == 0 {
+			continue
+		}
+		rule, err := parseHBARule(fields)
+		if err != nil {
+			return nil, fmt.Errorf("line %d: %w", n, err)
+		}
+		rule.Line = n
+		rules = append(rules, rule)
+	}
+	return rules, scanner.Err()
+}
+
+func parseHBARule(fields []string) (HBARule, error) {
+	rule := HBARule{Type: fields[0]}
+	want := 5 // type, database, user, address, method
+	switch rule.Type {
+	case "local":
+		want = 4
+	case "host", "hostssl", "hostnossl":
+	default:
+		return rule, fmt.Errorf("invalid connection type %q", rule.Type)
+	}
+	if len(fields) < want {
+		return rule, fmt.Errorf("end-of-line before authentication method")
+	}
+	rule.Databases = splitHBAList(fields[1])
+	rule.Users = splitHBAList(fields[2])
+	if want == 5 {
+		address, masked, err := parseHBAAddress(fields[3], fields[4])
+		if err != nil {
+			return rule, err
+		}
+		if masked {
+			// The mask is a separate field.
+			if want++; len(fields) < want {
+				return rule, fmt.Errorf("end-of-line before authentication method")
+			}
+		}
+		rule.Address = address
+	}
+	if len(fields) > want {
+		return rule, fmt.Errorf("authentication options are not supported: %s", strings.Join(fields[want:], " "))
+	}
+
+	rule.Method = HBAMethod(fields[want-1])
+	switch rule.Method {
+	case HBAMethodTrust, HBAMethodReject, HBAMethodPassword, HBAMethodScram, HBAMethodMD5:
+	case HBAMethodPeer:
+		if rule.Type != "local" {
+			return rule, fmt.Errorf("peer authentication is only supported on local sockets")
+		}
+	default:
+		return rule, fmt.Errorf("invalid authentication method %q", rule.Method)
+	}
+	return rule, nil
+}
+
+// parseHBAAddress parses the address of a rule, which is all, an address range in CIDR notation, a single address,
+// or an address followed by a separate mask, e.g., 192.168.0.0 255.255.0.0, in which case |masked| is true.
+func parseHBAAddress(address, next string) (ipNet *net.IPNet, masked bool, err error) {
+	if address == "all" {
+		return nil, false, nil
+	}
+	if strings.Contains(address, "/") {
+		if _, ipNet, err = net.ParseCIDR(address); err != nil {
+			return nil, false, fmt.Errorf("invalid IP address %q", address)
+		}
+		return ipNet, false, nil
+	}
+	ip := net.ParseIP(address)
+	if ip == nil {
+		return nil, false, fmt.Errorf("invalid IP address %q", address)
+	}
+	if ip4 := ip.To4(); ip4 != nil {
+		ip = ip4
+	}
+	if mask := net.ParseIP(next); mask != nil {
+		if len(ip) == net.IPv4len {
+			mask = mask.To4()
+		}
+		if len(mask) != len(ip) {
+			return nil, false, fmt.Errorf("IP address and mask do not match: %s %s", address, next)
+		}
+		return &net.IPNet{IP: ip.Mask(net.IPMask(mask)), Mask: net.IPMask(mask)}, true, nil
+	}
+	// A single address
+	bits := 8 * len(ip)
+	return &net.IPNet{IP: ip, Mask: net.CIDRMask(bits, bits)}, false, nil
+}
+
+// splitHBAList splits a comma-separated list of names, which may be double-quoted.
+func splitHBAList(field string) []string {
+	names := strings.Split(field, ",")
+	for i, name := range names {
+		if len(name) >= 2 && name[0] == '"' && name[len(name)-1] == '"' {
+			names[i] = name[1 : len(name)-1]
+		}
+	}
+	return names
+}
+
+// matches returns whether the rule applies to the connection.
+func (r *HBARule) matches(c hbaConnection) bool {
+	switch r.Type {
+	case "local":
+		if !c.local {
+			return false
+		}
+	case "host":
+		if c.local {
+			return false
+		}
+	case "hostssl":
+		if c.local || !c.ssl {
+			return false
+		}
+	case "hostnossl":
+		if c.local || c.ssl {
+			return false
+		}
+	}
+	if r.Address != nil && (c.ip == nil || !r.Address.Contains(c.ip)) {
+		return false
+	}
+	if !slices.Contains(r.Users, "all") && !slices.Contains(r.Users, c.user) {
+		return false
+	}
+	if c.replication {
+		return slices.Contains(r.Databases, "replication")
+	}
+	return slices.ContainsFunc(r.Databases, func(database string) bool {
+		switch database {
+		case "all":
+			return true
+		case "sameuser":
+			return c.database == c.user
+		case "replication":
+			return false
+		default:
+			return database == c.database
+		}
+	})
+}
+
+// matchHBARule returns the first of the rules that matches the connection, or nil if none does.
+func matchHBARule(rules []HBARule, c hbaConnection) *HBARule {
+	for i := range rules {
+		if rules[i].matches(c) {
+			return &rules[i]
+		}
+	}
+	return nil
+}
+
+// hbaError returns the message that rejects the connection, by a reject rule or for lacking a matching rule, which
+// are as in Postgres.
+func hbaError(c hbaConnection, rejected bool) string {
+	host := "[local]"
+	if !c.local {
+		host = c.ip.String()
+	}
+	encryption := "no encryption"
+	if c.ssl {
+		encryption = "SSL encryption"
+	}
+	database := fmt.Sprintf(`database "%s"`, c.database)
+	if c.replication {
+		database = "replication connection"
+	}
+	if rejected {
+		return fmt.Sprintf(`pg_hba.conf rejects connection for host "%s", user "%s", %s, %s`, host, c.user, database, encryption)
+	}
+	return fmt.Sprintf(`no pg_hba.conf entry for host "%s", user "%s", %s, %s`, host, c.user, database, encryption)
+}
@@ -0,0 +1,84 @@
+package pgserver
+
+import (
+	"net"
+	"strings"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+)
+
+func TestParseHBA(t *testing.T) {
+	rules, err := parseHBA(strings.NewReader(`
+# TYPE  DATABASE        USER            ADDRESS                 METHOD
+local   all             postgres                                peer
+host    sales,"Mixed"   alice,bob       192.168.1.0/24          scram-sha-256   # office
+host    all             all             10.0.0.0  255.0.0.0     password
+hostssl all             all             ::1                     md5
+host    replication     all             all                     trust
+`))
+	require.NoError(t, err)
+	require.Len(t, rules, 5)
+	require.Equal(t, HBARule{Type: "local", Databases: []string{"all"}, Users: []string{"postgres"}, Method: HBAMethodPeer, Line: 3}, rules[0])
+	require.Equal(t, []string{"sales", "Mixed"}, rules[1].Databases)
+	require.Equal(t, []string{"alice", "bob"}, rules[1].Users)
+	require.Equal(t, "192.168.1.0/24", rules[1].Address.String())
+	require.Equal(t, "10.0.0.0/8", rules[2].Address.String())
+	require.Equal(t, HBAMethodPassword, rules[2].Method)
+	require.Equal(t, "::1/128", rules[3].Address.String())
+	require.Nil(t, rules[4].Address)
+
+	for _, line := range []string{
+		"hosts all all all trust",
+		"host all all 10.0.0.0/8",
+		"host all all example.com trust",
+		"host all all 10.0.0.0/8 ldap",
+		"host all all 10.0.0.0/8 peer",
+		"host all all 10.0.0.0/8 password clientcert=verify-ca",
+		"local all all",
+	} {
+		_, err := parseHBA(strings.NewReader(line))
+		require.Error(t, err, line)
+	}
+}
+
+func TestMatchHBARule(t *testing.T) {
+	rules, err := parseHBA(strings.NewReader(`
+local     all          all                     trust
+hostssl   all          all     0.0.0.0/0       scram-sha-256
+host      sameuser     all     127.0.0.1/32    password
+host      sales        alice   192.168.0.0/16  trust
+host      replication  all     all             scram-sha-256
+host      all          all     192.168.0.0/16  reject
+`))
+	require.NoError(t, err)
+
+	office := net.ParseIP("192.168.1.2")
+	tests := []struct {
+		conn hbaConnection
+		line int // 0 if no rule matches
+	}{
+		{conn: hbaConnection{local: true, database: "sales", user: "bob"}, line: 2},
+		{conn: hbaConnection{ssl: true, ip: office, database: "sales", user: "bob"}, line: 3},
+		{conn: hbaConnection{ip: net.ParseIP("127.0.0.1"), database: "bob", user: "bob"}, line: 4},
+		{conn: hbaConnection{ip: net.ParseIP("127.0.0.1"), database: "sales", user: "bob"}},
+		{conn: hbaConnection{ip: office, database: "sales", user: "alice"}, line: 5},
+		{conn: hbaConnection{ip: office, database: "sales", user: "bob"}, line: 7},
+		{conn: hbaConnection{ip: office, database: "sales", user: "alice", replication: true}, line: 6},
+		{conn: hbaConnection{ip: net.ParseIP("::1"), database: "sales", user: "alice"}},
+	}
+	for _, tt := range tests {
+		rule := matchHBARule(rules, tt.conn)
+		if tt.line == 0 {
+			require.Nil(t, rule, "%+v", tt.conn)
+		} else {
+			require.NotNil(t, rule, "%+v", tt.conn)
+			require.Equal(t, tt.line, rule.Line, "%+v", tt.conn)
+		}
+	}
+
+	require.Equal(t, `no pg_hba.conf entry for host "::1", user "alice", database "sales", no encryption`,
+		hbaError(hbaConnection{ip: net.ParseIP("::1"), database: "sales", user: "alice"}, false))
+	require.Equal(t, `pg_hba.conf rejects connection for host "[local]", user "bob", replication connection, SSL encryption`,
+		hbaError(hbaConnection{local: true, ssl: true, user: "bob", replication: true}, true))
+}
@@ -6,10 +6,13 @@ import (
 	"errors"
 	"fmt"
 	"regexp"
+	"strconv"
 	"strings"
+	"time"
 
 	"github.com/apecloud/myduckserver/adapter"
 	"github.com/apecloud/myduckserver/catalog"
+	"github.com/apecloud/myduckserver/pgserver/logrepl"
 	"github.com/apecloud/myduckserver/pgserver/pgconfig"
 	"github.com/cockroachdb/cockroachdb-parser/pkg/sql/sem/tree"
 	"github.com/dolthub/go-mysql-server/sql"
@@ -19,11 +22,24 @@ import (
 // precompile a regex to match "select pg_catalog.pg_is_in_recovery();"
 var pgIsInRecoveryRegex = regexp.MustCompile(`(?i)^\s*select\s+pg_catalog\.pg_is_in_recovery\(\s*\)\s*;?\s*$`)
 
-// precompile a regex to match "select pg_catalog.pg_current_wal_lsn();" or "select pg_catalog.pg_last_wal_replay_lsn();"
-var pgWALLSNRegex = regexp.MustCompile(`(?i)^\s*select\s+pg_catalog\.(pg_current_wal_lsn|pg_last_wal_replay_lsn)\(\s*\)\s*;?\s*$`)
+// precompile a regex to match "select pg_catalog.pg_current_wal_lsn();", "select pg_catalog.pg_last_wal_replay_lsn();",
+// "select pg_catalog.pg_last_wal_receive_lsn();" or "select pg_catalog.pg_last_xact_replay_timestamp();"
+var pgWALLSNRegex = regexp.MustCompile(`(?i)^\s*select\s+(pg_catalog\.)?(pg_current_wal_lsn|pg_last_wal_replay_lsn|pg_last_wal_receive_lsn|pg_last_xact_replay_timestamp)\(\s*\)\s*;?\s*$`)
 
-// precompile a regex to match "select pg_catalog.current_setting('xxx');".
-var currentSettingRegex = regexp.MustCompile(`(?i)^\s*select\s+(pg_catalog.)?current_setting\(\s*'([^']+)'\s*\)\s*;?\s*$`)
+// precompile a regex to match "select pg_catalog.current_setting('xxx');" or "select current_setting('xxx', true);".
+var currentSettingRegex = regexp.MustCompile(`(?i)^\s*select\s+(pg_catalog.)?current_setting\(\s*'([^']+)'\s*(,\s*(true|false)\s*)?\)\s*;?\s*$`)
+
+// precompile a regex to match "select pg_catalog.set_config('xxx', 'yyy', false);".
+var setConfigRegex = regexp.MustCompile(`(?i)^\s*select\s+(pg_catalog\.)?set_config\(\s*'([^']+)'\s*,\s*'((?:[^']|'')*)'\s*,\s*(true|false)\s*\)\s*;?\s*$`)
+
+// precompile a regex to match "lock table xxx in access share mode;".
+var lockTableRegex = regexp.MustCompile(`(?i)^\s*lock\s+(table\s+)?`)
+
+// precompile a regex to match "select pg_catalog.pg_cancel_backend(pid);" or "select pg_catalog.pg_terminate_backend(pid);".
+var pgSignalBackendRegex = regexp.MustCompile(`(?i)^\s*select\s+(pg_catalog\.)?(pg_cancel_backend|pg_terminate_backend)\(\s*(\d+)\s*\)\s*;?\s*$`)
+
+// precompile a regex to match "select pg_catalog.pg_backend_pid();".
+var pgBackendPidRegex = regexp.MustCompile(`(?i)^\s*select\s+(pg_catalog\.)?pg_backend_pid\(\s*\)\s*;?\s*$`)
 
 // isInRecovery will get the count of
 func (h *ConnectionHandler) isInRecovery() (string, error) {
@@ -67,58 +83,197 @@ func (h *ConnectionHandler) readOneWALPositionStr() (string, error) {
 	return lsn, nil
 }
 
-// queryPGSetting will query the system variable value from the system variable map
-func (h *ConnectionHandler) queryPGSetting(name string) (any, error) {
+// queryWALPosition returns the SQL literal of the result of the WAL position function. On a standby, which is a
+// server with subscriptions, the positions are those of the running replication, or the recorded position if it is
+// not running. On a primary, the current position is the LSN of the last captured change, and the others are NULL.
+func (h *ConnectionHandler) queryWALPosition(function string) (string, error) {
+	inRecovery, err := h.isInRecovery()
+	if err != nil {
+		return "", err
+	}
+	if inRecovery == "f" {
+		if function != "pg_current_wal_lsn" {
+			return "NULL::VARCHAR", nil
+		}
+		provider, err := h.catalogProvider()
+		if err != nil {
+			return "", err
+		}
+		lsn, err := currentLSN(newPublicationStore(provider, provider.Storage()))
+		if err != nil {
+			return "", err
+		}
+		return "'" + lsn.String() + "'", nil
+	}
+
+	var status *logrepl.ReplicationStatus
+	for _, s := range logrepl.SubscriptionStatuses() {
+		if s.Status != nil {
+			status = s.Status
+			break
+		}
+	}
+	switch function {
+	case "pg_last_wal_receive_lsn":
+		if status == nil {
+			return "NULL::VARCHAR", nil
+		}
+		return "'" + status.ReceivedLSN.String() + "'", nil
+	case "pg_last_xact_replay_timestamp":
+		if status == nil || status.WrittenCommitTime.IsZero() {
+			return "NULL::TIMESTAMPTZ", nil
+		}
+		return "'" + status.WrittenCommitTime.Format(time.RFC3339Nano) + "'::TIMESTAMPTZ", nil
+	}
+	if status != nil {
+		return "'" + status.WrittenLSN.String() + "'", nil
+	}
+	lsn, err := h.readOneWALPositionStr()
+	if err != nil {
+		return "", err
+	}
+	return "'" + lsn + "'", nil
+}
+
+// queryPGSetting will query the system variable value from the system variable map,
+// or the custom setting of the application, and format it as Postgres does.
+func (h *ConnectionHandler) queryPGSetting(name string) (string, error) {
+	if isCustomSetting(name) {
+		value, ok := h.customSettings[name]
+		if !ok {
+			return "", fmt.Errorf("unrecognized configuration parameter \"%s\"", name)
+		}
+		return value, nil
+	}
 	sysVar, _, ok := sql.SystemVariables.GetGlobal(name)
 	if !ok {
-		return nil, fmt.Errorf("error: %s variable was not found", name)
+		return "", fmt.Errorf("error: %s variable was not found", name)
 	}
 	ctx, err := h.duckHandler.NewContext(context.Background(), h.mysqlConn, "")
 	if err != nil {
-		return nil, fmt.Errorf("error creating context: %w", err)
+		return "", fmt.Errorf("error creating context: %w", err)
 	}
 	v, err := sysVar.GetSessionScope().GetValue(ctx, name, sql.Collation_Default)
 	if err != nil {
-		return nil, fmt.Errorf("error: %s variable was not found, err: %w", name, err)
+		return "", fmt.Errorf("error: %s variable was not found, err: %w", name, err)
 	}
-	return v, nil
+	if param, ok := sysVar.(*pgconfig.Parameter); ok {
+		return param.FormatValue(v), nil
+	}
+	return fmt.Sprintf("%v", v), nil
+}
+
+// setPGSetting sets the parameter as set_config() does, and returns its new value formatted as Postgres does.
+// A parameter set locally to the transaction is kept for the session.
+func (h *ConnectionHandler) setPGSetting(name string, value string) (string, error) {
+	if isCustomSetting(name) {
+		h.customSettings[name] = value
+		return value, nil
+	}
+	if !pgconfig.IsValidPostgresConfigParameter(name) {
+		return "", fmt.Errorf("unrecognized configuration parameter \"%s\"", name)
+	}
+	v, err := h.setPgSessionVarValue(name, value, false)
+	if err != nil {
+		return "", err
+	}
+	if h.pgSettingsCreated {
+		if err := h.refreshPgSettings(); err != nil {
+			return "", err
+		}
+	}
+	sysVar, _, _ := sql.SystemVariables.GetGlobal(name)
+	param := sysVar.(*pgconfig.Parameter)
+	if pgconfig.IsReportedParameter(name) {
+		if err := h.send(&pgproto3.ParameterStatus{
+			Name:  param.Name,
+			Value: param.FormatValue(v),
+		}); err != nil {
+			return "", err
+		}
+	}
+	return param.FormatValue(v), nil
+}
+
+// quoteString quotes the string as a SQL string literal.
+func quoteString(s string) string {
+	return "'" + strings.ReplaceAll(s, "'", "''") + "'"
 }
 
 // setPgSessionVar will set the session variable to the value provided for pg.
 // And reply with the CommandComplete and ParameterStatus messages.
 func (h *ConnectionHandler) setPgSessionVar(name string, value any, useDefault bool, tag string) (bool, error) {
+	v, err := h.setPgSessionVarValue(name, value, useDefault)
+	if err != nil {
+		return false, err
+	}
+	// Keep the pg_settings table up to date for the prepared queries on it
+	if h.pgSettingsCreated {
+		if err := h.refreshPgSettings(); err != nil {
+			return false, err
+		}
+	}
+	// Sent CommandComplete message
+	err = h.send(makeCommandComplete(tag, 0))
+	if err != nil {
+		return true, err
+	}
+	// Sent ParameterStatus message for the parameters reported to the client
+	if !pgconfig.IsReportedParameter(name) {
+		return true, nil
+	}
+	sysVar, _, _ := sql.SystemVariables.GetGlobal(name)
+	param := sysVar.(*pgconfig.Parameter)
+	if err := h.send(&pgproto3.ParameterStatus{
+		Name:  param.Name,
+		Value: param.FormatValue(v),
+	}); err != nil {
+		return true, err
+	}
+	return true, nil
+}
+
+// setStatementValue returns the string of a value in a SET statement.
+func setStatementValue(query ConvertedStatement, value any) (string, error) {
+	switch val := value.(type) {
+	case *tree.UnresolvedName:
+		if val.NumParts != 1 {
+			return "", fmt.Errorf("error: invalid value in set statement: %v", query.String)
+		}
+		return val.Parts[0], nil
+	case *tree.StrVal:
+		return val.RawString(), nil
+	default:
+		return fmt.Sprintf("%v", val), nil
+	}
+}
+
+// setPgSessionVarValue sets the session variable and returns its new value.
+func (h *ConnectionHandler) setPgSessionVarValue(name string, value any, useDefault bool) (any, error) {
 	sysVar, _, ok := sql.SystemVariables.GetGlobal(name)
 	if !ok {
-		return false, fmt.Errorf("error: %s variable was not found", name)
+		return nil, fmt.Errorf("error: %s variable was not found", name)
 	}
 	ctx, err := h.duckHandler.NewContext(context.Background(), h.mysqlConn, "")
 	if err != nil {
-		return false, err
+		return nil, err
 	}
 	if useDefault {
 		value = sysVar.GetDefault()
 	}
 	err = sysVar.GetSessionScope().SetValue(ctx, name, value)
 	if err != nil {
-		return false, err
+		return nil, err
 	}
 	v, err := sysVar.GetSessionScope().GetValue(ctx, name, sql.Collation_Default)
 	if err != nil {
-		return false, fmt.Errorf("error: %s variable was not found, err: %w", name, err)
-	}
-	// Sent CommandComplete message
-	err = h.send(makeCommandComplete(tag, 0))
-	if err != nil {
-		return true, err
+		return nil, fmt.Errorf("error: %s variable was not found, err: %w", name, err)
 	}
-	// Sent ParameterStatus message
-	if err := h.send(&pgproto3.ParameterStatus{
-		Name:  name,
-		Value: fmt.Sprintf("%v", v),
-	}); err != nil {
-		return true, err
+	// Some parameters take effect in DuckDB, e.g., the search path, which resolves the unqualified names.
+	if err := h.applySetting(ctx, name); err != nil {
+		return nil, err
 	}
-	return true, nil
+	return v, nil
 }
 
 type InPlaceHandler struct {
@@ -164,11 +319,12 @@ var selectionConversions = []SelectionConversion{
 			return pgWALLSNRegex.MatchString(sql)
 		},
 		doConvert: func(h *ConnectionHandler, query *ConvertedStatement) error {
-			lsnStr, err := h.readOneWALPositionStr()
+			function := strings.ToLower(pgWALLSNRegex.FindStringSubmatch(RemoveComments(query.String))[2])
+			position, err := h.queryWALPosition(function)
 			if err != nil {
 				return err
 			}
-			sqlStr := fmt.Sprintf(`SELECT '%s' AS "%s";`, lsnStr, "pg_current_wal_lsn")
+			sqlStr := fmt.Sprintf(`SELECT %s AS "%s";`, position, function)
 			query.String = sqlStr
 			return nil
 		},
@@ -181,10 +337,10 @@ var selectionConversions = []SelectionConversion{
 				return false
 			}
 			matches := currentSettingRegex.FindStringSubmatch(sql)
-			if len(matches) != 3 {
+			if len(matches) != 5 {
 				return false
 			}
-			if !pgconfig.IsValidPostgresConfigParameter(matches[2]) {
+			if !pgconfig.IsValidPostgresConfigParameter(matches[2]) && !isCustomSetting(matches[2]) {
 				// This is a configuration of DuckDB, it should be bypassed to DuckDB
 				return false
 			}
@@ -193,16 +349,128 @@ var selectionConversions = []SelectionConversion{
 		doConvert: func(h *ConnectionHandler, query *ConvertedStatement) error {
 			sql := RemoveComments(query.String)
 			matches := currentSettingRegex.FindStringSubmatch(sql)
-			setting, err := h.queryPGSetting(matches[2])
+			setting, err := h.queryPGSetting(strings.ToLower(matches[2]))
 			if err != nil {
+				// With missing_ok, NULL is returned for an unrecognized setting.
+				if strings.EqualFold(matches[4], "true") {
+					query.String = `SELECT NULL::VARCHAR AS "current_setting";`
+					return nil
+				}
 				return err
 			}
-			sqlStr := fmt.Sprintf(`SELECT '%s' AS "current_setting";`, fmt.Sprintf("%v", setting))
+			sqlStr := fmt.Sprintf(`SELECT %s AS "current_setting";`, quoteString(setting))
 			query.String = sqlStr
 			return nil
 		},
 		isConstQuery: true,
 	},
+	{
+		needConvert: func(query *ConvertedStatement) bool {
+			sql := RemoveComments(query.String)
+			return pgSignalBackendRegex.MatchString(sql)
+		},
+		doConvert: func(h *ConnectionHandler, query *ConvertedStatement) error {
+			sql := RemoveComments(query.String)
+			matches := pgSignalBackendRegex.FindStringSubmatch(sql)
+			function := strings.ToLower(matches[2])
+			pid, err := strconv.ParseUint(matches[3], 10, 32)
+			if err != nil {
+				return fmt.Errorf("error: invalid PID in %s(): %s", function, matches[3])
+			}
+			signaled, err := h.signalBackend(uint32(pid), function == "pg_terminate_backend")
+			if err != nil {
+				return err
+			}
+			query.String = fmt.Sprintf(`SELECT %t AS "%s";`, signaled, function)
+			return nil
+		},
+		isConstQuery: true,
+	},
+	{
+		needConvert: func(query *ConvertedStatement) bool {
+			sql := RemoveComments(query.String)
+			return pgStatStatementsResetRegex.MatchString(sql)
+		},
+		doConvert: func(h *ConnectionHandler, query *ConvertedStatement) error {
+			reset, err := h.resetStatementStats()
+			if err != nil {
+				return err
+			}
+			query.String = fmt.Sprintf(`SELECT '%s'::TIMESTAMPTZ AS "pg_stat_statements_reset";`, reset.Format(time.RFC3339Nano))
+			return nil
+		},
+		isConstQuery: true,
+	},
+	{
+		needConvert: func(query *ConvertedStatement) bool {
+			sql := RemoveComments(query.String)
+			return setConfigRegex.MatchString(sql)
+		},
+		doConvert: func(h *ConnectionHandler, query *ConvertedStatement) error {
+			sql := RemoveComments(query.String)
+			matches := setConfigRegex.FindStringSubmatch(sql)
+			setting, err := h.setPGSetting(strings.ToLower(matches[2]), strings.ReplaceAll(matches[3], "''", "'"))
+			if err != nil {
+				return err
+			}
+			query.String = fmt.Sprintf(`SELECT %s AS "set_config";`, quoteString(setting))
+			return nil
+		},
+		isConstQuery: true,
+	},
+	{
+		needConvert: func(query *ConvertedStatement) bool {
+			sql := RemoveComments(query.String)
+			return pgBackendPidRegex.MatchString(sql)
+		},
+		doConvert: func(h *ConnectionHandler, query *ConvertedStatement) error {
+			query.String = fmt.Sprintf(`SELECT %d::INTEGER AS "pg_backend_pid";`, h.mysqlConn.ConnectionID)
+			return nil
+		},
+		isConstQuery: true,
+	},
+	{
+		needConvert: func(query *ConvertedStatement) bool {
+			sql := RemoveComments(query.String)
+			return pgNotifyRegex.MatchString(sql)
+		},
+		doConvert: func(h *ConnectionHandler, query *ConvertedStatement) error {
+			sql := RemoveComments(query.String)
+			matches := pgNotifyRegex.FindStringSubmatch(sql)
+			channel := strings.ReplaceAll(matches[2], "''", "'")
+			payload := strings.ReplaceAll(matches[4], "''", "'")
+			if err := h.notify(channel, payload); err != nil {
+				return err
+			}
+			query.String = `SELECT NULL AS "pg_notify";`
+			return nil
+		},
+		isConstQuery: true,
+	},
+	{
+		needConvert: func(query *ConvertedStatement) bool {
+			sql := RemoveComments(query.String)
+			return sessionViewRegex.MatchString(sql)
+		},
+		doConvert: func(h *ConnectionHandler, query *ConvertedStatement) error {
+			sqlStr, err := h.refreshSessionViews(query.String)
+			if err != nil {
+				return err
+			}
+			query.String = sqlStr
+			return nil
+		},
+	},
+	{
+		needConvert: func(query *ConvertedStatement) bool {
+			sql := RemoveComments(query.String)
+			return informationSchemaRegex.MatchString(sql)
+		},
+		doConvert: func(h *ConnectionHandler, query *ConvertedStatement) error {
+			query.String = ConvertInformationSchema(query.String)
+			return nil
+		},
+	},
 	{
 		needConvert: func(query *ConvertedStatement) bool {
 			sql := RemoveComments(query.String)
@@ -317,23 +585,30 @@ var inPlaceHandlers = map[string]InPlaceHandler{
 				return false, nil
 			}
 			key := strings.ToLower(showVar.Name)
-			if key != "all" {
-				setting, err := h.queryPGSetting(key)
-				if err != nil {
+			if key == "all" {
+				if err := h.refreshPgSettings(); err != nil {
 					return false, err
 				}
 				return true, h.run(ConvertedStatement{
-					String: fmt.Sprintf(`SELECT '%s' AS "%s";`, fmt.Sprintf("%v", setting), key),
+					String: "SELECT name, setting, short_desc AS description FROM " + pgSettingsTable + " ORDER BY name;",
 					Tag:    "SELECT",
 				})
 			}
-			// TODO(sean): Implement SHOW ALL
-			_ = h.send(&pgproto3.ErrorResponse{
-				Severity: string(ErrorResponseSeverity_Error),
-				Code:     "0A000",
-				Message:  "Statement 'SHOW ALL' is not supported yet.",
+			if !pgconfig.IsValidPostgresConfigParameter(key) && !isCustomSetting(key) {
+				// This may be a configuration of DuckDB
+				return true, h.run(ConvertedStatement{
+					String: fmt.Sprintf(`SELECT current_setting(%s)::VARCHAR AS "%s";`, quoteString(key), key),
+					Tag:    "SELECT",
+				})
+			}
+			setting, err := h.queryPGSetting(key)
+			if err != nil {
+				return false, err
+			}
+			return true, h.run(ConvertedStatement{
+				String: fmt.Sprintf(`SELECT %s AS "%s";`, quoteString(setting), key),
+				Tag:    "SELECT",
 			})
-			return true, nil
 		},
 	},
 	"SET": {
@@ -346,11 +621,15 @@ var inPlaceHandlers = map[string]InPlaceHandler{
 					// Route it to the engine directly.
 					return false, nil
 				}
+				if isCustomSetting(key) {
+					return true, nil
+				}
 				if !pgconfig.IsValidPostgresConfigParameter(key) {
 					// This is a configuration of DuckDB, it should be bypassed to DuckDB
 					return false, nil
 				}
-				if len(stmt.Values) > 1 {
+				// search_path is the only list of values, e.g., SET search_path TO myschema, public
+				if len(stmt.Values) > 1 && key != "search_path" {
 					return false, fmt.Errorf("error: invalid set statement: %v", query.String)
 				}
 				return true, nil
@@ -363,11 +642,13 @@ var inPlaceHandlers = map[string]InPlaceHandler{
 		Handler: func(h *ConnectionHandler, query ConvertedStatement) (bool, error) {
 			var key string
 			var value any
+			var values tree.Exprs
 			var isDefault bool
 			switch stmt := query.AST.(type) {
 			case *tree.SetVar:
 				key = strings.ToLower(stmt.Name)
 				value = stmt.Values[0]
+				values = stmt.Values
 				_, isDefault = value.(tree.DefaultVal)
 			case *tree.SetSessionCharacteristics:
 				// This is a statement of `SET SESSION CHARACTERISTICS AS TRANSACTION ISOLATION LEVEL xxx`.
@@ -383,27 +664,73 @@ var inPlaceHandlers = map[string]InPlaceHandler{
 				// Route it to the engine directly.
 				return false, nil
 			}
+			if isCustomSetting(key) {
+				value := ""
+				if !isDefault {
+					var err error
+					if value, err = setStatementValue(query, values[0]); err != nil {
+						return false, err
+					}
+				}
+				return h.setCustomSetting(key, value, "SET")
+			}
 			if !pgconfig.IsValidPostgresConfigParameter(key) {
 				// This is a configuration of DuckDB, it should be bypassed to DuckDB
 				return false, nil
 			}
 
 			var v any
-			switch val := value.(type) {
-			case *tree.UnresolvedName:
-				if val.NumParts != 1 {
-					return false, fmt.Errorf("error: invalid value in set statement: %v", query.String)
+			if key == "search_path" && !isDefault {
+				schemas := make([]string, len(values))
+				for i, val := range values {
+					schema, err := setStatementValue(query, val)
+					if err != nil {
+						return false, err
+					}
+					// The parser has folded the case of the identifiers, so they are quoted to keep it.
+					if _, ok := val.(*tree.UnresolvedName); ok {
+						schema = quoteSearchPathSchema(schema)
+					}
+					schemas[i] = schema
+				}
+				v = strings.Join(schemas, ", ")
+			} else {
+				var err error
+				if v, err = setStatementValue(query, value); err != nil {
+					return false, err
 				}
-				v = val.Parts[0]
-			case *tree.StrVal:
-				v = val.RawString()
-			default:
-				v = fmt.Sprintf("%v", val)
 			}
 
 			return h.setPgSessionVar(key, v, isDefault, "SET")
 		},
 	},
+	"SET TRANSACTION": {
+		ShouldBeHandledInPlace: func(h *ConnectionHandler, query *ConvertedStatement) (bool, error) {
+			_, ok := query.AST.(*tree.SetTransaction)
+			return ok, nil
+		},
+		Handler: func(h *ConnectionHandler, query ConvertedStatement) (bool, error) {
+			if _, ok := query.AST.(*tree.SetTransaction); !ok {
+				return false, nil
+			}
+			// A transaction of DuckDB always reads a snapshot of the database, which satisfies any isolation level
+			// that can be requested, e.g., REPEATABLE READ by pg_dump.
+			return true, h.send(makeCommandComplete("SET", 0))
+		},
+	},
+	"LOCK": {
+		ShouldBeHandledInPlace: func(h *ConnectionHandler, query *ConvertedStatement) (bool, error) {
+			return lockTableRegex.MatchString(RemoveComments(query.String)), nil
+		},
+		Handler: func(h *ConnectionHandler, query ConvertedStatement) (bool, error) {
+			if !lockTableRegex.MatchString(RemoveComments(query.String)) {
+				return false, nil
+			}
+			// DuckDB does not lock tables. The tables are locked by pg_dump to keep them from being changed or
+			// dropped during the dump, which the snapshot of the transaction already ensures for the data.
+			return true, h.send(makeCommandComplete("LOCK TABLE", 0))
+		},
+	},
 	"RESET": {
 		ShouldBeHandledInPlace: func(h *ConnectionHandler, query *ConvertedStatement) (bool, error) {
 			switch stmt := query.AST.(type) {
@@ -412,7 +739,7 @@ var inPlaceHandlers = map[string]InPlaceHandler{
 					return false, fmt.Errorf("error: invalid reset statement: %v", stmt)
 				}
 				key := strings.ToLower(stmt.Name)
-				if !pgconfig.IsValidPostgresConfigParameter(key) {
+				if !stmt.ResetAll && !pgconfig.IsValidPostgresConfigParameter(key) && !isCustomSetting(key) {
 					return false, nil
 				}
 				return true, nil
@@ -424,21 +751,18 @@ var inPlaceHandlers = map[string]InPlaceHandler{
 			if !ok || (!resetVar.Reset && !resetVar.ResetAll) {
 				return false, fmt.Errorf("error: invalid reset statement: %v", query.String)
 			}
+			if resetVar.ResetAll {
+				return h.resetAllPgSessionVars()
+			}
 			key := strings.ToLower(resetVar.Name)
+			if isCustomSetting(key) {
+				return h.setCustomSetting(key, "", "RESET")
+			}
 			if !pgconfig.IsValidPostgresConfigParameter(key) {
 				// This is a configuration of DuckDB, it should be bypassed to DuckDB
 				return false, nil
 			}
-			if !resetVar.ResetAll {
-				return h.setPgSessionVar(key, nil, true, "RESET")
-			}
-			// TODO(sean): Implement RESET ALL
-			_ = h.send(&pgproto3.ErrorResponse{
-				Severity: string(ErrorResponseSeverity_Error),
-				Code:     "0A000",
-				Message:  "Statement 'RESET ALL' is not supported yet.",
-			})
-			return true, nil
+			return h.setPgSessionVar(key, nil, true, "RESET")
 		},
 	},
 }
@@ -0,0 +1,282 @@
+package pgserver
+
+import (
+	"context"
+	"errors"
+	"fmt"
+	"strings"
+	"sync"
+	"time"
+
+	"github.com/cockroachdb/cockroachdb-parser/pkg/sql/sem/tree"
+)
+
+// Restoring a plain SQL dump, e.g., by psql -f, runs one INSERT statement after another outside any transaction
+// block, so that each of them is committed by DuckDB on its own. When the batching is enabled by
+// WithInsertBatchSize, the INSERT statements run outside transaction blocks are grouped into an implicit transaction,
+// which is committed once another kind of message arrives, the batch is full, the connection is idle for a while,
+// or the connection is closed. In addition, the consecutive INSERT ... VALUES statements on the same table in a single
+// query message are coalesced into one multi-row INSERT statement.
+//
+// Each statement still behaves as in autocommit mode to the client: it is acknowledged once it has run, and when
+// it fails, the implicit transaction is rolled back, and the statements before it are replayed and committed.
+// However, the rows are visible to the other connections only after the implicit transaction is committed.
+
+const (
+	// insertBatchIdleTimeout is how long the implicit transaction is kept open while the connection is idle.
+	insertBatchIdleTimeout = 100 * time.Millisecond
+	// maxCoalescedInserts is the maximum number of INSERT statements coalesced into a multi-row INSERT statement.
+	maxCoalescedInserts = 1000
+)
+
+// insertBatch is the implicit transaction grouping the INSERT statements of a connection.
+type insertBatch struct {
+	// mu is held while a message is handled, since the batch is also committed by the idle timer.
+	mu sync.Mutex
+	// statements are the statements run in the implicit transaction, which are replayed if a later one fails.
+	statements []string
+	timer      *time.Timer
+	// err is the error of committing the batch on the idle timer, which is reported to the next query.
+	err error
+}
+
+// insertGroup is a run of INSERT statements coalesced into a single statement.
+type insertGroup struct {
+	query string
+	ast   tree.Statement
+	// rows are the numbers of the rows inserted by the original statements if they are coalesced, or nil if the
+	// group is a single statement run as is.
+	rows []int32
+}
+
+// maybeInsertQuery returns whether the query may consist of INSERT statements, without parsing it.
+func maybeInsertQuery(query string) bool {
+	query = strings.TrimLeft(query, " \t\r\n")
+	return len(query) >= len("INSERT") && strings.EqualFold(query[:len("INSERT")], "INSERT")
+}
+
+// canBatchInserts returns whether the statements of a query message can be run in the implicit transaction.
+func (h *ConnectionHandler) canBatchInserts(statements []ConvertedStatement) bool {
+	if insertBatchSize <= 0 || h.inTransaction || len(statements) == 0 {
+		return false
+	}
+	for _, statement := range statements {
+		insert, ok := statement.AST.(*tree.Insert)
+		if !ok || !statement.PgParsable || statement.CursorConfig != nil || statement.NotifyConfig != nil ||
+			tree.HasReturningClause(insert.Returning) {
+			return false
+		}
+	}
+	return true
+}
+
+// coalescibleInsert returns the part of an INSERT ... VALUES statement before the VALUES keyword, the rows after it,
+// and the number of the rows. The statement is coalescible with the following ones of the same prefix.
+func coalescibleInsert(statement ConvertedStatement) (prefix, values string, rows int32, ok bool) {
+	insert, ok := statement.AST.(*tree.Insert)
+	if !ok || insert.With != nil || insert.OnConflict != nil || tree.HasReturningClause(insert.Returning) ||
+		insert.Rows == nil || insert.Rows.With != nil || insert.Rows.OrderBy != nil || insert.Rows.Limit != nil {
+		return "", "", 0, false
+	}
+	clause, ok := insert.Rows.Select.(*tree.ValuesClause)
+	if !ok || len(clause.Rows) == 0 {
+		return "", "", 0, false
+	}
+	prefix, values, ok = splitInsertValues(statement.String)
+	if !ok {
+		return "", "", 0, false
+	}
+	return prefix, values, int32(len(clause.Rows)), true
+}
+
+// splitInsertValues splits an INSERT statement at the VALUES keyword outside the parentheses and the quotes.
+func splitInsertValues(query string) (prefix, values string, ok bool) {
+	depth := 0
+	for i := 0; i < len(query); i++ {
+		switch c := query[i]; c {
+		case '\'', '"':
+			end := strings.IndexByte(query[i+1:], c)
+			if end < 0 {
+				return "", "", false
+			}
+			i += end + 1
+		case '(':
+			depth++
+		case ')':
+			depth--
+		case 'v', 'V':
+			if depth != 0 || i == 0 || !isSpaceOrParen(query[i-1]) || !strings.EqualFold(query[i:min(i+6, len(query))], "VALUES") {
+				continue
+			}
+			if i+6 < len(query) && !isSpaceOrParen(query[i+6]) {
+				continue
+			}
+			values = strings.TrimSpace(strings.TrimRight(strings.TrimSpace(query[i+6:]), ";"))
+			return strings.TrimSpace(query[:i]), values, strings.HasSuffix(values, ")")
+		}
+	}
+	return "", "", false
+}
+
+func isSpaceOrParen(c byte) bool {
+	return c == ' ' || c == '\t' || c == '\r' || c == '\n' || c == '(' || c == ')'
+}
+
+// coalesceInserts coalesces the consecutive INSERT ... VALUES statements on the same table with the same columns.
+func coalesceInserts(statements []ConvertedStatement) []insertGroup {
+	var groups []insertGroup
+	var prefix string
+	var values []string
+	flush := func() {
+		if len(values) > 1 {
+			groups[len(groups)-1].query = prefix + " VALUES " + strings.Join(values, ", ")
+		}
+		values = nil
+	}
+	for _, statement := range statements {
+		p, v, rows, ok := coalescibleInsert(statement)
+		if !ok {
+			flush()
+			groups = append(groups, insertGroup{query: statement.String, ast: statement.AST})
+			continue
+		}
+		if len(values) > 0 && p == prefix && len(values) < maxCoalescedInserts {
+			values = append(values, v)
+			last := &groups[len(groups)-1]
+			last.rows = append(last.rows, rows)
+			continue
+		}
+		flush()
+		prefix, values = p, []string{v}
+		groups = append(groups, insertGroup{query: statement.String, ast: statement.AST, rows: []int32{rows}})
+	}
+	flush()
+	return groups
+}
+
+// runBatchedInserts runs the INSERT statements of a query message in the implicit transaction, and sends a
+// CommandComplete message for each of them.
+func (h *ConnectionHandler) runBatchedInserts(statements []ConvertedStatement) error {
+	b := &h.insertBatch
+	if err := b.err; err != nil {
+		b.err = nil
+		return err
+	}
+	if len(b.statements) == 0 {
+		if _, err := h.execInsertBatch("BEGIN", &tree.BeginTransaction{}); err != nil {
+			return err
+		}
+	}
+
+	for _, group := range coalesceInserts(statements) {
+		h.beginStatement(group.query)
+		stopTimeout := h.startStatementTimeout()
+		affected, err := h.execInsertBatch(group.query, group.ast)
+		if err = stopTimeout(err); err != nil {
+			return h.abortInsertBatch(fmt.Errorf("fallback statement execution failed: %w", err))
+		}
+		b.statements = append(b.statements, group.query)
+
+		if group.rows == nil {
+			if err := h.send(makeCommandComplete("INSERT", affected)); err != nil {
+				return err
+			}
+			continue
+		}
+		for _, rows := range group.rows {
+			if err := h.send(makeCommandComplete("INSERT", rows)); err != nil {
+				return err
+			}
+		}
+	}
+
+	if len(b.statements) >= insertBatchSize {
+		return h.commitInsertBatch()
+	}
+	if b.timer == nil {
+		b.timer = time.AfterFunc(insertBatchIdleTimeout, h.commitIdleInsertBatch)
+	} else {
+		b.timer.Reset(insertBatchIdleTimeout)
+	}
+	return nil
+}
+
+// commitInsertBatch commits the implicit transaction if there is one. It also reports the error of the last commit
+// on the idle timer, if any.
+func (h *ConnectionHandler) commitInsertBatch() error {
+	b := &h.insertBatch
+	if err := b.err; err != nil {
+		b.err = nil
+		return err
+	}
+	if len(b.statements) == 0 {
+		return nil
+	}
+	if b.timer != nil {
+		b.timer.Stop()
+	}
+	count := len(b.statements)
+	b.statements = nil
+	if _, err := h.execInsertBatch("COMMIT", &tree.CommitTransaction{}); err != nil {
+		return fmt.Errorf("failed to commit the %d batched INSERT statements: %w", count, err)
+	}
+	return nil
+}
+
+// commitIdleInsertBatch commits the implicit transaction once the connection has been idle for a while.
+func (h *ConnectionHandler) commitIdleInsertBatch() {
+	h.insertBatch.mu.Lock()
+	defer h.insertBatch.mu.Unlock()
+	if len(h.insertBatch.statements) == 0 {
+		return
+	}
+	if err := h.commitInsertBatch(); err != nil {
+		h.logger.Warnf("Failed to commit the batched INSERT statements: %v", err)
+		h.insertBatch.err = err
+	}
+}
+
+// abortInsertBatch rolls back the implicit transaction after a statement in it fails, and replays the statements
+// before that one in a new transaction, so that only the failed statement is lost as in autocommit mode.
+func (h *ConnectionHandler) abortInsertBatch(err error) error {
+	b := &h.insertBatch
+	if b.timer != nil {
+		b.timer.Stop()
+	}
+	statements := b.statements
+	b.statements = nil
+	if _, rollbackErr := h.execInsertBatch("ROLLBACK", &tree.RollbackTransaction{}); rollbackErr != nil {
+		return errors.Join(err, rollbackErr)
+	}
+	if len(statements) == 0 {
+		return err
+	}
+
+	replay := func() error {
+		if _, err := h.execInsertBatch("BEGIN", &tree.BeginTransaction{}); err != nil {
+			return err
+		}
+		for _, statement := range statements {
+			if _, err := h.execInsertBatch(statement, &tree.Insert{}); err != nil {
+				_, _ = h.execInsertBatch("ROLLBACK", &tree.RollbackTransaction{})
+				return err
+			}
+		}
+		_, err := h.execInsertBatch("COMMIT", &tree.CommitTransaction{})
+		return err
+	}
+	if replayErr := replay(); replayErr != nil {
+		return errors.Join(err, fmt.Errorf("failed to replay the %d batched INSERT statements before: %w", len(statements), replayErr))
+	}
+	return err
+}
+
+// execInsertBatch runs a statement of the implicit transaction, and returns the number of the affected rows.
+func (h *ConnectionHandler) execInsertBatch(query string, ast tree.Statement) (int32, error) {
+	var affected int32
+	err := h.duckHandler.ComQuery(context.Background(), h.mysqlConn, query, ast, func(res *Result) error {
+		affected = int32(res.RowsAffected)
+		return nil
+	})
+	return affected, err
+}
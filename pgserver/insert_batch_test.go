@@ -0,0 +1,71 @@
+package pgserver
+
+import (
+	"testing"
+
+	"github.com/cockroachdb/cockroachdb-parser/pkg/sql/parser"
+	"github.com/stretchr/testify/require"
+)
+
+func TestCoalesceInserts(t *testing.T) {
+	convert := func(query string) []ConvertedStatement {
+		stmts, err := parser.Parse(query)
+		require.NoError(t, err)
+		statements := make([]ConvertedStatement, len(stmts))
+		for i, stmt := range stmts {
+			statements[i] = ConvertedStatement{String: stmt.SQL, AST: stmt.AST, Tag: stmt.AST.StatementTag(), PgParsable: true}
+		}
+		return statements
+	}
+
+	tests := []struct {
+		name    string
+		query   string
+		queries []string
+		rows    [][]int32
+	}{
+		{
+			name:    "same table",
+			query:   "INSERT INTO t VALUES (1, 'a'); INSERT INTO t VALUES (2, 'b;c'), (3, 'values');",
+			queries: []string{"INSERT INTO t VALUES (1, 'a'), (2, 'b;c'), (3, 'values')"},
+			rows:    [][]int32{{1, 2}},
+		},
+		{
+			name:  "different columns",
+			query: "INSERT INTO t (a) VALUES (1); INSERT INTO t (b) VALUES (2); INSERT INTO t (b) VALUES (3);",
+			queries: []string{
+				"INSERT INTO t (a) VALUES (1)",
+				"INSERT INTO t (b) VALUES (2), (3)",
+			},
+			rows: [][]int32{{1}, {1, 1}},
+		},
+		{
+			name:  "not coalescible",
+			query: "INSERT INTO t VALUES (1); INSERT INTO t SELECT 2; INSERT INTO t VALUES (3) ON CONFLICT DO NOTHING; INSERT INTO t VALUES (4);",
+			queries: []string{
+				"INSERT INTO t VALUES (1)",
+				"INSERT INTO t SELECT 2",
+				"INSERT INTO t VALUES (3) ON CONFLICT DO NOTHING",
+				"INSERT INTO t VALUES (4)",
+			},
+			rows: [][]int32{{1}, nil, nil, {1}},
+		},
+		{
+			name:    "quoted names",
+			query:   `INSERT INTO "my values" ("values") VALUES (1); INSERT INTO "my values" ("values") VALUES (2);`,
+			queries: []string{`INSERT INTO "my values" ("values") VALUES (1), (2)`},
+			rows:    [][]int32{{1, 1}},
+		},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			groups := coalesceInserts(convert(tt.query))
+			require.Len(t, groups, len(tt.queries))
+			for i, group := range groups {
+				require.Equal(t, tt.queries[i], group.query)
+				require.Equal(t, tt.rows[i], group.rows)
+			}
+		})
+	}
+}
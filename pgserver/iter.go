@@ -7,7 +7,9 @@ import (
 	"io"
 	"math/big"
 	"strings"
+	"time"
 
+	"github.com/apecloud/myduckserver/pgtypes"
 	"github.com/dolthub/go-mysql-server/sql"
 	"github.com/jackc/pgx/v5/pgtype"
 	"github.com/marcboeker/go-duckdb"
@@ -88,7 +90,8 @@ type SqlRowIter struct {
 
 	decimals []int
 	lists    []int
-	hugeInts []int
+	values   []int    // the columns whose values are converted by toPgValue
+	oids     []uint32 // the OIDs of the Postgres types of the columns, or of the elements of the list columns
 }
 
 func NewSqlRowIter(rows *stdsql.Rows, schema sql.Schema) (*SqlRowIter, error) {
@@ -104,28 +107,51 @@ func NewSqlRowIter(rows *stdsql.Rows, schema sql.Schema) (*SqlRowIter, error) {
 		ptrs[i] = &buf[i]
 	}
 
+	var lists []int
+	for i, t := range columns {
+		if pgtypes.IsListTypeName(t.DatabaseTypeName()) {
+			lists = append(lists, i)
+		}
+	}
+
 	var decimals []int
 	for i, c := range columns {
-		if strings.HasPrefix(c.DatabaseTypeName(), "DECIMAL") {
+		// The decimals in lists are converted along with the lists.
+		if strings.HasPrefix(c.DatabaseTypeName(), "DECIMAL") && !pgtypes.IsListTypeName(c.DatabaseTypeName()) {
 			decimals = append(decimals, i)
 		}
 	}
 
-	var lists []int
+	var values []int
 	for i, t := range columns {
-		if strings.HasSuffix(t.DatabaseTypeName(), "[]") {
-			lists = append(lists, i)
+		switch t.DatabaseTypeName() {
+		case "HUGEINT", "UHUGEINT", "UBIGINT", "INTERVAL", "UUID", "TIME", "TIMETZ":
+			values = append(values, i)
 		}
 	}
 
-	var hugeInts []int
+	oids := make([]uint32, len(columns))
 	for i, t := range columns {
-		if t.DatabaseTypeName() == "HUGEINT" {
-			hugeInts = append(hugeInts, i)
+		if t.DatabaseTypeName() == "TIMETZ" {
+			// pgtype does not support timetz, so the values are formatted and sent as text.
+			oids[i] = pgtype.TimetzOID
+			continue
+		}
+		if i >= len(schema) {
+			break
+		}
+		pgType, ok := schema[i].Type.(pgtypes.PostgresType)
+		if !ok {
+			continue
+		}
+		if codec, ok := pgType.PG.Codec.(*pgtype.ArrayCodec); ok {
+			oids[i] = codec.ElementType.OID
+		} else {
+			oids[i] = pgType.PG.OID
 		}
 	}
 
-	iter := &SqlRowIter{rows, columns, schema, buf, ptrs, decimals, lists, hugeInts}
+	iter := &SqlRowIter{rows, columns, schema, buf, ptrs, decimals, lists, values, oids}
 	if logrus.GetLevel() >= logrus.DebugLevel {
 		logrus.Debugf("New " + iter.String() + "\n")
 	}
@@ -165,23 +191,6 @@ func (iter *SqlRowIter) Next(ctx *sql.Context) (sql.Row, error) {
 				return nil, err
 			}
 			iter.buffer[idx] = n
-		case []any:
-			array := make([]pgtype.Numeric, len(v))
-			for i, x := range v {
-				switch y := x.(type) {
-				case nil:
-					array[i] = pgtype.Numeric{}
-				case duckdb.Decimal:
-					array[i] = pgtype.Numeric{Int: y.Value, Exp: -int32(y.Scale), Valid: true}
-				case string:
-					if err := array[i].Scan(y); err != nil {
-						return nil, err
-					}
-				default:
-					return nil, fmt.Errorf("unexpected type %T for decimal value", x)
-				}
-			}
-			iter.buffer[idx] = array
 		default:
 			return nil, fmt.Errorf("unexpected type %T for decimal value", v)
 		}
@@ -189,32 +198,22 @@ func (iter *SqlRowIter) Next(ctx *sql.Context) (sql.Row, error) {
 
 	// Process list values
 	for _, idx := range iter.lists {
-		var list []any
-		switch v := iter.buffer[idx].(type) {
-		case nil:
-			list = nil
-		case []pgtype.Numeric: // from the previous decimal processing step
-			iter.buffer[idx] = pgtype.FlatArray[pgtype.Numeric](v)
+		if iter.buffer[idx] == nil {
 			continue
-		case []any:
-			list = v
-		case duckdb.Composite[[]any]:
-			list = v.Get()
-		default:
-			return nil, fmt.Errorf("unexpected type %T for list value", v)
 		}
-		iter.buffer[idx] = pgtype.FlatArray[any](list)
+		list, ok := asList(iter.buffer[idx])
+		if !ok {
+			return nil, fmt.Errorf("unexpected type %T for list value", iter.buffer[idx])
+		}
+		array, err := toPgArray(list, iter.oids[idx])
+		if err != nil {
+			return nil, err
+		}
+		iter.buffer[idx] = array
 	}
 
-	for _, idx := range iter.hugeInts {
-		switch v := iter.buffer[idx].(type) {
-		case nil:
-			continue
-		case *big.Int:
-			iter.buffer[idx] = pgtype.Numeric{Int: v, Valid: true}
-		default:
-			return nil, fmt.Errorf("unexpected type %T for big.Int value", v)
-		}
+	for _, idx := range iter.values {
+		iter.buffer[idx] = toPgValue(iter.buffer[idx], iter.oids[idx])
 	}
 
 	// Prune or fill the values to match the schema
@@ -235,6 +234,118 @@ func (iter *SqlRowIter) Close(ctx *sql.Context) error {
 	return iter.rows.Close()
 }
 
+// asList returns the elements of a LIST value scanned from DuckDB.
+func asList(v any) ([]any, bool) {
+	switch v := v.(type) {
+	case []any:
+		return v, true
+	case duckdb.Composite[[]any]:
+		return v.Get(), true
+	default:
+		return nil, false
+	}
+}
+
+// toPgValue converts a value scanned from DuckDB into one that pgtype encodes in both the text and the binary format
+// of the Postgres type of the OID. The other values are returned as they are.
+func toPgValue(v any, oid uint32) any {
+	switch x := v.(type) {
+	case duckdb.Decimal:
+		return pgtype.Numeric{Int: x.Value, Exp: -int32(x.Scale), Valid: true}
+	case *big.Int:
+		return pgtype.Numeric{Int: x, Valid: true}
+	case uint64:
+		if oid == pgtype.NumericOID {
+			return pgtype.Numeric{Int: new(big.Int).SetUint64(x), Valid: true}
+		}
+	case duckdb.Interval:
+		return pgtype.Interval{Months: x.Months, Days: x.Days, Microseconds: x.Micros, Valid: true}
+	case time.Time:
+		switch oid {
+		case pgtype.TimeOID:
+			// The TIME values are scanned as the times of a day, whose date is to be ignored.
+			h, m, s := x.Clock()
+			micros := (int64(h)*3600+int64(m)*60+int64(s))*1000000 + int64(x.Nanosecond()/1000)
+			return pgtype.Time{Microseconds: micros, Valid: true}
+		case pgtype.TimetzOID:
+			return formatTimetz(x)
+		}
+	case []byte:
+		if oid == pgtype.UUIDOID && len(x) == 16 {
+			return pgtype.UUID{Bytes: [16]byte(x), Valid: true}
+		}
+	case [16]byte:
+		if oid == pgtype.UUIDOID {
+			return pgtype.UUID{Bytes: x, Valid: true}
+		}
+	case string:
+		if oid == pgtype.UUIDOID {
+			var uuid pgtype.UUID
+			if err := uuid.Scan(x); err == nil {
+				return uuid
+			}
+		}
+	}
+	return v
+}
+
+// formatTimetz formats a TIMETZ value as Postgres does, e.g., 12:34:56.789+08 and 12:34:56-09:30.
+func formatTimetz(t time.Time) string {
+	_, offset := t.Zone()
+	sign := byte('+')
+	if offset < 0 {
+		sign, offset = '-', -offset
+	}
+	s := fmt.Sprintf("%s%c%02d", t.Format("15:04:05.999999"), sign, offset/3600)
+	if minutes := offset % 3600 / 60; minutes != 0 {
+		s += fmt.Sprintf(":%02d", minutes)
+	}
+	return s
+}
+
+// toPgArray converts a LIST value into a Postgres array of the elements of the OID. The nested lists become
+// the dimensions of a multidimensional array, which must be rectangular as in Postgres.
+func toPgArray(list []any, oid uint32) (pgtype.Array[any], error) {
+	if len(list) == 0 {
+		return pgtype.Array[any]{Elements: []any{}, Valid: true}, nil
+	}
+	dims := []pgtype.ArrayDimension{{Length: int32(len(list)), LowerBound: 1}}
+	for first := list; len(first) > 0; {
+		sub, ok := asList(first[0])
+		if !ok {
+			break
+		}
+		dims = append(dims, pgtype.ArrayDimension{Length: int32(len(sub)), LowerBound: 1})
+		first = sub
+	}
+
+	var elements []any
+	var flatten func(list []any, dim int) error
+	flatten = func(list []any, dim int) error {
+		if int32(len(list)) != dims[dim].Length {
+			return fmt.Errorf("multidimensional arrays must have sub-arrays with matching dimensions")
+		}
+		for _, v := range list {
+			if dim+1 < len(dims) {
+				sub, ok := asList(v)
+				if !ok {
+					return fmt.Errorf("multidimensional arrays must have sub-arrays with matching dimensions")
+				}
+				if err := flatten(sub, dim+1); err != nil {
+					return err
+				}
+				continue
+			}
+			elements = append(elements, toPgValue(v, oid))
+		}
+		return nil
+	}
+	if err := flatten(list, 0); err != nil {
+		return pgtype.Array[any]{}, err
+	}
+	return pgtype.Array[any]{Elements: elements, Dims: dims, Valid: true}, nil
+}
+
 func formatSchema(schema sql.Schema) string {
 	var sb strings.Builder
 	for i, col := range schema {
@@ -0,0 +1,141 @@
+package pgserver
+
+import (
+	"context"
+	"math/big"
+	"strconv"
+	"testing"
+	"time"
+
+	"github.com/apecloud/myduckserver/pgtypes"
+	"github.com/apecloud/myduckserver/testutil"
+	"github.com/jackc/pgx/v5"
+	"github.com/jackc/pgx/v5/pgproto3"
+	"github.com/jackc/pgx/v5/pgtype"
+	"github.com/marcboeker/go-duckdb"
+	"github.com/stretchr/testify/require"
+)
+
+func TestToPgValue(t *testing.T) {
+	uuid := []byte{0xa0, 0xee, 0xbc, 0x99, 0x9c, 0x0b, 0x4e, 0xf8, 0xbb, 0x6d, 0x6b, 0xb9, 0xbd, 0x38, 0x0a, 0x11}
+	tests := []struct {
+		oid   uint32
+		value any
+		text  string
+	}{
+		{oid: pgtype.NumericOID, value: duckdb.Decimal{Width: 10, Scale: 2, Value: big.NewInt(-12345)}, text: "-123.45"},
+		{oid: pgtype.NumericOID, value: new(big.Int).Lsh(big.NewInt(1), 100), text: "1267650600228229401496703205376"},
+		{oid: pgtype.NumericOID, value: uint64(18446744073709551615), text: "18446744073709551615"},
+		{oid: pgtype.IntervalOID, value: duckdb.Interval{Months: 14, Days: 3, Micros: 3723000004}, text: "14 mon 3 day 01:02:03.000004"},
+		{oid: pgtype.TimeOID, value: time.Date(1970, 1, 1, 12, 34, 56, 789000, time.UTC), text: "12:34:56.000789"},
+		{oid: pgtype.TimestampOID, value: time.Date(2024, 2, 29, 1, 2, 3, 0, time.UTC), text: "2024-02-29 01:02:03"},
+		{oid: pgtype.DateOID, value: time.Date(2024, 2, 29, 0, 0, 0, 0, time.UTC), text: "2024-02-29"},
+		{oid: pgtype.UUIDOID, value: uuid, text: "a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11"},
+		{oid: pgtype.UUIDOID, value: "a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11", text: "a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11"},
+	}
+	for _, tt := range tests {
+		v := toPgValue(tt.value, tt.oid)
+		text, err := pgtypes.DefaultTypeMap.Encode(tt.oid, pgtype.TextFormatCode, v, nil)
+		require.NoError(t, err, tt.text)
+		require.Equal(t, tt.text, string(text))
+
+		// The binary format decodes to the same value.
+		binary, err := pgtypes.DefaultTypeMap.Encode(tt.oid, pgtype.BinaryFormatCode, v, nil)
+		require.NoError(t, err, tt.text)
+		var decoded any
+		require.NoError(t, pgtypes.DefaultTypeMap.Scan(tt.oid, pgtype.BinaryFormatCode, binary, &decoded), tt.text)
+		text, err = pgtypes.DefaultTypeMap.Encode(tt.oid, pgtype.TextFormatCode, decoded, nil)
+		require.NoError(t, err, tt.text)
+		require.Equal(t, tt.text, string(text))
+	}
+
+	require.Equal(t, "12:34:56.5+08", toPgValue(time.Date(1970, 1, 1, 12, 34, 56, 5e8, time.FixedZone("", 8*3600)), pgtype.TimetzOID))
+	require.Equal(t, "01:00:00-09:30", toPgValue(time.Date(1970, 1, 1, 1, 0, 0, 0, time.FixedZone("", -9*3600-1800)), pgtype.TimetzOID))
+
+	array, err := toPgArray([]any{duckdb.Interval{Days: 1}, nil}, pgtype.IntervalOID)
+	require.NoError(t, err)
+	binary, err := pgtypes.DefaultTypeMap.Encode(pgtype.IntervalArrayOID, pgtype.BinaryFormatCode, array, nil)
+	require.NoError(t, err)
+	var intervals []*pgtype.Interval
+	require.NoError(t, pgtypes.DefaultTypeMap.Scan(pgtype.IntervalArrayOID, pgtype.BinaryFormatCode, binary, &intervals))
+	require.Equal(t, []*pgtype.Interval{{Days: 1, Valid: true}, nil}, intervals)
+}
+
+func TestBinaryFormat(t *testing.T) {
+	// Setup MyDuck Server
+	testDir := testutil.CreateTestDir(t)
+	testEnv := testutil.NewTestEnv()
+	err := testutil.StartDuckSqlServer(t, testDir, nil, testEnv)
+	require.NoError(t, err)
+	defer testutil.StopDuckSqlServer(t, testEnv.DuckProcess)
+	dsn := "postgresql://postgres@localhost:" + strconv.Itoa(testEnv.DuckPgPort) + "/postgres"
+
+	ctx := context.Background()
+	conn, err := pgx.Connect(ctx, dsn)
+	require.NoError(t, err)
+	defer conn.Close(ctx)
+
+	// The results in binary format
+	query := `SELECT TIMESTAMP '2024-02-29 01:02:03.456789' AS ts, TIMESTAMPTZ '2024-02-29 01:02:03+00' AS tstz,
+		DATE '2024-02-29' AS d, TIME '12:34:56' AS t, 123.45::DECIMAL(10, 2) AS n, 170141183460469231731687303715884105727::HUGEINT AS h,
+		INTERVAL '1 year 2 days 3 seconds' AS i, 'a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11'::UUID AS u,
+		[TIMESTAMP '2024-01-01', NULL] AS tss, ['a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11'::UUID] AS us`
+	rows, err := conn.Query(ctx, query, pgx.QueryResultFormats{pgproto3.BinaryFormat})
+	require.NoError(t, err)
+	for _, field := range rows.FieldDescriptions() {
+		require.Equal(t, int16(pgproto3.BinaryFormat), field.Format, field.Name)
+	}
+	require.True(t, rows.Next())
+	var (
+		ts, tstz, d time.Time
+		tm          pgtype.Time
+		n, h        pgtype.Numeric
+		i           pgtype.Interval
+		u           pgtype.UUID
+		tss         []*time.Time
+		us          []pgtype.UUID
+	)
+	require.NoError(t, rows.Scan(&ts, &tstz, &d, &tm, &n, &h, &i, &u, &tss, &us))
+	require.Equal(t, time.Date(2024, 2, 29, 1, 2, 3, 456789000, time.UTC), ts)
+	require.True(t, time.Date(2024, 2, 29, 1, 2, 3, 0, time.UTC).Equal(tstz))
+	require.Equal(t, time.Date(2024, 2, 29, 0, 0, 0, 0, time.UTC), d)
+	require.Equal(t, int64(45296000000), tm.Microseconds)
+	require.Equal(t, "123.45", encodeText(t, pgtype.NumericOID, n))
+	require.Equal(t, "170141183460469231731687303715884105727", encodeText(t, pgtype.NumericOID, h))
+	require.Equal(t, pgtype.Interval{Months: 12, Days: 2, Microseconds: 3000000, Valid: true}, i)
+	require.Equal(t, "a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11", encodeText(t, pgtype.UUIDOID, u))
+	require.Len(t, tss, 2)
+	require.Equal(t, time.Date(2024, 1, 1, 0, 0, 0, 0, time.UTC), *tss[0])
+	require.Nil(t, tss[1])
+	require.Len(t, us, 1)
+	require.Equal(t, u, us[0])
+	rows.Close()
+	require.NoError(t, rows.Err())
+
+	// The bind parameters in binary format
+	params := []any{
+		time.Date(2024, 2, 29, 1, 2, 3, 0, time.UTC),
+		pgtype.Numeric{Int: big.NewInt(-12345), Exp: -2, Valid: true},
+		pgtype.Interval{Months: 1, Days: 2, Microseconds: 3000000, Valid: true},
+		u,
+	}
+	oids := []uint32{pgtype.TimestampOID, pgtype.NumericOID, pgtype.IntervalOID, pgtype.UUIDOID}
+	values := make([][]byte, len(params))
+	for j, p := range params {
+		values[j], err = conn.TypeMap().Encode(oids[j], pgproto3.BinaryFormat, p, nil)
+		require.NoError(t, err)
+	}
+	result := conn.PgConn().ExecParams(ctx, "SELECT $1::TIMESTAMP, $2::DECIMAL(10, 2), $3::INTERVAL, $4::UUID",
+		values, oids, []int16{pgproto3.BinaryFormat}, []int16{pgproto3.TextFormat}).Read()
+	require.NoError(t, result.Err)
+	require.Equal(t, "2024-02-29 01:02:03", string(result.Rows[0][0]))
+	require.Equal(t, "-123.45", string(result.Rows[0][1]))
+	require.Equal(t, "1 mon 2 day 00:00:03", string(result.Rows[0][2]))
+	require.Equal(t, "a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11", string(result.Rows[0][3]))
+}
+
+func encodeText(t *testing.T, oid uint32, v any) string {
+	text, err := pgtypes.DefaultTypeMap.Encode(oid, pgtype.TextFormatCode, v, nil)
+	require.NoError(t, err)
+	return string(text)
+}
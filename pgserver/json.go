@@ -0,0 +1,520 @@
+package pgserver
+
+import (
+	"encoding/json"
+	"strings"
+
+	"github.com/apecloud/myduckserver/catalog"
+)
+
+// The jsonb type of Postgres is the JSON type of DuckDB, which supports the -> and ->> operators as well, but neither
+// the other JSON operators nor the json_*/jsonb_* functions of Postgres. ConvertJSON rewrites them in the queries:
+//
+//	j #> '{a,0}'         ->  j -> '$."a"[0]'
+//	j #>> '{a,0}'        ->  j ->> '$."a"[0]'
+//	j @> '{"a": 1}'      ->  json_contains(j, '{"a": 1}')
+//	'{"a": 1}' <@ j      ->  json_contains(j, '{"a": 1}')
+//	j ? 'a'              ->  list_contains(json_keys(j), 'a')
+//	j ?| array['a', 'b'] ->  list_has_any(json_keys(j), array['a', 'b'])
+//	j ?& '{a,b}'         ->  list_has_all(json_keys(j), '[a, b]'::VARCHAR[])
+//
+// The @> and <@ operators are rewritten only if the containing side is apparently JSON, i.e., a JSON literal, a cast
+// to json or jsonb, or a call to a JSON function, since they are also the operators on arrays.
+// https://www.postgresql.org/docs/current/functions-json.html
+
+// jsonFunctionRenames are the functions of Postgres that are named differently in DuckDB, or defined as macros.
+var jsonFunctionRenames = map[string]string{
+	"json_build_object":         "json_object",
+	"jsonb_build_object":        "json_object",
+	"json_build_array":          "json_array",
+	"jsonb_build_array":         "json_array",
+	"json_agg":                  "json_group_array",
+	"jsonb_agg":                 "json_group_array",
+	"json_object_agg":           "json_group_object",
+	"jsonb_object_agg":          "json_group_object",
+	"to_jsonb":                  "to_json",
+	"jsonb_array_length":        "json_array_length",
+	"jsonb_pretty":              "json_pretty",
+	"json_typeof":               catalog.SchemaNameSYS + "." + catalog.MacroNameJSONTypeof,
+	"jsonb_typeof":              catalog.SchemaNameSYS + "." + catalog.MacroNameJSONTypeof,
+	"json_object_keys":          catalog.SchemaNameSYS + "." + catalog.MacroNameJSONObjectKeys,
+	"jsonb_object_keys":         catalog.SchemaNameSYS + "." + catalog.MacroNameJSONObjectKeys,
+	"json_array_elements":       catalog.SchemaNameSYS + "." + catalog.MacroNameJSONArrayElements,
+	"jsonb_array_elements":      catalog.SchemaNameSYS + "." + catalog.MacroNameJSONArrayElements,
+	"json_array_elements_text":  catalog.SchemaNameSYS + "." + catalog.MacroNameJSONArrayElementsText,
+	"jsonb_array_elements_text": catalog.SchemaNameSYS + "." + catalog.MacroNameJSONArrayElementsText,
+	"json_extract_path":         "json_extract",
+	"jsonb_extract_path":        "json_extract",
+	"json_extract_path_text":    "json_extract_string",
+	"jsonb_extract_path_text":   "json_extract_string",
+}
+
+// ConvertJSON rewrites the jsonb type, and the JSON operators and functions of Postgres in the query into those of
+// DuckDB. The query is left as it is if it cannot be tokenized.
+func ConvertJSON(sql string) string {
+	lower := strings.ToLower(sql)
+	if !strings.Contains(lower, "json") && !strings.ContainsAny(sql, "#?@") {
+		return sql
+	}
+	// Each rewrite changes the tokens, so the query is tokenized again after each, e.g., for nested operators.
+	for range 100 {
+		tokens, ok := tokenizeSQL(sql)
+		if !ok {
+			return sql
+		}
+		rewritten, ok := rewriteJSONOnce(sql, tokens)
+		if !ok {
+			return sql
+		}
+		sql = rewritten
+	}
+	return sql
+}
+
+// rewriteJSONOnce applies the first applicable rewrite to the query, and returns false if there is none.
+func rewriteJSONOnce(sql string, t sqlTokens) (string, bool) {
+	replace := func(start, end int, s string) (string, bool) {
+		return sql[:t[start].start] + s + sql[t[end].end:], true
+	}
+	text := func(start, end int) string {
+		return sql[t[start].start:t[end].end]
+	}
+
+	for i, tok := range t {
+		switch {
+		case tok.kind == tokenIdent && strings.EqualFold(tok.text, "jsonb") && !t.isFunctionName(i):
+			return replace(i, i, "json")
+
+		case tok.kind == tokenIdent && t.isFunctionName(i) && (i == 0 || t[i-1].text != "." || t.isPgCatalog(i-2)):
+			name := strings.ToLower(tok.text)
+			renamed, ok := jsonFunctionRenames[name]
+			if !ok {
+				continue
+			}
+			start := i
+			if i >= 2 && t[i-1].text == "." {
+				start = i - 2 // drop pg_catalog.
+			}
+			if strings.HasPrefix(name, "json") && strings.Contains(name, "extract_path") {
+				// The path elements are given as arguments, which are turned into a JSON path if they are constants.
+				end := t.matching(i + 1)
+				if end < 0 {
+					continue
+				}
+				args := t.splitArgs(i+2, end-1)
+				if len(args) < 2 {
+					continue
+				}
+				var path []any
+				for _, arg := range args[1:] {
+					if arg[0] != arg[1] || t[arg[0]].kind != tokenString {
+						path = nil
+						break
+					}
+					path = append(path, t[arg[0]].value)
+				}
+				if len(path) == 0 {
+					continue
+				}
+				return replace(start, end, renamed+"("+text(args[0][0], args[0][1])+", "+quoteSQLString(jsonPath(path))+")")
+			}
+			return replace(start, i, renamed)
+
+		case tok.kind == tokenOp && (tok.text == "#>" || tok.text == "#>>"):
+			if i+1 >= len(t) || t[i+1].kind != tokenString {
+				continue
+			}
+			path, err := parsePgArray(t[i+1].value)
+			if err != nil {
+				continue
+			}
+			op := "->"
+			if tok.text == "#>>" {
+				op = "->>"
+			}
+			return replace(i, i+1, op+" "+quoteSQLString(jsonPath(path)))
+
+		case tok.kind == tokenOp && (tok.text == "?" || tok.text == "?|" || tok.text == "?&"):
+			ls, re := t.operandStart(i-1), t.operandEnd(i+1)
+			if ls < 0 || re < 0 {
+				continue
+			}
+			right := text(i+1, re)
+			if tok.text != "?" && i+1 == re && t[re].kind == tokenString {
+				if list, err := pgArrayToDuckDBList(t[re].value); err == nil {
+					right = quoteSQLString(list) + "::VARCHAR[]"
+				}
+			}
+			fn := map[string]string{"?": "list_contains", "?|": "list_has_any", "?&": "list_has_all"}[tok.text]
+			return replace(ls, re, fn+"(json_keys("+text(ls, i-1)+"), "+right+")")
+
+		case tok.kind == tokenOp && (tok.text == "@>" || tok.text == "<@"):
+			ls, re := t.operandStart(i-1), t.operandEnd(i+1)
+			if ls < 0 || re < 0 {
+				continue
+			}
+			if !t.isJSONOperand(ls, i-1) && !t.isJSONOperand(i+1, re) {
+				continue
+			}
+			container, contained := text(ls, i-1), text(i+1, re)
+			if tok.text == "<@" {
+				container, contained = contained, container
+			}
+			return replace(ls, re, "json_contains("+container+", "+contained+")")
+		}
+	}
+	return sql, false
+}
+
+// isJSONOperand returns whether the operand between the tokens is apparently JSON, i.e., a JSON object or array
+// literal, a cast to json, or a call to a JSON function.
+func (t sqlTokens) isJSONOperand(start, end int) bool {
+	if start == end && t[start].kind == tokenString {
+		v := strings.TrimSpace(t[start].value)
+		return (strings.HasPrefix(v, "{") || strings.HasPrefix(v, "[")) && json.Valid([]byte(v))
+	}
+	if end-start >= 2 && t[end-1].text == "::" && strings.HasPrefix(strings.ToLower(t[end].text), "json") {
+		return true
+	}
+	if t[start].kind == tokenIdent && t.isFunctionName(start) && strings.Contains(strings.ToLower(t[start].text), "json") {
+		return true
+	}
+	return start+2 <= end && t[start].text == "(" && t.matching(start) == end && t.isJSONOperand(start+1, end-1)
+}
+
+// jsonPath returns the DuckDB JSON path of the path elements of Postgres, where integers are array indexes.
+func jsonPath(path []any) string {
+	var b strings.Builder
+	b.WriteString("$")
+	for _, element := range path {
+		key, _ := element.(string)
+		if key != "" && strings.Trim(key, "0123456789") == "" {
+			b.WriteString("[" + key + "]")
+			continue
+		}
+		b.WriteString(`."` + strings.ReplaceAll(strings.ReplaceAll(key, `\`, `\\`), `"`, `\"`) + `"`)
+	}
+	return b.String()
+}
+
+func quoteSQLString(s string) string {
+	return "'" + strings.ReplaceAll(s, "'", "''") + "'"
+}
+
+type tokenKind int
+
+const (
+	tokenIdent tokenKind = iota
+	tokenQuotedIdent
+	tokenString
+	tokenNumber
+	tokenParam
+	tokenOp
+	tokenPunct
+)
+
+// sqlToken is a token of a query. The value of a string is the unescaped content of the literal.
+type sqlToken struct {
+	kind       tokenKind
+	text       string
+	value      string
+	start, end int
+}
+
+type sqlTokens []sqlToken
+
+// pgOperatorChars are the characters that make up the operators of Postgres.
+const pgOperatorChars = "+-*/<>=~!@#%^&|`?"
+
+// tokenizeSQL splits a query into tokens, dropping the whitespace and the comments. It returns false if the query
+// is malformed, e.g., with an unterminated literal.
+func tokenizeSQL(sql string) (sqlTokens, bool) {
+	var tokens sqlTokens
+	for i := 0; i < len(sql); {
+		c := sql[i]
+		start := i
+		switch {
+		case c == ' ' || c == '\t' || c == '\r' || c == '\n' || c == '\f':
+			i++
+			continue
+		case strings.HasPrefix(sql[i:], "--"):
+			for i < len(sql) && sql[i] != '\n' {
+				i++
+			}
+			continue
+		case strings.HasPrefix(sql[i:], "/*"):
+			end := strings.Index(sql[i+2:], "*/")
+			if end < 0 {
+				return nil, false
+			}
+			i += end + 4
+			continue
+		case c == '\'' || ((c == 'E' || c == 'e') && i+1 < len(sql) && sql[i+1] == '\''):
+			escapes := c != '\''
+			if escapes {
+				i++
+			}
+			var b strings.Builder
+			for i++; ; i++ {
+				if i >= len(sql) {
+					return nil, false
+				}
+				if escapes && sql[i] == '\\' && i+1 < len(sql) {
+					i++
+					b.WriteByte(sql[i])
+					continue
+				}
+				if sql[i] == '\'' {
+					if i+1 < len(sql) && sql[i+1] == '\'' {
+						b.WriteByte('\'')
+						i++
+						continue
+					}
+					break
+				}
+				b.WriteByte(sql[i])
+			}
+			i++
+			tokens = append(tokens, sqlToken{kind: tokenString, text: sql[start:i], value: b.String(), start: start, end: i})
+		case c == '"':
+			end := strings.IndexByte(sql[i+1:], '"')
+			for end >= 0 && i+end+2 < len(sql) && sql[i+end+2] == '"' {
+				next := strings.IndexByte(sql[i+end+3:], '"')
+				if next < 0 {
+					end = -1
+					break
+				}
+				end += next + 2
+			}
+			if end < 0 {
+				return nil, false
+			}
+			i += end + 2
+			tokens = append(tokens, sqlToken{kind: tokenQuotedIdent, text: sql[start:i], start: start, end: i})
+		case c == '$':
+			j := i + 1
+			for j < len(sql) && (isIdentChar(sql[j]) && !(j == i+1 && sql[j] >= '0' && sql[j] <= '9')) {
+				j++
+			}
+			if j < len(sql) && sql[j] == '$' {
+				// A dollar-quoted string, e.g., $$text$$ or $tag$text$tag$
+				tag := sql[i : j+1]
+				end := strings.Index(sql[j+1:], tag)
+				if end < 0 {
+					return nil, false
+				}
+				i = j + 1 + end + len(tag)
+				tokens = append(tokens, sqlToken{kind: tokenString, text: sql[start:i], value: sql[j+1 : j+1+end], start: start, end: i})
+				continue
+			}
+			for i++; i < len(sql) && sql[i] >= '0' && sql[i] <= '9'; i++ {
+			}
+			tokens = append(tokens, sqlToken{kind: tokenParam, text: sql[start:i], start: start, end: i})
+		case c >= '0' && c <= '9' || c == '.' && i+1 < len(sql) && sql[i+1] >= '0' && sql[i+1] <= '9':
+			for i < len(sql) && (isIdentChar(sql[i]) || sql[i] == '.') {
+				i++
+			}
+			tokens = append(tokens, sqlToken{kind: tokenNumber, text: sql[start:i], start: start, end: i})
+		case isIdentChar(c):
+			for i < len(sql) && (isIdentChar(sql[i]) || sql[i] == '$') {
+				i++
+			}
+			tokens = append(tokens, sqlToken{kind: tokenIdent, text: sql[start:i], start: start, end: i})
+		case strings.HasPrefix(sql[i:], "::"):
+			i += 2
+			tokens = append(tokens, sqlToken{kind: tokenPunct, text: "::", start: start, end: i})
+		case strings.IndexByte(pgOperatorChars, c) >= 0:
+			for i < len(sql) && strings.IndexByte(pgOperatorChars, sql[i]) >= 0 &&
+				!strings.HasPrefix(sql[i:], "--") && !strings.HasPrefix(sql[i:], "/*") {
+				i++
+			}
+			// As in Postgres, a multi-character operator cannot end in + or - unless it contains one of ~!@#%^&|`?
+			op := sql[start:i]
+			for len(op) > 1 && strings.ContainsAny(op[len(op)-1:], "+-") && !strings.ContainsAny(op, "~!@#%^&|`?") {
+				op = op[:len(op)-1]
+			}
+			i = start + len(op)
+			tokens = append(tokens, sqlToken{kind: tokenOp, text: op, start: start, end: i})
+		default:
+			i++
+			tokens = append(tokens, sqlToken{kind: tokenPunct, text: sql[start:i], start: start, end: i})
+		}
+	}
+	return tokens, true
+}
+
+func isIdentChar(c byte) bool {
+	return c == '_' || c >= 'a' && c <= 'z' || c >= 'A' && c <= 'Z' || c >= '0' && c <= '9' || c >= 0x80
+}
+
+// nonFunctionKeywords are the keywords that may precede a parenthesized expression, which is not a function call.
+var nonFunctionKeywords = map[string]struct{}{
+	"all": {}, "and": {}, "any": {}, "as": {}, "between": {}, "by": {}, "case": {}, "distinct": {}, "else": {},
+	"exists": {}, "from": {}, "having": {}, "in": {}, "is": {}, "join": {}, "like": {}, "not": {}, "on": {},
+	"or": {}, "over": {}, "returning": {}, "select": {}, "set": {}, "some": {}, "then": {}, "using": {},
+	"values": {}, "when": {}, "where": {}, "with": {},
+}
+
+// isFunctionName returns whether the identifier at |i| is the name of a function being called.
+func (t sqlTokens) isFunctionName(i int) bool {
+	if i+1 >= len(t) || t[i+1].text != "(" || (t[i].kind != tokenIdent && t[i].kind != tokenQuotedIdent) {
+		return false
+	}
+	_, ok := nonFunctionKeywords[strings.ToLower(t[i].text)]
+	return !ok
+}
+
+// isPgCatalog returns whether the token at |i| is the pg_catalog schema.
+func (t sqlTokens) isPgCatalog(i int) bool {
+	return i >= 0 && strings.EqualFold(strings.Trim(t[i].text, `"`), "pg_catalog")
+}
+
+// matching returns the index of the bracket matching the one at |i|, searching forward for an opening bracket and
+// backward for a closing one, or -1 if there is none.
+func (t sqlTokens) matching(i int) int {
+	pairs := map[string]string{"(": ")", "[": "]", ")": "(", "]": "["}
+	if i < 0 || i >= len(t) || t[i].kind != tokenPunct {
+		return -1
+	}
+	other, ok := pairs[t[i].text]
+	if !ok {
+		return -1
+	}
+	step := 1
+	if t[i].text == ")" || t[i].text == "]" {
+		step = -1
+	}
+	depth := 0
+	for j := i; j >= 0 && j < len(t); j += step {
+		if t[j].kind != tokenPunct {
+			continue
+		}
+		switch t[j].text {
+		case t[i].text:
+			depth++
+		case other:
+			depth--
+			if depth == 0 {
+				return j
+			}
+		}
+	}
+	return -1
+}
+
+// splitArgs splits the tokens between |start| and |end| at the commas outside brackets, and returns the first and
+// the last tokens of each argument.
+func (t sqlTokens) splitArgs(start, end int) [][2]int {
+	var args [][2]int
+	argStart := start
+	for j := start; j <= end; j++ {
+		switch t[j].text {
+		case "(", "[":
+			if j = t.matching(j); j < 0 {
+				return nil
+			}
+		case ",":
+			args = append(args, [2]int{argStart, j - 1})
+			argStart = j + 1
+		}
+	}
+	if argStart <= end {
+		args = append(args, [2]int{argStart, end})
+	}
+	return args
+}
+
+// isOperandEnd returns whether the token at |i| may end an operand.
+func (t sqlTokens) isOperandEnd(i int) bool {
+	switch t[i].kind {
+	case tokenIdent:
+		_, ok := nonFunctionKeywords[strings.ToLower(t[i].text)]
+		return !ok
+	case tokenOp:
+		return false
+	case tokenPunct:
+		return t[i].text == ")" || t[i].text == "]"
+	default:
+		return true
+	}
+}
+
+// operandStart returns the index of the first token of the left operand of an operator, which ends at |end|, or -1.
+// The operand binds tighter than the operator, or as tight and on its left, such as casts, subscripts, function
+// calls, and the -> and ->> operators.
+func (t sqlTokens) operandStart(end int) int {
+	i := end
+	for {
+		if i < 0 || !t.isOperandEnd(i) {
+			return -1
+		}
+		switch t[i].text {
+		case ")", "]":
+			open := t.matching(i)
+			if open < 0 {
+				return -1
+			}
+			i = open
+			if open > 0 && (t[open].text == "[" && t.isOperandEnd(open-1) || t.isFunctionName(open-1)) {
+				// A subscript, an array constructor, or a function call
+				i = open - 1
+				continue
+			}
+		}
+		if i > 0 && (t[i-1].text == "." || t[i-1].text == "::" || t[i-1].text == "->" || t[i-1].text == "->>") {
+			i -= 2
+			continue
+		}
+		return i
+	}
+}
+
+// operandEnd returns the index of the last token of the right operand of an operator, which starts at |start|,
+// or -1. The operand binds tighter than the operator, such as casts, subscripts, and function calls.
+func (t sqlTokens) operandEnd(start int) int {
+	if start >= len(t) {
+		return -1
+	}
+	i := start
+	switch {
+	case t[i].text == "(" || t[i].text == "[":
+		if i = t.matching(i); i < 0 {
+			return -1
+		}
+	case t.isFunctionName(i):
+		if i = t.matching(i + 1); i < 0 {
+			return -1
+		}
+	case t[i].kind == tokenIdent && strings.EqualFold(t[i].text, "array") && i+1 < len(t) && t[i+1].text == "[":
+		if i = t.matching(i + 1); i < 0 {
+			return -1
+		}
+	case t[i].kind == tokenOp || t[i].kind == tokenPunct:
+		return -1
+	}
+	for i+1 < len(t) {
+		switch next := t[i+1].text; {
+		case next == "." && i+2 < len(t):
+			i += 2
+			if t.isFunctionName(i) {
+				if i = t.matching(i + 1); i < 0 {
+					return -1
+				}
+			}
+		case next == "[":
+			if i = t.matching(i + 1); i < 0 {
+				return -1
+			}
+		case next == "::" && i+2 < len(t):
+			i += 2
+			if i+1 < len(t) && t[i+1].text == "(" {
+				if i = t.matching(i + 1); i < 0 {
+					return -1
+				}
+			}
+		default:
+			return i
+		}
+	}
+	return i
+}
@@ -0,0 +1,57 @@
+package pgserver
+
+import "testing"
+
+func TestConvertJSON(t *testing.T) {
+	tests := []struct {
+		query string
+		want  string
+	}{
+		{
+			query: "CREATE TABLE t (id int, j jsonb)",
+			want:  "CREATE TABLE t (id int, j json)",
+		},
+		{
+			query: `SELECT '{"a": 1}'::jsonb, $1::JSONB, 'jsonb', "jsonb" FROM t`,
+			want:  `SELECT '{"a": 1}'::json, $1::json, 'jsonb', "jsonb" FROM t`,
+		},
+		{
+			query: "SELECT j #> '{a,0}', j #>> '{a,b}' FROM t",
+			want:  `SELECT j -> '$."a"[0]', j ->> '$."a"."b"' FROM t`,
+		},
+		{
+			query: `SELECT * FROM t WHERE j @> '{"a": 1}'`,
+			want:  `SELECT * FROM t WHERE json_contains(j, '{"a": 1}')`,
+		},
+		{
+			query: `SELECT '{"a": 1}' <@ j FROM t`,
+			want:  `SELECT json_contains(j, '{"a": 1}') FROM t`,
+		},
+		{
+			query: "SELECT ARRAY[1, 2] @> ARRAY[1]",
+			want:  "SELECT ARRAY[1, 2] @> ARRAY[1]",
+		},
+		{
+			query: "SELECT j ? 'a', j ?| array['a', 'b'], j ?& '{a,b}' FROM t",
+			want:  "SELECT list_contains(json_keys(j), 'a'), list_has_any(json_keys(j), array['a', 'b']), list_has_all(json_keys(j), '[a, b]'::VARCHAR[]) FROM t",
+		},
+		{
+			query: "SELECT j #> '{a}' ? 'b' FROM t",
+			want:  `SELECT list_contains(json_keys(j -> '$."a"'), 'b') FROM t`,
+		},
+		{
+			query: "SELECT jsonb_build_object('a', 1), pg_catalog.jsonb_typeof(j), json_agg(x) FROM t",
+			want:  "SELECT json_object('a', 1), __sys__.pg_json_typeof(j), json_group_array(x) FROM t",
+		},
+		{
+			query: "SELECT jsonb_extract_path(j, 'a', '0'), json_extract_path_text(j, 'b') FROM t",
+			want:  `SELECT json_extract(j, '$."a"[0]'), json_extract_string(j, '$."b"') FROM t`,
+		},
+	}
+
+	for _, tt := range tests {
+		if got := ConvertJSON(tt.query); got != tt.want {
+			t.Errorf("ConvertJSON(%q) = %q; want %q", tt.query, got, tt.want)
+		}
+	}
+}
@@ -16,6 +16,7 @@ package pgserver
 
 import (
 	"crypto/tls"
+	"crypto/x509"
 	"fmt"
 	"net"
 	"os"
@@ -25,17 +26,30 @@ import (
 	"github.com/dolthub/go-mysql-server/server"
 	"github.com/dolthub/vitess/go/mysql"
 	"github.com/dolthub/vitess/go/netutil"
+	"github.com/sirupsen/logrus"
 )
 
 var (
-	processID   = uint32(os.Getpid())
 	certificate tls.Certificate //TODO: move this into the mysql.ListenerConfig
+	clientCAs   *x509.CertPool  // Client certificates are verified against these CAs when they are given
+	requireSSL  bool            // Reject TCP connections without SSL
+	peerAuth    bool            // Authenticate the connections over the Unix domain socket by the OS users of the clients
+	hbaRules    []HBARule       // The host-based authentication rules, which are not checked if empty
+
+	insertBatchSize int // Group up to this many INSERT statements outside transaction blocks into a transaction
 )
 
+// resultFlushSize is the number of bytes of the rows of a result that are buffered before they are flushed to the
+// client. See spoolRowsCallback.
+var resultFlushSize = 64 << 10
+
 // Listener listens for connections to process PostgreSQL requests into Dolt requests.
 type Listener struct {
-	listener net.Listener
-	cfg      mysql.ListenerConfig
+	listener  net.Listener
+	socket     net.Listener // the Unix domain socket, if any
+	socketDir  string
+	socketPerm os.FileMode
+	cfg        mysql.ListenerConfig
 
 	engine *gms.Engine
 	sm     *server.SessionManager
@@ -50,6 +64,68 @@ func WithCertificate(cert tls.Certificate) ListenerOpt {
 	}
 }
 
+// WithClientCAs verifies the certificates presented by clients against the given CAs.
+func WithClientCAs(pool *x509.CertPool) ListenerOpt {
+	return func(l *Listener) {
+		clientCAs = pool
+	}
+}
+
+// WithRequireSSL rejects the TCP connections that do not request SSL. Unix domain sockets are not affected.
+func WithRequireSSL() ListenerOpt {
+	return func(l *Listener) {
+		requireSSL = true
+	}
+}
+
+// WithUnixSocketDir listens on a Unix domain socket in the given directory as well, which is named .s.PGSQL.<port>
+// as in Postgres, so that the local clients connect to it by default, e.g., psql without -h.
+func WithUnixSocketDir(dir string) ListenerOpt {
+	return func(l *Listener) {
+		l.socketDir = dir
+	}
+}
+
+// WithUnixSocketPermissions sets the access permissions of the Unix domain socket, as unix_socket_permissions of
+// Postgres, which are 0770 by default, so that only the users of the group of the server may connect.
+func WithUnixSocketPermissions(perm os.FileMode) ListenerOpt {
+	return func(l *Listener) {
+		l.socketPerm = perm
+	}
+}
+
+// WithPeerAuthentication authenticates the connections over the Unix domain socket by the operating system users of
+// the clients, who log in as the roles of the same names without passwords, as the peer method of Postgres.
+func WithPeerAuthentication() ListenerOpt {
+	return func(l *Listener) {
+		peerAuth = true
+	}
+}
+
+// WithHBARules authenticates the connections by the first of the host-based authentication rules that matches them,
+// and rejects the connections that match none. See hba.go.
+func WithHBARules(rules []HBARule) ListenerOpt {
+	return func(l *Listener) {
+		hbaRules = rules
+	}
+}
+
+// WithInsertBatchSize groups up to the given number of INSERT statements run outside transaction blocks into an
+// implicit transaction, which speeds up restoring plain SQL dumps. See insert_batch.go.
+func WithInsertBatchSize(size int) ListenerOpt {
+	return func(l *Listener) {
+		insertBatchSize = size
+	}
+}
+
+// WithResultFlushSize flushes the rows of a query result to the client whenever the given number of bytes are
+// buffered, instead of every 64 KiB.
+func WithResultFlushSize(size int) ListenerOpt {
+	return func(l *Listener) {
+		resultFlushSize = size
+	}
+}
+
 func WithEngine(engine *gms.Engine) ListenerOpt {
 	return func(l *Listener) {
 		l.engine = engine
@@ -75,8 +151,9 @@ func NewListener(listenerCfg mysql.ListenerConfig) (*Listener, error) {
 
 func NewListenerWithOpts(listenerCfg mysql.ListenerConfig, opts ...ListenerOpt) (*Listener, error) {
 	l := &Listener{
-		listener: listenerCfg.Listener,
-		cfg:      listenerCfg,
+		listener:   listenerCfg.Listener,
+		socketPerm: 0770,
+		cfg:        listenerCfg,
 	}
 
 	for _, opt := range opts {
@@ -86,10 +163,40 @@ func NewListenerWithOpts(listenerCfg mysql.ListenerConfig, opts ...ListenerOpt)
 	return l, nil
 }
 
+// listenUnixSocket listens on the Unix domain socket of the path. A socket file left behind by a server that did not
+// exit cleanly is replaced, but one that another server is still listening on is not.
+func listenUnixSocket(path string, perm os.FileMode) (net.Listener, error) {
+	if _, err := os.Stat(path); err == nil {
+		if conn, err := net.Dial("unix", path); err == nil {
+			conn.Close()
+			return nil, fmt.Errorf("the Unix domain socket %s is in use by another server", path)
+		}
+		if err := os.Remove(path); err != nil {
+			return nil, err
+		}
+	}
+	l, err := net.Listen("unix", path)
+	if err != nil {
+		return nil, err
+	}
+	if err := os.Chmod(path, perm); err != nil {
+		l.Close()
+		return nil, err
+	}
+	return l, nil
+}
+
 // Accept handles incoming connections.
 func (l *Listener) Accept(server *Server) {
+	if l.socket != nil {
+		go l.accept(server, l.socket)
+	}
+	l.accept(server, l.listener)
+}
+
+func (l *Listener) accept(server *Server, listener net.Listener) {
 	for {
-		conn, err := l.listener.Accept()
+		conn, err := listener.Accept()
 		if err != nil {
 			if err.Error() == "use of closed network connection" {
 				break
@@ -98,6 +205,15 @@ func (l *Listener) Accept(server *Server) {
 			continue
 		}
 
+		// The credentials are read from the socket itself, before it is wrapped below, for the peer authentication by
+		// either the server option or a host-based authentication rule.
+		var peerUser string
+		if listener == l.socket {
+			if peerUser, err = peerUserName(conn); err != nil {
+				logrus.WithError(err).Warnln("Unable to get the user of the peer of the Unix domain socket")
+			}
+		}
+
 		// Configure read timeouts on this connection
 		// TODO: use timeouts from the live server values
 		if l.cfg.ConnReadTimeout != 0 || l.cfg.ConnWriteTimeout != 0 {
@@ -105,6 +221,7 @@ func (l *Listener) Accept(server *Server) {
 		}
 
 		connectionHandler := NewConnectionHandler(conn, l.cfg.Handler, l.engine, l.sm, l.connID.Add(1), server)
+		connectionHandler.peerUser = peerUser
 		go connectionHandler.HandleConnection()
 	}
 }
@@ -112,6 +229,9 @@ func (l *Listener) Accept(server *Server) {
 // Close stops the handling of incoming connections.
 func (l *Listener) Close() {
 	_ = l.listener.Close()
+	if l.socket != nil {
+		_ = l.socket.Close() // which removes the socket file
+	}
 }
 
 // Addr returns the address that the listener is listening on.
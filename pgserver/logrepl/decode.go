@@ -26,8 +26,8 @@ func init() {
 	}
 }
 
-// decodeToArrow decodes Postgres text format data and appends directly to Arrow builder
-func decodeToArrow(typeMap *pgtype.Map, columnType *pglogrepl.RelationMessageColumn, data []byte, format int16, builder array.Builder) (int, error) {
+// DecodeToArrow decodes Postgres text or binary format data and appends directly to Arrow builder
+func DecodeToArrow(typeMap *pgtype.Map, columnType *pglogrepl.RelationMessageColumn, data []byte, format int16, builder array.Builder) (int, error) {
 	if data == nil {
 		builder.AppendNull()
 		return 0, nil
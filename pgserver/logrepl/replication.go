@@ -54,6 +54,7 @@ type LogicalReplicator struct {
 	messageReceived bool
 	stop            chan struct{}
 	mu              *sync.Mutex
+	status          ReplicationStatus
 
 	logger *logrus.Entry
 }
@@ -74,6 +75,39 @@ func NewLogicalReplicator(subscription, primaryDns string) (*LogicalReplicator,
 	}, nil
 }
 
+// ReplicationStatus is the progress of a running replication, shown in pg_stat_subscription and
+// pg_stat_wal_receiver.
+type ReplicationStatus struct {
+	SlotName string
+	// StartLSN is the position the replication started from.
+	StartLSN pglogrepl.LSN
+	// ReceivedLSN is the last WAL position received from the primary.
+	ReceivedLSN pglogrepl.LSN
+	// WrittenLSN is the commit LSN of the last transaction written to the replica, and WrittenCommitTime is the time
+	// it was committed on the primary.
+	WrittenLSN        pglogrepl.LSN
+	WrittenCommitTime time.Time
+	// LastMsgSendTime and LastMsgReceiptTime are the times the last message was sent by the primary and received.
+	LastMsgSendTime    time.Time
+	LastMsgReceiptTime time.Time
+	// ReportedLSN is the last WAL position reported to the primary as flushed, at ReportedTime.
+	ReportedLSN  pglogrepl.LSN
+	ReportedTime time.Time
+}
+
+// Status returns the progress of the replication, and whether it is running.
+func (r *LogicalReplicator) Status() (ReplicationStatus, bool) {
+	r.mu.Lock()
+	defer r.mu.Unlock()
+	return r.status, r.running
+}
+
+func (r *LogicalReplicator) updateStatus(update func(status *ReplicationStatus)) {
+	r.mu.Lock()
+	defer r.mu.Unlock()
+	update(&r.status)
+}
+
 // PrimaryDns returns the DNS for the primary database. Not suitable for RPCs used in replication e.g.
 // StartReplication. See ReplicationDns.
 func (r *LogicalReplicator) PrimaryDns() string {
@@ -166,6 +200,9 @@ type replicationState struct {
 	// This becomes the lastWrittenLSN when we commit the transaction to the database.
 	lastCommitLSN pglogrepl.LSN
 
+	// lastCommitTimestamp is the time the transaction of the last commit message was committed on the primary.
+	lastCommitTimestamp time.Time
+
 	// inStream tracks the state of the replication stream. When we receive a StreamStartMessage, we set inStream to
 	// true, and then back to false when we receive a StreamStopMessage.
 	inStream bool
@@ -180,6 +217,7 @@ type replicationState struct {
 	processMessages bool
 
 	typeMap   *pgtype.Map
+	types     map[uint32]*pglogrepl.TypeMessageV2 // the non-builtin types described by the Type messages
 	relations map[uint32]*pglogrepl.RelationMessageV2
 	schemas   map[uint32]sql.Schema
 	keys      map[uint32][]uint16 // relationID -> slice of key column indices
@@ -192,6 +230,10 @@ type replicationState struct {
 	dirtyTxn        bool      // true if we have uncommitted changes
 	dirtyStream     bool      // true if the binlog stream does not end with a commit
 	inTxnStmtID     uint64    // statement ID within transaction
+
+	// origin is the replication origin of the current transaction, which is set by an Origin message if the
+	// transaction was replicated to the primary from another node. It is recorded as the txn_server of the changes.
+	origin string
 }
 
 func (state *replicationState) reset(ctx *sql.Context, slotName string, lsn pglogrepl.LSN) {
@@ -199,6 +241,7 @@ func (state *replicationState) reset(ctx *sql.Context, slotName string, lsn pglo
 		state.deltas.Close()
 	}
 	if state.relations != nil {
+		clear(state.types)
 		clear(state.relations)
 		clear(state.schemas)
 		clear(state.keys)
@@ -209,6 +252,7 @@ func (state *replicationState) reset(ctx *sql.Context, slotName string, lsn pglo
 		lastWrittenLSN: lsn,
 		lastCommitLSN:  lsn,
 		typeMap:        pgtype.NewMap(),
+		types:          map[uint32]*pglogrepl.TypeMessageV2{},
 		relations:      map[uint32]*pglogrepl.RelationMessageV2{},
 		schemas:        map[uint32]sql.Schema{},
 		keys:           map[uint32][]uint16{},
@@ -284,6 +328,10 @@ func (r *LogicalReplicator) StartReplication(sqlCtx *sql.Context, slotName strin
 		}
 
 		r.logger.Debugf("Sent Standby status message with WALWritePosition = %s, WALApplyPosition = %s\n", state.lastReceivedLSN+1, state.lastWrittenLSN+1)
+		r.updateStatus(func(status *ReplicationStatus) {
+			status.ReportedLSN = state.lastWrittenLSN
+			status.ReportedTime = time.Now()
+		})
 		nextStandbyMessageDeadline = time.Now().Add(standbyMessageTimeout)
 		return nil
 	}
@@ -293,6 +341,12 @@ func (r *LogicalReplicator) StartReplication(sqlCtx *sql.Context, slotName strin
 	r.running = true
 	r.messageReceived = false
 	r.stop = make(chan struct{})
+	r.status = ReplicationStatus{
+		SlotName:    slotName,
+		StartLSN:    lastWrittenLsn,
+		ReceivedLSN: lastWrittenLsn,
+		WrittenLSN:  lastWrittenLsn,
+	}
 	r.mu.Unlock()
 
 	ticker := time.NewTicker(r.flushInterval)
@@ -408,6 +462,7 @@ func (r *LogicalReplicator) StartReplication(sqlCtx *sql.Context, slotName strin
 
 				r.logger.Debugln("Primary Keepalive Message =>", "ServerWALEnd:", pkm.ServerWALEnd, "ServerTime:", pkm.ServerTime, "ReplyRequested:", pkm.ReplyRequested)
 				state.lastReceivedLSN = pkm.ServerWALEnd
+				r.updateReceivedStatus(state, pkm.ServerTime)
 
 				if pkm.ReplyRequested {
 					return sendStandbyStatusUpdate(state)
@@ -423,6 +478,7 @@ func (r *LogicalReplicator) StartReplication(sqlCtx *sql.Context, slotName strin
 					// TODO: do we need more than one handler, one for each connection?
 					return handleErrWithRetry(err, true)
 				}
+				r.updateReceivedStatus(state, xld.ServerTime)
 				if commit {
 					return sendStandbyStatusUpdate(state)
 				}
@@ -449,6 +505,15 @@ func (r *LogicalReplicator) StartReplication(sqlCtx *sql.Context, slotName strin
 	}
 }
 
+// updateReceivedStatus records the receipt of a message sent by the primary at |sendTime|.
+func (r *LogicalReplicator) updateReceivedStatus(state *replicationState, sendTime time.Time) {
+	r.updateStatus(func(status *ReplicationStatus) {
+		status.ReceivedLSN = state.lastReceivedLSN
+		status.LastMsgSendTime = sendTime
+		status.LastMsgReceiptTime = time.Now()
+	})
+}
+
 func (r *LogicalReplicator) rollback(ctx *sql.Context) error {
 	defer adapter.CloseTxn(ctx)
 	txn := adapter.TryGetTxn(ctx)
@@ -715,7 +780,13 @@ func (r *LogicalReplicator) processMessage(
 		schema := make(sql.Schema, len(logicalMsg.Columns))
 		var keys []uint16
 		for i, col := range logicalMsg.Columns {
-			pgType, err := pgtypes.NewPostgresType(state.typeMap, col.DataType, col.TypeModifier)
+			dataType := col.DataType
+			if _, ok := state.typeMap.TypeForOID(dataType); !ok && (dataType >= pgtypes.FirstNormalObjectID || state.types[dataType] != nil) {
+				// The user-defined types, e.g., the enum types, are replicated as text, and so are the other types
+				// described by Type messages, e.g., the domains of information_schema.
+				dataType = pgtype.TextOID
+			}
+			pgType, err := pgtypes.NewPostgresType(state.typeMap, dataType, col.TypeModifier)
 			if err != nil {
 				return false, err
 			}
@@ -750,6 +821,7 @@ func (r *LogicalReplicator) processMessage(
 
 		state.processMessages = true
 		state.currentTransactionLSN = logicalMsg.FinalLSN
+		state.origin = ""
 
 		// Start a new transaction or extend existing batch
 		extend, reason := r.mayExtendBatchTxn(state)
@@ -769,6 +841,7 @@ func (r *LogicalReplicator) processMessage(
 		r.logger.Debugf("CommitMessage: %v", logicalMsg)
 
 		state.lastCommitLSN = logicalMsg.CommitLSN
+		state.lastCommitTimestamp = logicalMsg.CommitTime
 		state.commitCount += 1
 
 		extend, reason := r.mayExtendBatchTxn(state)
@@ -812,12 +885,10 @@ func (r *LogicalReplicator) processMessage(
 		case pglogrepl.UpdateMessageTupleTypeOld:
 			err = r.append(state, logicalMsg.RelationID, logicalMsg.OldTuple.Columns, binlog.DeleteRowEvent, binlog.UpdateRowEvent, false)
 		default:
-			// No old tuple provided; it means the key columns are unchanged.
-			// It's fine not to append a delete event to the delta in this case.
-			// However, the delta appender implements an optimization that
-			// uses INSERT instead of UPSERT+DELETE or DELETE+INSERT when there is no deletion in a batch.
-			// We need to enforce the latter code path here because the deletion count is zero.
-			err = r.append(state, logicalMsg.RelationID, nil, binlog.DeleteRowEvent, binlog.UpdateRowEvent, true)
+			// No old tuple provided; it means the key columns are unchanged, so the old row has the keys of the new one.
+			// The delete is appended anyway, so that the update is a delete followed by an insert in the delta
+			// as the other updates, which is how the captured changes tell an update from an insert.
+			err = r.append(state, logicalMsg.RelationID, logicalMsg.NewTuple.Columns, binlog.DeleteRowEvent, binlog.UpdateRowEvent, false)
 		}
 		if err != nil {
 			return false, err
@@ -864,12 +935,23 @@ func (r *LogicalReplicator) processMessage(
 			return false, nil
 		}
 
-		r.logger.Debugf("Truncate message: xid %d\n", logicalMsg.Xid)
+		r.logger.Debugf("Truncate message: xid %d, options %d\n", logicalMsg.Xid, logicalMsg.Option)
 
-		// Flush the delta buffer first
-		r.flushDeltaBuffer(state, nil, nil, delta.DMLStmtFlushReason)
+		// Flush the delta buffer first, so that the changes before the TRUNCATE are truncated as well.
+		conn, err := adapter.GetCatalogConn(state.replicaCtx)
+		if err != nil {
+			return false, err
+		}
+		tx, err := adapter.GetCatalogTxn(state.replicaCtx, nil)
+		if err != nil {
+			return false, err
+		}
+		if err := r.flushDeltaBuffer(state, conn, tx, delta.DMLStmtFlushReason); err != nil {
+			return false, err
+		}
 
-		// Truncate the tables
+		// Truncate the tables. The tables truncated by CASCADE are listed by the primary, and the sequences
+		// restarted by RESTART IDENTITY are not replicated.
 		for _, relationID := range logicalMsg.RelationIDs {
 			if err := r.truncate(state, relationID); err != nil {
 				return false, err
@@ -881,9 +963,14 @@ func (r *LogicalReplicator) processMessage(
 		state.inTxnStmtID += 1
 
 	case *pglogrepl.TypeMessageV2:
-		r.logger.Debugf("typeMessage for xid %d\n", logicalMsg.Xid)
+		// The columns of the type are replicated as text, see the RelationMessageV2 case.
+		r.logger.Debugf("Type message: xid %d, type %s.%s (OID %d)", logicalMsg.Xid, logicalMsg.Namespace, logicalMsg.Name, logicalMsg.DataType)
+		state.types[logicalMsg.DataType] = logicalMsg
 	case *pglogrepl.OriginMessage:
-		r.logger.Debugf("originMessage for xid %s\n", logicalMsg.Name)
+		r.logger.Debugf("Origin message: origin %s, commit LSN %s", logicalMsg.Name, logicalMsg.CommitLSN)
+		if state.processMessages {
+			state.origin = logicalMsg.Name
+		}
 	case *pglogrepl.LogicalDecodingMessageV2:
 		r.logger.Debugf("Logical decoding message: %q, %q, %d", logicalMsg.Prefix, logicalMsg.Content, logicalMsg.Xid)
 	case *pglogrepl.StreamStartMessageV2:
@@ -1019,6 +1106,10 @@ func (r *LogicalReplicator) commitOngoingTxn(state *replicationState, flushReaso
 	state.lastCommitTime = time.Now()
 
 	state.lastWrittenLSN = state.lastCommitLSN
+	r.updateStatus(func(status *ReplicationStatus) {
+		status.WrittenLSN = state.lastWrittenLSN
+		status.WrittenCommitTime = state.lastCommitTimestamp
+	})
 
 	return nil
 }
@@ -1044,12 +1135,6 @@ func (r *LogicalReplicator) append(state *replicationState, relationID uint32, t
 	}
 
 	if len(tuple) == 0 {
-		// The only case where we can have an empty tuple is when
-		// we're deleting+inserting a row and the key columns are unchanged.
-		if eventType == binlog.UpdateRowEvent && actionType == binlog.DeleteRowEvent {
-			appender.ObserveEvents(binlog.UpdateRowEvent, 1)
-			return nil
-		}
 		return fmt.Errorf("empty tuple data")
 	}
 
@@ -1063,7 +1148,7 @@ func (r *LogicalReplicator) append(state *replicationState, relationID uint32, t
 
 	actions.Append(int8(actionType))
 	txnTags.AppendNull()
-	txnServers.Append([]byte(""))
+	txnServers.Append([]byte(state.origin))
 	txnGroups.AppendNull()
 	txnSeqNumbers.Append(uint64(state.currentTransactionLSN))
 	txnStmtOrdinals.Append(state.inTxnStmtID)
@@ -1088,7 +1173,7 @@ func (r *LogicalReplicator) append(state *replicationState, relationID uint32, t
 		case pglogrepl.TupleDataTypeNull:
 			builder.AppendNull()
 		case pglogrepl.TupleDataTypeText, pglogrepl.TupleDataTypeBinary:
-			length, err := decodeToArrow(state.typeMap, metadata, col.Data, tupleDataFormat(col.DataType), builder)
+			length, err := DecodeToArrow(state.typeMap, metadata, col.Data, tupleDataFormat(col.DataType), builder)
 			if err != nil {
 				return err
 			}
@@ -637,6 +637,61 @@ var replicationTests = []ReplicationTest{
 			},
 		},
 	},
+	{
+		Name: "Truncate tables in a transaction",
+		SetUpScript: []string{
+			dropReplicationSlot,
+			createReplicationSlot,
+			startReplication,
+			"/* replica */ drop table if exists public.test",
+			"/* replica */ drop table if exists public.test2",
+			"drop table if exists public.test",
+			"drop table if exists public.test2",
+			"CREATE TABLE public.test (id INT primary key, name varchar(10))",
+			"CREATE TABLE public.test2 (id INT primary key, name varchar(10))",
+			"INSERT INTO public.test2 VALUES (1, 'one')",
+			"BEGIN",
+			"INSERT INTO public.test VALUES (1, 'one'), (2, 'two')",
+			"UPDATE public.test2 SET name = 'uno' WHERE id = 1",
+			"TRUNCATE TABLE public.test, public.test2",
+			"INSERT INTO public.test VALUES (3, 'three')",
+			"COMMIT",
+			waitForCatchup,
+		},
+		Assertions: []ScriptTestAssertion{
+			{
+				Query: "/* replica */ SELECT * FROM public.test order by id",
+				Expected: []sql.Row{
+					{int32(3), "three"},
+				},
+			},
+			{
+				Query:    "/* replica */ SELECT count(*) FROM public.test2",
+				Expected: []sql.Row{{int64(0)}},
+			},
+		},
+	},
+	{
+		Name: "Types described by Type messages",
+		SetUpScript: []string{
+			dropReplicationSlot,
+			createReplicationSlot,
+			startReplication,
+			"/* replica */ drop table if exists public.test",
+			"drop table if exists public.test",
+			"CREATE TABLE public.test (id INT primary key, name information_schema.sql_identifier)",
+			"INSERT INTO public.test VALUES (1, 'one')",
+			waitForCatchup,
+		},
+		Assertions: []ScriptTestAssertion{
+			{
+				Query: "/* replica */ SELECT * FROM public.test order by id",
+				Expected: []sql.Row{
+					{int32(1), "one"},
+				},
+			},
+		},
+	},
 }
 
 func TestReplication(t *testing.T) {
@@ -8,6 +8,8 @@ import (
 	"github.com/apecloud/myduckserver/catalog"
 	"github.com/dolthub/go-mysql-server/sql"
 	"github.com/jackc/pglogrepl"
+	"slices"
+	"strings"
 	"sync"
 )
 
@@ -103,6 +105,33 @@ func UpdateSubscriptions(ctx *sql.Context) error {
 	return nil
 }
 
+// SubscriptionStatus is a subscription with the status of its replication, which is nil if it is not running.
+type SubscriptionStatus struct {
+	Subscription string
+	Conn         string
+	Status       *ReplicationStatus
+}
+
+// SubscriptionStatuses returns the statuses of the subscriptions, ordered by their names.
+func SubscriptionStatuses() []SubscriptionStatus {
+	var statuses []SubscriptionStatus
+	subscriptionMap.Range(func(key, value interface{}) bool {
+		subscription, _ := value.(*Subscription)
+		s := SubscriptionStatus{Subscription: subscription.Subscription, Conn: subscription.Conn}
+		if subscription.Replicator != nil {
+			if status, running := subscription.Replicator.Status(); running {
+				s.Status = &status
+			}
+		}
+		statuses = append(statuses, s)
+		return true
+	})
+	slices.SortFunc(statuses, func(a, b SubscriptionStatus) int {
+		return strings.Compare(a.Subscription, b.Subscription)
+	})
+	return statuses
+}
+
 func CreateSubscription(ctx *sql.Context, name, conn, pub, lsn string, enabled bool) error {
 	_, err := adapter.ExecCatalogInTxn(ctx, catalog.InternalTables.PgSubscription.UpsertStmt(), name, conn, pub, lsn, enabled)
 	return err
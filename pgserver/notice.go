@@ -0,0 +1,87 @@
+package pgserver
+
+import (
+	"context"
+	"fmt"
+	"slices"
+	"strings"
+
+	"github.com/dolthub/go-mysql-server/sql"
+	"github.com/jackc/pgx/v5/pgproto3"
+)
+
+// clientMessageLevels are the levels of client_min_messages from the lowest to the highest. A notice is sent to the
+// client only if its level is at least client_min_messages, except that INFO messages are always sent, as in Postgres.
+var clientMessageLevels = []string{"debug5", "debug4", "debug3", "debug2", "debug1", "log", "notice", "warning", "error"}
+
+// clientMessageLevel returns the rank of the severity in clientMessageLevels.
+func clientMessageLevel(severity ErrorResponseSeverity) int {
+	switch severity {
+	case ErrorResponseSeverity_Debug:
+		return slices.Index(clientMessageLevels, "debug1")
+	case ErrorResponseSeverity_Info:
+		return len(clientMessageLevels)
+	default:
+		return slices.Index(clientMessageLevels, strings.ToLower(string(severity)))
+	}
+}
+
+// clientMinMessages returns the rank of the client_min_messages setting of the session.
+func (h *ConnectionHandler) clientMinMessages() int {
+	ctx, err := h.duckHandler.NewContext(context.Background(), h.mysqlConn, "")
+	if err != nil {
+		return clientMessageLevel(ErrorResponseSeverity_Notice)
+	}
+	v, err := ctx.GetSessionVariable(ctx, "client_min_messages")
+	if err != nil {
+		return clientMessageLevel(ErrorResponseSeverity_Notice)
+	}
+	if level := slices.Index(clientMessageLevels, strings.ToLower(fmt.Sprint(v))); level >= 0 {
+		return level
+	}
+	return clientMessageLevel(ErrorResponseSeverity_Notice)
+}
+
+// sendNotice sends a NoticeResponse message with the severity, SQLSTATE code, and message, unless its severity is
+// below the client_min_messages setting of the session.
+func (h *ConnectionHandler) sendNotice(severity ErrorResponseSeverity, code string, message string) error {
+	if clientMessageLevel(severity) < h.clientMinMessages() {
+		return nil
+	}
+	return h.send(&pgproto3.NoticeResponse{
+		Severity: string(severity),
+		Code:     code,
+		Message:  message,
+	})
+}
+
+// forwardWarnings sends the warnings raised in the session while running a statement or loading data as
+// NoticeResponse messages, and clears them. The notes are sent as NOTICE, and the other warnings as WARNING.
+func (h *ConnectionHandler) forwardWarnings(ctx *sql.Context) {
+	// The warnings are listed from the most recent.
+	warnings := ctx.Session.Warnings()
+	if len(warnings) == 0 {
+		return
+	}
+	ctx.ClearWarnings()
+	for i := len(warnings) - 1; i >= 0; i-- {
+		warning := warnings[i]
+		severity, code := ErrorResponseSeverity_Warning, "01000" // warning
+		if strings.EqualFold(warning.Level, "Note") {
+			severity, code = ErrorResponseSeverity_Notice, "00000" // successful_completion
+		}
+		if err := h.sendNotice(severity, code, warning.Message); err != nil {
+			h.logger.WithError(err).Warnln("Failed to send a notice")
+			return
+		}
+	}
+}
+
+// skipTransactionStatement completes a transaction statement that has no effect in the current state of the
+// transaction, with a warning.
+func (h *ConnectionHandler) skipTransactionStatement(statement ConvertedStatement, code string, message string) error {
+	if err := h.sendNotice(ErrorResponseSeverity_Warning, code, message); err != nil {
+		return err
+	}
+	return h.send(makeCommandComplete(statement.Tag, 0))
+}
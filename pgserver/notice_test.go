@@ -0,0 +1,16 @@
+package pgserver
+
+import (
+	"testing"
+
+	"github.com/stretchr/testify/require"
+)
+
+func TestClientMessageLevel(t *testing.T) {
+	require.Less(t, clientMessageLevel(ErrorResponseSeverity_Debug), clientMessageLevel(ErrorResponseSeverity_Log))
+	require.Less(t, clientMessageLevel(ErrorResponseSeverity_Log), clientMessageLevel(ErrorResponseSeverity_Notice))
+	require.Less(t, clientMessageLevel(ErrorResponseSeverity_Notice), clientMessageLevel(ErrorResponseSeverity_Warning))
+	require.Less(t, clientMessageLevel(ErrorResponseSeverity_Warning), clientMessageLevel(ErrorResponseSeverity_Error))
+	// INFO messages are sent regardless of client_min_messages.
+	require.Greater(t, clientMessageLevel(ErrorResponseSeverity_Info), clientMessageLevel(ErrorResponseSeverity_Error))
+}
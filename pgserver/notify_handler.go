@@ -0,0 +1,221 @@
+package pgserver
+
+import (
+	"fmt"
+	"regexp"
+	"strings"
+	"sync"
+
+	"github.com/dolthub/go-mysql-server/sql"
+	"github.com/jackc/pgx/v5/pgproto3"
+)
+
+// This file handles the SQL statements for asynchronous notifications. It supports:
+//
+// 1. Listening on a channel:
+//    LISTEN channel;
+//
+// 2. Stopping listening on a channel or on all of them:
+//    UNLISTEN channel;
+//    UNLISTEN *;
+//
+// 3. Sending a notification to the connections listening on a channel:
+//    NOTIFY channel [, 'payload'];
+//    SELECT pg_notify('channel', 'payload');
+//    A notification sent in a transaction is delivered when the transaction is committed, and dropped if it is
+//    rolled back. A listening connection receives the notifications when it is idle, or at the end of its current
+//    transaction.
+
+// NotifyAction represents the type of notification statement.
+type NotifyAction string
+
+const (
+	NotifyListen   NotifyAction = "LISTEN"
+	NotifyUnlisten NotifyAction = "UNLISTEN"
+	NotifyNotify   NotifyAction = "NOTIFY"
+)
+
+// NotifyConfig represents a parsed notification statement.
+type NotifyConfig struct {
+	Action  NotifyAction
+	Channel string // Empty for UNLISTEN *
+	Payload string
+}
+
+// maxNotifyPayloadLength is the maximum length of a notification payload in bytes, as in Postgres.
+const maxNotifyPayloadLength = 8000
+
+// listenRegex matches a LISTEN or UNLISTEN statement and captures the channel, or * for UNLISTEN.
+var listenRegex = regexp.MustCompile(`(?i)^(LISTEN|UNLISTEN)\s+(\w+|"[^"]+"|\*)$`)
+
+// notifyRegex matches a NOTIFY statement and captures the channel and the optional payload.
+var notifyRegex = regexp.MustCompile(`(?is)^NOTIFY\s+(\w+|"[^"]+")(?:\s*,\s*'((?:[^']|'')*)')?$`)
+
+// pgNotifyRegex matches "select pg_catalog.pg_notify('channel', 'payload');" and captures the channel and the payload.
+var pgNotifyRegex = regexp.MustCompile(`(?is)^\s*select\s+(pg_catalog\.)?pg_notify\(\s*'((?:[^']|'')*)'\s*,\s*('((?:[^']|'')*)'|null)\s*\)\s*;?\s*$`)
+
+// parseNotifySQL parses the given SQL statement and returns a NotifyConfig, or nil if it is not a notification statement.
+func parseNotifySQL(query string) (*NotifyConfig, error) {
+	query = strings.TrimSpace(sql.RemoveSpaceAndDelimiter(RemoveComments(query), ';'))
+	var config NotifyConfig
+	switch {
+	case listenRegex.MatchString(query):
+		matches := listenRegex.FindStringSubmatch(query)
+		config.Action = NotifyAction(strings.ToUpper(matches[1]))
+		if matches[2] == "*" {
+			if config.Action == NotifyListen {
+				return nil, fmt.Errorf("syntax error at or near \"*\"")
+			}
+		} else {
+			config.Channel = foldIdentifier(matches[2])
+		}
+
+	case notifyRegex.MatchString(query):
+		matches := notifyRegex.FindStringSubmatch(query)
+		config.Action = NotifyNotify
+		config.Channel = foldIdentifier(matches[1])
+		config.Payload = strings.ReplaceAll(matches[2], "''", "'")
+
+	default:
+		return nil, nil
+	}
+	return &config, nil
+}
+
+// notificationHub keeps track of the connections listening on each channel of the server.
+type notificationHub struct {
+	mu        sync.Mutex
+	listeners map[string]map[*ConnectionHandler]struct{} // channel -> listening connections
+}
+
+func (n *notificationHub) listen(channel string, h *ConnectionHandler) {
+	n.mu.Lock()
+	defer n.mu.Unlock()
+	if n.listeners == nil {
+		n.listeners = make(map[string]map[*ConnectionHandler]struct{})
+	}
+	if n.listeners[channel] == nil {
+		n.listeners[channel] = make(map[*ConnectionHandler]struct{})
+	}
+	n.listeners[channel][h] = struct{}{}
+}
+
+func (n *notificationHub) unlisten(channel string, h *ConnectionHandler) {
+	n.mu.Lock()
+	defer n.mu.Unlock()
+	delete(n.listeners[channel], h)
+	if len(n.listeners[channel]) == 0 {
+		delete(n.listeners, channel)
+	}
+}
+
+// notify delivers the notification to all the connections listening on its channel.
+func (n *notificationHub) notify(notification *pgproto3.NotificationResponse) {
+	n.mu.Lock()
+	targets := make([]*ConnectionHandler, 0, len(n.listeners[notification.Channel]))
+	for h := range n.listeners[notification.Channel] {
+		targets = append(targets, h)
+	}
+	n.mu.Unlock()
+
+	for _, h := range targets {
+		h.deliverNotification(notification)
+	}
+}
+
+// executeNotifyStatement executes a LISTEN, UNLISTEN, or NOTIFY statement.
+func (h *ConnectionHandler) executeNotifyStatement(statement ConvertedStatement) error {
+	config := statement.NotifyConfig
+	switch config.Action {
+	case NotifyListen:
+		if err := h.listen(config.Channel); err != nil {
+			return err
+		}
+	case NotifyUnlisten:
+		h.unlisten(config.Channel)
+	case NotifyNotify:
+		if err := h.notify(config.Channel, config.Payload); err != nil {
+			return err
+		}
+	default:
+		return fmt.Errorf("unsupported notification statement: %s", statement.String)
+	}
+	return h.send(makeCommandComplete(string(config.Action), 0))
+}
+
+// listen starts listening on the channel.
+func (h *ConnectionHandler) listen(channel string) error {
+	if h.server == nil {
+		return fmt.Errorf("LISTEN is not supported by this connection")
+	}
+	h.listening[channel] = struct{}{}
+	h.server.notifications.listen(channel, h)
+	return nil
+}
+
+// unlisten stops listening on the channel, or on all the channels if |channel| is empty.
+func (h *ConnectionHandler) unlisten(channel string) {
+	for listening := range h.listening {
+		if channel == "" || channel == listening {
+			delete(h.listening, listening)
+			h.server.notifications.unlisten(listening, h)
+		}
+	}
+}
+
+// notify sends a notification to the connections listening on the channel, including this one. In a transaction,
+// the notification is queued until the transaction is committed, and a duplicate of a queued one is dropped.
+func (h *ConnectionHandler) notify(channel, payload string) error {
+	if channel == "" {
+		return fmt.Errorf("channel name cannot be empty")
+	}
+	if len(payload) >= maxNotifyPayloadLength {
+		return fmt.Errorf("payload string too long")
+	}
+	if h.server == nil {
+		return fmt.Errorf("NOTIFY is not supported by this connection")
+	}
+	notification := &pgproto3.NotificationResponse{
+		PID:     h.mysqlConn.ConnectionID,
+		Channel: channel,
+		Payload: payload,
+	}
+	if !h.inTransaction {
+		h.server.notifications.notify(notification)
+		return nil
+	}
+	for _, queued := range h.outgoingNotifications {
+		if queued.Channel == channel && queued.Payload == payload {
+			return nil
+		}
+	}
+	h.outgoingNotifications = append(h.outgoingNotifications, notification)
+	return nil
+}
+
+// sendQueuedNotifications sends the notifications queued in the transaction when it ends, or drops them if it is
+// rolled back.
+func (h *ConnectionHandler) sendQueuedNotifications(commit bool) {
+	if commit {
+		for _, notification := range h.outgoingNotifications {
+			h.server.notifications.notify(notification)
+		}
+	}
+	h.outgoingNotifications = nil
+}
+
+// deliverNotification sends the notification to the client right away if the connection is idle. Otherwise, it is
+// sent before the next ReadyForQuery outside a transaction. It may be called from the goroutine of any connection.
+func (h *ConnectionHandler) deliverNotification(notification *pgproto3.NotificationResponse) {
+	h.sendMu.Lock()
+	defer h.sendMu.Unlock()
+	if !h.idle {
+		h.incomingNotifications = append(h.incomingNotifications, notification)
+		return
+	}
+	h.backend.Send(notification)
+	if err := h.backend.Flush(); err != nil {
+		// The message loop of the connection finds out that the connection is broken by itself.
+		h.logger.WithError(err).Warnln("Failed to deliver a notification")
+	}
+}
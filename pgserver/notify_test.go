@@ -0,0 +1,98 @@
+package pgserver
+
+import (
+	"context"
+	"strconv"
+	"testing"
+	"time"
+
+	"github.com/apecloud/myduckserver/testutil"
+	"github.com/jackc/pgx/v5/pgconn"
+	"github.com/stretchr/testify/require"
+)
+
+func TestListenNotify(t *testing.T) {
+	// Setup MyDuck Server
+	testDir := testutil.CreateTestDir(t)
+	testEnv := testutil.NewTestEnv()
+	err := testutil.StartDuckSqlServer(t, testDir, nil, testEnv)
+	require.NoError(t, err)
+	defer testutil.StopDuckSqlServer(t, testEnv.DuckProcess)
+	dsn := "postgresql://postgres@localhost:" + strconv.Itoa(testEnv.DuckPgPort) + "/postgres"
+	ctx := context.Background()
+
+	notifications := make(chan *pgconn.Notification, 16)
+	config, err := pgconn.ParseConfig(dsn)
+	require.NoError(t, err)
+	config.OnNotification = func(_ *pgconn.PgConn, n *pgconn.Notification) {
+		notifications <- n
+	}
+	listener, err := pgconn.ConnectConfig(ctx, config)
+	require.NoError(t, err)
+	defer listener.Close(ctx)
+
+	notifier, err := pgconn.Connect(ctx, dsn)
+	require.NoError(t, err)
+	defer notifier.Close(ctx)
+
+	exec := func(conn *pgconn.PgConn, query string) {
+		_, err := conn.Exec(ctx, query).ReadAll()
+		require.NoError(t, err, query)
+	}
+	// expect waits for the next notifications, which are delivered while the listener is idle.
+	expect := func(payloads ...string) {
+		for _, payload := range payloads {
+			waitCtx, cancel := context.WithTimeout(ctx, 5*time.Second)
+			if len(notifications) == 0 {
+				require.NoError(t, listener.WaitForNotification(waitCtx))
+			}
+			cancel()
+			n := <-notifications
+			require.Equal(t, "events", n.Channel)
+			require.Equal(t, payload, n.Payload)
+		}
+		// No other notification is delivered before the listener's next query completes.
+		exec(listener, "SELECT 1")
+		require.Empty(t, notifications)
+	}
+
+	exec(listener, "LISTEN events")
+	exec(notifier, "NOTIFY events, 'hello'")
+	expect("hello")
+
+	// A connection is notified by itself too, and the PID identifies the notifying connection.
+	exec(listener, "NOTIFY Events")
+	n := <-notifications
+	require.Equal(t, listener.PID(), n.PID)
+	require.Empty(t, n.Payload)
+
+	// The notifications of a transaction are sent at COMMIT without duplicates, or dropped by ROLLBACK.
+	exec(notifier, "BEGIN")
+	exec(notifier, "NOTIFY events, 'it''s committed'")
+	exec(notifier, "NOTIFY events, 'it''s committed'")
+	exec(notifier, "SELECT pg_notify('events', 'second')")
+	exec(listener, "SELECT 1")
+	require.Empty(t, notifications)
+	exec(notifier, "COMMIT")
+	expect("it's committed", "second")
+
+	exec(notifier, "BEGIN")
+	exec(notifier, "NOTIFY events, 'dropped'")
+	exec(notifier, "ROLLBACK")
+	exec(notifier, "NOTIFY events, 'after rollback'")
+	expect("after rollback")
+
+	// A listening connection in a transaction receives the notifications at its end.
+	exec(listener, "BEGIN")
+	exec(notifier, "NOTIFY events, 'deferred'")
+	exec(listener, "SELECT 1")
+	require.Empty(t, notifications)
+	exec(listener, "COMMIT")
+	require.Len(t, notifications, 1)
+	require.Equal(t, "deferred", (<-notifications).Payload)
+
+	// Nothing is delivered after UNLISTEN
+	exec(listener, "UNLISTEN *")
+	exec(notifier, "NOTIFY events, 'ignored'")
+	expect()
+}
@@ -0,0 +1,35 @@
+package pgserver
+
+import (
+	"fmt"
+	"net"
+	"os/user"
+	"strconv"
+)
+
+// peerUserName returns the name of the operating system user of the client on the other end of a Unix domain socket.
+func peerUserName(conn net.Conn) (string, error) {
+	unixConn, ok := conn.(*net.UnixConn)
+	if !ok {
+		return "", fmt.Errorf("not a Unix domain socket: %T", conn)
+	}
+	raw, err := unixConn.SyscallConn()
+	if err != nil {
+		return "", err
+	}
+	var uid uint32
+	var uidErr error
+	if err := raw.Control(func(fd uintptr) {
+		uid, uidErr = peerUID(int(fd))
+	}); err != nil {
+		return "", err
+	}
+	if uidErr != nil {
+		return "", uidErr
+	}
+	u, err := user.LookupId(strconv.FormatUint(uint64(uid), 10))
+	if err != nil {
+		return "", err
+	}
+	return u.Username, nil
+}
@@ -0,0 +1,12 @@
+package pgserver
+
+import "golang.org/x/sys/unix"
+
+// peerUID returns the user ID of the process on the other end of the Unix domain socket.
+func peerUID(fd int) (uint32, error) {
+	cred, err := unix.GetsockoptUcred(fd, unix.SOL_SOCKET, unix.SO_PEERCRED)
+	if err != nil {
+		return 0, err
+	}
+	return cred.Uid, nil
+}
@@ -0,0 +1,10 @@
+//go:build !linux && !darwin
+
+package pgserver
+
+import "errors"
+
+// peerUID returns the user ID of the process on the other end of the Unix domain socket.
+func peerUID(fd int) (uint32, error) {
+	return 0, errors.New("peer authentication is not supported on this platform")
+}
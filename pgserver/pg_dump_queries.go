@@ -0,0 +1,253 @@
+package pgserver
+
+import (
+	"context"
+	"regexp"
+	"strings"
+
+	"github.com/apecloud/myduckserver/catalog"
+)
+
+// pg_dump reads the definitions of the objects from pg_catalog with queries that depend on many functions and
+// types of Postgres, such as acldefault() and the casts to regclass. These queries are recognized here, as those
+// of the psql commands are, and replaced with equivalent queries on the pg_catalog views of MyDuck Server, so that
+// the dump can be restored into Postgres. The queries on the objects that DuckDB does not support, e.g.,
+// extensions, functions, and triggers, return no rows.
+
+// pgDumpEmptyQueries are the prefixes of the queries of pg_dump on the objects that DuckDB does not support.
+var pgDumpEmptyQueries = []string{
+	"SELECT set_config(name, 'view, foreign-table', false) FROM pg_settings ",
+	"SELECT x.tableoid, x.oid, x.extname, ",
+	"SELECT p.tableoid, p.oid, p.proname, ",
+	"SELECT p.tableoid, p.oid, p.proname AS aggname, ",
+	"SELECT tableoid, oid, typname, typnamespace, ",
+	"SELECT tableoid, oid, lanname, ",
+	"SELECT tableoid, oid, oprname, ",
+	"SELECT tableoid, oid, amname, amtype, ",
+	"SELECT tableoid, oid, opcmethod, ",
+	"SELECT tableoid, oid, opfmethod, ",
+	"SELECT tableoid, oid, prsname, ",
+	"SELECT tableoid, oid, tmplname, ",
+	"SELECT tableoid, oid, dictname, ",
+	"SELECT tableoid, oid, cfgname, ",
+	"SELECT tableoid, oid, fdwname, ",
+	"SELECT tableoid, oid, srvname, ",
+	"SELECT oid, tableoid, defaclrole, ",
+	"SELECT tableoid, oid, collname, ",
+	"SELECT tableoid, oid, conname, connamespace, conowner FROM pg_conversion",
+	"SELECT tableoid, oid, castsource, ",
+	"SELECT tableoid, oid, trftype, ",
+	"SELECT inhrelid, inhparent FROM pg_inherits",
+	"SELECT e.tableoid, e.oid, evtname, ",
+	"SELECT partrelid FROM pg_partitioned_table ",
+	"SELECT tableoid, oid, stxname, ",
+	"SELECT t.tgrelid, t.tgname, ",
+	"SELECT tableoid, oid, rulename, ",
+	"SELECT pol.oid, pol.tableoid, ",
+	"SELECT p.tableoid, p.oid, p.pubname, ",
+	"SELECT tableoid, oid, prpubid, ",
+	"SELECT tableoid, oid, pnpubid, ",
+	"WITH RECURSIVE w AS ( SELECT d1.objid, d2.refobjid, ",
+	"SELECT oid, lomowner, lomacl, ",
+	"SELECT DISTINCT attrelid FROM pg_attribute WHERE attacl IS NOT NULL",
+	"SELECT objoid, classoid, objsubid, privtype, initprivs FROM pg_init_privs",
+	"SELECT label, provider, classoid, objoid, objsubid FROM pg_catalog.pg_seclabels ",
+}
+
+// pgDumpEmptyQueryRegex matches any of pgDumpEmptyQueries.
+var pgDumpEmptyQueryRegex = func() *regexp.Regexp {
+	prefixes := make([]string, len(pgDumpEmptyQueries))
+	for i, prefix := range pgDumpEmptyQueries {
+		prefixes[i] = regexp.QuoteMeta(prefix)
+	}
+	return regexp.MustCompile(`^(?:` + strings.Join(prefixes, "|") + `)`)
+}()
+
+// pgDumpSequenceNameRegex matches the qualified name of a sequence, as quoted by pg_dump.
+var pgDumpSequenceNameRegex = regexp.MustCompile(`^("(?:[^"]|"")+"|[^".]+)\.("(?:[^"]|"")+"|[^".]+)$`)
+
+// unquoteIdentifier removes the double quotes around an identifier, if any.
+func unquoteIdentifier(name string) string {
+	if len(name) > 1 && strings.HasPrefix(name, `"`) && strings.HasSuffix(name, `"`) {
+		return strings.ReplaceAll(name[1:len(name)-1], `""`, `"`)
+	}
+	return name
+}
+
+var pgDumpQueries = []clientQuery{
+	{
+		pattern: pgDumpEmptyQueryRegex,
+		query: func(h *ConnectionHandler, matches []string) (string, error) {
+			return `SELECT NULL WHERE false;`, nil
+		},
+	},
+	// The schemas and the relations matching the patterns of -n and -t
+	{
+		pattern: regexp.MustCompile(`^SELECT oid FROM pg_catalog\.pg_namespace n WHERE (.*)$`),
+		query: func(h *ConnectionHandler, matches []string) (string, error) {
+			filters, err := h.psqlFilters(matches[1])
+			if err != nil {
+				return "", err
+			}
+			return `SELECT n.oid FROM __sys__.pg_namespace n WHERE true` + filters + `;`, nil
+		},
+	},
+	{
+		pattern: regexp.MustCompile(`^SELECT c\.oid FROM pg_catalog\.pg_class c LEFT JOIN pg_catalog\.pg_namespace n ON n\.oid OPERATOR\(pg_catalog\.=\) c\.relnamespace WHERE c\.relkind OPERATOR\(pg_catalog\.=\) ANY \(array\[([^\]]*)\]\)(.*)$`),
+		query: func(h *ConnectionHandler, matches []string) (string, error) {
+			filters, err := h.psqlFilters(matches[2])
+			if err != nil {
+				return "", err
+			}
+			return `SELECT c.oid FROM __sys__.pg_class c LEFT JOIN __sys__.pg_namespace n ON n.oid = c.relnamespace` +
+				` WHERE c.relkind IN (` + matches[1] + `)` + filters + `;`, nil
+		},
+	},
+	// The owners of the objects
+	{
+		pattern: regexp.MustCompile(`^SELECT oid, rolname FROM pg_catalog\.pg_roles ORDER BY 1$`),
+		query: func(h *ConnectionHandler, matches []string) (string, error) {
+			provider, err := h.catalogProvider()
+			if err != nil {
+				return "", err
+			}
+			return `SELECT oid, rolname FROM ` + newRoleStore(provider, provider.Storage()).table(catalog.InternalTables.PGAuthID) + ` ORDER BY 1;`, nil
+		},
+	},
+	{
+		pattern: regexp.MustCompile(`^SELECT n\.tableoid, n\.oid, n\.nspname, n\.nspowner, n\.nspacl, `),
+		query: func(h *ConnectionHandler, matches []string) (string, error) {
+			return `SELECT 2615 AS tableoid, n.oid, n.nspname, n.nspowner, n.nspacl, NULL AS acldefault FROM __sys__.pg_namespace n;`, nil
+		},
+	},
+	{
+		pattern: regexp.MustCompile(`^SELECT c\.tableoid, c\.oid, c\.relname, c\.relnamespace, c\.relkind, `),
+		query: func(h *ConnectionHandler, matches []string) (string, error) {
+			// relhastriggers makes pg_dump look up the foreign keys of the table.
+			return `SELECT 1259 AS tableoid, c.oid, c.relname, c.relnamespace, c.relkind, c.reltype, c.relowner, c.relchecks,` +
+				` c.relhasindex, c.relhasrules, c.relpages,` +
+				` EXISTS (SELECT 1 FROM __sys__.pg_constraint WHERE contype = 'f' AND conrelid = c.oid) AS relhastriggers,` +
+				` c.relpersistence, c.reloftype, c.relacl, NULL AS acldefault, 0 AS foreignserver, c.relfrozenxid,` +
+				` 0 AS tfrozenxid, 0 AS toid, 0 AS toastpages, NULL AS toast_reloptions, NULL AS owning_tab, NULL AS owning_col,` +
+				` NULL AS reltablespace, false AS relhasoids, c.relispopulated, c.relreplident, c.relrowsecurity,` +
+				` c.relforcerowsecurity, c.relminmxid, 0 AS tminmxid, NULL AS reloptions, NULL AS checkoption,` +
+				` CASE c.relkind WHEN 'r' THEN 'heap' END AS amname, false AS is_identity_sequence, c.relispartition AS ispartition` +
+				` FROM __sys__.pg_class c WHERE c.relkind IN ('r', 'S', 'v') AND c.oid >= 16384 ORDER BY c.oid;`, nil
+		},
+	},
+	// The columns, the default values, and the check constraints of the tables
+	{
+		pattern: regexp.MustCompile(`^SELECT a\.attrelid, a\.attnum, a\.attname, .* FROM unnest\('\{([\d,]*)\}'::pg_catalog\.oid\[\]\) AS src\(tbloid\) JOIN pg_catalog\.pg_attribute a `),
+		query: func(h *ConnectionHandler, matches []string) (string, error) {
+			return `SELECT a.attrelid, a.attnum, a.attname, a.atttypmod, a.attstattarget, a.attstorage, t.typstorage, a.attnotnull,` +
+				` a.atthasdef, a.attisdropped, a.attlen, a.attalign, a.attislocal, __sys__.format_type(a.atttypid, a.atttypmod) AS atttypname,` +
+				` '' AS attoptions, 0 AS attcollation, '' AS attfdwoptions, a.attcompression, a.attidentity, NULL AS attmissingval, a.attgenerated` +
+				` FROM __sys__.pg_attribute a LEFT JOIN __sys__.pg_type t ON t.oid = a.atttypid` +
+				` WHERE list_contains([` + matches[1] + `], a.attrelid) AND a.attnum > 0 ORDER BY a.attrelid, a.attnum;`, nil
+		},
+	},
+	{
+		pattern: regexp.MustCompile(`^SELECT a\.tableoid, a\.oid, adrelid, adnum, pg_catalog\.pg_get_expr\(adbin, adrelid\) AS adsrc FROM unnest\('\{([\d,]*)\}'`),
+		query: func(h *ConnectionHandler, matches []string) (string, error) {
+			return `SELECT 2604 AS tableoid, a.oid, a.adrelid, a.adnum, a.adbin AS adsrc FROM __sys__.pg_attrdef a` +
+				` WHERE list_contains([` + matches[1] + `], a.adrelid) ORDER BY a.adrelid, a.adnum;`, nil
+		},
+	},
+	{
+		pattern: regexp.MustCompile(`^SELECT c\.tableoid, c\.oid, conrelid, conname, pg_catalog\.pg_get_constraintdef\(c\.oid\) AS consrc, conislocal, convalidated FROM unnest\('\{([\d,]*)\}'`),
+		query: func(h *ConnectionHandler, matches []string) (string, error) {
+			return `SELECT 2606 AS tableoid, c.oid, c.conrelid, c.conname, __sys__.pg_get_constraintdef(c.oid) AS consrc, c.conislocal, c.convalidated` +
+				` FROM __sys__.pg_constraint c WHERE list_contains([` + matches[1] + `], c.conrelid) AND c.contype = 'c'` +
+				` ORDER BY c.conrelid, c.conname;`, nil
+		},
+	},
+	// The indexes and the foreign keys of the tables
+	{
+		pattern: regexp.MustCompile(`^SELECT t\.tableoid, t\.oid, i\.indrelid, t\.relname AS indexname, .* FROM unnest\('\{([\d,]*)\}'::pg_catalog\.oid\[\]\) AS src\(tbloid\) JOIN pg_catalog\.pg_index i `),
+		query: func(h *ConnectionHandler, matches []string) (string, error) {
+			// indkey is an int2vector, whose elements are separated by spaces.
+			return `SELECT 1259 AS tableoid, t.oid, i.indrelid, t.relname AS indexname, __sys__.pg_get_indexdef(i.indexrelid) AS indexdef,` +
+				` array_to_string(i.indkey, ' ') AS indkey, i.indisclustered, c.contype, c.conname, c.condeferrable, c.condeferred,` +
+				` 2606 AS contableoid, c.oid AS conoid, __sys__.pg_get_constraintdef(c.oid, false) AS condef, NULL AS tablespace,` +
+				` NULL AS indreloptions, i.indisreplident, 0 AS parentidx, i.indnkeyatts, i.indnatts, NULL AS indstatcols,` +
+				` NULL AS indstatvals, i.indnullsnotdistinct` +
+				` FROM __sys__.pg_index i JOIN __sys__.pg_class t ON t.oid = i.indexrelid` +
+				` LEFT JOIN __sys__.pg_constraint c ON c.conrelid = i.indrelid AND c.conindid = i.indexrelid AND c.contype IN ('p', 'u')` +
+				` WHERE list_contains([` + matches[1] + `], i.indrelid) ORDER BY i.indrelid, indexname;`, nil
+		},
+	},
+	{
+		pattern: regexp.MustCompile(`^SELECT c\.tableoid, c\.oid, conrelid, conname, confrelid, conindid, pg_catalog\.pg_get_constraintdef\(c\.oid\) AS condef FROM unnest\('\{([\d,]*)\}'`),
+		query: func(h *ConnectionHandler, matches []string) (string, error) {
+			// The dump is restored with an empty search path, so the referenced table is qualified with its schema.
+			return `SELECT 2606 AS tableoid, c.oid, c.conrelid, c.conname, c.confrelid, c.conindid,` +
+				` replace(__sys__.pg_get_constraintdef(c.oid), ' REFERENCES ', ' REFERENCES ' || n.nspname || '.') AS condef` +
+				` FROM __sys__.pg_constraint c JOIN __sys__.pg_class r ON r.oid = c.confrelid JOIN __sys__.pg_namespace n ON n.oid = r.relnamespace` +
+				` WHERE list_contains([` + matches[1] + `], c.conrelid) AND c.contype = 'f' ORDER BY c.conrelid, c.conname;`, nil
+		},
+	},
+	// The dependencies between the objects, which decide the order of the objects in the dump
+	{
+		pattern: regexp.MustCompile(`^SELECT classid, objid, refclassid, refobjid, deptype FROM pg_depend WHERE deptype != 'p' AND deptype != 'e' `),
+		query: func(h *ConnectionHandler, matches []string) (string, error) {
+			return `SELECT classid, objid, refclassid, refobjid, deptype FROM __sys__.pg_depend WHERE deptype NOT IN ('p', 'e') ORDER BY 1, 2;`, nil
+		},
+	},
+	{
+		pattern: regexp.MustCompile(`^SELECT count\(\*\) FROM pg_subscription WHERE subdbid = `),
+		query: func(h *ConnectionHandler, matches []string) (string, error) {
+			return `SELECT 0 AS count;`, nil
+		},
+	},
+	{
+		pattern: regexp.MustCompile(`^SELECT description, classoid, objoid, objsubid FROM pg_catalog\.pg_description ORDER BY classoid, objoid, objsubid$`),
+		query: func(h *ConnectionHandler, matches []string) (string, error) {
+			// pg_dump resets the comment of the public schema unless it is the one given by initdb.
+			return `SELECT description, classoid, objoid, objsubid FROM __sys__.pg_description` +
+				` UNION ALL SELECT 'standard public schema', 2615, n.oid, 0 FROM __sys__.pg_namespace n` +
+				` WHERE n.nspname = 'public' AND n.oid NOT IN (SELECT objoid FROM __sys__.pg_description WHERE classoid = 2615)` +
+				` ORDER BY classoid, objoid, objsubid;`, nil
+		},
+	},
+	{
+		pattern: regexp.MustCompile(`^SELECT pg_catalog\.current_schemas\(false\)$`),
+		query: func(h *ConnectionHandler, matches []string) (string, error) {
+			ctx, err := h.duckHandler.NewContext(context.Background(), h.mysqlConn, "")
+			if err != nil {
+				return "", err
+			}
+			path, err := SearchPath(ctx)
+			if err != nil {
+				return "", err
+			}
+			return `SELECT ` + quoteString("{"+strings.Join(path, ",")+"}") + ` AS current_schemas;`, nil
+		},
+	},
+	// The definitions of the views and the sequences, and the values of the sequences
+	{
+		pattern: regexp.MustCompile(`^SELECT pg_catalog\.pg_get_viewdef\('(\d+)'::pg_catalog\.oid\) AS viewdef$`),
+		query: func(h *ConnectionHandler, matches []string) (string, error) {
+			return `SELECT __sys__.pg_get_viewdef(` + matches[1] + `) AS viewdef;`, nil
+		},
+	},
+	{
+		pattern: regexp.MustCompile(`^SELECT format_type\(seqtypid, NULL\), seqstart, seqincrement, seqmax, seqmin, seqcache, seqcycle FROM pg_catalog\.pg_sequence WHERE seqrelid = '(\d+)'::oid$`),
+		query: func(h *ConnectionHandler, matches []string) (string, error) {
+			return `SELECT 'bigint' AS format_type, start_value AS seqstart, increment_by AS seqincrement, max_value AS seqmax,` +
+				` min_value AS seqmin, 1 AS seqcache, cycle AS seqcycle FROM duckdb_sequences()` +
+				` WHERE database_name = current_database() AND 16384 + sequence_oid = ` + matches[1] + `;`, nil
+		},
+	},
+	{
+		pattern: regexp.MustCompile(`^SELECT last_value, is_called FROM (\S+)$`),
+		query: func(h *ConnectionHandler, matches []string) (string, error) {
+			name := pgDumpSequenceNameRegex.FindStringSubmatch(matches[1])
+			if name == nil {
+				return "", nil
+			}
+			return `SELECT COALESCE(last_value, start_value) AS last_value, last_value IS NOT NULL AS is_called FROM duckdb_sequences()` +
+				` WHERE database_name = current_database() AND schema_name = ` + quoteString(unquoteIdentifier(name[1])) +
+				` AND sequence_name = ` + quoteString(unquoteIdentifier(name[2])) + `;`, nil
+		},
+	},
+}
@@ -17,7 +17,10 @@ package pgconfig
 
 import (
 	"fmt"
+	"math"
 	"regexp"
+	"slices"
+	"strconv"
 	"strings"
 	"time"
 
@@ -25,6 +28,7 @@ import (
 
 	"github.com/dolthub/go-mysql-server/sql"
 	"github.com/dolthub/go-mysql-server/sql/variables"
+	"github.com/dolthub/vitess/go/vt/proto/query"
 )
 
 // doltConfigParameters is a list of Dolt-specific configuration parameters that can be used in SET statement.
@@ -60,6 +64,7 @@ var _ sql.SystemVariable = (*Parameter)(nil)
 type Parameter struct {
 	Name         string
 	Default      any
+	Unit         string // The unit of a numeric parameter, such as "ms" or "kB", if it has one
 	Category     string
 	ShortDesc    string
 	Context      ParameterContext
@@ -128,10 +133,128 @@ func (p *Parameter) SetValue(val any, global bool) (sql.SystemVarValue, error) {
 	if p.IsReadOnly() {
 		return sql.SystemVarValue{}, ErrCannotChangeAtRuntime.New(p.Name)
 	}
-	// TODO: Do parsing of units for memory and time parameters
+	if s, ok := val.(string); ok && p.Unit != "" {
+		converted, err := convertUnit(s, p.Unit, p.VarType() == "real")
+		if err != nil {
+			return sql.SystemVarValue{}, ErrInvalidValue.New(p.Name, s)
+		}
+		val = converted
+	}
 	return p.InitValue(val, global)
 }
 
+// VarType returns the type of the parameter as shown in the vartype column of pg_settings.
+func (p *Parameter) VarType() string {
+	switch p.Type.Type() {
+	case query.Type_INT8:
+		return "bool"
+	case query.Type_ENUM:
+		return "enum"
+	case query.Type_INT64, query.Type_UINT64:
+		return "integer"
+	case query.Type_FLOAT64:
+		return "real"
+	default:
+		return "string"
+	}
+}
+
+// FormatValue formats the value of the parameter as Postgres does, e.g., "on" or "off" for a boolean parameter.
+func (p *Parameter) FormatValue(val any) string {
+	if p.VarType() == "bool" {
+		switch v := val.(type) {
+		case int8:
+			return boolString(v != 0)
+		case int64:
+			return boolString(v != 0)
+		case bool:
+			return boolString(v)
+		}
+	}
+	return fmt.Sprintf("%v", val)
+}
+
+func boolString(b bool) string {
+	if b {
+		return "on"
+	}
+	return "off"
+}
+
+// unitRegex matches a numeric value with an optional unit, e.g., "5s" or "1.5 MB".
+var unitRegex = regexp.MustCompile(`^\s*([+-]?(?:\d+\.?\d*|\.\d+)(?:[eE][+-]?\d+)?)\s*([a-zA-Z]*)\s*$`)
+
+// memoryUnits and timeUnits are the units of the parameters, in bytes and microseconds respectively.
+var (
+	memoryUnits = map[string]float64{"B": 1, "kB": 1 << 10, "8kB": 8 << 10, "MB": 1 << 20, "GB": 1 << 30, "TB": 1 << 40}
+	timeUnits   = map[string]float64{"us": 1, "ms": 1e3, "s": 1e6, "min": 60e6, "h": 3600e6, "d": 86400e6}
+)
+
+// convertUnit converts a value such as "5s" to a number in the unit of the parameter, e.g., 5000 for "ms", which is
+// rounded to an integer unless |isReal| is set. A value without a unit is already in the unit of the parameter.
+func convertUnit(value string, unit string, isReal bool) (any, error) {
+	matches := unitRegex.FindStringSubmatch(value)
+	if matches == nil {
+		return nil, fmt.Errorf("invalid value %q", value)
+	}
+	if matches[2] == "" {
+		return value, nil
+	}
+	n, err := strconv.ParseFloat(matches[1], 64)
+	if err != nil {
+		return nil, err
+	}
+	units := timeUnits
+	if _, ok := memoryUnits[unit]; ok {
+		units = memoryUnits
+	}
+	from, ok1 := units[matches[2]]
+	to, ok2 := units[unit]
+	if !ok1 || !ok2 {
+		return nil, fmt.Errorf("invalid unit %q", matches[2])
+	}
+	if isReal {
+		return n * from / to, nil
+	}
+	return int64(math.Round(n * from / to)), nil
+}
+
+// reportedParameters are the parameters whose changes are reported to the client by ParameterStatus messages.
+// https://www.postgresql.org/docs/current/protocol-flow.html#PROTOCOL-ASYNC
+var reportedParameters = map[string]bool{
+	"application_name":              true,
+	"client_encoding":               true,
+	"datestyle":                     true,
+	"default_transaction_read_only": true,
+	"in_hot_standby":                true,
+	"integer_datetimes":             true,
+	"intervalstyle":                 true,
+	"is_superuser":                  true,
+	"scram_iterations":              true,
+	"server_encoding":               true,
+	"server_version":                true,
+	"session_authorization":         true,
+	"standard_conforming_strings":   true,
+	"timezone":                      true,
+}
+
+// IsReportedParameter returns true if the changes of the given parameter are reported to the client.
+func IsReportedParameter(name string) bool {
+	return reportedParameters[strings.ToLower(name)]
+}
+
+// Parameters returns all the Postgres configuration parameters, sorted by name.
+func Parameters() []*Parameter {
+	params := make([]*Parameter, 0, len(postgresConfigParameters))
+	for _, sysVar := range postgresConfigParameters {
+		params = append(params, sysVar.(*Parameter))
+	}
+	slices.SortFunc(params, func(a, b *Parameter) int {
+		return strings.Compare(strings.ToLower(a.Name), strings.ToLower(b.Name))
+	})
+	return params
+}
+
 // IsReadOnly implements sql.SystemVariable.
 func (p *Parameter) IsReadOnly() bool {
 	switch strings.ToLower(p.Name) {
@@ -0,0 +1,176 @@
+package pgserver
+
+import (
+	"context"
+	"fmt"
+	"strconv"
+	"strings"
+	"testing"
+
+	"github.com/apecloud/myduckserver/testutil"
+	"github.com/jackc/pgx/v5/pgconn"
+	"github.com/jackc/pgx/v5/pgproto3"
+	"github.com/stretchr/testify/require"
+)
+
+// receiveUntilReady receives the messages up to the next ReadyForQuery, and describes each of them.
+func receiveUntilReady(t *testing.T, frontend *pgproto3.Frontend) []string {
+	received, _ := receiveWithTxStatus(t, frontend)
+	return received
+}
+
+// receiveWithTxStatus is receiveUntilReady that also returns the transaction status of the ReadyForQuery.
+func receiveWithTxStatus(t *testing.T, frontend *pgproto3.Frontend) ([]string, byte) {
+	var received []string
+	for {
+		msg, err := frontend.Receive()
+		require.NoError(t, err)
+		switch msg := msg.(type) {
+		case *pgproto3.ReadyForQuery:
+			return received, msg.TxStatus
+		case *pgproto3.DataRow:
+			received = append(received, "DataRow "+string(msg.Values[0]))
+		case *pgproto3.CommandComplete:
+			received = append(received, "CommandComplete "+string(msg.CommandTag))
+		case *pgproto3.ErrorResponse:
+			received = append(received, "ErrorResponse "+msg.Message)
+		default:
+			received = append(received, strings.TrimPrefix(fmt.Sprintf("%T", msg), "*pgproto3."))
+		}
+	}
+}
+
+func TestPortalSuspension(t *testing.T) {
+	// Setup MyDuck Server
+	testDir := testutil.CreateTestDir(t)
+	testEnv := testutil.NewTestEnv()
+	err := testutil.StartDuckSqlServer(t, testDir, nil, testEnv)
+	require.NoError(t, err)
+	defer testutil.StopDuckSqlServer(t, testEnv.DuckProcess)
+	dsn := "postgresql://postgres@localhost:" + strconv.Itoa(testEnv.DuckPgPort) + "/postgres"
+
+	conn, err := pgconn.Connect(context.Background(), dsn)
+	require.NoError(t, err)
+	defer conn.Close(context.Background())
+	frontend := conn.Frontend()
+	text := []int16{pgproto3.TextFormat}
+
+	// Describe before Bind, then fetch the rows of a named portal two at a time
+	frontend.SendParse(&pgproto3.Parse{Name: "s1", Query: "SELECT i::VARCHAR FROM range(5) t(i) ORDER BY i"})
+	frontend.SendDescribe(&pgproto3.Describe{ObjectType: 'S', Name: "s1"})
+	frontend.SendBind(&pgproto3.Bind{DestinationPortal: "p1", PreparedStatement: "s1", ResultFormatCodes: text})
+	frontend.SendDescribe(&pgproto3.Describe{ObjectType: 'P', Name: "p1"})
+	frontend.SendExecute(&pgproto3.Execute{Portal: "p1", MaxRows: 2})
+	frontend.SendExecute(&pgproto3.Execute{Portal: "p1", MaxRows: 2})
+	frontend.SendExecute(&pgproto3.Execute{Portal: "p1", MaxRows: 2})
+	frontend.SendSync(&pgproto3.Sync{})
+	require.NoError(t, frontend.Flush())
+	require.Equal(t, []string{
+		"ParseComplete", "ParameterDescription", "RowDescription", "BindComplete", "RowDescription",
+		"DataRow 0", "DataRow 1", "PortalSuspended",
+		"DataRow 2", "DataRow 3", "PortalSuspended",
+		"DataRow 4", "CommandComplete SELECT 1",
+	}, receiveUntilReady(t, frontend))
+
+	// The statement outlives its closed portal, and a portal may be resumed without a limit
+	frontend.SendClose(&pgproto3.Close{ObjectType: 'P', Name: "p1"})
+	frontend.SendBind(&pgproto3.Bind{DestinationPortal: "p1", PreparedStatement: "s1", ResultFormatCodes: text})
+	frontend.SendExecute(&pgproto3.Execute{Portal: "p1", MaxRows: 3})
+	frontend.SendExecute(&pgproto3.Execute{Portal: "p1"})
+	frontend.SendExecute(&pgproto3.Execute{Portal: "p1", MaxRows: 3})
+	frontend.SendSync(&pgproto3.Sync{})
+	require.NoError(t, frontend.Flush())
+	require.Equal(t, []string{
+		"CloseComplete", "BindComplete",
+		"DataRow 0", "DataRow 1", "DataRow 2", "PortalSuspended",
+		"DataRow 3", "DataRow 4", "CommandComplete SELECT 2",
+		"CommandComplete SELECT 0",
+	}, receiveUntilReady(t, frontend))
+
+	// A named statement or portal must be closed before it is redefined
+	frontend.SendParse(&pgproto3.Parse{Name: "s1", Query: "SELECT 1"})
+	frontend.SendSync(&pgproto3.Sync{})
+	frontend.SendBind(&pgproto3.Bind{DestinationPortal: "p1", PreparedStatement: "s1", ResultFormatCodes: text})
+	frontend.SendBind(&pgproto3.Bind{DestinationPortal: "p1", PreparedStatement: "s1", ResultFormatCodes: text})
+	frontend.SendSync(&pgproto3.Sync{})
+	require.NoError(t, frontend.Flush())
+	require.Equal(t, []string{`ErrorResponse prepared statement "s1" already exists`}, receiveUntilReady(t, frontend))
+	require.Equal(t, []string{"BindComplete", `ErrorResponse portal "p1" already exists`}, receiveUntilReady(t, frontend))
+
+	// Closing the statement closes its portals, and the unnamed ones are replaced freely
+	frontend.SendClose(&pgproto3.Close{ObjectType: 'S', Name: "s1"})
+	frontend.SendParse(&pgproto3.Parse{Query: "SELECT $1::VARCHAR"})
+	frontend.SendBind(&pgproto3.Bind{Parameters: [][]byte{[]byte("a")}, ResultFormatCodes: text})
+	frontend.SendParse(&pgproto3.Parse{Query: "SELECT $1::VARCHAR"})
+	frontend.SendBind(&pgproto3.Bind{Parameters: [][]byte{[]byte("b")}, ResultFormatCodes: text})
+	frontend.SendExecute(&pgproto3.Execute{MaxRows: 1})
+	frontend.SendExecute(&pgproto3.Execute{Portal: "p1"})
+	frontend.SendSync(&pgproto3.Sync{})
+	require.NoError(t, frontend.Flush())
+	require.Equal(t, []string{
+		"CloseComplete", "ParseComplete", "BindComplete", "ParseComplete", "BindComplete",
+		"DataRow b", "PortalSuspended",
+		"ErrorResponse portal p1 does not exist",
+	}, receiveUntilReady(t, frontend))
+}
+
+func TestPortalTransactionLifetime(t *testing.T) {
+	// Setup MyDuck Server
+	testDir := testutil.CreateTestDir(t)
+	testEnv := testutil.NewTestEnv()
+	err := testutil.StartDuckSqlServer(t, testDir, nil, testEnv)
+	require.NoError(t, err)
+	defer testutil.StopDuckSqlServer(t, testEnv.DuckProcess)
+	dsn := "postgresql://postgres@localhost:" + strconv.Itoa(testEnv.DuckPgPort) + "/postgres"
+
+	conn, err := pgconn.Connect(context.Background(), dsn)
+	require.NoError(t, err)
+	defer conn.Close(context.Background())
+	frontend := conn.Frontend()
+	text := []int16{pgproto3.TextFormat}
+
+	// In a transaction block, a suspended portal outlives Sync, which is how JDBC fetches the rows with setFetchSize
+	frontend.SendQuery(&pgproto3.Query{String: "BEGIN"})
+	require.NoError(t, frontend.Flush())
+	received, status := receiveWithTxStatus(t, frontend)
+	require.Equal(t, []string{"CommandComplete BEGIN"}, received)
+	require.Equal(t, byte(ReadyForQueryTransactionIndicator_TransactionBlock), status)
+
+	frontend.SendParse(&pgproto3.Parse{Name: "s1", Query: "SELECT i::VARCHAR FROM range(3) t(i) ORDER BY i"})
+	frontend.SendBind(&pgproto3.Bind{DestinationPortal: "C_1", PreparedStatement: "s1", ResultFormatCodes: text})
+	frontend.SendExecute(&pgproto3.Execute{Portal: "C_1", MaxRows: 2})
+	frontend.SendSync(&pgproto3.Sync{})
+	require.NoError(t, frontend.Flush())
+	received, status = receiveWithTxStatus(t, frontend)
+	require.Equal(t, []string{"ParseComplete", "BindComplete", "DataRow 0", "DataRow 1", "PortalSuspended"}, received)
+	require.Equal(t, byte(ReadyForQueryTransactionIndicator_TransactionBlock), status)
+
+	frontend.SendExecute(&pgproto3.Execute{Portal: "C_1", MaxRows: 2})
+	frontend.SendSync(&pgproto3.Sync{})
+	require.NoError(t, frontend.Flush())
+	require.Equal(t, []string{"DataRow 2", "CommandComplete SELECT 1"}, receiveUntilReady(t, frontend))
+
+	// The end of the transaction destroys the portals
+	frontend.SendBind(&pgproto3.Bind{DestinationPortal: "C_2", PreparedStatement: "s1", ResultFormatCodes: text})
+	frontend.SendExecute(&pgproto3.Execute{Portal: "C_2", MaxRows: 1})
+	frontend.SendSync(&pgproto3.Sync{})
+	frontend.SendQuery(&pgproto3.Query{String: "COMMIT"})
+	frontend.SendExecute(&pgproto3.Execute{Portal: "C_2", MaxRows: 1})
+	frontend.SendSync(&pgproto3.Sync{})
+	require.NoError(t, frontend.Flush())
+	require.Equal(t, []string{"BindComplete", "DataRow 0", "PortalSuspended"}, receiveUntilReady(t, frontend))
+	received, status = receiveWithTxStatus(t, frontend)
+	require.Equal(t, []string{"CommandComplete COMMIT"}, received)
+	require.Equal(t, byte(ReadyForQueryTransactionIndicator_Idle), status)
+	require.Equal(t, []string{"ErrorResponse portal C_2 does not exist"}, receiveUntilReady(t, frontend))
+
+	// Outside a transaction block, Sync destroys the portals
+	frontend.SendBind(&pgproto3.Bind{DestinationPortal: "C_3", PreparedStatement: "s1", ResultFormatCodes: text})
+	frontend.SendExecute(&pgproto3.Execute{Portal: "C_3", MaxRows: 1})
+	frontend.SendSync(&pgproto3.Sync{})
+	frontend.SendExecute(&pgproto3.Execute{Portal: "C_3", MaxRows: 1})
+	frontend.SendSync(&pgproto3.Sync{})
+	require.NoError(t, frontend.Flush())
+	require.Equal(t, []string{"BindComplete", "DataRow 0", "PortalSuspended"}, receiveUntilReady(t, frontend))
+	require.Equal(t, []string{"ErrorResponse portal C_3 does not exist"}, receiveUntilReady(t, frontend))
+}
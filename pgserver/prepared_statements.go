@@ -0,0 +1,331 @@
+package pgserver
+
+import (
+	"context"
+	stdsql "database/sql"
+	"fmt"
+	"regexp"
+	"strconv"
+	"strings"
+	"sync/atomic"
+
+	"github.com/apecloud/myduckserver/adapter"
+	"github.com/dolthub/go-mysql-server/sql"
+)
+
+// This file handles the SQL statements for prepared statements, and DISCARD:
+//
+//	PREPARE p [(type, ...)] AS SELECT ...;    -- Prepares the statement, with the types of its parameters $1, $2, ...
+//	EXECUTE p [(value, ...)];                 -- Executes the prepared statement with the values of its parameters.
+//	DEALLOCATE [PREPARE] p;                   -- Closes the prepared statement.
+//	DEALLOCATE [PREPARE] ALL;                 -- Closes all the prepared statements.
+//	DISCARD {ALL | PLANS | SEQUENCES | TEMP}; -- Resets the state of the session.
+//
+// As in Postgres, the statements prepared by PREPARE share their names with those prepared by the Parse messages of
+// the extended query protocol, so that EXECUTE may run the ones prepared by a driver and DEALLOCATE may close them.
+// Connection poolers like PgBouncer reset a pooled connection with DISCARD ALL or DEALLOCATE ALL.
+
+// PrepareAction represents the type of statement on prepared statements.
+type PrepareAction string
+
+const (
+	PreparePrepare    PrepareAction = "PREPARE"
+	PrepareExecute    PrepareAction = "EXECUTE"
+	PrepareDeallocate PrepareAction = "DEALLOCATE"
+	PrepareDiscard    PrepareAction = "DISCARD"
+)
+
+// PrepareConfig represents a parsed PREPARE, EXECUTE, DEALLOCATE, or DISCARD statement.
+type PrepareConfig struct {
+	Action PrepareAction
+	Name   string // Empty for DEALLOCATE ALL
+	// For PREPARE
+	ParamTypes []string
+	Query      string
+	// For EXECUTE: the expressions of the values of the parameters
+	Params []string
+	// For DISCARD: ALL, PLANS, SEQUENCES, or TEMP
+	Target string
+}
+
+// Tag returns the command tag of the statement. The tag of EXECUTE is that of the prepared statement.
+func (config *PrepareConfig) Tag() string {
+	switch {
+	case config.Action == PrepareDeallocate && config.Name == "":
+		return "DEALLOCATE ALL"
+	case config.Action == PrepareDiscard:
+		return "DISCARD " + config.Target
+	}
+	return string(config.Action)
+}
+
+// prepareRegex matches a PREPARE statement and captures the name, the types of the parameters, and the query.
+var prepareRegex = regexp.MustCompile(`(?is)^PREPARE\s+(\w+|"[^"]+")(?:\s*\((.*?)\))?\s+AS\s+(.+)$`)
+
+// executeRegex matches an EXECUTE statement and captures the name and the values of the parameters.
+var executeRegex = regexp.MustCompile(`(?is)^EXECUTE\s+(\w+|"[^"]+")\s*(?:\((.*)\))?$`)
+
+// deallocateRegex matches a DEALLOCATE statement and captures the name, or ALL.
+var deallocateRegex = regexp.MustCompile(`(?i)^DEALLOCATE\s+(?:PREPARE\s+)?(\w+|"[^"]+")$`)
+
+// discardRegex matches a DISCARD statement and captures what is discarded.
+var discardRegex = regexp.MustCompile(`(?i)^DISCARD\s+(ALL|PLANS|SEQUENCES|TEMP|TEMPORARY)$`)
+
+// parsePrepareSQL parses the given SQL statement and returns a PrepareConfig, or nil if it is not a PREPARE,
+// EXECUTE, DEALLOCATE, or DISCARD statement.
+func parsePrepareSQL(query string) (*PrepareConfig, error) {
+	query = strings.TrimSpace(sql.RemoveSpaceAndDelimiter(RemoveComments(query), ';'))
+	var config PrepareConfig
+	switch {
+	case prepareRegex.MatchString(query):
+		matches := prepareRegex.FindStringSubmatch(query)
+		config.Action = PreparePrepare
+		config.Name = foldIdentifier(matches[1])
+		if strings.TrimSpace(matches[2]) != "" {
+			types, ok := splitSQLList(matches[2])
+			if !ok {
+				return nil, fmt.Errorf("invalid parameter types of prepared statement \"%s\": %s", config.Name, matches[2])
+			}
+			config.ParamTypes = types
+		}
+		config.Query = matches[3]
+
+	case executeRegex.MatchString(query):
+		matches := executeRegex.FindStringSubmatch(query)
+		config.Action = PrepareExecute
+		config.Name = foldIdentifier(matches[1])
+		if strings.TrimSpace(matches[2]) != "" {
+			params, ok := splitSQLList(matches[2])
+			if !ok {
+				return nil, fmt.Errorf("invalid parameters of prepared statement \"%s\": %s", config.Name, matches[2])
+			}
+			config.Params = params
+		}
+
+	case deallocateRegex.MatchString(query):
+		matches := deallocateRegex.FindStringSubmatch(query)
+		config.Action = PrepareDeallocate
+		if !strings.EqualFold(matches[1], "ALL") {
+			config.Name = foldIdentifier(matches[1])
+		}
+
+	case discardRegex.MatchString(query):
+		matches := discardRegex.FindStringSubmatch(query)
+		config.Action = PrepareDiscard
+		config.Target = strings.ToUpper(matches[1])
+		if config.Target == "TEMPORARY" {
+			config.Target = "TEMP"
+		}
+
+	default:
+		return nil, nil
+	}
+	return &config, nil
+}
+
+// splitSQLList splits a comma-separated list of expressions, or of types, at the commas outside the parentheses,
+// brackets, and literals. It returns false if the list is malformed.
+func splitSQLList(list string) ([]string, bool) {
+	tokens, ok := tokenizeSQL(list)
+	if !ok || len(tokens) == 0 {
+		return nil, false
+	}
+	var items []string
+	start, depth := 0, 0
+	for _, token := range tokens {
+		if token.kind != tokenPunct {
+			continue
+		}
+		switch token.text {
+		case "(", "[":
+			depth++
+		case ")", "]":
+			depth--
+		case ",":
+			if depth == 0 {
+				items = append(items, strings.TrimSpace(list[start:token.start]))
+				start = token.end
+			}
+		}
+	}
+	items = append(items, strings.TrimSpace(list[start:]))
+	for _, item := range items {
+		if item == "" {
+			return nil, false
+		}
+	}
+	return items, depth == 0
+}
+
+// castParams casts the parameters $1, $2, ... of the query to the given types, so that DuckDB infers them as such.
+func castParams(query string, types []string) string {
+	tokens, ok := tokenizeSQL(query)
+	if !ok {
+		return query
+	}
+	var b strings.Builder
+	last := 0
+	for _, token := range tokens {
+		if token.kind != tokenParam {
+			continue
+		}
+		n, err := strconv.Atoi(token.text[1:])
+		if err != nil || n < 1 || n > len(types) || strings.EqualFold(types[n-1], "unknown") {
+			continue
+		}
+		b.WriteString(query[last:token.start])
+		b.WriteString("(" + token.text + "::" + types[n-1] + ")")
+		last = token.end
+	}
+	b.WriteString(query[last:])
+	return b.String()
+}
+
+// executePrepareStatement executes a PREPARE, EXECUTE, DEALLOCATE, or DISCARD statement.
+func (h *ConnectionHandler) executePrepareStatement(statement ConvertedStatement) error {
+	config := statement.PrepareConfig
+	switch config.Action {
+	case PreparePrepare:
+		return h.prepareSQLStatement(config)
+	case PrepareExecute:
+		return h.executeSQLStatement(config)
+	case PrepareDeallocate:
+		if config.Name == "" {
+			h.deleteAllPreparedStatements()
+		} else {
+			if _, ok := h.preparedStatements[config.Name]; !ok {
+				return fmt.Errorf("prepared statement \"%s\" does not exist", config.Name)
+			}
+			h.deletePreparedStatement(config.Name)
+		}
+		return h.send(makeCommandComplete(config.Tag(), 0))
+	case PrepareDiscard:
+		switch config.Target {
+		case "ALL":
+			if h.inTransaction {
+				return fmt.Errorf("DISCARD ALL cannot run inside a transaction block")
+			}
+			return h.discardAll(statement)
+		case "TEMP":
+			if err := h.dropTempTables(); err != nil {
+				return err
+			}
+		}
+		// DuckDB caches no plans, and the sequences keep no state of the session.
+		return h.send(makeCommandComplete(config.Tag(), 0))
+	default:
+		return fmt.Errorf("unsupported statement on prepared statements: %s", statement.String)
+	}
+}
+
+// prepareSQLStatement prepares the query of a PREPARE statement in DuckDB.
+func (h *ConnectionHandler) prepareSQLStatement(config *PrepareConfig) error {
+	if _, ok := h.preparedStatements[config.Name]; ok {
+		return fmt.Errorf("prepared statement \"%s\" already exists", config.Name)
+	}
+	statements, err := h.convertQuery(castParams(config.Query, config.ParamTypes))
+	if err != nil {
+		return err
+	}
+	if len(statements) != 1 {
+		return fmt.Errorf("cannot insert multiple commands into a prepared statement")
+	}
+	statement := statements[0]
+	switch statement.Tag {
+	case "SELECT", "INSERT", "UPDATE", "DELETE", "MERGE":
+	default:
+		return fmt.Errorf("%s cannot be prepared, only SELECT, INSERT, UPDATE, DELETE, and MERGE can", statement.Tag)
+	}
+
+	stmt, params, fields, err := h.duckHandler.ComPrepareParsed(context.Background(), h.mysqlConn, statement.String, statement.AST)
+	if err != nil {
+		return err
+	}
+	if !statement.PgParsable {
+		statement.Tag = GetStatementTag(stmt)
+	}
+	h.preparedStatements[config.Name] = PreparedStatementData{
+		Statement:    statement,
+		ReturnFields: fields,
+		BindVarTypes: params,
+		Stmt:         stmt,
+		Closed:       new(atomic.Bool),
+	}
+	return h.send(makeCommandComplete(string(PreparePrepare), 0))
+}
+
+// executeSQLStatement executes a prepared statement with the values of the parameters of an EXECUTE statement,
+// and sends its result as the result of the EXECUTE statement.
+func (h *ConnectionHandler) executeSQLStatement(config *PrepareConfig) error {
+	prepared, ok := h.preparedStatements[config.Name]
+	if !ok {
+		return fmt.Errorf("prepared statement \"%s\" does not exist", config.Name)
+	}
+	if prepared.Stmt == nil {
+		return fmt.Errorf("prepared statement \"%s\" cannot be executed by EXECUTE", config.Name)
+	}
+	if len(config.Params) != len(prepared.BindVarTypes) {
+		return fmt.Errorf("wrong number of parameters for prepared statement \"%s\": expected %d parameters but got %d",
+			config.Name, len(prepared.BindVarTypes), len(config.Params))
+	}
+	vars, err := h.evaluateParams(config.Params)
+	if err != nil {
+		return err
+	}
+
+	fields, err := h.duckHandler.ComBind(context.Background(), h.mysqlConn, prepared, vars)
+	if err != nil {
+		return err
+	}
+	portal := PortalData{
+		Statement: prepared.Statement,
+		Fields:    fields,
+		Stmt:      prepared.Stmt,
+		Closed:    prepared.Closed,
+		Vars:      vars,
+	}
+	rowsAffected := int32(0)
+	callback := h.spoolRowsCallback(prepared.Statement, &rowsAffected, false)
+	stopTimeout := h.startStatementTimeout()
+	if err := stopTimeout(h.duckHandler.ComExecuteBound(context.Background(), h.mysqlConn, portal, callback)); err != nil {
+		return err
+	}
+	h.trackTransaction(prepared.Statement, vars)
+	return h.send(makeCommandComplete(prepared.Statement.Tag, rowsAffected))
+}
+
+// evaluateParams evaluates the expressions of the values of the parameters in DuckDB, which are bound as text like
+// those of a Bind message. A NULL value is bound as nil.
+func (h *ConnectionHandler) evaluateParams(params []string) ([]any, error) {
+	if len(params) == 0 {
+		return nil, nil
+	}
+	ctx, err := h.duckHandler.NewContext(context.Background(), h.mysqlConn, "")
+	if err != nil {
+		return nil, err
+	}
+	columns := make([]string, len(params))
+	values := make([]stdsql.NullString, len(params))
+	dest := make([]any, len(params))
+	for i, param := range params {
+		columns[i] = "(" + ConvertArrayLiterals(param) + ")::VARCHAR"
+		dest[i] = &values[i]
+	}
+	if err := adapter.QueryRow(ctx, "SELECT "+strings.Join(columns, ", ")).Scan(dest...); err != nil {
+		return nil, err
+	}
+	vars := make([]any, len(params))
+	for i, value := range values {
+		if value.Valid {
+			vars[i] = value.String
+		}
+	}
+	return vars, nil
+}
+
+// deleteAllPreparedStatements closes all the prepared statements, along with their portals.
+func (h *ConnectionHandler) deleteAllPreparedStatements() {
+	for name := range h.preparedStatements {
+		h.deletePreparedStatement(name)
+	}
+}
@@ -0,0 +1,82 @@
+package pgserver
+
+import (
+	"testing"
+
+	"github.com/stretchr/testify/require"
+)
+
+func TestParsePrepareSQL(t *testing.T) {
+	tests := []struct {
+		query string
+		want  *PrepareConfig
+	}{
+		{
+			query: "PREPARE q AS SELECT * FROM t WHERE id = $1",
+			want:  &PrepareConfig{Action: PreparePrepare, Name: "q", Query: "SELECT * FROM t WHERE id = $1"},
+		},
+		{
+			query: `prepare "Insert" (int, numeric(10, 2), text[]) as INSERT INTO t VALUES ($1, $2, $3);`,
+			want: &PrepareConfig{
+				Action:     PreparePrepare,
+				Name:       "Insert",
+				ParamTypes: []string{"int", "numeric(10, 2)", "text[]"},
+				Query:      "INSERT INTO t VALUES ($1, $2, $3)",
+			},
+		},
+		{
+			query: "EXECUTE Q",
+			want:  &PrepareConfig{Action: PrepareExecute, Name: "q"},
+		},
+		{
+			query: "EXECUTE q (1, 'a, b', ARRAY[1, 2], NULL)",
+			want:  &PrepareConfig{Action: PrepareExecute, Name: "q", Params: []string{"1", "'a, b'", "ARRAY[1, 2]", "NULL"}},
+		},
+		{
+			query: "DEALLOCATE PREPARE q",
+			want:  &PrepareConfig{Action: PrepareDeallocate, Name: "q"},
+		},
+		{
+			query: "deallocate all;",
+			want:  &PrepareConfig{Action: PrepareDeallocate},
+		},
+		{
+			query: "DISCARD ALL",
+			want:  &PrepareConfig{Action: PrepareDiscard, Target: "ALL"},
+		},
+		{
+			query: "discard temporary",
+			want:  &PrepareConfig{Action: PrepareDiscard, Target: "TEMP"},
+		},
+		{
+			query: "PREPARE TRANSACTION 'tx1'",
+		},
+		{
+			query: "SELECT 1",
+		},
+	}
+	for _, tt := range tests {
+		got, err := parsePrepareSQL(tt.query)
+		require.NoError(t, err, tt.query)
+		require.Equal(t, tt.want, got, tt.query)
+	}
+
+	for _, query := range []string{
+		"PREPARE q (int,) AS SELECT $1",
+		"EXECUTE q (1, (2)",
+	} {
+		_, err := parsePrepareSQL(query)
+		require.Error(t, err, query)
+	}
+
+	require.Equal(t, "DEALLOCATE ALL", (&PrepareConfig{Action: PrepareDeallocate}).Tag())
+	require.Equal(t, "DISCARD PLANS", (&PrepareConfig{Action: PrepareDiscard, Target: "PLANS"}).Tag())
+}
+
+func TestCastParams(t *testing.T) {
+	require.Equal(t,
+		"SELECT ($1::int) + ($2::bigint), '$1', $3",
+		castParams("SELECT $1 + $2, '$1', $3", []string{"int", "bigint"}),
+	)
+	require.Equal(t, "SELECT $1", castParams("SELECT $1", []string{"unknown"}))
+}
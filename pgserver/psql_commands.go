@@ -0,0 +1,283 @@
+package pgserver
+
+import (
+	"context"
+	"regexp"
+	"strings"
+
+	"github.com/apecloud/myduckserver/catalog"
+)
+
+// The backslash commands of psql, such as \d and \dt, describe the objects of the database with queries on
+// pg_catalog, which depend on many functions and operators of Postgres. These queries are recognized here and
+// replaced with equivalent queries on the pg_catalog views of MyDuck Server. The queries are matched with the
+// whitespace collapsed, so that those of psql 15 and 16 are both recognized.
+
+// clientQuery emulates one of the queries issued by a client tool, such as psql and pg_dump.
+type clientQuery struct {
+	// pattern matches the query issued by the client tool.
+	pattern *regexp.Regexp
+	// query returns the query to run instead, given the submatches of pattern.
+	query func(h *ConnectionHandler, matches []string) (string, error)
+}
+
+// psqlNamePatternRegex matches the conditions on the object names, which psql adds for the pattern of a command.
+var psqlNamePatternRegex = regexp.MustCompile(`(\w+\.\w+) OPERATOR\(pg_catalog\.~\) ('(?:[^']|'')*') COLLATE pg_catalog\.default`)
+
+var psqlCommands = []clientQuery{
+	// \l
+	{
+		pattern: regexp.MustCompile(`^SELECT d\.datname as "Name", .* FROM pg_catalog\.pg_database d ORDER BY 1;$`),
+		query: func(h *ConnectionHandler, matches []string) (string, error) {
+			return `SELECT d.datname AS "Name", 'postgres' AS "Owner", 'UTF8' AS "Encoding", 'en_US.UTF-8' AS "Collate", 'en_US.UTF-8' AS "Ctype", 'en-US' AS "ICU Locale", CASE d.datlocprovider WHEN 'c' THEN 'libc' WHEN 'i' THEN 'icu' END AS "Locale Provider", '' AS "Access privileges" FROM __sys__.pg_database d ORDER BY 1;`, nil
+		},
+	},
+	// \d, \dt, \dv, \di, \ds and their combinations, with or without a pattern
+	{
+		pattern: regexp.MustCompile(`^SELECT n\.nspname as "Schema", c\.relname as "Name", CASE c\.relkind .*? END as "Type", pg_catalog\.pg_get_userbyid\(c\.relowner\) as "Owner"(.*?) FROM pg_catalog\.pg_class c .*?WHERE c\.relkind IN \(([^)]*)\)(.*) ORDER BY 1,2;$`),
+		query: func(h *ConnectionHandler, matches []string) (string, error) {
+			columns, kinds, conditions := matches[1], matches[2], matches[3]
+			var b strings.Builder
+			b.WriteString(`SELECT n.nspname AS "Schema", c.relname AS "Name", CASE c.relkind WHEN 'r' THEN 'table' WHEN 'v' THEN 'view' WHEN 'i' THEN 'index' WHEN 'S' THEN 'sequence' END AS "Type", 'postgres' AS "Owner"`)
+			if strings.Contains(columns, `as "Table"`) {
+				b.WriteString(`, c2.relname AS "Table"`)
+			}
+			if strings.Contains(columns, `as "Persistence"`) {
+				b.WriteString(`, CASE c.relpersistence WHEN 'p' THEN 'permanent' WHEN 't' THEN 'temporary' END AS "Persistence"`)
+			}
+			if strings.Contains(columns, `as "Access method"`) {
+				b.WriteString(`, CASE c.relam WHEN 2 THEN 'heap' WHEN 403 THEN 'btree' END AS "Access method"`)
+			}
+			if strings.Contains(columns, `as "Size"`) {
+				b.WriteString(`, NULL AS "Size"`)
+			}
+			if strings.Contains(columns, `as "Description"`) {
+				b.WriteString(`, d.description AS "Description"`)
+			}
+			b.WriteString(` FROM __sys__.pg_class c JOIN __sys__.pg_namespace n ON n.oid = c.relnamespace`)
+			b.WriteString(` LEFT JOIN __sys__.pg_index i ON i.indexrelid = c.oid LEFT JOIN __sys__.pg_class c2 ON c2.oid = i.indrelid`)
+			b.WriteString(` LEFT JOIN __sys__.pg_description d ON d.objoid = c.oid AND d.classoid = 1259 AND d.objsubid = 0`)
+			b.WriteString(` WHERE c.relkind IN (` + kinds + `)`)
+			filters, err := h.psqlFilters(conditions)
+			if err != nil {
+				return "", err
+			}
+			b.WriteString(filters)
+			b.WriteString(` ORDER BY 1, 2;`)
+			return b.String(), nil
+		},
+	},
+	// \dn
+	{
+		pattern: regexp.MustCompile(`^SELECT n\.nspname AS "Name", pg_catalog\.pg_get_userbyid\(n\.nspowner\) AS "Owner" FROM pg_catalog\.pg_namespace n (?:WHERE (.*) )?ORDER BY 1;$`),
+		query: func(h *ConnectionHandler, matches []string) (string, error) {
+			filters, err := h.psqlFilters(matches[1])
+			if err != nil {
+				return "", err
+			}
+			return `SELECT n.nspname AS "Name", 'postgres' AS "Owner" FROM __sys__.pg_namespace n WHERE true` + filters + ` ORDER BY 1;`, nil
+		},
+	},
+	// \df
+	{
+		pattern: regexp.MustCompile(`^SELECT n\.nspname as "Schema", p\.proname as "Name", .* END as "Type" FROM pg_catalog\.pg_proc p LEFT JOIN pg_catalog\.pg_namespace n ON n\.oid = p\.pronamespace (?:WHERE (.*) )?ORDER BY 1, 2, 4;$`),
+		query: func(h *ConnectionHandler, matches []string) (string, error) {
+			filters, err := h.psqlFilters(strings.ReplaceAll(matches[1], "pg_catalog.pg_function_is_visible(p.oid)", "pg_catalog.pg_table_is_visible(c.oid)"))
+			if err != nil {
+				return "", err
+			}
+			return `SELECT n.nspname AS "Schema", p.proname AS "Name", COALESCE(p.return_type, '') AS "Result data type", COALESCE(list_aggregate(p.parameters, 'string_agg', ', '), '') AS "Argument data types", 'func' AS "Type"` +
+				` FROM (SELECT function_name AS proname, schema_name, return_type, parameters FROM duckdb_functions() WHERE database_name = current_database() AND NOT internal) p` +
+				` JOIN __sys__.pg_namespace n ON n.nspname = p.schema_name` +
+				` WHERE n.nspname NOT IN ('__sys__', 'mysql')` + filters + ` ORDER BY 1, 2, 4;`, nil
+		},
+	},
+	// \du
+	{
+		pattern: regexp.MustCompile(`^SELECT r\.rolname, r\.rolsuper, r\.rolinherit, r\.rolcreaterole, r\.rolcreatedb, r\.rolcanlogin, r\.rolconnlimit, r\.rolvaliduntil(.*) FROM pg_catalog\.pg_roles r WHERE r\.rolname !~ '\^pg_'(.*) ORDER BY 1;$`),
+		query: func(h *ConnectionHandler, matches []string) (string, error) {
+			columns := matches[1]
+			provider, err := h.catalogProvider()
+			if err != nil {
+				return "", err
+			}
+			store := newRoleStore(provider, provider.Storage())
+			var b strings.Builder
+			b.WriteString(`SELECT r.rolname, r.rolsuper, r.rolinherit, r.rolcreaterole, r.rolcreatedb, r.rolcanlogin, r.rolconnlimit, r.rolvaliduntil`)
+			if strings.Contains(columns, "as memberof") {
+				b.WriteString(`, coalesce((SELECT list(b.rolname ORDER BY b.rolname) FROM ` + store.table(catalog.InternalTables.PGAuthMembers) + ` m` +
+					` JOIN ` + store.table(catalog.InternalTables.PGAuthID) + ` b ON m.roleid = b.oid WHERE m.member = r.oid), []::VARCHAR[]) AS memberof`)
+			}
+			if strings.Contains(columns, "AS description") {
+				b.WriteString(`, NULL AS description`)
+			}
+			if strings.Contains(columns, "r.rolreplication") {
+				b.WriteString(`, r.rolreplication`)
+			}
+			if strings.Contains(columns, "r.rolbypassrls") {
+				b.WriteString(`, r.rolbypassrls`)
+			}
+			b.WriteString(` FROM ` + store.table(catalog.InternalTables.PGAuthID) + ` r WHERE true`)
+			filters, err := h.psqlFilters(matches[2])
+			if err != nil {
+				return "", err
+			}
+			b.WriteString(filters)
+			b.WriteString(` ORDER BY 1;`)
+			return b.String(), nil
+		},
+	},
+	// \d NAME: the relations matching the pattern
+	{
+		pattern: regexp.MustCompile(`^SELECT c\.oid, n\.nspname, c\.relname FROM pg_catalog\.pg_class c LEFT JOIN pg_catalog\.pg_namespace n ON n\.oid = c\.relnamespace WHERE (.*) ORDER BY 2, 3;$`),
+		query: func(h *ConnectionHandler, matches []string) (string, error) {
+			filters, err := h.psqlFilters(matches[1])
+			if err != nil {
+				return "", err
+			}
+			return `SELECT c.oid, n.nspname, c.relname FROM __sys__.pg_class c LEFT JOIN __sys__.pg_namespace n ON n.oid = c.relnamespace WHERE true` + filters + ` ORDER BY 2, 3;`, nil
+		},
+	},
+	// \d NAME: the properties of a relation
+	{
+		pattern: regexp.MustCompile(`^SELECT c\.relchecks, c\.relkind, c\.relhasindex, .* FROM pg_catalog\.pg_class c LEFT JOIN pg_catalog\.pg_class tc ON \(c\.reltoastrelid = tc\.oid\) LEFT JOIN pg_catalog\.pg_am am ON \(c\.relam = am\.oid\) WHERE c\.oid = '(\d+)';$`),
+		query: func(h *ConnectionHandler, matches []string) (string, error) {
+			// relhastriggers makes psql look up the foreign keys of the relation.
+			return `SELECT c.relchecks, c.relkind, c.relhasindex, c.relhasrules,` +
+				` EXISTS (SELECT 1 FROM __sys__.pg_constraint WHERE contype = 'f' AND (conrelid = c.oid OR confrelid = c.oid)) AS relhastriggers,` +
+				` c.relrowsecurity, c.relforcerowsecurity, false AS relhasoids, c.relispartition, '' AS reloptions, c.reltablespace, '' AS reloftype,` +
+				` c.relpersistence, c.relreplident, CASE c.relam WHEN 2 THEN 'heap' WHEN 403 THEN 'btree' END AS amname` +
+				` FROM __sys__.pg_class c WHERE c.oid = ` + matches[1] + `;`, nil
+		},
+	},
+	// \d NAME: the columns of a relation
+	{
+		pattern: regexp.MustCompile(`^SELECT a\.attname, pg_catalog\.format_type\(a\.atttypid, a\.atttypmod\),(.*) FROM pg_catalog\.pg_attribute a WHERE a\.attrelid = '(\d+)' AND a\.attnum > 0 AND NOT a\.attisdropped ORDER BY a\.attnum;$`),
+		query: func(h *ConnectionHandler, matches []string) (string, error) {
+			columns := matches[1]
+			if strings.Contains(columns, "AS indexdef") {
+				// The columns of an index
+				return "", nil
+			}
+			var b strings.Builder
+			b.WriteString(`SELECT a.attname, __sys__.format_type(a.atttypid, a.atttypmod)`)
+			for _, column := range []struct{ expr, replacement string }{
+				{"pg_catalog.pg_get_expr(d.adbin, d.adrelid, true)", "ad.adbin"},
+				{"a.attnotnull", "a.attnotnull"},
+				{"AS attcollation", "NULL AS attcollation"},
+				{"a.attidentity", "a.attidentity"},
+				{"a.attgenerated", "a.attgenerated"},
+				{"a.attstorage", "a.attstorage"},
+				{"AS attcompression", "a.attcompression"},
+				{"AS attstattarget", "NULL AS attstattarget"},
+				{"pg_catalog.col_description(a.attrelid, a.attnum)", "d.description"},
+			} {
+				if strings.Contains(columns, column.expr) {
+					b.WriteString(", " + column.replacement)
+				}
+			}
+			b.WriteString(` FROM __sys__.pg_attribute a`)
+			b.WriteString(` LEFT JOIN __sys__.pg_attrdef ad ON ad.adrelid = a.attrelid AND ad.adnum = a.attnum`)
+			b.WriteString(` LEFT JOIN __sys__.pg_description d ON d.objoid = a.attrelid AND d.classoid = 1259 AND d.objsubid = a.attnum`)
+			b.WriteString(` WHERE a.attrelid = ` + matches[2] + ` AND a.attnum > 0 ORDER BY a.attnum;`)
+			return b.String(), nil
+		},
+	},
+	// \d NAME: the indexes of a table
+	{
+		pattern: regexp.MustCompile(`^SELECT c2\.relname, i\.indisprimary, i\.indisunique, i\.indisclustered, i\.indisvalid, .* WHERE c\.oid = '(\d+)' AND c\.oid = i\.indrelid AND i\.indexrelid = c2\.oid ORDER BY i\.indisprimary DESC, c2\.relname;$`),
+		query: func(h *ConnectionHandler, matches []string) (string, error) {
+			return `SELECT c2.relname, i.indisprimary, i.indisunique, i.indisclustered, i.indisvalid,` +
+				` __sys__.pg_get_indexdef(i.indexrelid, 0, true), __sys__.pg_get_constraintdef(con.oid, true),` +
+				` con.contype, con.condeferrable, con.condeferred, i.indisreplident, c2.reltablespace` +
+				` FROM __sys__.pg_index i JOIN __sys__.pg_class c2 ON c2.oid = i.indexrelid` +
+				` LEFT JOIN __sys__.pg_constraint con ON con.conrelid = i.indrelid AND con.conindid = i.indexrelid AND con.contype IN ('p', 'u')` +
+				` WHERE i.indrelid = ` + matches[1] + ` ORDER BY i.indisprimary DESC, c2.relname;`, nil
+		},
+	},
+	// \d NAME: the check constraints of a table
+	{
+		pattern: regexp.MustCompile(`^SELECT r\.conname, pg_catalog\.pg_get_constraintdef\(r\.oid, true\) FROM pg_catalog\.pg_constraint r WHERE r\.conrelid = '(\d+)' AND r\.contype = 'c' ORDER BY 1;$`),
+		query: func(h *ConnectionHandler, matches []string) (string, error) {
+			return `SELECT r.conname, __sys__.pg_get_constraintdef(r.oid, true) FROM __sys__.pg_constraint r WHERE r.conrelid = ` + matches[1] + ` AND r.contype = 'c' ORDER BY 1;`, nil
+		},
+	},
+	// \d NAME: the foreign keys of a table
+	{
+		pattern: regexp.MustCompile(`^SELECT true as sametable, conname, pg_catalog\.pg_get_constraintdef\(r\.oid, true\) as condef, conrelid::pg_catalog\.regclass AS ontable FROM pg_catalog\.pg_constraint r WHERE r\.conrelid = '(\d+)' AND r\.contype = 'f' .*ORDER BY conname;?$`),
+		query: func(h *ConnectionHandler, matches []string) (string, error) {
+			return `SELECT true AS sametable, r.conname, __sys__.pg_get_constraintdef(r.oid, true) AS condef, c.relname AS ontable` +
+				` FROM __sys__.pg_constraint r JOIN __sys__.pg_class c ON c.oid = r.conrelid` +
+				` WHERE r.conrelid = ` + matches[1] + ` AND r.contype = 'f' ORDER BY r.conname;`, nil
+		},
+	},
+	// \d NAME: the foreign keys referencing a table
+	{
+		pattern: regexp.MustCompile(`^SELECT conname, conrelid::pg_catalog\.regclass AS ontable, pg_catalog\.pg_get_constraintdef\(oid, true\) AS condef FROM pg_catalog\.pg_constraint c WHERE confrelid IN \(SELECT pg_catalog\.pg_partition_ancestors\('(\d+)'\) .*ORDER BY conname;$`),
+		query: func(h *ConnectionHandler, matches []string) (string, error) {
+			return `SELECT r.conname, c.relname AS ontable, __sys__.pg_get_constraintdef(r.oid, true) AS condef` +
+				` FROM __sys__.pg_constraint r JOIN __sys__.pg_class c ON c.oid = r.conrelid` +
+				` WHERE r.confrelid = ` + matches[1] + ` AND r.contype = 'f' ORDER BY r.conname;`, nil
+		},
+	},
+	// \d NAME and \dn NAME: the policies, statistics, publications, triggers, rules, and inheritance of an object,
+	// none of which DuckDB supports
+	{
+		pattern: regexp.MustCompile(`FROM pg_catalog\.(?:pg_policy pol|pg_statistic_ext|pg_publication p|pg_trigger t|pg_rewrite r|pg_class c, pg_catalog\.pg_inherits i) `),
+		query: func(h *ConnectionHandler, matches []string) (string, error) {
+			return `SELECT NULL WHERE false;`, nil
+		},
+	},
+}
+
+// handledClientQueries handles the catalog queries of the client tools, such as those of the psql commands
+// \l and \dt, and those of pg_dump.
+func (h *ConnectionHandler) handledClientQueries(statement string) (bool, error) {
+	statement = strings.Join(strings.Fields(statement), " ")
+	for _, queries := range [][]clientQuery{psqlCommands, pgDumpQueries} {
+		for _, q := range queries {
+			matches := q.pattern.FindStringSubmatch(statement)
+			if matches == nil {
+				continue
+			}
+			query, err := q.query(h, matches)
+			if err != nil || query == "" {
+				return false, err
+			}
+			return true, h.run(ConvertedStatement{
+				String: query,
+				Tag:    "SELECT",
+			})
+		}
+	}
+	return false, nil
+}
+
+// psqlFilters translates the conditions of a psql query on the names and the visibility of the objects into
+// those on the pg_catalog views.
+func (h *ConnectionHandler) psqlFilters(conditions string) (string, error) {
+	var b strings.Builder
+	for _, matches := range psqlNamePatternRegex.FindAllStringSubmatch(conditions, -1) {
+		b.WriteString(" AND regexp_matches(" + matches[1] + ", " + matches[2] + ")")
+	}
+	if strings.Contains(conditions, "n.nspname <> 'pg_catalog'") || strings.Contains(conditions, "n.nspname !~ '^pg_'") {
+		b.WriteString(" AND n.nspname NOT IN ('pg_catalog', 'information_schema') AND NOT starts_with(n.nspname, 'pg_')")
+	}
+	if strings.Contains(conditions, "pg_catalog.pg_table_is_visible(c.oid)") {
+		// An object is visible if its schema is in the search path.
+		ctx, err := h.duckHandler.NewContext(context.Background(), h.mysqlConn, "")
+		if err != nil {
+			return "", err
+		}
+		path, err := SearchPath(ctx)
+		if err != nil {
+			return "", err
+		}
+		schemas := []string{quoteString("pg_catalog")}
+		for _, schema := range path {
+			schemas = append(schemas, quoteString(schema))
+		}
+		b.WriteString(" AND n.nspname IN (" + strings.Join(schemas, ", ") + ")")
+	}
+	return b.String(), nil
+}
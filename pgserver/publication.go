@@ -0,0 +1,771 @@
+package pgserver
+
+import (
+	"context"
+	stdsql "database/sql"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"regexp"
+	"slices"
+	"strings"
+	"sync"
+
+	"github.com/apecloud/myduckserver/adapter"
+	"github.com/apecloud/myduckserver/binlog"
+	"github.com/apecloud/myduckserver/catalog"
+	"github.com/apecloud/myduckserver/delta"
+	"github.com/apecloud/myduckserver/pgtypes"
+	"github.com/cockroachdb/cockroachdb-parser/pkg/sql/sem/tree"
+	"github.com/dolthub/go-mysql-server/sql"
+	"github.com/jackc/pgx/v5/pgconn"
+	"github.com/jackc/pgx/v5/pgtype"
+)
+
+// This file handles the statements of the publications, whose changes are streamed to the logical replication
+// subscribers of MyDuck Server (see walsender.go):
+//
+//	CREATE PUBLICATION name [FOR ALL TABLES | FOR TABLE table, ...] [WITH (publish = 'insert, update, delete')];
+//	ALTER PUBLICATION name ADD|SET|DROP TABLE table, ...;
+//	ALTER PUBLICATION name SET (publish = '...');
+//	DROP PUBLICATION [IF EXISTS] name, ...;
+//
+// The publications are stored in the __sys__.pg_publication and __sys__.publication_rel tables of the default
+// catalog, and may only be created in the default database. While there are both publications and replication
+// slots, the changes flushed by the replication of MyDuck Server to the published tables are captured into
+// __sys__.replication_change in the transaction of the flush, so that a change is published once it is committed.
+// A flush may group several transactions of the source, which are then published as one. The changes made by the
+// statements of the clients are not captured, so that, meanwhile, the published tables may only be written by the
+// replication: the INSERT, UPDATE, DELETE, and COPY FROM statements of the Postgres clients on them are rejected (see
+// checkClientWrite), as are the INSERT, REPLACE, UPDATE, and DELETE statements of the MySQL clients (see
+// backend.checkPublishedTables). Neither are TRUNCATE and the DDL statements captured, so publishing TRUNCATE is
+// rejected as not supported.
+
+// PublicationAction represents the type of publication statement.
+type PublicationAction string
+
+const (
+	PublicationCreate PublicationAction = "CREATE PUBLICATION"
+	PublicationAlter  PublicationAction = "ALTER PUBLICATION"
+	PublicationDrop   PublicationAction = "DROP PUBLICATION"
+)
+
+// PublicationConfig represents a parsed publication statement.
+type PublicationConfig struct {
+	Action PublicationAction
+	// Names are the publications, of which only DROP PUBLICATION may have several.
+	Names     []string
+	IfExists  bool
+	AllTables bool
+	// TableAction is "ADD", "SET", or "DROP" for the tables of ALTER PUBLICATION.
+	TableAction string
+	Tables      []PublicationTable
+	// Publish are the operations given by the publish parameter, or nil if it is not given.
+	Publish *PublicationOperations
+}
+
+// PublicationTable is a table of a publication, whose schema is empty if it is not qualified.
+type PublicationTable struct {
+	Schema string
+	Name   string
+}
+
+// PublicationOperations are the operations published by a publication.
+type PublicationOperations struct {
+	Insert   bool
+	Update   bool
+	Delete   bool
+	Truncate bool
+}
+
+// publicationStatementRegex matches the statements that may be publication statements, which are then tokenized.
+var publicationStatementRegex = regexp.MustCompile(`(?is)^(CREATE|ALTER|DROP)\s+PUBLICATION\s`)
+
+// parsePublicationSQL parses the given SQL statement and returns a PublicationConfig, or nil if it is not a
+// publication statement.
+func parsePublicationSQL(query string) (*PublicationConfig, error) {
+	query = strings.TrimSpace(sql.RemoveSpaceAndDelimiter(RemoveComments(query), ';'))
+	if !publicationStatementRegex.MatchString(query) {
+		return nil, nil
+	}
+	tokens, err := tokenizeRoleSQL(query)
+	if err != nil {
+		return nil, err
+	}
+	p := &roleParser{tokens: tokens}
+	var config *PublicationConfig
+	switch {
+	case p.accept("create", "publication"):
+		config, err = p.parseCreatePublication()
+	case p.accept("alter", "publication"):
+		config, err = p.parseAlterPublication()
+	case p.accept("drop", "publication"):
+		config, err = p.parseDropPublication()
+	}
+	if err != nil {
+		return nil, err
+	}
+	if !p.done() {
+		return nil, p.syntaxError()
+	}
+	return config, nil
+}
+
+// acceptPunctuation consumes the punctuation if it is next, and returns whether it is.
+func (p *roleParser) acceptPunctuation(c string) bool {
+	if p.done() || p.tokens[p.pos].kind != roleTokenPunctuation || p.tokens[p.pos].text != c {
+		return false
+	}
+	p.pos++
+	return true
+}
+
+func (p *roleParser) parseCreatePublication() (*PublicationConfig, error) {
+	name, err := p.name()
+	if err != nil {
+		return nil, err
+	}
+	config := &PublicationConfig{Action: PublicationCreate, Names: []string{name}}
+	switch {
+	case p.accept("for", "all", "tables"):
+		config.AllTables = true
+	case p.accept("for", "tables", "in", "schema"):
+		return nil, fmt.Errorf("FOR TABLES IN SCHEMA is not supported")
+	case p.accept("for", "table"):
+		if config.Tables, err = p.publicationTables(); err != nil {
+			return nil, err
+		}
+	}
+	if p.accept("with") {
+		if config.Publish, err = p.publicationParameters(); err != nil {
+			return nil, err
+		}
+	}
+	return config, nil
+}
+
+func (p *roleParser) parseAlterPublication() (*PublicationConfig, error) {
+	name, err := p.name()
+	if err != nil {
+		return nil, err
+	}
+	config := &PublicationConfig{Action: PublicationAlter, Names: []string{name}}
+	switch {
+	case p.accept("add", "table"):
+		config.TableAction = "ADD"
+	case p.accept("set", "table"):
+		config.TableAction = "SET"
+	case p.accept("drop", "table"):
+		config.TableAction = "DROP"
+	case p.accept("set"):
+		config.Publish, err = p.publicationParameters()
+		return config, err
+	default:
+		return nil, p.syntaxError()
+	}
+	config.Tables, err = p.publicationTables()
+	return config, err
+}
+
+func (p *roleParser) parseDropPublication() (*PublicationConfig, error) {
+	config := &PublicationConfig{Action: PublicationDrop, IfExists: p.accept("if", "exists")}
+	var err error
+	if config.Names, err = p.names(); err != nil {
+		return nil, err
+	}
+	_ = p.accept("cascade") || p.accept("restrict")
+	return config, nil
+}
+
+// publicationTables consumes a comma-separated list of tables, which may be qualified by their schemas.
+func (p *roleParser) publicationTables() ([]PublicationTable, error) {
+	var tables []PublicationTable
+	for {
+		_ = p.accept("only")
+		var table PublicationTable
+		name, err := p.name()
+		if err != nil {
+			return nil, err
+		}
+		if p.acceptPunctuation(".") {
+			table.Schema = name
+			if name, err = p.name(); err != nil {
+				return nil, err
+			}
+		}
+		table.Name = name
+		_ = p.acceptPunctuation("*")
+		tables = append(tables, table)
+		if !p.acceptPunctuation(",") {
+			return tables, nil
+		}
+	}
+}
+
+// publicationParameters consumes the parenthesized parameters of a publication, of which publish is the only one
+// that takes effect.
+func (p *roleParser) publicationParameters() (*PublicationOperations, error) {
+	if !p.acceptPunctuation("(") {
+		return nil, p.syntaxError()
+	}
+	var operations *PublicationOperations
+	for {
+		name, err := p.name()
+		if err != nil {
+			return nil, err
+		}
+		if !p.acceptPunctuation("=") {
+			return nil, p.syntaxError()
+		}
+		if p.done() {
+			return nil, p.syntaxError()
+		}
+		value := p.tokens[p.pos].text
+		p.pos++
+		switch name {
+		case "publish":
+			operations = &PublicationOperations{}
+			for _, operation := range strings.Split(value, ",") {
+				switch strings.ToLower(strings.TrimSpace(operation)) {
+				case "insert":
+					operations.Insert = true
+				case "update":
+					operations.Update = true
+				case "delete":
+					operations.Delete = true
+				case "truncate":
+					return nil, &pgconn.PgError{Code: "0A000" /* feature_not_supported */, Message: "publishing TRUNCATE is not supported"}
+				case "":
+				default:
+					return nil, fmt.Errorf("unrecognized value for publication option \"publish\": \"%s\"", strings.TrimSpace(operation))
+				}
+			}
+		case "publish_via_partition_root":
+			// DuckDB has no partitioned tables.
+		default:
+			return nil, fmt.Errorf("unrecognized publication parameter: \"%s\"", name)
+		}
+		if p.acceptPunctuation(")") {
+			return operations, nil
+		}
+		if !p.acceptPunctuation(",") {
+			return nil, p.syntaxError()
+		}
+	}
+}
+
+// unpublishedSchemas are the schemas whose tables are not published by the FOR ALL TABLES publications.
+var unpublishedSchemas = []string{"__sys__", "mysql", "pg_catalog", "information_schema"}
+
+// publicationStore accesses the publication and replication tables in the default catalog.
+type publicationStore struct {
+	q       roleQuerier
+	catalog string
+}
+
+func newPublicationStore(provider *catalog.DatabaseProvider, q roleQuerier) *publicationStore {
+	return &publicationStore{q: q, catalog: provider.DefaultCatalogName()}
+}
+
+// table returns the qualified name of the publication or replication table.
+func (s *publicationStore) table(t catalog.InternalTable) string {
+	return s.catalog + "." + t.QualifiedName()
+}
+
+// publicationInfo is a publication as stored in pg_publication, with its tables.
+type publicationInfo struct {
+	oid        int64
+	name       string
+	owner      int64
+	allTables  bool
+	operations PublicationOperations
+	tables     []PublicationTable
+}
+
+// publishes returns whether the publication publishes the action ('I', 'U', or 'D') on the table.
+func (p *publicationInfo) publishes(table PublicationTable, action byte) bool {
+	switch {
+	case action == 'I' && !p.operations.Insert,
+		action == 'U' && !p.operations.Update,
+		action == 'D' && !p.operations.Delete:
+		return false
+	}
+	if p.allTables {
+		return !slices.Contains(unpublishedSchemas, table.Schema)
+	}
+	return slices.Contains(p.tables, table)
+}
+
+// lookup returns the publication with the name, or nil if there is no such publication.
+func (s *publicationStore) lookup(name string) (*publicationInfo, error) {
+	pub := &publicationInfo{}
+	err := s.q.QueryRowContext(context.Background(),
+		"SELECT oid, pubname, pubowner, puballtables, pubinsert, pubupdate, pubdelete, pubtruncate FROM "+
+			s.table(catalog.InternalTables.PGPublication)+" WHERE pubname = ?",
+		name,
+	).Scan(&pub.oid, &pub.name, &pub.owner, &pub.allTables,
+		&pub.operations.Insert, &pub.operations.Update, &pub.operations.Delete, &pub.operations.Truncate)
+	if errors.Is(err, stdsql.ErrNoRows) {
+		return nil, nil
+	} else if err != nil {
+		return nil, err
+	}
+	pub.tables, err = s.publishedTables("WHERE pubname = ?", name)
+	if err != nil {
+		return nil, err
+	}
+	return pub, nil
+}
+
+// mustLookup returns the publication with the name, or an error if there is no such publication.
+func (s *publicationStore) mustLookup(name string) (*publicationInfo, error) {
+	pub, err := s.lookup(name)
+	if err == nil && pub == nil {
+		err = fmt.Errorf("publication \"%s\" does not exist", name)
+	}
+	return pub, err
+}
+
+// publishedTables returns the tables in publication_rel that satisfy the WHERE clause.
+func (s *publicationStore) publishedTables(where string, args ...any) ([]PublicationTable, error) {
+	rows, err := s.q.QueryContext(context.Background(),
+		"SELECT DISTINCT schemaname, tablename FROM "+s.table(catalog.InternalTables.PublicationRel)+" "+where+" ORDER BY 1, 2",
+		args...,
+	)
+	if err != nil {
+		return nil, err
+	}
+	defer rows.Close()
+	var tables []PublicationTable
+	for rows.Next() {
+		var table PublicationTable
+		if err := rows.Scan(&table.Schema, &table.Name); err != nil {
+			return nil, err
+		}
+		tables = append(tables, table)
+	}
+	return tables, rows.Err()
+}
+
+// currentLSN returns the subquery of the LSN of the last captured change, which is never below the LSNs confirmed
+// by the replication slots, so that the LSNs keep increasing after the confirmed changes are removed.
+func (s *publicationStore) currentLSN() string {
+	return "(SELECT greatest(" +
+		"(SELECT coalesce(max(lsn), 0) FROM " + s.table(catalog.InternalTables.ReplicationChange) + "), " +
+		"(SELECT coalesce(max(confirmed_flush_lsn), 0) FROM " + s.table(catalog.InternalTables.ReplicationSlot) + ")))"
+}
+
+// executePublicationStatement executes a publication statement in a transaction of the storage.
+func (h *ConnectionHandler) executePublicationStatement(statement ConvertedStatement) error {
+	config := statement.PublicationConfig
+	provider, err := h.catalogProvider()
+	if err != nil {
+		return err
+	}
+	ctx, err := h.duckHandler.NewContext(context.Background(), h.mysqlConn, statement.String)
+	if err != nil {
+		return err
+	}
+	if adapter.GetCurrentCatalog(ctx) != provider.DefaultCatalogName() {
+		return fmt.Errorf("publications are only supported in the default database")
+	}
+	tx, err := provider.Storage().BeginTx(context.Background(), nil)
+	if err != nil {
+		return err
+	}
+	defer tx.Rollback()
+	store := newPublicationStore(provider, tx)
+	current, err := newRoleStore(provider, tx).lookup(h.mysqlConn.User)
+	if err != nil {
+		return err
+	}
+
+	switch config.Action {
+	case PublicationCreate:
+		err = h.createPublication(ctx, store, current, config)
+	case PublicationAlter:
+		err = h.alterPublication(ctx, store, current, config)
+	case PublicationDrop:
+		err = h.dropPublications(store, current, config)
+	default:
+		err = fmt.Errorf("unsupported publication statement: %s", statement.String)
+	}
+	if err != nil {
+		return err
+	}
+	if err := tx.Commit(); err != nil {
+		return err
+	}
+	if h.server != nil {
+		if err := h.server.publisher.reload(); err != nil {
+			return err
+		}
+	}
+	return h.send(makeCommandComplete(string(config.Action), 0))
+}
+
+// resolvePublicationTables qualifies the tables by the current schema, and checks that they exist.
+func resolvePublicationTables(ctx *sql.Context, store *publicationStore, tables []PublicationTable) ([]PublicationTable, error) {
+	resolved := make([]PublicationTable, 0, len(tables))
+	for _, table := range tables {
+		if table.Schema == "" {
+			table.Schema = adapter.GetCurrentSchema(ctx)
+		}
+		var exists bool
+		if err := store.q.QueryRowContext(context.Background(),
+			"SELECT count(*) > 0 FROM duckdb_tables() WHERE database_name = ? AND schema_name = ? AND table_name = ? AND NOT temporary",
+			store.catalog, table.Schema, table.Name,
+		).Scan(&exists); err != nil {
+			return nil, err
+		}
+		if !exists {
+			return nil, fmt.Errorf("relation \"%s\" does not exist", table.Name)
+		}
+		if slices.Contains(unpublishedSchemas, table.Schema) {
+			return nil, fmt.Errorf("cannot add relation \"%s.%s\" to publication", table.Schema, table.Name)
+		}
+		if !slices.Contains(resolved, table) {
+			resolved = append(resolved, table)
+		}
+	}
+	return resolved, nil
+}
+
+// checkPublicationOwner rejects the alteration of a publication by a role other than its owner or a superuser.
+func checkPublicationOwner(current *roleInfo, pub *publicationInfo) error {
+	if !current.isSuperuser() && current.oidOrZero() != pub.owner {
+		return fmt.Errorf("must be owner of publication %s", pub.name)
+	}
+	return nil
+}
+
+func (h *ConnectionHandler) createPublication(ctx *sql.Context, store *publicationStore, current *roleInfo, config *PublicationConfig) error {
+	name := config.Names[0]
+	if existing, err := store.lookup(name); err != nil {
+		return err
+	} else if existing != nil {
+		return fmt.Errorf("publication \"%s\" already exists", name)
+	}
+	if config.AllTables && !current.isSuperuser() {
+		return fmt.Errorf("must be superuser to create FOR ALL TABLES publication")
+	}
+	tables, err := resolvePublicationTables(ctx, store, config.Tables)
+	if err != nil {
+		return err
+	}
+	operations := PublicationOperations{Insert: true, Update: true, Delete: true}
+	if config.Publish != nil {
+		operations = *config.Publish
+	}
+
+	table := store.table(catalog.InternalTables.PGPublication)
+	if _, err := store.q.ExecContext(context.Background(),
+		fmt.Sprintf("INSERT INTO %s VALUES (?, (SELECT greatest(coalesce(max(oid), 0) + 1, %d) FROM %s), ?, ?, ?, ?, ?, ?)",
+			table, pgtypes.FirstNormalObjectID, table),
+		name, current.oidOrZero(), config.AllTables,
+		operations.Insert, operations.Update, operations.Delete, operations.Truncate,
+	); err != nil {
+		return err
+	}
+	return addPublicationTables(store, name, tables)
+}
+
+func addPublicationTables(store *publicationStore, name string, tables []PublicationTable) error {
+	for _, table := range tables {
+		if _, err := store.q.ExecContext(context.Background(),
+			"INSERT INTO "+store.table(catalog.InternalTables.PublicationRel)+" VALUES (?, ?, ?)",
+			name, table.Schema, table.Name,
+		); err != nil {
+			return err
+		}
+	}
+	return nil
+}
+
+func (h *ConnectionHandler) alterPublication(ctx *sql.Context, store *publicationStore, current *roleInfo, config *PublicationConfig) error {
+	pub, err := store.mustLookup(config.Names[0])
+	if err != nil {
+		return err
+	}
+	if err := checkPublicationOwner(current, pub); err != nil {
+		return err
+	}
+
+	if config.Publish != nil {
+		_, err := store.q.ExecContext(context.Background(),
+			"UPDATE "+store.table(catalog.InternalTables.PGPublication)+
+				" SET pubinsert = ?, pubupdate = ?, pubdelete = ?, pubtruncate = ? WHERE pubname = ?",
+			config.Publish.Insert, config.Publish.Update, config.Publish.Delete, config.Publish.Truncate, pub.name,
+		)
+		return err
+	}
+
+	if pub.allTables {
+		return fmt.Errorf("publication \"%s\" is defined as FOR ALL TABLES", pub.name)
+	}
+	tables, err := resolvePublicationTables(ctx, store, config.Tables)
+	if err != nil {
+		return err
+	}
+	rel := store.table(catalog.InternalTables.PublicationRel)
+	switch config.TableAction {
+	case "ADD":
+		for _, table := range tables {
+			if slices.Contains(pub.tables, table) {
+				return fmt.Errorf("relation \"%s\" is already member of publication \"%s\"", table.Name, pub.name)
+			}
+		}
+	case "DROP":
+		for _, table := range tables {
+			if !slices.Contains(pub.tables, table) {
+				return fmt.Errorf("relation \"%s\" is not part of the publication", table.Name)
+			}
+			if _, err := store.q.ExecContext(context.Background(),
+				"DELETE FROM "+rel+" WHERE pubname = ? AND schemaname = ? AND tablename = ?",
+				pub.name, table.Schema, table.Name,
+			); err != nil {
+				return err
+			}
+		}
+		return nil
+	case "SET":
+		if _, err := store.q.ExecContext(context.Background(), "DELETE FROM "+rel+" WHERE pubname = ?", pub.name); err != nil {
+			return err
+		}
+	}
+	return addPublicationTables(store, pub.name, tables)
+}
+
+func (h *ConnectionHandler) dropPublications(store *publicationStore, current *roleInfo, config *PublicationConfig) error {
+	for _, name := range config.Names {
+		pub, err := store.lookup(name)
+		if err != nil {
+			return err
+		}
+		if pub == nil {
+			if !config.IfExists {
+				return fmt.Errorf("publication \"%s\" does not exist", name)
+			}
+			if err := h.sendNotice(ErrorResponseSeverity_Notice, "00000" /* successful_completion */, fmt.Sprintf("publication \"%s\" does not exist, skipping", name)); err != nil {
+				return err
+			}
+			continue
+		}
+		if err := checkPublicationOwner(current, pub); err != nil {
+			return err
+		}
+		for _, t := range []catalog.InternalTable{catalog.InternalTables.PublicationRel, catalog.InternalTables.PGPublication} {
+			if _, err := store.q.ExecContext(context.Background(), "DELETE FROM "+store.table(t)+" WHERE pubname = ?", name); err != nil {
+				return err
+			}
+		}
+	}
+	return nil
+}
+
+// publishedTable is the JSON form of the changes to a table in __sys__.replication_change.
+type publishedTable struct {
+	Schema  string            `json:"schema"`
+	Table   string            `json:"table"`
+	Columns []publishedColumn `json:"columns"`
+	Rows    []publishedRow    `json:"rows"`
+}
+
+// publishedColumn is a column of a published table, with the OID of its Postgres type.
+type publishedColumn struct {
+	Name string `json:"name"`
+	Type uint32 `json:"type"`
+	Key  bool   `json:"key,omitempty"`
+}
+
+// publishedRow is a change to a row, whose action is 'I', 'U', or 'D' as in pgoutput.
+type publishedRow struct {
+	Action string    `json:"action"`
+	Old    []*string `json:"old,omitempty"`
+	New    []*string `json:"new,omitempty"`
+}
+
+func newPublishedTable(changes delta.TableChanges) publishedTable {
+	table := publishedTable{
+		Schema:  changes.DBName,
+		Table:   changes.TableName,
+		Columns: make([]publishedColumn, len(changes.Schema)),
+		Rows:    make([]publishedRow, len(changes.Rows)),
+	}
+	for i, column := range changes.Schema {
+		table.Columns[i] = publishedColumn{Name: column.Name, Type: columnTypeOID(column.Type), Key: column.PrimaryKey}
+	}
+	for i, row := range changes.Rows {
+		action := "I"
+		switch row.Action {
+		case binlog.UpdateRowEvent:
+			action = "U"
+		case binlog.DeleteRowEvent:
+			action = "D"
+		}
+		table.Rows[i] = publishedRow{Action: action, Old: row.Old, New: row.New}
+	}
+	return table
+}
+
+// columnTypeOID returns the OID of the Postgres type of a column, where the MySQL types are mapped as in the results.
+func columnTypeOID(t sql.Type) uint32 {
+	if pgType, ok := t.(pgtypes.PostgresType); ok {
+		return pgType.PG.OID
+	}
+	if oid, err := VitessTypeToObjectID(t.Type()); err == nil {
+		return oid
+	}
+	return pgtype.TextOID
+}
+
+// publisher captures the changes of the published tables as the delta.ChangeSink of the replication, and wakes up
+// the connections streaming them.
+type publisher struct {
+	provider *catalog.DatabaseProvider
+
+	mu sync.RWMutex
+	// capturing is set while there are both publications and replication slots.
+	capturing bool
+	allTables bool
+	tables    []PublicationTable
+	// captured is closed once changes are captured, and then replaced.
+	captured chan struct{}
+}
+
+var _ delta.ChangeSink = (*publisher)(nil)
+
+func newPublisher(provider *catalog.DatabaseProvider) *publisher {
+	return &publisher{provider: provider, captured: make(chan struct{})}
+}
+
+// start releases the replication slots left active by the connections of a previous run, and loads the published
+// tables.
+func (p *publisher) start() error {
+	store := newPublicationStore(p.provider, p.provider.Storage())
+	if _, err := store.q.ExecContext(context.Background(),
+		"UPDATE "+store.table(catalog.InternalTables.ReplicationSlot)+" SET active_pid = NULL WHERE active_pid IS NOT NULL",
+	); err != nil {
+		return err
+	}
+	return p.reload()
+}
+
+// reload loads the published tables, after the publications or the replication slots change.
+func (p *publisher) reload() error {
+	store := newPublicationStore(p.provider, p.provider.Storage())
+	var slots, publications int
+	var allTables bool
+	err := store.q.QueryRowContext(context.Background(),
+		"SELECT (SELECT count(*) FROM "+store.table(catalog.InternalTables.ReplicationSlot)+"), count(*), coalesce(bool_or(puballtables), false)"+
+			" FROM "+store.table(catalog.InternalTables.PGPublication),
+	).Scan(&slots, &publications, &allTables)
+	if err != nil {
+		return err
+	}
+	tables, err := store.publishedTables("")
+	if err != nil {
+		return err
+	}
+
+	p.mu.Lock()
+	defer p.mu.Unlock()
+	p.capturing = slots > 0 && publications > 0
+	p.allTables = allTables
+	p.tables = tables
+	return nil
+}
+
+// Captures implements delta.ChangeSink.
+func (p *publisher) Captures(dbName, tableName string) bool {
+	p.mu.RLock()
+	defer p.mu.RUnlock()
+	if !p.capturing {
+		return false
+	}
+	if p.allTables {
+		return !slices.Contains(unpublishedSchemas, dbName)
+	}
+	return slices.Contains(p.tables, PublicationTable{Schema: dbName, Name: tableName})
+}
+
+// checkClientWrite rejects the INSERT, UPDATE, DELETE, and COPY FROM statements of the clients on the published tables,
+// whose changes would be missed by the subscribers, since only those flushed by the replication are captured.
+func (h *DuckHandler) checkClientWrite(ctx *sql.Context, parsed tree.Statement) error {
+	var table tree.TableExpr
+	switch stmt := parsed.(type) {
+	case *tree.Insert:
+		table = stmt.Table
+	case *tree.Update:
+		table = stmt.Table
+	case *tree.Delete:
+		table = stmt.Table
+	case *tree.CopyFrom:
+		table = &stmt.Table
+	}
+	if aliased, ok := table.(*tree.AliasedTableExpr); ok {
+		table = aliased.Expr
+	}
+	name, ok := table.(*tree.TableName)
+	if !ok {
+		return nil
+	}
+	provider := h.GetCatalogProvider()
+	catalogName, schema := adapter.GetCurrentCatalog(ctx), adapter.GetCurrentSchema(ctx)
+	if name.ExplicitCatalog {
+		catalogName = name.Catalog()
+	}
+	if name.ExplicitSchema {
+		schema = name.Schema()
+	}
+	if provider == nil || catalogName != provider.DefaultCatalogName() || !delta.Captured(schema, name.Table()) {
+		return nil
+	}
+	return fmt.Errorf("cannot modify table \"%s.%s\" because it is published; the published tables may only be written by the replication",
+		schema, name.Table())
+}
+
+// Capture implements delta.ChangeSink. The changes of a flush are stored as one transaction with the next LSN.
+func (p *publisher) Capture(ctx *sql.Context, tx *stdsql.Tx, changes []delta.TableChanges) error {
+	tables := make([]publishedTable, len(changes))
+	for i, c := range changes {
+		tables[i] = newPublishedTable(c)
+	}
+	data, err := json.Marshal(tables)
+	if err != nil {
+		return err
+	}
+	store := newPublicationStore(p.provider, tx)
+	if _, err := tx.ExecContext(ctx,
+		"INSERT INTO "+store.table(catalog.InternalTables.ReplicationChange)+" SELECT "+store.currentLSN()+" + 1, now(), ?",
+		string(data),
+	); err != nil {
+		return err
+	}
+
+	p.mu.Lock()
+	defer p.mu.Unlock()
+	close(p.captured)
+	p.captured = make(chan struct{})
+	return nil
+}
+
+// changes returns the channel closed once changes are captured.
+func (p *publisher) changes() <-chan struct{} {
+	p.mu.RLock()
+	defer p.mu.RUnlock()
+	return p.captured
+}
+
+// startPublisher starts capturing the changes of the published tables.
+func (s *Server) startPublisher() error {
+	if s.Provider == nil {
+		return nil
+	}
+	s.publisher = newPublisher(s.Provider)
+	if err := s.publisher.start(); err != nil {
+		return err
+	}
+	delta.SetChangeSink(s.publisher)
+	return nil
+}
@@ -0,0 +1,211 @@
+package pgserver
+
+import (
+	"testing"
+	"time"
+
+	"github.com/jackc/pglogrepl"
+	"github.com/jackc/pgx/v5/pgtype"
+	"github.com/stretchr/testify/require"
+)
+
+func TestParsePublicationSQL(t *testing.T) {
+	tests := []struct {
+		query string
+		want  *PublicationConfig
+	}{
+		{
+			query: "CREATE PUBLICATION everything FOR ALL TABLES",
+			want:  &PublicationConfig{Action: PublicationCreate, Names: []string{"everything"}, AllTables: true},
+		},
+		{
+			query: `create publication "Orders" for table only shop.orders *, items with (publish = 'insert, update', publish_via_partition_root = true);`,
+			want: &PublicationConfig{
+				Action:  PublicationCreate,
+				Names:   []string{"Orders"},
+				Tables:  []PublicationTable{{Schema: "shop", Name: "orders"}, {Name: "items"}},
+				Publish: &PublicationOperations{Insert: true, Update: true},
+			},
+		},
+		{
+			query: "CREATE PUBLICATION empty",
+			want:  &PublicationConfig{Action: PublicationCreate, Names: []string{"empty"}},
+		},
+		{
+			query: "ALTER PUBLICATION orders ADD TABLE shop.refunds",
+			want: &PublicationConfig{
+				Action:      PublicationAlter,
+				Names:       []string{"orders"},
+				TableAction: "ADD",
+				Tables:      []PublicationTable{{Schema: "shop", Name: "refunds"}},
+			},
+		},
+		{
+			query: "ALTER PUBLICATION orders SET (publish = 'delete')",
+			want:  &PublicationConfig{Action: PublicationAlter, Names: []string{"orders"}, Publish: &PublicationOperations{Delete: true}},
+		},
+		{
+			query: "DROP PUBLICATION IF EXISTS orders, everything CASCADE",
+			want:  &PublicationConfig{Action: PublicationDrop, Names: []string{"orders", "everything"}, IfExists: true},
+		},
+		{
+			query: "CREATE TABLE publication (id int)",
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.query, func(t *testing.T) {
+			config, err := parsePublicationSQL(tt.query)
+			require.NoError(t, err)
+			require.Equal(t, tt.want, config)
+		})
+	}
+
+	for _, query := range []string{
+		"CREATE PUBLICATION p FOR TABLES IN SCHEMA shop",
+		"CREATE PUBLICATION p WITH (publish = 'merge')",
+		"CREATE PUBLICATION p WITH (publish = 'insert, truncate')",
+		"CREATE PUBLICATION p WITH (streaming = on)",
+		"ALTER PUBLICATION p OWNER TO bob",
+		"DROP PUBLICATION p, q extra",
+	} {
+		_, err := parsePublicationSQL(query)
+		require.Error(t, err, query)
+	}
+}
+
+func TestParseReplicationCommand(t *testing.T) {
+	tests := []struct {
+		query string
+		want  *ReplicationCommand
+	}{
+		{
+			query: "IDENTIFY_SYSTEM",
+			want:  &ReplicationCommand{Action: ReplicationIdentifySystem},
+		},
+		{
+			query: `CREATE_REPLICATION_SLOT "sub_1" LOGICAL pgoutput (SNAPSHOT 'nothing');`,
+			want:  &ReplicationCommand{Action: ReplicationCreateSlot, Slot: "sub_1", Plugin: "pgoutput"},
+		},
+		{
+			query: "create_replication_slot sub_1 logical pgoutput noexport_snapshot",
+			want:  &ReplicationCommand{Action: ReplicationCreateSlot, Slot: "sub_1", Plugin: "pgoutput"},
+		},
+		{
+			query: "DROP_REPLICATION_SLOT sub_1 WAIT",
+			want:  &ReplicationCommand{Action: ReplicationDropSlot, Slot: "sub_1"},
+		},
+		{
+			query: `START_REPLICATION SLOT "sub_1" LOGICAL 0/1A2B (proto_version '2', publication_names '"orders",everything')`,
+			want: &ReplicationCommand{
+				Action:   ReplicationStart,
+				Slot:     "sub_1",
+				StartLSN: pglogrepl.LSN(0x1A2B),
+				Options:  map[string]string{"proto_version": "2", "publication_names": `"orders",everything`},
+			},
+		},
+		{
+			query: "SELECT 1",
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.query, func(t *testing.T) {
+			command, err := parseReplicationCommand(tt.query)
+			require.NoError(t, err)
+			require.Equal(t, tt.want, command)
+		})
+	}
+
+	for _, query := range []string{
+		"CREATE_REPLICATION_SLOT sub_1 TEMPORARY LOGICAL pgoutput",
+		"CREATE_REPLICATION_SLOT sub_1 PHYSICAL",
+		"CREATE_REPLICATION_SLOT sub_1 LOGICAL test_decoding",
+		`CREATE_REPLICATION_SLOT "Sub-1" LOGICAL pgoutput`,
+		"START_REPLICATION SLOT sub_1 PHYSICAL 0/0",
+		"START_REPLICATION 0/0",
+		"BASE_BACKUP",
+		"IDENTIFY_SYSTEM now",
+	} {
+		_, err := parseReplicationCommand(query)
+		require.Error(t, err, query)
+	}
+
+	names, err := parsePublicationNames(`"orders", everything`)
+	require.NoError(t, err)
+	require.Equal(t, []string{"orders", "everything"}, names)
+}
+
+func TestEncodePgoutput(t *testing.T) {
+	str := func(s string) *string { return &s }
+	table := publishedTable{
+		Schema: "shop",
+		Table:  "orders",
+		Columns: []publishedColumn{
+			{Name: "id", Type: pgtype.Int4OID, Key: true},
+			{Name: "note", Type: pgtype.TextOID},
+		},
+	}
+	committed := time.Date(2024, 5, 6, 7, 8, 9, 0, time.UTC)
+	lsn := pglogrepl.LSN(42)
+
+	msg, err := pglogrepl.Parse(encodeBegin(lsn, committed, 42))
+	require.NoError(t, err)
+	begin := msg.(*pglogrepl.BeginMessage)
+	require.Equal(t, lsn, begin.FinalLSN)
+	require.True(t, committed.Equal(begin.CommitTime))
+	require.Equal(t, uint32(42), begin.Xid)
+
+	msg, err = pglogrepl.Parse(encodeRelation(firstRelationID, table))
+	require.NoError(t, err)
+	relation := msg.(*pglogrepl.RelationMessage)
+	require.Equal(t, uint32(firstRelationID), relation.RelationID)
+	require.Equal(t, "shop", relation.Namespace)
+	require.Equal(t, "orders", relation.RelationName)
+	require.Equal(t, uint8('d'), relation.ReplicaIdentity)
+	require.Len(t, relation.Columns, 2)
+	require.Equal(t, uint8(1), relation.Columns[0].Flags)
+	require.Equal(t, uint8(0), relation.Columns[1].Flags)
+	require.Equal(t, "note", relation.Columns[1].Name)
+	require.Equal(t, uint32(pgtype.TextOID), relation.Columns[1].DataType)
+	require.Equal(t, int32(-1), relation.Columns[1].TypeModifier)
+
+	msg, err = pglogrepl.Parse(encodeRowChange(firstRelationID, table, publishedRow{Action: "I", New: []*string{str("1"), nil}}))
+	require.NoError(t, err)
+	insert := msg.(*pglogrepl.InsertMessage)
+	require.Equal(t, uint8('t'), insert.Tuple.Columns[0].DataType)
+	require.Equal(t, []byte("1"), insert.Tuple.Columns[0].Data)
+	require.Equal(t, uint8('n'), insert.Tuple.Columns[1].DataType)
+
+	// The old row is not sent unless the key is updated.
+	msg, err = pglogrepl.Parse(encodeRowChange(firstRelationID, table, publishedRow{Action: "U", Old: []*string{str("1"), nil}, New: []*string{str("1"), str("new")}}))
+	require.NoError(t, err)
+	update := msg.(*pglogrepl.UpdateMessage)
+	require.Nil(t, update.OldTuple)
+	require.Equal(t, []byte("new"), update.NewTuple.Columns[1].Data)
+
+	msg, err = pglogrepl.Parse(encodeRowChange(firstRelationID, table, publishedRow{Action: "U", Old: []*string{str("1"), str("old")}, New: []*string{str("2"), str("old")}}))
+	require.NoError(t, err)
+	update = msg.(*pglogrepl.UpdateMessage)
+	require.Equal(t, uint8('K'), update.OldTupleType)
+	require.Equal(t, []byte("1"), update.OldTuple.Columns[0].Data)
+	require.Equal(t, uint8('n'), update.OldTuple.Columns[1].DataType)
+
+	msg, err = pglogrepl.Parse(encodeRowChange(firstRelationID, table, publishedRow{Action: "D", Old: []*string{str("2"), str("old")}}))
+	require.NoError(t, err)
+	deleted := msg.(*pglogrepl.DeleteMessage)
+	require.Equal(t, uint8('K'), deleted.OldTupleType)
+	require.Equal(t, []byte("2"), deleted.OldTuple.Columns[0].Data)
+
+	// The replica identity of a table without a primary key is the whole row.
+	table.Columns[0].Key = false
+	msg, err = pglogrepl.Parse(encodeRowChange(firstRelationID, table, publishedRow{Action: "D", Old: []*string{str("2"), str("old")}}))
+	require.NoError(t, err)
+	deleted = msg.(*pglogrepl.DeleteMessage)
+	require.Equal(t, uint8('O'), deleted.OldTupleType)
+	require.Equal(t, []byte("old"), deleted.OldTuple.Columns[1].Data)
+
+	msg, err = pglogrepl.Parse(encodeCommit(lsn, committed))
+	require.NoError(t, err)
+	commit := msg.(*pglogrepl.CommitMessage)
+	require.Equal(t, lsn, commit.CommitLSN)
+	require.Equal(t, lsn, commit.TransactionEndLSN)
+}
@@ -0,0 +1,1459 @@
+package pgserver
+
+import (
+	"context"
+	stdsql "database/sql"
+	"errors"
+	"fmt"
+	"regexp"
+	"slices"
+	"strconv"
+	"strings"
+
+	"github.com/apecloud/myduckserver/adapter"
+	"github.com/apecloud/myduckserver/catalog"
+	"github.com/dolthub/doltgresql/server/auth"
+	"github.com/dolthub/go-mysql-server/sql"
+	"github.com/jackc/pgx/v5/pgproto3"
+	"github.com/marcboeker/go-duckdb"
+)
+
+// This file handles the statements of role management:
+//
+//	CREATE ROLE name [WITH] option ...;         -- CREATE USER is the same, with LOGIN by default.
+//	ALTER ROLE name [WITH] option ...;          -- Also ALTER ROLE name RENAME TO new_name.
+//	DROP ROLE [IF EXISTS] name, ...;
+//	GRANT role, ... TO member, ... [WITH ADMIN OPTION];
+//	REVOKE [ADMIN OPTION FOR] role, ... FROM member, ...;
+//	GRANT CONNECT ON DATABASE db, ... TO role|PUBLIC, ...;
+//	REVOKE CONNECT ON DATABASE db, ... FROM role|PUBLIC, ...;
+//
+// The options are SUPERUSER, CREATEDB, CREATEROLE, INHERIT, LOGIN, REPLICATION, BYPASSRLS, their NO forms,
+// CONNECTION LIMIT n, [ENCRYPTED] PASSWORD 'password'|NULL, VALID UNTIL 'timestamp', and, for CREATE ROLE, IN ROLE
+// and ROLE, which grant memberships.
+//
+// The roles are stored in the __sys__.pg_authid, __sys__.pg_auth_members, and __sys__.database_privilege tables of
+// the default catalog, with their passwords in __sys__.role_password, and shown in the pg_roles and pg_user views. A
+// role statement takes effect immediately, outside the transaction of the connection. The passwords are stored as
+// SCRAM-SHA-256 verifiers, and used to authenticate the roles at login, where the LOGIN attribute, the password
+// expiry, the connection limit, and the CONNECT privilege on the database are checked as well. The password of the
+// bootstrap superuser "postgres" is given by the server option, so that it can't be altered by a statement.
+//
+// The privileges on the other objects are not checked, so the roles only control who may log in. The internal tables
+// of the roles may only be written by the superusers, and that of the passwords only read by them, which is checked
+// against the tables that DuckDB binds a statement to (see checkTableAccess). pg_authid never shows the passwords,
+// unlike in Postgres. The pg_roles view of a database other than the default one only lists the bootstrap superuser.
+
+// RoleAction represents the type of role statement.
+type RoleAction string
+
+const (
+	RoleCreate        RoleAction = "CREATE ROLE"
+	RoleAlter         RoleAction = "ALTER ROLE"
+	RoleDrop          RoleAction = "DROP ROLE"
+	RoleGrant         RoleAction = "GRANT ROLE"
+	RoleRevoke        RoleAction = "REVOKE ROLE"
+	RoleGrantConnect  RoleAction = "GRANT CONNECT"
+	RoleRevokeConnect RoleAction = "REVOKE CONNECT"
+)
+
+// RoleConfig represents a parsed role statement.
+type RoleConfig struct {
+	Action RoleAction
+	// Roles are the roles created, altered, dropped, granted, or revoked.
+	Roles    []string
+	IfExists bool
+	// NewName is the new name of the role renamed by ALTER ROLE.
+	NewName string
+	Options []RoleOption
+	// InRoles are the roles that the created role is made a member of.
+	InRoles []string
+	// Members are the roles made members of the roles, or removed from them, or granted or revoked CONNECT, where
+	// "public" stands for all the roles.
+	Members     []string
+	AdminOption bool
+	Databases   []string
+}
+
+// RoleOption is an attribute of a role, given as its column in pg_authid and its value.
+type RoleOption struct {
+	Column string
+	Value  any
+}
+
+// Tag returns the command tag of the statement.
+func (c *RoleConfig) Tag() string {
+	switch c.Action {
+	case RoleGrantConnect:
+		return "GRANT"
+	case RoleRevokeConnect:
+		return "REVOKE"
+	default:
+		return string(c.Action)
+	}
+}
+
+// bootstrapSuperuserOID is the OID of the "postgres" role, as in Postgres.
+const bootstrapSuperuserOID = 10
+
+// publicRoleOID stands for PUBLIC in the roles granted CONNECT on a database.
+const publicRoleOID = 0
+
+// firstRoleOID is the OID of the first role created by a statement, which is the first normal OID in Postgres.
+const firstRoleOID = 16384
+
+// roleAttributes maps the boolean options of a role to their columns in pg_authid.
+var roleAttributes = map[string]string{
+	"superuser":   "rolsuper",
+	"inherit":     "rolinherit",
+	"createrole":  "rolcreaterole",
+	"createdb":    "rolcreatedb",
+	"login":       "rolcanlogin",
+	"replication": "rolreplication",
+	"bypassrls":   "rolbypassrls",
+}
+
+// superuserAttributes are the columns of the attributes that only a superuser may grant.
+var superuserAttributes = []string{"rolsuper", "rolreplication", "rolbypassrls"}
+
+// roleStatementRegex matches the statements that may be role statements, which are then tokenized.
+var roleStatementRegex = regexp.MustCompile(`(?is)^(CREATE\s+(ROLE|USER)|ALTER\s+(ROLE|USER)|DROP\s+(ROLE|USER)|GRANT|REVOKE)\s`)
+
+// parseRoleSQL parses the given SQL statement and returns a RoleConfig, or nil if it is not a role statement.
+// The GRANT and REVOKE statements on the privileges of the objects other than CONNECT on databases are not role
+// statements.
+func parseRoleSQL(query string) (*RoleConfig, error) {
+	query = strings.TrimSpace(sql.RemoveSpaceAndDelimiter(RemoveComments(query), ';'))
+	if !roleStatementRegex.MatchString(query) {
+		return nil, nil
+	}
+	tokens, err := tokenizeRoleSQL(query)
+	if err != nil {
+		return nil, err
+	}
+	p := &roleParser{tokens: tokens}
+	var config *RoleConfig
+	switch {
+	case p.accept("create"):
+		config, err = p.parseCreate()
+	case p.accept("alter"):
+		config, err = p.parseAlter()
+	case p.accept("drop"):
+		config, err = p.parseDrop()
+	case p.accept("grant"):
+		config, err = p.parseGrant(true)
+	case p.accept("revoke"):
+		config, err = p.parseGrant(false)
+	}
+	if config == nil || err != nil {
+		return nil, err
+	}
+	if !p.done() {
+		return nil, p.syntaxError()
+	}
+	return config, nil
+}
+
+type roleTokenKind int
+
+const (
+	// roleTokenWord is a keyword or an unquoted identifier, which is lower-cased.
+	roleTokenWord roleTokenKind = iota
+	roleTokenIdentifier
+	roleTokenString
+	roleTokenNumber
+	roleTokenPunctuation
+)
+
+type roleToken struct {
+	kind roleTokenKind
+	text string
+}
+
+// tokenizeRoleSQL splits a role statement into its words, quoted identifiers, string literals, numbers, and
+// punctuation.
+func tokenizeRoleSQL(query string) ([]roleToken, error) {
+	isWordByte := func(c byte) bool {
+		return c == '_' || c == '$' || c >= 0x80 || (c >= 'a' && c <= 'z') || (c >= 'A' && c <= 'Z') || (c >= '0' && c <= '9')
+	}
+	isDigit := func(c byte) bool { return c >= '0' && c <= '9' }
+	var tokens []roleToken
+	for i := 0; i < len(query); {
+		c := query[i]
+		switch {
+		case c == ' ' || c == '\t' || c == '\n' || c == '\r' || c == '\f':
+			i++
+		case c == '\'' || c == '"':
+			var b strings.Builder
+			j := i + 1
+			for ; ; j++ {
+				if j >= len(query) {
+					return nil, fmt.Errorf("unterminated quoted string at or near \"%s\"", query[i:])
+				}
+				if query[j] == c {
+					// A doubled quote stands for the quote itself.
+					if j+1 < len(query) && query[j+1] == c {
+						b.WriteByte(c)
+						j++
+						continue
+					}
+					break
+				}
+				b.WriteByte(query[j])
+			}
+			kind := roleTokenString
+			if c == '"' {
+				kind = roleTokenIdentifier
+			}
+			tokens = append(tokens, roleToken{kind: kind, text: b.String()})
+			i = j + 1
+		case isDigit(c) || (c == '-' && i+1 < len(query) && isDigit(query[i+1])):
+			j := i + 1
+			for j < len(query) && isDigit(query[j]) {
+				j++
+			}
+			tokens = append(tokens, roleToken{kind: roleTokenNumber, text: query[i:j]})
+			i = j
+		case isWordByte(c):
+			j := i + 1
+			for j < len(query) && isWordByte(query[j]) {
+				j++
+			}
+			tokens = append(tokens, roleToken{kind: roleTokenWord, text: strings.ToLower(query[i:j])})
+			i = j
+		default:
+			tokens = append(tokens, roleToken{kind: roleTokenPunctuation, text: string(c)})
+			i++
+		}
+	}
+	return tokens, nil
+}
+
+// roleParser parses the tokens of a role statement.
+type roleParser struct {
+	tokens []roleToken
+	pos    int
+}
+
+func (p *roleParser) done() bool {
+	return p.pos >= len(p.tokens)
+}
+
+// accept consumes the given keywords if they are next, and returns whether they are.
+func (p *roleParser) accept(words ...string) bool {
+	if p.pos+len(words) > len(p.tokens) {
+		return false
+	}
+	for i, word := range words {
+		if token := p.tokens[p.pos+i]; token.kind != roleTokenWord || token.text != word {
+			return false
+		}
+	}
+	p.pos += len(words)
+	return true
+}
+
+// expect consumes the given keywords, or returns a syntax error if they are not next.
+func (p *roleParser) expect(words ...string) error {
+	if !p.accept(words...) {
+		return p.syntaxError()
+	}
+	return nil
+}
+
+// contains returns whether the keyword is in the remaining tokens.
+func (p *roleParser) contains(word string) bool {
+	return slices.ContainsFunc(p.tokens[p.pos:], func(token roleToken) bool {
+		return token.kind == roleTokenWord && token.text == word
+	})
+}
+
+func (p *roleParser) syntaxError() error {
+	if p.done() {
+		return fmt.Errorf("syntax error at end of input")
+	}
+	return fmt.Errorf("syntax error at or near \"%s\"", p.tokens[p.pos].text)
+}
+
+// name consumes a name, which is either a word or a quoted identifier.
+func (p *roleParser) name() (string, error) {
+	if p.done() {
+		return "", p.syntaxError()
+	}
+	token := p.tokens[p.pos]
+	if token.kind != roleTokenWord && token.kind != roleTokenIdentifier {
+		return "", p.syntaxError()
+	}
+	p.pos++
+	return token.text, nil
+}
+
+// names consumes a comma-separated list of names.
+func (p *roleParser) names() ([]string, error) {
+	var names []string
+	for {
+		name, err := p.name()
+		if err != nil {
+			return nil, err
+		}
+		names = append(names, name)
+		if p.done() || p.tokens[p.pos].text != "," || p.tokens[p.pos].kind != roleTokenPunctuation {
+			return names, nil
+		}
+		p.pos++
+	}
+}
+
+// value consumes a string literal, or NULL, which is returned as nil.
+func (p *roleParser) value() (any, error) {
+	if p.accept("null") {
+		return nil, nil
+	}
+	if p.done() || p.tokens[p.pos].kind != roleTokenString {
+		return nil, p.syntaxError()
+	}
+	p.pos++
+	return p.tokens[p.pos-1].text, nil
+}
+
+func (p *roleParser) parseCreate() (*RoleConfig, error) {
+	var user bool
+	switch {
+	case p.accept("role"):
+	case p.accept("user"):
+		user = true
+	default:
+		return nil, nil
+	}
+	name, err := p.name()
+	if err != nil {
+		return nil, err
+	}
+	config := &RoleConfig{Action: RoleCreate, Roles: []string{name}}
+	if err := p.parseOptions(config, true); err != nil {
+		return nil, err
+	}
+	// CREATE USER is CREATE ROLE with LOGIN by default.
+	if user && !slices.ContainsFunc(config.Options, func(o RoleOption) bool { return o.Column == "rolcanlogin" }) {
+		config.Options = append(config.Options, RoleOption{Column: "rolcanlogin", Value: true})
+	}
+	return config, nil
+}
+
+func (p *roleParser) parseAlter() (*RoleConfig, error) {
+	if !p.accept("role") && !p.accept("user") {
+		return nil, nil
+	}
+	name, err := p.name()
+	if err != nil {
+		return nil, err
+	}
+	config := &RoleConfig{Action: RoleAlter, Roles: []string{name}}
+	switch {
+	case p.accept("rename", "to"):
+		if config.NewName, err = p.name(); err != nil {
+			return nil, err
+		}
+	case p.accept("set"), p.accept("reset"), p.accept("in"):
+		return nil, fmt.Errorf("ALTER ROLE ... SET and RESET are not supported")
+	default:
+		if err := p.parseOptions(config, false); err != nil {
+			return nil, err
+		}
+	}
+	return config, nil
+}
+
+func (p *roleParser) parseOptions(config *RoleConfig, create bool) error {
+	p.accept("with")
+	seen := make(map[string]bool)
+	option := func(column string, value any) error {
+		if seen[column] {
+			return fmt.Errorf("conflicting or redundant options")
+		}
+		seen[column] = true
+		config.Options = append(config.Options, RoleOption{Column: column, Value: value})
+		return nil
+	}
+	for !p.done() {
+		token := p.tokens[p.pos]
+		if token.kind != roleTokenWord {
+			return p.syntaxError()
+		}
+		p.pos++
+		if column, ok := roleAttributes[token.text]; ok {
+			if err := option(column, true); err != nil {
+				return err
+			}
+			continue
+		}
+		if column, ok := roleAttributes[strings.TrimPrefix(token.text, "no")]; ok && strings.HasPrefix(token.text, "no") {
+			if err := option(column, false); err != nil {
+				return err
+			}
+			continue
+		}
+		var err error
+		switch token.text {
+		case "connection":
+			if err := p.expect("limit"); err != nil {
+				return err
+			}
+			if p.done() || p.tokens[p.pos].kind != roleTokenNumber {
+				return p.syntaxError()
+			}
+			limit, parseErr := strconv.ParseInt(p.tokens[p.pos].text, 10, 32)
+			if parseErr != nil || limit < -1 {
+				return fmt.Errorf("invalid connection limit: %s", p.tokens[p.pos].text)
+			}
+			p.pos++
+			err = option("rolconnlimit", int32(limit))
+		case "encrypted", "password":
+			if token.text == "encrypted" {
+				if err := p.expect("password"); err != nil {
+					return err
+				}
+			}
+			password, valueErr := p.value()
+			if valueErr != nil {
+				return valueErr
+			}
+			err = option("rolpassword", password)
+		case "unencrypted":
+			return fmt.Errorf("UNENCRYPTED PASSWORD is no longer supported")
+		case "valid":
+			if err := p.expect("until"); err != nil {
+				return err
+			}
+			if p.done() || p.tokens[p.pos].kind != roleTokenString {
+				return p.syntaxError()
+			}
+			err = option("rolvaliduntil", p.tokens[p.pos].text)
+			p.pos++
+		case "in":
+			if !create || !(p.accept("role") || p.accept("group")) {
+				p.pos--
+				return p.syntaxError()
+			}
+			roles, namesErr := p.names()
+			if namesErr != nil {
+				return namesErr
+			}
+			config.InRoles = append(config.InRoles, roles...)
+		case "role", "user":
+			if !create {
+				p.pos--
+				return p.syntaxError()
+			}
+			members, namesErr := p.names()
+			if namesErr != nil {
+				return namesErr
+			}
+			config.Members = append(config.Members, members...)
+		default:
+			return fmt.Errorf("unrecognized role option \"%s\"", token.text)
+		}
+		if err != nil {
+			return err
+		}
+	}
+	return nil
+}
+
+func (p *roleParser) parseDrop() (*RoleConfig, error) {
+	if !p.accept("role") && !p.accept("user") {
+		return nil, nil
+	}
+	config := &RoleConfig{Action: RoleDrop, IfExists: p.accept("if", "exists")}
+	var err error
+	if config.Roles, err = p.names(); err != nil {
+		return nil, err
+	}
+	return config, nil
+}
+
+func (p *roleParser) parseGrant(grant bool) (*RoleConfig, error) {
+	to := "to"
+	if !grant {
+		to = "from"
+	}
+	config := &RoleConfig{Action: RoleGrant}
+	if !grant {
+		config.Action = RoleRevoke
+	}
+	var err error
+	switch {
+	case p.accept("connect", "on", "database"):
+		config.Action = RoleGrantConnect
+		if !grant {
+			config.Action = RoleRevokeConnect
+		}
+		if config.Databases, err = p.names(); err != nil {
+			return nil, err
+		}
+	case p.contains("on"):
+		// A privilege on an object other than a database.
+		return nil, nil
+	default:
+		if !grant && p.accept("admin", "option", "for") {
+			config.AdminOption = true
+		}
+		if config.Roles, err = p.names(); err != nil {
+			return nil, err
+		}
+	}
+	if err := p.expect(to); err != nil {
+		return nil, err
+	}
+	if config.Members, err = p.names(); err != nil {
+		return nil, err
+	}
+	if grant && config.Action == RoleGrant && p.accept("with") {
+		if !p.accept("admin", "option") && !p.accept("admin", "true") {
+			return nil, p.syntaxError()
+		}
+		config.AdminOption = true
+	}
+	// The grantor is always the current role.
+	if p.accept("granted", "by") {
+		if _, err := p.name(); err != nil {
+			return nil, err
+		}
+	}
+	if !grant {
+		_ = p.accept("cascade") || p.accept("restrict")
+	}
+	return config, nil
+}
+
+// roleQuerier is either the storage or a transaction of it.
+type roleQuerier interface {
+	ExecContext(ctx context.Context, query string, args ...any) (stdsql.Result, error)
+	QueryContext(ctx context.Context, query string, args ...any) (*stdsql.Rows, error)
+	QueryRowContext(ctx context.Context, query string, args ...any) *stdsql.Row
+}
+
+// roleStore accesses the role tables in the default catalog, whichever database the connection is using.
+type roleStore struct {
+	q       roleQuerier
+	catalog string
+}
+
+func newRoleStore(provider *catalog.DatabaseProvider, q roleQuerier) *roleStore {
+	return &roleStore{q: q, catalog: provider.DefaultCatalogName()}
+}
+
+// table returns the qualified name of the role table.
+func (s *roleStore) table(t catalog.InternalTable) string {
+	return s.catalog + "." + t.QualifiedName()
+}
+
+// roleInfo is a role as stored in pg_authid.
+type roleInfo struct {
+	oid        int64
+	name       string
+	super      bool
+	createRole bool
+	canLogin   bool
+	connLimit  int32
+	password   stdsql.NullString
+	expired    bool
+}
+
+func (r *roleInfo) isSuperuser() bool {
+	return r != nil && r.super
+}
+
+func (r *roleInfo) canCreateRoles() bool {
+	return r != nil && (r.super || r.createRole)
+}
+
+// oidOrZero returns the OID of the role, or 0 if there is no role.
+func (r *roleInfo) oidOrZero() int64 {
+	if r == nil {
+		return 0
+	}
+	return r.oid
+}
+
+// lookup returns the role with the name, or nil if there is no such role.
+func (s *roleStore) lookup(name string) (*roleInfo, error) {
+	role := &roleInfo{}
+	err := s.q.QueryRowContext(context.Background(),
+		"SELECT a.oid, a.rolname, a.rolsuper, a.rolcreaterole, a.rolcanlogin, a.rolconnlimit, p.rolpassword,"+
+			" coalesce(a.rolvaliduntil < now(), false) FROM "+s.table(catalog.InternalTables.PGAuthID)+" a"+
+			" LEFT JOIN "+s.table(catalog.InternalTables.RolePassword)+" p ON p.oid = a.oid WHERE a.rolname = ?",
+		name,
+	).Scan(&role.oid, &role.name, &role.super, &role.createRole, &role.canLogin, &role.connLimit, &role.password, &role.expired)
+	if errors.Is(err, stdsql.ErrNoRows) {
+		return nil, nil
+	} else if err != nil {
+		return nil, err
+	}
+	return role, nil
+}
+
+// setPassword stores the password of the role with the name, or removes it if it is NULL.
+func (s *roleStore) setPassword(name string, password stdsql.NullString) error {
+	table, roles := s.table(catalog.InternalTables.RolePassword), s.table(catalog.InternalTables.PGAuthID)
+	if !password.Valid {
+		_, err := s.q.ExecContext(context.Background(),
+			"DELETE FROM "+table+" WHERE oid = (SELECT oid FROM "+roles+" WHERE rolname = ?)", name,
+		)
+		return err
+	}
+	_, err := s.q.ExecContext(context.Background(),
+		"INSERT OR REPLACE INTO "+table+" SELECT oid, ? FROM "+roles+" WHERE rolname = ?", password.String, name,
+	)
+	return err
+}
+
+// mustLookup returns the role with the name, or an error if there is no such role.
+func (s *roleStore) mustLookup(name string) (*roleInfo, error) {
+	role, err := s.lookup(name)
+	if err == nil && role == nil {
+		err = fmt.Errorf("role \"%s\" does not exist", name)
+	}
+	return role, err
+}
+
+// memberOf returns the OIDs of the role and of the roles it is a member of, directly or indirectly.
+func (s *roleStore) memberOf(oid int64) ([]int64, error) {
+	rows, err := s.q.QueryContext(context.Background(), `WITH RECURSIVE member_of(oid) AS (
+		SELECT ?::BIGINT
+		UNION
+		SELECT m.roleid FROM `+s.table(catalog.InternalTables.PGAuthMembers)+` m JOIN member_of ON m.member = member_of.oid
+	) SELECT oid FROM member_of`, oid)
+	if err != nil {
+		return nil, err
+	}
+	defer rows.Close()
+	var oids []int64
+	for rows.Next() {
+		var oid int64
+		if err := rows.Scan(&oid); err != nil {
+			return nil, err
+		}
+		oids = append(oids, oid)
+	}
+	return oids, rows.Err()
+}
+
+// membership returns whether the member is directly a member of the role, and whether it has the admin option.
+func (s *roleStore) membership(roleID, member int64) (isMember bool, admin bool, err error) {
+	err = s.q.QueryRowContext(context.Background(),
+		"SELECT admin_option FROM "+s.table(catalog.InternalTables.PGAuthMembers)+" WHERE roleid = ? AND member = ?",
+		roleID, member,
+	).Scan(&admin)
+	if errors.Is(err, stdsql.ErrNoRows) {
+		return false, false, nil
+	}
+	return err == nil, admin, err
+}
+
+// connectList returns the OIDs of the roles granted CONNECT on the database, and whether they are set. All the roles
+// may connect to a database whose list is not set.
+func (s *roleStore) connectList(database string) ([]int64, bool, error) {
+	var list string
+	err := s.q.QueryRowContext(context.Background(),
+		"SELECT connect::VARCHAR FROM "+s.table(catalog.InternalTables.DatabasePrivilege)+" WHERE database = ?", database,
+	).Scan(&list)
+	if errors.Is(err, stdsql.ErrNoRows) {
+		return nil, false, nil
+	} else if err != nil {
+		return nil, false, err
+	}
+	var oids []int64
+	for _, element := range strings.Split(strings.Trim(list, "[]"), ",") {
+		if element = strings.TrimSpace(element); element == "" {
+			continue
+		}
+		oid, err := strconv.ParseInt(element, 10, 64)
+		if err != nil {
+			return nil, false, fmt.Errorf("invalid CONNECT privilege list of database \"%s\": %s", database, list)
+		}
+		oids = append(oids, oid)
+	}
+	return oids, true, nil
+}
+
+func (s *roleStore) setConnectList(database string, oids []int64) error {
+	elements := make([]string, len(oids))
+	for i, oid := range oids {
+		elements[i] = strconv.FormatInt(oid, 10)
+	}
+	_, err := s.q.ExecContext(context.Background(),
+		"INSERT OR REPLACE INTO "+s.table(catalog.InternalTables.DatabasePrivilege)+" VALUES (?, ?::BIGINT[])",
+		database, "["+strings.Join(elements, ", ")+"]",
+	)
+	return err
+}
+
+// canConnect returns whether the role may connect to the database.
+func (s *roleStore) canConnect(role *roleInfo, database string) (bool, error) {
+	if role.super {
+		return true, nil
+	}
+	granted, ok, err := s.connectList(database)
+	if err != nil || !ok || slices.Contains(granted, publicRoleOID) {
+		return err == nil, err
+	}
+	memberOf, err := s.memberOf(role.oid)
+	if err != nil {
+		return false, err
+	}
+	return slices.ContainsFunc(memberOf, func(oid int64) bool { return slices.Contains(granted, oid) }), nil
+}
+
+// syncAuthRole updates the role used for authentication from pg_authid. A role without a password can't be
+// authenticated, so it is dropped.
+func (s *roleStore) syncAuthRole(name string) error {
+	role, err := s.lookup(name)
+	if err != nil || role == nil || !role.password.Valid {
+		auth.DropRole(name)
+		return err
+	}
+	password, err := ParseScramSha256Verifier(role.password.String)
+	if err != nil {
+		return err
+	}
+	var authRole auth.Role
+	if auth.RoleExists(name) {
+		authRole = auth.GetRole(name)
+	} else {
+		authRole = auth.CreateDefaultRole(name)
+	}
+	authRole.CanLogin = role.canLogin
+	authRole.Password = password
+	auth.SetRole(authRole)
+	return nil
+}
+
+// catalogProvider returns the database provider, whose storage keeps the role tables.
+func (h *ConnectionHandler) catalogProvider() (*catalog.DatabaseProvider, error) {
+	provider := h.duckHandler.GetCatalogProvider()
+	if provider == nil {
+		return nil, fmt.Errorf("database provider not found")
+	}
+	return provider, nil
+}
+
+// currentRole returns the role of the connection, or nil if the user is not a stored role, which is possible when
+// authentication is disabled.
+func (h *ConnectionHandler) currentRole() (*roleInfo, error) {
+	provider, err := h.catalogProvider()
+	if err != nil {
+		return nil, err
+	}
+	return newRoleStore(provider, provider.Storage()).lookup(h.mysqlConn.User)
+}
+
+// isSuperuser returns whether the role of the connection is a superuser.
+func (h *ConnectionHandler) isSuperuser() bool {
+	role, err := h.currentRole()
+	if err != nil {
+		h.logger.WithError(err).Warnln("Failed to look up the role of the connection")
+	}
+	return role.isSuperuser()
+}
+
+// superuserTables are the internal tables that only the superusers may write, which are those of the roles, and those
+// of the foreign servers. Of them, secretTables are those that only the superusers may read either, which hold the
+// passwords, while the others are shown by views such as pg_roles and pg_foreign_server.
+var (
+	superuserTables = []catalog.InternalTable{
+		catalog.InternalTables.PGAuthID,
+		catalog.InternalTables.RolePassword,
+		catalog.InternalTables.PGAuthMembers,
+		catalog.InternalTables.DatabasePrivilege,
+		catalog.InternalTables.ForeignServer,
+		catalog.InternalTables.UserMapping,
+	}
+	secretTables = []catalog.InternalTable{
+		catalog.InternalTables.RolePassword,
+		catalog.InternalTables.UserMapping,
+	}
+
+	superuserTableRegex = tableNameRegex(superuserTables)
+	secretTableRegex    = tableNameRegex(secretTables)
+)
+
+// tableNameRegex matches the names of the tables as words, in a statement or in the plan of one.
+func tableNameRegex(tables []catalog.InternalTable) *regexp.Regexp {
+	names := make([]string, len(tables))
+	for i, table := range tables {
+		names[i] = table.Name
+	}
+	return regexp.MustCompile(`(?i)\b(` + strings.Join(names, "|") + `)\b`)
+}
+
+// permissionDenied returns the error of the first table that the regex matches in the text, or nil if there is none.
+func permissionDenied(regex *regexp.Regexp, text string) error {
+	if table := regex.FindString(text); table != "" {
+		return fmt.Errorf("permission denied for table %s", strings.ToLower(table))
+	}
+	return nil
+}
+
+// checkTableAccess rejects a statement of a role other than a superuser that reads one of the secretTables, or that
+// may write one of the superuserTables. The tables read are those of the plan of the statement, which DuckDB binds
+// with the parameters |vars|, so that the reads through views, CTEs, and the query and query_table functions are
+// rejected as well. The tables written are those named by the statement, since the views of DuckDB cannot be
+// written, and the query functions only run SELECT statements.
+func (h *DuckHandler) checkTableAccess(ctx *sql.Context, query string, vars []any) error {
+	if h.connectionHandler == nil || h.connectionHandler.isSuperuser() {
+		return nil
+	}
+	conn, err := adapter.GetCatalogConn(ctx)
+	if err != nil {
+		return err
+	}
+	// Preparing the statement fails if the query has several statements, of which only the first would be explained.
+	var stmtType duckdb.StmtType
+	if err := conn.Raw(func(driverConn interface{}) error {
+		stmt, err := driverConn.(*duckdb.Conn).PrepareContext(ctx, query)
+		if err != nil {
+			return err
+		}
+		defer stmt.Close()
+		stmtType = stmt.(*duckdb.Stmt).StatementType()
+		return nil
+	}); err != nil {
+		return err
+	}
+
+	// The other statements, such as the transaction ones, only read the tables they name.
+	plan := query
+	switch stmtType {
+	case duckdb.DUCKDB_STATEMENT_TYPE_SELECT,
+		duckdb.DUCKDB_STATEMENT_TYPE_INSERT,
+		duckdb.DUCKDB_STATEMENT_TYPE_UPDATE,
+		duckdb.DUCKDB_STATEMENT_TYPE_DELETE,
+		duckdb.DUCKDB_STATEMENT_TYPE_CREATE,
+		duckdb.DUCKDB_STATEMENT_TYPE_CALL,
+		duckdb.DUCKDB_STATEMENT_TYPE_VARIABLE_SET:
+		if plan, err = explainPlan(ctx, conn, query, vars); err != nil {
+			return err
+		}
+	}
+	if err := permissionDenied(secretTableRegex, plan); err != nil {
+		return err
+	}
+	if stmtType == duckdb.DUCKDB_STATEMENT_TYPE_SELECT {
+		return nil
+	}
+	return permissionDenied(superuserTableRegex, RemoveComments(query))
+}
+
+// explainPlan returns the plan of the statement, in JSON if DuckDB supports it, or the statement itself if DuckDB fails
+// to explain it.
+func explainPlan(ctx context.Context, conn *stdsql.Conn, query string, vars []any) (string, error) {
+	rows, err := conn.QueryContext(ctx, "EXPLAIN (FORMAT JSON) "+query, vars...)
+	if err != nil {
+		rows, err = conn.QueryContext(ctx, "EXPLAIN "+query, vars...)
+	}
+	if err != nil {
+		return query, nil
+	}
+	defer rows.Close()
+	var plan strings.Builder
+	for rows.Next() {
+		var key, value string
+		if err := rows.Scan(&key, &value); err != nil {
+			return "", err
+		}
+		plan.WriteString(value)
+	}
+	return plan.String(), rows.Err()
+}
+
+// executeRoleStatement executes a role statement in a transaction of the storage, and updates the roles used for
+// authentication once it commits.
+func (h *ConnectionHandler) executeRoleStatement(statement ConvertedStatement) error {
+	config := statement.RoleConfig
+	provider, err := h.catalogProvider()
+	if err != nil {
+		return err
+	}
+	tx, err := provider.Storage().BeginTx(context.Background(), nil)
+	if err != nil {
+		return err
+	}
+	defer tx.Rollback()
+	store := newRoleStore(provider, tx)
+	current, err := store.lookup(h.mysqlConn.User)
+	if err != nil {
+		return err
+	}
+
+	// changed are the roles whose passwords or LOGIN attributes may have changed, and dropped the roles dropped.
+	var changed, dropped []string
+	switch config.Action {
+	case RoleCreate:
+		err = h.createRole(store, current, config)
+		changed = config.Roles
+	case RoleAlter:
+		err = h.alterRole(store, current, config)
+		changed = []string{config.Roles[0]}
+		if config.NewName != "" {
+			changed, dropped = []string{config.NewName}, []string{config.Roles[0]}
+		}
+	case RoleDrop:
+		dropped, err = h.dropRoles(store, current, config)
+	case RoleGrant, RoleRevoke:
+		err = h.grantRoles(store, current, config)
+	case RoleGrantConnect, RoleRevokeConnect:
+		err = h.grantConnect(store, current, config, provider)
+	default:
+		err = fmt.Errorf("unsupported role statement: %s", statement.String)
+	}
+	if err != nil {
+		return err
+	}
+	if err := tx.Commit(); err != nil {
+		return err
+	}
+
+	for _, name := range dropped {
+		auth.DropRole(name)
+	}
+	stored := newRoleStore(provider, provider.Storage())
+	for _, name := range changed {
+		if err := stored.syncAuthRole(name); err != nil {
+			return err
+		}
+	}
+	return h.send(makeCommandComplete(config.Tag(), 0))
+}
+
+// checkRoleName rejects the reserved names of roles.
+func checkRoleName(name string) error {
+	if name == "public" || name == "none" || strings.HasPrefix(name, "pg_") {
+		return fmt.Errorf("role name \"%s\" is reserved", name)
+	}
+	return nil
+}
+
+// roleValues converts the options of a role to the values of their columns of pg_authid, and returns the password
+// apart, hashed, since it is stored in role_password: nil if it is not set, and NULL if it is removed.
+func (h *ConnectionHandler) roleValues(options []RoleOption) ([]string, []any, *stdsql.NullString, error) {
+	columns := make([]string, 0, len(options))
+	values := make([]any, 0, len(options))
+	var password *stdsql.NullString
+	for _, option := range options {
+		if option.Column != "rolpassword" {
+			columns = append(columns, option.Column)
+			values = append(values, option.Value)
+			continue
+		}
+		password = &stdsql.NullString{}
+		value, ok := option.Value.(string)
+		switch {
+		case !ok:
+			// PASSWORD NULL removes the password.
+		case value == "":
+			if err := h.sendNotice(ErrorResponseSeverity_Notice, "00000" /* successful_completion */, "empty string is not a valid password, clearing password"); err != nil {
+				return nil, nil, nil, err
+			}
+		case strings.HasPrefix(value, scramSha256VerifierPrefix):
+			if _, err := ParseScramSha256Verifier(value); err != nil {
+				return nil, nil, nil, err
+			}
+			*password = stdsql.NullString{String: value, Valid: true}
+		case strings.HasPrefix(value, "md5") && len(value) == 35:
+			return nil, nil, nil, fmt.Errorf("MD5 passwords are not supported, only SCRAM-SHA-256 ones")
+		default:
+			verifier, err := auth.NewScramSha256Password(value)
+			if err != nil {
+				return nil, nil, nil, err
+			}
+			*password = stdsql.NullString{String: FormatScramSha256Verifier(verifier), Valid: true}
+		}
+	}
+	return columns, values, password, nil
+}
+
+// checkRoleOptions checks that the current role may set the options.
+func checkRoleOptions(current *roleInfo, options []RoleOption, action string) error {
+	for _, option := range options {
+		if slices.Contains(superuserAttributes, option.Column) && option.Value == true && !current.isSuperuser() {
+			return fmt.Errorf("permission denied to %s: only superusers may grant the %s attribute",
+				action, strings.ToUpper(strings.TrimPrefix(option.Column, "rol")))
+		}
+	}
+	return nil
+}
+
+// nextRoleOID returns the subquery of the OID of a new role in pg_authid.
+func nextRoleOID(table string) string {
+	return fmt.Sprintf("(SELECT greatest(coalesce(max(oid), 0) + 1, %d) FROM %s)", firstRoleOID, table)
+}
+
+// setClause returns the SET clause of an UPDATE statement of pg_authid for the columns.
+func setClause(columns []string) string {
+	assignments := make([]string, len(columns))
+	for i, column := range columns {
+		assignments[i] = column + " = ?"
+		if column == "rolvaliduntil" {
+			assignments[i] += "::TIMESTAMPTZ"
+		}
+	}
+	return strings.Join(assignments, ", ")
+}
+
+func (h *ConnectionHandler) createRole(store *roleStore, current *roleInfo, config *RoleConfig) error {
+	name := config.Roles[0]
+	if !current.canCreateRoles() {
+		return fmt.Errorf("permission denied to create role: only superusers and roles with CREATEROLE may create roles")
+	}
+	if err := checkRoleOptions(current, config.Options, "create role"); err != nil {
+		return err
+	}
+	if err := checkRoleName(name); err != nil {
+		return err
+	}
+	if existing, err := store.lookup(name); err != nil {
+		return err
+	} else if existing != nil {
+		return fmt.Errorf("role \"%s\" already exists", name)
+	}
+	columns, values, password, err := h.roleValues(config.Options)
+	if err != nil {
+		return err
+	}
+
+	table := store.table(catalog.InternalTables.PGAuthID)
+	if _, err := store.q.ExecContext(context.Background(),
+		"INSERT INTO "+table+" VALUES ("+nextRoleOID(table)+", ?, false, true, false, false, false, false, false, -1, NULL)",
+		name,
+	); err != nil {
+		return err
+	}
+	if len(columns) > 0 {
+		if _, err := store.q.ExecContext(context.Background(),
+			"UPDATE "+table+" SET "+setClause(columns)+" WHERE rolname = ?", append(values, name)...,
+		); err != nil {
+			return err
+		}
+	}
+	if password != nil {
+		if err := store.setPassword(name, *password); err != nil {
+			return err
+		}
+	}
+
+	for _, role := range config.InRoles {
+		if err := h.grantRole(store, current, role, name, false); err != nil {
+			return err
+		}
+	}
+	for _, member := range config.Members {
+		if err := h.grantRole(store, current, name, member, false); err != nil {
+			return err
+		}
+	}
+	return nil
+}
+
+func (h *ConnectionHandler) alterRole(store *roleStore, current *roleInfo, config *RoleConfig) error {
+	name := config.Roles[0]
+	role, err := store.mustLookup(name)
+	if err != nil {
+		return err
+	}
+	if role.oid == bootstrapSuperuserOID {
+		return fmt.Errorf("role \"%s\" is the bootstrap superuser, whose password is set by the server option, and cannot be altered", name)
+	}
+	// Any role may change its own password.
+	ownPassword := role.oid == current.oidOrZero() && len(config.Options) == 1 && config.Options[0].Column == "rolpassword"
+	if !ownPassword {
+		if !current.canCreateRoles() || (role.super && !current.isSuperuser()) {
+			return fmt.Errorf("permission denied to alter role \"%s\"", name)
+		}
+		if err := checkRoleOptions(current, config.Options, "alter role"); err != nil {
+			return err
+		}
+	}
+
+	table := store.table(catalog.InternalTables.PGAuthID)
+	if config.NewName != "" {
+		if name == h.mysqlConn.User {
+			return fmt.Errorf("session user cannot be renamed")
+		}
+		if err := checkRoleName(config.NewName); err != nil {
+			return err
+		}
+		if existing, err := store.lookup(config.NewName); err != nil {
+			return err
+		} else if existing != nil {
+			return fmt.Errorf("role \"%s\" already exists", config.NewName)
+		}
+		_, err := store.q.ExecContext(context.Background(), "UPDATE "+table+" SET rolname = ? WHERE oid = ?", config.NewName, role.oid)
+		return err
+	}
+
+	columns, values, password, err := h.roleValues(config.Options)
+	if err != nil {
+		return err
+	}
+	if len(columns) > 0 {
+		if _, err := store.q.ExecContext(context.Background(),
+			"UPDATE "+table+" SET "+setClause(columns)+" WHERE oid = ?", append(values, role.oid)...,
+		); err != nil {
+			return err
+		}
+	}
+	if password != nil {
+		return store.setPassword(name, *password)
+	}
+	return nil
+}
+
+func (h *ConnectionHandler) dropRoles(store *roleStore, current *roleInfo, config *RoleConfig) ([]string, error) {
+	if !current.canCreateRoles() {
+		return nil, fmt.Errorf("permission denied to drop role: only superusers and roles with CREATEROLE may drop roles")
+	}
+	var dropped []string
+	for _, name := range config.Roles {
+		role, err := store.lookup(name)
+		if err != nil {
+			return nil, err
+		}
+		switch {
+		case role == nil && config.IfExists:
+			if err := h.sendNotice(ErrorResponseSeverity_Notice, "00000" /* successful_completion */, fmt.Sprintf("role \"%s\" does not exist, skipping", name)); err != nil {
+				return nil, err
+			}
+			continue
+		case role == nil:
+			return nil, fmt.Errorf("role \"%s\" does not exist", name)
+		case role.oid == bootstrapSuperuserOID:
+			return nil, fmt.Errorf("cannot drop role %s because it is required by the database system", name)
+		case name == h.mysqlConn.User:
+			return nil, fmt.Errorf("current user cannot be dropped")
+		case role.super && !current.isSuperuser():
+			return nil, fmt.Errorf("permission denied to drop role \"%s\"", name)
+		}
+
+		var database string
+		err = store.q.QueryRowContext(context.Background(),
+			"SELECT database FROM "+store.table(catalog.InternalTables.DatabasePrivilege)+" WHERE list_contains(connect, ?) LIMIT 1",
+			role.oid,
+		).Scan(&database)
+		if err == nil {
+			return nil, fmt.Errorf("role \"%s\" cannot be dropped because some objects depend on it: privileges for database %s", name, database)
+		} else if !errors.Is(err, stdsql.ErrNoRows) {
+			return nil, err
+		}
+		if _, err := store.q.ExecContext(context.Background(),
+			"DELETE FROM "+store.table(catalog.InternalTables.PGAuthMembers)+" WHERE roleid = ? OR member = ?", role.oid, role.oid,
+		); err != nil {
+			return nil, err
+		}
+		if _, err := store.q.ExecContext(context.Background(),
+			"DELETE FROM "+store.table(catalog.InternalTables.RolePassword)+" WHERE oid = ?", role.oid,
+		); err != nil {
+			return nil, err
+		}
+		if _, err := store.q.ExecContext(context.Background(),
+			"DELETE FROM "+store.table(catalog.InternalTables.PGAuthID)+" WHERE oid = ?", role.oid,
+		); err != nil {
+			return nil, err
+		}
+		dropped = append(dropped, name)
+	}
+	return dropped, nil
+}
+
+func (h *ConnectionHandler) grantRoles(store *roleStore, current *roleInfo, config *RoleConfig) error {
+	for _, role := range config.Roles {
+		for _, member := range config.Members {
+			var err error
+			if config.Action == RoleGrant {
+				err = h.grantRole(store, current, role, member, config.AdminOption)
+			} else {
+				err = h.revokeRole(store, current, role, member, config.AdminOption)
+			}
+			if err != nil {
+				return err
+			}
+		}
+	}
+	return nil
+}
+
+// checkGrantRole checks that the current role may grant or revoke the membership of the role, which requires the
+// CREATEROLE attribute or the admin option on the role, and a superuser for a superuser role.
+func checkGrantRole(store *roleStore, current *roleInfo, role *roleInfo, action string) error {
+	if current.isSuperuser() {
+		return nil
+	}
+	if !role.super {
+		if current.canCreateRoles() {
+			return nil
+		}
+		if current != nil {
+			_, admin, err := store.membership(role.oid, current.oid)
+			if err != nil || admin {
+				return err
+			}
+		}
+	}
+	return fmt.Errorf("permission denied to %s role \"%s\"", action, role.name)
+}
+
+// grantRole makes the member a member of the role.
+func (h *ConnectionHandler) grantRole(store *roleStore, current *roleInfo, roleName, memberName string, admin bool) error {
+	role, err := store.mustLookup(roleName)
+	if err != nil {
+		return err
+	}
+	member, err := store.mustLookup(memberName)
+	if err != nil {
+		return err
+	}
+	if err := checkGrantRole(store, current, role, "grant"); err != nil {
+		return err
+	}
+	// A role can't be a member of itself, directly or indirectly.
+	memberOf, err := store.memberOf(role.oid)
+	if err != nil {
+		return err
+	}
+	if slices.Contains(memberOf, member.oid) {
+		return fmt.Errorf("role \"%s\" is a member of role \"%s\"", role.name, member.name)
+	}
+
+	isMember, hasAdmin, err := store.membership(role.oid, member.oid)
+	if err != nil {
+		return err
+	}
+	if isMember && (hasAdmin || !admin) {
+		return h.sendNotice(ErrorResponseSeverity_Notice, "00000" /* successful_completion */, fmt.Sprintf("role \"%s\" is already a member of role \"%s\"", member.name, role.name))
+	}
+	grantor := int64(bootstrapSuperuserOID)
+	if current != nil {
+		grantor = current.oid
+	}
+	_, err = store.q.ExecContext(context.Background(),
+		"INSERT OR REPLACE INTO "+store.table(catalog.InternalTables.PGAuthMembers)+" VALUES (?, ?, ?, ?)",
+		role.oid, member.oid, grantor, admin,
+	)
+	return err
+}
+
+// revokeRole removes the member from the role, or only its admin option.
+func (h *ConnectionHandler) revokeRole(store *roleStore, current *roleInfo, roleName, memberName string, adminOnly bool) error {
+	role, err := store.mustLookup(roleName)
+	if err != nil {
+		return err
+	}
+	member, err := store.mustLookup(memberName)
+	if err != nil {
+		return err
+	}
+	if err := checkGrantRole(store, current, role, "revoke"); err != nil {
+		return err
+	}
+	isMember, _, err := store.membership(role.oid, member.oid)
+	if err != nil {
+		return err
+	}
+	if !isMember {
+		return h.sendNotice(ErrorResponseSeverity_Warning, "01000" /* warning */, fmt.Sprintf("role \"%s\" is not a member of role \"%s\"", member.name, role.name))
+	}
+	table := store.table(catalog.InternalTables.PGAuthMembers)
+	if adminOnly {
+		_, err = store.q.ExecContext(context.Background(), "UPDATE "+table+" SET admin_option = false WHERE roleid = ? AND member = ?", role.oid, member.oid)
+	} else {
+		_, err = store.q.ExecContext(context.Background(), "DELETE FROM "+table+" WHERE roleid = ? AND member = ?", role.oid, member.oid)
+	}
+	return err
+}
+
+// grantConnect grants or revokes the CONNECT privilege on the databases.
+func (h *ConnectionHandler) grantConnect(store *roleStore, current *roleInfo, config *RoleConfig, provider *catalog.DatabaseProvider) error {
+	if !current.isSuperuser() {
+		return fmt.Errorf("permission denied to %s CONNECT: only superusers may grant the privileges on databases", strings.ToLower(config.Tag()))
+	}
+	members := make([]int64, len(config.Members))
+	for i, name := range config.Members {
+		if name == "public" {
+			members[i] = publicRoleOID
+			continue
+		}
+		role, err := store.mustLookup(name)
+		if err != nil {
+			return err
+		}
+		members[i] = role.oid
+	}
+	for _, database := range config.Databases {
+		database = resolveDatabaseName(provider, database)
+		if !provider.HasCatalog(database) {
+			return fmt.Errorf("database \"%s\" does not exist", database)
+		}
+		granted, ok, err := store.connectList(database)
+		if err != nil {
+			return err
+		}
+		if !ok {
+			granted = []int64{publicRoleOID}
+		}
+		for _, member := range members {
+			if config.Action == RoleGrantConnect {
+				if !slices.Contains(granted, member) {
+					granted = append(granted, member)
+				}
+			} else {
+				granted = slices.DeleteFunc(granted, func(oid int64) bool { return oid == member })
+			}
+		}
+		if err := store.setConnectList(database, granted); err != nil {
+			return err
+		}
+	}
+	return nil
+}
+
+// resolveDatabaseName returns the catalog of the database, where "postgres" is the default catalog, as at login.
+func resolveDatabaseName(provider *catalog.DatabaseProvider, database string) string {
+	if database == "postgres" || database == "mysql" {
+		return provider.DefaultCatalogName()
+	}
+	return database
+}
+
+// checkRoleLogin rejects the connection if its role may not log in to the database: if the role lacks the LOGIN
+// attribute, its password has expired, its connection limit is reached, or it lacks the CONNECT privilege. The users
+// that are not stored roles may log in only if authentication is disabled, in which case they are accepted.
+func (h *ConnectionHandler) checkRoleLogin() error {
+	provider := h.duckHandler.GetCatalogProvider()
+	if provider == nil {
+		return nil
+	}
+	store := newRoleStore(provider, provider.Storage())
+	user := h.mysqlConn.User
+	role, err := store.lookup(user)
+	if err != nil || role == nil {
+		return err
+	}
+
+	var code, message string
+	switch {
+	case !role.canLogin:
+		code, message = "28000" /* invalid_authorization_specification */, fmt.Sprintf("role \"%s\" is not permitted to log in", user)
+	case role.expired:
+		code, message = "28P01" /* invalid_password */, fmt.Sprintf("password authentication failed for user \"%s\"", user)
+	case role.connLimit >= 0 && !role.super && h.server != nil && h.server.countBackends(user) > int(role.connLimit):
+		code, message = "53300" /* too_many_connections */, fmt.Sprintf("too many connections for role \"%s\"", user)
+	default:
+		ctx, err := h.duckHandler.NewContext(context.Background(), h.mysqlConn, "")
+		if err != nil {
+			return err
+		}
+		database := adapter.GetCurrentCatalog(ctx)
+		allowed, err := store.canConnect(role, database)
+		if err != nil {
+			return err
+		}
+		if !allowed {
+			code, message = "42501" /* insufficient_privilege */, fmt.Sprintf("permission denied for database \"%s\"", database)
+		}
+	}
+	if code == "" {
+		return nil
+	}
+	_ = h.send(&pgproto3.ErrorResponse{
+		Severity: string(ErrorResponseSeverity_Fatal),
+		Code:     code,
+		Message:  message,
+		Routine:  "InitPostgres",
+	})
+	return errors.New(message)
+}
+
+// countBackends returns the number of the connections of the user.
+func (s *Server) countBackends(user string) int {
+	count := 0
+	s.backends.Range(func(_, v any) bool {
+		if v.(*ConnectionHandler).mysqlConn.User == user {
+			count++
+		}
+		return true
+	})
+	return count
+}
+
+// loadRoles makes the stored roles with passwords available for authentication.
+func (s *Server) loadRoles() error {
+	if s.Provider == nil {
+		return nil
+	}
+	store := newRoleStore(s.Provider, s.Provider.Storage())
+	rows, err := store.q.QueryContext(context.Background(),
+		"SELECT a.rolname FROM "+store.table(catalog.InternalTables.PGAuthID)+" a JOIN "+
+			store.table(catalog.InternalTables.RolePassword)+" p ON p.oid = a.oid WHERE a.oid <> ?",
+		bootstrapSuperuserOID,
+	)
+	if err != nil {
+		return err
+	}
+	var names []string
+	for rows.Next() {
+		var name string
+		if err := rows.Scan(&name); err != nil {
+			rows.Close()
+			return err
+		}
+		names = append(names, name)
+	}
+	rows.Close()
+	if err := rows.Err(); err != nil {
+		return err
+	}
+	for _, name := range names {
+		if err := store.syncAuthRole(name); err != nil {
+			return fmt.Errorf("failed to load role \"%s\": %w", name, err)
+		}
+	}
+	return nil
+}
+
+// LoadUsers loads the users of the file at |path| (see the LoadUsers function), and stores them as login roles, so
+// that they are listed in pg_roles. The password in the file replaces the stored one, and the bootstrap superuser is
+// not stored, since its password is given by the server option.
+func (s *Server) LoadUsers(path string) error {
+	roles, err := LoadUsers(path)
+	if err != nil || s.Provider == nil {
+		return err
+	}
+	store := newRoleStore(s.Provider, s.Provider.Storage())
+	table := store.table(catalog.InternalTables.PGAuthID)
+	for _, role := range roles {
+		if role.Name == "postgres" {
+			continue
+		}
+		verifier := FormatScramSha256Verifier(role.Password)
+		result, err := store.q.ExecContext(context.Background(),
+			"UPDATE "+table+" SET rolcanlogin = true WHERE rolname = ?", role.Name,
+		)
+		var updated int64
+		if err == nil {
+			updated, err = result.RowsAffected()
+		}
+		if err == nil && updated == 0 {
+			_, err = store.q.ExecContext(context.Background(),
+				"INSERT INTO "+table+" VALUES ("+nextRoleOID(table)+", ?, false, true, false, false, true, false, false, -1, NULL)",
+				role.Name,
+			)
+		}
+		if err == nil {
+			err = store.setPassword(role.Name, stdsql.NullString{String: verifier, Valid: true})
+		}
+		if err != nil {
+			return fmt.Errorf("failed to store user \"%s\": %w", role.Name, err)
+		}
+	}
+	return nil
+}
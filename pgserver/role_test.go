@@ -0,0 +1,138 @@
+package pgserver
+
+import (
+	"testing"
+
+	"github.com/stretchr/testify/require"
+)
+
+func TestParseRoleSQL(t *testing.T) {
+	tests := []struct {
+		query string
+		want  *RoleConfig
+	}{
+		{
+			query: "CREATE ROLE reader",
+			want:  &RoleConfig{Action: RoleCreate, Roles: []string{"reader"}},
+		},
+		{
+			query: `create user "Alice" with password 'it''s' connection limit 2 valid until '2030-01-01' in role readers, "Writers";`,
+			want: &RoleConfig{
+				Action: RoleCreate,
+				Roles:  []string{"Alice"},
+				Options: []RoleOption{
+					{Column: "rolpassword", Value: "it's"},
+					{Column: "rolconnlimit", Value: int32(2)},
+					{Column: "rolvaliduntil", Value: "2030-01-01"},
+					{Column: "rolcanlogin", Value: true},
+				},
+				InRoles: []string{"readers", "Writers"},
+			},
+		},
+		{
+			query: "CREATE USER bob NOLOGIN CREATEDB ROLE carol",
+			want: &RoleConfig{
+				Action:  RoleCreate,
+				Roles:   []string{"bob"},
+				Options: []RoleOption{{Column: "rolcanlogin", Value: false}, {Column: "rolcreatedb", Value: true}},
+				Members: []string{"carol"},
+			},
+		},
+		{
+			query: "ALTER ROLE bob WITH NOSUPERUSER ENCRYPTED PASSWORD NULL",
+			want: &RoleConfig{
+				Action:  RoleAlter,
+				Roles:   []string{"bob"},
+				Options: []RoleOption{{Column: "rolsuper", Value: false}, {Column: "rolpassword", Value: nil}},
+			},
+		},
+		{
+			query: "ALTER USER bob RENAME TO robert",
+			want:  &RoleConfig{Action: RoleAlter, Roles: []string{"bob"}, NewName: "robert"},
+		},
+		{
+			query: "DROP ROLE IF EXISTS bob, carol",
+			want:  &RoleConfig{Action: RoleDrop, Roles: []string{"bob", "carol"}, IfExists: true},
+		},
+		{
+			query: "GRANT readers, writers TO bob WITH ADMIN OPTION",
+			want:  &RoleConfig{Action: RoleGrant, Roles: []string{"readers", "writers"}, Members: []string{"bob"}, AdminOption: true},
+		},
+		{
+			query: "REVOKE ADMIN OPTION FOR readers FROM bob CASCADE",
+			want:  &RoleConfig{Action: RoleRevoke, Roles: []string{"readers"}, Members: []string{"bob"}, AdminOption: true},
+		},
+		{
+			query: "GRANT CONNECT ON DATABASE mydb TO bob",
+			want:  &RoleConfig{Action: RoleGrantConnect, Databases: []string{"mydb"}, Members: []string{"bob"}},
+		},
+		{
+			query: "REVOKE CONNECT ON DATABASE mydb FROM PUBLIC",
+			want:  &RoleConfig{Action: RoleRevokeConnect, Databases: []string{"mydb"}, Members: []string{"public"}},
+		},
+		{
+			query: "GRANT SELECT ON t TO bob",
+		},
+		{
+			query: "CREATE TABLE t (id INT)",
+		},
+		{
+			query: "DROP TABLE t",
+		},
+	}
+
+	for _, tt := range tests {
+		got, err := parseRoleSQL(tt.query)
+		require.NoError(t, err, tt.query)
+		require.Equal(t, tt.want, got, tt.query)
+	}
+
+	for _, query := range []string{
+		"CREATE ROLE bob LOGIN NOLOGIN",
+		"CREATE ROLE bob SUPERPOWERS",
+		"CREATE ROLE bob CONNECTION LIMIT -2",
+		"CREATE ROLE bob PASSWORD 'unterminated",
+		"ALTER ROLE bob IN ROLE readers",
+		"GRANT readers TO",
+	} {
+		_, err := parseRoleSQL(query)
+		require.Error(t, err, query)
+	}
+}
+
+func TestRoleConfigTag(t *testing.T) {
+	require.Equal(t, "CREATE ROLE", (&RoleConfig{Action: RoleCreate}).Tag())
+	require.Equal(t, "GRANT ROLE", (&RoleConfig{Action: RoleGrant}).Tag())
+	require.Equal(t, "GRANT", (&RoleConfig{Action: RoleGrantConnect}).Tag())
+	require.Equal(t, "REVOKE", (&RoleConfig{Action: RoleRevokeConnect}).Tag())
+}
+
+func TestSuperuserTableRegex(t *testing.T) {
+	for _, query := range []string{
+		`UPDATE "__sys__"."pg_authid" SET rolsuper = true`,
+		"update __sys__ . PG_AUTHID set rolsuper = true",
+		"INSERT INTO database_privilege VALUES ('postgres', [])",
+		"INSERT INTO pg_catalog.pg_auth_members VALUES (10, 16384, 10, true)",
+		"TRUNCATE __sys__.role_password",
+		"DELETE FROM __sys__.foreign_server",
+		"UPDATE __sys__.user_mapping SET umoptions = []",
+	} {
+		require.True(t, superuserTableRegex.MatchString(query), query)
+	}
+
+	for _, query := range []string{
+		"INSERT INTO t SELECT * FROM __sys__.pg_authid_masked",
+		"UPDATE t SET name = 'pg_authids'",
+		"DELETE FROM pg_roles_backup",
+	} {
+		require.False(t, superuserTableRegex.MatchString(query), query)
+	}
+}
+
+func TestSecretTableRegex(t *testing.T) {
+	require.EqualError(t, permissionDenied(secretTableRegex, `[{"name": "SEQ_SCAN ", "extra_info": {"Table": "role_password"}}]`),
+		"permission denied for table role_password")
+	require.EqualError(t, permissionDenied(secretTableRegex, `[{"name": "SEQ_SCAN ", "extra_info": {"Table": "user_mapping"}}]`),
+		"permission denied for table user_mapping")
+	require.NoError(t, permissionDenied(secretTableRegex, `[{"name": "SEQ_SCAN ", "extra_info": {"Table": "pg_authid"}}]`))
+}
@@ -0,0 +1,169 @@
+package pgserver
+
+import (
+	"context"
+	"errors"
+	"fmt"
+
+	"github.com/apecloud/myduckserver/adapter"
+	"github.com/cockroachdb/cockroachdb-parser/pkg/sql/sem/tree"
+)
+
+// DuckDB does not support savepoints, and a failed statement aborts the whole transaction. Savepoints are therefore
+// emulated by MyDuck Server: the statements run in a transaction block are logged, and ROLLBACK TO SAVEPOINT rolls
+// back the transaction in DuckDB, then starts a new one and replays the statements run before the savepoint:
+//
+//	BEGIN;
+//	INSERT INTO t VALUES (1);
+//	SAVEPOINT s;
+//	INSERT INTO t VALUES (1); -- Fails with a constraint violation, which aborts the transaction.
+//	ROLLBACK TO SAVEPOINT s;  -- Runs ROLLBACK, BEGIN, and INSERT INTO t VALUES (1).
+//	COMMIT;
+//
+// SELECT statements are not replayed, which also matches the non-transactional nature of sequences. A transaction
+// in which COPY FROM STDIN has run can't be replayed, so ROLLBACK TO SAVEPOINT fails in it. The cursors declared in
+// the transaction are closed by ROLLBACK TO SAVEPOINT, and the notifications queued after the savepoint are dropped.
+
+// transactionLog is the log of the statements run in the current transaction block.
+type transactionLog struct {
+	// begin is the statement that started the transaction block.
+	begin      loggedStatement
+	statements []loggedStatement
+	savepoints []savepoint
+	// unreplayable is the tag of the first statement in the transaction that can't be replayed, if any.
+	unreplayable string
+}
+
+// loggedStatement is a statement run in a transaction block, along with its bound parameters if it is run through
+// the extended query protocol.
+type loggedStatement struct {
+	query string
+	ast   tree.Statement
+	vars  []any
+}
+
+// savepoint marks the position of a SAVEPOINT statement in the transaction log.
+type savepoint struct {
+	name          string
+	statements    int
+	notifications int
+}
+
+// logTransactionStatement records a statement that has run successfully for the transaction tracking.
+func (h *ConnectionHandler) logTransactionStatement(statement ConvertedStatement, vars []any) {
+	log := &h.transactionLog
+	switch statement.AST.(type) {
+	case *tree.BeginTransaction:
+		*log = transactionLog{begin: loggedStatement{query: statement.String, ast: statement.AST}}
+	case *tree.CommitTransaction, *tree.RollbackTransaction:
+		*log = transactionLog{}
+	case *tree.Select:
+	default:
+		if h.inTransaction {
+			log.statements = append(log.statements, loggedStatement{query: statement.String, ast: statement.AST, vars: vars})
+		}
+	}
+}
+
+// markTransactionUnreplayable records that a statement that can't be replayed has run in the transaction block.
+func (h *ConnectionHandler) markTransactionUnreplayable(tag string) {
+	if h.inTransaction && h.transactionLog.unreplayable == "" {
+		h.transactionLog.unreplayable = tag
+	}
+}
+
+// executeSavepointStatement handles SAVEPOINT, RELEASE SAVEPOINT, and ROLLBACK TO SAVEPOINT.
+func (h *ConnectionHandler) executeSavepointStatement(statement ConvertedStatement) error {
+	log := &h.transactionLog
+	switch stmt := statement.AST.(type) {
+	case *tree.Savepoint:
+		if !h.inTransaction {
+			return fmt.Errorf("SAVEPOINT can only be used in transaction blocks")
+		}
+		log.savepoints = append(log.savepoints, savepoint{
+			name:          string(stmt.Name),
+			statements:    len(log.statements),
+			notifications: len(h.outgoingNotifications),
+		})
+	case *tree.ReleaseSavepoint:
+		if !h.inTransaction {
+			return fmt.Errorf("RELEASE SAVEPOINT can only be used in transaction blocks")
+		}
+		i, err := h.findSavepoint(string(stmt.Savepoint))
+		if err != nil {
+			return err
+		}
+		log.savepoints = log.savepoints[:i]
+	case *tree.RollbackToSavepoint:
+		if !h.inTransaction {
+			return fmt.Errorf("ROLLBACK TO SAVEPOINT can only be used in transaction blocks")
+		}
+		i, err := h.findSavepoint(string(stmt.Savepoint))
+		if err != nil {
+			return err
+		}
+		if err := h.rollbackToSavepoint(log.savepoints[i]); err != nil {
+			return err
+		}
+		log.savepoints = log.savepoints[:i+1]
+	}
+	return h.send(makeCommandComplete(statement.Tag, 0))
+}
+
+// findSavepoint returns the index of the latest savepoint with the name.
+func (h *ConnectionHandler) findSavepoint(name string) (int, error) {
+	for i := len(h.transactionLog.savepoints) - 1; i >= 0; i-- {
+		if h.transactionLog.savepoints[i].name == name {
+			return i, nil
+		}
+	}
+	return 0, fmt.Errorf("savepoint \"%s\" does not exist", name)
+}
+
+// rollbackToSavepoint restarts the transaction in DuckDB and replays the statements run before the savepoint.
+func (h *ConnectionHandler) rollbackToSavepoint(sp savepoint) error {
+	log := &h.transactionLog
+	if log.unreplayable != "" {
+		return fmt.Errorf("ROLLBACK TO SAVEPOINT is not supported in a transaction in which %s has run", log.unreplayable)
+	}
+
+	h.endCursorTransaction(false)
+	h.outgoingNotifications = h.outgoingNotifications[:sp.notifications]
+	statements := log.statements[:sp.statements]
+	log.statements = statements
+
+	if _, err := h.execInsertBatch("ROLLBACK", &tree.RollbackTransaction{}); err != nil {
+		return err
+	}
+	replay := func() error {
+		if _, err := h.execInsertBatch(log.begin.query, &tree.BeginTransaction{}); err != nil {
+			return err
+		}
+		for _, statement := range statements {
+			if err := h.replayStatement(statement); err != nil {
+				return err
+			}
+		}
+		return nil
+	}
+	if err := replay(); err != nil {
+		// The transaction is left aborted, and has to be rolled back by the client.
+		log.unreplayable = "ROLLBACK TO SAVEPOINT"
+		return errors.Join(fmt.Errorf("failed to replay the %d statements before savepoint \"%s\"", len(statements), sp.name), err)
+	}
+	return nil
+}
+
+// replayStatement runs a logged statement again, discarding its result.
+func (h *ConnectionHandler) replayStatement(statement loggedStatement) error {
+	if statement.vars == nil {
+		_, err := h.execInsertBatch(statement.query, statement.ast)
+		return err
+	}
+	ctx, err := h.duckHandler.NewContext(context.Background(), h.mysqlConn, statement.query)
+	if err != nil {
+		return err
+	}
+	_, err = adapter.ExecCatalog(ctx, statement.query, statement.vars...)
+	return err
+}
@@ -2,6 +2,9 @@ package pgserver
 
 import (
 	"fmt"
+	"path/filepath"
+	"sync"
+
 	"github.com/apecloud/myduckserver/catalog"
 	"github.com/dolthub/go-mysql-server/server"
 	"github.com/dolthub/go-mysql-server/sql"
@@ -12,6 +15,11 @@ type Server struct {
 	Listener       *Listener
 	Provider       *catalog.DatabaseProvider
 	NewInternalCtx func() *sql.Context
+
+	backends      sync.Map // connection ID -> *ConnectionHandler, for cancellation and pg_stat_activity
+	notifications notificationHub
+	advisoryLocks advisoryLockTable
+	publisher     *publisher
 }
 
 func NewServer(provider *catalog.DatabaseProvider, host string, port int, password string, newCtx func() *sql.Context, options ...ListenerOpt) (*Server, error) {
@@ -32,7 +40,23 @@ func NewServer(provider *catalog.DatabaseProvider, host string, port int, passwo
 	if err != nil {
 		return nil, err
 	}
-	return &Server{Listener: listener, Provider: provider, NewInternalCtx: newCtx}, nil
+	if listener.socketDir != "" {
+		path := filepath.Join(listener.socketDir, fmt.Sprintf(".s.PGSQL.%d", port))
+		if listener.socket, err = listenUnixSocket(path, listener.socketPerm); err != nil {
+			return nil, fmt.Errorf("failed to listen on the Unix domain socket: %w", err)
+		}
+	}
+	s := &Server{Listener: listener, Provider: provider, NewInternalCtx: newCtx}
+	if err := s.loadRoles(); err != nil {
+		return nil, fmt.Errorf("failed to load the roles: %w", err)
+	}
+	if err := s.attachForeignServers(); err != nil {
+		return nil, fmt.Errorf("failed to attach the foreign servers: %w", err)
+	}
+	if err := s.startPublisher(); err != nil {
+		return nil, fmt.Errorf("failed to start the publisher: %w", err)
+	}
+	return s, nil
 }
 
 func (s *Server) Start() {
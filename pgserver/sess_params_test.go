@@ -72,10 +72,34 @@ func TestSessParam(t *testing.T) {
 			name: "Show ALL",
 			executions: []Execution{
 				{
-					// SHOW ALL is not supported yet.
 					SQL:      "SHOW ALL;",
+					Expected: [][]string{{"allow_in_place_tablespaces", "off", "Allows tablespaces directly inside pg_tblspc, for testing."}},
+					WantErr:  false,
+				},
+			},
+		},
+		{
+			name: "Show boolean parameter",
+			executions: []Execution{
+				{
+					SQL:      "SHOW standard_conforming_strings;",
+					Expected: [][]string{{"on"}},
+					WantErr:  false,
+				},
+			},
+		},
+		{
+			name: "Select from pg_settings",
+			executions: []Execution{
+				{
+					SQL:      "SET work_mem = '64MB';",
 					Expected: nil,
-					WantErr:  true,
+					WantErr:  false,
+				},
+				{
+					SQL:      "SELECT setting, unit, vartype FROM pg_catalog.pg_settings WHERE name = 'work_mem';",
+					Expected: [][]string{{"65536", "kB", "integer"}},
+					WantErr:  false,
 				},
 			},
 		},
@@ -410,13 +434,106 @@ func TestSessParam(t *testing.T) {
 			name: "Reset ALL",
 			executions: []Execution{
 				{
-					// RESET ALL is not supported yet.
+					SQL:      "SET application_name TO 'myDUCK';",
+					Expected: nil,
+					WantErr:  false,
+				},
+				{
+					SQL:      "SET myapp.tenant_id = '42';",
+					Expected: nil,
+					WantErr:  false,
+				},
+				{
 					SQL:      "RESET ALL;",
 					Expected: nil,
+					WantErr:  false,
+				},
+				{
+					SQL:      "SELECT CURRENT_SETTING('application_name');",
+					Expected: [][]string{{"psql"}},
+					WantErr:  false,
+				},
+				{
+					SQL:      "SHOW myapp.tenant_id;",
+					Expected: [][]string{{""}},
+					WantErr:  false,
+				},
+			},
+		},
+
+		// Parameters with units
+		{
+			name: "Set statement_timeout with units",
+			executions: []Execution{
+				{
+					SQL:      "SET statement_timeout = '5s';",
+					Expected: nil,
+					WantErr:  false,
+				},
+				{
+					SQL:      "SHOW statement_timeout;",
+					Expected: [][]string{{"5000"}},
+					WantErr:  false,
+				},
+				{
+					SQL:      "SET statement_timeout = '2min';",
+					Expected: nil,
+					WantErr:  false,
+				},
+				{
+					SQL:      "SHOW statement_timeout;",
+					Expected: [][]string{{"120000"}},
+					WantErr:  false,
+				},
+				{
+					SQL:      "SET statement_timeout = '5 lightyears';",
+					Expected: nil,
+					WantErr:  true,
+				},
+			},
+		},
+
+		// Custom and unknown parameters
+		{
+			name: "Set custom parameter",
+			executions: []Execution{
+				{
+					SQL:      "SET myapp.tenant_id = '42';",
+					Expected: nil,
+					WantErr:  false,
+				},
+				{
+					SQL:      "SHOW myapp.tenant_id;",
+					Expected: [][]string{{"42"}},
+					WantErr:  false,
+				},
+				{
+					SQL:      "SELECT CURRENT_SETTING('myapp.tenant_id');",
+					Expected: [][]string{{"42"}},
+					WantErr:  false,
+				},
+				{
+					SQL:      "SELECT CURRENT_SETTING('myapp.missing', true);",
+					Expected: [][]string{{"<nil>"}},
+					WantErr:  false,
+				},
+				{
+					SQL:      "SELECT CURRENT_SETTING('myapp.missing');",
+					Expected: nil,
 					WantErr:  true,
 				},
 			},
 		},
+		{
+			name: "Set unknown parameter",
+			executions: []Execution{
+				{
+					SQL:      "SET no_such_parameter = 1;",
+					Expected: nil,
+					WantErr:  false,
+				},
+			},
+		},
 		{
 			name: "Test Session Characteristics Setting",
 			executions: []Execution{
@@ -0,0 +1,48 @@
+package pgserver
+
+import (
+	"regexp"
+	"strings"
+)
+
+// Some views of pg_catalog show the state of the server or of the session, which DuckDB knows nothing about.
+// They are backed by temporary tables of the session, which are filled right before a query reads them.
+
+// sessionViewRegex matches the references to these views in a query.
+var sessionViewRegex = regexp.MustCompile(`(?i)\b(FROM|JOIN)\s+(?:"?pg_catalog"?\.)?"?(pg_settings|pg_stat_activity|pg_stat_replication|pg_stat_subscription|pg_stat_wal_receiver|pg_stat_statements)\b"?`)
+
+// sessionViewSchema is the schema of the temporary tables of the session.
+const sessionViewSchema = "temp.main"
+
+// refreshSessionViews fills the tables of the views referenced by the query, and returns the query rewritten to
+// read these tables.
+func (h *ConnectionHandler) refreshSessionViews(query string) (string, error) {
+	query = RemoveComments(query)
+	refreshed := make(map[string]bool)
+	for _, matches := range sessionViewRegex.FindAllStringSubmatch(query, -1) {
+		name := strings.ToLower(matches[2])
+		if refreshed[name] {
+			continue
+		}
+		refreshed[name] = true
+		var err error
+		switch name {
+		case "pg_settings":
+			err = h.refreshPgSettings()
+		case "pg_stat_activity":
+			err = h.refreshPgStatActivity()
+		case "pg_stat_replication":
+			err = h.refreshPgStatReplication()
+		case "pg_stat_subscription":
+			err = h.refreshPgStatSubscription()
+		case "pg_stat_wal_receiver":
+			err = h.refreshPgStatWalReceiver()
+		case "pg_stat_statements":
+			err = h.refreshPgStatStatements()
+		}
+		if err != nil {
+			return "", err
+		}
+	}
+	return sessionViewRegex.ReplaceAllString(query, "$1 "+sessionViewSchema+".$2"), nil
+}
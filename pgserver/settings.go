@@ -0,0 +1,199 @@
+package pgserver
+
+import (
+	"context"
+	"errors"
+	"fmt"
+	"strings"
+	"sync/atomic"
+	"time"
+
+	"github.com/apecloud/myduckserver/adapter"
+	"github.com/apecloud/myduckserver/pgserver/pgconfig"
+	"github.com/dolthub/go-mysql-server/sql"
+	"github.com/jackc/pgx/v5/pgproto3"
+)
+
+// The configuration parameters of Postgres are kept as session variables, see pgconfig. In addition:
+//
+//  1. A parameter with a dot in its name, e.g., myapp.tenant_id, is a custom parameter of the application,
+//     which is kept by the connection as is, and read back by SHOW and current_setting().
+//  2. The other parameters are passed to DuckDB. If DuckDB does not recognize one either, it is ignored with a
+//     warning outside a transaction block, which deliberately deviates from Postgres (see ignoreUnrecognizedSetting).
+//  3. The parameters are listed in the pg_settings view of the session, and by SHOW ALL.
+
+// errStatementTimeout is returned for a statement canceled by the statement_timeout.
+var errStatementTimeout = errors.New("canceling statement due to statement timeout")
+
+// errLockTimeout is returned for a wait for a lock canceled by the lock_timeout.
+var errLockTimeout = errors.New("canceling statement due to lock timeout")
+
+// pgSettingsTable is the temporary table of the session that backs the pg_settings view.
+const pgSettingsTable = sessionViewSchema + ".pg_settings"
+
+// isCustomSetting returns whether the parameter is a custom parameter of the application.
+func isCustomSetting(name string) bool {
+	return strings.Contains(name, ".")
+}
+
+// isUnrecognizedSetting returns whether the error results from setting a parameter that DuckDB does not recognize.
+func isUnrecognizedSetting(err error) bool {
+	return strings.Contains(err.Error(), "unrecognized configuration parameter")
+}
+
+// setCustomSetting sets a custom parameter of the application, or resets it to an empty string.
+func (h *ConnectionHandler) setCustomSetting(name string, value string, tag string) (bool, error) {
+	h.customSettings[strings.ToLower(name)] = value
+	return true, h.send(makeCommandComplete(tag, 0))
+}
+
+// ignoreUnrecognizedSetting completes a SET statement of a parameter that neither Postgres nor DuckDB recognizes.
+// Postgres rejects such a parameter with 42704 unless its name has a dot, but it is deliberately accepted with a
+// warning instead, so that the clients and the tools setting the parameters of other Postgres versions or of
+// extensions still work. In a transaction block, the statement fails as in Postgres, since DuckDB has already aborted
+// the transaction.
+func (h *ConnectionHandler) ignoreUnrecognizedSetting(name string) error {
+	message := fmt.Sprintf("unrecognized configuration parameter \"%s\" is ignored", name)
+	if err := h.sendNotice(ErrorResponseSeverity_Warning, "42704" /* undefined_object */, message); err != nil {
+		return err
+	}
+	return h.send(makeCommandComplete("SET", 0))
+}
+
+// resetAllPgSessionVars handles RESET ALL, which resets all the parameters that can be set in the session.
+func (h *ConnectionHandler) resetAllPgSessionVars() (bool, error) {
+	clear(h.customSettings)
+	var reported []*pgproto3.ParameterStatus
+	for _, param := range pgconfig.Parameters() {
+		if param.IsReadOnly() {
+			continue
+		}
+		name := strings.ToLower(param.Name)
+		v, err := h.setPgSessionVarValue(name, nil, true)
+		if err != nil {
+			return false, err
+		}
+		if pgconfig.IsReportedParameter(name) {
+			reported = append(reported, &pgproto3.ParameterStatus{Name: param.Name, Value: param.FormatValue(v)})
+		}
+	}
+	if h.pgSettingsCreated {
+		if err := h.refreshPgSettings(); err != nil {
+			return false, err
+		}
+	}
+	if err := h.send(makeCommandComplete("RESET", 0)); err != nil {
+		return true, err
+	}
+	for _, status := range reported {
+		if err := h.send(status); err != nil {
+			return true, err
+		}
+	}
+	return true, nil
+}
+
+// refreshPgSettings fills the pg_settings table of the session with the current values of the parameters.
+// It is refreshed whenever a query reads it, and whenever a parameter is set after it is created.
+func (h *ConnectionHandler) refreshPgSettings() error {
+	ctx, err := h.duckHandler.NewContext(context.Background(), h.mysqlConn, "")
+	if err != nil {
+		return err
+	}
+	if _, err := adapter.ExecCatalog(ctx, `CREATE OR REPLACE TEMP TABLE pg_settings (
+		name VARCHAR, setting VARCHAR, unit VARCHAR, category VARCHAR, short_desc VARCHAR, extra_desc VARCHAR,
+		context VARCHAR, vartype VARCHAR, source VARCHAR, min_val VARCHAR, max_val VARCHAR, enumvals VARCHAR[],
+		boot_val VARCHAR, reset_val VARCHAR, sourcefile VARCHAR, sourceline INTEGER, pending_restart BOOLEAN)`); err != nil {
+		return err
+	}
+
+	params := pgconfig.Parameters()
+	var b strings.Builder
+	b.WriteString("INSERT INTO " + pgSettingsTable +
+		" (name, setting, unit, category, short_desc, context, vartype, source, boot_val, reset_val, pending_restart) VALUES ")
+	args := make([]any, 0, len(params)*10)
+	for i, param := range params {
+		if i > 0 {
+			b.WriteString(", ")
+		}
+		b.WriteString("(?, ?, ?, ?, ?, ?, ?, ?, ?, ?, false)")
+		v, err := param.GetSessionScope().GetValue(ctx, strings.ToLower(param.Name), sql.Collation_Default)
+		if err != nil {
+			return err
+		}
+		var unit any
+		if param.Unit != "" {
+			unit = param.Unit
+		}
+		setting, source := param.FormatValue(v), string(param.Source)
+		if setting != param.FormatValue(param.Default) {
+			source = "session"
+		}
+		args = append(args, param.Name, setting, unit, param.Category, param.ShortDesc,
+			string(param.Context), param.VarType(), source,
+			param.FormatValue(param.Default), param.FormatValue(param.ResetVal))
+	}
+	if _, err := adapter.ExecCatalog(ctx, b.String(), args...); err != nil {
+		return err
+	}
+	h.pgSettingsCreated = true
+	return nil
+}
+
+// applySetting applies a parameter that takes effect outside the session variables, e.g., in DuckDB.
+func (h *ConnectionHandler) applySetting(ctx *sql.Context, name string) error {
+	switch name {
+	case "search_path":
+		return ApplySearchPath(ctx)
+	case "application_name":
+		value, err := ctx.GetSessionVariable(ctx, name)
+		if err != nil {
+			return err
+		}
+		h.setActivityApplicationName(fmt.Sprintf("%v", value))
+	case "timezone":
+		// The time zone of DuckDB renders the TIMESTAMPTZ values.
+		value, err := ctx.GetSessionVariable(ctx, name)
+		if err != nil {
+			return err
+		}
+		timeZone := strings.ReplaceAll(fmt.Sprintf("%v", value), "'", "''")
+		if _, err := adapter.ExecCatalog(ctx, fmt.Sprintf("SET TimeZone = '%s'", timeZone)); err != nil {
+			h.logger.WithError(err).Warnf("Failed to set the time zone of DuckDB to %q", timeZone)
+		}
+	}
+	return nil
+}
+
+// sessionTimeout returns the timeout in milliseconds of a parameter of the session such as statement_timeout,
+// which is 0 if the timeout is disabled.
+func (h *ConnectionHandler) sessionTimeout(name string) int64 {
+	var timeout int64
+	if ctx, err := h.duckHandler.NewContext(context.Background(), h.mysqlConn, ""); err == nil {
+		if v, err := ctx.GetSessionVariable(ctx, name); err == nil {
+			timeout, _ = v.(int64)
+		}
+	}
+	return max(timeout, 0)
+}
+
+// startStatementTimeout arms the statement_timeout of the session for the statement about to run. The returned
+// function disarms it, and turns the error of a statement canceled by the timeout into errStatementTimeout.
+func (h *ConnectionHandler) startStatementTimeout() func(error) error {
+	timeout := h.sessionTimeout("statement_timeout")
+	if timeout == 0 {
+		return func(err error) error { return err }
+	}
+	var timedOut atomic.Bool
+	timer := time.AfterFunc(time.Duration(timeout)*time.Millisecond, func() {
+		timedOut.Store(true)
+		h.cancelQuery()
+	})
+	return func(err error) error {
+		timer.Stop()
+		if err != nil && timedOut.Load() && isQueryCanceled(err) {
+			return errStatementTimeout
+		}
+		return err
+	}
+}
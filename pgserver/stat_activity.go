@@ -0,0 +1,191 @@
+package pgserver
+
+import (
+	"context"
+	"net"
+	"slices"
+	"strings"
+	"sync"
+	"time"
+
+	"github.com/apecloud/myduckserver/adapter"
+	"github.com/jackc/pgx/v5/pgproto3"
+)
+
+// The pg_stat_activity view shows one row per connection of the server, with the state and the current query of
+// the connection. As in Postgres, only superusers and the role of a connection may see what it is executing.
+// https://www.postgresql.org/docs/current/monitoring-stats.html#MONITORING-PG-STAT-ACTIVITY-VIEW
+
+const (
+	backendStateActive            = "active"
+	backendStateIdle              = "idle"
+	backendStateIdleInTransaction = "idle in transaction"
+)
+
+// backendActivity is the activity of a connection shown in pg_stat_activity. It is updated by the goroutine of the
+// connection and read by those of the others, so it is guarded by its own mutex.
+type backendActivity struct {
+	mu              sync.Mutex
+	database        string
+	applicationName string
+	backendStart    time.Time
+	xactStart       time.Time // zero outside a transaction block
+	queryStart      time.Time
+	stateChange     time.Time
+	state           string
+	query           string
+}
+
+// initActivity records the connection in pg_stat_activity once it is established.
+func (h *ConnectionHandler) initActivity(startupMessage *pgproto3.StartupMessage) error {
+	ctx, err := h.duckHandler.NewContext(context.Background(), h.mysqlConn, "")
+	if err != nil {
+		return err
+	}
+	a := &h.activity
+	a.mu.Lock()
+	defer a.mu.Unlock()
+	now := time.Now()
+	a.database = adapter.GetCurrentCatalog(ctx)
+	a.applicationName = startupMessage.Parameters["application_name"]
+	a.backendStart = now
+	a.state = backendStateIdle
+	a.stateChange = now
+	return nil
+}
+
+// startActivity records that the connection starts executing the query.
+func (h *ConnectionHandler) startActivity(query string) {
+	a := &h.activity
+	a.mu.Lock()
+	defer a.mu.Unlock()
+	now := time.Now()
+	a.query = query
+	a.queryStart = now
+	if a.state != backendStateActive {
+		a.state = backendStateActive
+		a.stateChange = now
+	}
+}
+
+// stopActivity records that the connection waits for the next query of the client.
+func (h *ConnectionHandler) stopActivity() {
+	a := &h.activity
+	a.mu.Lock()
+	defer a.mu.Unlock()
+	state := backendStateIdle
+	if h.inTransaction {
+		state = backendStateIdleInTransaction
+	}
+	if a.state != state {
+		a.state = state
+		a.stateChange = time.Now()
+	}
+}
+
+// setActivityTransaction records the start or the end of a transaction block.
+func (h *ConnectionHandler) setActivityTransaction(inTransaction bool) {
+	a := &h.activity
+	a.mu.Lock()
+	defer a.mu.Unlock()
+	if inTransaction {
+		a.xactStart = a.queryStart
+	} else {
+		a.xactStart = time.Time{}
+	}
+}
+
+// setActivityApplicationName records the application_name of the connection.
+func (h *ConnectionHandler) setActivityApplicationName(name string) {
+	a := &h.activity
+	a.mu.Lock()
+	defer a.mu.Unlock()
+	a.applicationName = name
+}
+
+// listBackends returns the registered connections of the server, ordered by their IDs.
+func (s *Server) listBackends() []*ConnectionHandler {
+	var backends []*ConnectionHandler
+	s.backends.Range(func(_, v any) bool {
+		backends = append(backends, v.(*ConnectionHandler))
+		return true
+	})
+	slices.SortFunc(backends, func(a, b *ConnectionHandler) int {
+		return int(a.mysqlConn.ConnectionID) - int(b.mysqlConn.ConnectionID)
+	})
+	return backends
+}
+
+// refreshPgStatActivity fills the pg_stat_activity table of the session with the current activity of the server.
+func (h *ConnectionHandler) refreshPgStatActivity() error {
+	ctx, err := h.duckHandler.NewContext(context.Background(), h.mysqlConn, "")
+	if err != nil {
+		return err
+	}
+	if _, err := adapter.ExecCatalog(ctx, `CREATE OR REPLACE TEMP TABLE pg_stat_activity (
+		datid INTEGER, datname VARCHAR, pid INTEGER, leader_pid INTEGER, usesysid INTEGER, usename VARCHAR,
+		application_name VARCHAR, client_addr VARCHAR, client_hostname VARCHAR, client_port INTEGER,
+		backend_start TIMESTAMPTZ, xact_start TIMESTAMPTZ, query_start TIMESTAMPTZ, state_change TIMESTAMPTZ,
+		wait_event_type VARCHAR, wait_event VARCHAR, state VARCHAR, backend_xid VARCHAR, backend_xmin VARCHAR,
+		query_id BIGINT, query VARCHAR, backend_type VARCHAR)`); err != nil {
+		return err
+	}
+
+	backends := []*ConnectionHandler{h}
+	if h.server != nil {
+		backends = h.server.listBackends()
+	}
+	if len(backends) == 0 {
+		return nil
+	}
+	var b strings.Builder
+	b.WriteString("INSERT INTO " + sessionViewSchema + ".pg_stat_activity" +
+		" (datname, pid, usename, application_name, client_addr, client_port," +
+		" backend_start, xact_start, query_start, state_change, state, query, backend_type) VALUES ")
+	args := make([]any, 0, len(backends)*13)
+	superuser := h.isSuperuser()
+	for i, backend := range backends {
+		if i > 0 {
+			b.WriteString(", ")
+		}
+		b.WriteString("(?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)")
+		args = h.appendActivityRow(args, backend, superuser)
+		backendType := "client backend"
+		if backend.walsender {
+			backendType = "walsender"
+		}
+		args = append(args, backendType)
+	}
+	_, err = adapter.ExecCatalog(ctx, b.String(), args...)
+	return err
+}
+
+// appendActivityRow appends the values of the row of the connection in pg_stat_activity to |args|. The activity of
+// a connection of another role is hidden, unless this connection is of a superuser.
+func (h *ConnectionHandler) appendActivityRow(args []any, backend *ConnectionHandler, superuser bool) []any {
+	var clientAddr, clientPort any
+	if addr, ok := backend.Conn().RemoteAddr().(*net.TCPAddr); ok {
+		clientAddr, clientPort = addr.IP.String(), addr.Port
+	} else {
+		// A connection over a Unix socket
+		clientPort = -1
+	}
+
+	a := &backend.activity
+	a.mu.Lock()
+	defer a.mu.Unlock()
+	args = append(args, a.database, backend.mysqlConn.ConnectionID, backend.mysqlConn.User, a.applicationName,
+		clientAddr, clientPort, nullTime(a.backendStart))
+	if h.mysqlConn.User != backend.mysqlConn.User && !superuser {
+		return append(args, nil, nil, nil, nil, "<insufficient privilege>")
+	}
+	return append(args, nullTime(a.xactStart), nullTime(a.queryStart), nullTime(a.stateChange), a.state, a.query)
+}
+
+// nullTime returns nil for the zero time, which is NULL in pg_stat_activity.
+func nullTime(t time.Time) any {
+	if t.IsZero() {
+		return nil
+	}
+	return t
+}
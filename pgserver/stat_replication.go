@@ -0,0 +1,300 @@
+package pgserver
+
+import (
+	"context"
+	"fmt"
+	"strings"
+	"sync"
+	"time"
+
+	"github.com/apecloud/myduckserver/adapter"
+	"github.com/apecloud/myduckserver/pgserver/logrepl"
+	"github.com/jackc/pglogrepl"
+	"github.com/jackc/pgx/v5/pgconn"
+)
+
+// The pg_stat_replication view shows one row per replication connection streaming the changes of the server, with
+// the positions the standby has confirmed and the lags of these positions. The pg_stat_subscription and
+// pg_stat_wal_receiver views show the subscriptions replicating the changes of another server into this one.
+// https://www.postgresql.org/docs/current/monitoring-stats.html#MONITORING-PG-STAT-REPLICATION-VIEW
+// https://www.postgresql.org/docs/current/monitoring-stats.html#MONITORING-PG-STAT-WAL-RECEIVER-VIEW
+
+const (
+	walSenderStateCatchup   = "catchup"
+	walSenderStateStreaming = "streaming"
+)
+
+// walSenderStatus is the status of the stream of a replication connection shown in pg_stat_replication. It is
+// updated by the goroutines of the stream and read by those of the other connections.
+type walSenderStatus struct {
+	mu    sync.Mutex
+	state string // empty while the connection does not stream changes
+	sent  pglogrepl.LSN
+	write pglogrepl.LSN
+	flush pglogrepl.LSN
+	apply pglogrepl.LSN
+	// The lags of the positions, which are negative if they are unknown.
+	writeLag  time.Duration
+	flushLag  time.Duration
+	applyLag  time.Duration
+	replyTime time.Time
+	// samples are the sent transactions whose lags are not measured for all the positions yet.
+	samples []lagSample
+	// caughtUp is set when the last reply confirmed all the sent transactions, with no lag to measure.
+	caughtUp bool
+}
+
+// lagSample is a sent transaction, of which the lag of a position is the time between its commit and the reply
+// of the standby that confirms the position reached the transaction.
+type lagSample struct {
+	lsn       pglogrepl.LSN
+	committed time.Time
+	measured  [3]bool
+}
+
+// start records that the connection starts streaming the changes after |lsn|.
+func (s *walSenderStatus) start(lsn pglogrepl.LSN) {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+	s.state = walSenderStateCatchup
+	s.sent, s.write, s.flush, s.apply = lsn, 0, 0, 0
+	s.writeLag, s.flushLag, s.applyLag = -1, -1, -1
+	s.replyTime = time.Time{}
+	s.samples = nil
+	s.caughtUp = false
+}
+
+// stop records that the connection stops streaming.
+func (s *walSenderStatus) stop() {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+	s.state = ""
+	s.samples = nil
+}
+
+func (s *walSenderStatus) setState(state string) {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+	s.state = state
+}
+
+// sentTransaction records that the transaction committed at |committed| has been sent up to |lsn|.
+func (s *walSenderStatus) sentTransaction(lsn pglogrepl.LSN, committed time.Time) {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+	s.sent = lsn
+	s.samples = append(s.samples, lagSample{lsn: lsn, committed: committed})
+}
+
+// reply records a standby status update of the standby at |t|, and measures the lags of the positions it confirms.
+// As in Postgres, the lags are kept while the standby is idle, and reset once it has confirmed all the sent
+// transactions twice without anything new to measure.
+func (s *walSenderStatus) reply(write, flush, apply pglogrepl.LSN, t time.Time) {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+	s.write, s.flush, s.apply, s.replyTime = write, flush, apply, t
+
+	now := time.Now()
+	positions := [3]pglogrepl.LSN{write, flush, apply}
+	lags := [3]*time.Duration{&s.writeLag, &s.flushLag, &s.applyLag}
+	measuredApply := false
+	for i, position := range positions {
+		// The lag of the position is that of the latest transaction it reached.
+		latest := -1
+		for j, sample := range s.samples {
+			if sample.lsn > position {
+				break
+			}
+			if !sample.measured[i] {
+				latest = j
+			}
+		}
+		if latest < 0 {
+			continue
+		}
+		*lags[i] = now.Sub(s.samples[latest].committed)
+		for j := 0; j <= latest; j++ {
+			s.samples[j].measured[i] = true
+		}
+		measuredApply = measuredApply || i == 2
+	}
+	measured := 0
+	for measured < len(s.samples) && s.samples[measured].measured == [3]bool{true, true, true} {
+		measured++
+	}
+	s.samples = s.samples[measured:]
+
+	if apply >= s.sent && !measuredApply {
+		if s.caughtUp {
+			s.writeLag, s.flushLag, s.applyLag = -1, -1, -1
+		}
+		s.caughtUp = true
+	} else {
+		s.caughtUp = false
+	}
+}
+
+// flushed returns the position the standby has confirmed as flushed.
+func (s *walSenderStatus) flushed() pglogrepl.LSN {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+	return s.flush
+}
+
+// nullLag returns the lag in microseconds, or nil if it is unknown.
+func nullLag(lag time.Duration) any {
+	if lag < 0 {
+		return nil
+	}
+	return lag.Microseconds()
+}
+
+// refreshPgStatReplication fills the pg_stat_replication table of the session with the streaming replication
+// connections of the server.
+func (h *ConnectionHandler) refreshPgStatReplication() error {
+	ctx, err := h.duckHandler.NewContext(context.Background(), h.mysqlConn, "")
+	if err != nil {
+		return err
+	}
+	if _, err := adapter.ExecCatalog(ctx, `CREATE OR REPLACE TEMP TABLE pg_stat_replication (
+		pid INTEGER, usesysid BIGINT, usename VARCHAR, application_name VARCHAR, client_addr VARCHAR,
+		client_hostname VARCHAR, client_port INTEGER, backend_start TIMESTAMPTZ, backend_xmin VARCHAR,
+		state VARCHAR, sent_lsn VARCHAR, write_lsn VARCHAR, flush_lsn VARCHAR, replay_lsn VARCHAR,
+		write_lag INTERVAL, flush_lag INTERVAL, replay_lag INTERVAL, sync_priority INTEGER, sync_state VARCHAR,
+		reply_time TIMESTAMPTZ)`); err != nil {
+		return err
+	}
+	if h.server == nil {
+		return nil
+	}
+
+	var b strings.Builder
+	b.WriteString("INSERT INTO " + sessionViewSchema + ".pg_stat_replication" +
+		" (pid, usename, application_name, client_addr, client_port, backend_start, state," +
+		" sent_lsn, write_lsn, flush_lsn, replay_lsn, write_lag, flush_lag, replay_lag," +
+		" sync_priority, sync_state, reply_time) VALUES ")
+	var args []any
+	superuser := h.isSuperuser()
+	rows := 0
+	for _, backend := range h.server.listBackends() {
+		if !backend.walsender {
+			continue
+		}
+		s := &backend.replication
+		s.mu.Lock()
+		state, sent, write, flush, apply := s.state, s.sent, s.write, s.flush, s.apply
+		writeLag, flushLag, applyLag, replyTime := s.writeLag, s.flushLag, s.applyLag, s.replyTime
+		s.mu.Unlock()
+		if state == "" {
+			continue
+		}
+
+		if rows > 0 {
+			b.WriteString(", ")
+		}
+		rows++
+		b.WriteString("(?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, to_microseconds(?), to_microseconds(?), to_microseconds(?), 0, 'async', ?)")
+		// The columns of the activity are taken from the row of the connection in pg_stat_activity.
+		activity := h.appendActivityRow(nil, backend, true)
+		args = append(args, activity[1:7]...)
+		if h.mysqlConn.User != backend.mysqlConn.User && !superuser {
+			args = append(args, nil, nil, nil, nil, nil, nil, nil, nil, nil)
+			continue
+		}
+		args = append(args, state, sent.String(), write.String(), flush.String(), apply.String(),
+			nullLag(writeLag), nullLag(flushLag), nullLag(applyLag), nullTime(replyTime))
+	}
+	if rows == 0 {
+		return nil
+	}
+	_, err = adapter.ExecCatalog(ctx, b.String(), args...)
+	return err
+}
+
+// refreshPgStatSubscription fills the pg_stat_subscription table of the session with the subscriptions of the
+// server. The columns of the replication are NULL for a subscription that is not running.
+func (h *ConnectionHandler) refreshPgStatSubscription() error {
+	ctx, err := h.duckHandler.NewContext(context.Background(), h.mysqlConn, "")
+	if err != nil {
+		return err
+	}
+	if _, err := adapter.ExecCatalog(ctx, `CREATE OR REPLACE TEMP TABLE pg_stat_subscription (
+		subid BIGINT, subname VARCHAR, worker_type VARCHAR, pid INTEGER, leader_pid INTEGER, relid BIGINT,
+		received_lsn VARCHAR, last_msg_send_time TIMESTAMPTZ, last_msg_receipt_time TIMESTAMPTZ,
+		latest_end_lsn VARCHAR, latest_end_time TIMESTAMPTZ)`); err != nil {
+		return err
+	}
+
+	statuses := logrepl.SubscriptionStatuses()
+	if len(statuses) == 0 {
+		return nil
+	}
+	var b strings.Builder
+	b.WriteString("INSERT INTO " + sessionViewSchema + ".pg_stat_subscription" +
+		" (subname, worker_type, received_lsn, last_msg_send_time, last_msg_receipt_time, latest_end_lsn, latest_end_time) VALUES ")
+	args := make([]any, 0, len(statuses)*6)
+	for i, s := range statuses {
+		if i > 0 {
+			b.WriteString(", ")
+		}
+		b.WriteString("(?, 'apply', ?, ?, ?, ?, ?)")
+		args = append(args, s.Subscription)
+		if s.Status == nil {
+			args = append(args, nil, nil, nil, nil, nil)
+			continue
+		}
+		args = append(args, s.Status.ReceivedLSN.String(), nullTime(s.Status.LastMsgSendTime),
+			nullTime(s.Status.LastMsgReceiptTime), s.Status.ReportedLSN.String(), nullTime(s.Status.ReportedTime))
+	}
+	_, err = adapter.ExecCatalog(ctx, b.String(), args...)
+	return err
+}
+
+// refreshPgStatWalReceiver fills the pg_stat_wal_receiver table of the session with the running subscriptions of
+// the server, which receive the changes of their primaries as the WAL receiver of a standby does.
+func (h *ConnectionHandler) refreshPgStatWalReceiver() error {
+	ctx, err := h.duckHandler.NewContext(context.Background(), h.mysqlConn, "")
+	if err != nil {
+		return err
+	}
+	if _, err := adapter.ExecCatalog(ctx, `CREATE OR REPLACE TEMP TABLE pg_stat_wal_receiver (
+		pid INTEGER, status VARCHAR, receive_start_lsn VARCHAR, receive_start_tli INTEGER, written_lsn VARCHAR,
+		flushed_lsn VARCHAR, received_tli INTEGER, last_msg_send_time TIMESTAMPTZ,
+		last_msg_receipt_time TIMESTAMPTZ, latest_end_lsn VARCHAR, latest_end_time TIMESTAMPTZ, slot_name VARCHAR,
+		sender_host VARCHAR, sender_port INTEGER, conninfo VARCHAR)`); err != nil {
+		return err
+	}
+
+	var b strings.Builder
+	b.WriteString("INSERT INTO " + sessionViewSchema + ".pg_stat_wal_receiver" +
+		" (status, receive_start_lsn, receive_start_tli, written_lsn, flushed_lsn, received_tli," +
+		" last_msg_send_time, last_msg_receipt_time, latest_end_lsn, latest_end_time, slot_name," +
+		" sender_host, sender_port, conninfo) VALUES ")
+	var args []any
+	rows := 0
+	for _, s := range logrepl.SubscriptionStatuses() {
+		if s.Status == nil {
+			continue
+		}
+		if rows > 0 {
+			b.WriteString(", ")
+		}
+		rows++
+		b.WriteString("('streaming', ?, 1, ?, ?, 1, ?, ?, ?, ?, ?, ?, ?, ?)")
+		var senderHost, senderPort, conninfo any
+		if config, err := pgconn.ParseConfig(s.Conn); err == nil {
+			// As in Postgres, the password is obfuscated in the connection string.
+			senderHost, senderPort = config.Host, int(config.Port)
+			conninfo = fmt.Sprintf("user=%s password=******** dbname=%s host=%s port=%d",
+				config.User, config.Database, config.Host, config.Port)
+		}
+		args = append(args, s.Status.StartLSN.String(), s.Status.ReceivedLSN.String(), s.Status.WrittenLSN.String(),
+			nullTime(s.Status.LastMsgSendTime), nullTime(s.Status.LastMsgReceiptTime), s.Status.ReportedLSN.String(),
+			nullTime(s.Status.ReportedTime), s.Status.SlotName, senderHost, senderPort, conninfo)
+	}
+	if rows == 0 {
+		return nil
+	}
+	_, err = adapter.ExecCatalog(ctx, b.String(), args...)
+	return err
+}
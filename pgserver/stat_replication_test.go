@@ -0,0 +1,46 @@
+package pgserver
+
+import (
+	"testing"
+	"time"
+
+	"github.com/jackc/pglogrepl"
+	"github.com/stretchr/testify/require"
+)
+
+func TestWalSenderStatusLag(t *testing.T) {
+	var s walSenderStatus
+	s.start(pglogrepl.LSN(10))
+	require.Equal(t, walSenderStateCatchup, s.state)
+	require.Equal(t, time.Duration(-1), s.writeLag)
+
+	committed := time.Now().Add(-time.Minute)
+	s.sentTransaction(pglogrepl.LSN(20), committed)
+	s.sentTransaction(pglogrepl.LSN(30), committed.Add(30*time.Second))
+
+	// The standby has written the first transaction, but applied nothing yet.
+	s.reply(pglogrepl.LSN(20), pglogrepl.LSN(10), pglogrepl.LSN(10), time.Now())
+	require.GreaterOrEqual(t, s.writeLag, time.Minute)
+	require.Equal(t, time.Duration(-1), s.flushLag)
+	require.Equal(t, time.Duration(-1), s.applyLag)
+	require.Equal(t, pglogrepl.LSN(10), s.flushed())
+	require.Len(t, s.samples, 2)
+
+	// The lags are those of the latest transactions the positions reached.
+	s.reply(pglogrepl.LSN(30), pglogrepl.LSN(30), pglogrepl.LSN(30), time.Now())
+	require.GreaterOrEqual(t, s.writeLag, 30*time.Second)
+	require.Less(t, s.writeLag, time.Minute)
+	require.GreaterOrEqual(t, s.applyLag, 30*time.Second)
+	require.Equal(t, pglogrepl.LSN(30), s.flushed())
+	require.Empty(t, s.samples)
+
+	// The lags are kept for one idle reply, and reset by the next one.
+	s.reply(pglogrepl.LSN(30), pglogrepl.LSN(30), pglogrepl.LSN(30), time.Now())
+	require.GreaterOrEqual(t, s.applyLag, 30*time.Second)
+	s.reply(pglogrepl.LSN(30), pglogrepl.LSN(30), pglogrepl.LSN(30), time.Now())
+	require.Equal(t, time.Duration(-1), s.writeLag)
+	require.Equal(t, time.Duration(-1), s.applyLag)
+
+	s.stop()
+	require.Empty(t, s.state)
+}
@@ -0,0 +1,124 @@
+package pgserver
+
+import (
+	"bytes"
+	"context"
+	"fmt"
+	"regexp"
+	"strconv"
+	"strings"
+	"time"
+
+	"github.com/apecloud/myduckserver/adapter"
+	"github.com/apecloud/myduckserver/backend"
+)
+
+// The pg_stat_statements view shows the statistics of the statements executed through both the MySQL and the
+// Postgres protocols, which are kept by the backend package. A statement of the Postgres protocol is counted once
+// its CommandComplete message is sent, with the rows of its command tag. As in Postgres, only superusers may see the
+// texts of the statements of the other roles, and reset the statistics with pg_stat_statements_reset().
+// https://www.postgresql.org/docs/current/pgstatstatements.html
+
+// pgStatStatementsResetRegex matches "SELECT pg_stat_statements_reset();", whose arguments are ignored.
+var pgStatStatementsResetRegex = regexp.MustCompile(`(?i)^\s*select\s+(pg_catalog\.|public\.)?pg_stat_statements_reset\([^)]*\)\s*;?\s*$`)
+
+// statementTiming is the statement being executed by the connection, which is counted in pg_stat_statements when
+// it completes.
+type statementTiming struct {
+	query    string // empty if no statement is being executed
+	database string
+	start    time.Time
+}
+
+// beginStatement records that the connection starts executing the statement.
+func (h *ConnectionHandler) beginStatement(query string) {
+	a := &h.activity
+	a.mu.Lock()
+	database := a.database
+	a.mu.Unlock()
+	h.statement = statementTiming{query: query, database: database, start: time.Now()}
+}
+
+// completeStatement counts the statement being executed, whose command tag is |tag|, in pg_stat_statements.
+// The next command tag of the statement, such as that of another INSERT of a batch, is counted from now on.
+func (h *ConnectionHandler) completeStatement(tag []byte) {
+	s := &h.statement
+	if s.query == "" {
+		return
+	}
+	var rows int64
+	if i := bytes.LastIndexByte(tag, ' '); i >= 0 {
+		rows, _ = strconv.ParseInt(string(tag[i+1:]), 10, 64)
+	}
+	now := time.Now()
+	backend.RecordStatement(h.mysqlConn.User, s.database, s.query, now.Sub(s.start), rows)
+	s.start = now
+}
+
+// resetStatementStats implements pg_stat_statements_reset(), and returns the time of the reset.
+func (h *ConnectionHandler) resetStatementStats() (time.Time, error) {
+	if !h.isSuperuser() {
+		return time.Time{}, fmt.Errorf("permission denied for function pg_stat_statements_reset")
+	}
+	return backend.ResetStatementStats(), nil
+}
+
+// refreshPgStatStatements fills the pg_stat_statements table of the session with the statistics of the statements.
+func (h *ConnectionHandler) refreshPgStatStatements() error {
+	ctx, err := h.duckHandler.NewContext(context.Background(), h.mysqlConn, "")
+	if err != nil {
+		return err
+	}
+	if _, err := adapter.ExecCatalog(ctx, `CREATE OR REPLACE TEMP TABLE pg_stat_statements (
+		userid BIGINT, dbid BIGINT, toplevel BOOLEAN, queryid BIGINT, query VARCHAR, calls BIGINT,
+		total_exec_time DOUBLE, min_exec_time DOUBLE, max_exec_time DOUBLE, mean_exec_time DOUBLE,
+		stddev_exec_time DOUBLE, rows BIGINT)`); err != nil {
+		return err
+	}
+
+	stats := backend.StatementStats()
+	superuser := h.isSuperuser()
+	userIDs := make(map[string]any)
+	const batchSize = 500
+	for len(stats) > 0 {
+		batch := stats[:min(batchSize, len(stats))]
+		stats = stats[len(batch):]
+		var b strings.Builder
+		b.WriteString("INSERT INTO " + sessionViewSchema + ".pg_stat_statements VALUES ")
+		args := make([]any, 0, len(batch)*11)
+		for i, stat := range batch {
+			if i > 0 {
+				b.WriteString(", ")
+			}
+			b.WriteString("(?, NULL, true, ?, ?, ?, ?, ?, ?, ?, ?, ?)")
+			userID, ok := userIDs[stat.User]
+			if !ok {
+				userID = h.roleOID(stat.User)
+				userIDs[stat.User] = userID
+			}
+			var queryID, query any = stat.QueryID, stat.Query
+			if stat.User != h.mysqlConn.User && !superuser {
+				queryID, query = nil, "<insufficient privilege>"
+			}
+			args = append(args, userID, queryID, query, stat.Calls,
+				stat.TotalTime, stat.MinTime, stat.MaxTime, stat.MeanTime, stat.StddevTime, stat.Rows)
+		}
+		if _, err := adapter.ExecCatalog(ctx, b.String(), args...); err != nil {
+			return err
+		}
+	}
+	return nil
+}
+
+// roleOID returns the OID of the role with the name, or nil if it is unknown.
+func (h *ConnectionHandler) roleOID(name string) any {
+	provider, err := h.catalogProvider()
+	if err != nil {
+		return nil
+	}
+	role, err := newRoleStore(provider, provider.Storage()).lookup(name)
+	if err != nil || role == nil {
+		return nil
+	}
+	return role.oid
+}
@@ -290,8 +290,22 @@ func getPgCatalogRegex() *regexp.Regexp {
 	return pgCatalogRegex
 }
 
+// pgAuthIDRegex matches the reads of pg_authid, which are rewritten to those of a view without the passwords.
+var pgAuthIDRegex = regexp.MustCompile(`(?i)\b(FROM|JOIN)\s+(?:pg_catalog\.)?"?pg_authid\b"?`)
+
 func ConvertToSys(sql string) string {
-	return getPgCatalogRegex().ReplaceAllString(RemoveComments(sql), "$1 __sys__.$2")
+	sql = pgAuthIDRegex.ReplaceAllString(RemoveComments(sql), "$1 __sys__.pg_authid_masked")
+	return getPgCatalogRegex().ReplaceAllString(sql, "$1 __sys__.$2")
+}
+
+// informationSchemaRegex matches the views of information_schema that have Postgres versions in __sys__.
+var informationSchemaRegex = regexp.MustCompile(
+	`(?i)\b(FROM|JOIN)\s+"?information_schema"?\."?(tables|columns|table_constraints|key_column_usage)\b"?`)
+
+// ConvertInformationSchema rewrites the references to the views of information_schema in the query to their Postgres
+// versions, which show the type names of Postgres rather than those of DuckDB.
+func ConvertInformationSchema(sql string) string {
+	return informationSchemaRegex.ReplaceAllString(RemoveComments(sql), "$1 __sys__.information_schema_$2")
 }
 
 var (
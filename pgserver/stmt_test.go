@@ -180,3 +180,69 @@ func TestRemoveComments(t *testing.T) {
 		})
 	}
 }
+
+func TestConvertInformationSchema(t *testing.T) {
+	tests := []struct {
+		name  string
+		query string
+		want  string
+	}{
+		{
+			name:  "columns",
+			query: "SELECT data_type FROM information_schema.columns WHERE table_name = 't'",
+			want:  "SELECT data_type FROM __sys__.information_schema_columns WHERE table_name = 't'",
+		},
+		{
+			name:  "quoted and upper case",
+			query: `SELECT * FROM "information_schema"."TABLES" t JOIN INFORMATION_SCHEMA.TABLE_CONSTRAINTS c USING (table_name)`,
+			want:  `SELECT * FROM __sys__.information_schema_TABLES t JOIN __sys__.information_schema_TABLE_CONSTRAINTS c USING (table_name)`,
+		},
+		{
+			name:  "other views",
+			query: "SELECT * FROM information_schema.schemata",
+			want:  "SELECT * FROM information_schema.schemata",
+		},
+		{
+			name:  "prefix of a view name",
+			query: "SELECT * FROM information_schema.columns_extra",
+			want:  "SELECT * FROM information_schema.columns_extra",
+		},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			got := ConvertInformationSchema(tt.query)
+			if got != tt.want {
+				t.Errorf("ConvertInformationSchema(%q) = %q; want %q", tt.query, got, tt.want)
+			}
+		})
+	}
+}
+
+func TestConvertToSys(t *testing.T) {
+	tests := []struct {
+		name  string
+		query string
+		want  string
+	}{
+		{
+			name:  "pg_authid without the passwords",
+			query: "SELECT rolname FROM pg_catalog.pg_authid WHERE oid = 10",
+			want:  "SELECT rolname FROM __sys__.pg_authid_masked WHERE oid = 10",
+		},
+		{
+			name:  "quoted pg_authid and other views",
+			query: `SELECT * FROM pg_roles r JOIN "pg_authid" a USING (oid)`,
+			want:  "SELECT * FROM __sys__.pg_roles r JOIN __sys__.pg_authid_masked a USING (oid)",
+		},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			got := ConvertToSys(tt.query)
+			if got != tt.want {
+				t.Errorf("ConvertToSys(%q) = %q; want %q", tt.query, got, tt.want)
+			}
+		})
+	}
+}
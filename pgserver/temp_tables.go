@@ -0,0 +1,215 @@
+package pgserver
+
+import (
+	"context"
+	"regexp"
+	"strings"
+
+	"github.com/apecloud/myduckserver/adapter"
+	"github.com/apecloud/myduckserver/catalog"
+)
+
+// The temporary tables of DuckDB are visible only to the DuckDB connection that creates them, and are dropped when
+// it is closed. Since each Postgres connection has its own DuckDB connection, which is closed at the end of the
+// session or by DISCARD ALL, CREATE TEMP TABLE runs in DuckDB as it is. The temporary tables are in the temp.main
+// schema of DuckDB, which the pg_temp schema of Postgres is rewritten into.
+//
+// However, neither the Postgres parser nor DuckDB supports the ON COMMIT clause except ON COMMIT PRESERVE ROWS.
+// The clause is removed from the statement, and the action is taken by MyDuck Server at the end of each transaction:
+//
+//	CREATE TEMP TABLE t (...) ON COMMIT DROP;        -- The table is dropped.
+//	CREATE TEMP TABLE t (...) ON COMMIT DELETE ROWS; -- The rows of the table are deleted.
+//
+// Outside a transaction block, a statement is committed on its own, so a table created with ON COMMIT DROP is dropped
+// right away, as in Postgres.
+
+// OnCommitAction is the action taken on a temporary table at the end of a transaction.
+type OnCommitAction string
+
+const (
+	OnCommitPreserveRows OnCommitAction = "PRESERVE ROWS"
+	OnCommitDeleteRows   OnCommitAction = "DELETE ROWS"
+	OnCommitDrop         OnCommitAction = "DROP"
+)
+
+// TempTableConfig represents a CREATE TEMP TABLE statement with an ON COMMIT action other than PRESERVE ROWS.
+type TempTableConfig struct {
+	Name     string
+	OnCommit OnCommitAction
+}
+
+// tempTable is a temporary table of the connection with an ON COMMIT action.
+type tempTable struct {
+	name     string
+	onCommit OnCommitAction
+	// created is set if the table is created in the current transaction, so it is gone if the transaction is
+	// rolled back.
+	created bool
+}
+
+var (
+	onCommitRegex = regexp.MustCompile(`(?i)\bON\s+COMMIT\b`)
+	pgTempRegex   = regexp.MustCompile(`(?i)\bpg_temp\s*\.`)
+)
+
+// ConvertTempSchema rewrites the references to the pg_temp schema in the query into the temp.main schema of DuckDB.
+func ConvertTempSchema(sql string) string {
+	if !strings.Contains(strings.ToLower(sql), "pg_temp") {
+		return sql
+	}
+	return pgTempRegex.ReplaceAllString(sql, "temp.main.")
+}
+
+// stripOnCommit removes the ON COMMIT clauses of the CREATE TEMP TABLE statements in the query. It returns the ON
+// COMMIT action of each of these statements in order, which is PRESERVE ROWS if it has no such clause, or nil if
+// there is no ON COMMIT clause in the query at all.
+func stripOnCommit(query string) (string, []OnCommitAction) {
+	if !onCommitRegex.MatchString(query) {
+		return query, nil
+	}
+	tokens, ok := tokenizeSQL(query)
+	if !ok {
+		return query, nil
+	}
+
+	var b strings.Builder
+	var actions []OnCommitAction
+	last, start, depth := 0, 0, 0
+	action := OnCommitPreserveRows
+	for i := 0; i <= len(tokens); i++ {
+		if i == len(tokens) || tokens[i].text == ";" {
+			if isCreateTempTable(tokens[start:i]) {
+				actions = append(actions, action)
+			}
+			start, depth, action = i+1, 0, OnCommitPreserveRows
+			continue
+		}
+		switch tokens[i].text {
+		case "(":
+			depth++
+		case ")":
+			depth--
+		}
+		if depth != 0 || !tokens.isKeyword(i, "ON") || !tokens.isKeyword(i+1, "COMMIT") || !isCreateTempTable(tokens[start:i]) {
+			continue
+		}
+		end := -1
+		switch {
+		case tokens.isKeyword(i+2, "DROP"):
+			end, action = i+2, OnCommitDrop
+		case tokens.isKeyword(i+2, "DELETE") && tokens.isKeyword(i+3, "ROWS"):
+			end, action = i+3, OnCommitDeleteRows
+		case tokens.isKeyword(i+2, "PRESERVE") && tokens.isKeyword(i+3, "ROWS"):
+			end, action = i+3, OnCommitPreserveRows
+		}
+		if end < 0 {
+			continue
+		}
+		b.WriteString(query[last:tokens[i].start])
+		last = tokens[end].end
+		i = end
+	}
+	b.WriteString(query[last:])
+	return b.String(), actions
+}
+
+// isCreateTempTable returns whether the tokens start a CREATE [LOCAL | GLOBAL] {TEMP | TEMPORARY} TABLE statement.
+func isCreateTempTable(t sqlTokens) bool {
+	if !t.isKeyword(0, "CREATE") {
+		return false
+	}
+	i := 1
+	if t.isKeyword(i, "LOCAL") || t.isKeyword(i, "GLOBAL") {
+		i++
+	}
+	return (t.isKeyword(i, "TEMP") || t.isKeyword(i, "TEMPORARY")) && t.isKeyword(i+1, "TABLE")
+}
+
+// isKeyword returns whether the token at |i| is the unquoted keyword.
+func (t sqlTokens) isKeyword(i int, keyword string) bool {
+	return i < len(t) && t[i].kind == tokenIdent && strings.EqualFold(t[i].text, keyword)
+}
+
+// trackTempTable registers the temporary table created with an ON COMMIT action.
+func (h *ConnectionHandler) trackTempTable(config *TempTableConfig) {
+	h.tempTables = append(h.tempTables, &tempTable{name: config.Name, onCommit: config.OnCommit, created: true})
+}
+
+// endTempTableTransaction takes the ON COMMIT actions on the temporary tables at the end of a transaction, or forgets
+// the tables created in it if it is rolled back.
+func (h *ConnectionHandler) endTempTableTransaction(commit bool) {
+	if len(h.tempTables) == 0 {
+		return
+	}
+	kept := h.tempTables[:0]
+	for _, table := range h.tempTables {
+		created := table.created
+		table.created = false
+		if !commit {
+			if !created {
+				kept = append(kept, table)
+			}
+			continue
+		}
+		name := "temp.main." + catalog.QuoteIdentifierANSI(table.name)
+		switch table.onCommit {
+		case OnCommitDrop:
+			if err := h.execTempTableStatement("DROP TABLE IF EXISTS " + name); err != nil {
+				h.logger.Warnf("Failed to drop the temporary table %s on commit: %v", table.name, err)
+			}
+			continue
+		case OnCommitDeleteRows:
+			if err := h.execTempTableStatement("DELETE FROM " + name); err != nil {
+				// The table has been dropped.
+				h.logger.Debugf("Failed to delete the rows of the temporary table %s on commit: %v", table.name, err)
+				continue
+			}
+		}
+		kept = append(kept, table)
+	}
+	h.tempTables = kept
+}
+
+// execTempTableStatement runs a statement on a temporary table outside the transaction blocks.
+func (h *ConnectionHandler) execTempTableStatement(query string) error {
+	ctx, err := h.duckHandler.NewContext(context.Background(), h.mysqlConn, query)
+	if err != nil {
+		return err
+	}
+	_, err = adapter.Exec(ctx, query)
+	return err
+}
+
+// dropTempTables drops all the temporary tables of the connection for DISCARD TEMP, including the tables of the
+// session views, which are created again when they are read.
+func (h *ConnectionHandler) dropTempTables() error {
+	ctx, err := h.duckHandler.NewContext(context.Background(), h.mysqlConn, "")
+	if err != nil {
+		return err
+	}
+	rows, err := adapter.Query(ctx, "SELECT table_name FROM duckdb_tables() WHERE temporary")
+	if err != nil {
+		return err
+	}
+	var names []string
+	for rows.Next() {
+		var name string
+		if err := rows.Scan(&name); err != nil {
+			rows.Close()
+			return err
+		}
+		names = append(names, name)
+	}
+	rows.Close()
+	if err := rows.Err(); err != nil {
+		return err
+	}
+	for _, name := range names {
+		if _, err := adapter.Exec(ctx, "DROP TABLE IF EXISTS temp.main."+catalog.QuoteIdentifierANSI(name)); err != nil {
+			return err
+		}
+	}
+	h.tempTables = nil
+	h.pgSettingsCreated = false
+	return nil
+}
@@ -0,0 +1,45 @@
+package pgserver
+
+import (
+	"testing"
+
+	"github.com/stretchr/testify/require"
+)
+
+func TestStripOnCommit(t *testing.T) {
+	tests := []struct {
+		query   string
+		want    string
+		actions []OnCommitAction
+	}{
+		{
+			query: "CREATE TEMP TABLE t (id INT)",
+			want:  "CREATE TEMP TABLE t (id INT)",
+		},
+		{
+			query:   "CREATE TEMP TABLE t (id INT) ON COMMIT DROP",
+			want:    "CREATE TEMP TABLE t (id INT) ",
+			actions: []OnCommitAction{OnCommitDrop},
+		},
+		{
+			query:   "BEGIN; CREATE TEMPORARY TABLE a (id INT); CREATE LOCAL TEMP TABLE b (id INT) on commit delete rows; CREATE TEMP TABLE c ON COMMIT PRESERVE ROWS AS SELECT 1;",
+			want:    "BEGIN; CREATE TEMPORARY TABLE a (id INT); CREATE LOCAL TEMP TABLE b (id INT) ; CREATE TEMP TABLE c  AS SELECT 1;",
+			actions: []OnCommitAction{OnCommitPreserveRows, OnCommitDeleteRows, OnCommitPreserveRows},
+		},
+		{
+			query: "CREATE TABLE t (id INT); SELECT 'ON COMMIT DROP'",
+			want:  "CREATE TABLE t (id INT); SELECT 'ON COMMIT DROP'",
+		},
+	}
+
+	for _, tt := range tests {
+		got, actions := stripOnCommit(tt.query)
+		require.Equal(t, tt.want, got, tt.query)
+		require.Equal(t, tt.actions, actions, tt.query)
+	}
+}
+
+func TestConvertTempSchema(t *testing.T) {
+	require.Equal(t, "SELECT * FROM temp.main.t", ConvertTempSchema("SELECT * FROM pg_temp.t"))
+	require.Equal(t, "SELECT * FROM my_pg_temp.t", ConvertTempSchema("SELECT * FROM my_pg_temp.t"))
+}
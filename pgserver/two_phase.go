@@ -0,0 +1,243 @@
+package pgserver
+
+import (
+	"context"
+	stdsql "database/sql"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"regexp"
+	"strings"
+
+	"github.com/apecloud/myduckserver/adapter"
+	"github.com/apecloud/myduckserver/catalog"
+	"github.com/cockroachdb/cockroachdb-parser/pkg/sql/sem/tree"
+	"github.com/dolthub/go-mysql-server/sql"
+)
+
+// This file handles the statements of two-phase commit:
+//
+//	PREPARE TRANSACTION 'gid';  -- Prepares the current transaction block for two-phase commit.
+//	COMMIT PREPARED 'gid';      -- Commits a prepared transaction, from any connection.
+//	ROLLBACK PREPARED 'gid';    -- Rolls back a prepared transaction, from any connection.
+//
+// DuckDB can't keep a transaction open beyond its connection, so a prepared transaction is persisted as the log of the
+// statements run in it, which is kept for savepoints (see savepoint.go), in the __sys__.prepared_transaction table.
+// PREPARE TRANSACTION rolls back the transaction in DuckDB and stores its statements, and COMMIT PREPARED replays them
+// in a new transaction, which also deletes the stored transaction. The prepared transactions survive the restarts of
+// the server and are listed in the pg_prepared_xacts view.
+//
+// Unlike in Postgres, a prepared transaction holds no locks, so COMMIT PREPARED fails if the statements no longer
+// apply, e.g., if a concurrent transaction has inserted a conflicting row in the meantime.
+
+// TwoPhaseAction represents the type of two-phase commit statement.
+type TwoPhaseAction string
+
+const (
+	TwoPhasePrepare  TwoPhaseAction = "PREPARE TRANSACTION"
+	TwoPhaseCommit   TwoPhaseAction = "COMMIT PREPARED"
+	TwoPhaseRollback TwoPhaseAction = "ROLLBACK PREPARED"
+)
+
+// TwoPhaseConfig represents a parsed two-phase commit statement.
+type TwoPhaseConfig struct {
+	Action TwoPhaseAction
+	GID    string
+}
+
+// maxPreparedTransactions is the number of prepared transactions allowed at the same time, which is shown by the
+// max_prepared_transactions parameter.
+const maxPreparedTransactions = 100
+
+// maxGIDLength is the maximum length of the identifier of a prepared transaction, as in Postgres.
+const maxGIDLength = 200
+
+// twoPhaseRegex matches a two-phase commit statement and captures the action and the transaction identifier.
+var twoPhaseRegex = regexp.MustCompile(`(?is)^(PREPARE\s+TRANSACTION|COMMIT\s+PREPARED|ROLLBACK\s+PREPARED)\s+'((?:[^']|'')*)'$`)
+
+var whitespaceRegex = regexp.MustCompile(`\s+`)
+
+// parseTwoPhaseSQL parses the given SQL statement and returns a TwoPhaseConfig, or nil if it is not a two-phase
+// commit statement.
+func parseTwoPhaseSQL(query string) (*TwoPhaseConfig, error) {
+	query = strings.TrimSpace(sql.RemoveSpaceAndDelimiter(RemoveComments(query), ';'))
+	matches := twoPhaseRegex.FindStringSubmatch(query)
+	if matches == nil {
+		return nil, nil
+	}
+	config := &TwoPhaseConfig{
+		Action: TwoPhaseAction(strings.ToUpper(whitespaceRegex.ReplaceAllString(matches[1], " "))),
+		GID:    strings.ReplaceAll(matches[2], "''", "'"),
+	}
+	if len(config.GID) >= maxGIDLength {
+		return nil, fmt.Errorf("transaction identifier \"%s\" is too long", config.GID)
+	}
+	return config, nil
+}
+
+// preparedStatement is a statement of a prepared transaction as it is stored.
+type preparedStatement struct {
+	Query string `json:"query"`
+	Vars  []any  `json:"vars,omitempty"`
+}
+
+// executeTwoPhaseStatement executes a PREPARE TRANSACTION, COMMIT PREPARED, or ROLLBACK PREPARED statement.
+func (h *ConnectionHandler) executeTwoPhaseStatement(statement ConvertedStatement) error {
+	config := statement.TwoPhaseConfig
+	var err error
+	switch config.Action {
+	case TwoPhasePrepare:
+		err = h.prepareTransaction(config.GID)
+	case TwoPhaseCommit, TwoPhaseRollback:
+		if h.inTransaction {
+			return fmt.Errorf("%s cannot run inside a transaction block", config.Action)
+		}
+		err = h.finishPreparedTransaction(config.GID, config.Action == TwoPhaseCommit)
+	default:
+		err = fmt.Errorf("unsupported two-phase commit statement: %s", statement.String)
+	}
+	if err != nil {
+		return err
+	}
+	return h.send(makeCommandComplete(string(config.Action), 0))
+}
+
+// prepareTransaction ends the current transaction block, and stores its statements to be committed later. As in
+// Postgres, the transaction is rolled back if it can't be prepared.
+func (h *ConnectionHandler) prepareTransaction(gid string) error {
+	if !h.inTransaction {
+		return fmt.Errorf("PREPARE TRANSACTION can only be used in transaction blocks")
+	}
+	log := &h.transactionLog
+	var err error
+	switch {
+	case log.unreplayable != "":
+		err = fmt.Errorf("cannot PREPARE a transaction in which %s has run", log.unreplayable)
+	case len(h.outgoingNotifications) > 0:
+		err = fmt.Errorf("cannot PREPARE a transaction that has executed LISTEN, UNLISTEN, or NOTIFY")
+	case h.hasTempTablesInTransaction():
+		err = fmt.Errorf("cannot PREPARE a transaction that has operated on temporary objects")
+	case h.hasHoldCursorsInTransaction():
+		err = fmt.Errorf("cannot PREPARE a transaction that has created a cursor WITH HOLD")
+	}
+
+	statements := make([]preparedStatement, len(log.statements))
+	for i, statement := range log.statements {
+		statements[i] = preparedStatement{Query: statement.query, Vars: statement.vars}
+	}
+	encoded, encodeErr := json.Marshal(statements)
+	if err == nil {
+		err = encodeErr
+	}
+
+	// The transaction is rolled back in DuckDB either way, and replayed by COMMIT PREPARED.
+	_, rollbackErr := h.execInsertBatch("ROLLBACK", &tree.RollbackTransaction{})
+	h.trackTransaction(ConvertedStatement{AST: &tree.RollbackTransaction{}}, nil)
+	if err != nil || rollbackErr != nil {
+		return errors.Join(err, rollbackErr)
+	}
+
+	ctx, err := h.duckHandler.NewContext(context.Background(), h.mysqlConn, "")
+	if err != nil {
+		return err
+	}
+	table := catalog.InternalTables.PreparedTransaction
+	var count int
+	if err := adapter.QueryRowCatalog(ctx, table.CountAllStmt()).Scan(&count); err != nil {
+		return err
+	}
+	if count >= maxPreparedTransactions {
+		return fmt.Errorf("maximum number of prepared transactions reached")
+	}
+	var exists bool
+	if err := adapter.QueryRowCatalog(ctx, "SELECT count(*) > 0 FROM "+table.QualifiedName()+" WHERE gid = ?", gid).Scan(&exists); err != nil {
+		return err
+	}
+	if exists {
+		return fmt.Errorf("transaction identifier \"%s\" is already in use", gid)
+	}
+	_, err = adapter.ExecCatalog(ctx,
+		"INSERT INTO "+table.QualifiedName()+" (gid, xid, prepared, owner, database, schema, statements)"+
+			" VALUES (?, txid_current(), now(), ?, ?, ?, ?)",
+		gid, h.mysqlConn.User, adapter.GetCurrentCatalog(ctx), adapter.GetCurrentSchema(ctx), string(encoded),
+	)
+	return err
+}
+
+// finishPreparedTransaction commits a prepared transaction by replaying its statements, or rolls it back by
+// discarding them.
+func (h *ConnectionHandler) finishPreparedTransaction(gid string, commit bool) error {
+	ctx, err := h.duckHandler.NewContext(context.Background(), h.mysqlConn, "")
+	if err != nil {
+		return err
+	}
+	table := catalog.InternalTables.PreparedTransaction
+	var owner, database, schema, encoded string
+	err = adapter.QueryRowCatalog(ctx,
+		"SELECT owner, database, schema, statements FROM "+table.QualifiedName()+" WHERE gid = ?", gid,
+	).Scan(&owner, &database, &schema, &encoded)
+	if errors.Is(err, stdsql.ErrNoRows) {
+		return fmt.Errorf("prepared transaction with identifier \"%s\" does not exist", gid)
+	} else if err != nil {
+		return err
+	}
+	if h.mysqlConn.User != owner && !h.isSuperuser() {
+		return fmt.Errorf("permission denied to finish prepared transaction")
+	}
+
+	if !commit {
+		_, err = adapter.ExecCatalog(ctx, table.DeleteStmt(), gid)
+		return err
+	}
+
+	if current := adapter.GetCurrentCatalog(ctx); current != database {
+		return fmt.Errorf("prepared transaction belongs to another database \"%s\"", database)
+	}
+	if current := adapter.GetCurrentSchema(ctx); current != schema {
+		return fmt.Errorf("prepared transaction was prepared in another schema \"%s\"", schema)
+	}
+	var statements []preparedStatement
+	if err := json.Unmarshal([]byte(encoded), &statements); err != nil {
+		return fmt.Errorf("failed to decode prepared transaction \"%s\": %w", gid, err)
+	}
+
+	replay := func() error {
+		for _, statement := range statements {
+			if _, err := adapter.ExecCatalog(ctx, statement.Query, statement.Vars...); err != nil {
+				return err
+			}
+		}
+		_, err := adapter.ExecCatalog(ctx, table.DeleteStmt(), gid)
+		return err
+	}
+	if _, err := h.execInsertBatch("BEGIN", &tree.BeginTransaction{}); err != nil {
+		return err
+	}
+	if err := replay(); err != nil {
+		_, rollbackErr := h.execInsertBatch("ROLLBACK", &tree.RollbackTransaction{})
+		return errors.Join(fmt.Errorf("failed to commit prepared transaction \"%s\": %w", gid, err), rollbackErr)
+	}
+	_, err = h.execInsertBatch("COMMIT", &tree.CommitTransaction{})
+	return err
+}
+
+// hasTempTablesInTransaction returns whether a temporary table with an ON COMMIT action is created in the current
+// transaction.
+func (h *ConnectionHandler) hasTempTablesInTransaction() bool {
+	for _, table := range h.tempTables {
+		if table.created {
+			return true
+		}
+	}
+	return false
+}
+
+// hasHoldCursorsInTransaction returns whether a cursor WITH HOLD is declared in the current transaction.
+func (h *ConnectionHandler) hasHoldCursorsInTransaction() bool {
+	for _, cursor := range h.cursors {
+		if cursor.hold && cursor.declaredInTransaction {
+			return true
+		}
+	}
+	return false
+}
@@ -0,0 +1,43 @@
+package pgserver
+
+import (
+	"strings"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+)
+
+func TestParseTwoPhaseSQL(t *testing.T) {
+	tests := []struct {
+		query string
+		want  *TwoPhaseConfig
+	}{
+		{
+			query: "PREPARE TRANSACTION 'tx1'",
+			want:  &TwoPhaseConfig{Action: TwoPhasePrepare, GID: "tx1"},
+		},
+		{
+			query: "commit  prepared 'it''s';",
+			want:  &TwoPhaseConfig{Action: TwoPhaseCommit, GID: "it's"},
+		},
+		{
+			query: "ROLLBACK\n\tPREPARED 'tx1'",
+			want:  &TwoPhaseConfig{Action: TwoPhaseRollback, GID: "tx1"},
+		},
+		{
+			query: "PREPARE stmt AS SELECT 1",
+		},
+		{
+			query: "COMMIT",
+		},
+	}
+
+	for _, tt := range tests {
+		got, err := parseTwoPhaseSQL(tt.query)
+		require.NoError(t, err, tt.query)
+		require.Equal(t, tt.want, got, tt.query)
+	}
+
+	_, err := parseTwoPhaseSQL("PREPARE TRANSACTION '" + strings.Repeat("x", maxGIDLength) + "'")
+	require.Error(t, err)
+}
@@ -0,0 +1,840 @@
+package pgserver
+
+import (
+	"context"
+	stdsql "database/sql"
+	"encoding/binary"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"hash/fnv"
+	"regexp"
+	"slices"
+	"strconv"
+	"strings"
+	"time"
+
+	"github.com/apecloud/myduckserver/adapter"
+	"github.com/apecloud/myduckserver/catalog"
+	"github.com/dolthub/go-mysql-server/sql"
+	"github.com/jackc/pglogrepl"
+	"github.com/jackc/pgx/v5/pgproto3"
+	"github.com/jackc/pgx/v5/pgtype"
+)
+
+// This file handles the replication connections, which are started with the replication=database parameter, and
+// stream the changes of the publications (see publication.go) to the logical replication subscribers, e.g., Postgres
+// or Debezium:
+//
+//	IDENTIFY_SYSTEM;
+//	CREATE_REPLICATION_SLOT slot_name LOGICAL pgoutput [(option, ...)];
+//	DROP_REPLICATION_SLOT slot_name [WAIT];
+//	START_REPLICATION SLOT slot_name LOGICAL lsn (proto_version 'n', publication_names 'name, ...');
+//
+// The other statements of a replication connection are run as usual. The slots are stored in the
+// __sys__.replication_slot table of the default catalog. The captured changes of each LSN are sent as a
+// transaction in the pgoutput format, with the values of the columns in text, and are removed once all the slots
+// have confirmed them. Only the logical slots of the pgoutput plugin are supported, which may not be temporary, and
+// no snapshot is exported, so a subscriber has to copy the initial data of the tables by itself.
+
+// ReplicationAction represents the type of replication command.
+type ReplicationAction string
+
+const (
+	ReplicationIdentifySystem ReplicationAction = "IDENTIFY_SYSTEM"
+	ReplicationCreateSlot     ReplicationAction = "CREATE_REPLICATION_SLOT"
+	ReplicationDropSlot       ReplicationAction = "DROP_REPLICATION_SLOT"
+	ReplicationStart          ReplicationAction = "START_REPLICATION"
+)
+
+// ReplicationCommand represents a parsed replication command.
+type ReplicationCommand struct {
+	Action ReplicationAction
+	Slot   string
+	// Plugin is the output plugin of the slot created by CREATE_REPLICATION_SLOT.
+	Plugin string
+	// StartLSN and Options are those of START_REPLICATION.
+	StartLSN pglogrepl.LSN
+	Options  map[string]string
+}
+
+// pgoutputProtocolVersion is the latest version of the pgoutput protocol supported. The messages of the streamed
+// and the two-phase transactions of the later versions are never sent, so the versions differ in nothing else.
+const pgoutputProtocolVersion = 4
+
+var (
+	replicationCommandRegex = regexp.MustCompile(`(?is)^(IDENTIFY_SYSTEM|CREATE_REPLICATION_SLOT|DROP_REPLICATION_SLOT|START_REPLICATION|READ_REPLICATION_SLOT|TIMELINE_HISTORY|BASE_BACKUP)\b`)
+	startReplicationRegex   = regexp.MustCompile(`(?is)^START_REPLICATION\s+SLOT\s+("(?:[^"]|"")+"|[^\s"]+)\s+LOGICAL\s+([0-9A-F]+/[0-9A-F]+)\s*(?:\((.*)\))?$`)
+	logicalReplicationRegex = regexp.MustCompile(`(?is)\sLOGICAL\s`)
+	slotNameRegex           = regexp.MustCompile(`^[a-z0-9_]{1,63}$`)
+)
+
+// parseReplicationCommand parses the given command of a replication connection and returns a ReplicationCommand, or
+// nil if it is not a replication command.
+func parseReplicationCommand(query string) (*ReplicationCommand, error) {
+	query = strings.TrimSpace(sql.RemoveSpaceAndDelimiter(RemoveComments(query), ';'))
+	matches := replicationCommandRegex.FindStringSubmatch(query)
+	if matches == nil {
+		return nil, nil
+	}
+	action := ReplicationAction(strings.ToUpper(matches[1]))
+	switch action {
+	case ReplicationIdentifySystem:
+		if len(query) != len(matches[0]) {
+			return nil, fmt.Errorf("syntax error at or near \"%s\"", strings.TrimSpace(query[len(matches[0]):]))
+		}
+		return &ReplicationCommand{Action: action}, nil
+	case ReplicationStart:
+		return parseStartReplication(query)
+	case ReplicationCreateSlot, ReplicationDropSlot:
+	default:
+		return nil, fmt.Errorf("%s is not supported", action)
+	}
+
+	tokens, err := tokenizeRoleSQL(query[len(matches[0]):])
+	if err != nil {
+		return nil, err
+	}
+	p := &roleParser{tokens: tokens}
+	slot, err := p.name()
+	if err != nil {
+		return nil, err
+	}
+	if err := checkSlotName(slot); err != nil {
+		return nil, err
+	}
+	command := &ReplicationCommand{Action: action, Slot: slot}
+	if action == ReplicationDropSlot {
+		_ = p.accept("wait")
+		if !p.done() {
+			return nil, p.syntaxError()
+		}
+		return command, nil
+	}
+
+	switch {
+	case p.accept("temporary"):
+		return nil, fmt.Errorf("temporary replication slots are not supported")
+	case p.accept("physical"):
+		return nil, fmt.Errorf("physical replication is not supported")
+	case !p.accept("logical"):
+		return nil, p.syntaxError()
+	}
+	if command.Plugin, err = p.name(); err != nil {
+		return nil, err
+	}
+	if command.Plugin != "pgoutput" {
+		return nil, fmt.Errorf("output plugin \"%s\" is not supported", command.Plugin)
+	}
+	// The options of the snapshot and the two-phase transactions are ignored, since neither is supported.
+	return command, nil
+}
+
+func parseStartReplication(query string) (*ReplicationCommand, error) {
+	matches := startReplicationRegex.FindStringSubmatch(query)
+	if matches == nil {
+		if !logicalReplicationRegex.MatchString(query) {
+			return nil, fmt.Errorf("physical replication is not supported")
+		}
+		return nil, fmt.Errorf("syntax error in START_REPLICATION: %s", query)
+	}
+	command := &ReplicationCommand{Action: ReplicationStart, Slot: matches[1], Options: map[string]string{}}
+	if strings.HasPrefix(command.Slot, `"`) {
+		command.Slot = strings.ReplaceAll(command.Slot[1:len(command.Slot)-1], `""`, `"`)
+	} else {
+		command.Slot = strings.ToLower(command.Slot)
+	}
+	if err := checkSlotName(command.Slot); err != nil {
+		return nil, err
+	}
+	lsn, err := pglogrepl.ParseLSN(matches[2])
+	if err != nil {
+		return nil, err
+	}
+	command.StartLSN = lsn
+
+	tokens, err := tokenizeRoleSQL(matches[3])
+	if err != nil {
+		return nil, err
+	}
+	p := &roleParser{tokens: tokens}
+	for !p.done() {
+		name, err := p.name()
+		if err != nil {
+			return nil, err
+		}
+		var value string
+		if !p.done() && p.tokens[p.pos].kind != roleTokenPunctuation {
+			value = p.tokens[p.pos].text
+			p.pos++
+		}
+		command.Options[name] = value
+		if !p.done() && !p.acceptPunctuation(",") {
+			return nil, p.syntaxError()
+		}
+	}
+	return command, nil
+}
+
+func checkSlotName(name string) error {
+	if !slotNameRegex.MatchString(name) {
+		return fmt.Errorf("replication slot name \"%s\" contains invalid character", name)
+	}
+	return nil
+}
+
+// parsePublicationNames parses the publication_names option of START_REPLICATION, a comma-separated list of names
+// that may be quoted.
+func parsePublicationNames(option string) ([]string, error) {
+	tokens, err := tokenizeRoleSQL(option)
+	if err != nil {
+		return nil, err
+	}
+	p := &roleParser{tokens: tokens}
+	names, err := p.names()
+	if err != nil || !p.done() {
+		return nil, fmt.Errorf("invalid publication_names syntax")
+	}
+	return names, nil
+}
+
+// checkReplicationConnection accepts the logical replication connections of the superusers and the roles with the
+// REPLICATION attribute, and rejects the physical ones, which are not supported.
+func (h *ConnectionHandler) checkReplicationConnection(startupMessage *pgproto3.StartupMessage) error {
+	var code, message string
+	switch value := startupMessage.Parameters["replication"]; strings.ToLower(value) {
+	case "", "false", "off", "no", "0":
+		return nil
+	case "database":
+		allowed, err := h.canReplicate()
+		if err != nil {
+			return err
+		}
+		if allowed {
+			h.walsender = true
+			return nil
+		}
+		code, message = "42501" /* insufficient_privilege */, "permission denied to start WAL sender"
+	case "true", "on", "yes", "1":
+		code, message = "0A000" /* feature_not_supported */, "physical replication is not supported"
+	default:
+		code, message = "22023" /* invalid_parameter_value */, fmt.Sprintf("invalid value for parameter \"replication\": \"%s\"", value)
+	}
+	_ = h.send(&pgproto3.ErrorResponse{
+		Severity: string(ErrorResponseSeverity_Fatal),
+		Code:     code,
+		Message:  message,
+		Routine:  "InitPostgres",
+	})
+	return errors.New(message)
+}
+
+// canReplicate returns whether the role of the connection may start a replication connection. The users that are
+// not stored roles may, since they log in only if authentication is disabled.
+func (h *ConnectionHandler) canReplicate() (bool, error) {
+	provider, err := h.catalogProvider()
+	if err != nil {
+		return false, err
+	}
+	store := newRoleStore(provider, provider.Storage())
+	var allowed bool
+	err = store.q.QueryRowContext(context.Background(),
+		"SELECT coalesce(rolsuper OR rolreplication, false) FROM "+store.table(catalog.InternalTables.PGAuthID)+" WHERE rolname = ?",
+		h.mysqlConn.User,
+	).Scan(&allowed)
+	if errors.Is(err, stdsql.ErrNoRows) {
+		return true, nil
+	}
+	return allowed, err
+}
+
+// executeReplicationCommand executes a replication command of a replication connection.
+func (h *ConnectionHandler) executeReplicationCommand(statement ConvertedStatement) error {
+	command := statement.ReplicationCommand
+	provider, err := h.catalogProvider()
+	if err != nil {
+		return err
+	}
+	ctx, err := h.duckHandler.NewContext(context.Background(), h.mysqlConn, statement.String)
+	if err != nil {
+		return err
+	}
+	if h.inTransaction {
+		return fmt.Errorf("%s cannot be executed inside a transaction block", command.Action)
+	}
+	store := newPublicationStore(provider, provider.Storage())
+	switch command.Action {
+	case ReplicationIdentifySystem:
+		return h.identifySystem(ctx, store)
+	case ReplicationCreateSlot:
+		return h.createReplicationSlot(ctx, provider, command)
+	case ReplicationDropSlot:
+		return h.dropReplicationSlot(store, command)
+	case ReplicationStart:
+		return h.startReplication(store, command)
+	default:
+		return fmt.Errorf("unsupported replication command: %s", statement.String)
+	}
+}
+
+// sendReplicationResult sends the result row of a replication command, whose columns are text, except those of
+// int4.
+func (h *ConnectionHandler) sendReplicationResult(tag string, columns []string, oids []uint32, values [][]byte) error {
+	fields := make([]pgproto3.FieldDescription, len(columns))
+	for i, column := range columns {
+		size := int16(-1)
+		if oids[i] == pgtype.Int4OID {
+			size = 4
+		}
+		fields[i] = pgproto3.FieldDescription{Name: []byte(column), DataTypeOID: oids[i], DataTypeSize: size, TypeModifier: -1}
+	}
+	h.sendBuffered(&pgproto3.RowDescription{Fields: fields})
+	h.sendBuffered(&pgproto3.DataRow{Values: values})
+	return h.send(makeCommandComplete(tag, 0))
+}
+
+// currentLSN returns the LSN of the last captured change.
+func currentLSN(store *publicationStore) (pglogrepl.LSN, error) {
+	var lsn int64
+	err := store.q.QueryRowContext(context.Background(), "SELECT "+store.currentLSN()).Scan(&lsn)
+	return pglogrepl.LSN(lsn), err
+}
+
+// identifySystem sends the system identifier, which is derived from the default catalog, the timeline, which is
+// always 1, the current LSN, and the database of the connection.
+func (h *ConnectionHandler) identifySystem(ctx *sql.Context, store *publicationStore) error {
+	lsn, err := currentLSN(store)
+	if err != nil {
+		return err
+	}
+	hash := fnv.New64a()
+	hash.Write([]byte(store.catalog))
+	return h.sendReplicationResult(string(ReplicationIdentifySystem),
+		[]string{"systemid", "timeline", "xlogpos", "dbname"},
+		[]uint32{pgtype.TextOID, pgtype.Int4OID, pgtype.TextOID, pgtype.TextOID},
+		[][]byte{
+			[]byte(strconv.FormatUint(hash.Sum64()>>1, 10)),
+			[]byte("1"),
+			[]byte(lsn.String()),
+			[]byte(adapter.GetCurrentCatalog(ctx)),
+		},
+	)
+}
+
+// createReplicationSlot creates a logical slot, which streams the changes captured from the current LSN on.
+func (h *ConnectionHandler) createReplicationSlot(ctx *sql.Context, provider *catalog.DatabaseProvider, command *ReplicationCommand) error {
+	tx, err := provider.Storage().BeginTx(context.Background(), nil)
+	if err != nil {
+		return err
+	}
+	defer tx.Rollback()
+	store := newPublicationStore(provider, tx)
+	table := store.table(catalog.InternalTables.ReplicationSlot)
+	var exists bool
+	if err := tx.QueryRow("SELECT count(*) > 0 FROM "+table+" WHERE slot_name = ?", command.Slot).Scan(&exists); err != nil {
+		return err
+	}
+	if exists {
+		return fmt.Errorf("replication slot \"%s\" already exists", command.Slot)
+	}
+	lsn, err := currentLSN(store)
+	if err != nil {
+		return err
+	}
+	if _, err := tx.Exec("INSERT INTO "+table+" VALUES (?, ?, ?, ?, NULL)",
+		command.Slot, command.Plugin, adapter.GetCurrentCatalog(ctx), int64(lsn),
+	); err != nil {
+		return err
+	}
+	if err := tx.Commit(); err != nil {
+		return err
+	}
+	if err := h.server.publisher.reload(); err != nil {
+		return err
+	}
+	return h.sendReplicationResult(string(ReplicationCreateSlot),
+		[]string{"slot_name", "consistent_point", "snapshot_name", "output_plugin"},
+		[]uint32{pgtype.TextOID, pgtype.TextOID, pgtype.TextOID, pgtype.TextOID},
+		[][]byte{[]byte(command.Slot), []byte(lsn.String()), nil, []byte(command.Plugin)},
+	)
+}
+
+// dropReplicationSlot drops a slot that is not active, and removes the changes confirmed by the remaining slots.
+func (h *ConnectionHandler) dropReplicationSlot(store *publicationStore, command *ReplicationCommand) error {
+	table := store.table(catalog.InternalTables.ReplicationSlot)
+	result, err := store.q.ExecContext(context.Background(),
+		"DELETE FROM "+table+" WHERE slot_name = ? AND active_pid IS NULL", command.Slot,
+	)
+	if err != nil {
+		return err
+	}
+	if dropped, err := result.RowsAffected(); err != nil {
+		return err
+	} else if dropped == 0 {
+		var pid int64
+		err := store.q.QueryRowContext(context.Background(),
+			"SELECT active_pid FROM "+table+" WHERE slot_name = ?", command.Slot,
+		).Scan(&pid)
+		if errors.Is(err, stdsql.ErrNoRows) {
+			return fmt.Errorf("replication slot \"%s\" does not exist", command.Slot)
+		} else if err != nil {
+			return err
+		}
+		return fmt.Errorf("replication slot \"%s\" is active for PID %d", command.Slot, pid)
+	}
+	if err := store.pruneChanges(); err != nil {
+		return err
+	}
+	if err := h.server.publisher.reload(); err != nil {
+		return err
+	}
+	return h.send(makeCommandComplete(string(ReplicationDropSlot), 0))
+}
+
+// pruneChanges removes the changes confirmed by all the replication slots, except the last one, which keeps the
+// LSNs increasing.
+func (s *publicationStore) pruneChanges() error {
+	changes := s.table(catalog.InternalTables.ReplicationChange)
+	_, err := s.q.ExecContext(context.Background(),
+		"DELETE FROM "+changes+" WHERE lsn < (SELECT max(lsn) FROM "+changes+")"+
+			" AND lsn <= coalesce((SELECT min(confirmed_flush_lsn) FROM "+s.table(catalog.InternalTables.ReplicationSlot)+"),"+
+			" (SELECT max(lsn) FROM "+changes+"))",
+	)
+	return err
+}
+
+// startReplication streams the changes after the LSN confirmed by the slot, or after the given one if it is later,
+// until the client ends the COPY mode. The slot is active meanwhile, so that it is not streamed by other connections.
+func (h *ConnectionHandler) startReplication(store *publicationStore, command *ReplicationCommand) error {
+	var protoVersion int
+	if option, ok := command.Options["proto_version"]; !ok {
+		return fmt.Errorf("proto_version option missing")
+	} else if v, err := strconv.Atoi(option); err != nil {
+		return fmt.Errorf("invalid proto_version")
+	} else {
+		protoVersion = v
+	}
+	if protoVersion < 1 || protoVersion > pgoutputProtocolVersion {
+		return fmt.Errorf("client sent proto_version=%d but server only supports protocol %d or lower", protoVersion, pgoutputProtocolVersion)
+	}
+	option, ok := command.Options["publication_names"]
+	if !ok {
+		return fmt.Errorf("publication_names parameter missing")
+	}
+	names, err := parsePublicationNames(option)
+	if err != nil {
+		return err
+	}
+	for _, name := range names {
+		if _, err := store.mustLookup(name); err != nil {
+			return err
+		}
+	}
+
+	table := store.table(catalog.InternalTables.ReplicationSlot)
+	var confirmed int64
+	err = store.q.QueryRowContext(context.Background(),
+		"UPDATE "+table+" SET active_pid = ? WHERE slot_name = ? AND active_pid IS NULL RETURNING confirmed_flush_lsn",
+		h.mysqlConn.ConnectionID, command.Slot,
+	).Scan(&confirmed)
+	if errors.Is(err, stdsql.ErrNoRows) {
+		var pid int64
+		err = store.q.QueryRowContext(context.Background(), "SELECT active_pid FROM "+table+" WHERE slot_name = ?", command.Slot).Scan(&pid)
+		if errors.Is(err, stdsql.ErrNoRows) {
+			return fmt.Errorf("replication slot \"%s\" does not exist", command.Slot)
+		} else if err == nil {
+			err = fmt.Errorf("replication slot \"%s\" is active for PID %d", command.Slot, pid)
+		}
+	}
+	if err != nil {
+		return err
+	}
+	defer func() {
+		if _, err := store.q.ExecContext(context.Background(), "UPDATE "+table+" SET active_pid = NULL WHERE slot_name = ?", command.Slot); err != nil {
+			h.logger.WithError(err).Warnf("Failed to release the replication slot %s", command.Slot)
+		}
+	}()
+
+	w := &walSender{
+		h:            h,
+		store:        store,
+		slot:         command.Slot,
+		publications: names,
+		relations:    map[PublicationTable]*walRelation{},
+		sent:         max(command.StartLSN, pglogrepl.LSN(confirmed)),
+	}
+	if err := h.send(&pgproto3.CopyBothResponse{}); err != nil {
+		return err
+	}
+	h.replication.start(w.sent)
+	defer h.replication.stop()
+	if err := w.stream(); err != nil {
+		// As in Postgres, the connection ends with the failed stream, which leaves no way to tell the client
+		// messages that are still coming from the copy data.
+		_ = h.send(&pgproto3.ErrorResponse{
+			Severity: string(ErrorResponseSeverity_Fatal),
+			Code:     "XX000", // internal_error
+			Message:  err.Error(),
+		})
+		h.terminated = true
+		return err
+	}
+	h.sendBuffered(makeCommandComplete("COPY", 0))
+	return h.send(makeCommandComplete(string(ReplicationStart), 0))
+}
+
+// walSender streams the changes of a replication slot.
+type walSender struct {
+	h            *ConnectionHandler
+	store        *publicationStore
+	slot         string
+	publications []string
+	// relations are the relations sent by Relation messages, by their tables.
+	relations map[PublicationTable]*walRelation
+	// sent is the LSN of the last change sent or skipped.
+	sent pglogrepl.LSN
+}
+
+// walRelation is a relation sent to the client, with its columns when it was sent.
+type walRelation struct {
+	id      uint32
+	columns []publishedColumn
+}
+
+// walSenderKeepaliveInterval is the interval of the keepalive messages, which ask the client for its status.
+const walSenderKeepaliveInterval = 10 * time.Second
+
+// walSenderPollInterval is the interval of polling the captured changes, which are committed a moment after the
+// publisher is notified of them.
+const walSenderPollInterval = time.Second
+
+// stream sends the changes until the client ends the COPY mode, while receiving the status updates of the client,
+// which confirm the changes it has flushed.
+func (w *walSender) stream() error {
+	var (
+		feedback = make(chan struct{}, 1)
+		done     = make(chan error, 1)
+		received = make(chan struct{})
+	)
+	go func() {
+		defer close(received)
+		for {
+			msg, err := w.h.backend.Receive()
+			if err != nil {
+				done <- err
+				return
+			}
+			switch msg := msg.(type) {
+			case *pgproto3.CopyData:
+				// A standby status update: 'r', the LSNs written, flushed, and applied, the time, and the reply flag.
+				if len(msg.Data) == 34 && msg.Data[0] == 'r' {
+					w.h.replication.reply(
+						pglogrepl.LSN(binary.BigEndian.Uint64(msg.Data[1:9])),
+						pglogrepl.LSN(binary.BigEndian.Uint64(msg.Data[9:17])),
+						pglogrepl.LSN(binary.BigEndian.Uint64(msg.Data[17:25])),
+						postgresEpoch.Add(time.Duration(int64(binary.BigEndian.Uint64(msg.Data[25:33])))*time.Microsecond),
+					)
+					select {
+					case feedback <- struct{}{}:
+					default:
+					}
+				}
+			case *pgproto3.CopyDone:
+				done <- nil
+				return
+			case *pgproto3.CopyFail:
+				done <- fmt.Errorf("COPY failed: %s", msg.Message)
+				return
+			default:
+				done <- fmt.Errorf("unexpected message type %T during COPY", msg)
+				return
+			}
+		}
+	}()
+	// The receiving goroutine is stopped before returning, by failing its pending read, so that it does not read the
+	// messages after the COPY mode.
+	defer func() {
+		select {
+		case <-received:
+		default:
+			_ = w.h.Conn().SetReadDeadline(time.Now())
+			<-received
+			_ = w.h.Conn().SetReadDeadline(time.Time{})
+		}
+	}()
+
+	keepalive := time.NewTicker(walSenderKeepaliveInterval)
+	defer keepalive.Stop()
+	poll := time.NewTicker(walSenderPollInterval)
+	defer poll.Stop()
+	for {
+		if err := w.sendChanges(); err != nil {
+			return err
+		}
+		w.h.replication.setState(walSenderStateStreaming)
+		select {
+		case <-w.h.server.publisher.changes():
+		case <-poll.C:
+		case <-keepalive.C:
+			if err := w.sendKeepalive(true); err != nil {
+				return err
+			}
+		case <-feedback:
+			if err := w.confirm(w.h.replication.flushed()); err != nil {
+				return err
+			}
+		case err := <-done:
+			if err != nil {
+				return err
+			}
+			return w.h.send(&pgproto3.CopyDone{})
+		}
+	}
+}
+
+// confirm records the LSN flushed by the client in the slot, and removes the changes confirmed by all the slots.
+func (w *walSender) confirm(lsn pglogrepl.LSN) error {
+	lsn = min(lsn, w.sent)
+	if _, err := w.store.q.ExecContext(context.Background(),
+		"UPDATE "+w.store.table(catalog.InternalTables.ReplicationSlot)+
+			" SET confirmed_flush_lsn = greatest(confirmed_flush_lsn, ?) WHERE slot_name = ?",
+		int64(lsn), w.slot,
+	); err != nil {
+		return err
+	}
+	return w.store.pruneChanges()
+}
+
+func (w *walSender) sendKeepalive(replyRequested bool) error {
+	msg := make([]byte, 0, 18)
+	msg = append(msg, pglogrepl.PrimaryKeepaliveMessageByteID)
+	msg = binary.BigEndian.AppendUint64(msg, uint64(w.sent))
+	msg = binary.BigEndian.AppendUint64(msg, uint64(pgoutputTime(time.Now())))
+	if replyRequested {
+		msg = append(msg, 1)
+	} else {
+		msg = append(msg, 0)
+	}
+	return w.h.send(&pgproto3.CopyData{Data: msg})
+}
+
+// walSenderBatchSize is the number of the captured changes read at a time.
+const walSenderBatchSize = 64
+
+// sendChanges sends the captured changes after the last one sent.
+func (w *walSender) sendChanges() error {
+	for {
+		rows, err := w.store.q.QueryContext(context.Background(),
+			"SELECT lsn, committed, changes::VARCHAR FROM "+w.store.table(catalog.InternalTables.ReplicationChange)+
+				" WHERE lsn > ? ORDER BY lsn LIMIT "+strconv.Itoa(walSenderBatchSize),
+			int64(w.sent),
+		)
+		if err != nil {
+			return err
+		}
+		type change struct {
+			lsn       int64
+			committed time.Time
+			data      string
+		}
+		var changes []change
+		for rows.Next() {
+			var c change
+			if err := rows.Scan(&c.lsn, &c.committed, &c.data); err != nil {
+				rows.Close()
+				return err
+			}
+			changes = append(changes, c)
+		}
+		rows.Close()
+		if err := rows.Err(); err != nil {
+			return err
+		}
+		if len(changes) == 0 {
+			return nil
+		}
+
+		// The publications are looked up again for each batch, so that their changes take effect.
+		publications := make([]*publicationInfo, 0, len(w.publications))
+		for _, name := range w.publications {
+			pub, err := w.store.mustLookup(name)
+			if err != nil {
+				return err
+			}
+			publications = append(publications, pub)
+		}
+		for _, c := range changes {
+			var tables []publishedTable
+			if err := json.Unmarshal([]byte(c.data), &tables); err != nil {
+				return fmt.Errorf("invalid captured changes at %s: %w", pglogrepl.LSN(c.lsn), err)
+			}
+			if err := w.sendTransaction(pglogrepl.LSN(c.lsn), c.committed, tables, publications); err != nil {
+				return err
+			}
+			w.sent = pglogrepl.LSN(c.lsn)
+			w.h.replication.sentTransaction(w.sent, c.committed)
+		}
+		if len(changes) < walSenderBatchSize {
+			return nil
+		}
+	}
+}
+
+// sendTransaction sends the published changes of an LSN as a transaction, preceded by the Relation messages of the
+// tables that are new to the client or whose columns have changed. A transaction without published changes is
+// skipped, as in Postgres.
+func (w *walSender) sendTransaction(lsn pglogrepl.LSN, committed time.Time, tables []publishedTable, publications []*publicationInfo) error {
+	var messages [][]byte
+	for _, table := range tables {
+		id := PublicationTable{Schema: table.Schema, Name: table.Table}
+		var relation *walRelation
+		for _, row := range table.Rows {
+			if !slices.ContainsFunc(publications, func(pub *publicationInfo) bool { return pub.publishes(id, row.Action[0]) }) {
+				continue
+			}
+			if relation == nil {
+				relation = w.relations[id]
+				if relation == nil {
+					relation = &walRelation{id: uint32(len(w.relations)) + firstRelationID}
+					w.relations[id] = relation
+				}
+				if relation.columns == nil || !slices.Equal(relation.columns, table.Columns) {
+					relation.columns = table.Columns
+					messages = append(messages, encodeRelation(relation.id, table))
+				}
+			}
+			messages = append(messages, encodeRowChange(relation.id, table, row))
+		}
+	}
+	if len(messages) == 0 {
+		return nil
+	}
+
+	xid := uint32(lsn)
+	w.sendXLogData(lsn, encodeBegin(lsn, committed, xid))
+	for _, message := range messages {
+		w.sendXLogData(lsn, message)
+	}
+	w.sendXLogData(lsn, encodeCommit(lsn, committed))
+	return w.h.flush()
+}
+
+// firstRelationID is the ID of the first relation sent to a client. The IDs only identify the relations in the
+// stream, so they are assigned by each stream.
+const firstRelationID = 16384
+
+// sendXLogData buffers an XLogData message of a pgoutput message.
+func (w *walSender) sendXLogData(lsn pglogrepl.LSN, message []byte) {
+	data := make([]byte, 0, 25+len(message))
+	data = append(data, pglogrepl.XLogDataByteID)
+	data = binary.BigEndian.AppendUint64(data, uint64(lsn))
+	data = binary.BigEndian.AppendUint64(data, uint64(lsn))
+	data = binary.BigEndian.AppendUint64(data, uint64(pgoutputTime(time.Now())))
+	data = append(data, message...)
+	w.h.sendBuffered(&pgproto3.CopyData{Data: data})
+}
+
+// postgresEpoch is the epoch of the timestamps in the replication protocol.
+var postgresEpoch = time.Date(2000, 1, 1, 0, 0, 0, 0, time.UTC)
+
+// pgoutputTime returns the microseconds since the Postgres epoch.
+func pgoutputTime(t time.Time) int64 {
+	return t.Sub(postgresEpoch).Microseconds()
+}
+
+func encodeBegin(lsn pglogrepl.LSN, committed time.Time, xid uint32) []byte {
+	msg := []byte{byte(pglogrepl.MessageTypeBegin)}
+	msg = binary.BigEndian.AppendUint64(msg, uint64(lsn))
+	msg = binary.BigEndian.AppendUint64(msg, uint64(pgoutputTime(committed)))
+	return binary.BigEndian.AppendUint32(msg, xid)
+}
+
+func encodeCommit(lsn pglogrepl.LSN, committed time.Time) []byte {
+	msg := []byte{byte(pglogrepl.MessageTypeCommit), 0}
+	msg = binary.BigEndian.AppendUint64(msg, uint64(lsn))
+	msg = binary.BigEndian.AppendUint64(msg, uint64(lsn))
+	return binary.BigEndian.AppendUint64(msg, uint64(pgoutputTime(committed)))
+}
+
+// hasKey returns whether the table has a primary key, which is its replica identity. The replica identity of a table
+// without one is all of its columns.
+func (t *publishedTable) hasKey() bool {
+	return slices.ContainsFunc(t.Columns, func(c publishedColumn) bool { return c.Key })
+}
+
+func encodeRelation(id uint32, table publishedTable) []byte {
+	msg := []byte{byte(pglogrepl.MessageTypeRelation)}
+	msg = binary.BigEndian.AppendUint32(msg, id)
+	msg = append(append(msg, table.Schema...), 0)
+	msg = append(append(msg, table.Table...), 0)
+	hasKey := table.hasKey()
+	if hasKey {
+		msg = append(msg, 'd')
+	} else {
+		msg = append(msg, 'f')
+	}
+	msg = binary.BigEndian.AppendUint16(msg, uint16(len(table.Columns)))
+	for _, column := range table.Columns {
+		if column.Key || !hasKey {
+			msg = append(msg, 1)
+		} else {
+			msg = append(msg, 0)
+		}
+		msg = append(append(msg, column.Name...), 0)
+		msg = binary.BigEndian.AppendUint32(msg, column.Type)
+		msg = binary.BigEndian.AppendUint32(msg, 0xFFFFFFFF) // typmod -1
+	}
+	return msg
+}
+
+// encodeRowChange encodes an Insert, Update, or Delete message. The old row of a table with a primary key is sent
+// as its key, with the other columns NULL, and only if it is a delete or the key is updated.
+func encodeRowChange(id uint32, table publishedTable, row publishedRow) []byte {
+	msg := []byte{row.Action[0]}
+	msg = binary.BigEndian.AppendUint32(msg, id)
+	old := row.Old
+	if table.hasKey() && old != nil {
+		key := make([]*string, len(old))
+		changed := false
+		for i, column := range table.Columns {
+			if column.Key {
+				key[i] = old[i]
+				if row.New != nil && !equalValues(old[i], row.New[i]) {
+					changed = true
+				}
+			}
+		}
+		old = nil
+		if row.Action == "D" || changed {
+			msg = append(msg, 'K')
+			msg = encodeTuple(msg, key)
+		}
+	}
+	if old != nil {
+		msg = append(msg, 'O')
+		msg = encodeTuple(msg, old)
+	}
+	if row.New != nil {
+		msg = append(msg, 'N')
+		msg = encodeTuple(msg, row.New)
+	}
+	return msg
+}
+
+func equalValues(a, b *string) bool {
+	return a == b || (a != nil && b != nil && *a == *b)
+}
+
+func encodeTuple(msg []byte, values []*string) []byte {
+	msg = binary.BigEndian.AppendUint16(msg, uint16(len(values)))
+	for _, value := range values {
+		if value == nil {
+			msg = append(msg, 'n')
+			continue
+		}
+		msg = append(msg, 't')
+		msg = binary.BigEndian.AppendUint32(msg, uint32(len(*value)))
+		msg = append(msg, *value...)
+	}
+	return msg
+}
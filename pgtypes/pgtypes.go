@@ -5,6 +5,7 @@ import (
 	"database/sql/driver"
 	"fmt"
 	"reflect"
+	"regexp"
 	"strings"
 
 	"github.com/apache/arrow-go/v18/arrow"
@@ -18,6 +19,10 @@ import (
 
 var DefaultTypeMap *pgtype.Map
 
+// FirstNormalObjectID is the first OID of the user-defined objects in Postgres. The user-defined types that are not
+// in DefaultTypeMap, e.g., the enum types, are sent as text.
+const FirstNormalObjectID uint32 = 16384
+
 func init() {
 	DefaultTypeMap = pgtype.NewMap()
 }
@@ -49,6 +54,7 @@ var duckdbTypeNameToPostgresTypeName = map[string]string{
 	"TIMESTAMP_NS": "timestamp",
 	"ENUM":         "text",
 	"UUID":         "uuid",
+	"JSON":         "json",
 	"BIT":          "bit",
 	"TIMETZ":       "timetz",
 	"TIMESTAMPTZ":  "timestamptz",
@@ -249,6 +255,15 @@ func PostgresTypeSize(oid uint32) int32 {
 	return -1
 }
 
+// listTypeSuffixRegex matches the dimensions in the names of the LIST and ARRAY types reported by go-duckdb,
+// e.g., INTEGER[][] and INTEGER[3]. A Postgres array type covers all the dimensions of its element type.
+var listTypeSuffixRegex = regexp.MustCompile(`(\[\d*\])+$`)
+
+// IsListTypeName returns whether a type name reported by the go-duckdb driver is of a LIST or an ARRAY type.
+func IsListTypeName(name string) bool {
+	return listTypeSuffixRegex.MatchString(name)
+}
+
 // GoDuckDBTypeNameToPostgresType parses a type name reported by the go-duckdb driver
 // into a corresponding pgtype.Type with its precision and scale (if applicable).
 // Unknown types are fallback to text.
@@ -256,10 +271,10 @@ func PostgresTypeSize(oid uint32) int32 {
 // TODO(fan): Make this function more rigorous for nested types.
 func GoDuckDBTypeNameToPostgresType(name string) (pt *pgtype.Type, precision, scale int32, fallback bool, err error) {
 	var list bool
-	if strings.HasSuffix(name, "[]") {
-		// LIST type
+	if loc := listTypeSuffixRegex.FindStringIndex(name); loc != nil {
+		// LIST or ARRAY type
 		// Ref: logicalTypeNameList in go-duckdb
-		name = strings.TrimSuffix(name, "[]")
+		name = name[:loc[0]]
 		list = true
 	}
 
@@ -388,6 +403,18 @@ type PostgresType struct {
 	Fallback bool
 }
 
+// TypeModifier returns the type modifier reported in RowDescription, i.e., the precision and the scale encoded as in
+// Postgres for numeric and its array type, or -1 for the other types.
+func (p PostgresType) TypeModifier() int32 {
+	switch p.PG.OID {
+	case pgtype.NumericOID, pgtype.NumericArrayOID:
+		if p.Precision > 0 {
+			return (p.Precision<<16 | p.Scale) + 4 // add VARHDRSZ
+		}
+	}
+	return -1
+}
+
 func NewPostgresType(registry *pgtype.Map, oid uint32, modifier int32) (PostgresType, error) {
 	t, ok := registry.TypeForOID(oid)
 	if !ok {
@@ -0,0 +1,52 @@
+#!/usr/bin/env bats
+bats_require_minimum_version 1.5.0
+
+load helper
+
+setup_file() {
+    mysql_exec_stdin <<-'EOF'
+    CREATE DATABASE savepoint_test;
+    USE savepoint_test;
+    CREATE TABLE t (id INT PRIMARY KEY);
+EOF
+}
+
+teardown_file() {
+    mysql_exec_stdin <<-'EOF'
+    DROP DATABASE IF EXISTS savepoint_test;
+EOF
+}
+
+@test "ROLLBACK TO SAVEPOINT should undo the statements after the savepoint" {
+    run -0 mysql_exec_stdin <<-'EOF'
+    USE savepoint_test;
+    DELETE FROM t;
+    START TRANSACTION;
+    INSERT INTO t VALUES (1);
+    SAVEPOINT s1;
+    INSERT INTO t VALUES (2);
+    SAVEPOINT s2;
+    INSERT INTO t VALUES (3);
+    ROLLBACK TO SAVEPOINT s2;
+    SELECT COUNT(*) FROM t;
+    RELEASE SAVEPOINT s2;
+    ROLLBACK TO s1;
+    SELECT COUNT(*) FROM t;
+    INSERT INTO t VALUES (4);
+    COMMIT;
+    SELECT id FROM t ORDER BY id;
+EOF
+    [ "${lines[0]}" = "2" ]
+    [ "${lines[1]}" = "1" ]
+    [ "${lines[2]}" = "1" ]
+    [ "${lines[3]}" = "4" ]
+}
+
+@test "ROLLBACK TO SAVEPOINT should fail for an unknown savepoint" {
+    run mysql_exec_stdin <<-'EOF'
+    USE savepoint_test;
+    START TRANSACTION;
+    ROLLBACK TO SAVEPOINT missing;
+EOF
+    [ "$status" -ne 0 ]
+}
@@ -0,0 +1,93 @@
+#!/usr/bin/env bats
+bats_require_minimum_version 1.5.0
+
+load helper
+
+@test "session-level advisory locks are held until unlocked" {
+    run -0 --separate-stderr psql_exec_stdin -q <<-EOF
+        SELECT pg_advisory_lock(1001);
+        SELECT pg_advisory_lock(1001);
+        SELECT pg_advisory_unlock(1001);
+        SELECT pg_advisory_unlock(1001);
+        SELECT pg_advisory_unlock(1001);
+EOF
+    # The void results are blank lines, which are not in |lines|
+    [ "${lines[0]}" = "t" ]
+    [ "${lines[1]}" = "t" ]
+    [ "${lines[2]}" = "f" ]
+    [[ "${stderr}" == *"you don't own a lock of type ExclusiveLock"* ]]
+}
+
+@test "an advisory lock held by another session is not available" {
+    outfile=$(mktemp)
+    psql_exec_stdin > "${outfile}" 2>&1 <<-EOF &
+        SELECT pg_advisory_lock(1002);
+        \! sleep 4
+EOF
+    job=$!
+    sleep 2
+
+    run -0 psql_exec "SELECT pg_try_advisory_lock(1002)"
+    [ "${output}" = "f" ]
+    run -0 psql_exec "SELECT pg_try_advisory_lock_shared(1002)"
+    [ "${output}" = "f" ]
+
+    # The lock is released when the session ends, which ends the wait for it
+    run -0 psql_exec_stdin -q <<-EOF
+        SELECT pg_advisory_lock(1002);
+        SELECT pg_try_advisory_lock(1002);
+EOF
+    [ "${lines[0]}" = "t" ]
+    wait "${job}"
+    rm "${outfile}"
+}
+
+@test "transaction-level advisory locks are released at the end of the transaction" {
+    run -0 psql_exec_stdin -q <<-EOF
+        BEGIN;
+        SELECT pg_advisory_xact_lock(1003, 1);
+        COMMIT;
+EOF
+
+    run -0 psql_exec "SELECT pg_try_advisory_xact_lock(1003, 1)"
+    [ "${output}" = "t" ]
+}
+
+@test "a wait for an advisory lock is canceled by statement_timeout" {
+    outfile=$(mktemp)
+    psql_exec_stdin > "${outfile}" 2>&1 <<-EOF &
+        SELECT pg_advisory_lock_shared(1004);
+        \! sleep 4
+EOF
+    job=$!
+    sleep 2
+
+    run psql_exec_stdin <<-EOF
+        SET statement_timeout = 500;
+        SELECT pg_advisory_lock(1004);
+EOF
+    [ "$status" -ne 0 ]
+    [[ "${output}" == *"statement timeout"* ]]
+    wait "${job}"
+    rm "${outfile}"
+}
+
+@test "a wait for an advisory lock is canceled by lock_timeout" {
+    outfile=$(mktemp)
+    psql_exec_stdin > "${outfile}" 2>&1 <<-EOF &
+        SELECT pg_advisory_lock(1005);
+        \! sleep 4
+EOF
+    job=$!
+    sleep 2
+
+    run psql_exec_stdin <<-EOF
+        SET lock_timeout = 500;
+        SET statement_timeout = '1min';
+        SELECT pg_advisory_lock(1005);
+EOF
+    [ "$status" -ne 0 ]
+    [[ "${output}" == *"canceling statement due to lock timeout"* ]]
+    wait "${job}"
+    rm "${outfile}"
+}
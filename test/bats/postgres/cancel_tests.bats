@@ -0,0 +1,63 @@
+#!/usr/bin/env bats
+bats_require_minimum_version 1.5.0
+
+load helper
+
+# A query that runs for much longer than the tests wait for it
+LONG_QUERY="SELECT sum(i * i % 7) FROM range(100000000000) t(i);"
+
+@test "cancel request" {
+    # psql sends a CancelRequest on SIGINT
+    run timeout -s INT 3 psql -h "$PG_HOST" -U "$PG_USER" -v "ON_ERROR_STOP=1" -c "${LONG_QUERY}"
+    [ "$status" -ne 0 ]
+    [[ "${output}" == *"canceling statement due to user request"* ]]
+
+    # The server keeps serving queries
+    run -0 psql_exec "SELECT 1"
+    [ "${output}" = "1" ]
+}
+
+@test "pg_cancel_backend" {
+    outfile=$(mktemp)
+    psql_exec_stdin > "${outfile}" 2>&1 <<-EOF &
+        SELECT pg_backend_pid();
+        ${LONG_QUERY}
+EOF
+    job=$!
+    sleep 2
+    pid=$(head -n 1 "${outfile}")
+    [ -n "${pid}" ]
+
+    run -0 psql_exec "SELECT pg_cancel_backend(${pid})"
+    [ "${output}" = "t" ]
+    run wait "${job}"
+    [ "$status" -ne 0 ]
+    run -0 cat "${outfile}"
+    [[ "${output}" == *"canceling statement due to user request"* ]]
+    rm "${outfile}"
+
+    # An unknown PID is not signaled
+    run -0 psql_exec "SELECT pg_cancel_backend(2147483647)"
+    [ "${output}" = "f" ]
+}
+
+@test "pg_terminate_backend" {
+    outfile=$(mktemp)
+    psql_exec_stdin > "${outfile}" 2>&1 <<-EOF &
+        SELECT pg_backend_pid();
+        ${LONG_QUERY}
+        SELECT 'not reached';
+EOF
+    job=$!
+    sleep 2
+    pid=$(head -n 1 "${outfile}")
+    [ -n "${pid}" ]
+
+    run -0 psql_exec "SELECT pg_catalog.pg_terminate_backend(${pid})"
+    [ "${output}" = "t" ]
+    run wait "${job}"
+    [ "$status" -ne 0 ]
+    run -0 cat "${outfile}"
+    [[ "${output}" != *"not reached"* ]]
+    rm "${outfile}"
+}
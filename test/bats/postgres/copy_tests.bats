@@ -14,7 +14,7 @@ EOF
 
 teardown() {
     psql_exec "DROP SCHEMA IF EXISTS test_copy CASCADE;"
-    rm -f test_*.{csv,parquet,arrow,db} 2>/dev/null
+    rm -f test_*.{csv,parquet,arrow,bin,db} 2>/dev/null
 }
 
 @test "copy with csv format" {
@@ -121,6 +121,54 @@ EOF
     [ "${output}" == "3" ]
 }
 
+@test "copy with binary format" {
+    # pgtest/testdata/basic.bin was written by PostgreSQL with COPY ... TO STDOUT (FORMAT binary)
+    psql_exec_stdin <<-EOF
+        USE test_copy;
+        CREATE TABLE binary_test (i int, s text, f float8, n numeric(10, 2), ts timestamp, b bytea);
+        \copy binary_test FROM 'pgtest/testdata/basic.bin' (FORMAT binary);
+EOF
+    run -0 psql_exec "SELECT count(*) FROM test_copy.binary_test"
+    [ "${output}" = "3" ]
+    run -0 psql_exec "SELECT s, f, n, ts::text, octet_length(b) FROM test_copy.binary_test WHERE i = 1"
+    [ "${output}" = "one,1.5,10.25,2024-01-02 03:04:05.123456,4" ]
+    run -0 psql_exec "SELECT s, f, n FROM test_copy.binary_test WHERE i = 2"
+    [ "${output}" = "zwei ü,-2.25,-0.50" ]
+    run -0 psql_exec "SELECT count(*) FROM test_copy.binary_test WHERE s IS NULL AND f IS NULL AND n IS NULL AND ts IS NULL AND b IS NULL"
+    [ "${output}" = "1" ]
+
+    # Test with column selection
+    psql_exec_stdin <<-EOF
+        USE test_copy;
+        CREATE TABLE binary_cols (i int, s text, f float8, n numeric(10, 2), ts timestamp, b bytea, extra int);
+        \copy binary_cols (i, s, f, n, ts, b) FROM 'pgtest/testdata/basic.bin' (FORMAT binary);
+EOF
+    run -0 psql_exec "SELECT count(*), count(extra) FROM test_copy.binary_cols"
+    [ "${output}" = "3,0" ]
+
+    # Test COPY TO in binary format, read back with COPY FROM
+    outfile="test_out.bin"
+    psql_exec_stdin <<-EOF
+        USE test_copy;
+        \copy binary_test TO '${outfile}' (FORMAT binary);
+        CREATE TABLE binary_round_trip (i int, s text, f float8, n numeric(10, 2), ts timestamp, b bytea);
+        \copy binary_round_trip FROM '${outfile}' (FORMAT binary);
+EOF
+    run -0 head -c 6 "${outfile}"
+    [ "${output}" = "PGCOPY" ]
+    run -0 psql_exec "SELECT count(*) FROM (SELECT * FROM test_copy.binary_test EXCEPT SELECT * FROM test_copy.binary_round_trip)"
+    [ "${output}" = "0" ]
+    run -0 psql_exec "SELECT count(*) FROM test_copy.binary_round_trip"
+    [ "${output}" = "3" ]
+
+    # A truncated file is rejected
+    tmpfile=$(mktemp)
+    head -c 100 pgtest/testdata/basic.bin > "${tmpfile}"
+    run psql_exec "\copy test_copy.binary_test FROM '${tmpfile}' (FORMAT binary);"
+    [ "$status" -ne 0 ]
+    rm "${tmpfile}"
+}
+
 # TODO(sean): Since the Table Macro is not copyable, this test is disabled until we use the next version of DuckDB.
 # https://github.com/duckdb/duckdb/pull/15548
 #@test "copy from database" {
@@ -153,3 +201,24 @@ EOF
     [ "$status" -ne 0 ]
     rm "${tmpfile}"
 }
+
+@test "copy to and from a file on the server" {
+    tmpfile=$(mktemp -u).parquet
+    run -0 psql_exec "COPY (SELECT a, b FROM test_copy.t WHERE a > 1) TO '${tmpfile}' (FORMAT PARQUET)"
+    [ "${output}" = "COPY 2" ]
+    run -0 duckdb_exec "SELECT COUNT(*) FROM '${tmpfile}'"
+    [ "${output}" = "2" ]
+
+    run -0 psql_exec_stdin -q <<-EOF
+        CREATE TABLE test_copy.u (a int, b text);
+        COPY test_copy.u FROM '${tmpfile}';
+        SELECT a, b FROM test_copy.u ORDER BY a;
+EOF
+    [ "${lines[0]}" = "2,two" ]
+    [ "${lines[1]}" = "3,three" ]
+    rm "${tmpfile}"
+
+    run psql_exec "COPY test_copy.t TO '${tmpfile}' (FORMAT BINARY)"
+    [ "$status" -ne 0 ]
+    [[ "${output}" == *'COPY format "binary" is not supported for files'* ]]
+}
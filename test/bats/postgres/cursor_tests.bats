@@ -0,0 +1,115 @@
+#!/usr/bin/env bats
+bats_require_minimum_version 1.5.0
+
+load helper
+
+@test "fetch from a cursor" {
+    run -0 psql_exec_stdin -q <<-EOF
+        BEGIN;
+        DECLARE c CURSOR FOR SELECT i, i * i AS sq FROM range(10) t(i) ORDER BY i;
+        FETCH c;
+        FETCH 2 FROM c;
+        MOVE FORWARD 3 IN c;
+        FETCH RELATIVE 2 c;
+        FETCH ABSOLUTE 10 c;
+        FETCH ALL c;
+        CLOSE c;
+        COMMIT;
+EOF
+    [ "${lines[0]}" = "0,0" ]
+    [ "${lines[1]}" = "1,1" ]
+    [ "${lines[2]}" = "2,4" ]
+    [ "${lines[3]}" = "7,49" ]
+    [ "${lines[4]}" = "9,81" ]
+    [ "${#lines[@]}" -eq 5 ]
+
+    # The command tags report the number of rows
+    run -0 psql -h "$PG_HOST" -U "$PG_USER" -v "ON_ERROR_STOP=1" -c "BEGIN" -c "DECLARE c CURSOR FOR FROM range(5)" -c "MOVE ALL c" -c "FETCH c" -c "CLOSE ALL" -c "COMMIT"
+    [[ "${output}" == *"DECLARE CURSOR"* ]]
+    [[ "${output}" == *"MOVE 5"* ]]
+    [[ "${output}" == *"(0 rows)"* ]]
+    [[ "${output}" == *"CLOSE CURSOR ALL"* ]]
+}
+
+@test "cursor lifetime" {
+    # A cursor declared without WITH HOLD is closed at the end of the transaction
+    run psql_exec_stdin <<-EOF
+        BEGIN;
+        DECLARE c CURSOR FOR SELECT 1;
+        COMMIT;
+        FETCH c;
+EOF
+    [ "$status" -ne 0 ]
+    [[ "${output}" == *'cursor "c" does not exist'* ]]
+
+    run -0 psql_exec_stdin -q <<-EOF
+        BEGIN;
+        DECLARE "Held" CURSOR WITH HOLD FOR SELECT 42;
+        COMMIT;
+        FETCH "Held";
+        CLOSE "Held";
+EOF
+    [ "${output}" = "42" ]
+}
+
+@test "cursor declared WITH HOLD" {
+    # The rows not fetched before COMMIT can be fetched after it
+    run -0 psql_exec_stdin -q <<-EOF
+        BEGIN;
+        DECLARE c CURSOR WITH HOLD FOR SELECT i FROM range(5) t(i) ORDER BY i;
+        FETCH 2 c;
+        COMMIT;
+        SELECT 'committed';
+        FETCH ALL c;
+        CLOSE c;
+EOF
+    [ "${lines[0]}" = "0" ]
+    [ "${lines[1]}" = "1" ]
+    [ "${lines[2]}" = "committed" ]
+    [ "${lines[3]}" = "2" ]
+    [ "${lines[4]}" = "3" ]
+    [ "${lines[5]}" = "4" ]
+
+    # A cursor can be declared WITH HOLD outside a transaction block
+    run -0 psql_exec_stdin -q <<-EOF
+        DECLARE c CURSOR WITH HOLD FOR SELECT 42;
+        FETCH c;
+EOF
+    [ "${output}" = "42" ]
+
+    # It is closed if the transaction is rolled back
+    run psql_exec_stdin <<-EOF
+        BEGIN;
+        DECLARE c CURSOR WITH HOLD FOR SELECT 1;
+        ROLLBACK;
+        FETCH c;
+EOF
+    [ "$status" -ne 0 ]
+    [[ "${output}" == *'cursor "c" does not exist'* ]]
+}
+
+@test "cursor errors" {
+    # Cursors can only scan forward
+    run psql_exec_stdin <<-EOF
+        BEGIN;
+        DECLARE c CURSOR FOR SELECT * FROM range(3);
+        FETCH 2 c;
+        FETCH PRIOR c;
+EOF
+    [ "$status" -ne 0 ]
+    [[ "${output}" == *"cursor can only scan forward"* ]]
+
+    run psql_exec "DECLARE c SCROLL CURSOR FOR SELECT 1"
+    [ "$status" -ne 0 ]
+
+    run psql_exec_stdin <<-EOF
+        BEGIN;
+        DECLARE c CURSOR FOR SELECT 1;
+        DECLARE c CURSOR FOR SELECT 2;
+EOF
+    [ "$status" -ne 0 ]
+    [[ "${output}" == *'cursor "c" already exists'* ]]
+
+    run psql_exec "CLOSE nonexistent"
+    [ "$status" -ne 0 ]
+}
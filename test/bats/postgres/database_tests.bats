@@ -0,0 +1,60 @@
+#!/usr/bin/env bats
+bats_require_minimum_version 1.5.0
+
+load helper
+
+setup_file() {
+    psql_exec_stdin -q <<-EOF
+        DROP DATABASE IF EXISTS test_db_a;
+        DROP DATABASE IF EXISTS test_db_b;
+        CREATE DATABASE test_db_a;
+        CREATE DATABASE test_db_b;
+EOF
+}
+
+teardown_file() {
+    psql_exec_stdin -q <<-EOF
+        DROP DATABASE IF EXISTS test_db_a;
+        DROP DATABASE IF EXISTS test_db_b;
+EOF
+}
+
+@test "the databases are isolated from each other" {
+    run -0 psql_exec_stdin -q -d test_db_a <<-EOF
+        CREATE TABLE t (id INT PRIMARY KEY, name TEXT);
+        INSERT INTO t VALUES (1, 'a');
+EOF
+    run -0 psql_exec_stdin -q -d test_db_b <<-EOF
+        CREATE TABLE t (id INT PRIMARY KEY, name TEXT);
+        INSERT INTO t VALUES (1, 'b'), (2, 'b');
+EOF
+    run -0 psql_exec "SELECT current_database(), count(*) FROM t" -d test_db_a
+    [ "${output}" = "test_db_a,1" ]
+    run -0 psql_exec "SELECT current_database(), count(*) FROM public.t" -d test_db_b
+    [ "${output}" = "test_db_b,2" ]
+}
+
+@test "the databases are listed in pg_database" {
+    run -0 psql_exec "SELECT datname FROM pg_database WHERE datname LIKE 'test_db_%' ORDER BY 1"
+    [ "${lines[0]}" = "test_db_a" ]
+    [ "${lines[1]}" = "test_db_b" ]
+
+    run -0 psql -h "$PG_HOST" -U "$PG_USER" -l
+    [[ "${output}" == *"test_db_a"* ]]
+    [[ "${output}" == *"test_db_b"* ]]
+}
+
+@test "the errors of databases" {
+    run psql_exec "CREATE DATABASE test_db_a"
+    [ "$status" -ne 0 ]
+    [[ "${output}" == *'database "test_db_a" already exists'* ]]
+    run -0 psql_exec "CREATE DATABASE IF NOT EXISTS test_db_a"
+
+    run psql_exec "SELECT 1" -d test_db_missing
+    [ "$status" -ne 0 ]
+    [[ "${output}" == *'database "test_db_missing" does not exist'* ]]
+
+    run psql_exec "DROP DATABASE test_db_a" -d test_db_a
+    [ "$status" -ne 0 ]
+    [[ "${output}" == *'cannot drop the currently open database'* ]]
+}
@@ -0,0 +1,61 @@
+#!/usr/bin/env bats
+bats_require_minimum_version 1.5.0
+
+load helper
+
+setup_file() {
+    psql_exec_stdin <<-EOF
+        CREATE SCHEMA IF NOT EXISTS test_enum;
+        CREATE TYPE test_enum.mood AS ENUM ('sad', 'ok', 'happy');
+        CREATE TABLE test_enum.t (id INTEGER PRIMARY KEY, m test_enum.mood);
+        INSERT INTO test_enum.t VALUES (1, 'happy'), (2, 'sad'), (3, 'ok'), (4, NULL);
+EOF
+}
+
+teardown_file() {
+    psql_exec_stdin <<-EOF
+        DROP SCHEMA IF EXISTS test_enum CASCADE;
+EOF
+}
+
+@test "enum values are ordered by their labels" {
+    run -0 psql_exec "SELECT id, m FROM test_enum.t WHERE m IS NOT NULL ORDER BY m"
+    [ "${lines[0]}" = "2,sad" ]
+    [ "${lines[1]}" = "3,ok" ]
+    [ "${lines[2]}" = "1,happy" ]
+
+    run -0 psql_exec "SELECT id FROM test_enum.t WHERE m > 'sad'::test_enum.mood ORDER BY id"
+    [ "${lines[0]}" = "1" ]
+    [ "${lines[1]}" = "3" ]
+
+    run -0 psql_exec "SELECT id FROM test_enum.t WHERE m = 'ok'"
+    [ "${output}" = "3" ]
+}
+
+@test "pg_type and pg_enum list the enum types" {
+    run -0 psql_exec "SELECT t.typname, t.typtype, n.nspname FROM pg_type t JOIN pg_namespace n ON n.oid = t.typnamespace WHERE t.typname = 'mood'"
+    [ "${output}" = "mood,e,test_enum" ]
+
+    run -0 psql_exec "SELECT e.enumlabel, e.enumsortorder FROM pg_enum e JOIN pg_type t ON t.oid = e.enumtypid WHERE t.typname = 'mood' ORDER BY e.enumsortorder"
+    [ "${lines[0]}" = "sad,1" ]
+    [ "${lines[1]}" = "ok,2" ]
+    [ "${lines[2]}" = "happy,3" ]
+
+    # The built-in types are still there
+    run -0 psql_exec "SELECT typname FROM pg_type WHERE oid = 16"
+    [ "${output}" = "bool" ]
+}
+
+@test "pg_attribute shows the enum types of the columns" {
+    run -0 psql_exec "SELECT a.attname, t.typname FROM pg_attribute a JOIN pg_class c ON c.oid = a.attrelid JOIN pg_type t ON t.oid = a.atttypid WHERE c.relname = 't' AND a.attname = 'm'"
+    [ "${output}" = "m,mood" ]
+}
+
+@test "an enum type is dropped with DROP TYPE" {
+    psql_exec_stdin <<-EOF
+        CREATE TYPE test_enum.tmp AS ENUM ('a');
+        DROP TYPE test_enum.tmp;
+EOF
+    run -0 psql_exec "SELECT count(*) FROM pg_type WHERE typname = 'tmp'"
+    [ "${output}" = "0" ]
+}
@@ -0,0 +1,105 @@
+#!/usr/bin/env bats
+bats_require_minimum_version 1.5.0
+
+load helper
+
+setup_file() {
+    rm -f /tmp/test_fdw.duckdb /tmp/test_fdw.duckdb.wal
+    psql_exec_stdin -q <<-EOF
+        CREATE SCHEMA IF NOT EXISTS test_fdw;
+        CREATE TABLE IF NOT EXISTS test_fdw.orders (id INT PRIMARY KEY, item_id INT);
+        INSERT INTO test_fdw.orders VALUES (1, 10), (2, 20), (3, 10);
+        CREATE SERVER test_remote FOREIGN DATA WRAPPER duckdb_fdw OPTIONS (database '/tmp/test_fdw.duckdb');
+        CREATE TABLE test_remote.main.items (id INT, name TEXT);
+        INSERT INTO test_remote.main.items VALUES (10, 'apple'), (20, 'pear');
+EOF
+}
+
+teardown_file() {
+    psql_exec_stdin -q <<-EOF
+        DROP VIEW IF EXISTS test_credentials;
+        DROP SERVER IF EXISTS test_remote CASCADE;
+        DROP SCHEMA IF EXISTS test_fdw CASCADE;
+        DROP ROLE IF EXISTS test_fdw_user;
+EOF
+    rm -f /tmp/test_fdw.duckdb /tmp/test_fdw.duckdb.wal
+}
+
+@test "a foreign table is joined with a local table" {
+    run -0 psql_exec_stdin -q <<-EOF
+        CREATE FOREIGN TABLE test_fdw.items (id int, label text OPTIONS (column_name 'name')) SERVER test_remote OPTIONS (table_name 'items');
+        SELECT o.id, i.label FROM test_fdw.orders o JOIN test_fdw.items i ON i.id = o.item_id ORDER BY o.id;
+EOF
+    [ "${lines[0]}" = "1,apple" ]
+    [ "${lines[1]}" = "2,pear" ]
+    [ "${lines[2]}" = "3,apple" ]
+
+    # The foreign table is live.
+    run -0 psql_exec_stdin -q <<-EOF
+        INSERT INTO test_remote.main.items VALUES (30, 'plum');
+        SELECT count(*) FROM test_fdw.items;
+EOF
+    [ "${output}" = "3" ]
+
+    run -0 psql_exec "SELECT c.relname, s.srvname FROM pg_foreign_table f JOIN pg_class c ON c.oid = f.ftrelid JOIN pg_foreign_server s ON s.oid = f.ftserver"
+    [ "${output}" = "items,test_remote" ]
+
+    run -0 psql_exec "DROP FOREIGN TABLE test_fdw.items"
+    run psql_exec "SELECT * FROM test_fdw.items"
+    [ "$status" -ne 0 ]
+}
+
+@test "a foreign schema is imported" {
+    run -0 psql_exec_stdin -q <<-EOF
+        CREATE SCHEMA test_fdw_import;
+        IMPORT FOREIGN SCHEMA main FROM SERVER test_remote INTO test_fdw_import;
+        SELECT name FROM test_fdw_import.items WHERE id = 20;
+EOF
+    [ "${output}" = "pear" ]
+
+    run psql_exec "DROP SERVER test_remote"
+    [ "$status" -ne 0 ]
+    [[ "${output}" == *'cannot drop server test_remote because other objects depend on it'* ]]
+
+    run -0 psql_exec "DROP FOREIGN TABLE test_fdw_import.items"
+    run -0 psql_exec "DROP SCHEMA test_fdw_import"
+}
+
+@test "the errors of foreign servers" {
+    run psql_exec "CREATE SERVER test_remote FOREIGN DATA WRAPPER duckdb_fdw OPTIONS (database '/tmp/test_fdw.duckdb')"
+    [ "$status" -ne 0 ]
+    [[ "${output}" == *'server "test_remote" already exists'* ]]
+    run psql_exec "CREATE SERVER test_bad FOREIGN DATA WRAPPER oracle_fdw"
+    [ "$status" -ne 0 ]
+    [[ "${output}" == *'foreign-data wrapper "oracle_fdw" does not exist'* ]]
+    run psql_exec "CREATE SERVER test_bad FOREIGN DATA WRAPPER postgres_fdw OPTIONS (schema 'x')"
+    [ "$status" -ne 0 ]
+    [[ "${output}" == *'invalid option "schema"'* ]]
+    run psql_exec "CREATE FOREIGN TABLE test_fdw.t () SERVER missing"
+    [ "$status" -ne 0 ]
+    [[ "${output}" == *'server "missing" does not exist'* ]]
+}
+
+@test "the credentials of foreign servers may only be read by the superusers" {
+    run -0 psql_exec "CREATE USER test_fdw_user"
+    run -0 psql_exec "CREATE VIEW test_credentials AS SELECT * FROM __sys__.user_mapping"
+    for query in \
+        "SELECT umoptions FROM __sys__.user_mapping" \
+        "SELECT * FROM test_credentials" \
+        "WITH m AS (SELECT * FROM __sys__.user_mapping) SELECT umoptions FROM m" \
+        "SELECT * FROM query('SELECT umoptions FROM __sys__.user_' || 'mapping')" \
+        "SELECT s.srvname, u.umoptions FROM pg_foreign_server s JOIN __sys__.user_mapping u USING (srvname)"; do
+        PG_USER=test_fdw_user run psql_exec "$query"
+        [ "$status" -ne 0 ]
+        [[ "${output}" == *'permission denied for table user_mapping'* ]]
+    done
+    PG_USER=test_fdw_user run psql_exec "UPDATE __sys__.foreign_server SET srvowner = 16384"
+    [ "$status" -ne 0 ]
+    [[ "${output}" == *'permission denied for table foreign_server'* ]]
+    PG_USER=test_fdw_user run psql_exec "DELETE FROM __sys__.user_mapping"
+    [ "$status" -ne 0 ]
+    PG_USER=test_fdw_user run -0 psql_exec "SELECT srvname FROM pg_foreign_server WHERE srvname = 'test_remote'"
+    [ "${output}" = "test_remote" ]
+    run -0 psql_exec "SELECT count(*) FROM __sys__.foreign_server WHERE srvname = 'test_remote'"
+    [ "${output}" = "1" ]
+}
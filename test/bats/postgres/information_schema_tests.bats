@@ -0,0 +1,38 @@
+#!/usr/bin/env bats
+bats_require_minimum_version 1.5.0
+
+load helper
+
+setup_file() {
+    psql_exec_stdin <<-EOF
+        CREATE SCHEMA IF NOT EXISTS test_is;
+        CREATE TABLE test_is.t (id BIGINT PRIMARY KEY, name VARCHAR NOT NULL, price DECIMAL(10, 2), tags VARCHAR[], created TIMESTAMP);
+        CREATE VIEW test_is.v AS SELECT id FROM test_is.t;
+EOF
+}
+
+teardown_file() {
+    psql_exec_stdin <<-EOF
+        DROP SCHEMA IF EXISTS test_is CASCADE;
+EOF
+}
+
+@test "information_schema.tables shows the table types of Postgres" {
+    run -0 psql_exec "SELECT table_name, table_type FROM information_schema.tables WHERE table_schema = 'test_is' ORDER BY 1"
+    [ "${lines[0]}" = "t,BASE TABLE" ]
+    [ "${lines[1]}" = "v,VIEW" ]
+}
+
+@test "information_schema.columns shows the type names of Postgres" {
+    run -0 psql_exec "SELECT column_name, data_type, udt_name, is_nullable, numeric_precision, numeric_scale FROM information_schema.columns WHERE table_schema = 'test_is' AND table_name = 't' ORDER BY ordinal_position"
+    [ "${lines[0]}" = "id,bigint,int8,NO,64,0" ]
+    [ "${lines[1]}" = "name,text,text,NO,," ]
+    [ "${lines[2]}" = "price,numeric,numeric,YES,10,2" ]
+    [ "${lines[3]}" = "tags,ARRAY,_text,YES,," ]
+    [ "${lines[4]}" = "created,timestamp without time zone,timestamp,YES,," ]
+}
+
+@test "information_schema shows the keys of a table" {
+    run -0 psql_exec "SELECT tc.constraint_type, kcu.column_name, kcu.ordinal_position FROM information_schema.table_constraints tc JOIN information_schema.key_column_usage kcu ON kcu.constraint_name = tc.constraint_name WHERE tc.table_schema = 'test_is' AND tc.table_name = 't'"
+    [ "${output}" = "PRIMARY KEY,id,1" ]
+}
@@ -0,0 +1,30 @@
+#!/usr/bin/env bats
+bats_require_minimum_version 1.5.0
+
+load helper
+
+@test "redundant transaction statements are warned about" {
+    run -0 --separate-stderr psql_exec_stdin -q <<-EOF
+        BEGIN;
+        BEGIN;
+        COMMIT;
+        COMMIT;
+        ROLLBACK;
+EOF
+    [[ "${stderr}" == *"WARNING:  there is already a transaction in progress"* ]]
+    [[ "${stderr}" == *"WARNING:  there is no transaction in progress"* ]]
+}
+
+@test "client_min_messages filters the notices" {
+    run -0 --separate-stderr psql_exec_stdin -q <<-EOF
+        SET client_min_messages = warning;
+        COMMIT;
+EOF
+    [[ "${stderr}" == *"there is no transaction in progress"* ]]
+
+    run -0 --separate-stderr psql_exec_stdin -q <<-EOF
+        SET client_min_messages = error;
+        COMMIT;
+EOF
+    [ -z "${stderr}" ]
+}
@@ -0,0 +1,58 @@
+#!/usr/bin/env bats
+bats_require_minimum_version 1.5.0
+
+load helper
+
+setup_file() {
+    psql_exec_stdin <<-EOF
+        CREATE SCHEMA IF NOT EXISTS test_pgc;
+        CREATE TABLE test_pgc.parent (id INTEGER PRIMARY KEY, name VARCHAR NOT NULL, price DECIMAL(10, 2));
+        CREATE TABLE test_pgc.child (id INTEGER, parent_id INTEGER REFERENCES test_pgc.parent (id));
+        CREATE INDEX child_parent_idx ON test_pgc.child (parent_id);
+        CREATE VIEW test_pgc.v AS SELECT id FROM test_pgc.parent;
+        COMMENT ON TABLE test_pgc.parent IS 'the parent';
+EOF
+}
+
+teardown_file() {
+    psql_exec_stdin <<-EOF
+        DROP SCHEMA IF EXISTS test_pgc CASCADE;
+EOF
+}
+
+@test "pg_class and pg_namespace list the relations in DuckDB" {
+    run -0 psql_exec "SELECT c.relname, c.relkind FROM pg_catalog.pg_class c JOIN pg_catalog.pg_namespace n ON n.oid = c.relnamespace WHERE n.nspname = 'test_pgc' AND c.relkind IN ('r', 'v') ORDER BY 1"
+    [ "${lines[0]}" = "child,r" ]
+    [ "${lines[1]}" = "parent,r" ]
+    [ "${lines[2]}" = "v,v" ]
+
+    # The built-in relations are still there
+    run -0 psql_exec "SELECT count(*) FROM pg_catalog.pg_class WHERE relname = 'pg_type'"
+    [ "${output}" = "1" ]
+}
+
+@test "pg_attribute lists the columns of a table" {
+    run -0 psql_exec "SELECT a.attnum, a.attname, a.atttypid, a.attnotnull, a.atttypmod FROM pg_attribute a JOIN pg_class c ON c.oid = a.attrelid WHERE c.relname = 'parent' ORDER BY 1"
+    [ "${lines[0]}" = "1,id,23,t,-1" ]
+    [ "${lines[1]}" = "2,name,25,t,-1" ]
+    [ "${lines[2]}" = "3,price,1700,f,655366" ]
+}
+
+@test "pg_constraint and pg_index describe the keys of a table" {
+    run -0 psql_exec "SELECT c.contype, c.conkey FROM pg_constraint c JOIN pg_class t ON t.oid = c.conrelid WHERE t.relname = 'parent'"
+    [ "${output}" = "p,{1}" ]
+
+    run -0 psql_exec "SELECT ci.relname IS NOT NULL, i.indisprimary, i.indkey FROM pg_index i JOIN pg_class ct ON ct.oid = i.indrelid JOIN pg_class ci ON ci.oid = i.indexrelid WHERE ct.relname = 'parent'"
+    [ "${output}" = "t,t,{1}" ]
+
+    run -0 psql_exec "SELECT i.indisprimary, i.indkey FROM pg_index i JOIN pg_class ci ON ci.oid = i.indexrelid WHERE ci.relname = 'child_parent_idx'"
+    [ "${output}" = "f,{2}" ]
+
+    run -0 psql_exec "SELECT c.contype, r.relname, c.confkey FROM pg_constraint c JOIN pg_class r ON r.oid = c.confrelid WHERE c.contype = 'f' AND c.conrelid = (SELECT oid FROM pg_class WHERE relname = 'child')"
+    [ "${output}" = "f,parent,{1}" ]
+}
+
+@test "pg_description shows the comments of the objects" {
+    run -0 psql_exec "SELECT d.description FROM pg_description d JOIN pg_class c ON c.oid = d.objoid WHERE c.relname = 'parent' AND d.objsubid = 0"
+    [ "${output}" = "the parent" ]
+}
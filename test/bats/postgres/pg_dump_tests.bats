@@ -0,0 +1,60 @@
+#!/usr/bin/env bats
+bats_require_minimum_version 1.5.0
+
+load helper
+
+setup_file() {
+    psql_exec_stdin <<-EOF
+        CREATE SCHEMA IF NOT EXISTS test_dump;
+        CREATE TABLE test_dump.parent (id INTEGER PRIMARY KEY, name VARCHAR NOT NULL DEFAULT 'x', price DECIMAL(10, 2) CHECK (price > 0));
+        CREATE TABLE test_dump.child (id INTEGER, parent_id INTEGER REFERENCES test_dump.parent (id));
+        CREATE INDEX child_parent_idx ON test_dump.child (parent_id);
+        CREATE VIEW test_dump.a_view AS SELECT id, name FROM test_dump.parent;
+        CREATE SEQUENCE test_dump.seq START 10;
+        INSERT INTO test_dump.parent VALUES (1, 'a', 1.5), (2, 'b', NULL);
+        INSERT INTO test_dump.child VALUES (1, 1), (2, NULL);
+        SELECT nextval('test_dump.seq');
+EOF
+}
+
+teardown_file() {
+    psql_exec_stdin <<-EOF
+        DROP SCHEMA IF EXISTS test_dump CASCADE;
+EOF
+}
+
+pg_dump_exec() {
+    pg_dump -h "$PG_HOST" -U "$PG_USER" "$@"
+}
+
+@test "pg_dump dumps the definitions of the objects" {
+    run -0 pg_dump_exec --schema-only --schema=test_dump
+    [[ "${output}" == *"CREATE SCHEMA test_dump;"* ]]
+    [[ "${output}" == *"CREATE TABLE test_dump.parent ("* ]]
+    [[ "${output}" =~ id[[:space:]]integer[[:space:]]NOT[[:space:]]NULL ]]
+    [[ "${output}" =~ name[[:space:]]text[[:space:]]DEFAULT ]]
+    [[ "${output}" =~ price[[:space:]]numeric\(10,2\) ]]
+    [[ "${output}" == *"CHECK ("*"price > 0"* ]]
+    [[ "${output}" == *"PRIMARY KEY (id)"* ]]
+    [[ "${output}" == *"CREATE INDEX child_parent_idx ON test_dump.child USING btree (parent_id);"* ]]
+    [[ "${output}" == *"FOREIGN KEY (parent_id) REFERENCES test_dump.parent(id);"* ]]
+    [[ "${output}" == *"CREATE SEQUENCE test_dump.seq"* ]]
+
+    # The view is created after the table it references
+    [[ "${output}" == *"CREATE TABLE test_dump.parent"*"CREATE VIEW test_dump.a_view AS"* ]]
+}
+
+@test "pg_dump dumps the data of the tables" {
+    run -0 pg_dump_exec --data-only --schema=test_dump
+    [[ "${output}" == *"COPY test_dump.parent (id, name, price) FROM stdin;"* ]]
+    [[ "${output}" == *$'1\ta\t1.50'* ]]
+    [[ "${output}" == *$'2\tb\t\\N'* ]]
+    [[ "${output}" == *"COPY test_dump.child (id, parent_id) FROM stdin;"* ]]
+    [[ "${output}" == *"SELECT pg_catalog.setval('test_dump.seq', 10, true);"* ]]
+}
+
+@test "pg_dump dumps the tables matching a pattern" {
+    run -0 pg_dump_exec --table='test_dump.par*'
+    [[ "${output}" == *"CREATE TABLE test_dump.parent"* ]]
+    [[ "${output}" != *"CREATE TABLE test_dump.child"* ]]
+}
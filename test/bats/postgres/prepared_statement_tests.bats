@@ -0,0 +1,87 @@
+#!/usr/bin/env bats
+bats_require_minimum_version 1.5.0
+
+load helper
+
+setup_file() {
+    psql_exec_stdin -q <<-EOF
+        CREATE SCHEMA IF NOT EXISTS test_prepare;
+        CREATE TABLE IF NOT EXISTS test_prepare.t (id INT PRIMARY KEY, name TEXT);
+EOF
+}
+
+teardown_file() {
+    psql_exec "DROP SCHEMA IF EXISTS test_prepare CASCADE"
+}
+
+@test "a statement is prepared, executed, and deallocated" {
+    run -0 psql_exec_stdin -q <<-EOF
+        PREPARE ins (int, text) AS INSERT INTO test_prepare.t VALUES (\$1, \$2);
+        EXECUTE ins (1, 'one');
+        EXECUTE ins (1 + 1, NULL);
+        PREPARE sel AS SELECT id, coalesce(name, '-') FROM test_prepare.t WHERE id >= \$1 ORDER BY id;
+        EXECUTE sel (1);
+        DEALLOCATE ins;
+        DEALLOCATE PREPARE sel;
+EOF
+    [ "${lines[0]}" = "1,one" ]
+    [ "${lines[1]}" = "2,-" ]
+}
+
+@test "DEALLOCATE ALL and DISCARD ALL close the prepared statements" {
+    run psql_exec_stdin -q <<-EOF
+        PREPARE one AS SELECT 1;
+        PREPARE two AS SELECT 2;
+        DEALLOCATE ALL;
+        EXECUTE one;
+EOF
+    [ "$status" -ne 0 ]
+    [[ "${output}" == *'prepared statement "one" does not exist'* ]]
+
+    run psql_exec_stdin -q <<-EOF
+        PREPARE one AS SELECT 1;
+        DISCARD ALL;
+        PREPARE one AS SELECT 'again';
+        EXECUTE one;
+EOF
+    [ "$status" -eq 0 ]
+    [ "${output}" = "again" ]
+}
+
+@test "DISCARD PLANS, SEQUENCES, and TEMP are accepted" {
+    run -0 psql_exec_stdin -q <<-EOF
+        CREATE TEMP TABLE tt (id int);
+        DISCARD PLANS;
+        DISCARD SEQUENCES;
+        DISCARD TEMP;
+        SELECT count(*) FROM duckdb_tables() WHERE temporary AND table_name = 'tt';
+EOF
+    [ "${output}" = "0" ]
+}
+
+@test "the errors of prepared statements" {
+    run psql_exec_stdin -q <<-EOF
+        PREPARE one AS SELECT 1;
+        PREPARE one AS SELECT 2;
+EOF
+    [ "$status" -ne 0 ]
+    [[ "${output}" == *'prepared statement "one" already exists'* ]]
+    run psql_exec "EXECUTE missing"
+    [ "$status" -ne 0 ]
+    [[ "${output}" == *'prepared statement "missing" does not exist'* ]]
+    run psql_exec "DEALLOCATE missing"
+    [ "$status" -ne 0 ]
+    [[ "${output}" == *'prepared statement "missing" does not exist'* ]]
+    run psql_exec_stdin -q <<-EOF
+        PREPARE one AS SELECT \$1::INT;
+        EXECUTE one (1, 2);
+EOF
+    [ "$status" -ne 0 ]
+    [[ "${output}" == *'wrong number of parameters for prepared statement "one"'* ]]
+    run psql_exec_stdin -q <<-EOF
+        BEGIN;
+        DISCARD ALL;
+EOF
+    [ "$status" -ne 0 ]
+    [[ "${output}" == *'DISCARD ALL cannot run inside a transaction block'* ]]
+}
@@ -0,0 +1,81 @@
+#!/usr/bin/env bats
+bats_require_minimum_version 1.5.0
+
+load helper
+
+setup_file() {
+    psql_exec_stdin <<-EOF
+        CREATE SCHEMA IF NOT EXISTS test_psql;
+        CREATE TABLE test_psql.parent (id INTEGER PRIMARY KEY, name VARCHAR NOT NULL DEFAULT 'x', price DECIMAL(10, 2) CHECK (price > 0));
+        CREATE TABLE test_psql.child (id INTEGER, parent_id INTEGER REFERENCES test_psql.parent (id));
+        CREATE INDEX child_parent_idx ON test_psql.child (parent_id);
+        CREATE VIEW test_psql.v AS SELECT id FROM test_psql.parent;
+EOF
+}
+
+teardown_file() {
+    psql_exec_stdin <<-EOF
+        DROP SCHEMA IF EXISTS test_psql CASCADE;
+EOF
+}
+
+# psql -c runs the backslash commands, and prints their footers, which psql_exec turns off
+psql_command() {
+    psql -h "$PG_HOST" -U "$PG_USER" -v "ON_ERROR_STOP=1" -c "$1"
+}
+
+@test "psql \\dt, \\dv and \\di list the relations" {
+    run -0 psql_exec '\dt test_psql.*'
+    [ "${lines[0]}" = "test_psql,child,table,postgres" ]
+    [ "${lines[1]}" = "test_psql,parent,table,postgres" ]
+
+    run -0 psql_exec '\dv test_psql.*'
+    [ "${output}" = "test_psql,v,view,postgres" ]
+
+    run -0 psql_exec '\di test_psql.child*'
+    [ "${output}" = "test_psql,child_parent_idx,index,postgres,child" ]
+}
+
+@test "psql \\d lists the relations in the search path" {
+    run -0 psql_exec_stdin <<-EOF
+        SET search_path TO test_psql;
+        \d
+EOF
+    [ "${lines[0]}" = "test_psql,child,table,postgres" ]
+    [ "${lines[1]}" = "test_psql,parent,table,postgres" ]
+    [ "${lines[2]}" = "test_psql,v,view,postgres" ]
+}
+
+@test "psql \\dn lists the schemas" {
+    run -0 psql_exec '\dn'
+    [[ "${output}" == *"test_psql,postgres"* ]]
+    [[ "${output}" != *"pg_catalog"* ]]
+}
+
+@test "psql \\du lists the roles" {
+    run -0 psql_exec '\du'
+    [[ "${output}" == *"postgres,Superuser"* ]]
+}
+
+@test "psql \\df lists the functions" {
+    run -0 psql_exec_stdin <<-EOF
+        CREATE MACRO test_psql.add_one(x) AS x + 1;
+        \df test_psql.*
+EOF
+    [ "${output}" = "test_psql,add_one,,x,func" ]
+}
+
+@test "psql \\d describes a table" {
+    run -0 psql_command '\d test_psql.parent'
+    [[ "${output}" == *'Table "test_psql.parent"'* ]]
+    [[ "${output}" =~ id[[:space:]]+\|[[:space:]]integer[[:space:]]+\|[[:space:]]+\|[[:space:]]not\ null ]]
+    [[ "${output}" =~ price[[:space:]]+\|[[:space:]]numeric\(10,2\) ]]
+    [[ "${output}" == *'PRIMARY KEY, btree (id)'* ]]
+    [[ "${output}" == *'CHECK ((price > 0))'* || "${output}" == *'CHECK (price > 0)'* ]]
+    [[ "${output}" == *'Referenced by:'* ]]
+    [[ "${output}" == *'TABLE "child" CONSTRAINT'* ]]
+
+    run -0 psql_command '\d test_psql.child'
+    [[ "${output}" == *'"child_parent_idx" btree (parent_id)'* ]]
+    [[ "${output}" == *'FOREIGN KEY (parent_id) REFERENCES parent(id)'* ]]
+}
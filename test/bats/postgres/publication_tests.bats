@@ -0,0 +1,126 @@
+#!/usr/bin/env bats
+bats_require_minimum_version 1.5.0
+
+load helper
+
+# psql_replication runs the replication command in a replication connection.
+psql_replication() {
+    psql_exec "$1" -d "dbname=postgres replication=database"
+}
+
+setup_file() {
+    psql_exec_stdin -q <<-EOF
+        CREATE SCHEMA IF NOT EXISTS test_pub;
+        CREATE TABLE IF NOT EXISTS test_pub.orders (id INT PRIMARY KEY, note TEXT);
+        CREATE TABLE IF NOT EXISTS test_pub.items (id INT PRIMARY KEY, name TEXT);
+EOF
+}
+
+teardown() {
+    psql_exec_stdin -q <<-EOF
+        DROP PUBLICATION IF EXISTS test_orders, test_all;
+EOF
+    psql_replication "DROP_REPLICATION_SLOT test_slot" >/dev/null 2>&1 || true
+}
+
+teardown_file() {
+    psql_exec "DROP SCHEMA IF EXISTS test_pub CASCADE"
+}
+
+@test "a publication is created, altered, and dropped" {
+    run -0 psql_exec_stdin -q <<-EOF
+        CREATE PUBLICATION test_orders FOR TABLE test_pub.orders WITH (publish = 'insert, update');
+        SELECT pubname, puballtables, pubinsert, pubupdate, pubdelete FROM pg_publication WHERE pubname = 'test_orders';
+        SELECT schemaname, tablename FROM pg_publication_tables WHERE pubname = 'test_orders';
+        ALTER PUBLICATION test_orders ADD TABLE test_pub.items;
+        ALTER PUBLICATION test_orders SET (publish = 'delete');
+        SELECT pubinsert, pubdelete FROM pg_publication WHERE pubname = 'test_orders';
+        SELECT tablename FROM pg_publication_tables WHERE pubname = 'test_orders' ORDER BY 1;
+        ALTER PUBLICATION test_orders DROP TABLE test_pub.orders;
+        SELECT tablename FROM pg_publication_tables WHERE pubname = 'test_orders';
+        DROP PUBLICATION test_orders;
+        SELECT count(*) FROM pg_publication WHERE pubname = 'test_orders';
+EOF
+    [ "${lines[0]}" = "test_orders,f,t,t,f" ]
+    [ "${lines[1]}" = "test_pub,orders" ]
+    [ "${lines[2]}" = "f,t" ]
+    [ "${lines[3]}" = "items" ]
+    [ "${lines[4]}" = "orders" ]
+    [ "${lines[5]}" = "items" ]
+    [ "${lines[6]}" = "0" ]
+}
+
+@test "a publication for all tables lists the user tables" {
+    run -0 psql_exec_stdin -q <<-EOF
+        CREATE PUBLICATION test_all FOR ALL TABLES;
+        SELECT tablename FROM pg_publication_tables WHERE pubname = 'test_all' AND schemaname = 'test_pub' ORDER BY 1;
+EOF
+    [ "${lines[0]}" = "items" ]
+    [ "${lines[1]}" = "orders" ]
+}
+
+@test "the errors of publication statements" {
+    run -0 psql_exec "CREATE PUBLICATION test_orders FOR TABLE test_pub.orders"
+    run psql_exec "CREATE PUBLICATION test_orders"
+    [ "$status" -ne 0 ]
+    [[ "${output}" == *'publication "test_orders" already exists'* ]]
+    run psql_exec "CREATE PUBLICATION test_all FOR TABLE test_pub.missing"
+    [ "$status" -ne 0 ]
+    [[ "${output}" == *'does not exist'* ]]
+    run psql_exec "DROP PUBLICATION test_missing"
+    [ "$status" -ne 0 ]
+    [[ "${output}" == *'publication "test_missing" does not exist'* ]]
+    run psql_exec "CREATE PUBLICATION test_all FOR ALL TABLES WITH (publish = 'insert, truncate')"
+    [ "$status" -ne 0 ]
+    [[ "${output}" == *'publishing TRUNCATE is not supported'* ]]
+    run -0 --separate-stderr psql_exec "DROP PUBLICATION IF EXISTS test_missing"
+    [[ "${stderr}" == *'publication "test_missing" does not exist, skipping'* ]]
+}
+
+@test "a replication connection identifies the system and manages slots" {
+    run -0 psql_replication "IDENTIFY_SYSTEM"
+    [[ "${output}" =~ ^[0-9]+,1,[0-9A-F]+/[0-9A-F]+, ]]
+
+    run -0 psql_replication "CREATE_REPLICATION_SLOT test_slot LOGICAL pgoutput"
+    [[ "${output}" =~ ^test_slot,[0-9A-F]+/[0-9A-F]+,,pgoutput$ ]]
+    run -0 psql_exec "SELECT slot_name, plugin, slot_type, active FROM pg_replication_slots WHERE slot_name = 'test_slot'"
+    [ "${output}" = "test_slot,pgoutput,logical,f" ]
+
+    run psql_replication "CREATE_REPLICATION_SLOT test_slot LOGICAL pgoutput"
+    [ "$status" -ne 0 ]
+    [[ "${output}" == *'replication slot "test_slot" already exists'* ]]
+    run psql_replication "CREATE_REPLICATION_SLOT test_physical PHYSICAL"
+    [ "$status" -ne 0 ]
+
+    run -0 psql_replication "DROP_REPLICATION_SLOT test_slot"
+    run -0 psql_exec "SELECT count(*) FROM pg_replication_slots WHERE slot_name = 'test_slot'"
+    [ "${output}" = "0" ]
+    run psql_replication "DROP_REPLICATION_SLOT test_slot"
+    [ "$status" -ne 0 ]
+    [[ "${output}" == *'replication slot "test_slot" does not exist'* ]]
+}
+
+@test "the published tables may only be written by the replication while there are replication slots" {
+    run -0 psql_exec "CREATE PUBLICATION test_orders FOR TABLE test_pub.orders"
+    run -0 psql_exec "INSERT INTO test_pub.orders VALUES (1, 'before the slot')"
+    run -0 psql_replication "CREATE_REPLICATION_SLOT test_slot LOGICAL pgoutput"
+    for query in \
+        "INSERT INTO test_pub.orders VALUES (2, 'client')" \
+        "UPDATE test_pub.orders SET note = 'client' WHERE id = 1" \
+        "DELETE FROM test_pub.orders o WHERE o.id = 1"; do
+        run psql_exec "$query"
+        [ "$status" -ne 0 ]
+        [[ "${output}" == *'cannot modify table "test_pub.orders" because it is published'* ]]
+    done
+    run -0 psql_exec "INSERT INTO test_pub.items VALUES (1, 'unpublished')"
+    run -0 psql_replication "DROP_REPLICATION_SLOT test_slot"
+    run -0 psql_exec "DELETE FROM test_pub.orders"
+}
+
+@test "the replication commands are only accepted by replication connections" {
+    run psql_exec "IDENTIFY_SYSTEM"
+    [ "$status" -ne 0 ]
+    run psql_exec "SELECT 1" -d "dbname=postgres replication=true"
+    [ "$status" -ne 0 ]
+    [[ "${output}" == *'physical replication is not supported'* ]]
+}
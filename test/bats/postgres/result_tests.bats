@@ -0,0 +1,15 @@
+#!/usr/bin/env bats
+bats_require_minimum_version 1.5.0
+
+load helper
+
+@test "a large result is streamed as one result set" {
+    run -0 psql_exec "SELECT i, repeat('x', 100) FROM range(100000) t(i)"
+    [ "${#lines[@]}" -eq 100000 ]
+    [ "${lines[0]}" = "0,$(printf 'x%.0s' {1..100})" ]
+    [[ "${lines[99999]}" == "99999,"* ]]
+
+    # psql prints the row count of each result set in its footer
+    run -0 psql -h "$PG_HOST" -U "$PG_USER" -c "SELECT i FROM range(1000) t(i)"
+    [ "$(grep -c '^(1000 rows)$' <<< "${output}")" -eq 1 ]
+}
@@ -0,0 +1,170 @@
+#!/usr/bin/env bats
+bats_require_minimum_version 1.5.0
+
+load helper
+
+# psql_exec_as runs the query as the given role.
+psql_exec_as() {
+    local user="$1"
+    shift
+    PG_USER="$user" psql_exec "$@" -d postgres
+}
+
+teardown() {
+    psql_exec_stdin -q <<-EOF
+        GRANT CONNECT ON DATABASE postgres TO PUBLIC;
+        DROP VIEW IF EXISTS test_passwords;
+        DROP ROLE IF EXISTS test_member;
+        DROP ROLE IF EXISTS test_user;
+        DROP ROLE IF EXISTS test_group;
+        DROP ROLE IF EXISTS test_renamed;
+EOF
+}
+
+@test "a created role is listed in pg_roles" {
+    run -0 psql_exec_stdin -q <<-EOF
+        CREATE ROLE test_group;
+        CREATE USER test_user WITH CREATEDB CONNECTION LIMIT 3 PASSWORD 'secret';
+        SELECT rolname, rolsuper, rolcreatedb, rolcanlogin, rolconnlimit, rolpassword FROM pg_roles WHERE rolname LIKE 'test_%' ORDER BY 1;
+        SELECT usename FROM pg_user WHERE usename LIKE 'test_%';
+        SELECT rolpassword IS NULL FROM pg_authid WHERE rolname = 'test_user';
+        SELECT starts_with(p.rolpassword, 'SCRAM-SHA-256') FROM __sys__.role_password p JOIN pg_roles r USING (oid) WHERE r.rolname = 'test_user';
+EOF
+    [ "${lines[0]}" = "test_group,f,f,f,-1,********" ]
+    [ "${lines[1]}" = "test_user,f,t,t,3,********" ]
+    [ "${lines[2]}" = "test_user" ]
+    [ "${lines[3]}" = "t" ]
+    [ "${lines[4]}" = "t" ]
+}
+
+@test "a role is altered, renamed, and dropped" {
+    run -0 psql_exec_stdin -q <<-EOF
+        CREATE ROLE test_user;
+        ALTER ROLE test_user LOGIN CREATEROLE;
+        ALTER ROLE test_user RENAME TO test_renamed;
+        SELECT rolname, rolcanlogin, rolcreaterole FROM pg_roles WHERE rolname LIKE 'test_%';
+        DROP ROLE test_renamed;
+        SELECT count(*) FROM pg_roles WHERE rolname LIKE 'test_%';
+EOF
+    [ "${lines[0]}" = "test_renamed,t,t" ]
+    [ "${lines[1]}" = "0" ]
+}
+
+@test "the errors of role statements" {
+    run -0 psql_exec "CREATE ROLE test_user"
+    run psql_exec "CREATE ROLE test_user"
+    [ "$status" -ne 0 ]
+    [[ "${output}" == *'role "test_user" already exists'* ]]
+    run psql_exec "DROP ROLE test_missing"
+    [ "$status" -ne 0 ]
+    [[ "${output}" == *'role "test_missing" does not exist'* ]]
+    run psql_exec "CREATE ROLE pg_test"
+    [ "$status" -ne 0 ]
+    [[ "${output}" == *'role name "pg_test" is reserved'* ]]
+    run psql_exec "DROP ROLE postgres"
+    [ "$status" -ne 0 ]
+    run -0 --separate-stderr psql_exec "DROP ROLE IF EXISTS test_missing"
+    [[ "${stderr}" == *'role "test_missing" does not exist, skipping'* ]]
+}
+
+@test "the memberships of roles are granted and revoked" {
+    run -0 psql_exec_stdin -q <<-EOF
+        CREATE ROLE test_group;
+        CREATE ROLE test_user IN ROLE test_group;
+        CREATE ROLE test_member;
+        GRANT test_user TO test_member WITH ADMIN OPTION;
+        SELECT r.rolname, m.rolname, a.admin_option FROM pg_auth_members a
+            JOIN pg_roles r ON a.roleid = r.oid JOIN pg_roles m ON a.member = m.oid ORDER BY 1;
+        REVOKE test_group FROM test_user;
+        SELECT count(*) FROM pg_auth_members a JOIN pg_roles r ON a.roleid = r.oid WHERE r.rolname = 'test_group';
+EOF
+    [ "${lines[0]}" = "test_group,test_user,f" ]
+    [ "${lines[1]}" = "test_user,test_member,t" ]
+    [ "${lines[2]}" = "0" ]
+
+    run psql_exec "GRANT test_member TO test_user"
+    [ "$status" -ne 0 ]
+    [[ "${output}" == *'role "test_member" is a member of role "test_user"'* ]]
+}
+
+@test "a role without LOGIN may not log in" {
+    run -0 psql_exec "CREATE ROLE test_user"
+    run psql_exec_as test_user "SELECT 1"
+    [ "$status" -ne 0 ]
+    [[ "${output}" == *'role "test_user" is not permitted to log in'* ]]
+
+    run -0 psql_exec "ALTER ROLE test_user LOGIN"
+    run -0 psql_exec_as test_user "SELECT 1"
+    [ "${output}" = "1" ]
+}
+
+@test "a role that is not a superuser may not manage roles" {
+    run -0 psql_exec "CREATE USER test_user"
+    run psql_exec_as test_user "CREATE ROLE test_group"
+    [ "$status" -ne 0 ]
+    [[ "${output}" == *'permission denied to create role'* ]]
+
+    run -0 psql_exec "ALTER ROLE test_user CREATEROLE"
+    run -0 psql_exec_as test_user "CREATE ROLE test_group"
+    run psql_exec_as test_user "CREATE ROLE test_member SUPERUSER"
+    [ "$status" -ne 0 ]
+}
+
+@test "a role that is not a superuser may not access the tables of the roles" {
+    run -0 psql_exec "CREATE USER test_user"
+    run psql_exec_as test_user "SELECT rolpassword FROM __sys__.role_password"
+    [ "$status" -ne 0 ]
+    [[ "${output}" == *'permission denied for table role_password'* ]]
+    run psql_exec_as test_user "UPDATE __sys__.pg_authid SET rolsuper = true WHERE rolname = 'test_user'"
+    [ "$status" -ne 0 ]
+    [[ "${output}" == *'permission denied for table pg_authid'* ]]
+    run psql_exec_as test_user "INSERT INTO pg_catalog.pg_auth_members VALUES (10, 16384, 10, true)"
+    [ "$status" -ne 0 ]
+    run psql_exec_as test_user "DELETE FROM __sys__.database_privilege"
+    [ "$status" -ne 0 ]
+
+    run -0 psql_exec "SELECT rolsuper FROM pg_roles WHERE rolname = 'test_user'"
+    [ "${output}" = "f" ]
+    run -0 psql_exec_as test_user "SELECT rolname FROM pg_roles WHERE rolname = 'test_user'"
+    [ "${output}" = "test_user" ]
+}
+
+@test "a role that is not a superuser may not read the passwords through views, CTEs, or table functions" {
+    run -0 psql_exec_stdin -q <<-EOF
+        CREATE USER test_user;
+        CREATE ROLE test_member PASSWORD 'secret';
+        CREATE VIEW test_passwords AS SELECT * FROM __sys__.role_password;
+EOF
+    run -0 psql_exec "SELECT count(*) > 0 FROM test_passwords"
+    [ "${output}" = "t" ]
+
+    run psql_exec_as test_user "SELECT * FROM test_passwords"
+    [ "$status" -ne 0 ]
+    [[ "${output}" == *'permission denied for table role_password'* ]]
+    run psql_exec_as test_user "WITH p AS (SELECT * FROM __sys__.role_password) SELECT count(*) FROM p"
+    [ "$status" -ne 0 ]
+    [[ "${output}" == *'permission denied for table role_password'* ]]
+    run psql_exec_as test_user "SELECT * FROM query('SELECT rolpassword FROM __sys__.role_' || 'password')"
+    [ "$status" -ne 0 ]
+    [[ "${output}" == *'permission denied for table role_password'* ]]
+    run psql_exec_as test_user "CREATE TABLE test_copy AS SELECT * FROM query('SELECT * FROM __sys__.role_' || 'password')"
+    [ "$status" -ne 0 ]
+    [[ "${output}" == *'permission denied for table role_password'* ]]
+}
+
+@test "the CONNECT privilege on a database is checked at login" {
+    run -0 psql_exec_stdin -q <<-EOF
+        CREATE USER test_user;
+        REVOKE CONNECT ON DATABASE postgres FROM PUBLIC;
+EOF
+    run psql_exec_as test_user "SELECT 1"
+    [ "$status" -ne 0 ]
+    [[ "${output}" == *'permission denied for database'* ]]
+
+    run -0 psql_exec "GRANT CONNECT ON DATABASE postgres TO test_user"
+    run -0 psql_exec_as test_user "SELECT 1"
+    run psql_exec "DROP ROLE test_user"
+    [ "$status" -ne 0 ]
+    [[ "${output}" == *'cannot be dropped because some objects depend on it'* ]]
+    run -0 psql_exec "REVOKE CONNECT ON DATABASE postgres FROM test_user"
+}
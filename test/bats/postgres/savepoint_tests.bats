@@ -0,0 +1,68 @@
+#!/usr/bin/env bats
+bats_require_minimum_version 1.5.0
+
+load helper
+
+setup_file() {
+    psql_exec_stdin <<-EOF
+        CREATE SCHEMA IF NOT EXISTS test_savepoint;
+        CREATE TABLE test_savepoint.t (id INTEGER PRIMARY KEY);
+EOF
+}
+
+teardown_file() {
+    psql_exec_stdin <<-EOF
+        DROP SCHEMA IF EXISTS test_savepoint CASCADE;
+EOF
+}
+
+@test "ROLLBACK TO SAVEPOINT undoes the statements after the savepoint" {
+    run -0 psql_exec_stdin -q <<-EOF
+        DELETE FROM test_savepoint.t;
+        BEGIN;
+        INSERT INTO test_savepoint.t VALUES (1);
+        SAVEPOINT s1;
+        INSERT INTO test_savepoint.t VALUES (2);
+        SAVEPOINT s2;
+        INSERT INTO test_savepoint.t VALUES (3);
+        ROLLBACK TO SAVEPOINT s2;
+        SELECT count(*) FROM test_savepoint.t;
+        RELEASE SAVEPOINT s2;
+        ROLLBACK TO s1;
+        SELECT count(*) FROM test_savepoint.t;
+        INSERT INTO test_savepoint.t VALUES (4);
+        COMMIT;
+        SELECT id FROM test_savepoint.t ORDER BY id;
+EOF
+    [ "${lines[0]}" = "2" ]
+    [ "${lines[1]}" = "1" ]
+    [ "${lines[2]}" = "1" ]
+    [ "${lines[3]}" = "4" ]
+}
+
+@test "ROLLBACK TO SAVEPOINT recovers from a failed statement" {
+    run -0 --separate-stderr psql_exec_stdin -q -v ON_ERROR_STOP=0 <<-EOF
+        DELETE FROM test_savepoint.t;
+        BEGIN;
+        INSERT INTO test_savepoint.t VALUES (1);
+        SAVEPOINT s;
+        INSERT INTO test_savepoint.t VALUES (1);
+        ROLLBACK TO SAVEPOINT s;
+        INSERT INTO test_savepoint.t VALUES (2);
+        COMMIT;
+        SELECT id FROM test_savepoint.t ORDER BY id;
+EOF
+    [ "${lines[0]}" = "1" ]
+    [ "${lines[1]}" = "2" ]
+}
+
+@test "savepoints can only be used in transaction blocks" {
+    run psql_exec "SAVEPOINT s"
+    [ "$status" -ne 0 ]
+
+    run psql_exec_stdin <<-EOF
+        BEGIN;
+        ROLLBACK TO SAVEPOINT missing;
+EOF
+    [ "$status" -ne 0 ]
+}
@@ -0,0 +1,64 @@
+#!/usr/bin/env bats
+bats_require_minimum_version 1.5.0
+
+load helper
+
+setup_file() {
+    psql_exec_stdin <<-EOF
+        CREATE SCHEMA IF NOT EXISTS test_sp_a;
+        CREATE SCHEMA IF NOT EXISTS test_sp_b;
+        CREATE TABLE test_sp_a.t (v VARCHAR);
+        INSERT INTO test_sp_a.t VALUES ('a');
+        CREATE TABLE test_sp_b.t (v VARCHAR);
+        INSERT INTO test_sp_b.t VALUES ('b');
+        CREATE TABLE test_sp_b.only_b (v VARCHAR);
+        INSERT INTO test_sp_b.only_b VALUES ('only b');
+EOF
+}
+
+teardown_file() {
+    psql_exec_stdin <<-EOF
+        DROP SCHEMA IF EXISTS test_sp_a CASCADE;
+        DROP SCHEMA IF EXISTS test_sp_b CASCADE;
+EOF
+}
+
+@test "unqualified names are resolved in the search path in order" {
+    run -0 psql_exec_stdin -q <<-EOF
+        SET search_path TO test_sp_a, test_sp_b;
+        SHOW search_path;
+        SELECT current_schema();
+        SELECT v FROM t;
+        SELECT v FROM only_b;
+EOF
+    [ "${lines[0]}" = "test_sp_a, test_sp_b" ]
+    [ "${lines[1]}" = "test_sp_a" ]
+    [ "${lines[2]}" = "a" ]
+    [ "${lines[3]}" = "only b" ]
+}
+
+@test "the schemas that do not exist are skipped" {
+    run -0 psql_exec_stdin -q <<-EOF
+        SET search_path = nonexistent, "test_sp_b";
+        SELECT v FROM t;
+        CREATE TABLE created_here (i INTEGER);
+EOF
+    [ "${output}" = "b" ]
+
+    run -0 psql_exec "SELECT count(*) FROM test_sp_b.created_here"
+    [ "${output}" = "0" ]
+}
+
+@test "the search path is set by the startup options" {
+    run -0 env PGOPTIONS="-c search_path=test_sp_b,test_sp_a" psql_exec "SELECT v FROM t"
+    [ "${output}" = "b" ]
+}
+
+@test "the search path is reset to the default" {
+    run psql_exec_stdin <<-EOF
+        SET search_path TO test_sp_b;
+        RESET search_path;
+        SELECT v FROM only_b;
+EOF
+    [ "$status" -ne 0 ]
+}
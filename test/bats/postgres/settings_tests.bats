@@ -0,0 +1,54 @@
+#!/usr/bin/env bats
+bats_require_minimum_version 1.5.0
+
+load helper
+
+@test "pg_settings reflects the session settings" {
+    run -0 psql_exec_stdin -q <<-EOF
+        SET statement_timeout = '1min';
+        SELECT setting, unit, source FROM pg_settings WHERE name = 'statement_timeout';
+        SELECT count(*) > 100 FROM pg_catalog.pg_settings;
+EOF
+    [ "${lines[0]}" = "60000,ms,session" ]
+    [ "${lines[1]}" = "t" ]
+}
+
+@test "unknown settings are ignored with a warning" {
+    run -0 psql_exec_stdin -q <<-EOF
+        SET no_such_parameter = 'x';
+        SELECT 1;
+EOF
+    [[ "${output}" == *'unrecognized configuration parameter "no_such_parameter" is ignored'* ]]
+    [[ "${output}" == *"1"* ]]
+}
+
+@test "statement_timeout cancels a long running statement" {
+    run psql_exec_stdin <<-EOF
+        SET statement_timeout = 100;
+        SELECT sum(i) FROM range(1000000000000) t(i);
+EOF
+    [ "$status" -ne 0 ]
+    [[ "${output}" == *"canceling statement due to statement timeout"* ]]
+}
+
+@test "statement_timeout cancels a long running COPY TO STDOUT" {
+    run psql_exec_stdin <<-EOF
+        SET statement_timeout = 100;
+        COPY (SELECT sum(i) FROM range(1000000000000) t(i)) TO STDOUT;
+EOF
+    [ "$status" -ne 0 ]
+    [[ "${output}" == *"canceling statement due to statement timeout"* ]]
+}
+
+@test "set_config sets a parameter of the session" {
+    run -0 psql_exec_stdin -q <<-EOF
+        SELECT pg_catalog.set_config('statement_timeout', '2min', false);
+        SHOW statement_timeout;
+        SELECT set_config('myapp.user_id', '42', false);
+        SELECT current_setting('myapp.user_id');
+EOF
+    [ "${lines[0]}" = "2min" ]
+    [ "${lines[1]}" = "2min" ]
+    [ "${lines[2]}" = "42" ]
+    [ "${lines[3]}" = "42" ]
+}
@@ -0,0 +1,20 @@
+#!/usr/bin/env bats
+bats_require_minimum_version 1.5.0
+
+load helper
+
+@test "pg_stat_activity shows the current connection" {
+    run -0 psql_exec "SELECT usename, state, backend_type, query FROM pg_stat_activity WHERE query LIKE '%FROM pg_stat_activity%'"
+    [ "${output}" = "${PG_USER},active,client backend,SELECT usename, state, backend_type, query FROM pg_stat_activity WHERE query LIKE '%FROM pg_stat_activity%'" ]
+}
+
+@test "pg_stat_activity shows the transaction of the connection" {
+    run -0 psql_exec_stdin -q <<-EOF
+        SELECT count(*) FROM pg_catalog.pg_stat_activity WHERE xact_start IS NOT NULL AND application_name = 'psql';
+        BEGIN;
+        SELECT count(*) FROM pg_catalog.pg_stat_activity WHERE xact_start IS NOT NULL AND application_name = 'psql';
+        COMMIT;
+EOF
+    [ "${lines[0]}" = "0" ]
+    [ "${lines[1]}" = "1" ]
+}
@@ -0,0 +1,31 @@
+#!/usr/bin/env bats
+bats_require_minimum_version 1.5.0
+
+load helper
+
+@test "the replication views are empty without replication" {
+    run -0 psql_exec_stdin -q <<-EOF
+        SELECT count(*) FROM pg_stat_replication;
+        SELECT count(*) FROM pg_catalog.pg_stat_wal_receiver;
+        SELECT count(*) FROM pg_stat_subscription;
+EOF
+    [ "${lines[0]}" = "0" ]
+    [ "${lines[1]}" = "0" ]
+    [ "${lines[2]}" = "0" ]
+}
+
+@test "the WAL positions of a primary" {
+    run -0 psql_exec "SELECT pg_catalog.pg_is_in_recovery()"
+    [ "${output}" = "f" ]
+    run -0 psql_exec "SELECT pg_catalog.pg_current_wal_lsn()"
+    [[ "${output}" =~ ^[0-9A-F]+/[0-9A-F]+$ ]]
+    run -0 psql_exec "SELECT pg_catalog.pg_last_wal_replay_lsn()"
+    [ "${output}" = "" ]
+    run -0 psql_exec "SELECT pg_last_xact_replay_timestamp()"
+    [ "${output}" = "" ]
+}
+
+@test "pg_wal_lsn_diff returns the bytes between two LSNs" {
+    run -0 psql_exec "SELECT pg_wal_lsn_diff('0/10', '0/4'), pg_wal_lsn_diff('1/0', '0/FFFFFFFF'), pg_wal_lsn_diff('0/4', '0/10')"
+    [ "${output}" = "12,1,-12" ]
+}
@@ -0,0 +1,39 @@
+#!/usr/bin/env bats
+bats_require_minimum_version 1.5.0
+
+load helper
+
+setup_file() {
+    psql_exec_stdin -q <<-EOF
+        CREATE SCHEMA IF NOT EXISTS test_pss;
+        CREATE TABLE IF NOT EXISTS test_pss.t (id INT PRIMARY KEY, name TEXT);
+EOF
+}
+
+teardown_file() {
+    psql_exec "DROP SCHEMA IF EXISTS test_pss CASCADE"
+}
+
+@test "pg_stat_statements groups the statements by their normalized texts" {
+    run -0 psql_exec "SELECT pg_stat_statements_reset()"
+    run -0 psql_exec_stdin -q <<-EOF
+        INSERT INTO test_pss.t VALUES (1, 'one');
+        INSERT INTO test_pss.t VALUES (2, 'two');
+        SELECT name FROM test_pss.t WHERE id = 1;
+        SELECT name FROM test_pss.t WHERE id = 2;
+        SELECT name FROM test_pss.t WHERE id > 0;
+EOF
+    run -0 psql_exec "SELECT query, calls, rows, total_exec_time >= max_exec_time, max_exec_time >= min_exec_time FROM pg_stat_statements WHERE query LIKE '%test_pss.t%' AND query NOT LIKE '%pg_stat_statements%' ORDER BY query"
+    [ "${lines[0]}" = 'INSERT INTO test_pss.t VALUES ($1, $2),2,2,t,t' ]
+    [ "${lines[1]}" = 'SELECT name FROM test_pss.t WHERE id = $1,2,2,t,t' ]
+    [ "${lines[2]}" = 'SELECT name FROM test_pss.t WHERE id > $1,1,2,t,t' ]
+}
+
+@test "pg_stat_statements_reset discards the statistics" {
+    run -0 psql_exec "SELECT 42"
+    run -0 psql_exec "SELECT count(*) > 0 FROM pg_stat_statements WHERE query = 'SELECT \$1'"
+    [ "${output}" = "t" ]
+    run -0 psql_exec "SELECT pg_stat_statements_reset()"
+    run -0 psql_exec "SELECT count(*) FROM pg_stat_statements WHERE query = 'SELECT \$1'"
+    [ "${output}" = "0" ]
+}
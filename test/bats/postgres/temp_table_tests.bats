@@ -0,0 +1,45 @@
+#!/usr/bin/env bats
+bats_require_minimum_version 1.5.0
+
+load helper
+
+@test "a temporary table is visible only to its session" {
+    run -0 psql_exec_stdin -q <<-EOF
+        CREATE TEMP TABLE test_tmp (id INTEGER);
+        INSERT INTO test_tmp VALUES (1), (2);
+        SELECT count(*) FROM test_tmp;
+        SELECT count(*) FROM pg_temp.test_tmp;
+EOF
+    [ "${lines[0]}" = "2" ]
+    [ "${lines[1]}" = "2" ]
+
+    # The table is dropped at the end of the session
+    run psql_exec "SELECT count(*) FROM test_tmp"
+    [ "$status" -ne 0 ]
+}
+
+@test "ON COMMIT DROP drops a temporary table at the end of the transaction" {
+    run -0 psql_exec_stdin -q <<-EOF
+        BEGIN;
+        CREATE TEMP TABLE test_tmp_drop (id INTEGER) ON COMMIT DROP;
+        INSERT INTO test_tmp_drop VALUES (1);
+        SELECT count(*) FROM test_tmp_drop;
+        COMMIT;
+        SELECT count(*) FROM duckdb_tables() WHERE table_name = 'test_tmp_drop';
+EOF
+    [ "${lines[0]}" = "1" ]
+    [ "${lines[1]}" = "0" ]
+}
+
+@test "ON COMMIT DELETE ROWS empties a temporary table at the end of the transaction" {
+    run -0 psql_exec_stdin -q <<-EOF
+        CREATE TEMP TABLE test_tmp_delete (id INTEGER) ON COMMIT DELETE ROWS;
+        BEGIN;
+        INSERT INTO test_tmp_delete VALUES (1), (2);
+        SELECT count(*) FROM test_tmp_delete;
+        COMMIT;
+        SELECT count(*) FROM test_tmp_delete;
+EOF
+    [ "${lines[0]}" = "2" ]
+    [ "${lines[1]}" = "0" ]
+}
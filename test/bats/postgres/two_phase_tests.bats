@@ -0,0 +1,56 @@
+#!/usr/bin/env bats
+bats_require_minimum_version 1.5.0
+
+load helper
+
+setup_file() {
+    psql_exec_stdin <<-EOF
+        CREATE TABLE IF NOT EXISTS test_two_phase (id INTEGER PRIMARY KEY);
+EOF
+}
+
+teardown_file() {
+    psql_exec_stdin <<-EOF
+        DROP TABLE IF EXISTS test_two_phase;
+EOF
+}
+
+@test "a prepared transaction is committed by another session" {
+    run -0 psql_exec_stdin -q <<-EOF
+        BEGIN;
+        INSERT INTO test_two_phase VALUES (1), (2);
+        PREPARE TRANSACTION 'test_tx';
+        SELECT count(*) FROM test_two_phase;
+        SELECT gid, owner FROM pg_prepared_xacts;
+EOF
+    [ "${lines[0]}" = "0" ]
+    [ "${lines[1]}" = "test_tx,postgres" ]
+
+    run -0 psql_exec "COMMIT PREPARED 'test_tx'"
+    run -0 psql_exec "SELECT count(*) FROM test_two_phase"
+    [ "${output}" = "2" ]
+    run -0 psql_exec "SELECT count(*) FROM pg_prepared_xacts"
+    [ "${output}" = "0" ]
+}
+
+@test "a prepared transaction is rolled back" {
+    run -0 psql_exec_stdin -q <<-EOF
+        BEGIN;
+        INSERT INTO test_two_phase VALUES (3);
+        PREPARE TRANSACTION 'test_tx';
+EOF
+    run -0 psql_exec "ROLLBACK PREPARED 'test_tx'"
+    run -0 psql_exec "SELECT count(*) FROM test_two_phase WHERE id = 3"
+    [ "${output}" = "0" ]
+
+    run psql_exec "COMMIT PREPARED 'test_tx'"
+    [ "$status" -ne 0 ]
+}
+
+@test "COMMIT PREPARED cannot run inside a transaction block" {
+    run psql_exec_stdin <<-EOF
+        BEGIN;
+        COMMIT PREPARED 'test_tx';
+EOF
+    [ "$status" -ne 0 ]
+}
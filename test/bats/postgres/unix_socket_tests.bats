@@ -0,0 +1,22 @@
+#!/usr/bin/env bats
+bats_require_minimum_version 1.5.0
+
+load helper
+
+# The server must be started with --pg-socket-dir=/tmp, since there is no socket by default.
+
+@test "connect over the Unix domain socket" {
+    [ -S "/tmp/.s.PGSQL.${PG_PORT}" ]
+    PG_HOST=/tmp run -0 psql_exec "SELECT 1"
+    [ "${output}" = "1" ]
+}
+
+@test "only the owner and the group of the server may connect over the Unix domain socket by default" {
+    run -0 stat -c '%a' "/tmp/.s.PGSQL.${PG_PORT}"
+    [ "${output}" = "770" ]
+}
+
+@test "pg_stat_activity shows no client address over the Unix domain socket" {
+    PG_HOST=/tmp run -0 psql_exec "SELECT client_addr IS NULL, client_port FROM pg_stat_activity WHERE query LIKE '%client_port FROM pg_stat_activity%'"
+    [ "${output}" = "t,-1" ]
+}